@@ -0,0 +1,61 @@
+#![feature(test)]
+
+extern crate test;
+
+use flexi_logger::Logger;
+use log::*;
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use test::Bencher;
+
+// Counts the heap allocations, so that the allocation rate of the write path becomes visible.
+// Log lines are formatted into reused buffers, so after the warm-up
+// the write path should not cause allocations.
+struct CountingAllocator;
+static ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+        System.alloc(layout)
+    }
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAllocator = CountingAllocator;
+
+const NO_OF_RECORDS: usize = 100;
+
+#[bench]
+fn b10_allocations_per_record(b: &mut Bencher) {
+    let _handle = Logger::with_str("info/message")
+        .log_to_file()
+        .directory("log_files/bench_allocations")
+        .buffer_and_flush()
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    // warm up the reused buffers
+    write_records();
+
+    let before = ALLOCATIONS.load(Ordering::Relaxed);
+    let mut iterations = 0;
+    b.iter(|| {
+        write_records();
+        iterations += 1;
+    });
+    let allocations = ALLOCATIONS.load(Ordering::Relaxed) - before;
+    println!(
+        "\nallocations per record: {:.2}",
+        allocations as f64 / (iterations * NO_OF_RECORDS) as f64
+    );
+}
+
+fn write_records() {
+    for i in 0..NO_OF_RECORDS {
+        error!("This is error message number {} with some {}", i, "payload");
+    }
+}
@@ -0,0 +1,186 @@
+// Criterion benchmarks for evaluating performance-motivated changes in the formatters and writers.
+//
+// Run with `cargo bench --bench bench_criterion`.
+// The duplication benchmark writes to stderr, so you might want to redirect stderr to /dev/null.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use flexi_logger::writers::{FileLogWriter, LogWriter};
+use flexi_logger::{
+    default_format, detailed_format, opt_format, with_thread, Cleanup, DeferredNow, Duplicate,
+    FormatFunction, LogTarget, Logger, Naming, WriteMode,
+};
+use log::{Level, Log, Record};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const DIRECTORY: &str = "log_files/bench_criterion";
+
+// Calls f with a typical log record.
+fn with_record<F: FnMut(&Record)>(mut f: F) {
+    f(&Record::builder()
+        .args(format_args!(
+            "This is a typical log message with some {} and a number: {}",
+            "payload", 4711
+        ))
+        .level(Level::Info)
+        .target("bench_criterion")
+        .module_path(Some("bench_criterion"))
+        .file(Some("benches/bench_criterion.rs"))
+        .line(Some(42))
+        .build());
+}
+
+fn file_log_writer(discriminant: &str, buffered: bool) -> FileLogWriter {
+    FileLogWriter::builder()
+        .directory(DIRECTORY)
+        .discriminant(discriminant)
+        .suppress_timestamp()
+        .use_buffering(buffered)
+        .try_build()
+        .unwrap()
+}
+
+fn formatting(c: &mut Criterion) {
+    let mut group = c.benchmark_group("formatting");
+    let formats: [(&str, FormatFunction); 4] = [
+        ("default_format", default_format),
+        ("opt_format", opt_format),
+        ("detailed_format", detailed_format),
+        ("with_thread", with_thread),
+    ];
+    for (name, format) in &formats {
+        let mut buffer = Vec::<u8>::with_capacity(200);
+        group.bench_function(*name, |b| {
+            b.iter(|| {
+                with_record(|record| {
+                    buffer.clear();
+                    format(&mut buffer, &mut DeferredNow::new(), record).unwrap();
+                })
+            })
+        });
+    }
+    group.finish();
+}
+
+fn file_writes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("file_writes");
+    group.throughput(Throughput::Elements(1));
+    for buffered in &[false, true] {
+        let writer = file_log_writer(&format!("buffered_{}", buffered), *buffered);
+        group.bench_with_input(
+            BenchmarkId::new("buffered", buffered),
+            &writer,
+            |b, writer| {
+                b.iter(|| {
+                    with_record(|record| writer.write(&mut DeferredNow::new(), record).unwrap())
+                })
+            },
+        );
+        writer.shutdown();
+    }
+    group.finish();
+}
+
+fn rotating_writes(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rotating_writes");
+    group.throughput(Throughput::Elements(1));
+    // small files, so that rotation and cleanup happen frequently
+    let writer = FileLogWriter::builder()
+        .directory(DIRECTORY)
+        .discriminant("rotating")
+        .rotate(
+            flexi_logger::Criterion::Size(10_000),
+            Naming::Numbers,
+            Cleanup::KeepLogFiles(5),
+        )
+        .cleanup_in_background_thread(false)
+        .try_build()
+        .unwrap();
+    group.bench_function("size_10_000", |b| {
+        b.iter(|| with_record(|record| writer.write(&mut DeferredNow::new(), record).unwrap()))
+    });
+    writer.shutdown();
+    group.finish();
+}
+
+fn duplication(c: &mut Criterion) {
+    let mut group = c.benchmark_group("duplication");
+    group.throughput(Throughput::Elements(1));
+    for (name, duplicate, write_mode) in vec![
+        ("none", Duplicate::None, WriteMode::Direct),
+        ("direct", Duplicate::All, WriteMode::Direct),
+        ("buffered", Duplicate::All, WriteMode::Buffer(8 * 1024)),
+    ] {
+        let (logger, handle) = Logger::with_str("info")
+            .log_target(LogTarget::DevNull)
+            .duplicate_to_stderr(duplicate)
+            .write_mode_for_stderr(write_mode)
+            .build()
+            .unwrap();
+        group.bench_function(name, |b| {
+            b.iter(|| with_record(|record| logger.log(record)))
+        });
+        handle.shutdown();
+    }
+    group.finish();
+}
+
+// Several threads log concurrently through the primary writer and contend for its mutex,
+// without and with sharded staging buffers.
+fn contention(c: &mut Criterion) {
+    const RECORDS_PER_THREAD: u64 = 1_000;
+    let mut group = c.benchmark_group("contention");
+    for (no_of_threads, no_of_shards) in &[(1_u64, 0), (4, 0), (16, 0), (4, 4), (16, 16)] {
+        let mut logger = Logger::with_str("info")
+            .log_to_file()
+            .directory(DIRECTORY)
+            .discriminant(format!("contention_{}_{}", no_of_threads, no_of_shards))
+            .suppress_timestamp()
+            .use_buffering(true);
+        if *no_of_shards > 0 {
+            logger = logger.sharded(*no_of_shards);
+        }
+        let (logger, handle) = logger.build().unwrap();
+        let logger: Arc<dyn Log> = Arc::from(logger);
+        group.throughput(Throughput::Elements(no_of_threads * RECORDS_PER_THREAD));
+        group.bench_with_input(
+            BenchmarkId::new(format!("shards_{}", no_of_shards), no_of_threads),
+            no_of_threads,
+            |b, no_of_threads| {
+                b.iter_custom(|iterations| {
+                    let mut elapsed = Duration::default();
+                    for _ in 0..iterations {
+                        let start = Instant::now();
+                        let threads: Vec<_> = (0..*no_of_threads)
+                            .map(|_| {
+                                let logger = Arc::clone(&logger);
+                                std::thread::spawn(move || {
+                                    for _ in 0..RECORDS_PER_THREAD {
+                                        with_record(|record| logger.log(record));
+                                    }
+                                })
+                            })
+                            .collect();
+                        for thread in threads {
+                            thread.join().unwrap();
+                        }
+                        elapsed += start.elapsed();
+                    }
+                    elapsed
+                })
+            },
+        );
+        handle.shutdown();
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    formatting,
+    file_writes,
+    rotating_writes,
+    duplication,
+    contention
+);
+criterion_main!(benches);
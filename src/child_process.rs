@@ -0,0 +1,162 @@
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Child, Command, ExitStatus, Stdio};
+use std::thread::JoinHandle;
+
+/// Forwards the output of child processes line by line to the logger.
+///
+/// Each line that the child writes to stdout or stderr becomes a log record with the
+/// configured level and target, so that the output of subprocesses ends up in the same
+/// (rotated) log as the own records. The target is also used as module path,
+/// and thus appears in the log lines of the provided format functions.
+///
+/// ```rust,no_run
+/// use flexi_logger::ChildProcessCapture;
+/// use log::Level;
+/// use std::process::Command;
+///
+/// let captured = ChildProcessCapture::new("backup")
+///     .stderr_level(Level::Error)
+///     .prefix("> ")
+///     .spawn(Command::new("rsync").args(&["-a", "src/", "dst/"]))
+///     .unwrap();
+/// let exit_status = captured.wait().unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct ChildProcessCapture {
+    target: String,
+    prefix: String,
+    stdout_level: log::Level,
+    stderr_level: log::Level,
+}
+impl ChildProcessCapture {
+    /// Creates a capture that logs with the given target,
+    /// the lines from stdout with level `Info`, and those from stderr with level `Warn`.
+    #[must_use]
+    pub fn new<S: Into<String>>(target: S) -> Self {
+        Self {
+            target: target.into(),
+            prefix: String::new(),
+            stdout_level: log::Level::Info,
+            stderr_level: log::Level::Warn,
+        }
+    }
+
+    /// Sets the level for the lines from stdout.
+    #[must_use]
+    pub fn stdout_level(mut self, level: log::Level) -> Self {
+        self.stdout_level = level;
+        self
+    }
+
+    /// Sets the level for the lines from stderr.
+    #[must_use]
+    pub fn stderr_level(mut self, level: log::Level) -> Self {
+        self.stderr_level = level;
+        self
+    }
+
+    /// Sets a prefix that is put in front of each line.
+    #[must_use]
+    pub fn prefix<S: Into<String>>(mut self, prefix: S) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    /// Spawns the command with piped stdout and stderr and captures its output.
+    ///
+    /// # Errors
+    ///
+    /// `std::io::Error` if the command cannot be spawned,
+    /// or if the threads that read the output cannot be started.
+    pub fn spawn(&self, command: &mut Command) -> std::io::Result<CapturedChild> {
+        let child = command
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        self.adopt(child)
+    }
+
+    /// Captures the output of an already running child process.
+    ///
+    /// Only the streams that were configured with [`Stdio::piped`] are captured.
+    ///
+    /// # Errors
+    ///
+    /// `std::io::Error` if the threads that read the output cannot be started.
+    pub fn adopt(&self, mut child: Child) -> std::io::Result<CapturedChild> {
+        let mut readers = Vec::new();
+        if let Some(stdout) = child.stdout.take() {
+            readers.push(self.start_reader("stdout", stdout, self.stdout_level)?);
+        }
+        if let Some(stderr) = child.stderr.take() {
+            readers.push(self.start_reader("stderr", stderr, self.stderr_level)?);
+        }
+        Ok(CapturedChild { child, readers })
+    }
+
+    fn start_reader<R: Read + Send + 'static>(
+        &self,
+        stream: &str,
+        pipe: R,
+        level: log::Level,
+    ) -> std::io::Result<JoinHandle<()>> {
+        let target = self.target.clone();
+        let prefix = self.prefix.clone();
+        std::thread::Builder::new()
+            .name(format!("flexi_logger-child-{}", stream))
+            .spawn(move || {
+                for line in BufReader::new(pipe).split(b'\n') {
+                    let mut line = match line {
+                        Ok(line) => line,
+                        Err(e) => {
+                            crate::error_channel::report(format_args!(
+                                "reading the output of a child process failed with {}",
+                                e
+                            ));
+                            break;
+                        }
+                    };
+                    if line.last() == Some(&b'\r') {
+                        line.pop();
+                    }
+                    if level <= log::max_level() {
+                        log::logger().log(
+                            &log::Record::builder()
+                                .args(format_args!("{}{}", prefix, String::from_utf8_lossy(&line)))
+                                .level(level)
+                                .target(&target)
+                                .module_path(Some(target.as_str()))
+                                .build(),
+                        );
+                    }
+                }
+            })
+    }
+}
+
+/// A child process whose output is forwarded to the logger,
+/// see [`ChildProcessCapture`](crate::ChildProcessCapture).
+#[derive(Debug)]
+pub struct CapturedChild {
+    child: Child,
+    readers: Vec<JoinHandle<()>>,
+}
+impl CapturedChild {
+    /// Gives access to the child process, e.g. to its id or its stdin.
+    pub fn child(&mut self) -> &mut Child {
+        &mut self.child
+    }
+
+    /// Waits until the child process has exited and all its output is logged.
+    ///
+    /// # Errors
+    ///
+    /// `std::io::Error` if waiting for the child process fails.
+    pub fn wait(mut self) -> std::io::Result<ExitStatus> {
+        let exit_status = self.child.wait()?;
+        for reader in self.readers {
+            reader.join().ok();
+        }
+        Ok(exit_status)
+    }
+}
@@ -115,8 +115,9 @@
 //! [`Logger::print_message`](crate::Logger::print_message)
 //! prints an info to `stdout` to which file the log is written.
 //!
-//! `create_symlink(path)` creates (on unix-systems only) a symbolic link at the
-//! specified path that points to the log file.
+//! `create_symlink(path)` creates a symbolic link at the
+//! specified path that points to the log file
+//! (or, where this is not possible, a hard link or a pointer file).
 //!
 //! ```rust
 //! # use flexi_logger::Logger;
@@ -159,6 +160,7 @@
 //!  - [`detailed_format`](crate::detailed_format)
 //!  - [`opt_format`](crate::opt_format)
 //!  - [`with_thread`](crate::with_thread),
+//!  - [`elapsed_format`](crate::elapsed_format),
 //!
 //! or one of their coloring pendants
 //!
@@ -166,6 +168,7 @@
 //!  - [`colored_detailed_format`](crate::colored_detailed_format)
 //!  - [`colored_opt_format`](crate::colored_opt_format).
 //!  - [`colored_with_thread`](crate::colored_with_thread).
+//!  - [`colored_elapsed_format`](crate::colored_elapsed_format).
 //!
 //! ### Adaptive Coloring
 //!
@@ -242,8 +245,8 @@
 //!
 //! ## Rotate the log file
 //!
-//! With rotation, the logs are always written to a file
-//! with the infix `rCURRENT`, like e.g. `foo_rCURRENT.log`.
+//! With rotation, the logs are always written to the file without infix,
+//! like e.g. `foo.log`.
 //!
 //! [`Logger::rotate`](crate::Logger::rotate)
 //! takes three enum arguments to define its behavior:
@@ -254,12 +257,17 @@
 //!    - with `Criterion::Size` the rotation happens when the current log file exceeds
 //!      the specified limit
 //!    - with `Criterion::AgeOrSize` the rotation happens when either of the two limits is reached
+//!    - with `Criterion::Lines` the rotation happens when the current log file has reached
+//!      the specified number of lines
+//!    - with `Criterion::Any`, `Criterion::All`, and `Criterion::MinInterval` you can combine
+//!      the other criteria, e.g. to rotate daily or above a size, but not more often than
+//!      every ten minutes
 //!
 //! - [`Naming`](crate::Naming)<br>The current file is then renamed
 //!   - with `Naming::Timestamps` to something like `foo_r2020-11-16_08-56-52.log`
-//!   - with `Naming::Numbers` to something like `foo_r00000.log`
+//!   - with `Naming::Numbers` to something like `foo.0.log`
 //!
-//!   and a fresh `rCURRENT` file is created.
+//!   and a fresh `foo.log` is created.
 //!
 //! - [`Cleanup`](crate::Cleanup) defines if and how you
 //!   avoid accumulating log files indefinitely:
@@ -269,6 +277,8 @@
 //!     retained, and these are being compressed additionally
 //!   - with `Cleanup::KeepLogAndCompressedFiles` you specify the number of log files that should be
 //!     retained as is, and an additional number that are being compressed
+//!   - with `Cleanup::MoveToDirectory` you specify the number of log files that should be
+//!     retained; the older ones are moved into another directory, e.g. for an external archiver
 //!   - with `Cleanup::Never` no cleanup is done, all files are retained.
 //!
 //! ```rust
@@ -0,0 +1,439 @@
+use crate::writers::LogWriter;
+use crate::{
+    Cleanup, Criterion, Duplicate, FlexiLoggerError, LogSpecification, LogTarget, Logger, Naming,
+};
+use std::io::Read;
+use std::path::Path;
+
+/// Creates a `LogWriter` that is referenced by name in a [`LoggerConfig`](crate::LoggerConfig).
+///
+/// See [`Logger::from_config_and_factories`](crate::Logger::from_config_and_factories).
+///
+/// This type is only available with feature `config_file`.
+pub type WriterFactory = fn() -> Box<dyn LogWriter>;
+
+/// Describes a complete logger configuration, mirroring the methods of
+/// [`Logger`](crate::Logger).
+///
+/// `LoggerConfig` implements `serde::Deserialize`, so that applications can embed it
+/// into their own configuration, and use it with
+/// [`Logger::from_config`](crate::Logger::from_config).
+/// All members except `spec` are optional; if a member is not given,
+/// the default of the respective `Logger` method applies.
+///
+/// ## Example
+///
+/// A toml representation can look like this:
+///
+/// ```toml
+/// spec = "info, mod1::mod2=debug"
+/// target = "file"
+/// format = "detailed"
+/// duplicate_to_stderr = "warn"
+///
+/// [file]
+/// directory = "log_files"
+///
+/// [file.rotate]
+/// criterion = { size = 10_000_000 }
+/// naming = "numbers"
+/// cleanup = { keep_log_files = 7 }
+/// ```
+///
+/// This type is only available with feature `config_file`.
+#[derive(Clone, Debug, serde_derive::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LoggerConfig {
+    /// The log specification, see [`LogSpecification`](crate::LogSpecification).
+    pub spec: String,
+    /// The log target, one of `stderr` (default), `stdout`, `file`, `file_per_thread`,
+    /// `writer`, `file_and_writer`, or `devnull`.
+    pub target: Option<String>,
+    /// The name of the writer factory that is used for the targets `writer`
+    /// and `file_and_writer`.
+    pub writer: Option<String>,
+    /// The name of the format for all outputs, one of `default`, `detailed`, `opt`,
+    /// `with_thread`, or `elapsed`.
+    ///
+    /// The output to stderr and stdout is colored if feature `colors` is active
+    /// and the output goes to a terminal.
+    pub format: Option<String>,
+    /// The name of the format for the log files; overrides `format`.
+    pub format_for_files: Option<String>,
+    /// The name of the format for stderr; overrides `format`.
+    pub format_for_stderr: Option<String>,
+    /// The name of the format for stdout; overrides `format`.
+    pub format_for_stdout: Option<String>,
+    /// The name of the format for the writer; overrides `format`.
+    pub format_for_writer: Option<String>,
+    /// See [`Logger::duplicate_to_stderr`](crate::Logger::duplicate_to_stderr).
+    pub duplicate_to_stderr: Option<Duplicate>,
+    /// See [`Logger::duplicate_to_stdout`](crate::Logger::duplicate_to_stdout).
+    pub duplicate_to_stdout: Option<Duplicate>,
+    /// See [`Logger::set_palette`](crate::Logger::set_palette);
+    /// is ignored without feature `colors`.
+    pub palette: Option<String>,
+    /// See [`Logger::colorize_level_only`](crate::Logger::colorize_level_only);
+    /// is ignored without feature `colors`.
+    pub colorize_level_only: Option<bool>,
+    /// See [`Logger::use_buffering`](crate::Logger::use_buffering).
+    pub use_buffering: Option<bool>,
+    /// With `buffer_capacity` or `flush_interval_ms`,
+    /// [`Logger::buffer_and_flush_with`](crate::Logger::buffer_and_flush_with) is used.
+    pub buffer_capacity: Option<usize>,
+    /// The flush interval in milliseconds, see `buffer_capacity`.
+    pub flush_interval_ms: Option<u64>,
+    /// The configuration of the log files.
+    pub file: Option<FileConfig>,
+    /// Additional writers, see [`Logger::add_writer`](crate::Logger::add_writer);
+    /// each writer is created with the writer factory of the same name.
+    pub writers: Option<Vec<String>>,
+}
+
+/// Describes the configuration of the log files, as part of a
+/// [`LoggerConfig`](crate::LoggerConfig).
+///
+/// This type is only available with feature `config_file`.
+#[derive(Clone, Debug, Default, serde_derive::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FileConfig {
+    /// See [`Logger::directory`](crate::Logger::directory).
+    pub directory: Option<String>,
+    /// See [`Logger::create_directory`](crate::Logger::create_directory).
+    pub create_directory: Option<bool>,
+    /// See [`Logger::directory_mode`](crate::Logger::directory_mode);
+    /// is ignored on other platforms than unix.
+    pub directory_mode: Option<u32>,
+    /// See [`Logger::basename`](crate::Logger::basename).
+    pub basename: Option<String>,
+    /// See [`Logger::discriminant`](crate::Logger::discriminant).
+    pub discriminant: Option<String>,
+    /// See [`Logger::suffix`](crate::Logger::suffix).
+    pub suffix: Option<String>,
+    /// See [`Logger::o_timestamp`](crate::Logger::o_timestamp).
+    pub timestamp: Option<bool>,
+    /// See [`Logger::o_append`](crate::Logger::o_append).
+    pub append: Option<bool>,
+    /// See [`Logger::create_symlink`](crate::Logger::create_symlink).
+    pub create_symlink: Option<String>,
+    /// See [`Logger::o_print_message`](crate::Logger::o_print_message).
+    pub print_message: Option<bool>,
+    /// See [`Logger::rotate`](crate::Logger::rotate).
+    pub rotate: Option<RotationConfig>,
+    /// See [`Logger::use_rotation_lock`](crate::Logger::use_rotation_lock).
+    pub use_rotation_lock: Option<bool>,
+    /// See [`Logger::sharded`](crate::Logger::sharded).
+    pub shards: Option<usize>,
+}
+
+/// Describes the file rotation, as part of a [`FileConfig`](crate::FileConfig).
+///
+/// See [`Logger::rotate`](crate::Logger::rotate).
+///
+/// This type is only available with feature `config_file`.
+#[derive(Clone, Debug, serde_derive::Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RotationConfig {
+    /// When to rotate.
+    pub criterion: Criterion,
+    /// How to name the rotated files.
+    pub naming: Naming,
+    /// What to do with older files.
+    pub cleanup: Cleanup,
+}
+
+// Reads the configuration file and creates a fully configured `Logger` from it.
+pub(crate) fn logger_from_config_file(
+    path: &Path,
+    factories: &[(&str, WriterFactory)],
+) -> Result<Logger, FlexiLoggerError> {
+    let mut s = String::new();
+    std::fs::File::open(path)
+        .and_then(|mut file| file.read_to_string(&mut s))
+        .map_err(FlexiLoggerError::ConfigFileIo)?;
+
+    let config: LoggerConfig = match path
+        .extension()
+        .unwrap_or_else(|| std::ffi::OsStr::new(""))
+        .to_str()
+        .unwrap_or("")
+    {
+        "toml" => toml::from_str(&s).map_err(config_err),
+        "json" => serde_json::from_str(&s).map_err(config_err),
+        "yaml" | "yml" => serde_yaml::from_str(&s).map_err(config_err),
+        _ => Err(FlexiLoggerError::ConfigFile(
+            "only configuration files with extension toml, json, yaml, or yml are supported"
+                .to_string(),
+        )),
+    }?;
+    logger_from_config(config, factories)
+}
+
+// Creates a fully configured `Logger` from the configuration.
+pub(crate) fn logger_from_config(
+    config: LoggerConfig,
+    factories: &[(&str, WriterFactory)],
+) -> Result<Logger, FlexiLoggerError> {
+    let spec = LogSpecification::parse(&config.spec).map_err(|e| match e {
+        FlexiLoggerError::Parse(details, _) | FlexiLoggerError::LevelFilter(details) => {
+            FlexiLoggerError::SpecParse { details }
+        }
+        e => e,
+    })?;
+    let mut logger = Logger::with(spec);
+
+    let writer = |name: &Option<String>| -> Result<Box<dyn LogWriter>, FlexiLoggerError> {
+        let name = name
+            .as_ref()
+            .ok_or_else(|| FlexiLoggerError::ConfigFile("target requires a writer".to_string()))?;
+        create_writer(name, factories)
+    };
+    logger = logger.log_target(match config.target.as_deref().unwrap_or("stderr") {
+        "stderr" => LogTarget::StdErr,
+        "stdout" => LogTarget::StdOut,
+        "file" => LogTarget::File,
+        "file_per_thread" => LogTarget::FilePerThread,
+        "writer" => LogTarget::Writer(writer(&config.writer)?),
+        "file_and_writer" => LogTarget::FileAndWriter(writer(&config.writer)?),
+        "devnull" => LogTarget::DevNull,
+        s => return Err(invalid_value("target", s)),
+    });
+
+    if let Some(ref name) = config.format {
+        logger = logger
+            .named_format(name)
+            .ok_or_else(|| invalid_value("format", name))?;
+    }
+    if let Some(ref name) = config.format_for_files {
+        logger = logger
+            .named_format_for_files(name)
+            .ok_or_else(|| invalid_value("format_for_files", name))?;
+    }
+    if let Some(ref name) = config.format_for_stderr {
+        logger = logger
+            .named_format_for_stderr(name)
+            .ok_or_else(|| invalid_value("format_for_stderr", name))?;
+    }
+    if let Some(ref name) = config.format_for_stdout {
+        logger = logger
+            .named_format_for_stdout(name)
+            .ok_or_else(|| invalid_value("format_for_stdout", name))?;
+    }
+    if let Some(ref name) = config.format_for_writer {
+        logger = logger
+            .named_format_for_writer(name)
+            .ok_or_else(|| invalid_value("format_for_writer", name))?;
+    }
+
+    if let Some(duplicate) = config.duplicate_to_stderr {
+        logger = logger.duplicate_to_stderr(duplicate);
+    }
+    if let Some(duplicate) = config.duplicate_to_stdout {
+        logger = logger.duplicate_to_stdout(duplicate);
+    }
+    #[cfg(feature = "colors")]
+    {
+        if let Some(palette) = config.palette {
+            logger = logger.set_palette(palette);
+        }
+        if let Some(level_only) = config.colorize_level_only {
+            logger = logger.colorize_level_only(level_only);
+        }
+    }
+
+    if let Some(use_buffering) = config.use_buffering {
+        logger = logger.use_buffering(use_buffering);
+    }
+    if config.buffer_capacity.is_some() || config.flush_interval_ms.is_some() {
+        logger = logger.buffer_and_flush_with(
+            config
+                .buffer_capacity
+                .unwrap_or(crate::DEFAULT_BUFFER_CAPACITY),
+            config.flush_interval_ms.map_or(
+                crate::DEFAULT_FLUSH_WAIT_TIME,
+                std::time::Duration::from_millis,
+            ),
+        );
+    }
+
+    if let Some(file_config) = config.file {
+        logger = file_config.apply(logger);
+    }
+
+    for name in config.writers.unwrap_or_default() {
+        let writer = create_writer(&name, factories)?;
+        logger = logger.add_writer(name, writer);
+    }
+    Ok(logger)
+}
+
+impl FileConfig {
+    fn apply(self, mut logger: Logger) -> Logger {
+        logger = logger
+            .o_directory(self.directory)
+            .o_basename(self.basename)
+            .o_discriminant(self.discriminant)
+            .o_create_symlink(self.create_symlink);
+        if let Some(create_directory) = self.create_directory {
+            logger = logger.create_directory(create_directory);
+        }
+        #[cfg(unix)]
+        {
+            if let Some(mode) = self.directory_mode {
+                logger = logger.directory_mode(mode);
+            }
+        }
+        if let Some(suffix) = self.suffix {
+            logger = logger.suffix(suffix);
+        }
+        if let Some(rotation) = self.rotate {
+            logger = logger.rotate(rotation.criterion, rotation.naming, rotation.cleanup);
+        }
+        if let Some(use_rotation_lock) = self.use_rotation_lock {
+            logger = logger.use_rotation_lock(use_rotation_lock);
+        }
+        if let Some(no_of_shards) = self.shards {
+            logger = logger.sharded(no_of_shards);
+        }
+        // must come after rotate(), which suppresses the timestamp
+        if let Some(timestamp) = self.timestamp {
+            logger = logger.o_timestamp(timestamp);
+        }
+        if let Some(append) = self.append {
+            logger = logger.o_append(append);
+        }
+        if let Some(print_message) = self.print_message {
+            logger = logger.o_print_message(print_message);
+        }
+        logger
+    }
+}
+
+fn create_writer(
+    name: &str,
+    factories: &[(&str, WriterFactory)],
+) -> Result<Box<dyn LogWriter>, FlexiLoggerError> {
+    factories
+        .iter()
+        .find(|(factory_name, _)| *factory_name == name)
+        .map(|(_, factory)| factory())
+        .ok_or_else(|| {
+            FlexiLoggerError::ConfigFile(format!("no writer factory registered for {}", name))
+        })
+}
+
+fn invalid_value(key: &str, value: &str) -> FlexiLoggerError {
+    FlexiLoggerError::ConfigFile(format!("invalid value \"{}\" for {}", value, key))
+}
+
+fn config_err(e: impl std::fmt::Display) -> FlexiLoggerError {
+    FlexiLoggerError::ConfigFile(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{config_err, logger_from_config, LoggerConfig};
+    use crate::writers::LogWriter;
+    use crate::{Cleanup, Criterion, DeferredNow, FlexiLoggerError};
+    use log::Record;
+
+    struct DummyWriter;
+    impl LogWriter for DummyWriter {
+        fn write(&self, _now: &mut DeferredNow, _record: &Record) -> std::io::Result<()> {
+            Ok(())
+        }
+        fn flush(&self) -> std::io::Result<()> {
+            Ok(())
+        }
+        fn max_log_level(&self) -> log::LevelFilter {
+            log::LevelFilter::Trace
+        }
+    }
+    fn dummy() -> Box<dyn LogWriter> {
+        Box::new(DummyWriter)
+    }
+
+    fn build(toml: &str) -> Result<crate::Logger, FlexiLoggerError> {
+        logger_from_config(
+            toml::from_str(toml).map_err(config_err)?,
+            &[("dummy", dummy)],
+        )
+    }
+
+    #[test]
+    fn config_file_valid() {
+        build(
+            r#"
+            spec = "info, mod1=debug"
+            target = "file_and_writer"
+            writer = "dummy"
+            format = "detailed"
+            format_for_stderr = "opt"
+            duplicate_to_stderr = "warn"
+            duplicate_to_stdout = "warn..info"
+            flush_interval_ms = 500
+            writers = ["dummy"]
+
+            [file]
+            directory = "log_files/config_file"
+            basename = "config"
+            timestamp = false
+
+            [file.rotate]
+            criterion = { age_or_size = ["day", 1000] }
+            naming = "timestamps"
+            cleanup = { keep_log_files = 3 }
+            "#,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn config_file_invalid() {
+        for toml in &[
+            "spec = 'info'\ntarget = 'nowhere'",
+            "spec = 'info'\ntarget = 'writer'",
+            "spec = 'info'\ntarget = 'writer'\nwriter = 'unknown'",
+            "spec = 'info'\nformat = 'fancy'",
+            "spec = 'info'\nduplicate_to_stdout = 'loud'",
+            "spec = 'info'\nduplicate_to_stdout = 'warn..loud'",
+            "spec = 'info'\n[file.rotate]\nnaming = 'numbers'\ncleanup = 'never'",
+            "spec = 'info'\n[file.rotate]\ncriterion = { age = 'week' }\n\
+             naming = 'numbers'\ncleanup = 'never'",
+        ] {
+            match build(toml) {
+                Err(FlexiLoggerError::ConfigFile(_)) => {}
+                _ => panic!("invalid configuration was not detected: {}", toml),
+            }
+        }
+    }
+
+    #[test]
+    fn config_file_invalid_spec() {
+        match build("spec = 'info, mod1=loud'") {
+            Err(FlexiLoggerError::SpecParse { details }) => {
+                assert!(details.contains("loud"), "{}", details)
+            }
+            _ => panic!("invalid log specification was not detected"),
+        }
+    }
+
+    #[test]
+    fn config_deserialize() {
+        let config: LoggerConfig = serde_json::from_str(
+            r#"{
+                "spec": "debug",
+                "duplicate_to_stdout": "all",
+                "file": {"rotate": {
+                    "criterion": {"size": 1000},
+                    "naming": "numbers",
+                    "cleanup": "never"
+                }}
+            }"#,
+        )
+        .unwrap();
+        let rotation = config.file.unwrap().rotate.unwrap();
+        assert!(matches!(rotation.criterion, Criterion::Size(1000)));
+        assert!(matches!(rotation.cleanup, Cleanup::Never));
+    }
+}
@@ -1,11 +1,22 @@
-use chrono::{DateTime, Local};
+use crate::timestamp::Timestamp;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
 
 /// Deferred timestamp creation.
 ///
 /// Is used to ensure that a log record that is sent to multiple outputs
 /// (in maybe different formats) always uses the same timestamp.
 #[derive(Debug)]
-pub struct DeferredNow(Option<DateTime<Local>>);
+pub struct DeferredNow {
+    timestamp: Option<Timestamp>,
+    instant: Option<Instant>,
+    delta: Option<Duration>,
+    sequence_number: Option<u64>,
+    // the counter of the writer that is currently used; None for the primary writer
+    o_sequence_counter: Option<Arc<AtomicU64>>,
+}
 impl Default for DeferredNow {
     fn default() -> Self {
         Self::new()
@@ -16,16 +27,161 @@ impl<'a> DeferredNow {
     /// Constructs a new instance, but does not generate the timestamp.
     #[must_use]
     pub fn new() -> Self {
-        Self(None)
+        Self {
+            timestamp: None,
+            instant: None,
+            delta: None,
+            sequence_number: None,
+            o_sequence_counter: None,
+        }
     }
 
     /// Retrieve the timestamp.
     ///
     /// Requires mutability because the first caller will generate the timestamp.
-    pub fn now(&'a mut self) -> &'a DateTime<Local> {
-        if self.0.is_none() {
-            self.0 = Some(Local::now());
+    ///
+    /// Is only available with crate feature `chrono` (default).
+    #[cfg(feature = "chrono")]
+    pub fn now(&'a mut self) -> &'a Timestamp {
+        self.timestamp()
+    }
+
+    /// Retrieve the timestamp as `time::OffsetDateTime`.
+    ///
+    /// Requires mutability because the first caller will generate the timestamp.
+    ///
+    /// Is only available with crate feature `time`.
+    #[cfg(feature = "time")]
+    pub fn now_time(&mut self) -> time::OffsetDateTime {
+        crate::timestamp::to_time(self.timestamp())
+    }
+
+    pub(crate) fn timestamp(&'a mut self) -> &'a Timestamp {
+        if self.timestamp.is_none() {
+            self.timestamp = Some(crate::timestamp::now());
+        }
+        self.timestamp.as_ref().unwrap()
+    }
+
+    /// Retrieve the time that has passed since the logger was started
+    /// (or, without logger, since this method or [`DeferredNow::delta`] was first called).
+    ///
+    /// Is measured with a monotonic clock, and is thus not affected by changes
+    /// of the system time.
+    pub fn elapsed(&mut self) -> Duration {
+        self.instant().duration_since(clock().start)
+    }
+
+    /// Retrieve the time that has passed since the previous log record,
+    /// like the relative timestamps of `dmesg`.
+    ///
+    /// The previous record is the one, of any thread, for which `delta` was called before.
+    pub fn delta(&mut self) -> Duration {
+        if let Some(delta) = self.delta {
+            return delta;
+        }
+        let instant = self.instant();
+        let mut previous = clock()
+            .previous
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        // with concurrent records, the previous one can have a later instant
+        let delta = if instant > *previous {
+            instant.duration_since(*previous)
+        } else {
+            Duration::from_secs(0)
+        };
+        *previous = std::cmp::max(instant, *previous);
+        self.delta = Some(delta);
+        delta
+    }
+
+    /// Retrieve the sequence number of the record.
+    ///
+    /// The first caller takes the next value of the counter of the writer that writes
+    /// the record; the counters start with 1, and the primary writer and each
+    /// [additional writer](crate::Logger::add_writer) have their own counter,
+    /// which lives as long as the process.
+    /// So the records of each output are numbered without gaps, and gaps in the sequence
+    /// reveal records that were dropped or lost.
+    pub fn sequence_number(&mut self) -> u64 {
+        if let Some(sequence_number) = self.sequence_number {
+            return sequence_number;
         }
-        self.0.as_ref().unwrap()
+        let counter: &AtomicU64 = match self.o_sequence_counter {
+            Some(ref counter) => counter,
+            None => &PRIMARY_SEQUENCE_COUNTER,
+        };
+        let sequence_number = counter.fetch_add(1, Ordering::Relaxed) + 1;
+        self.sequence_number = Some(sequence_number);
+        sequence_number
     }
+
+    // Makes `sequence_number()` use the counter of the given additional writer,
+    // or, with None, that of the primary writer.
+    pub(crate) fn use_sequence_counter_of(&mut self, o_writer_name: Option<&str>) {
+        self.sequence_number = None;
+        self.o_sequence_counter = o_writer_name.map(sequence_counter);
+    }
+
+    fn instant(&mut self) -> Instant {
+        if let Some(instant) = self.instant {
+            return instant;
+        }
+        let instant = Instant::now();
+        self.instant = Some(instant);
+        instant
+    }
+}
+
+// The last sequence number that was handed out for the primary writer.
+static PRIMARY_SEQUENCE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+lazy_static::lazy_static! {
+    // The last sequence numbers that were handed out for the additional writers, by name.
+    static ref SEQUENCE_COUNTERS: RwLock<HashMap<String, Arc<AtomicU64>>> =
+        RwLock::new(HashMap::new());
+}
+
+fn sequence_counter(writer_name: &str) -> Arc<AtomicU64> {
+    if let Some(counter) = SEQUENCE_COUNTERS
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .get(writer_name)
+    {
+        return Arc::clone(counter);
+    }
+    Arc::clone(
+        SEQUENCE_COUNTERS
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .entry(writer_name.to_string())
+            .or_default(),
+    )
+}
+
+// The reference points for the relative timestamps.
+struct Clock {
+    start: Instant,
+    previous: Mutex<Instant>,
+}
+
+lazy_static::lazy_static! {
+    static ref CLOCK: Clock = {
+        let now = Instant::now();
+        Clock {
+            start: now,
+            previous: Mutex::new(now),
+        }
+    };
+}
+
+// Returns the clock, and starts it if necessary.
+fn clock() -> &'static Clock {
+    &CLOCK
+}
+
+// Starts the clock for the relative timestamps, if it is not yet running.
+pub(crate) fn start_clock() {
+    clock();
 }
@@ -0,0 +1,146 @@
+use crate::writers::RotationEvent;
+use std::cell::Cell;
+use std::sync::mpsc::{channel, sync_channel, Sender, SyncSender, TrySendError};
+use std::sync::RwLock;
+use std::time::{Duration, Instant};
+
+// The number of events that can wait for being written; further events are dropped.
+const CAPACITY: usize = 1024;
+
+lazy_static::lazy_static! {
+    // The self-diagnostics of the logger; None as long as they are switched off.
+    static ref DIAGNOSTICS: RwLock<Option<Diagnostics>> = RwLock::new(None);
+}
+
+thread_local! {
+    // Is set in the thread that writes the diagnostic records, so that problems
+    // with writing them are not reported as diagnostic records again,
+    // and while a dropped event is counted, so that the drop is not reported either.
+    static SUPPRESSED: Cell<bool> = Cell::new(false);
+}
+
+struct Diagnostics {
+    level: log::Level,
+    sender: SyncSender<Message>,
+}
+
+enum Message {
+    Event(String),
+    Drain(Sender<()>),
+}
+
+// Starts the thread that writes the events as log records with the given level and target.
+//
+// The events are handed over to this thread, because they are often detected
+// while a writer is locked, and writing them directly could then deadlock.
+// The thread of a previous start ends when its channel is dropped.
+pub(crate) fn start(level: log::Level, target: String) -> Result<(), std::io::Error> {
+    let (sender, receiver) = sync_channel::<Message>(CAPACITY);
+    std::thread::Builder::new()
+        .name("flexi_logger-diagnostics".to_string())
+        .spawn(move || {
+            SUPPRESSED.with(|suppressed| suppressed.set(true));
+            for message in receiver {
+                match message {
+                    Message::Event(event) => log::logger().log(
+                        &log::Record::builder()
+                            .args(format_args!("{}", event))
+                            .level(level)
+                            .target(&target)
+                            .module_path_static(Some("flexi_logger"))
+                            .build(),
+                    ),
+                    Message::Drain(ack) => {
+                        ack.send(()).ok();
+                    }
+                }
+            }
+        })?;
+    *DIAGNOSTICS
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(Diagnostics { level, sender });
+    Ok(())
+}
+
+// Calls `f` if the self-diagnostics are switched on and their records would be written.
+fn with_active<R>(f: impl FnOnce(&Diagnostics) -> R) -> Option<R> {
+    if SUPPRESSED.try_with(Cell::get).unwrap_or(true) {
+        return None;
+    }
+    let diagnostics = DIAGNOSTICS.read().ok()?;
+    diagnostics
+        .as_ref()
+        .filter(|diagnostics| diagnostics.level <= log::max_level())
+        .map(f)
+}
+
+// Reports a lifecycle event of the logger, if the self-diagnostics are switched on;
+// if too many events are waiting, the event is dropped and counted.
+pub(crate) fn emit(event: std::fmt::Arguments) {
+    if let Some(Err(TrySendError::Full(_))) = with_active(|diagnostics| {
+        diagnostics
+            .sender
+            .try_send(Message::Event(event.to_string()))
+    }) {
+        SUPPRESSED.with(|suppressed| suppressed.set(true));
+        crate::stats::count_dropped_records(1);
+        SUPPRESSED.with(|suppressed| suppressed.set(false));
+    }
+}
+
+pub(crate) fn is_active() -> bool {
+    with_active(|_| ()).is_some()
+}
+
+// Waits until the events that were emitted so far are written, but not longer than a second.
+pub(crate) fn drain() {
+    if let Some(sender) = with_active(|diagnostics| diagnostics.sender.clone()) {
+        let deadline = Instant::now() + Duration::from_secs(1);
+        let (ack_sender, ack_receiver) = channel();
+        let mut message = Message::Drain(ack_sender);
+        loop {
+            match sender.try_send(message) {
+                Ok(()) => break,
+                Err(TrySendError::Full(returned)) if Instant::now() < deadline => {
+                    message = returned;
+                    std::thread::sleep(Duration::from_millis(10));
+                }
+                Err(_) => return,
+            }
+        }
+        ack_receiver
+            .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            .ok();
+    }
+}
+
+pub(crate) fn rotation(event: &RotationEvent) {
+    match event {
+        RotationEvent::Rotated {
+            rotated_file,
+            current_file,
+        } => emit(format_args!(
+            "event=rotated rotated_file={:?} current_file={:?}",
+            rotated_file, current_file
+        )),
+        RotationEvent::Compressed {
+            log_file,
+            compressed_file,
+        } => emit(format_args!(
+            "event=compressed file={:?} compressed_file={:?}",
+            log_file, compressed_file
+        )),
+        RotationEvent::Encrypted {
+            file,
+            encrypted_file,
+        } => emit(format_args!(
+            "event=encrypted file={:?} encrypted_file={:?}",
+            file, encrypted_file
+        )),
+        RotationEvent::Moved { file, moved_file } => emit(format_args!(
+            "event=moved file={:?} moved_file={:?}",
+            file, moved_file
+        )),
+        RotationEvent::Removed { file } => emit(format_args!("event=removed file={:?}", file)),
+    }
+}
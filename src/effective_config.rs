@@ -0,0 +1,80 @@
+/// Describes what a [`Logger`](crate::Logger) would do if it was started,
+/// as returned by [`Logger::build_config`](crate::Logger::build_config).
+///
+/// The `Display` implementation renders a multi-line overview, which applications can print
+/// at startup; with optional crate feature `serde`
+/// (which is also implied by `specfile` and `config_file`), the description can be serialized
+/// with `serde`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct EffectiveConfig {
+    /// The log specification.
+    pub spec: String,
+    /// The primary log target: one of `stderr`, `stdout`, `file`, `file_per_thread`,
+    /// `writer`, `file_and_writer`, and `devnull`.
+    pub target: String,
+    /// The log files, if the log target writes files.
+    pub file: Option<EffectiveFileConfig>,
+    /// The format for the log files: its name if it was chosen by name,
+    /// e.g. `detailed` in the configuration file, `default` if none was chosen,
+    /// or `custom` if a format function was given directly.
+    pub format_for_file: String,
+    /// The format for stderr; adaptive formats are shown like `adaptive(opt)`.
+    pub format_for_stderr: String,
+    /// The format for stdout; adaptive formats are shown like `adaptive(opt)`.
+    pub format_for_stdout: String,
+    /// The format for the writer of the log target `writer` or `file_and_writer`.
+    pub format_for_writer: String,
+    /// The level up to which log lines are duplicated to stderr, e.g. `Warn`, or `None`.
+    pub duplicate_to_stderr: String,
+    /// The level up to which log lines are duplicated to stdout, e.g. `Warn`, or `None`.
+    pub duplicate_to_stdout: String,
+    /// The names of the additional writers, sorted alphabetically.
+    pub writers: Vec<String>,
+}
+
+/// Describes the log files of an [`EffectiveConfig`](crate::EffectiveConfig).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct EffectiveFileConfig {
+    /// The path of the log file that is written to, where a timestamp in the file name
+    /// is shown as `<timestamp>`.
+    pub path: String,
+    /// The rotation as `criterion, naming, cleanup`, e.g. `Size(1000), Numbers, Never`,
+    /// if the log files are rotated.
+    pub rotation: Option<String>,
+    /// The path of the rotated log files, where the number or timestamp in the file name
+    /// is shown as `<number>` or `<timestamp>`, if the log files are rotated.
+    pub rotated_path: Option<String>,
+    /// If the log file is appended to, rather than truncated.
+    pub append: bool,
+    /// The link to the current log file, if one is created.
+    pub symlink: Option<String>,
+}
+
+impl std::fmt::Display for EffectiveConfig {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "log specification:   {}", self.spec)?;
+        writeln!(f, "log target:          {}", self.target)?;
+        if let Some(ref file) = self.file {
+            writeln!(f, "log file:            {}", file.path)?;
+            if let Some(ref rotation) = file.rotation {
+                writeln!(f, "rotation:            {}", rotation)?;
+            }
+            if let Some(ref rotated_path) = file.rotated_path {
+                writeln!(f, "rotated files:       {}", rotated_path)?;
+            }
+            writeln!(f, "append:              {}", file.append)?;
+            if let Some(ref symlink) = file.symlink {
+                writeln!(f, "link:                {}", symlink)?;
+            }
+            writeln!(f, "format for file:     {}", self.format_for_file)?;
+        }
+        writeln!(f, "format for stderr:   {}", self.format_for_stderr)?;
+        writeln!(f, "format for stdout:   {}", self.format_for_stdout)?;
+        writeln!(f, "format for writer:   {}", self.format_for_writer)?;
+        writeln!(f, "duplicate to stderr: {}", self.duplicate_to_stderr)?;
+        writeln!(f, "duplicate to stdout: {}", self.duplicate_to_stdout)?;
+        write!(f, "writers:             {}", self.writers.join(", "))
+    }
+}
@@ -0,0 +1,99 @@
+use std::error::Error;
+use std::fmt;
+
+/// Displays an error together with the chain of its causes, one cause per line.
+///
+/// The first line shows the error itself, every following line one of its sources,
+/// indented by two spaces and prefixed with `caused by: `, e.g.
+///
+/// ```text
+/// loading the configuration failed
+///   caused by: cannot read "app.toml"
+///   caused by: No such file or directory (os error 2)
+/// ```
+///
+/// The whole chain is one log message, so the configured format function
+/// adds its prefix, e.g. the timestamp and the level, only to the first line.
+///
+/// Usually, you use it via [`log_error_chain!`](crate::log_error_chain).
+/// An `anyhow::Error` can be handed over with `&*error`.
+///
+/// ## Example
+///
+/// ```rust
+/// use flexi_logger::ErrorChain;
+///
+/// # let error = std::io::Error::new(std::io::ErrorKind::Other, "oh no");
+/// log::warn!("retrying after {}", ErrorChain::new(&error));
+/// ```
+pub struct ErrorChain<'a> {
+    error: &'a (dyn Error + 'a),
+}
+impl<'a> ErrorChain<'a> {
+    /// Wraps the given error.
+    #[must_use]
+    pub fn new(error: &'a (dyn Error + 'a)) -> Self {
+        Self { error }
+    }
+}
+impl<'a> fmt::Display for ErrorChain<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.error)?;
+        let mut o_source = self.error.source();
+        while let Some(source) = o_source {
+            write!(f, "\n  caused by: {}", source)?;
+            o_source = source.source();
+        }
+        Ok(())
+    }
+}
+
+/// Logs an error together with the chain of its causes as one error record,
+/// see [`ErrorChain`](crate::ErrorChain).
+///
+/// Like with the macros of the `log` crate, a target can be given.
+///
+/// ## Example
+///
+/// ```rust
+/// use flexi_logger::log_error_chain;
+///
+/// if let Err(e) = std::fs::File::open("does_not_exist.toml") {
+///     log_error_chain!(&e);
+/// }
+/// ```
+#[macro_export]
+macro_rules! log_error_chain {
+    (target: $target:expr, $error:expr) => {
+        $crate::__log::error!(target: $target, "{}", $crate::ErrorChain::new($error))
+    };
+    ($error:expr) => {
+        $crate::__log::error!("{}", $crate::ErrorChain::new($error))
+    };
+}
+
+// Installs a panic hook that writes the panic as an error record with target `panic`,
+// in the same layout as an `ErrorChain`, and then calls the previous panic hook.
+pub(crate) fn log_panics() {
+    let previous_hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let payload = info.payload();
+        let message = payload
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("Box<dyn Any>");
+        let thread = std::thread::current();
+        let location = info.location().map_or_else(String::new, |location| {
+            format!("\n  at: {}:{}", location.file(), location.line())
+        });
+        log::error!(
+            target: "panic",
+            "thread '{}' panicked: {}{}",
+            thread.name().unwrap_or("<unnamed>"),
+            message,
+            location
+        );
+        previous_hook(info);
+    }));
+}
@@ -0,0 +1,130 @@
+use crate::timestamp::Layout;
+use std::cell::{Cell, RefCell};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::RwLock;
+
+/// Describes where `flexi_logger` reports the problems it encounters itself,
+/// like failing writes, rotations, or cleanups, or an invalid log specification
+/// that is set at runtime.
+///
+/// Used in [`Logger::error_channel`](crate::Logger::error_channel).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ErrorChannel {
+    /// Write the reports to stderr (default).
+    Stderr,
+    /// Append the reports, with a timestamp, to the specified file.
+    ///
+    /// If the file cannot be written, the reports are lost.
+    File(PathBuf),
+    /// Discard the reports.
+    Ignore,
+    /// Panic with the report.
+    ///
+    /// If the problem occurs while a log call, flush, or shutdown uses the writers,
+    /// the panic is raised when the writers are released again, so that their locks are
+    /// not poisoned. While the thread is already panicking, e.g. in the panic hook that
+    /// [`Logger::log_panics`](crate::Logger::log_panics) installs, the report is written
+    /// to stderr instead.
+    ///
+    /// This is mainly useful in tests, to not overlook problems.
+    Panic,
+}
+
+lazy_static::lazy_static! {
+    // The error channel of the logger.
+    static ref ERROR_CHANNEL: RwLock<ErrorChannel> = RwLock::new(ErrorChannel::Stderr);
+}
+
+thread_local! {
+    // the number of DeferredPanics that exist on the current thread
+    static DEFERRING: Cell<usize> = Cell::new(0);
+    // the first problem that was reported with ErrorChannel::Panic while panics were deferred
+    static DEFERRED_PROBLEM: RefCell<Option<String>> = RefCell::new(None);
+}
+
+// While an instance exists on the current thread, `ErrorChannel::Panic` does not panic
+// in `report()`; the panic is raised when the outermost instance is dropped instead,
+// i.e., after the locks that were taken in the meantime are released.
+pub(crate) struct DeferredPanics(());
+impl DeferredPanics {
+    pub(crate) fn new() -> Self {
+        DEFERRING.with(|deferring| deferring.set(deferring.get() + 1));
+        Self(())
+    }
+}
+impl Drop for DeferredPanics {
+    fn drop(&mut self) {
+        let deferring = DEFERRING.with(|deferring| {
+            deferring.set(deferring.get() - 1);
+            deferring.get()
+        });
+        if deferring == 0 {
+            if let Some(problem) = DEFERRED_PROBLEM.with(|deferred| deferred.borrow_mut().take()) {
+                if !std::thread::panicking() {
+                    panic!("[flexi_logger] {}", problem);
+                }
+            }
+        }
+    }
+}
+
+// Makes `report()` use the given error channel.
+pub(crate) fn set(error_channel: ErrorChannel) {
+    *ERROR_CHANNEL
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner) = error_channel;
+}
+
+// Reports a problem of flexi_logger itself to the active error channel.
+pub(crate) fn report(problem: std::fmt::Arguments) {
+    if crate::diagnostics::is_active() {
+        crate::diagnostics::emit(format_args!(
+            "event=problem message={:?}",
+            problem.to_string()
+        ));
+    }
+    // the lock is released before the report is written, or the panic is raised or deferred
+    let error_channel = ERROR_CHANNEL
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .clone();
+    match error_channel {
+        ErrorChannel::Stderr => write_to_stderr(problem),
+        ErrorChannel::File(path) => {
+            if let Ok(mut file) = std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+            {
+                writeln!(
+                    file,
+                    "[{}] [flexi_logger] {}",
+                    crate::timestamp::format(&crate::timestamp::now(), Layout::LogLine),
+                    problem
+                )
+                .ok();
+            }
+        }
+        ErrorChannel::Ignore => {}
+        ErrorChannel::Panic => {
+            if std::thread::panicking() {
+                // a panic while panicking would abort the process
+                write_to_stderr(problem);
+            } else if DEFERRING.with(Cell::get) > 0 {
+                DEFERRED_PROBLEM.with(|deferred| {
+                    deferred
+                        .borrow_mut()
+                        .get_or_insert_with(|| problem.to_string());
+                });
+            } else {
+                panic!("[flexi_logger] {}", problem);
+            }
+        }
+    }
+}
+
+// Unlike eprintln!, this does not panic if stderr is closed.
+fn write_to_stderr(problem: std::fmt::Arguments) {
+    writeln!(std::io::stderr(), "[flexi_logger] {}", problem).ok();
+}
@@ -9,12 +9,51 @@ pub enum FlexiLoggerError {
     #[error("Log file cannot be written because the specified path is not a directory")]
     OutputBadDirectory,
 
+    /// The folder for the log files does not exist, and creating it is switched off.
+    #[error("The folder for the log files does not exist: {0:?}")]
+    OutputMissingDirectory(std::path::PathBuf),
+
+    /// The folder for the log files cannot be created.
+    #[error("The folder for the log files cannot be created: {0:?}")]
+    OutputCreateDirectory(std::path::PathBuf, #[source] std::io::Error),
+
     /// Spawning the cleanup thread failed.
     ///
     /// This error can safely be avoided with `Logger::cleanup_in_background_thread(false)`.
     #[error("Spawning the cleanup thread failed.")]
     OutputCleanupThread(std::io::Error),
 
+    /// A file or directory cannot be accessed.
+    #[error("{path:?} cannot be accessed")]
+    Io {
+        /// The file or directory.
+        path: std::path::PathBuf,
+        /// The error that occurred when accessing it.
+        source: std::io::Error,
+    },
+
+    /// The rotation cannot work with the other settings of the log files.
+    #[error("The rotation conflicts with other settings: {0}")]
+    RotationConfigConflict(String),
+
+    /// A setting for the log files is given, but no log files are written.
+    #[error("A setting for log files is given, but no log files are written: {0}")]
+    FileConfigConflict(String),
+
+    /// The logger does not write to a log file.
+    #[error("The logger does not write to a log file")]
+    NoFileLog,
+
+    /// A writer reported an error in its health check,
+    /// see [`LogWriter::health_check`](crate::writers::LogWriter::health_check).
+    #[error("The writer \"{0}\" failed its health check")]
+    WriterHealthCheck(String, #[source] std::io::Error),
+
+    /// The requested functionality is not available on the target platform,
+    /// e.g. writing log files in a web build (`wasm32-unknown-unknown`).
+    #[error("Not supported on this platform: {0}")]
+    Unsupported(&'static str),
+
     /// Log cannot be written, e.g. because the configured output directory is not accessible.
     #[error(
         "Log cannot be written, e.g. because the configured output directory is not accessible"
@@ -31,6 +70,16 @@ pub enum FlexiLoggerError {
     #[cfg(feature = "specfile_without_notification")]
     SpecfileToml(#[from] toml::de::Error),
 
+    /// Parsing or writing the configured logspec json-file failed.
+    #[error("Parsing or writing the configured logspec json-file failed")]
+    #[cfg(feature = "specfile_without_notification")]
+    SpecfileJson(#[from] serde_json::Error),
+
+    /// Parsing or writing the configured logspec yaml-file failed.
+    #[error("Parsing or writing the configured logspec yaml-file failed")]
+    #[cfg(feature = "specfile_without_notification")]
+    SpecfileYaml(#[from] serde_yaml::Error),
+
     /// Specfile cannot be accessed or created.
     #[error("Specfile cannot be accessed or created")]
     #[cfg(feature = "specfile_without_notification")]
@@ -41,10 +90,47 @@ pub enum FlexiLoggerError {
     #[cfg(feature = "specfile_without_notification")]
     SpecfileExtension(&'static str),
 
+    /// The logger was not started with a specfile.
+    #[error("The logger was not started with a specfile")]
+    #[cfg(feature = "specfile_without_notification")]
+    NoSpecfile,
+
+    /// Specfile contains an invalid value for one of the writer settings.
+    #[error("Specfile contains an invalid value: {0}")]
+    SpecfileValue(String),
+
+    /// Logger configuration file cannot be read.
+    #[error("Logger configuration file cannot be read")]
+    #[cfg(feature = "config_file")]
+    ConfigFileIo(std::io::Error),
+
+    /// Logger configuration file is invalid.
+    #[error("Logger configuration file is invalid: {0}")]
+    #[cfg(feature = "config_file")]
+    ConfigFile(String),
+
+    /// An environment variable that overrides a builder setting has an invalid value.
+    #[error("Invalid value in environment variable: {0}")]
+    EnvOverride(String),
+
     /// Invalid level filter.
-    #[error("Invalid level filter")]
+    #[error("Invalid level filter: {0}")]
     LevelFilter(String),
 
+    /// The offset given to `Logger::assume_utc_offset` is not less than a day.
+    #[error("Invalid UTC offset (in seconds): {0}")]
+    UtcOffset(i32),
+
+    /// The log specification in a configuration is malformed.
+    ///
+    /// Unlike with [`FlexiLoggerError::Parse`], there is no usable part of the
+    /// log specification to fall back to.
+    #[error("Failed to parse log specification: {details}")]
+    SpecParse {
+        /// Describes what is wrong with the log specification.
+        details: String,
+    },
+
     /// Failed to parse log specification.
     #[error("Failed to parse log specification: {0}")]
     Parse(String, LogSpecification),
@@ -60,4 +146,34 @@ pub enum FlexiLoggerError {
     /// Palette parsing failed
     #[error("Palette parsing failed")]
     Palette(#[from] std::num::ParseIntError),
+
+    /// The Kafka producer could not be created.
+    #[error("The Kafka producer could not be created")]
+    #[cfg(feature = "kafka_writer")]
+    Kafka(#[from] kafka::Error),
+
+    /// The `SQLite` database could not be opened or prepared.
+    #[error("The SQLite database could not be opened or prepared")]
+    #[cfg(feature = "sqlite_writer")]
+    Sqlite(#[from] rusqlite::Error),
+
+    /// The Sentry DSN is invalid.
+    #[error("The Sentry DSN is invalid: {0}")]
+    #[cfg(feature = "sentry_writer")]
+    SentryDsn(String),
+
+    /// The configuration of the S3 archive is invalid.
+    #[error("The configuration of the S3 archive is invalid: {0}")]
+    #[cfg(feature = "archive-s3")]
+    S3Archive(String),
+
+    /// The encryption of the rotated files is misconfigured.
+    #[error("The encryption of the rotated files is misconfigured: {0}")]
+    #[cfg(feature = "encrypt")]
+    Encryption(String),
+
+    /// The hash chain of the log files is broken.
+    #[error("The hash chain of the log files is broken: {0}")]
+    #[cfg(feature = "integrity")]
+    HashChain(String),
 }
@@ -1,11 +1,181 @@
+use crate::formats::{with_format_settings, FormatSettings};
+use crate::middleware::{RecordDraft, RecordMiddleware};
 use crate::primary_writer::PrimaryWriter;
 use crate::writers::LogWriter;
-use crate::LogSpecification;
+use crate::{FlexiLoggerError, LogSpecification};
 
-#[cfg(feature = "textfilter")]
-use regex::Regex;
-use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex, RwLock};
+
+thread_local! {
+    // optional overlay of the log specification for the current thread,
+    // see `LoggerHandle::thread_spec()`
+    static THREAD_SPEC: RefCell<Option<Arc<LogSpecification>>> = RefCell::new(None);
+}
+
+// Sets the overlay spec for the current thread and returns the previous one.
+pub(crate) fn replace_thread_spec(
+    o_spec: Option<Arc<LogSpecification>>,
+) -> Option<Arc<LogSpecification>> {
+    THREAD_SPEC.with(|thread_spec| thread_spec.replace(o_spec))
+}
+
+fn thread_spec() -> Option<Arc<LogSpecification>> {
+    THREAD_SPEC.with(|thread_spec| thread_spec.borrow().clone())
+}
+
+// The writers and their settings, which can be exchanged as a whole
+// with `LoggerHandle::replace()`.
+//
+// A backend is not changed while it is in use; the methods of `LoggerHandle` that change
+// single settings install a modified clone instead, which shares the writers
+// (see `modify_backend()`), so that the log calls need no lock while the writers run.
+#[derive(Clone)]
+pub(crate) struct Backend {
+    pub(crate) primary_writer: Arc<PrimaryWriter>,
+    // can be changed with `LoggerHandle::add_writer()` and `LoggerHandle::remove_writer()`
+    pub(crate) other_writers: HashMap<String, Arc<dyn LogWriter>>,
+    // names of the other writers whose records are also written to the default target
+    pub(crate) fallthrough_writers: HashSet<String>,
+    // messages beyond this length are truncated
+    pub(crate) o_max_message_length: Option<usize>,
+    // applied in this order to each record before it is written
+    pub(crate) middlewares: Arc<Vec<Box<dyn RecordMiddleware>>>,
+    // overrides that lower the maximum levels of the other writers
+    pub(crate) writer_levels: HashMap<String, log::LevelFilter>,
+    // the settings of the provided format functions, which are current while the writers are used
+    pub(crate) format_settings: FormatSettings,
+    // the writers are shut down only once, even if several handles call shutdown();
+    // shared with the modified clones
+    pub(crate) shut_down: Arc<AtomicBool>,
+}
+impl Backend {
+    // The maximum level of the named writer, possibly lowered by an override.
+    pub(crate) fn writer_max_level(&self, name: &str, writer: &dyn LogWriter) -> log::LevelFilter {
+        let max_level = writer.max_log_level();
+        match self.writer_levels.get(name) {
+            Some(level) => std::cmp::min(max_level, *level),
+            None => max_level,
+        }
+    }
+
+    // Runs `f`, which uses the writers, with the format settings of this backend;
+    // with `ErrorChannel::Panic`, the panic for a problem is raised after `f` has released
+    // the locks of the writers.
+    pub(crate) fn with_settings<R>(&self, f: impl FnOnce() -> R) -> R {
+        let _deferred_panics = crate::error_channel::DeferredPanics::new();
+        with_format_settings(self.format_settings, f)
+    }
+
+    pub(crate) fn flush(&self) {
+        self.with_settings(|| {
+            self.primary_writer.flush().ok();
+            for writer in self.other_writers.values() {
+                writer.flush().ok();
+            }
+        });
+    }
+
+    // Flushes all writers and then shuts them down, see `LogWriter::shutdown()`.
+    pub(crate) fn shutdown(&self) {
+        if self.shut_down.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        self.flush();
+        self.with_settings(|| {
+            if let PrimaryWriter::Multi(writer) = &*self.primary_writer {
+                writer.shutdown();
+            }
+            for writer in self.other_writers.values() {
+                writer.shutdown();
+            }
+        });
+    }
+
+    // Like flush(), but reports errors.
+    fn flush_and_report(&self) {
+        self.with_settings(|| {
+            self.primary_writer.flush().unwrap_or_else(|e| {
+                crate::error_channel::report(format_args!(
+                    "flushing primary writer failed with {}",
+                    e
+                ));
+            });
+            for writer in self.other_writers.values() {
+                writer.flush().unwrap_or_else(|e| {
+                    crate::error_channel::report(format_args!(
+                        "flushing custom writer failed with {}",
+                        e
+                    ));
+                });
+            }
+        });
+    }
+
+    // Checks all writers; the primary writer is reported as "_Default".
+    pub(crate) fn health_check(&self) -> Result<(), FlexiLoggerError> {
+        self.with_settings(|| {
+            self.primary_writer
+                .health_check()
+                .map_err(|e| FlexiLoggerError::WriterHealthCheck("_Default".to_string(), e))?;
+            for (name, writer) in &self.other_writers {
+                writer
+                    .health_check()
+                    .map_err(|e| FlexiLoggerError::WriterHealthCheck(name.clone(), e))?;
+            }
+            Ok(())
+        })
+    }
+}
+
+// The backend that is currently in use; shared by the logger and its handles.
+pub(crate) type SharedBackend = Arc<RwLock<Arc<Backend>>>;
+
+pub(crate) fn current_backend(shared_backend: &SharedBackend) -> Arc<Backend> {
+    Arc::clone(&shared_backend.read().unwrap(/* catch and expose error? */))
+}
+
+// Installs a modified clone of the current backend; the lock is only held for the exchange,
+// not while log calls use the writers of the previous backend.
+pub(crate) fn modify_backend<R>(
+    shared_backend: &SharedBackend,
+    modify: impl FnOnce(&mut Backend) -> R,
+) -> R {
+    let mut current = shared_backend.write().unwrap(/* catch and expose error? */);
+    let mut backend = Backend::clone(&current);
+    let result = modify(&mut backend);
+    *current = Arc::new(backend);
+    result
+}
+
+// Starts a thread that flushes the writers periodically, as long as the current backend
+// is in use, or one of its modified clones.
+pub(crate) fn start_flusher(
+    shared_backend: &SharedBackend,
+    wait_time: std::time::Duration,
+) -> Result<(), std::io::Error> {
+    let shared_backend = Arc::clone(shared_backend);
+    let primary_writer = Arc::clone(&current_backend(&shared_backend).primary_writer);
+    std::thread::Builder::new()
+        .name("flexi_logger-flusher".to_string())
+        .stack_size(128)
+        .spawn(move || {
+            let (_sender, receiver): (Sender<()>, Receiver<()>) = channel();
+            loop {
+                receiver.recv_timeout(wait_time).ok();
+                let backend = current_backend(&shared_backend);
+                if !Arc::ptr_eq(&primary_writer, &backend.primary_writer) {
+                    // the backend was replaced
+                    return;
+                }
+                backend.flush();
+            }
+        })?;
+    Ok(())
+}
 
 // Implements log::Log to plug into the log crate.
 //
@@ -15,135 +185,364 @@ use std::sync::{Arc, RwLock};
 // and can additionally duplicate log lines to stderr.
 pub(crate) struct FlexiLogger {
     log_specification: Arc<RwLock<LogSpecification>>,
-    primary_writer: Arc<PrimaryWriter>,
-    other_writers: Arc<HashMap<String, Box<dyn LogWriter>>>,
+    backend: SharedBackend,
+    // unknown writer names that were already reported
+    reported_unknown_writers: Mutex<HashSet<String>>,
 }
 
 impl FlexiLogger {
-    pub fn new(
-        log_specification: Arc<RwLock<LogSpecification>>,
-        primary_writer: Arc<PrimaryWriter>,
-        other_writers: Arc<HashMap<String, Box<dyn LogWriter>>>,
-    ) -> Self {
+    pub fn new(log_specification: Arc<RwLock<LogSpecification>>, backend: SharedBackend) -> Self {
         Self {
             log_specification,
-            primary_writer,
-            other_writers,
+            backend,
+            reported_unknown_writers: Mutex::new(HashSet::new()),
         }
     }
 
-    fn primary_enabled(&self, level: log::Level, module: &str) -> bool {
-        self.log_specification.read().as_ref()
-                                .unwrap(/* catch and expose error? */)
-                                .enabled(level, module)
+    // Applies the middlewares and writes the record;
+    // the middlewares only see the records that pass the log specification and the writers.
+    fn log_with_middlewares(&self, backend: &Backend, record: &log::Record) {
+        if backend.middlewares.is_empty() {
+            self.log_with(backend, record);
+        } else if self.is_wanted(backend, record) {
+            let mut draft = RecordDraft::from_record(record);
+            if backend
+                .middlewares
+                .iter()
+                .all(|middleware| middleware.process(&mut draft))
+            {
+                self.log_with(
+                    backend,
+                    &log::Record::builder()
+                        .args(format_args!("{}", draft.message))
+                        .level(draft.level)
+                        .target(&draft.target)
+                        .module_path(draft.module_path.as_deref())
+                        .file(draft.file.as_deref())
+                        .line(draft.line)
+                        .build(),
+                );
+            }
+        }
+    }
+    // Truncates the message if necessary and writes the record.
+    fn log_with(&self, backend: &Backend, record: &log::Record) {
+        // the message is only formatted for records that are written
+        if !self.is_wanted(backend, record) {
+            return;
+        }
+        let written = match backend.o_max_message_length {
+            Some(max_length) => {
+                let mut message = TruncatedMessage::new(max_length);
+                // writing into a TruncatedMessage cannot fail
+                std::fmt::write(&mut message, *record.args()).ok();
+                if message.omitted == 0 {
+                    self.write(backend, record)
+                } else {
+                    self.write(
+                        backend,
+                        &log::Record::builder()
+                            .args(format_args!("{}", message))
+                            .metadata(record.metadata().clone())
+                            .module_path(record.module_path())
+                            .file(record.file())
+                            .line(record.line())
+                            .build(),
+                    )
+                }
+            }
+            None => self.write(backend, record),
+        };
+        if written {
+            crate::stats::count_record(record.level());
+        }
     }
-}
-
-impl log::Log for FlexiLogger {
-    //  If other writers are configured and the metadata target addresses them correctly,
-    //      - we should determine if the metadata-level is digested by any of the writers
-    //        (including the primary writer)
-    //  else we fall back to default behavior:
-    //      Return true if
-    //      - target is filled with module path and level is accepted by log specification
-    //      - target is filled with crap and ???
-    //
-    // Caveat:
-    // Rocket e.g. sets target explicitly to several fantasy names;
-    // these hopefully do not collide with any of the modules in the log specification;
-    // since they do not conform with the {} syntax expected by flexi_logger, they're treated as
-    // module names.
-    fn enabled(&self, metadata: &log::Metadata) -> bool {
-        let target = metadata.target();
-        let level = metadata.level();
 
-        if !self.other_writers.is_empty() && target.starts_with('{') {
-            // at least one other writer is configured _and_ addressed
-            let targets: Vec<&str> = target[1..(target.len() - 1)].split(',').collect();
-            for t in targets {
-                if t != "_Default" {
-                    match self.other_writers.get(t) {
-                        None => eprintln!("[flexi_logger] bad writer spec: {}", t),
+    // Returns whether at least one of the addressed writers accepts the record.
+    fn is_wanted(&self, backend: &Backend, record: &log::Record) -> bool {
+        let target = record.metadata().target();
+        let o_names = writer_names(target);
+        if let Some(names) = o_names.clone() {
+            let other_writers = &backend.other_writers;
+            let mut use_default = false;
+            for name in names {
+                if name == "_Default" {
+                    use_default = true;
+                } else {
+                    use_default |= backend.fallthrough_writers.contains(name);
+                    match other_writers.get(name) {
+                        None => self.report_unknown_writer(name),
                         Some(writer) => {
-                            if level < writer.max_log_level() {
+                            if record.level() <= backend.writer_max_level(name, &**writer) {
                                 return true;
                             }
                         }
                     }
                 }
             }
+            if !use_default {
+                return false;
+            }
         }
 
-        self.primary_enabled(level, target)
+        let effective_target = if o_names.is_some() {
+            record.module_path().unwrap_or_default()
+        } else {
+            target
+        };
+        if !self.primary_enabled(record.level(), effective_target) {
+            return false;
+        }
+        #[cfg(feature = "textfilter")]
+        {
+            if !self.text_enabled(effective_target, record) {
+                return false;
+            }
+        }
+        true
     }
 
-    fn log(&self, record: &log::Record) {
+    // Writes the record to the addressed writers;
+    // returns whether it was written to at least one of them.
+    fn write(&self, backend: &Backend, record: &log::Record) -> bool {
         let target = record.metadata().target();
         let mut now = crate::DeferredNow::new();
-        if target.starts_with('{') {
+        let o_names = writer_names(target);
+        let mut written = false;
+        if let Some(names) = o_names.clone() {
+            let other_writers = &backend.other_writers;
             let mut use_default = false;
-            let targets: Vec<&str> = target[1..(target.len() - 1)].split(',').collect();
-            for t in targets {
-                if t == "_Default" {
+            for name in names {
+                if name == "_Default" {
                     use_default = true;
                 } else {
-                    match self.other_writers.get(t) {
-                        None => eprintln!("[flexi_logger] found bad writer spec: {}", t),
+                    use_default |= backend.fallthrough_writers.contains(name);
+                    match other_writers.get(name) {
+                        None => self.report_unknown_writer(name),
+                        Some(writer)
+                            if record.level() > backend.writer_max_level(name, &**writer) => {}
                         Some(writer) => {
-                            writer.write(&mut now, record).unwrap_or_else(|e| {
-                                eprintln!(
-                                    "[flexi_logger] writing log line to custom writer \"{}\" \
-                                     failed with: \"{}\"",
-                                    t, e
-                                );
-                            });
+                            now.use_sequence_counter_of(Some(name));
+                            match writer.write(&mut now, record) {
+                                Ok(()) => written = true,
+                                Err(e) => {
+                                    crate::stats::count_write_error();
+                                    crate::error_channel::report(format_args!(
+                                        "writing log line to custom writer \"{}\" \
+                                         failed with: \"{}\"",
+                                        name, e
+                                    ));
+                                }
+                            }
                         }
                     }
                 }
             }
             if !use_default {
-                return;
+                return written;
             }
         }
 
-        let effective_target = if target.starts_with('{') {
+        let effective_target = if o_names.is_some() {
             record.module_path().unwrap_or_default()
         } else {
             target
         };
         if !self.primary_enabled(record.level(), effective_target) {
-            return;
+            return written;
         }
 
         #[cfg(feature = "textfilter")]
         {
-            // closure that we need below
-            let check_text_filter = |text_filter: Option<&Regex>| {
-                text_filter.map_or(true, |filter| filter.is_match(&*record.args().to_string()))
-            };
+            if !self.text_enabled(effective_target, record) {
+                return written;
+            }
+        }
 
-            if !check_text_filter(
-                self.log_specification.read().as_ref().unwrap(/* expose this? */).text_filter(),
-            ) {
-                return;
+        now.use_sequence_counter_of(None);
+        match backend.primary_writer.write(&mut now, record) {
+            Ok(()) => true,
+            Err(e) => {
+                crate::stats::count_write_error();
+                crate::error_channel::report(format_args!("writing log line failed with {}", e));
+                written
             }
         }
+    }
 
-        self.primary_writer
-            .write(&mut now, record)
-            .unwrap_or_else(|e| {
-                eprintln!("[flexi_logger] writing log line failed with {}", e);
-            });
+    fn primary_enabled(&self, level: log::Level, module: &str) -> bool {
+        if let Some(spec) = thread_spec() {
+            return spec.enabled(level, module);
+        }
+        self.log_specification.read().as_ref()
+                                .unwrap(/* catch and expose error? */)
+                                .enabled(level, module)
+    }
+
+    // Log calls that address an unknown writer are not written to it;
+    // this is reported once per name, to not flood stderr.
+    fn report_unknown_writer(&self, name: &str) {
+        if let Ok(mut reported) = self.reported_unknown_writers.lock() {
+            if reported.insert(name.to_string()) {
+                crate::error_channel::report(format_args!(
+                    "log call addresses unknown writer \"{}\"; \
+                     such log calls are not written to it",
+                    name
+                ));
+            }
+        }
+    }
+
+    #[cfg(feature = "textfilter")]
+    fn text_enabled(&self, module: &str, record: &log::Record) -> bool {
+        if let Some(spec) = thread_spec() {
+            return spec.text_enabled(module, record);
+        }
+        self.log_specification.read().as_ref()
+                                .unwrap(/* expose this? */)
+                                .text_enabled(module, record)
+    }
+}
+
+// Returns the names of the addressed writers if the target has the form `{Name1,Name2,...}`.
+//
+// The names are separated by commas, surrounding whitespace is ignored, as are empty names.
+// Targets that start with `{` but do not end with `}` are treated as normal targets.
+fn writer_names(target: &str) -> Option<impl Iterator<Item = &str> + Clone> {
+    if target.len() >= 2 && target.starts_with('{') && target.ends_with('}') {
+        Some(
+            target[1..(target.len() - 1)]
+                .split(',')
+                .map(str::trim)
+                .filter(|name| !name.is_empty()),
+        )
+    } else {
+        None
+    }
+}
+
+impl log::Log for FlexiLogger {
+    //  If other writers are configured and the metadata target addresses them correctly,
+    //      - we should determine if the metadata-level is digested by any of the writers
+    //        (including the primary writer)
+    //  else we fall back to default behavior:
+    //      Return true if
+    //      - target is filled with module path and level is accepted by log specification
+    //      - target is filled with crap and ???
+    //
+    // Caveat:
+    // Rocket e.g. sets target explicitly to several fantasy names;
+    // these hopefully do not collide with any of the modules in the log specification;
+    // since they do not conform with the {} syntax expected by flexi_logger, they're treated as
+    // module names.
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        let target = metadata.target();
+        let level = metadata.level();
+
+        let backend = current_backend(&self.backend);
+        let other_writers = &backend.other_writers;
+        if !other_writers.is_empty() {
+            if let Some(names) = writer_names(target) {
+                // at least one other writer is configured _and_ addressed
+                for name in names.filter(|name| *name != "_Default") {
+                    if let Some(writer) = other_writers.get(name) {
+                        if level <= backend.writer_max_level(name, &**writer) {
+                            return true;
+                        }
+                    }
+                }
+            }
+        }
+
+        self.primary_enabled(level, target)
+    }
+
+    fn log(&self, record: &log::Record) {
+        let backend = current_backend(&self.backend);
+        backend.with_settings(|| self.log_with_middlewares(&backend, record));
     }
 
     fn flush(&self) {
-        self.primary_writer.flush().unwrap_or_else(|e| {
-            eprintln!("[flexi_logger] flushing primary writer failed with {}", e);
-        });
-        for writer in self.other_writers.values() {
-            writer.flush().unwrap_or_else(|e| {
-                eprintln!("[flexi_logger] flushing custom writer failed with {}", e);
-            });
+        let backend = current_backend(&self.backend);
+        backend.flush_and_report();
+    }
+}
+
+// Keeps the beginning of a message, up to the maximum length, and counts the omitted bytes.
+struct TruncatedMessage {
+    kept: String,
+    max_length: usize,
+    omitted: usize,
+}
+impl TruncatedMessage {
+    fn new(max_length: usize) -> Self {
+        Self {
+            kept: String::new(),
+            max_length,
+            omitted: 0,
+        }
+    }
+}
+impl std::fmt::Write for TruncatedMessage {
+    fn write_str(&mut self, s: &str) -> std::fmt::Result {
+        if self.omitted > 0 {
+            self.omitted += s.len();
+            return Ok(());
+        }
+        let mut len = std::cmp::min(s.len(), self.max_length - self.kept.len());
+        while !s.is_char_boundary(len) {
+            len -= 1;
         }
+        self.kept.push_str(&s[..len]);
+        self.omitted = s.len() - len;
+        Ok(())
+    }
+}
+impl std::fmt::Display for TruncatedMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}...[{} bytes omitted]", self.kept, self.omitted)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{writer_names, TruncatedMessage};
+
+    #[test]
+    fn test_writer_names() {
+        let names = |target| writer_names(target).map(Iterator::collect::<Vec<_>>);
+        assert_eq!(names("{Alert}"), Some(vec!["Alert"]));
+        assert_eq!(
+            names("{ Alert, Security ,,_Default}"),
+            Some(vec!["Alert", "Security", "_Default"])
+        );
+        assert_eq!(names("{}"), Some(vec![]));
+        assert_eq!(names("{Alert"), None);
+        assert_eq!(names("my_app::module"), None);
+    }
+
+    #[test]
+    fn test_truncated_message() {
+        use std::fmt::Write;
+        let truncate = |max_length, parts: &[&str]| {
+            let mut message = TruncatedMessage::new(max_length);
+            for part in parts {
+                message.write_str(part).unwrap();
+            }
+            (message.omitted, message.to_string())
+        };
+        assert_eq!(
+            truncate(10, &["short"]),
+            (0, "short...[0 bytes omitted]".to_string())
+        );
+        assert_eq!(
+            truncate(10, &["0123456", "789abc", "def"]),
+            (6, "0123456789...[6 bytes omitted]".to_string())
+        );
+        // multi-byte characters are not split
+        assert_eq!(
+            truncate(4, &["aäöü"]),
+            (4, "aä...[4 bytes omitted]".to_string())
+        );
     }
 }
@@ -1,8 +1,12 @@
-use crate::DeferredNow;
+use crate::timestamp::Layout;
+use crate::{DeferredNow, TimestampFormat};
 use log::Record;
+use std::borrow::Cow;
+use std::sync::{Arc, PoisonError, RwLock};
 use std::thread;
+use std::time::Duration;
 #[cfg(feature = "colors")]
-use yansi::{Color, Paint, Style};
+use yansi::Paint;
 
 /// Function type for Format functions.
 ///
@@ -23,6 +27,12 @@ use yansi::{Color, Paint, Style};
 ///
 /// - `record`: the log line's content and metadata, as provided by the log crate's macros.
 ///
+/// ## Failures
+///
+/// If a format function returns an error or panics, the writers of `flexi_logger` discard
+/// what it wrote for the record, write the record in a minimal fallback format
+/// (like [`default_format`]) instead, and report the problem to the
+/// [`ErrorChannel`](crate::ErrorChannel).
 pub type FormatFunction = fn(
     write: &mut dyn std::io::Write,
     now: &mut DeferredNow,
@@ -37,16 +47,10 @@ pub type FormatFunction = fn(
 /// See `std::write`
 pub fn default_format(
     w: &mut dyn std::io::Write,
-    _now: &mut DeferredNow,
+    now: &mut DeferredNow,
     record: &Record,
 ) -> Result<(), std::io::Error> {
-    write!(
-        w,
-        "{} [{}] {}",
-        record.level(),
-        record.module_path().unwrap_or("<unnamed>"),
-        record.args()
-    )
+    write_fields(w, now, record, Fields::MODULE, false)
 }
 
 #[allow(clippy::doc_markdown)]
@@ -55,7 +59,7 @@ pub fn default_format(
 /// <code><span style="color:red">ERROR</span> &#91;my_prog::some_submodule&#93; <span
 /// style="color:red">File not found</span></code>
 ///
-/// See method [style](crate::style) if you want to influence coloring.
+/// See [`Palette`](crate::Palette) if you want to influence coloring.
 ///
 /// Only available with feature `colors`.
 ///
@@ -65,17 +69,10 @@ pub fn default_format(
 #[cfg(feature = "colors")]
 pub fn colored_default_format(
     w: &mut dyn std::io::Write,
-    _now: &mut DeferredNow,
+    now: &mut DeferredNow,
     record: &Record,
 ) -> Result<(), std::io::Error> {
-    let level = record.level();
-    write!(
-        w,
-        "{} [{}] {}",
-        style(level, level),
-        record.module_path().unwrap_or("<unnamed>"),
-        style(level, record.args())
-    )
+    write_fields(w, now, record, Fields::MODULE, true)
 }
 
 /// A logline-formatter that produces log lines with timestamp and file location, like
@@ -91,20 +88,12 @@ pub fn opt_format(
     now: &mut DeferredNow,
     record: &Record,
 ) -> Result<(), std::io::Error> {
-    write!(
-        w,
-        "[{}] {} [{}:{}] {}",
-        now.now().format("%Y-%m-%d %H:%M:%S%.6f %:z"),
-        record.level(),
-        record.file().unwrap_or("<unnamed>"),
-        record.line().unwrap_or(0),
-        &record.args()
-    )
+    write_fields(w, now, record, Fields::TIMESTAMP | Fields::FILE_LINE, false)
 }
 
 /// A colored version of the logline-formatter `opt_format`.
 ///
-/// See method [style](crate::style) if you want to influence coloring.
+/// See [`Palette`](crate::Palette) if you want to influence coloring.
 ///
 /// Only available with feature `colors`.
 ///
@@ -117,16 +106,7 @@ pub fn colored_opt_format(
     now: &mut DeferredNow,
     record: &Record,
 ) -> Result<(), std::io::Error> {
-    let level = record.level();
-    write!(
-        w,
-        "[{}] {} [{}:{}] {}",
-        style(level, now.now().format("%Y-%m-%d %H:%M:%S%.6f %:z")),
-        style(level, level),
-        record.file().unwrap_or("<unnamed>"),
-        record.line().unwrap_or(0),
-        style(level, &record.args())
-    )
+    write_fields(w, now, record, Fields::TIMESTAMP | Fields::FILE_LINE, true)
 }
 
 /// A logline-formatter that produces log lines like
@@ -143,21 +123,18 @@ pub fn detailed_format(
     now: &mut DeferredNow,
     record: &Record,
 ) -> Result<(), std::io::Error> {
-    write!(
+    write_fields(
         w,
-        "[{}] {} [{}] {}:{}: {}",
-        now.now().format("%Y-%m-%d %H:%M:%S%.6f %:z"),
-        record.level(),
-        record.module_path().unwrap_or("<unnamed>"),
-        record.file().unwrap_or("<unnamed>"),
-        record.line().unwrap_or(0),
-        &record.args()
+        now,
+        record,
+        Fields::TIMESTAMP | Fields::MODULE | Fields::FILE_LINE,
+        false,
     )
 }
 
 /// A colored version of the logline-formatter `detailed_format`.
 ///
-/// See method [style](crate::style) if you want to influence coloring.
+/// See [`Palette`](crate::Palette) if you want to influence coloring.
 ///
 /// Only available with feature `colors`.
 ///
@@ -170,16 +147,12 @@ pub fn colored_detailed_format(
     now: &mut DeferredNow,
     record: &Record,
 ) -> Result<(), std::io::Error> {
-    let level = record.level();
-    write!(
+    write_fields(
         w,
-        "[{}] {} [{}] {}:{}: {}",
-        style(level, now.now().format("%Y-%m-%d %H:%M:%S%.6f %:z")),
-        style(level, record.level()),
-        record.module_path().unwrap_or("<unnamed>"),
-        record.file().unwrap_or("<unnamed>"),
-        record.line().unwrap_or(0),
-        style(level, &record.args())
+        now,
+        record,
+        Fields::TIMESTAMP | Fields::MODULE | Fields::FILE_LINE,
+        true,
     )
 }
 
@@ -197,21 +170,18 @@ pub fn with_thread(
     now: &mut DeferredNow,
     record: &Record,
 ) -> Result<(), std::io::Error> {
-    write!(
+    write_fields(
         w,
-        "[{}] T[{:?}] {} [{}:{}] {}",
-        now.now().format("%Y-%m-%d %H:%M:%S%.6f %:z"),
-        thread::current().name().unwrap_or("<unnamed>"),
-        record.level(),
-        record.file().unwrap_or("<unnamed>"),
-        record.line().unwrap_or(0),
-        &record.args()
+        now,
+        record,
+        Fields::TIMESTAMP | Fields::THREAD | Fields::FILE_LINE,
+        false,
     )
 }
 
 /// A colored version of the logline-formatter `with_thread`.
 ///
-/// See method [style](crate::style) if you want to influence coloring.
+/// See [`Palette`](crate::Palette) if you want to influence coloring.
 ///
 /// Only available with feature `colors`.
 ///
@@ -224,37 +194,778 @@ pub fn colored_with_thread(
     now: &mut DeferredNow,
     record: &Record,
 ) -> Result<(), std::io::Error> {
-    let level = record.level();
+    write_fields(
+        w,
+        now,
+        record,
+        Fields::TIMESTAMP | Fields::THREAD | Fields::FILE_LINE,
+        true,
+    )
+}
+
+/// A logline-formatter that produces log lines like
+/// <br>
+/// ```[3.016481s +0.000112s] INFO [my_prog::some_submodule] Task successfully read from conf.json```
+/// <br>
+/// i.e. with the time since the start of the logger and the time since the previous record,
+/// see [`DeferredNow::elapsed`](crate::DeferredNow::elapsed) and
+/// [`DeferredNow::delta`](crate::DeferredNow::delta), which helps e.g. with profiling
+/// startup sequences.
+///
+/// # Errors
+///
+/// See `std::write`
+pub fn elapsed_format(
+    w: &mut dyn std::io::Write,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    write_fields(
+        w,
+        now,
+        record,
+        Fields::ELAPSED | Fields::DELTA | Fields::MODULE,
+        false,
+    )
+}
+
+/// A colored version of the logline-formatter `elapsed_format`.
+///
+/// See [`Palette`](crate::Palette) if you want to influence coloring.
+///
+/// Only available with feature `colors`.
+///
+/// # Errors
+///
+/// See `std::write`
+#[cfg(feature = "colors")]
+pub fn colored_elapsed_format(
+    w: &mut dyn std::io::Write,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    write_fields(
+        w,
+        now,
+        record,
+        Fields::ELAPSED | Fields::DELTA | Fields::MODULE,
+        true,
+    )
+}
+
+// Formats the record with the given format function into the buffer.
+//
+// If the format function fails or panics, what it wrote is discarded, the record is rendered
+// with `fallback_format()` instead, and the problem is reported, so that the record is not lost.
+// While the thread is panicking already, e.g. in the panic hook of `Logger::log_panics()`,
+// another panic would abort the process, so then only the fallback is used.
+pub(crate) fn format_with_fallback(
+    format: FormatFunction,
+    buffer: &mut Vec<u8>,
+    now: &mut DeferredNow,
+    record: &Record,
+) {
+    let start = buffer.len();
+    if !thread::panicking() {
+        let problem = match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            (format)(&mut *buffer, now, record)
+        })) {
+            Ok(Ok(())) => return,
+            Ok(Err(e)) => format!("failed with {}", e),
+            Err(payload) => format!(
+                "panicked with {}",
+                payload
+                    .downcast_ref::<&str>()
+                    .copied()
+                    .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+                    .unwrap_or("Box<dyn Any>")
+            ),
+        };
+        crate::error_channel::report(format_args!(
+            "the format function {}; the record is written in the fallback format",
+            problem
+        ));
+    }
+    buffer.truncate(start);
+    // the message itself can fail or panic as well, in its Display implementation
+    let rendered = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        fallback_format(&mut *buffer, record)
+    }));
+    if !matches!(rendered, Ok(Ok(()))) {
+        buffer.truncate(start);
+        let w: &mut dyn std::io::Write = buffer;
+        write!(
+            w,
+            "{} [{}] <the message cannot be rendered>",
+            record.level(),
+            record.module_path().unwrap_or("<unnamed>")
+        )
+        .ok();
+    }
+}
+
+// The minimal layout of records whose format function failed, like `default_format()`.
+fn fallback_format(w: &mut dyn std::io::Write, record: &Record) -> Result<(), std::io::Error> {
     write!(
         w,
-        "[{}] T[{:?}] {} [{}:{}] {}",
-        style(level, now.now().format("%Y-%m-%d %H:%M:%S%.6f %:z")),
-        style(level, thread::current().name().unwrap_or("<unnamed>")),
-        style(level, level),
-        record.file().unwrap_or("<unnamed>"),
-        record.line().unwrap_or(0),
-        style(level, &record.args())
+        "{} [{}] {}",
+        record.level(),
+        record.module_path().unwrap_or("<unnamed>"),
+        record.args()
     )
 }
 
+// The optional fields of the provided human-readable format functions.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct Fields(u8);
+impl Fields {
+    const TIMESTAMP: Self = Self(1);
+    pub(crate) const THREAD: Self = Self(2);
+    pub(crate) const MODULE: Self = Self(4);
+    pub(crate) const FILE_LINE: Self = Self(8);
+    pub(crate) const TARGET: Self = Self(16);
+    pub(crate) const ELAPSED: Self = Self(32);
+    pub(crate) const DELTA: Self = Self(64);
+    pub(crate) const SEQUENCE_NUMBER: Self = Self(128);
+
+    fn contains(self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+impl std::ops::BitOr for Fields {
+    type Output = Self;
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+// Overrides of the fields that the provided human-readable format functions show,
+// see e.g. `Logger::show_module()`; a field that is neither shown nor hidden here
+// is shown or not depending on the format function.
+#[derive(Clone, Copy, Default)]
+pub(crate) struct FieldOverrides {
+    shown: Fields,
+    hidden: Fields,
+}
+impl FieldOverrides {
+    pub(crate) fn set(&mut self, field: Fields, show: bool) {
+        if show {
+            self.shown.0 |= field.0;
+            self.hidden.0 &= !field.0;
+        } else {
+            self.hidden.0 |= field.0;
+            self.shown.0 &= !field.0;
+        }
+    }
+
+    fn apply(self, fields: Fields) -> Fields {
+        Fields((fields.0 | self.shown.0) & !self.hidden.0)
+    }
+}
+
+/// Defines how the provided human-readable format functions shorten long module paths,
+/// to keep the log lines readable in deep module hierarchies.
+///
+/// Used in [`Logger::shorten_module_path`](crate::Logger::shorten_module_path);
+/// [`json_format`](crate::json_format) always writes the complete module path.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShortModulePath {
+    /// Module paths that are longer than the given number of characters keep their
+    /// first segment and as many of their last segments as fit, and the segments
+    /// in between are replaced with `…`,
+    /// e.g. `my_crate::…::db::pool` for `my_crate::storage::sql::db::pool`.
+    ///
+    /// The first and the last segment are always shown, so a shortened path can still
+    /// exceed the width.
+    MaxWidth(usize),
+    /// Only the given number of last segments are shown,
+    /// e.g. `db::pool` for `my_crate::storage::db::pool` with `LastSegments(2)`.
+    LastSegments(usize),
+}
+impl ShortModulePath {
+    fn apply(self, path: &str) -> Cow<str> {
+        let segments: Vec<&str> = path.split("::").collect();
+        match self {
+            Self::MaxWidth(max_width) => {
+                if segments.len() <= 2 || path.chars().count() <= max_width {
+                    return Cow::Borrowed(path);
+                }
+                // the first segment, `::…::`, and the last segment
+                let mut width =
+                    segments[0].chars().count() + 5 + segments[segments.len() - 1].chars().count();
+                let mut start = segments.len() - 1;
+                // at least the second segment is omitted
+                while start > 2 {
+                    let extended = width + segments[start - 1].chars().count() + 2;
+                    if extended > max_width {
+                        break;
+                    }
+                    width = extended;
+                    start -= 1;
+                }
+                Cow::Owned(format!(
+                    "{}::…::{}",
+                    segments[0],
+                    segments[start..].join("::")
+                ))
+            }
+            Self::LastSegments(count) => {
+                let count = std::cmp::max(count, 1);
+                if segments.len() <= count {
+                    Cow::Borrowed(path)
+                } else {
+                    Cow::Owned(segments[segments.len() - count..].join("::"))
+                }
+            }
+        }
+    }
+}
+
+// The logger-wide settings of the provided format functions, like the field toggles or
+// the timestamp format. Each logger keeps its own settings and makes them current
+// while it writes, so that several loggers (or a replaced one) don't affect each other.
+#[derive(Clone, Copy)]
+pub(crate) struct FormatSettings {
+    pub(crate) field_overrides: FieldOverrides,
+    pub(crate) o_short_module_path: Option<ShortModulePath>,
+    // the widths to which the level and the module path are padded,
+    // see `Logger::align_columns()`; 0 means no padding
+    pub(crate) level_width: usize,
+    pub(crate) module_width: usize,
+    pub(crate) o_timestamp_format: Option<TimestampFormat>,
+    // in seconds east of UTC; with None, the local offset is used
+    pub(crate) o_utc_offset: Option<i32>,
+    #[cfg(feature = "atty")]
+    pub(crate) color_choice: ColorChoice,
+    #[cfg(feature = "colors")]
+    pub(crate) colorize_level_only: bool,
+}
+impl Default for FormatSettings {
+    fn default() -> Self {
+        Self {
+            field_overrides: FieldOverrides::default(),
+            o_short_module_path: None,
+            level_width: 0,
+            module_width: 0,
+            o_timestamp_format: None,
+            o_utc_offset: None,
+            #[cfg(feature = "atty")]
+            color_choice: ColorChoice::Auto,
+            #[cfg(feature = "colors")]
+            colorize_level_only: false,
+        }
+    }
+}
+
+thread_local! {
+    static FORMAT_SETTINGS: std::cell::Cell<Option<FormatSettings>> =
+        std::cell::Cell::new(None);
+}
+
+// Runs `f` with the given settings as the current ones.
+pub(crate) fn with_format_settings<R>(settings: FormatSettings, f: impl FnOnce() -> R) -> R {
+    let o_previous = FORMAT_SETTINGS.with(|current| current.replace(Some(settings)));
+    let result = f();
+    FORMAT_SETTINGS.with(|current| current.set(o_previous));
+    result
+}
+
+// The settings of the logger that is currently writing on this thread,
+// or the defaults, e.g. if a `FileLogWriter` is used without a logger.
+pub(crate) fn current_format_settings() -> FormatSettings {
+    FORMAT_SETTINGS
+        .try_with(std::cell::Cell::get)
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
+
+#[derive(Clone, Copy)]
+enum Part {
+    Timestamp,
+    Level,
+    Text,
+}
+
+// Writes the record with the given fields, which can be overridden by the active
+// `FieldOverrides`, in the common layout of the provided human-readable format functions.
+fn write_fields(
+    w: &mut dyn std::io::Write,
+    now: &mut DeferredNow,
+    record: &Record,
+    fields: Fields,
+    colored: bool,
+) -> Result<(), std::io::Error> {
+    let settings = current_format_settings();
+    let fields = settings.field_overrides.apply(fields);
+    let level = record.level();
+    if fields.contains(Fields::SEQUENCE_NUMBER) {
+        write_part(
+            w,
+            level,
+            &format_args!("#{}", now.sequence_number()),
+            Part::Timestamp,
+            colored,
+        )?;
+        w.write_all(b" ")?;
+    }
+    if fields.contains(Fields::TIMESTAMP) {
+        w.write_all(b"[")?;
+        write_part(
+            w,
+            level,
+            &crate::timestamp::format(
+                now.timestamp(),
+                crate::timestamp::chosen_layout_or(Layout::LogLine),
+            ),
+            Part::Timestamp,
+            colored,
+        )?;
+        w.write_all(b"] ")?;
+    }
+    if fields.contains(Fields::ELAPSED) || fields.contains(Fields::DELTA) {
+        let o_elapsed = if fields.contains(Fields::ELAPSED) {
+            Some(now.elapsed())
+        } else {
+            None
+        };
+        let o_delta = if fields.contains(Fields::DELTA) {
+            Some(now.delta())
+        } else {
+            None
+        };
+        w.write_all(b"[")?;
+        write_part(
+            w,
+            level,
+            &RelativeTimes(o_elapsed, o_delta),
+            Part::Timestamp,
+            colored,
+        )?;
+        w.write_all(b"] ")?;
+    }
+    if fields.contains(Fields::THREAD) {
+        w.write_all(b"T[")?;
+        write_part(
+            w,
+            level,
+            &format_args!("{:?}", thread::current().name().unwrap_or("<unnamed>")),
+            Part::Text,
+            colored,
+        )?;
+        w.write_all(b"] ")?;
+    }
+    if settings.level_width == 0 {
+        write_part(w, level, &level, Part::Level, colored)?;
+    } else {
+        write_part(
+            w,
+            level,
+            &format_args!("{:<1$}", level, settings.level_width),
+            Part::Level,
+            colored,
+        )?;
+    }
+    if fields.contains(Fields::MODULE) {
+        let module_path = record.module_path().unwrap_or("<unnamed>");
+        let module_path = match settings.o_short_module_path {
+            Some(short_module_path) => short_module_path.apply(module_path),
+            None => Cow::Borrowed(module_path),
+        };
+        write!(w, " [{}]", module_path)?;
+        let padding = settings
+            .module_width
+            .saturating_sub(module_path.chars().count());
+        if padding > 0 {
+            write!(w, "{:1$}", "", padding)?;
+        }
+    }
+    if fields.contains(Fields::FILE_LINE) {
+        let file = record.file().unwrap_or("<unnamed>");
+        let line = record.line().unwrap_or(0);
+        if fields.contains(Fields::MODULE) {
+            write!(w, " {}:{}:", file, line)?;
+        } else {
+            write!(w, " [{}:{}]", file, line)?;
+        }
+    }
+    if fields.contains(Fields::TARGET) {
+        write!(w, " <{}>", record.target())?;
+    }
+    w.write_all(b" ")?;
+    write_part(w, level, record.args(), Part::Text, colored)
+}
+
+// Elapsed time and delta, like `3.016481s +0.000112s`.
+struct RelativeTimes(Option<Duration>, Option<Duration>);
+impl std::fmt::Display for RelativeTimes {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if let Some(elapsed) = self.0 {
+            write!(f, "{}.{:06}s", elapsed.as_secs(), elapsed.subsec_micros())?;
+            if self.1.is_some() {
+                f.write_str(" ")?;
+            }
+        }
+        if let Some(delta) = self.1 {
+            write!(f, "+{}.{:06}s", delta.as_secs(), delta.subsec_micros())?;
+        }
+        Ok(())
+    }
+}
+
+#[allow(unused_variables)]
+fn write_part(
+    w: &mut dyn std::io::Write,
+    level: log::Level,
+    item: &dyn std::fmt::Display,
+    part: Part,
+    colored: bool,
+) -> Result<(), std::io::Error> {
+    #[cfg(feature = "colors")]
+    {
+        if colored {
+            return match part {
+                Part::Timestamp => write!(w, "{}", style_timestamp(level, item)),
+                Part::Level => write!(w, "{}", style_level(level, item)),
+                Part::Text => write!(w, "{}", style(level, item)),
+            };
+        }
+    }
+    write!(w, "{}", item)
+}
+
+/// A logline-formatter that produces log lines as JSON objects, like
+/// <br>
+/// ```{"timestamp":"2016-01-13T15:25:01.640870+01:00","level":"INFO","module_path":"my_prog::some_submodule","file":"src/foo/bar.rs","line":26,"text":"Task successfully read from conf.json"}```
+/// <br>
+/// which is convenient for log shippers and structured log collectors.
+///
+/// Missing values of `module_path`, `file`, or `line` are written as `null`.
+///
+/// With [`Logger::show_sequence_number`](crate::Logger::show_sequence_number),
+/// each object starts with the sequence number of the record, like `{"seq":42,"timestamp":...`.
+/// With [`Logger::timestamp_format`](crate::Logger::timestamp_format), the timestamp can be
+/// written in another format; the Unix epoch formats are written as numbers.
+///
+/// # Errors
+///
+/// See `std::write`
+pub fn json_format(
+    w: &mut dyn std::io::Write,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    if current_format_settings()
+        .field_overrides
+        .shown
+        .contains(Fields::SEQUENCE_NUMBER)
+    {
+        write!(w, "{{\"seq\":{},", now.sequence_number())?;
+    } else {
+        w.write_all(b"{")?;
+    }
+    let layout = crate::timestamp::chosen_layout_or(Layout::Rfc3339);
+    let quote = if layout.is_numeric() { "" } else { "\"" };
+    write!(
+        w,
+        "\"timestamp\":{}{}{},\"level\":\"{}\",\"module_path\":",
+        quote,
+        crate::timestamp::format(now.timestamp(), layout),
+        quote,
+        record.level(),
+    )?;
+    write_json_str_or_null(w, record.module_path())?;
+    w.write_all(b",\"file\":")?;
+    write_json_str_or_null(w, record.file())?;
+    match record.line() {
+        Some(line) => write!(w, ",\"line\":{},\"text\":\"", line)?,
+        None => w.write_all(b",\"line\":null,\"text\":\"")?,
+    }
+    write!(JsonEscaper(w), "{}", record.args())?;
+    w.write_all(b"\"}")
+}
+
+pub(crate) fn write_json_str_or_null(
+    w: &mut dyn std::io::Write,
+    o_s: Option<&str>,
+) -> std::io::Result<()> {
+    match o_s {
+        Some(s) => {
+            w.write_all(b"\"")?;
+            JsonEscaper(w).write_str(s)?;
+            w.write_all(b"\"")
+        }
+        None => w.write_all(b"null"),
+    }
+}
+
+// Writes the content of a JSON string to the output stream, escaping it where necessary,
+// without an intermediate String.
+struct JsonEscaper<'a>(&'a mut dyn std::io::Write);
+impl JsonEscaper<'_> {
+    fn write_str(&mut self, s: &str) -> std::io::Result<()> {
+        let bytes = s.as_bytes();
+        let mut start = 0;
+        for (i, &byte) in bytes.iter().enumerate() {
+            let escaped: &[u8] = match byte {
+                b'"' => b"\\\"",
+                b'\\' => b"\\\\",
+                b'\n' => b"\\n",
+                b'\r' => b"\\r",
+                b'\t' => b"\\t",
+                0..=0x1f => {
+                    self.0.write_all(&bytes[start..i])?;
+                    write!(self.0, "\\u{:04x}", byte)?;
+                    start = i + 1;
+                    continue;
+                }
+                _ => continue,
+            };
+            self.0.write_all(&bytes[start..i])?;
+            self.0.write_all(escaped)?;
+            start = i + 1;
+        }
+        self.0.write_all(&bytes[start..])
+    }
+
+    // used by the write! macro
+    fn write_fmt(&mut self, args: std::fmt::Arguments) -> std::io::Result<()> {
+        struct Adapter<'a, 'b> {
+            escaper: &'a mut JsonEscaper<'b>,
+            result: std::io::Result<()>,
+        }
+        impl std::fmt::Write for Adapter<'_, '_> {
+            fn write_str(&mut self, s: &str) -> std::fmt::Result {
+                self.result = self.escaper.write_str(s);
+                if self.result.is_ok() {
+                    Ok(())
+                } else {
+                    Err(std::fmt::Error)
+                }
+            }
+        }
+        let mut adapter = Adapter {
+            escaper: self,
+            result: Ok(()),
+        };
+        if std::fmt::Write::write_fmt(&mut adapter, args).is_err() {
+            adapter.result?;
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                "formatting failed",
+            ));
+        }
+        Ok(())
+    }
+}
+
 /// Helper function that is used in the provided coloring format functions to apply
-/// colors based on the log level and the effective color palette.
+/// colors to the message, based on the log level and the effective color palette.
+///
+/// Applies no colors if [`Logger::colorize_level_only`](crate::Logger::colorize_level_only)
+/// is used.
 ///
-/// See [`Logger::set_palette`](crate::Logger::set_palette) if you want to
+/// See [`Logger::set_palette`](crate::Logger::set_palette) and
+/// [`Logger::palette`](crate::Logger::palette) if you want to
 /// modify the color palette.
 ///
 /// Only available with feature `colors`.
 #[cfg(feature = "colors")]
 pub fn style<T>(level: log::Level, item: T) -> Paint<T> {
-    let palette = &*(PALETTE.read().unwrap());
-    match level {
-        log::Level::Error => palette.error,
-        log::Level::Warn => palette.warn,
-        log::Level::Info => palette.info,
-        log::Level::Debug => palette.debug,
-        log::Level::Trace => palette.trace,
+    if colorize_level_only() {
+        Paint::new(item)
+    } else {
+        level_styles(level).message.to_yansi().paint(item)
     }
-    .paint(item)
+}
+
+/// Helper function that is used in the provided coloring format functions to apply
+/// colors to the level token (like `ERROR`), based on the log level and the effective
+/// color palette.
+///
+/// Only available with feature `colors`.
+#[cfg(feature = "colors")]
+pub fn style_level<T>(level: log::Level, item: T) -> Paint<T> {
+    level_styles(level).level.to_yansi().paint(item)
+}
+
+/// Helper function that is used in the provided coloring format functions to apply
+/// colors to the timestamp, based on the log level and the effective color palette.
+///
+/// Applies no colors if [`Logger::colorize_level_only`](crate::Logger::colorize_level_only)
+/// is used.
+///
+/// Only available with feature `colors`.
+#[cfg(feature = "colors")]
+pub fn style_timestamp<T>(level: log::Level, item: T) -> Paint<T> {
+    if colorize_level_only() {
+        Paint::new(item)
+    } else {
+        level_styles(level).timestamp.to_yansi().paint(item)
+    }
+}
+
+#[cfg(feature = "colors")]
+fn colorize_level_only() -> bool {
+    current_format_settings().colorize_level_only
+}
+
+#[cfg(feature = "colors")]
+fn level_styles(level: log::Level) -> LevelStyles {
+    if let Some(styles) = STYLE_OVERRIDES.with(|current| {
+        current
+            .borrow()
+            .as_ref()
+            .and_then(|overrides| overrides.o_palette.as_ref())
+            .map(|palette| palette.level_styles(level))
+    }) {
+        return styles;
+    }
+    PALETTE
+        .read()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .level_styles(level)
+}
+
+/// The output style of stderr or of stdout, consisting of the format,
+/// the palette, and the timestamp format.
+///
+/// Use it with [`Logger::style_for_stderr`](crate::Logger::style_for_stderr) and
+/// [`Logger::style_for_stdout`](crate::Logger::style_for_stdout), both for the log target
+/// and for the duplication. The parts that are not set here are taken from the
+/// logger-wide settings.
+///
+/// ```rust
+/// # #[cfg(feature = "colors")]
+/// # {
+/// use flexi_logger::{colored_default_format, opt_format, ConsoleStyle, Duplicate,
+///     Logger, Palette, TimestampFormat};
+/// Logger::with_str("info")
+///     .log_to_file()
+///     .format_for_files(opt_format)
+///     .duplicate_to_stderr(Duplicate::Warn)
+///     .style_for_stderr(
+///         ConsoleStyle::new()
+///             .format(colored_default_format)
+///             .palette("9;11;-;7;8".parse::<Palette>().unwrap())
+///             .timestamp_format(TimestampFormat::Rfc3339Millis),
+///     );
+/// # }
+/// ```
+#[derive(Clone, Default)]
+pub struct ConsoleStyle {
+    pub(crate) o_format: Option<FormatFunction>,
+    #[cfg(feature = "atty")]
+    pub(crate) o_adaptive_format: Option<AdaptiveFormat>,
+    pub(crate) overrides: StyleOverrides,
+}
+impl ConsoleStyle {
+    /// Creates a style that takes all its parts from the logger-wide settings.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the format function.
+    #[must_use]
+    pub fn format(mut self, format: FormatFunction) -> Self {
+        self.o_format = Some(format);
+        #[cfg(feature = "atty")]
+        {
+            self.o_adaptive_format = None;
+        }
+        self
+    }
+
+    /// Sets an adaptive format, which uses colors only if the stream is a tty.
+    ///
+    /// Only available with feature `atty`.
+    #[cfg(feature = "atty")]
+    #[must_use]
+    pub fn adaptive_format(mut self, adaptive_format: AdaptiveFormat) -> Self {
+        self.o_adaptive_format = Some(adaptive_format);
+        self.o_format = None;
+        self
+    }
+
+    /// Sets the palette for the coloring format functions.
+    ///
+    /// Unlike the logger-wide palette, it is not affected by the environment variable
+    /// `FLEXI_LOGGER_PALETTE`.
+    ///
+    /// Only available with feature `colors`.
+    #[cfg(feature = "colors")]
+    #[must_use]
+    pub fn palette(mut self, palette: Palette) -> Self {
+        self.overrides.o_palette = Some(palette);
+        self
+    }
+
+    /// Sets the format of the timestamps, see [`TimestampFormat`](crate::TimestampFormat).
+    #[must_use]
+    pub fn timestamp_format(mut self, timestamp_format: TimestampFormat) -> Self {
+        self.overrides.o_timestamp_format = Some(timestamp_format);
+        self
+    }
+}
+
+// A format function that can be replaced at runtime, e.g. via the specfile.
+pub(crate) struct ReplaceableFormat(RwLock<FormatFunction>);
+impl ReplaceableFormat {
+    pub(crate) fn new(format: FormatFunction) -> Self {
+        Self(RwLock::new(format))
+    }
+
+    pub(crate) fn load(&self) -> FormatFunction {
+        *self.0.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    pub(crate) fn store(&self, format: FormatFunction) {
+        *self.0.write().unwrap_or_else(PoisonError::into_inner) = format;
+    }
+}
+
+// The parts of a `ConsoleStyle` that replace the logger-wide settings
+// while a stream writer formats a record.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct StyleOverrides {
+    #[cfg(feature = "colors")]
+    o_palette: Option<Palette>,
+    o_timestamp_format: Option<TimestampFormat>,
+}
+
+thread_local! {
+    static STYLE_OVERRIDES: std::cell::RefCell<Option<Arc<StyleOverrides>>> =
+        std::cell::RefCell::new(None);
+}
+
+// Runs `f` with the given overrides of the palette and the timestamp format.
+pub(crate) fn with_style_overrides<R>(
+    o_overrides: Option<&Arc<StyleOverrides>>,
+    f: impl FnOnce() -> R,
+) -> R {
+    match o_overrides {
+        None => f(),
+        Some(overrides) => {
+            let o_previous =
+                STYLE_OVERRIDES.with(|current| current.replace(Some(Arc::clone(overrides))));
+            let result = f();
+            STYLE_OVERRIDES.with(|current| current.replace(o_previous));
+            result
+        }
+    }
+}
+
+pub(crate) fn timestamp_format_override() -> Option<TimestampFormat> {
+    STYLE_OVERRIDES
+        .try_with(|current| {
+            current
+                .borrow()
+                .as_ref()
+                .and_then(|overrides| overrides.o_timestamp_format)
+        })
+        .ok()
+        .flatten()
 }
 
 #[cfg(feature = "colors")]
@@ -262,73 +973,298 @@ lazy_static::lazy_static! {
     static ref PALETTE: std::sync::RwLock<Palette> = std::sync::RwLock::new(Palette::default());
 }
 
+// How the palette was specified.
+#[cfg(feature = "colors")]
+#[derive(Clone, Debug)]
+pub(crate) enum PaletteInput {
+    String(String),
+    Palette(Palette),
+}
+
 // Overwrites the default PALETTE value either from the environment, if set,
 // or from the parameter, if filled.
 // Returns an error if parsing failed.
 #[cfg(feature = "colors")]
-pub(crate) fn set_palette(input: &Option<String>) -> Result<(), std::num::ParseIntError> {
-    match std::env::var_os("FLEXI_LOGGER_PALETTE") {
-        Some(ref env_osstring) => {
-            *(PALETTE.write().unwrap()) = Palette::from(env_osstring.to_string_lossy().as_ref())?;
-        }
+pub(crate) fn set_palette(input: &Option<PaletteInput>) -> Result<(), std::num::ParseIntError> {
+    let palette = match std::env::var_os("FLEXI_LOGGER_PALETTE") {
+        Some(ref env_osstring) => env_osstring.to_string_lossy().parse()?,
         None => match input {
-            Some(ref input_string) => {
-                *(PALETTE.write().unwrap()) = Palette::from(input_string)?;
-            }
-            None => {}
+            Some(PaletteInput::String(ref input_string)) => input_string.parse()?,
+            Some(PaletteInput::Palette(ref palette)) => palette.clone(),
+            None => return Ok(()),
         },
-    }
+    };
+    *(PALETTE
+        .write()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)) = palette;
     Ok(())
 }
 
+/// The color palette that is used by the provided coloring format functions,
+/// with separate styles for each log level.
+///
+/// Styles can use fixed colors, RGB colors, and attributes like bold, italic, or underline,
+/// see [`Style`](crate::Style) and [`Color`](crate::Color).
+///
+/// ```rust
+/// # #[cfg(feature = "colors")]
+/// # {
+/// use flexi_logger::{Color, LevelStyles, Logger, Palette, Style};
+/// let palette = Palette {
+///     error: LevelStyles {
+///         level: Style::new(Color::Rgb(255, 0, 0)).bold().underline(),
+///         timestamp: Style::new(Color::Fixed(8)),
+///         message: Style::new(Color::Rgb(255, 0, 0)),
+///     },
+///     info: LevelStyles::new(Style::new(Color::Unset).italic()),
+///     ..Palette::default()
+/// };
+/// Logger::with_str("info").palette(palette);
+/// # }
+/// ```
+///
+/// A palette can also be parsed from a String, see
+/// [`Logger::set_palette`](crate::Logger::set_palette).
+///
+/// Only available with feature `colors`.
 #[cfg(feature = "colors")]
-#[derive(Debug)]
-struct Palette {
-    pub error: Style,
-    pub warn: Style,
-    pub info: Style,
-    pub debug: Style,
-    pub trace: Style,
+#[derive(Clone, Debug, PartialEq)]
+pub struct Palette {
+    /// Styles for `error` messages.
+    pub error: LevelStyles,
+    /// Styles for `warn` messages.
+    pub warn: LevelStyles,
+    /// Styles for `info` messages.
+    pub info: LevelStyles,
+    /// Styles for `debug` messages.
+    pub debug: LevelStyles,
+    /// Styles for `trace` messages.
+    pub trace: LevelStyles,
 }
 #[cfg(feature = "colors")]
 impl Palette {
-    fn default() -> Palette {
-        Palette {
-            error: Style::new(Color::Fixed(196)).bold(),
-            warn: Style::new(Color::Fixed(208)).bold(),
-            info: Style::new(Color::Unset),
-            debug: Style::new(Color::Fixed(7)),
-            trace: Style::new(Color::Fixed(8)),
+    fn level_styles(&self, level: log::Level) -> LevelStyles {
+        match level {
+            log::Level::Error => self.error,
+            log::Level::Warn => self.warn,
+            log::Level::Info => self.info,
+            log::Level::Debug => self.debug,
+            log::Level::Trace => self.trace,
+        }
+    }
+}
+#[cfg(feature = "colors")]
+impl Default for Palette {
+    /// The default palette, `"196;208;-;7;8"`, with bold error and warn messages.
+    fn default() -> Self {
+        Self {
+            error: LevelStyles::new(Style::new(Color::Fixed(196)).bold()),
+            warn: LevelStyles::new(Style::new(Color::Fixed(208)).bold()),
+            info: LevelStyles::new(Style::new(Color::Unset)),
+            debug: LevelStyles::new(Style::new(Color::Fixed(7))),
+            trace: LevelStyles::new(Style::new(Color::Fixed(8))),
         }
     }
+}
+#[cfg(feature = "colors")]
+impl std::str::FromStr for Palette {
+    type Err = std::num::ParseIntError;
 
-    fn from(palette: &str) -> Result<Palette, std::num::ParseIntError> {
+    /// Parses the palette format described in
+    /// [`Logger::set_palette`](crate::Logger::set_palette).
+    fn from_str(palette: &str) -> Result<Self, Self::Err> {
         let mut items = palette.split(';');
-        Ok(Palette {
-            error: parse_style(items.next().unwrap_or("196").trim())?,
-            warn: parse_style(items.next().unwrap_or("208").trim())?,
-            info: parse_style(items.next().unwrap_or("-").trim())?,
-            debug: parse_style(items.next().unwrap_or("7").trim())?,
-            trace: parse_style(items.next().unwrap_or("8").trim())?,
+        let mut next = |default| -> Result<LevelStyles, Self::Err> {
+            Ok(LevelStyles::new(parse_style(
+                items.next().unwrap_or(default).trim(),
+            )?))
+        };
+        Ok(Self {
+            error: next("196")?,
+            warn: next("208")?,
+            info: next("-")?,
+            debug: next("7")?,
+            trace: next("8")?,
         })
     }
 }
 
+/// The styles that are used for the parts of log lines of a certain level.
+///
+/// Only available with feature `colors`.
+#[cfg(feature = "colors")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LevelStyles {
+    /// Style of the level token, like `ERROR`.
+    pub level: Style,
+    /// Style of the timestamp.
+    pub timestamp: Style,
+    /// Style of the message body.
+    pub message: Style,
+}
+#[cfg(feature = "colors")]
+impl LevelStyles {
+    /// Uses the given style for all parts of the log line.
+    #[must_use]
+    pub fn new(style: Style) -> Self {
+        Self {
+            level: style,
+            timestamp: style,
+            message: style,
+        }
+    }
+}
+
+/// A color of the provided coloring format functions, see [`Style`](crate::Style).
+///
+/// Only available with feature `colors`.
+#[cfg(feature = "colors")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Color {
+    /// The default color of the terminal.
+    Unset,
+    /// One of the 256 colors of an ANSI terminal.
+    Fixed(u8),
+    /// An RGB color; not all terminals support these.
+    Rgb(u8, u8, u8),
+}
+
+/// The style of a part of a log line: a color and some attributes.
+///
+/// Only available with feature `colors`.
+#[cfg(feature = "colors")]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Style {
+    color: Color,
+    bold: bool,
+    italic: bool,
+    underline: bool,
+}
+#[cfg(feature = "colors")]
+impl Style {
+    /// A style with the given color and without attributes.
+    #[must_use]
+    pub fn new(color: Color) -> Self {
+        Self {
+            color,
+            bold: false,
+            italic: false,
+            underline: false,
+        }
+    }
+
+    /// Adds the attribute bold.
+    #[must_use]
+    pub fn bold(mut self) -> Self {
+        self.bold = true;
+        self
+    }
+
+    /// Adds the attribute italic.
+    #[must_use]
+    pub fn italic(mut self) -> Self {
+        self.italic = true;
+        self
+    }
+
+    /// Adds the attribute underline.
+    #[must_use]
+    pub fn underline(mut self) -> Self {
+        self.underline = true;
+        self
+    }
+
+    fn to_yansi(self) -> yansi::Style {
+        let mut style = yansi::Style::new(match self.color {
+            Color::Unset => yansi::Color::Unset,
+            Color::Fixed(number) => yansi::Color::Fixed(number),
+            Color::Rgb(r, g, b) => yansi::Color::RGB(r, g, b),
+        });
+        if self.bold {
+            style = style.bold();
+        }
+        if self.italic {
+            style = style.italic();
+        }
+        if self.underline {
+            style = style.underline();
+        }
+        style
+    }
+}
+
+// Parses a dash (no coloring), a fixed color number, or an RGB color in the form `#rrggbb`.
 #[cfg(feature = "colors")]
 fn parse_style(input: &str) -> Result<Style, std::num::ParseIntError> {
     Ok(if input == "-" {
         Style::new(Color::Unset)
+    } else if let Some(hex) = input.strip_prefix('#') {
+        // other input than six hex digits fails like an empty number
+        let valid = hex.len() == 6 && hex.bytes().all(|b| b.is_ascii_hexdigit());
+        let channel = |range| u8::from_str_radix(if valid { &hex[range] } else { "" }, 16);
+        Style::new(Color::Rgb(channel(0..2)?, channel(2..4)?, channel(4..6)?))
     } else {
         Style::new(Color::Fixed(input.parse()?))
     })
 }
 
+// Resolves the format names that can be used in the specfile, in the configuration file,
+// and in the environment to the (uncolored) format function.
+pub(crate) fn format_for_name(name: &str) -> Option<FormatFunction> {
+    match name {
+        "default" => Some(default_format),
+        "detailed" => Some(detailed_format),
+        "opt" => Some(opt_format),
+        "with_thread" => Some(with_thread),
+        "elapsed" => Some(elapsed_format),
+        _ => None,
+    }
+}
+
+// Resolves the format names to the adaptive format.
+#[cfg(feature = "colors")]
+pub(crate) fn adaptive_format_for_name(name: &str) -> Option<AdaptiveFormat> {
+    match name {
+        "default" => Some(AdaptiveFormat::Default),
+        "detailed" => Some(AdaptiveFormat::Detailed),
+        "opt" => Some(AdaptiveFormat::Opt),
+        "with_thread" => Some(AdaptiveFormat::WithThread),
+        "elapsed" => Some(AdaptiveFormat::Elapsed),
+        _ => None,
+    }
+}
+
+// Resolves the format names to the format functions for files, for stderr, and for stdout.
+#[cfg(feature = "specfile_without_notification")]
+pub(crate) fn formats_for_name(
+    name: &str,
+) -> Option<(FormatFunction, FormatFunction, FormatFunction)> {
+    let format = format_for_name(name)?;
+    #[cfg(feature = "colors")]
+    {
+        let adaptive_format = adaptive_format_for_name(name)?;
+        Some((
+            format,
+            adaptive_format.format_function(Stream::StdErr),
+            adaptive_format.format_function(Stream::StdOut),
+        ))
+    }
+    #[cfg(not(feature = "colors"))]
+    Some((format, format, format))
+}
+
 /// Specifies the `FormatFunction` and decides if coloring should be used.
 ///
 /// Is used in
 /// [`Logger::adaptive_format_for_stderr`](crate::Logger::adaptive_format_for_stderr) and
 /// [`Logger::adaptive_format_for_stdout`](crate::Logger::adaptive_format_for_stdout).
-/// The coloring format functions are used if the output channel is a tty.
+/// The coloring format functions are used if the output channel is a tty,
+/// unless this is overridden with environment variables or with
+/// [`Logger::color_choice`](crate::Logger::color_choice), see [`ColorChoice`](crate::ColorChoice).
+///
+/// On Windows, the processing of ANSI escape sequences is enabled on the console
+/// when coloring is active; if this fails (e.g. on legacy consoles),
+/// the non-coloring format functions are used.
 ///
 /// Only available with feature `atty`.
 #[cfg(feature = "atty")]
@@ -358,6 +1294,12 @@ pub enum AdaptiveFormat {
     /// Only available with feature `colors`.
     #[cfg(feature = "colors")]
     WithThread,
+    /// Chooses between [`elapsed_format`](crate::elapsed_format)
+    /// and [`colored_elapsed_format`](crate::colored_elapsed_format).
+    ///
+    /// Only available with feature `colors`.
+    #[cfg(feature = "colors")]
+    Elapsed,
     /// Chooses between the first format function (which is supposed to be uncolored)
     /// and the second (which is supposed to be colored).
     ///
@@ -368,9 +1310,43 @@ pub enum AdaptiveFormat {
 
 #[cfg(feature = "atty")]
 impl AdaptiveFormat {
+    // The name of the adaptive format, e.g. `adaptive(opt)`.
+    pub(crate) fn name(self) -> String {
+        let name = match self {
+            #[cfg(feature = "colors")]
+            Self::Default => "default",
+            #[cfg(feature = "colors")]
+            Self::Detailed => "detailed",
+            #[cfg(feature = "colors")]
+            Self::Opt => "opt",
+            #[cfg(feature = "colors")]
+            Self::WithThread => "with_thread",
+            #[cfg(feature = "colors")]
+            Self::Elapsed => "elapsed",
+            Self::Custom(_, _) => "custom",
+        };
+        format!("adaptive({})", name)
+    }
+
+    fn uncolored(self) -> FormatFunction {
+        match self {
+            #[cfg(feature = "colors")]
+            Self::Default => default_format,
+            #[cfg(feature = "colors")]
+            Self::Detailed => detailed_format,
+            #[cfg(feature = "colors")]
+            Self::Opt => opt_format,
+            #[cfg(feature = "colors")]
+            Self::WithThread => with_thread,
+            #[cfg(feature = "colors")]
+            Self::Elapsed => elapsed_format,
+            Self::Custom(uncolored, _) => uncolored,
+        }
+    }
+
     #[must_use]
     pub(crate) fn format_function(self, stream: Stream) -> FormatFunction {
-        if stream.is_tty() {
+        if stream.use_colors() {
             match self {
                 #[cfg(feature = "colors")]
                 Self::Default => colored_default_format,
@@ -380,20 +1356,12 @@ impl AdaptiveFormat {
                 Self::Opt => colored_opt_format,
                 #[cfg(feature = "colors")]
                 Self::WithThread => colored_with_thread,
+                #[cfg(feature = "colors")]
+                Self::Elapsed => colored_elapsed_format,
                 Self::Custom(_, colored) => colored,
             }
         } else {
-            match self {
-                #[cfg(feature = "colors")]
-                Self::Default => default_format,
-                #[cfg(feature = "colors")]
-                Self::Detailed => detailed_format,
-                #[cfg(feature = "colors")]
-                Self::Opt => opt_format,
-                #[cfg(feature = "colors")]
-                Self::WithThread => with_thread,
-                Self::Custom(uncolored, _) => uncolored,
-            }
+            self.uncolored()
         }
     }
 }
@@ -413,4 +1381,286 @@ impl Stream {
             Self::StdErr => atty::is(atty::Stream::Stderr),
         }
     }
+
+    // Decides if the adaptive formats use colors for this stream.
+    #[must_use]
+    fn use_colors(self) -> bool {
+        let use_colors = match current_format_settings().color_choice {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => ColorChoice::from_env(|name| std::env::var_os(name))
+                .unwrap_or_else(|| self.is_tty()),
+        };
+        use_colors && ansi_support()
+    }
+}
+
+// Tries (once) to enable the processing of ANSI escape sequences on Windows consoles
+// (ENABLE_VIRTUAL_TERMINAL_PROCESSING), and disables coloring if this fails,
+// e.g. on legacy consoles.
+// Returns true if escape sequences can be used; is always true on other platforms.
+#[cfg(feature = "atty")]
+pub(crate) fn ansi_support() -> bool {
+    #[cfg(all(windows, feature = "colors"))]
+    {
+        lazy_static::lazy_static! {
+            static ref ANSI_SUPPORT: bool = {
+                let supported = yansi::Paint::enable_windows_ascii();
+                if !supported {
+                    yansi::Paint::disable();
+                }
+                supported
+            };
+        }
+        *ANSI_SUPPORT
+    }
+    #[cfg(not(all(windows, feature = "colors")))]
+    true
+}
+
+/// Decides if the adaptive formats (see [`AdaptiveFormat`](crate::AdaptiveFormat))
+/// use colors.
+///
+/// Is used in [`Logger::color_choice`](crate::Logger::color_choice).
+///
+/// Only available with feature `atty`.
+#[cfg(feature = "atty")]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorChoice {
+    /// Colors are used if the output channel is a tty, unless the environment
+    /// decides otherwise (this is the default):
+    ///
+    /// * if `CLICOLOR_FORCE` is set and not `0`, colors are used,
+    /// * otherwise, if `NO_COLOR` is set and not empty, no colors are used,
+    /// * otherwise, if `CLICOLOR` is `0`, no colors are used.
+    Auto,
+    /// Colors are always used.
+    Always,
+    /// Colors are never used.
+    Never,
+}
+#[cfg(feature = "atty")]
+impl ColorChoice {
+    // Evaluates the conventions CLICOLOR_FORCE, NO_COLOR, and CLICOLOR, as `var` reports them.
+    fn from_env<F>(var: F) -> Option<bool>
+    where
+        F: Fn(&str) -> Option<std::ffi::OsString>,
+    {
+        if var("CLICOLOR_FORCE").map_or(false, |v| v != "0") {
+            Some(true)
+        } else if var("NO_COLOR").map_or(false, |v| !v.is_empty())
+            || var("CLICOLOR").map_or(false, |v| v == "0")
+        {
+            Some(false)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(all(test, feature = "atty"))]
+mod tests {
+    use super::ColorChoice;
+
+    // The provided formats keep their layout as long as no fields are overridden.
+    #[test]
+    fn provided_formats_layout() {
+        use super::{default_format, detailed_format, opt_format, with_thread, FormatFunction};
+        let format = |format_function: FormatFunction| {
+            let mut buffer = Vec::<u8>::new();
+            format_function(
+                &mut buffer,
+                &mut crate::DeferredNow::new(),
+                &log::Record::builder()
+                    .args(format_args!("Task done"))
+                    .level(log::Level::Info)
+                    .module_path(Some("foo::bar"))
+                    .file(Some("src/foo/bar.rs"))
+                    .line(Some(26))
+                    .build(),
+            )
+            .unwrap();
+            let line = String::from_utf8(buffer).unwrap();
+            // cut off the timestamp
+            match line.find("] ") {
+                Some(idx) if line.starts_with('[') => line[idx + 2..].to_string(),
+                _ => line,
+            }
+        };
+        assert_eq!(format(default_format), "INFO [foo::bar] Task done");
+        assert_eq!(format(opt_format), "INFO [src/foo/bar.rs:26] Task done");
+        assert_eq!(
+            format(detailed_format),
+            "INFO [foo::bar] src/foo/bar.rs:26: Task done"
+        );
+        assert!(format(with_thread).starts_with("T[\""));
+        assert!(format(with_thread).ends_with("] INFO [src/foo/bar.rs:26] Task done"));
+    }
+
+    #[test]
+    fn relative_times() {
+        use super::RelativeTimes;
+        use std::time::Duration;
+        let elapsed = Some(Duration::from_micros(3_016_481));
+        let delta = Some(Duration::from_micros(112));
+        assert_eq!(
+            RelativeTimes(elapsed, delta).to_string(),
+            "3.016481s +0.000112s"
+        );
+        assert_eq!(RelativeTimes(elapsed, None).to_string(), "3.016481s");
+        assert_eq!(RelativeTimes(None, delta).to_string(), "+0.000112s");
+    }
+
+    #[test]
+    fn color_choice_from_env() {
+        fn from_vars(vars: &[(&str, &str)]) -> Option<bool> {
+            ColorChoice::from_env(|name| {
+                vars.iter().find(|(n, _)| *n == name).map(|(_, v)| v.into())
+            })
+        }
+
+        assert_eq!(from_vars(&[]), None);
+
+        assert_eq!(from_vars(&[("CLICOLOR", "0")]), Some(false));
+        assert_eq!(from_vars(&[("CLICOLOR", "1")]), None);
+
+        assert_eq!(from_vars(&[("NO_COLOR", "")]), None);
+        assert_eq!(from_vars(&[("NO_COLOR", "1")]), Some(false));
+
+        // CLICOLOR_FORCE wins over NO_COLOR
+        assert_eq!(
+            from_vars(&[("NO_COLOR", "1"), ("CLICOLOR_FORCE", "0")]),
+            Some(false)
+        );
+        assert_eq!(
+            from_vars(&[("NO_COLOR", "1"), ("CLICOLOR_FORCE", "1")]),
+            Some(true)
+        );
+    }
+
+    #[cfg(feature = "colors")]
+    #[test]
+    fn palette_from_str() {
+        use super::{Color, LevelStyles, Palette, Style};
+
+        assert_eq!(
+            "196;208;-;7;8".parse::<Palette>().unwrap().debug,
+            Palette::default().debug
+        );
+
+        let palette: Palette = "#ff8000;1;-".parse().unwrap();
+        assert_eq!(
+            palette.error,
+            LevelStyles::new(Style::new(Color::Rgb(255, 128, 0)))
+        );
+        assert_eq!(palette.warn, LevelStyles::new(Style::new(Color::Fixed(1))));
+        assert_eq!(palette.trace, LevelStyles::new(Style::new(Color::Fixed(8))));
+
+        assert!("#ff80;1".parse::<Palette>().is_err());
+        assert!("#ff80000".parse::<Palette>().is_err());
+        assert!("#+f8000".parse::<Palette>().is_err());
+        assert!("#ff80zz".parse::<Palette>().is_err());
+        assert!("256".parse::<Palette>().is_err());
+    }
+}
+
+#[cfg(test)]
+mod short_module_path_tests {
+    use super::ShortModulePath;
+
+    #[test]
+    fn short_module_path() {
+        let path = "my_crate::storage::sql::db::pool";
+        assert_eq!(ShortModulePath::MaxWidth(100).apply(path), path);
+        assert_eq!(
+            ShortModulePath::MaxWidth(25).apply(path),
+            "my_crate::…::db::pool"
+        );
+        assert_eq!(
+            ShortModulePath::MaxWidth(5).apply(path),
+            "my_crate::…::pool"
+        );
+        assert_eq!(
+            ShortModulePath::MaxWidth(5).apply("a::bbbbbbbb"),
+            "a::bbbbbbbb"
+        );
+        assert_eq!(ShortModulePath::LastSegments(2).apply(path), "db::pool");
+        assert_eq!(ShortModulePath::LastSegments(0).apply(path), "pool");
+        assert_eq!(ShortModulePath::LastSegments(9).apply(path), path);
+    }
+}
+
+#[cfg(test)]
+mod json_tests {
+    use super::json_format;
+    use crate::DeferredNow;
+
+    #[test]
+    fn json_format_escapes() {
+        let mut buffer = Vec::new();
+        json_format(
+            &mut buffer,
+            &mut DeferredNow::new(),
+            &log::Record::builder()
+                .args(format_args!("say \"hi\"\\\n\t{}", '\u{1}'))
+                .level(log::Level::Warn)
+                .module_path(Some("json_tests"))
+                .line(Some(7))
+                .build(),
+        )
+        .unwrap();
+        let line = String::from_utf8(buffer).unwrap();
+        assert!(line.starts_with("{\"timestamp\":\""), "{}", line);
+        assert!(
+            line.ends_with(
+                "\"level\":\"WARN\",\"module_path\":\"json_tests\",\"file\":null,\
+                 \"line\":7,\"text\":\"say \\\"hi\\\"\\\\\\n\\t\\u0001\"}"
+            ),
+            "{}",
+            line
+        );
+    }
+}
+
+#[cfg(test)]
+mod style_tests {
+    use super::{json_format, with_style_overrides, StyleOverrides};
+    use crate::{DeferredNow, TimestampFormat};
+    use std::sync::Arc;
+
+    fn json_line() -> String {
+        let mut buffer = Vec::new();
+        json_format(
+            &mut buffer,
+            &mut DeferredNow::new(),
+            &log::Record::builder()
+                .args(format_args!("styled"))
+                .level(log::Level::Error)
+                .build(),
+        )
+        .unwrap();
+        String::from_utf8(buffer).unwrap()
+    }
+
+    #[test]
+    fn style_overrides() {
+        let overrides = Arc::new(StyleOverrides {
+            #[cfg(feature = "colors")]
+            o_palette: Some("1;2;3;4;5".parse().unwrap()),
+            o_timestamp_format: Some(TimestampFormat::UnixSeconds),
+        });
+
+        // e.g. `{"timestamp":1610632800,...`
+        let line = with_style_overrides(Some(&overrides), json_line);
+        let timestamp = line[13..].split(',').next().unwrap();
+        assert!(timestamp.chars().all(|c| c.is_ascii_digit()), "{}", line);
+        #[cfg(feature = "colors")]
+        assert_eq!(
+            with_style_overrides(Some(&overrides), || super::level_styles(log::Level::Error)),
+            super::LevelStyles::new(super::Style::new(super::Color::Fixed(1)))
+        );
+
+        // outside of the stream writers, the global settings apply again
+        assert!(json_line()[13..].starts_with('"'));
+    }
 }
@@ -38,15 +38,29 @@
 //! See the documentation of method [style](crate::style)
 //! for a description how this can be done.
 
+mod child_process;
+#[cfg(feature = "config_file")]
+mod config_file;
 mod deferred_now;
+mod diagnostics;
+mod effective_config;
+mod error_chain;
+mod error_channel;
 mod flexi_error;
 mod flexi_logger;
 mod formats;
 mod log_specification;
 mod logger;
 mod logger_handle;
+mod middleware;
 mod parameters;
 mod primary_writer;
+mod stats;
+#[cfg(unix)]
+mod std_capture;
+mod timestamp;
+#[cfg(all(target_arch = "wasm32", feature = "wasm-bindgen"))]
+mod wasm_console;
 
 pub mod code_examples;
 pub mod writers;
@@ -54,13 +68,31 @@ pub mod writers;
 /// Re-exports from log crate
 pub use log::{Level, LevelFilter, Record};
 
+// Used by the exported macros, so that they work without a dependency to log.
+#[doc(hidden)]
+pub use log as __log;
+
+pub use crate::child_process::{CapturedChild, ChildProcessCapture};
+#[cfg(feature = "config_file")]
+pub use crate::config_file::{FileConfig, LoggerConfig, RotationConfig, WriterFactory};
 pub use crate::deferred_now::DeferredNow;
+pub use crate::effective_config::{EffectiveConfig, EffectiveFileConfig};
+pub use crate::error_chain::ErrorChain;
+pub use crate::error_channel::ErrorChannel;
 pub use crate::flexi_error::FlexiLoggerError;
 pub use crate::formats::*;
-pub use crate::log_specification::{LogSpecBuilder, LogSpecification, ModuleFilter};
+pub use crate::log_specification::{ConstLogSpec, LogSpecBuilder, LogSpecification, ModuleFilter};
 pub use crate::logger::{Duplicate, LogTarget, Logger};
-pub use crate::logger_handle::LoggerHandle;
-pub use crate::parameters::{Age, Cleanup, Criterion, Naming};
+pub use crate::logger_handle::{LoggerHandle, ScopedSpecGuard, ThreadSpecGuard};
+pub use crate::middleware::{RecordDraft, RecordMiddleware};
+pub use crate::parameters::{
+    Age, BrokenPipe, Cleanup, Criterion, DiskFull, FlushPolicy, Naming, NumberOrder, SyncPolicy,
+    WriteMode,
+};
+pub use crate::stats::LogStats;
+#[cfg(feature = "chrono")]
+pub use crate::timestamp::Timestamp;
+pub use crate::timestamp::TimestampFormat;
 
 /// For backwards compatibility.
 #[deprecated]
@@ -68,3 +100,6 @@ pub type ReconfigurationHandle = LoggerHandle;
 
 pub(crate) const DEFAULT_BUFFER_CAPACITY: usize = 8 * 1024;
 pub(crate) const DEFAULT_FLUSH_WAIT_TIME: std::time::Duration = std::time::Duration::from_secs(1);
+#[cfg(feature = "specfile")]
+pub(crate) const DEFAULT_SPECFILE_DEBOUNCE_DELAY: std::time::Duration =
+    std::time::Duration::from_millis(100);
@@ -1,10 +1,15 @@
 use crate::flexi_error::FlexiLoggerError;
 use crate::LevelFilter;
 
+use glob::Pattern;
 #[cfg(feature = "textfilter")]
 use regex::Regex;
+#[cfg(feature = "textfilter")]
+use std::cell::RefCell;
 use std::collections::HashMap;
 use std::env;
+#[cfg(feature = "textfilter")]
+use std::fmt::Write;
 
 ///
 /// Immutable struct that defines which loglines are to be written,
@@ -18,9 +23,11 @@ use std::env;
 /// described with some Backus-Naur-form:
 ///
 /// ```text
-/// <log_level_spec> ::= single_log_level_spec[{,single_log_level_spec}][/<text_filter>]
-/// <single_log_level_spec> ::= <path_to_module>|<log_level>|<path_to_module>=<log_level>
-/// <text_filter> ::= <regex>
+/// <log_level_spec> ::= single_log_level_spec[{,single_log_level_spec}][/<text_filters>]
+/// <single_log_level_spec> ::= <path_to_module>|<log_level>|<path_to_module>=<log_level>|-<path_to_module>
+/// <path_to_module> ::= <module_name>|<module_glob>
+/// <text_filters> ::= [<text_filter>][{/<module_name>=<text_filter>}]
+/// <text_filter> ::= <regex>|!<regex>
 /// ```
 ///
 /// * Examples:
@@ -29,9 +36,14 @@ use std::env;
 ///   * `"crate1"`: all logs of this crate are written, but nothing else
 ///   * `"warn, crate2::mod_a=debug, mod_x::mod_y=trace"`: all crates log warnings and errors,
 ///     `mod_a` additionally debug messages, and `mod_x::mod_y` is fully traced
+///   * `"info, my_crate::*::db=trace, *engine*=debug"`: all `db` modules one level below
+///     `my_crate` are fully traced, and all modules with `engine` in their path log debug messages
 ///
 /// * If you just specify the module, without `log_level`, all levels will be traced for this
 ///   module.
+/// * If you prefix the module with a dash, e.g. `"info, -noisy_dep::poller"`,
+///   the module is excluded, i.e., nothing is logged for it;
+///   this is equivalent to `noisy_dep::poller=off`.
 /// * If you just specify a log level, this will be applied as default to all modules without
 ///   explicit log level assigment.
 ///   (You see that for modules named error, warn, info, debug or trace,
@@ -45,7 +57,38 @@ use std::env;
 ///   * `foobaz` (!)
 ///   * `foobaz::bar` (!)
 ///
+/// * A module name that contains one of the characters `*`, `?`, or `[` is treated as a
+///   glob pattern (see [`glob::Pattern`](https://docs.rs/glob/latest/glob/struct.Pattern.html)).
+///   `*` matches any sequence of characters, including `::`.
+///   A glob pattern affects a module if it matches the full module path,
+///   or one of its parent paths; `"my_crate::*::db"` thus affects `my_crate::a::db`
+///   and `my_crate::a::db::pool`, but not `my_crate::a::dbx`.
+/// * If several filters affect a module, the most specific one wins, which is the one with the
+///   most characters that are not wildcards (`*`, `?`, and character classes like `[ab]`).
+///   With a tie, a plain module name wins over a glob pattern, and otherwise the lexically
+///   smaller name wins, so the order of the filters in the specification does not matter.
+///   Example: in `"a*=off, ab=debug"`, `ab=debug` wins for module `ab`,
+///   and in `"*::*::*=trace, my_crate=info"`, `my_crate=info` wins for `my_crate::a::b`.
+///
 /// The optional text filter is applied for all modules.
+/// If it starts with `!`, it is negated, i.e., only log lines that do _not_ match the regex
+/// are written (`"info/!heartbeat"` suppresses all log lines that contain `heartbeat`).
+/// A regex that starts with a literal `!` is written with a leading backslash (`\!`).
+///
+/// Text filters that are applied only to specific modules follow the global text filter,
+/// which can be left empty, e.g. `"info/!heartbeat/crate1=^important/crate1::mod1=!noise"`
+/// or `"info//crate1=^important"`.
+/// They can also be defined with
+/// [`LogSpecBuilder::module_text_filter`](crate::LogSpecBuilder::module_text_filter)
+/// and its siblings, or in the specfile (see
+/// [`Logger::start_with_specfile`](crate::Logger::start_with_specfile)).
+/// A log line must pass both the global text filter and the text filter of the
+/// longest matching module, if these exist.
+///
+/// A `/` that is part of a regex is written with a backslash (`\/`);
+/// backslashes directly in front of such a `/`, or in front of a separating `/`,
+/// are doubled (`r"info/a\\\\/b=c"` has the global regex `a\\`, which matches a literal
+/// backslash, and the regex `c` for module `b`).
 ///
 /// Note that external module names are to be specified like in ```"extern crate ..."```, i.e.,
 /// for crates with a dash in their name this means: the dash is to be replaced with
@@ -57,8 +100,125 @@ use std::env;
 #[derive(Clone, Debug, Default)]
 pub struct LogSpecification {
     module_filters: Vec<ModuleFilter>,
+    // glob patterns for those module filters whose name is a glob, aligned with module_filters
+    module_globs: Vec<Option<Pattern>>,
+    #[cfg(feature = "textfilter")]
+    textfilter: Option<Box<TextFilter>>,
+    // sorted by the length of the module name, like the module filters
     #[cfg(feature = "textfilter")]
-    textfilter: Option<Box<Regex>>,
+    module_textfilters: Vec<(String, TextFilter)>,
+}
+
+// The content of a specfile; it is the same for all supported file formats.
+// Besides the log specification, it can contain settings for the writers,
+// which are evaluated by the specfile handling in the logger.
+#[cfg(feature = "serde")]
+#[derive(Clone, Debug, Default, serde::Deserialize, serde::Serialize)]
+pub(crate) struct LogSpecFileFormat {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) format: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) duplicate_to_stderr: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) duplicate_to_stdout: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub(crate) palette: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    global_level: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    global_pattern: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    modules: Option<std::collections::BTreeMap<String, String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    module_patterns: Option<std::collections::BTreeMap<String, String>>,
+}
+#[cfg(feature = "specfile_without_notification")]
+impl LogSpecFileFormat {
+    // Keeps the writer settings and takes the log specification from the given one.
+    pub(crate) fn with_spec(&self, spec: &LogSpecification) -> Self {
+        Self {
+            format: self.format.clone(),
+            duplicate_to_stderr: self.duplicate_to_stderr.clone(),
+            duplicate_to_stdout: self.duplicate_to_stdout.clone(),
+            palette: self.palette.clone(),
+            ..spec.to_file_format()
+        }
+    }
+
+    pub(crate) fn from_toml(s: &str) -> Result<Self, FlexiLoggerError> {
+        Ok(toml::from_str(s)?)
+    }
+
+    pub(crate) fn from_json(s: &str) -> Result<Self, FlexiLoggerError> {
+        Ok(serde_json::from_str(s)?)
+    }
+
+    pub(crate) fn from_yaml(s: &str) -> Result<Self, FlexiLoggerError> {
+        // an empty yaml document is "null"
+        if s.trim().is_empty() {
+            Ok(Self::default())
+        } else {
+            Ok(serde_yaml::from_str(s)?)
+        }
+    }
+}
+
+// A regex that log lines must match, or, if negated, must not match.
+#[cfg(feature = "textfilter")]
+#[derive(Clone, Debug)]
+struct TextFilter {
+    regex: Regex,
+    negated: bool,
+}
+#[cfg(feature = "textfilter")]
+impl TextFilter {
+    // A leading '!' negates the filter. A regex that starts with a literal '!'
+    // is escaped with a backslash, and backslashes in front of it are doubled.
+    fn parse(s: &str) -> Result<Self, regex::Error> {
+        if s.starts_with('!') {
+            return Ok(Self {
+                regex: Regex::new(&s[1..])?,
+                negated: true,
+            });
+        }
+        let backslashes = s.len() - s.trim_start_matches('\\').len();
+        let regex = if backslashes > 0 && s[backslashes..].starts_with('!') {
+            Regex::new(&format!(
+                "{}{}",
+                "\\".repeat(backslashes / 2),
+                &s[backslashes..]
+            ))?
+        } else {
+            Regex::new(s)?
+        };
+        Ok(Self {
+            regex,
+            negated: false,
+        })
+    }
+
+    fn accepts(&self, text: &str) -> bool {
+        self.regex.is_match(text) != self.negated
+    }
+}
+#[cfg(feature = "textfilter")]
+impl std::fmt::Display for TextFilter {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let regex = self.regex.as_str();
+        let backslashes = regex.len() - regex.trim_start_matches('\\').len();
+        if self.negated {
+            write!(f, "!{}", regex)
+        } else if regex[backslashes..].starts_with('!') {
+            write!(
+                f,
+                "{}{}",
+                "\\".repeat(2 * backslashes + 1),
+                &regex[backslashes..]
+            )
+        } else {
+            write!(f, "{}", regex)
+        }
+    }
 }
 
 /// Defines which loglevel filter to use for the specified module.
@@ -73,12 +233,74 @@ pub struct ModuleFilter {
 }
 
 impl LogSpecification {
+    // Sorts the module filters and prepares the glob patterns, without text filter.
+    // Module filters with an invalid glob pattern are dropped and reported in `parse_errs`.
+    fn from_module_filters(module_filters: Vec<ModuleFilter>, parse_errs: &mut String) -> Self {
+        let module_filters = module_filters
+            .into_iter()
+            .filter(|mf| {
+                !mf.module_name
+                    .as_ref()
+                    .map_or(false, |name| invalid_glob(name, parse_errs))
+            })
+            .collect::<Vec<ModuleFilter>>()
+            .level_sort();
+        let module_globs = module_filters
+            .iter()
+            .map(|mf| {
+                mf.module_name
+                    .as_ref()
+                    .filter(|name| is_glob(name))
+                    .and_then(|name| Pattern::new(name).ok())
+            })
+            .collect();
+        Self {
+            module_filters,
+            module_globs,
+            #[cfg(feature = "textfilter")]
+            textfilter: None,
+            #[cfg(feature = "textfilter")]
+            module_textfilters: Vec::new(),
+        }
+    }
+
+    // Like `from_module_filters`, for the constructors that cannot fail:
+    // the dropped module filters are reported to the error channel.
+    fn from_module_filters_or_report(module_filters: Vec<ModuleFilter>) -> Self {
+        let mut parse_errs = String::new();
+        let logspec = Self::from_module_filters(module_filters, &mut parse_errs);
+        if !parse_errs.is_empty() {
+            crate::error_channel::report(format_args!("{}", parse_errs));
+        }
+        logspec
+    }
+
+    #[cfg(feature = "textfilter")]
+    fn set_textfilters(
+        &mut self,
+        o_textfilter: Option<TextFilter>,
+        module_textfilters: HashMap<String, TextFilter>,
+    ) {
+        // an empty regex accepts everything, just like no text filter
+        self.textfilter = o_textfilter
+            .filter(|textfilter| textfilter.negated || !textfilter.regex.as_str().is_empty())
+            .map(Box::new);
+        let mut module_textfilters: Vec<(String, TextFilter)> =
+            module_textfilters.into_iter().collect();
+        module_textfilters.sort_by(|(name1, _), (name2, _)| {
+            name2.len().cmp(&name1.len()).then_with(|| name1.cmp(name2))
+        });
+        self.module_textfilters = module_textfilters;
+    }
+
     pub(crate) fn update_from(&mut self, other: Self) {
         self.module_filters = other.module_filters;
+        self.module_globs = other.module_globs;
 
         #[cfg(feature = "textfilter")]
         {
             self.textfilter = other.textfilter;
+            self.module_textfilters = other.module_textfilters;
         }
     }
 
@@ -94,10 +316,14 @@ impl LogSpecification {
     #[must_use]
     pub fn enabled(&self, level: log::Level, writing_module: &str) -> bool {
         // Search for the longest match, the vector is assumed to be pre-sorted.
-        for module_filter in &self.module_filters {
+        for (module_filter, o_glob) in self.module_filters.iter().zip(&self.module_globs) {
             match module_filter.module_name {
                 Some(ref module_name) => {
-                    if writing_module.starts_with(module_name) {
+                    let matches = match o_glob {
+                        Some(glob) => glob_matches(glob, writing_module),
+                        None => writing_module.starts_with(module_name),
+                    };
+                    if matches {
                         return level <= module_filter.level_filter;
                     }
                 }
@@ -107,6 +333,40 @@ impl LogSpecification {
         false
     }
 
+    // Returns true if the text of the record passes the global text filter
+    // and the text filter of the longest matching module, if these exist.
+    #[cfg(feature = "textfilter")]
+    pub(crate) fn text_enabled(&self, writing_module: &str, record: &log::Record) -> bool {
+        let o_module_textfilter = self
+            .module_textfilters
+            .iter()
+            .find(|(module_name, _)| writing_module.starts_with(module_name.as_str()))
+            .map(|(_, textfilter)| textfilter);
+        if self.textfilter.is_none() && o_module_textfilter.is_none() {
+            return true;
+        }
+        let accepts = |text: &str| {
+            self.textfilter
+                .as_ref()
+                .map_or(true, |textfilter| textfilter.accepts(text))
+                && o_module_textfilter.map_or(true, |textfilter| textfilter.accepts(text))
+        };
+
+        // render the message into a reused thread-local buffer
+        thread_local! {
+            static TEXT: RefCell<String> = RefCell::new(String::with_capacity(200));
+        }
+        TEXT.with(|tl_text| match tl_text.try_borrow_mut() {
+            Ok(mut text) => {
+                text.clear();
+                write!(text, "{}", record.args()).ok();
+                accepts(&text)
+            }
+            // recursive logging, e.g. from a Display implementation
+            Err(_) => accepts(&record.args().to_string()),
+        })
+    }
+
     /// Returns a `LogSpecification` where all traces are switched off.
     #[must_use]
     pub fn off() -> Self {
@@ -123,89 +383,86 @@ impl LogSpecification {
         let mut parse_errs = String::new();
         let mut dirs = Vec::<ModuleFilter>::new();
 
-        let mut parts = spec.split('/');
-        let mods = parts.next();
+        // module names cannot contain a '/', so the first one ends the module filters
+        let mods = spec.find('/').map_or(spec, |idx| &spec[..idx]);
         #[cfg(feature = "textfilter")]
-        let filter = parts.next();
-        if parts.next().is_some() {
-            push_err(
-                &format!("invalid log spec '{}' (too many '/'s), ignoring it", spec),
-                &mut parse_errs,
-            );
-            return parse_err(parse_errs, Self::off());
-        }
-        if let Some(m) = mods {
-            for s in m.split(',') {
-                let s = s.trim();
-                if s.is_empty() {
-                    continue;
+        let o_filters = spec.find('/').map(|idx| &spec[idx + 1..]);
+        for s in mods.split(',') {
+            let s = s.trim();
+            if s.is_empty() {
+                continue;
+            }
+            let mut parts = s.split('=');
+            let (log_level, name) = match (
+                parts.next().map(str::trim),
+                parts.next().map(str::trim),
+                parts.next(),
+            ) {
+                (Some(part_0), part_1, _) if part_0.starts_with('-') => {
+                    match parse_exclusion(s, part_0, part_1.is_some(), &mut parse_errs) {
+                        Some(excluded) => (LevelFilter::Off, Some(excluded)),
+                        None => continue,
+                    }
                 }
-                let mut parts = s.split('=');
-                let (log_level, name) = match (
-                    parts.next().map(str::trim),
-                    parts.next().map(str::trim),
-                    parts.next(),
-                ) {
-                    (Some(part_0), None, None) => {
-                        if contains_whitespace(part_0, &mut parse_errs) {
-                            continue;
-                        }
-                        // if the single argument is a log-level string or number,
-                        // treat that as a global fallback setting
-                        match parse_level_filter(part_0.trim()) {
-                            Ok(num) => (num, None),
-                            Err(_) => (LevelFilter::max(), Some(part_0)),
-                        }
+
+                (Some(part_0), None, None) => {
+                    if contains_whitespace(part_0, &mut parse_errs) {
+                        continue;
                     }
+                    // if the single argument is a log-level string or number,
+                    // treat that as a global fallback setting
+                    match parse_level_filter(part_0.trim()) {
+                        Ok(num) => (num, None),
+                        Err(_) => (LevelFilter::max(), Some(part_0)),
+                    }
+                }
 
-                    (Some(part_0), Some(""), None) => {
-                        if contains_whitespace(part_0, &mut parse_errs) {
-                            continue;
-                        }
-                        (LevelFilter::max(), Some(part_0))
+                (Some(part_0), Some(""), None) => {
+                    if contains_whitespace(part_0, &mut parse_errs) {
+                        continue;
                     }
+                    (LevelFilter::max(), Some(part_0))
+                }
 
-                    (Some(part_0), Some(part_1), None) => {
-                        if contains_whitespace(part_0, &mut parse_errs) {
+                (Some(part_0), Some(part_1), None) => {
+                    if contains_whitespace(part_0, &mut parse_errs) {
+                        continue;
+                    }
+                    match parse_level_filter(part_1.trim()) {
+                        Ok(num) => (num, Some(part_0.trim())),
+                        Err(e) => {
+                            push_err(&e.to_string(), &mut parse_errs);
                             continue;
                         }
-                        match parse_level_filter(part_1.trim()) {
-                            Ok(num) => (num, Some(part_0.trim())),
-                            Err(e) => {
-                                push_err(&e.to_string(), &mut parse_errs);
-                                continue;
-                            }
-                        }
-                    }
-                    _ => {
-                        push_err(
-                            &format!("invalid part in log spec '{}', ignoring it", s),
-                            &mut parse_errs,
-                        );
-                        continue;
                     }
-                };
-                dirs.push(ModuleFilter {
-                    module_name: name.map(ToString::to_string),
-                    level_filter: log_level,
-                });
+                }
+                _ => {
+                    push_err(
+                        &format!("invalid part in log spec '{}', ignoring it", s),
+                        &mut parse_errs,
+                    );
+                    continue;
+                }
+            };
+            if name.map_or(false, |name| invalid_glob(name, &mut parse_errs)) {
+                continue;
             }
+            dirs.push(ModuleFilter {
+                module_name: name.map(ToString::to_string),
+                level_filter: log_level,
+            });
         }
 
+        #[allow(unused_mut)]
+        let mut logspec = Self::from_module_filters(dirs, &mut parse_errs);
         #[cfg(feature = "textfilter")]
-        let textfilter = filter.and_then(|filter| match Regex::new(filter) {
-            Ok(re) => Some(Box::new(re)),
-            Err(e) => {
-                push_err(&format!("invalid regex filter - {}", e), &mut parse_errs);
-                None
+        {
+            if let Some(filters) = o_filters {
+                let (o_textfilter, module_textfilters) =
+                    parse_text_filters(filters, &mut parse_errs);
+                logspec.set_textfilters(o_textfilter, module_textfilters);
             }
-        });
-
-        let logspec = Self {
-            module_filters: dirs.level_sort(),
-            #[cfg(feature = "textfilter")]
-            textfilter,
-        };
+        }
 
         if parse_errs.is_empty() {
             Ok(logspec)
@@ -240,23 +497,67 @@ impl LogSpecification {
             .or_else(|_| Self::parse(given_spec.as_ref()))
     }
 
+    /// Returns a log specification based on the value of the environment variable `RUST_LOG`,
+    /// if it exists and can be parsed, or on the given compile-time specification.
+    #[must_use]
+    pub fn env_or_const(const_spec: &ConstLogSpec) -> Self {
+        env::var("RUST_LOG")
+            .ok()
+            .and_then(|value| Self::parse(&value).ok())
+            .unwrap_or_else(|| const_spec.to_log_specification())
+    }
+
     /// Reads a log specification from an appropriate toml document.
     ///
-    /// This method is only avaible with feature `specfile`.
+    /// This method is only available with feature `specfile`.
     ///
     /// # Errors
     ///
     /// `FlexiLoggerError::Parse` if the input is malformed.
     #[cfg(feature = "specfile_without_notification")]
     pub fn from_toml(s: &str) -> Result<Self, FlexiLoggerError> {
-        #[derive(Clone, Debug, serde_derive::Deserialize)]
-        struct LogSpecFileFormat {
-            pub global_level: Option<String>,
-            pub global_pattern: Option<String>,
-            pub modules: Option<std::collections::BTreeMap<String, String>>,
-        }
+        Self::from_file_format(LogSpecFileFormat::from_toml(s)?)
+    }
+
+    /// Reads a log specification from an appropriate json document.
+    ///
+    /// The document is an object with the same (optional) members as the toml document
+    /// (see [`to_toml`](crate::LogSpecification::to_toml)), e.g.
+    /// `{"global_level": "info", "modules": {"mod1::mod2": "debug"}}`.
+    ///
+    /// This method is only available with feature `specfile`.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::SpecfileJson` if the input is no valid json,
+    /// `FlexiLoggerError::Parse` if the input is malformed.
+    #[cfg(feature = "specfile_without_notification")]
+    pub fn from_json(s: &str) -> Result<Self, FlexiLoggerError> {
+        Self::from_file_format(LogSpecFileFormat::from_json(s)?)
+    }
+
+    /// Reads a log specification from an appropriate yaml document.
+    ///
+    /// The document is a mapping with the same (optional) keys as the toml document
+    /// (see [`to_toml`](crate::LogSpecification::to_toml)).
+    ///
+    /// This method is only available with feature `specfile`.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::SpecfileYaml` if the input is no valid yaml,
+    /// `FlexiLoggerError::Parse` if the input is malformed.
+    #[cfg(feature = "specfile_without_notification")]
+    pub fn from_yaml(s: &str) -> Result<Self, FlexiLoggerError> {
+        Self::from_file_format(LogSpecFileFormat::from_yaml(s)?)
+    }
 
-        let logspec_ff: LogSpecFileFormat = toml::from_str(s)?;
+    // Ignores the writer settings of the specfile.
+    #[cfg(feature = "serde")]
+    pub(crate) fn from_file_format(
+        logspec_ff: LogSpecFileFormat,
+    ) -> Result<Self, FlexiLoggerError> {
+        #[allow(unused_mut)]
         let mut parse_errs = String::new();
         let mut module_filters = Vec::<ModuleFilter>::new();
 
@@ -277,8 +578,8 @@ impl LogSpecification {
         #[cfg(feature = "textfilter")]
         let textfilter = match logspec_ff.global_pattern {
             None => None,
-            Some(s) => match Regex::new(&s) {
-                Ok(re) => Some(Box::new(re)),
+            Some(s) => match TextFilter::parse(&s) {
+                Ok(tf) => Some(tf),
                 Err(e) => {
                     push_err(&format!("invalid regex filter - {}", e), &mut parse_errs);
                     None
@@ -286,11 +587,25 @@ impl LogSpecification {
             },
         };
 
-        let logspec = Self {
-            module_filters: module_filters.level_sort(),
-            #[cfg(feature = "textfilter")]
-            textfilter,
-        };
+        #[cfg(feature = "textfilter")]
+        let mut module_textfilters = HashMap::new();
+        #[cfg(feature = "textfilter")]
+        for (k, v) in logspec_ff.module_patterns.unwrap_or_default() {
+            match TextFilter::parse(&v) {
+                Ok(tf) => {
+                    module_textfilters.insert(k, tf);
+                }
+                Err(e) => push_err(
+                    &format!("invalid regex filter for module {} - {}", k, e),
+                    &mut parse_errs,
+                ),
+            }
+        }
+
+        #[allow(unused_mut)]
+        let mut logspec = Self::from_module_filters(module_filters, &mut parse_errs);
+        #[cfg(feature = "textfilter")]
+        logspec.set_textfilters(textfilter, module_textfilters);
         if parse_errs.is_empty() {
             Ok(logspec)
         } else {
@@ -300,7 +615,7 @@ impl LogSpecification {
 
     /// Serializes itself in toml format.
     ///
-    /// This method is only avaible with feature `specfile`.
+    /// This method is only available with feature `specfile`.
     ///
     /// # Errors
     ///
@@ -312,12 +627,15 @@ impl LogSpecification {
         if last.is_some() && last.as_ref().unwrap().module_name.is_none() {
             w.write_all(
                 format!(
-                    "global_level = '{}'\n",
-                    last.as_ref()
-                        .unwrap()
-                        .level_filter
-                        .to_string()
-                        .to_lowercase()
+                    "global_level = {}\n",
+                    toml_string(
+                        &last
+                            .as_ref()
+                            .unwrap()
+                            .level_filter
+                            .to_string()
+                            .to_lowercase()
+                    )
                 )
                 .as_bytes(),
             )?;
@@ -328,6 +646,23 @@ impl LogSpecification {
         w.write_all(
             b"\n### Optional: specify a regular expression to suppress all messages that don't match\n",
         )?;
+        w.write_all(
+            b"### (a leading '!' negates it, then all messages that match are suppressed)\n",
+        )?;
+        #[cfg(feature = "textfilter")]
+        match self.textfilter {
+            Some(ref textfilter) => {
+                w.write_all(
+                    format!(
+                        "global_pattern = {}\n",
+                        toml_string(&textfilter.to_string())
+                    )
+                    .as_bytes(),
+                )?;
+            }
+            None => w.write_all(b"#global_pattern = 'foo'\n")?,
+        }
+        #[cfg(not(feature = "textfilter"))]
         w.write_all(b"#global_pattern = 'foo'\n")?;
 
         w.write_all(
@@ -343,17 +678,94 @@ impl LogSpecification {
             if mf.module_name.is_some() {
                 w.write_all(
                     format!(
-                        "'{}' = '{}'\n",
-                        mf.module_name.as_ref().unwrap(),
-                        mf.level_filter.to_string().to_lowercase()
+                        "{} = {}\n",
+                        toml_string(mf.module_name.as_ref().unwrap()),
+                        toml_string(&mf.level_filter.to_string().to_lowercase())
                     )
                     .as_bytes(),
                 )?;
             }
         }
+
+        #[cfg(feature = "textfilter")]
+        {
+            w.write_all(
+                b"\n### Optional: regular expressions that are only applied to specific modules\n",
+            )?;
+            w.write_all(b"[module_patterns]\n")?;
+            if self.module_textfilters.is_empty() {
+                w.write_all(b"#'mod1' = 'foo'\n")?;
+                w.write_all(b"#'mod2' = '!bar'\n")?;
+            }
+            for (module_name, textfilter) in &self.module_textfilters {
+                w.write_all(
+                    format!(
+                        "{} = {}\n",
+                        toml_string(module_name),
+                        toml_string(&textfilter.to_string())
+                    )
+                    .as_bytes(),
+                )?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Serializes itself in json format.
+    ///
+    /// This method is only available with feature `specfile`.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::SpecfileJson` if writing fails.
+    #[cfg(feature = "specfile_without_notification")]
+    pub fn to_json(&self, w: &mut dyn std::io::Write) -> Result<(), FlexiLoggerError> {
+        serde_json::to_writer_pretty(&mut *w, &self.to_file_format())?;
+        w.write_all(b"\n")?;
+        Ok(())
+    }
+
+    /// Serializes itself in yaml format.
+    ///
+    /// This method is only available with feature `specfile`.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::SpecfileYaml` if writing fails.
+    #[cfg(feature = "specfile_without_notification")]
+    pub fn to_yaml(&self, w: &mut dyn std::io::Write) -> Result<(), FlexiLoggerError> {
+        serde_yaml::to_writer(w, &self.to_file_format())?;
         Ok(())
     }
 
+    #[cfg(feature = "serde")]
+    fn to_file_format(&self) -> LogSpecFileFormat {
+        let mut logspec_ff = LogSpecFileFormat::default();
+        for mf in &self.module_filters {
+            let level = mf.level_filter.to_string().to_lowercase();
+            match mf.module_name {
+                None => logspec_ff.global_level = Some(level),
+                Some(ref module_name) => {
+                    logspec_ff
+                        .modules
+                        .get_or_insert_with(Default::default)
+                        .insert(module_name.clone(), level);
+                }
+            }
+        }
+        #[cfg(feature = "textfilter")]
+        {
+            logspec_ff.global_pattern = self.textfilter.as_ref().map(ToString::to_string);
+            for (module_name, textfilter) in &self.module_textfilters {
+                logspec_ff
+                    .module_patterns
+                    .get_or_insert_with(Default::default)
+                    .insert(module_name.clone(), textfilter.to_string());
+            }
+        }
+        logspec_ff
+    }
+
     /// Creates a `LogSpecBuilder`, setting the default log level.
     #[must_use]
     pub fn default(level_filter: LevelFilter) -> LogSpecBuilder {
@@ -371,14 +783,255 @@ impl LogSpecification {
 
     /// Provides a reference to the text filter.
     ///
-    /// This method is only avaible with feature `textfilter`, which is a default feature.
+    /// This method is only available with feature `textfilter`, which is a default feature.
     #[cfg(feature = "textfilter")]
     #[must_use]
     pub fn text_filter(&self) -> Option<&Regex> {
+        self.textfilter.as_ref().map(|textfilter| &textfilter.regex)
+    }
+
+    /// Returns true if the text filter is negated, i.e., if log lines that match it are suppressed.
+    ///
+    /// This method is only available with feature `textfilter`, which is a default feature.
+    #[cfg(feature = "textfilter")]
+    #[must_use]
+    pub fn is_text_filter_negated(&self) -> bool {
         self.textfilter
             .as_ref()
-            .map(|boxed_re| std::ops::Deref::deref(boxed_re))
+            .map_or(false, |textfilter| textfilter.negated)
+    }
+
+    /// Provides the text filter that is applied to the specified module, if any,
+    /// and whether it is negated.
+    ///
+    /// This method is only available with feature `textfilter`, which is a default feature.
+    #[cfg(feature = "textfilter")]
+    #[must_use]
+    pub fn module_text_filter(&self, module_name: &str) -> Option<(&Regex, bool)> {
+        self.module_textfilters
+            .iter()
+            .find(|(name, _)| name == module_name)
+            .map(|(_, textfilter)| (&textfilter.regex, textfilter.negated))
+    }
+}
+
+/// Renders the log specification in the syntax that is accepted by
+/// [`parse()`](crate::LogSpecification::parse), e.g. `info, crate1::mod1=trace/foo`,
+/// including the text filters for specific modules.
+///
+/// Parsing the output again (also with `FromStr`) gives a specification that is equal
+/// to the original one.
+impl std::fmt::Display for LogSpecification {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let mut parts = Vec::<String>::new();
+        // the default module filter has no name, and is thus sorted to the end
+        if let Some(level_filter) = self
+            .module_filters
+            .iter()
+            .find(|mf| mf.module_name.is_none())
+            .map(|mf| mf.level_filter)
+        {
+            parts.push(level_filter.to_string().to_lowercase());
+        }
+        for mf in &self.module_filters {
+            if let Some(ref module_name) = mf.module_name {
+                parts.push(format!(
+                    "{}={}",
+                    module_name,
+                    mf.level_filter.to_string().to_lowercase()
+                ));
+            }
+        }
+        if parts.is_empty() {
+            parts.push(LevelFilter::Off.to_string().to_lowercase());
+        }
+        write!(f, "{}", parts.join(", "))?;
+        #[cfg(feature = "textfilter")]
+        {
+            let mut filters = Vec::<String>::new();
+            if self.textfilter.is_some() || !self.module_textfilters.is_empty() {
+                filters.push(
+                    self.textfilter
+                        .as_ref()
+                        .map_or_else(String::new, ToString::to_string),
+                );
+            }
+            for (module_name, textfilter) in &self.module_textfilters {
+                filters.push(format!("{}={}", module_name, textfilter));
+            }
+            let last = filters.len();
+            for (idx, filter) in filters.iter().enumerate() {
+                write!(f, "/{}", escape_slashes(filter, idx + 1 < last))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Parses the syntax that is described for [`LogSpecification`],
+/// like [`parse()`](crate::LogSpecification::parse).
+impl std::str::FromStr for LogSpecification {
+    type Err = FlexiLoggerError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+/// Two specifications are equal if they have the same module filters and the same text filters.
+impl PartialEq for LogSpecification {
+    fn eq(&self, other: &Self) -> bool {
+        #[cfg(feature = "textfilter")]
+        {
+            let same_text = |tf1: &TextFilter, tf2: &TextFilter| {
+                tf1.negated == tf2.negated && tf1.regex.as_str() == tf2.regex.as_str()
+            };
+            let same_textfilter = match (&self.textfilter, &other.textfilter) {
+                (None, None) => true,
+                (Some(tf1), Some(tf2)) => same_text(tf1, tf2),
+                _ => false,
+            };
+            if !same_textfilter
+                || self.module_textfilters.len() != other.module_textfilters.len()
+                || !self.module_textfilters.iter().all(|(module_name, tf1)| {
+                    other
+                        .module_textfilters
+                        .iter()
+                        .any(|(name, tf2)| name == module_name && same_text(tf1, tf2))
+                })
+            {
+                return false;
+            }
+        }
+        // the module filters are sorted by precedence
+        self.module_filters == other.module_filters
+    }
+}
+
+/// Serializes the specification losslessly, including all text filters,
+/// as an object with the same members as the json specfile
+/// (see [`to_json`](crate::LogSpecification::to_json)).
+///
+/// This implementation is only available with feature `serde`,
+/// which is also implied by `specfile` and `config_file`.
+#[cfg(feature = "serde")]
+impl serde::Serialize for LogSpecification {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serde::Serialize::serialize(&self.to_file_format(), serializer)
+    }
+}
+
+/// Deserializes the format that is produced by the `Serialize` implementation.
+///
+/// This implementation is only available with feature `serde`,
+/// which is also implied by `specfile` and `config_file`.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for LogSpecification {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Self::from_file_format(<LogSpecFileFormat as serde::Deserialize>::deserialize(
+            deserializer,
+        )?)
+        .map_err(serde::de::Error::custom)
+    }
+}
+
+// Renders the string as toml key or value, with the quotes and escapes that it needs.
+#[cfg(feature = "specfile_without_notification")]
+pub(crate) fn toml_string(s: &str) -> String {
+    toml::Value::String(s.to_string()).to_string()
+}
+
+// Parses the text filters of a spec string: the global one, which can be empty,
+// and those for specific modules.
+#[cfg(feature = "textfilter")]
+fn parse_text_filters(
+    filters: &str,
+    parse_errs: &mut String,
+) -> (Option<TextFilter>, HashMap<String, TextFilter>) {
+    let mut segments = split_text_filters(filters).into_iter();
+    let o_textfilter = segments
+        .next()
+        .filter(|segment| !segment.is_empty())
+        .and_then(|segment| parse_text_filter(&segment, parse_errs));
+    let mut module_textfilters = HashMap::new();
+    for segment in segments {
+        let mut parts = segment.splitn(2, '=');
+        match (parts.next().map(str::trim), parts.next()) {
+            (Some(module_name), Some(text)) if !module_name.is_empty() => {
+                if contains_whitespace(module_name, parse_errs) {
+                    continue;
+                }
+                if let Some(textfilter) = parse_text_filter(text, parse_errs) {
+                    module_textfilters.insert(module_name.to_string(), textfilter);
+                }
+            }
+            _ => push_err(
+                &format!("invalid module text filter '{}', ignoring it", segment),
+                parse_errs,
+            ),
+        }
+    }
+    (o_textfilter, module_textfilters)
+}
+
+#[cfg(feature = "textfilter")]
+fn parse_text_filter(text: &str, parse_errs: &mut String) -> Option<TextFilter> {
+    match TextFilter::parse(text) {
+        Ok(textfilter) => Some(textfilter),
+        Err(e) => {
+            push_err(&format!("invalid regex filter - {}", e), parse_errs);
+            None
+        }
+    }
+}
+
+// Splits the text filters of a spec string at the separating slashes.
+// An odd number of backslashes in front of a slash makes the slash a part of the regex,
+// an even number makes it a separator; in both cases, the backslashes escape each other
+// in pairs. Other backslashes are kept as they are.
+#[cfg(feature = "textfilter")]
+fn split_text_filters(filters: &str) -> Vec<String> {
+    let mut segments = Vec::new();
+    let mut segment = String::new();
+    let mut backslashes = 0;
+    for c in filters.chars() {
+        if c == '\\' {
+            backslashes += 1;
+            continue;
+        }
+        if c == '/' {
+            segment.push_str(&"\\".repeat(backslashes / 2));
+            if backslashes % 2 == 1 {
+                segment.push('/');
+            } else {
+                segments.push(std::mem::take(&mut segment));
+            }
+        } else {
+            segment.push_str(&"\\".repeat(backslashes));
+            segment.push(c);
+        }
+        backslashes = 0;
+    }
+    segment.push_str(&"\\".repeat(backslashes));
+    segments.push(segment);
+    segments
+}
+
+// The inverse of `split_text_filters` for a single text filter.
+#[cfg(feature = "textfilter")]
+fn escape_slashes(filter: &str, followed_by_separator: bool) -> String {
+    let mut escaped = String::with_capacity(filter.len());
+    let mut backslashes = 0;
+    for c in filter.chars() {
+        if c == '/' {
+            escaped.push_str(&"\\".repeat(backslashes + 1));
+        }
+        backslashes = if c == '\\' { backslashes + 1 } else { 0 };
+        escaped.push(c);
     }
+    if followed_by_separator {
+        escaped.push_str(&"\\".repeat(backslashes));
+    }
+    escaped
 }
 
 fn push_err(s: &str, parse_errs: &mut String) {
@@ -410,6 +1063,68 @@ fn parse_level_filter<S: AsRef<str>>(s: S) -> Result<LevelFilter, FlexiLoggerErr
     }
 }
 
+fn is_glob(module_name: &str) -> bool {
+    module_name.contains(&['*', '?', '['][..])
+}
+
+// A glob matches a module if it matches the full module path, or one of its parent paths.
+fn glob_matches(glob: &Pattern, writing_module: &str) -> bool {
+    glob.matches(writing_module)
+        || writing_module
+            .match_indices("::")
+            .any(|(idx, _)| glob.matches(&writing_module[..idx]))
+}
+
+// An exclusion switches the module off.
+fn parse_exclusion<'a>(
+    s: &str,
+    part_0: &'a str,
+    with_level: bool,
+    parse_errs: &mut String,
+) -> Option<&'a str> {
+    if with_level {
+        push_err(
+            &format!(
+                "exclusion in log spec '{}' must not have a log level, ignoring it",
+                s
+            ),
+            parse_errs,
+        );
+        return None;
+    }
+    let excluded = part_0[1..].trim();
+    if excluded.is_empty() {
+        push_err(
+            &format!("invalid exclusion in log spec '{}', ignoring it", s),
+            parse_errs,
+        );
+        return None;
+    }
+    if contains_whitespace(excluded, parse_errs) {
+        return None;
+    }
+    Some(excluded)
+}
+
+fn invalid_glob(module_name: &str, parse_errs: &mut String) -> bool {
+    if !is_glob(module_name) {
+        return false;
+    }
+    match Pattern::new(module_name) {
+        Ok(_) => false,
+        Err(e) => {
+            push_err(
+                &format!(
+                    "invalid module pattern '{}' - {}, ignoring it",
+                    module_name, e
+                ),
+                parse_errs,
+            );
+            true
+        }
+    }
+}
+
 fn contains_whitespace(s: &str, parse_errs: &mut String) -> bool {
     let result = s.chars().any(char::is_whitespace);
     if result {
@@ -424,6 +1139,66 @@ fn contains_whitespace(s: &str, parse_errs: &mut String) -> bool {
     result
 }
 
+/// A log specification that is defined at compile time.
+///
+/// Since it can be a `const` or a `static`, a default specification can be baked
+/// into the binary, and it is converted into a `LogSpecification` without any parsing
+/// at startup. This is useful e.g. for embedded programs.
+/// Glob patterns are supported as with
+/// [`LogSpecification::parse`](crate::LogSpecification::parse); module filters with an
+/// invalid pattern are ignored and reported to the [`ErrorChannel`](crate::ErrorChannel).
+///
+/// ## Example
+///
+/// ```rust
+/// use flexi_logger::{ConstLogSpec, LevelFilter, Logger};
+///
+/// const DEFAULT_SPEC: ConstLogSpec = ConstLogSpec::new(
+///     LevelFilter::Info,
+///     &[("my_crate::db", LevelFilter::Debug), ("noisy_dep", LevelFilter::Off)],
+/// );
+///
+/// // RUST_LOG, if set, wins
+/// let logger = Logger::with_env_or_const(&DEFAULT_SPEC);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct ConstLogSpec {
+    default: LevelFilter,
+    modules: &'static [(&'static str, LevelFilter)],
+}
+impl ConstLogSpec {
+    /// Creates a specification with the given default level and the given
+    /// level filters for specific modules.
+    #[must_use]
+    pub const fn new(
+        default: LevelFilter,
+        modules: &'static [(&'static str, LevelFilter)],
+    ) -> Self {
+        Self { default, modules }
+    }
+
+    /// Converts this specification into a `LogSpecification`.
+    #[must_use]
+    pub fn to_log_specification(&self) -> LogSpecification {
+        let mut module_filters = vec![ModuleFilter {
+            module_name: None,
+            level_filter: self.default,
+        }];
+        module_filters.extend(self.modules.iter().map(|(module_name, level_filter)| {
+            ModuleFilter {
+                module_name: Some((*module_name).to_string()),
+                level_filter: *level_filter,
+            }
+        }));
+        LogSpecification::from_module_filters_or_report(module_filters)
+    }
+}
+impl From<&ConstLogSpec> for LogSpecification {
+    fn from(const_spec: &ConstLogSpec) -> Self {
+        const_spec.to_log_specification()
+    }
+}
+
 #[allow(clippy::needless_doctest_main)]
 /// Builder for `LogSpecification`.
 ///
@@ -462,6 +1237,10 @@ fn contains_whitespace(s: &str, parse_errs: &mut String) -> bool {
 #[derive(Clone, Debug, Default)]
 pub struct LogSpecBuilder {
     module_filters: HashMap<Option<String>, LevelFilter>,
+    #[cfg(feature = "textfilter")]
+    textfilter: Option<TextFilter>,
+    #[cfg(feature = "textfilter")]
+    module_textfilters: HashMap<String, TextFilter>,
 }
 
 impl LogSpecBuilder {
@@ -472,6 +1251,10 @@ impl LogSpecBuilder {
         modfilmap.insert(None, LevelFilter::Off);
         Self {
             module_filters: modfilmap,
+            #[cfg(feature = "textfilter")]
+            textfilter: None,
+            #[cfg(feature = "textfilter")]
+            module_textfilters: HashMap::new(),
         }
     }
 
@@ -484,6 +1267,10 @@ impl LogSpecBuilder {
         }
         Self {
             module_filters: modfilmap,
+            #[cfg(feature = "textfilter")]
+            textfilter: None,
+            #[cfg(feature = "textfilter")]
+            module_textfilters: HashMap::new(),
         }
     }
 
@@ -500,7 +1287,12 @@ impl LogSpecBuilder {
         self
     }
 
-    /// Adds a log level filter, or updates the log level filter, for a module.
+    /// Switches off all logging for a module, like an exclusion `-module` in the spec String.
+    pub fn exclude<M: AsRef<str>>(&mut self, module_name: M) -> &mut Self {
+        self.module(module_name, LevelFilter::Off)
+    }
+
+    /// Removes the log level filter for a module.
     pub fn remove<M: AsRef<str>>(&mut self, module_name: M) -> &mut Self {
         self.module_filters
             .remove(&Some(module_name.as_ref().to_owned()));
@@ -516,46 +1308,174 @@ impl LogSpecBuilder {
         self
     }
 
-    /// Creates a log specification without text filter.
+    /// Sets a text filter that is applied to all modules: only log lines that match
+    /// the regex are written.
+    ///
+    /// This method is only available with feature `textfilter`, which is a default feature.
+    #[cfg(feature = "textfilter")]
+    pub fn text_filter(&mut self, regex: Regex) -> &mut Self {
+        self.textfilter = Some(TextFilter {
+            regex,
+            negated: false,
+        });
+        self
+    }
+
+    /// Sets a negated text filter that is applied to all modules: log lines that match
+    /// the regex are suppressed.
+    ///
+    /// This method is only available with feature `textfilter`, which is a default feature.
+    #[cfg(feature = "textfilter")]
+    pub fn negated_text_filter(&mut self, regex: Regex) -> &mut Self {
+        self.textfilter = Some(TextFilter {
+            regex,
+            negated: true,
+        });
+        self
+    }
+
+    /// Removes the text filter that is applied to all modules.
+    ///
+    /// This method is only available with feature `textfilter`, which is a default feature.
+    #[cfg(feature = "textfilter")]
+    pub fn remove_text_filter(&mut self) -> &mut Self {
+        self.textfilter = None;
+        self
+    }
+
+    /// Adds or updates a text filter for a module: only log lines from this module
+    /// (and its submodules) that match the regex are written.
+    ///
+    /// This method is only available with feature `textfilter`, which is a default feature.
+    #[cfg(feature = "textfilter")]
+    pub fn module_text_filter<M: AsRef<str>>(&mut self, module_name: M, regex: Regex) -> &mut Self {
+        self.module_textfilters.insert(
+            module_name.as_ref().to_owned(),
+            TextFilter {
+                regex,
+                negated: false,
+            },
+        );
+        self
+    }
+
+    /// Adds or updates a negated text filter for a module: log lines from this module
+    /// (and its submodules) that match the regex are suppressed.
+    ///
+    /// This method is only available with feature `textfilter`, which is a default feature.
+    #[cfg(feature = "textfilter")]
+    pub fn negated_module_text_filter<M: AsRef<str>>(
+        &mut self,
+        module_name: M,
+        regex: Regex,
+    ) -> &mut Self {
+        self.module_textfilters.insert(
+            module_name.as_ref().to_owned(),
+            TextFilter {
+                regex,
+                negated: true,
+            },
+        );
+        self
+    }
+
+    /// Removes the text filter of a module.
+    ///
+    /// This method is only available with feature `textfilter`, which is a default feature.
+    #[cfg(feature = "textfilter")]
+    pub fn remove_module_text_filter<M: AsRef<str>>(&mut self, module_name: M) -> &mut Self {
+        self.module_textfilters.remove(module_name.as_ref());
+        self
+    }
+
+    /// Creates a log specification, with the text filters that were set on the builder.
     #[must_use]
     pub fn finalize(self) -> LogSpecification {
-        LogSpecification {
-            module_filters: self.module_filters.into_vec_module_filter(),
-            #[cfg(feature = "textfilter")]
-            textfilter: None,
-        }
+        #[allow(unused_mut)]
+        let mut logspec = LogSpecification::from_module_filters_or_report(
+            self.module_filters.into_vec_module_filter(),
+        );
+        #[cfg(feature = "textfilter")]
+        logspec.set_textfilters(self.textfilter, self.module_textfilters);
+        logspec
     }
 
-    /// Creates a log specification with text filter.
+    /// Creates a log specification with the given text filter,
+    /// which replaces the global text filter that might have been set on the builder.
     ///
-    /// This method is only avaible with feature `textfilter`, which is a default feature.
+    /// This method is only available with feature `textfilter`, which is a default feature.
     #[cfg(feature = "textfilter")]
     pub fn finalize_with_textfilter(self, tf: Regex) -> LogSpecification {
-        LogSpecification {
-            module_filters: self.module_filters.into_vec_module_filter(),
-            textfilter: Some(Box::new(tf)),
+        let mut logspec = LogSpecification::from_module_filters_or_report(
+            self.module_filters.into_vec_module_filter(),
+        );
+        logspec.set_textfilters(
+            Some(TextFilter {
+                regex: tf,
+                negated: false,
+            }),
+            self.module_textfilters,
+        );
+        logspec
+    }
+
+    /// Creates a log specification without being consumed, like
+    /// [`LogSpecBuilder::build`], but fails if a module name is an invalid glob pattern.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::Parse` if a module name is an invalid glob pattern.
+    pub fn try_build(&self) -> Result<LogSpecification, FlexiLoggerError> {
+        let mut parse_errs = String::new();
+        #[allow(unused_mut)]
+        let mut logspec = LogSpecification::from_module_filters(
+            self.module_filters.clone().into_vec_module_filter(),
+            &mut parse_errs,
+        );
+        #[cfg(feature = "textfilter")]
+        logspec.set_textfilters(self.textfilter.clone(), self.module_textfilters.clone());
+        if parse_errs.is_empty() {
+            Ok(logspec)
+        } else {
+            parse_err(parse_errs, logspec)
         }
     }
 
-    /// Creates a log specification without being consumed.
+    /// Creates a log specification without being consumed,
+    /// with the text filters that were set on the builder.
+    ///
+    /// Module filters whose name is an invalid glob pattern are ignored and reported
+    /// to the [`ErrorChannel`](crate::ErrorChannel); use [`LogSpecBuilder::try_build`]
+    /// to get an error instead.
     #[must_use]
     pub fn build(&self) -> LogSpecification {
-        LogSpecification {
-            module_filters: self.module_filters.clone().into_vec_module_filter(),
-            #[cfg(feature = "textfilter")]
-            textfilter: None,
-        }
+        #[allow(unused_mut)]
+        let mut logspec = LogSpecification::from_module_filters_or_report(
+            self.module_filters.clone().into_vec_module_filter(),
+        );
+        #[cfg(feature = "textfilter")]
+        logspec.set_textfilters(self.textfilter.clone(), self.module_textfilters.clone());
+        logspec
     }
 
-    /// Creates a log specification without being consumed, optionally with a text filter.
+    /// Creates a log specification without being consumed, optionally with a text filter,
+    /// which replaces the global text filter that might have been set on the builder.
     ///
-    /// This method is only avaible with feature `textfilter`, which is a default feature.
+    /// This method is only available with feature `textfilter`, which is a default feature.
     #[cfg(feature = "textfilter")]
+    #[must_use]
     pub fn build_with_textfilter(&self, tf: Option<Regex>) -> LogSpecification {
-        LogSpecification {
-            module_filters: self.module_filters.clone().into_vec_module_filter(),
-            textfilter: tf.map(Box::new),
-        }
+        let mut logspec = LogSpecification::from_module_filters_or_report(
+            self.module_filters.clone().into_vec_module_filter(),
+        );
+        logspec.set_textfilters(
+            tf.map(|regex| TextFilter {
+                regex,
+                negated: false,
+            }),
+            self.module_textfilters.clone(),
+        );
+        logspec
     }
 }
 
@@ -579,21 +1499,52 @@ trait LevelSort {
     fn level_sort(self) -> Vec<ModuleFilter>;
 }
 impl LevelSort for Vec<ModuleFilter> {
-    /// Sort the module filters by length of their name,
-    /// this allows a little more efficient lookup at runtime.
+    /// Sort the module filters by precedence, so that the first matching filter wins:
+    /// the most specific one first, then plain module names before glob patterns,
+    /// then in lexical order; the default filter comes last.
     fn level_sort(mut self) -> Vec<ModuleFilter> {
-        self.sort_by(|a, b| {
-            let a_len = a.module_name.as_ref().map_or(0, String::len);
-            let b_len = b.module_name.as_ref().map_or(0, String::len);
-            b_len.cmp(&a_len)
+        self.sort_by(|a, b| match (&a.module_name, &b.module_name) {
+            (Some(a_name), Some(b_name)) => specificity(b_name)
+                .cmp(&specificity(a_name))
+                .then_with(|| is_glob(a_name).cmp(&is_glob(b_name)))
+                .then_with(|| a_name.cmp(b_name)),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
         });
         self
     }
 }
 
+// The number of characters that a module name matches literally;
+// the wildcards of a glob pattern, including character classes like `[ab]`, don't count.
+fn specificity(module_name: &str) -> usize {
+    if !is_glob(module_name) {
+        return module_name.len();
+    }
+    let mut literal_len = 0;
+    let mut chars = module_name.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '*' | '?' => {}
+            '[' => {
+                // the first character of a class can be a ']', which does not close it
+                chars.next();
+                for c in &mut chars {
+                    if c == ']' {
+                        break;
+                    }
+                }
+            }
+            _ => literal_len += c.len_utf8(),
+        }
+    }
+    literal_len
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::LogSpecification;
+    use crate::{ConstLogSpec, LogSpecification};
     use log::{Level, LevelFilter};
 
     #[test]
@@ -622,6 +1573,84 @@ mod tests {
         assert!(spec.text_filter().is_none());
     }
 
+    #[test]
+    fn display() {
+        let spec =
+            LogSpecification::parse("crate1::mod1=error, warn, crate2=debug, -crate3").unwrap();
+        assert_eq!(
+            spec.to_string(),
+            "warn, crate1::mod1=error, crate2=debug, crate3=off"
+        );
+        assert_eq!(
+            LogSpecification::parse(&spec.to_string())
+                .unwrap()
+                .module_filters(),
+            spec.module_filters()
+        );
+        assert_eq!(LogSpecification::off().to_string(), "off");
+
+        #[cfg(feature = "textfilter")]
+        assert_eq!(
+            LogSpecification::parse("info/!heart.*beat")
+                .unwrap()
+                .to_string(),
+            "info/!heart.*beat"
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "textfilter")]
+    fn display_round_trip() {
+        for input in &[
+            "info/!heart.*beat",
+            "info/!heartbeat/crate1=^important/crate1::mod1=!noise",
+            r"warn, crate1=debug//crate2=\!bang",
+            r"info/a\\\\/b=c",
+        ] {
+            let spec = LogSpecification::parse(input).unwrap();
+            assert_eq!(spec.to_string().parse::<LogSpecification>().unwrap(), spec);
+        }
+
+        let spec = LogSpecification::parse(r"info/a\\\\/b=c").unwrap();
+        assert_eq!(spec.text_filter().unwrap().as_str(), r"a\\");
+        assert_eq!(spec.module_text_filter("b").unwrap().0.as_str(), "c");
+        let spec = LogSpecification::parse(r"warn//crate2=\!bang").unwrap();
+        assert!(spec.text_filter().is_none());
+        assert_eq!(
+            spec.module_text_filter("crate2").unwrap().0.as_str(),
+            "!bang"
+        );
+
+        let mut builder = crate::LogSpecBuilder::new();
+        builder.default(LevelFilter::Info);
+        builder.text_filter(regex::Regex::new("GET /api/").unwrap());
+        builder.negated_module_text_filter("crate1", regex::Regex::new(r"\\/").unwrap());
+        builder.module_text_filter("crate1::mod1", regex::Regex::new("!important").unwrap());
+        builder.module_text_filter("crate2", regex::Regex::new(r"a\\").unwrap());
+        let spec = builder.build();
+        assert_eq!(spec.to_string().parse::<LogSpecification>().unwrap(), spec);
+    }
+
+    #[test]
+    fn const_spec() {
+        const SPEC: ConstLogSpec = ConstLogSpec::new(
+            LevelFilter::Warn,
+            &[
+                ("crate1::mod1", LevelFilter::Error),
+                ("crate2", LevelFilter::Debug),
+            ],
+        );
+        let spec = SPEC.to_log_specification();
+        assert_eq!(
+            spec.module_filters(),
+            LogSpecification::parse("warn, crate1::mod1=error, crate2=debug")
+                .unwrap()
+                .module_filters()
+        );
+        assert!(spec.enabled(Level::Debug, "crate2::mod3"));
+        assert!(!spec.enabled(Level::Warn, "crate1::mod1"));
+    }
+
     #[test]
     fn parse_logging_spec_invalid_crate() {
         // test parse_logging_spec with multiple = in specification
@@ -686,6 +1715,46 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(feature = "textfilter")]
+    fn parse_logging_spec_negated_filter() {
+        let spec = LogSpecification::parse("info/!heart.*beat").unwrap();
+        assert!(spec.is_text_filter_negated());
+        assert_eq!(spec.text_filter().unwrap().to_string(), "heart.*beat");
+    }
+
+    #[test]
+    #[cfg(feature = "textfilter")]
+    fn text_filters() {
+        let mut builder = crate::LogSpecBuilder::new();
+        builder.default(LevelFilter::Info);
+        builder.negated_text_filter(regex::Regex::new("heartbeat").unwrap());
+        builder.module_text_filter("crate1", regex::Regex::new("^important").unwrap());
+        builder.negated_module_text_filter("crate1::mod1", regex::Regex::new("noise").unwrap());
+        let spec = builder.build();
+
+        let check = |module: &str, text: &str| {
+            spec.text_enabled(
+                module,
+                &log::Record::builder()
+                    .args(format_args!("{}", text))
+                    .module_path(Some(module))
+                    .build(),
+            )
+        };
+        assert!(check("crate2", "some text"));
+        assert!(!check("crate2", "a heartbeat"));
+        assert!(check("crate1", "important text"));
+        assert!(!check("crate1", "unimportant text"));
+        assert!(!check("crate1", "important heartbeat"));
+        assert!(check("crate1::mod1", "unimportant text"));
+        assert!(!check("crate1::mod1", "important noise"));
+
+        assert!(spec.module_text_filter("crate1").is_some());
+        assert!(spec.module_text_filter("crate1::mod1").unwrap().1);
+        assert!(spec.module_text_filter("crate2").is_none());
+    }
+
     #[test]
     fn parse_logging_spec_invalid_crate_filter() {
         assert!(LogSpecification::parse("crate1::mod1=error=warn,crate2=debug/a.c").is_err());
@@ -861,6 +1930,101 @@ mod tests {
         assert_eq!(spec.module_filters()[4].level_filter, LevelFilter::Info);
     }
 
+    #[test]
+    fn match_glob() {
+        let spec = LogSpecification::parse("info, my_crate::*::db=trace, *engine*=debug").unwrap();
+        assert!(spec.enabled(Level::Trace, "my_crate::a::db"));
+        assert!(spec.enabled(Level::Trace, "my_crate::b::db::pool"));
+        assert!(!spec.enabled(Level::Trace, "my_crate::b::dbx"));
+        assert!(!spec.enabled(Level::Debug, "my_crate::db"));
+        assert!(spec.enabled(Level::Debug, "game::engine::render"));
+        assert!(spec.enabled(Level::Debug, "my_engine_2"));
+        assert!(!spec.enabled(Level::Trace, "game::engine::render"));
+        assert!(spec.enabled(Level::Info, "other"));
+        assert!(!spec.enabled(Level::Debug, "other"));
+    }
+
+    #[test]
+    fn match_glob_from_builder() {
+        let mut builder = crate::LogSpecBuilder::new();
+        builder.default(LevelFilter::Warn);
+        builder.module("crate?::mod_[ab]", LevelFilter::Trace);
+        let spec = builder.build();
+        assert!(spec.enabled(Level::Trace, "crate1::mod_a"));
+        assert!(spec.enabled(Level::Trace, "crate2::mod_b::sub"));
+        assert!(!spec.enabled(Level::Trace, "crate2::mod_c"));
+        assert!(spec.enabled(Level::Warn, "crate2::mod_c"));
+    }
+
+    #[test]
+    fn precedence() {
+        let spec1 = LogSpecification::parse("ab=debug, a*=off").unwrap();
+        let spec2 = LogSpecification::parse("a*=off, ab=debug").unwrap();
+        assert_eq!(spec1, spec2);
+        for spec in &[spec1, spec2] {
+            assert!(spec.enabled(Level::Debug, "ab"));
+            assert!(!spec.enabled(Level::Error, "ac"));
+        }
+
+        let spec = LogSpecification::parse("*::*::*=trace, crate1::mod1=warn").unwrap();
+        assert!(!spec.enabled(Level::Info, "crate1::mod1::mod2"));
+        assert!(spec.enabled(Level::Trace, "crate2::mod1::mod2"));
+
+        let spec = LogSpecification::parse("info, my_crate=warn, my_crate::*::db=trace").unwrap();
+        assert!(spec.enabled(Level::Trace, "my_crate::a::db"));
+        assert!(!spec.enabled(Level::Info, "my_crate::a"));
+
+        let spec = LogSpecification::parse("crate[12]=warn, crate?=error").unwrap();
+        assert!(!spec.enabled(Level::Warn, "crate1"));
+        assert!(spec.enabled(Level::Error, "crate1"));
+        assert_eq!(
+            spec.to_string(),
+            LogSpecification::parse("crate?=error, crate[12]=warn")
+                .unwrap()
+                .to_string()
+        );
+    }
+
+    #[test]
+    fn parse_invalid_glob() {
+        assert!(LogSpecification::parse("info, crate1::[mod=debug").is_err());
+
+        let mut builder = crate::LogSpecBuilder::new();
+        builder.default(LevelFilter::Info);
+        builder.module("crate1::[mod", LevelFilter::Debug);
+        assert!(builder.try_build().is_err());
+        // an invalid pattern does not fall back to a prefix match
+        let spec = builder.build();
+        assert_eq!(spec.module_filters().len(), 1);
+        assert!(!spec.enabled(Level::Debug, "crate1::[mod"));
+    }
+
+    #[test]
+    fn exclusions() {
+        let spec = LogSpecification::parse("info, noisy_dep=off, -another_dep::poller, -*chatty*")
+            .unwrap();
+        assert_eq!(spec.module_filters().len(), 4);
+        assert!(spec.enabled(Level::Info, "itsme"));
+        assert!(!spec.enabled(Level::Error, "noisy_dep::mod1"));
+        assert!(!spec.enabled(Level::Error, "another_dep::poller"));
+        assert!(!spec.enabled(Level::Error, "another_dep::poller::inner"));
+        assert!(spec.enabled(Level::Info, "another_dep::other"));
+        assert!(!spec.enabled(Level::Error, "some::chatty_mod"));
+
+        let mut builder = crate::LogSpecBuilder::new();
+        builder.default(LevelFilter::Info);
+        builder.exclude("another_dep::poller");
+        let spec2 = builder.build();
+        assert!(!spec2.enabled(Level::Error, "another_dep::poller"));
+        assert!(spec2.enabled(Level::Info, "another_dep::other"));
+    }
+
+    #[test]
+    fn invalid_exclusions() {
+        assert!(LogSpecification::parse("info, -").is_err());
+        assert!(LogSpecification::parse("info, -crate1=debug").is_err());
+    }
+
     #[test]
     fn zero_level() {
         let spec = LogSpecification::parse("info,crate1::mod1=off").unwrap();
@@ -913,6 +2077,121 @@ mod test_with_specfile {
              ",
             "info, mod1::mod2 = debug, mod3 = trace /Foo",
         );
+
+        compare_specs(
+            "global_level = 'info'\n\
+             global_pattern = '!Foo'\n\
+             \n\
+             [modules]\n\
+             'mod1::mod2' = 'debug'\n\
+             ",
+            "info, mod1::mod2 = debug /!Foo",
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "textfilter")]
+    fn specfile_quotes() {
+        let spec = LogSpecification::parse("info, mod1 = debug /it's \"quoted\"").unwrap();
+        let mut toml = Vec::new();
+        spec.to_toml(&mut toml).unwrap();
+        let spec2 = LogSpecification::from_toml(&String::from_utf8(toml).unwrap()).unwrap();
+        assert_eq!(spec2, spec);
+    }
+
+    #[test]
+    #[cfg(feature = "textfilter")]
+    fn specfile_module_patterns() {
+        let spec = LogSpecification::from_toml(
+            "global_level = 'info'\n\
+             [module_patterns]\n\
+             'mod1' = 'foo'\n\
+             'mod2' = '!bar'\n\
+             ",
+        )
+        .unwrap();
+        assert!(!spec.module_text_filter("mod1").unwrap().1);
+        assert!(spec.module_text_filter("mod2").unwrap().1);
+
+        let mut buf = Vec::<u8>::new();
+        spec.to_toml(&mut buf).unwrap();
+        let spec2 = LogSpecification::from_toml(&String::from_utf8(buf).unwrap()).unwrap();
+        assert_eq!(spec.module_filters, spec2.module_filters);
+        assert_eq!(
+            spec2.module_text_filter("mod2").unwrap().0.to_string(),
+            "bar"
+        );
+    }
+
+    #[test]
+    fn specfile_json_and_yaml() {
+        let spec_string = "info, mod1::mod2 = debug, mod3 = trace";
+        let ls_spec = LogSpecification::parse(spec_string).unwrap();
+
+        let ls_json = LogSpecification::from_json(
+            r#"{
+                "global_level": "info",
+                "modules": {"mod1::mod2": "debug", "mod3": "trace"}
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(ls_json.module_filters, ls_spec.module_filters);
+
+        let ls_yaml = LogSpecification::from_yaml(
+            "global_level: info\n\
+             modules:\n  \
+             mod1::mod2: debug\n  \
+             mod3: trace\n",
+        )
+        .unwrap();
+        assert_eq!(ls_yaml.module_filters, ls_spec.module_filters);
+
+        assert!(LogSpecification::from_json("{}")
+            .unwrap()
+            .module_filters
+            .is_empty());
+        assert!(LogSpecification::from_yaml("")
+            .unwrap()
+            .module_filters
+            .is_empty());
+        assert!(LogSpecification::from_json(r#"{"global_level": "foo"}"#).is_err());
+
+        let mut buf = Vec::<u8>::new();
+        ls_spec.to_json(&mut buf).unwrap();
+        let ls_json2 = LogSpecification::from_json(&String::from_utf8(buf).unwrap()).unwrap();
+        assert_eq!(ls_json2.module_filters, ls_spec.module_filters);
+
+        let mut buf = Vec::<u8>::new();
+        ls_spec.to_yaml(&mut buf).unwrap();
+        let ls_yaml2 = LogSpecification::from_yaml(&String::from_utf8(buf).unwrap()).unwrap();
+        assert_eq!(ls_yaml2.module_filters, ls_spec.module_filters);
+    }
+
+    #[cfg(all(feature = "specfile_without_notification", feature = "textfilter"))]
+    #[test]
+    fn serde_round_trip() {
+        let mut builder = LogSpecification::default(LevelFilter::Info);
+        builder
+            .module("mod1::mod2", LevelFilter::Debug)
+            .exclude("mod3")
+            .negated_text_filter(regex::Regex::new("heart.*beat").unwrap())
+            .module_text_filter("mod1", regex::Regex::new("foo").unwrap())
+            .negated_module_text_filter("mod1::mod2", regex::Regex::new("bar").unwrap());
+        let spec = builder.build();
+
+        let json = serde_json::to_string(&spec).unwrap();
+        let spec2: LogSpecification = serde_json::from_str(&json).unwrap();
+        assert_eq!(spec, spec2);
+
+        let yaml = serde_yaml::to_string(&spec).unwrap();
+        let spec3: LogSpecification = serde_yaml::from_str(&yaml).unwrap();
+        assert_eq!(spec, spec3);
+
+        // the string form loses only the module text filters
+        let spec4: LogSpecification = spec.to_string().parse().unwrap();
+        assert_eq!(spec4.module_filters(), spec.module_filters());
+        assert_eq!(spec4.to_string(), spec.to_string());
+        assert_ne!(spec4, spec);
     }
 
     #[cfg(feature = "specfile_without_notification")]
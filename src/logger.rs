@@ -1,20 +1,30 @@
-use crate::flexi_logger::FlexiLogger;
-use crate::formats::default_format;
+use crate::flexi_logger::{start_flusher, Backend, FlexiLogger};
+use crate::formats::{
+    default_format, ConsoleStyle, Fields, FormatSettings, ShortModulePath, StyleOverrides,
+};
 #[cfg(feature = "atty")]
-use crate::formats::{AdaptiveFormat, Stream};
-use crate::primary_writer::PrimaryWriter;
-use crate::writers::{FileLogWriter, FileLogWriterBuilder, LogWriter};
+use crate::formats::{AdaptiveFormat, ColorChoice, Stream};
+#[cfg(feature = "colors")]
+use crate::formats::{Palette, PaletteInput};
+use crate::primary_writer::{PrimaryWriter, StdErrWriter, StdOutWriter};
+use crate::writers::{
+    FileFooter, FileHeader, FileLogWriter, FileLogWriterBuilder, LogWriter, RotationEvent,
+};
 use crate::{
-    Cleanup, Criterion, FlexiLoggerError, FormatFunction, LogSpecification, LoggerHandle, Naming,
+    BrokenPipe, Cleanup, ConstLogSpec, Criterion, DiskFull, EffectiveConfig, ErrorChannel,
+    FlexiLoggerError, FormatFunction, LogSpecification, LoggerHandle, Naming, RecordMiddleware,
+    SyncPolicy, TimestampFormat, WriteMode,
 };
 
+#[cfg(feature = "specfile_without_notification")]
+use crate::log_specification::{toml_string, LogSpecFileFormat};
 #[cfg(feature = "specfile")]
 use notify::{watcher, DebouncedEvent, RecursiveMode, Watcher};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 #[cfg(feature = "specfile_without_notification")]
 use std::io::Read;
 use std::path::PathBuf;
-use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::atomic::AtomicBool;
 use std::sync::{Arc, RwLock};
 
 /// The entry-point for using `flexi_logger`.
@@ -54,15 +64,41 @@ pub struct Logger {
     log_target: LogTarget,
     duplicate_err: Duplicate,
     duplicate_out: Duplicate,
+    broken_pipe: BrokenPipe,
+    log_panics: bool,
+    o_self_diagnostics: Option<(log::Level, String)>,
+    #[cfg(unix)]
+    captured_streams: (bool, bool),
+    // the settings of the provided format functions, like the field toggles
+    format_settings: FormatSettings,
     format_for_file: FormatFunction,
     format_for_stderr: FormatFunction,
     format_for_stdout: FormatFunction,
     format_for_writer: FormatFunction,
+    // the names of the formats, for build_config()
+    format_names: FormatNames,
+    o_max_message_length: Option<usize>,
+    middlewares: Vec<Box<dyn RecordMiddleware>>,
+    writer_levels: HashMap<String, log::LevelFilter>,
+    error_channel: ErrorChannel,
+    // adaptive formats are resolved in build(), when the color choice is known
+    #[cfg(feature = "atty")]
+    o_adaptive_format_for_stderr: Option<AdaptiveFormat>,
+    #[cfg(feature = "atty")]
+    o_adaptive_format_for_stdout: Option<AdaptiveFormat>,
     #[cfg(feature = "colors")]
-    o_palette: Option<String>,
+    o_palette: Option<PaletteInput>,
     o_flush_wait: Option<std::time::Duration>,
+    o_write_mode_for_stderr: Option<WriteMode>,
+    o_write_mode_for_stdout: Option<WriteMode>,
+    o_style_overrides_for_stderr: Option<Arc<StyleOverrides>>,
+    o_style_overrides_for_stdout: Option<Arc<StyleOverrides>>,
+    #[cfg(feature = "specfile")]
+    specfile_debounce_delay: std::time::Duration,
+    use_env_overrides: bool,
     flwb: FileLogWriterBuilder,
     other_writers: HashMap<String, Box<dyn LogWriter>>,
+    fallthrough_writers: HashSet<String>,
 }
 
 /// Describes the default log target.
@@ -85,6 +121,11 @@ pub enum LogTarget {
     /// The default pattern for the filename is '\<program_name\>\_\<date\>\_\<time\>.\<suffix\>',
     ///  e.g. `myprog_2015-07-08_10-44-11.log`.
     File,
+    /// Log is written to files, as with `LogTarget::File`, but each thread writes into its
+    /// own file, which has the thread name as additional infix.
+    ///
+    /// See [`FileLogWriterBuilder::per_thread`](crate::writers::FileLogWriterBuilder::per_thread).
+    FilePerThread,
     /// Log is written to an alternative `LogWriter` implementation.
     ///
     Writer(Box<dyn LogWriter>),
@@ -137,6 +178,129 @@ impl Logger {
         Self::from_result(LogSpecification::env_or_parse(s))
     }
 
+    /// Creates a Logger that reads the `LogSpecification` from the environment variable `RUST_LOG`,
+    /// or takes the given compile-time specification, if `RUST_LOG` is not set.
+    ///
+    /// Other than with [`with_env_or_str`](crate::Logger::with_env_or_str),
+    /// the default specification is not parsed at startup; see [`ConstLogSpec`].
+    #[must_use]
+    pub fn with_env_or_const(const_spec: &ConstLogSpec) -> Self {
+        Self::with(LogSpecification::env_or_const(const_spec))
+    }
+
+    /// Creates a fully configured Logger from a configuration file,
+    /// so that logging can be configured by operators, without recompiling the program.
+    ///
+    /// The file format is chosen based on the file suffix, which must be `.toml`, `.json`,
+    /// `.yaml`, or `.yml`. A toml file can look like this:
+    ///
+    /// ```toml
+    /// ### Mandatory: the log specification
+    /// spec = "info, mod1::mod2=debug"
+    /// ### Optional: one of stderr (default), stdout, file, writer, file_and_writer, devnull
+    /// target = "file"
+    /// ### Optional: the writer factory for the targets writer and file_and_writer
+    /// #writer = "my_writer"
+    /// ### Optional: one of default, detailed, opt, with_thread, elapsed;
+    /// ### can be refined with format_for_files, format_for_stderr, etc
+    /// format = "detailed"
+    /// ### Optional: one of none, error, warn, info, debug, trace, all
+    /// duplicate_to_stderr = "warn"
+    /// #duplicate_to_stdout = "none"
+    /// #palette = "196;208;-;7;8"
+    /// ### Optional: additional writers, created with the writer factories of the same name
+    /// #writers = ["alert"]
+    ///
+    /// [file]
+    /// directory = "log_files"
+    /// #basename = "my_program"
+    /// #discriminant = "instance_1"
+    /// #suffix = "log"
+    /// #timestamp = false
+    /// #append = true
+    /// #create_symlink = "current_log"
+    /// #print_message = true
+    ///
+    /// [file.rotate]
+    /// ### one of { size = <bytes> }, { age = <age> }, { age_or_size = [<age>, <bytes>] },
+    /// ### with age being one of day, hour, minute, second
+    /// criterion = { size = 10_000_000 }
+    /// ### numbers or timestamps
+    /// naming = "numbers"
+    /// ### one of never, { keep_log_files = <n> },
+    /// ### or, only with feature compress, { keep_compressed_files = <n> },
+    /// ### { keep_log_and_compressed_files = [<n>, <m>] }
+    /// cleanup = { keep_log_files = 7 }
+    /// ```
+    ///
+    /// See [`LoggerConfig`](crate::LoggerConfig) for a description of all members.
+    ///
+    /// The returned Logger can be further adapted before it is started.
+    ///
+    /// This method is only available with feature `config_file`.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::ConfigFileIo` if the file cannot be read,
+    /// `FlexiLoggerError::ConfigFile` if the file content is invalid,
+    /// `FlexiLoggerError::SpecParse` if the log specification is malformed.
+    #[cfg(feature = "config_file")]
+    pub fn with_config_file<P: AsRef<std::path::Path>>(path: P) -> Result<Self, FlexiLoggerError> {
+        Self::with_config_file_and_factories(path, &[])
+    }
+
+    /// Like [`with_config_file`](crate::Logger::with_config_file),
+    /// but allows the configuration file to reference the given writer factories by name.
+    ///
+    /// This method is only available with feature `config_file`.
+    ///
+    /// # Errors
+    ///
+    /// See [`with_config_file`](crate::Logger::with_config_file);
+    /// additionally, `FlexiLoggerError::ConfigFile` is returned if the file references
+    /// a writer factory that is not given.
+    #[cfg(feature = "config_file")]
+    pub fn with_config_file_and_factories<P: AsRef<std::path::Path>>(
+        path: P,
+        factories: &[(&str, crate::WriterFactory)],
+    ) -> Result<Self, FlexiLoggerError> {
+        crate::config_file::logger_from_config_file(path.as_ref(), factories)
+    }
+
+    /// Creates a fully configured Logger from the given configuration,
+    /// which is typically deserialized as part of the application's configuration.
+    ///
+    /// The returned Logger can be further adapted before it is started.
+    ///
+    /// This method is only available with feature `config_file`.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::ConfigFile` if the configuration contains invalid values,
+    /// `FlexiLoggerError::SpecParse` if the log specification is malformed.
+    #[cfg(feature = "config_file")]
+    pub fn from_config(config: crate::LoggerConfig) -> Result<Self, FlexiLoggerError> {
+        Self::from_config_and_factories(config, &[])
+    }
+
+    /// Like [`from_config`](crate::Logger::from_config),
+    /// but allows the configuration to reference the given writer factories by name.
+    ///
+    /// This method is only available with feature `config_file`.
+    ///
+    /// # Errors
+    ///
+    /// See [`from_config`](crate::Logger::from_config);
+    /// additionally, `FlexiLoggerError::ConfigFile` is returned if the configuration references
+    /// a writer factory that is not given.
+    #[cfg(feature = "config_file")]
+    pub fn from_config_and_factories(
+        config: crate::LoggerConfig,
+        factories: &[(&str, crate::WriterFactory)],
+    ) -> Result<Self, FlexiLoggerError> {
+        crate::config_file::logger_from_config(config, factories)
+    }
+
     fn from_result(result: Result<LogSpecification, FlexiLoggerError>) -> Self {
         match result {
             Ok(logspec) => Self::from_spec_and_errs(logspec, None),
@@ -149,40 +313,151 @@ impl Logger {
         }
     }
 
-    fn from_spec_and_errs(spec: LogSpecification, parse_errs: Option<String>) -> Self {
+    // Applies the overrides that the given lookup finds;
+    // the lookup is injected so that the tests need not modify the process environment.
+    fn apply_env_overrides<F>(mut self, var: F) -> Result<Self, FlexiLoggerError>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        fn invalid(name: &str, value: &str) -> FlexiLoggerError {
+            FlexiLoggerError::EnvOverride(format!("{}={}", name, value))
+        }
+
+        if let Some(directory) = var("FLEXI_LOGGER_DIRECTORY") {
+            self.flwb = self.flwb.directory(directory);
+        }
+        if let Some(basename) = var("FLEXI_LOGGER_BASENAME") {
+            self.flwb = self.flwb.basename(basename);
+        }
+        if let Some(discriminant) = var("FLEXI_LOGGER_DISCRIMINANT") {
+            self.flwb = self.flwb.discriminant(discriminant);
+        }
+        if let Some(suffix) = var("FLEXI_LOGGER_SUFFIX") {
+            self.flwb = self.flwb.suffix(suffix);
+        }
+        if let Some(s) = var("FLEXI_LOGGER_ROTATE_SIZE") {
+            let size = s
+                .parse()
+                .map_err(|_| invalid("FLEXI_LOGGER_ROTATE_SIZE", &s))?;
+            self.flwb = self.flwb.rotate_over_size(size);
+        }
+        if let Some(s) = var("FLEXI_LOGGER_KEEP_LOG_FILES") {
+            let keep = s
+                .parse()
+                .map_err(|_| invalid("FLEXI_LOGGER_KEEP_LOG_FILES", &s))?;
+            self.flwb = self
+                .flwb
+                .rotation_cleanup(Cleanup::KeepLogFiles(keep))
+                .ok_or_else(|| invalid("FLEXI_LOGGER_KEEP_LOG_FILES (without rotation)", &s))?;
+        }
+        if let Some(s) = var("FLEXI_LOGGER_FORMAT") {
+            self = self
+                .named_format(&s)
+                .ok_or_else(|| invalid("FLEXI_LOGGER_FORMAT", &s))?;
+        }
+        if let Some(s) = var("FLEXI_LOGGER_DUPLICATE_TO_STDERR") {
+            self.duplicate_err = Duplicate::from_name(&s)
+                .map_err(|_| invalid("FLEXI_LOGGER_DUPLICATE_TO_STDERR", &s))?;
+        }
+        if let Some(s) = var("FLEXI_LOGGER_DUPLICATE_TO_STDOUT") {
+            self.duplicate_out = Duplicate::from_name(&s)
+                .map_err(|_| invalid("FLEXI_LOGGER_DUPLICATE_TO_STDOUT", &s))?;
+        }
+        Ok(self)
+    }
+
+    // Uses the named format for all outputs; for stderr and stdout, the colored variant
+    // is chosen when the logger is built.
+    // Returns None if the name is unknown.
+    pub(crate) fn named_format(self, name: &str) -> Option<Self> {
+        self.named_format_for_files(name)?
+            .named_format_for_writer(name)?
+            .named_format_for_stderr(name)?
+            .named_format_for_stdout(name)
+    }
+
+    pub(crate) fn named_format_for_files(mut self, name: &str) -> Option<Self> {
+        self = self.format_for_files(crate::formats::format_for_name(name)?);
+        self.format_names.file = name.to_string();
+        Some(self)
+    }
+
+    pub(crate) fn named_format_for_writer(mut self, name: &str) -> Option<Self> {
+        self = self.format_for_writer(crate::formats::format_for_name(name)?);
+        self.format_names.writer = name.to_string();
+        Some(self)
+    }
+
+    pub(crate) fn named_format_for_stderr(mut self, name: &str) -> Option<Self> {
         #[cfg(feature = "colors")]
         {
-            // Enable ASCII escape sequence support on Windows consoles,
-            // but disable coloring on unsupported Windows consoles
-            if cfg!(windows) && !yansi::Paint::enable_windows_ascii() {
-                yansi::Paint::disable();
-            }
+            self = self.adaptive_format_for_stderr(crate::formats::adaptive_format_for_name(name)?);
+        }
+        #[cfg(not(feature = "colors"))]
+        {
+            self = self.format_for_stderr(crate::formats::format_for_name(name)?);
+        }
+        self.format_names.stderr = name.to_string();
+        Some(self)
+    }
+
+    pub(crate) fn named_format_for_stdout(mut self, name: &str) -> Option<Self> {
+        #[cfg(feature = "colors")]
+        {
+            self = self.adaptive_format_for_stdout(crate::formats::adaptive_format_for_name(name)?);
         }
+        #[cfg(not(feature = "colors"))]
+        {
+            self = self.format_for_stdout(crate::formats::format_for_name(name)?);
+        }
+        self.format_names.stdout = name.to_string();
+        Some(self)
+    }
 
+    fn from_spec_and_errs(spec: LogSpecification, parse_errs: Option<String>) -> Self {
         Self {
             spec,
             parse_errs,
             log_target: LogTarget::StdErr,
             duplicate_err: Duplicate::None,
             duplicate_out: Duplicate::None,
+            broken_pipe: BrokenPipe::StopDuplicating,
+            log_panics: false,
+            o_self_diagnostics: None,
+            #[cfg(unix)]
+            captured_streams: (false, false),
+            format_settings: FormatSettings::default(),
             format_for_file: default_format,
-
-            #[cfg(feature = "colors")]
-            format_for_stdout: AdaptiveFormat::Default.format_function(Stream::StdOut),
-            #[cfg(feature = "colors")]
-            format_for_stderr: AdaptiveFormat::Default.format_function(Stream::StdErr),
-
-            #[cfg(not(feature = "colors"))]
             format_for_stdout: default_format,
-            #[cfg(not(feature = "colors"))]
             format_for_stderr: default_format,
-
             format_for_writer: default_format,
+            format_names: FormatNames::default(),
+
+            #[cfg(feature = "colors")]
+            o_adaptive_format_for_stderr: Some(AdaptiveFormat::Default),
+            #[cfg(feature = "colors")]
+            o_adaptive_format_for_stdout: Some(AdaptiveFormat::Default),
+            #[cfg(all(feature = "atty", not(feature = "colors")))]
+            o_adaptive_format_for_stderr: None,
+            #[cfg(all(feature = "atty", not(feature = "colors")))]
+            o_adaptive_format_for_stdout: None,
             #[cfg(feature = "colors")]
             o_palette: None,
+            o_max_message_length: None,
+            middlewares: Vec::new(),
+            writer_levels: HashMap::new(),
+            error_channel: ErrorChannel::Stderr,
             o_flush_wait: None,
+            o_write_mode_for_stderr: None,
+            o_write_mode_for_stdout: None,
+            o_style_overrides_for_stderr: None,
+            o_style_overrides_for_stdout: None,
+            #[cfg(feature = "specfile")]
+            specfile_debounce_delay: crate::DEFAULT_SPECFILE_DEBOUNCE_DELAY,
+            use_env_overrides: false,
             flwb: FileLogWriter::builder(),
             other_writers: HashMap::<String, Box<dyn LogWriter>>::new(),
+            fallthrough_writers: HashSet::new(),
         }
     }
 }
@@ -264,6 +539,101 @@ impl Logger {
         self
     }
 
+    /// Defines what happens when the duplication to stderr or stdout fails because
+    /// the reading end of the pipe was closed, see [`BrokenPipe`](crate::BrokenPipe).
+    ///
+    /// By default, the duplication to the affected stream is stopped,
+    /// while writing to files and to additional writers continues.
+    #[must_use]
+    pub fn on_broken_pipe(mut self, broken_pipe: BrokenPipe) -> Self {
+        self.broken_pipe = broken_pipe;
+        self
+    }
+
+    /// Makes the logger write panics as error records with target `panic`.
+    ///
+    /// The record shows the thread, the panic message, and the location of the panic,
+    /// in the layout of an [`ErrorChain`](crate::ErrorChain).
+    /// The panic hook is installed when the logger is started with
+    /// [`start()`](crate::Logger::start) or
+    /// [`start_with_specfile()`](crate::Logger::start_with_specfile).
+    /// The panic hook that was installed before, e.g. the default hook, which prints
+    /// the panic and optionally a backtrace to stderr, is still called after the record
+    /// is written.
+    #[must_use]
+    pub fn log_panics(mut self) -> Self {
+        self.log_panics = true;
+        self
+    }
+
+    /// Redirects the own stdout of the process, e.g. the output of `println!`
+    /// or of C libraries, into the log file.
+    ///
+    /// A separate thread writes the captured output line by line and unformatted,
+    /// like [`LoggerHandle::write_raw`](crate::LoggerHandle::write_raw) does.
+    /// Child processes that inherit stdout are captured as well.
+    /// The redirection starts with [`start()`](crate::Logger::start) or
+    /// [`start_with_specfile()`](crate::Logger::start_with_specfile), and ends with
+    /// [`LoggerHandle::shutdown`](crate::LoggerHandle::shutdown),
+    /// which waits until the output that was captured so far is written.
+    ///
+    /// The log target must write to files, and duplication to stdout cannot be used;
+    /// a specfile value for `duplicate_to_stdout` is then reported to the error channel
+    /// and ignored.
+    ///
+    /// Only available on unix.
+    #[cfg(unix)]
+    #[must_use]
+    pub fn capture_stdout(mut self) -> Self {
+        self.captured_streams.0 = true;
+        self
+    }
+
+    /// Redirects the own stderr of the process, e.g. panic messages from dependencies
+    /// or the output of C libraries, into the log file,
+    /// like [`Logger::capture_stdout`] does with stdout.
+    ///
+    /// The log target must write to files, and duplication to stderr cannot be used;
+    /// a specfile value for `duplicate_to_stderr` is then reported to the error channel
+    /// and ignored.
+    ///
+    /// Only available on unix.
+    #[cfg(unix)]
+    #[must_use]
+    pub fn capture_stderr(mut self) -> Self {
+        self.captured_streams.1 = true;
+        self
+    }
+
+    /// Makes the logger write its own lifecycle events as log records with the given level
+    /// and target, so that the logger can be observed in production.
+    ///
+    /// The target can be a module-like name, like `flexi_logger`, which is then subject
+    /// to the log specification, or the name of an additional writer, like `{diagnostics}`.
+    /// The target should not address a writer whose problems are to be observed.
+    ///
+    /// The records have the module path `flexi_logger`, and their messages consist of
+    /// `key=value` pairs, starting with the kind of the event:
+    ///
+    /// * `event=started` and `event=shutdown`
+    /// * `event=rotated`, `event=compressed`, `event=encrypted`, `event=moved`, `event=removed`,
+    ///   with the affected files, see [`RotationEvent`](crate::writers::RotationEvent)
+    /// * `event=spec_changed spec="..."`
+    /// * `event=records_dropped count=...`
+    /// * `event=problem message="..."` for every problem that is also reported to the
+    ///   [`ErrorChannel`](crate::ErrorChannel), like failing writes
+    ///
+    /// The records are written asynchronously, by a separate thread, which is started
+    /// together with the logger by [`start()`](crate::Logger::start) or
+    /// [`start_with_specfile()`](crate::Logger::start_with_specfile).
+    /// If more than 1024 events wait for being written, further events are dropped
+    /// and counted in [`LogStats::records_dropped`](crate::LogStats::records_dropped).
+    #[must_use]
+    pub fn self_diagnostics<S: Into<String>>(mut self, level: log::Level, target: S) -> Self {
+        self.o_self_diagnostics = Some((level, target.into()));
+        self
+    }
+
     /// Makes the logger use the provided format function for all messages
     /// that are written to files, stderr, stdout, or to an additional writer.
     ///
@@ -289,6 +659,12 @@ impl Logger {
         self.format_for_stderr = format;
         self.format_for_stdout = format;
         self.format_for_writer = format;
+        self.format_names = FormatNames::custom();
+        #[cfg(feature = "atty")]
+        {
+            self.o_adaptive_format_for_stderr = None;
+            self.o_adaptive_format_for_stdout = None;
+        }
         self
     }
 
@@ -298,11 +674,26 @@ impl Logger {
     /// Regarding the default, see [`Logger::format`](crate::Logger::format).
     pub fn format_for_files(mut self, format: FormatFunction) -> Self {
         self.format_for_file = format;
+        self.format_names.file = CUSTOM_FORMAT.to_string();
+        self
+    }
+
+    /// Makes the logger write each record additionally, with the given format
+    /// (e.g. [`json_format`](crate::json_format)), to a structured file with suffix `.jsonl`,
+    /// that shares rotation and cleanup with the log file.
+    ///
+    /// This parameter only has an effect if `log_to_file()` is used, too.
+    /// See [`FileLogWriterBuilder::structured_file`](crate::writers::FileLogWriterBuilder::structured_file)
+    /// for details.
+    #[must_use]
+    pub fn structured_file(mut self, format: FormatFunction) -> Self {
+        self.flwb = self.flwb.structured_file(format);
         self
     }
 
     /// Makes the logger use the specified format for messages that are written to `stderr`.
-    /// Coloring is used if `stderr` is a tty.
+    /// Coloring is used if `stderr` is a tty, see also
+    /// [`Logger::color_choice`](crate::Logger::color_choice).
     ///
     /// Regarding the default, see [`Logger::format`](crate::Logger::format).
     ///
@@ -310,12 +701,13 @@ impl Logger {
     #[cfg(feature = "atty")]
     #[must_use]
     pub fn adaptive_format_for_stderr(mut self, adaptive_format: AdaptiveFormat) -> Self {
-        self.format_for_stderr = adaptive_format.format_function(Stream::StdErr);
+        self.o_adaptive_format_for_stderr = Some(adaptive_format);
         self
     }
 
     /// Makes the logger use the specified format for messages that are written to `stdout`.
-    /// Coloring is used if `stdout` is a tty.
+    /// Coloring is used if `stdout` is a tty, see also
+    /// [`Logger::color_choice`](crate::Logger::color_choice).
     ///
     /// Regarding the default, see [`Logger::format`](crate::Logger::format).
     ///
@@ -323,7 +715,60 @@ impl Logger {
     #[cfg(feature = "atty")]
     #[must_use]
     pub fn adaptive_format_for_stdout(mut self, adaptive_format: AdaptiveFormat) -> Self {
-        self.format_for_stdout = adaptive_format.format_function(Stream::StdOut);
+        self.o_adaptive_format_for_stdout = Some(adaptive_format);
+        self
+    }
+
+    /// Sets format, palette, and timestamp format for the output to stderr in one place,
+    /// independently of the settings for files and for stdout,
+    /// see [`ConsoleStyle`](crate::ConsoleStyle).
+    ///
+    /// The style applies both if stderr is the log target and if log lines are
+    /// duplicated to stderr.
+    #[must_use]
+    pub fn style_for_stderr(mut self, style: ConsoleStyle) -> Self {
+        if let Some(format) = style.o_format {
+            self = self.format_for_stderr(format);
+        }
+        #[cfg(feature = "atty")]
+        {
+            if let Some(adaptive_format) = style.o_adaptive_format {
+                self = self.adaptive_format_for_stderr(adaptive_format);
+            }
+        }
+        self.o_style_overrides_for_stderr = Some(Arc::new(style.overrides));
+        self
+    }
+
+    /// Sets format, palette, and timestamp format for the output to stdout in one place,
+    /// like [`Logger::style_for_stderr`] does for stderr.
+    #[must_use]
+    pub fn style_for_stdout(mut self, style: ConsoleStyle) -> Self {
+        if let Some(format) = style.o_format {
+            self = self.format_for_stdout(format);
+        }
+        #[cfg(feature = "atty")]
+        {
+            if let Some(adaptive_format) = style.o_adaptive_format {
+                self = self.adaptive_format_for_stdout(adaptive_format);
+            }
+        }
+        self.o_style_overrides_for_stdout = Some(Arc::new(style.overrides));
+        self
+    }
+
+    /// Decides if the adaptive formats use colors.
+    ///
+    /// By default ([`ColorChoice::Auto`](crate::ColorChoice::Auto)),
+    /// colors are used if the output channel is a tty,
+    /// unless the environment variables `NO_COLOR`, `CLICOLOR`, or `CLICOLOR_FORCE`
+    /// decide otherwise.
+    ///
+    /// Only available with feature `atty`.
+    #[cfg(feature = "atty")]
+    #[must_use]
+    pub fn color_choice(mut self, color_choice: ColorChoice) -> Self {
+        self.format_settings.color_choice = color_choice;
         self
     }
 
@@ -333,6 +778,11 @@ impl Logger {
     /// Regarding the default, see [`Logger::format`](crate::Logger::format).
     pub fn format_for_stderr(mut self, format: FormatFunction) -> Self {
         self.format_for_stderr = format;
+        self.format_names.stderr = CUSTOM_FORMAT.to_string();
+        #[cfg(feature = "atty")]
+        {
+            self.o_adaptive_format_for_stderr = None;
+        }
         self
     }
 
@@ -342,6 +792,36 @@ impl Logger {
     /// Regarding the default, see [`Logger::format`](crate::Logger::format).
     pub fn format_for_stdout(mut self, format: FormatFunction) -> Self {
         self.format_for_stdout = format;
+        self.format_names.stdout = CUSTOM_FORMAT.to_string();
+        #[cfg(feature = "atty")]
+        {
+            self.o_adaptive_format_for_stdout = None;
+        }
+        self
+    }
+
+    /// Determines how log lines are written to stderr, both if stderr is the log target
+    /// and if log lines are duplicated to stderr.
+    ///
+    /// Writing every log line directly to the unbuffered stderr can dominate the runtime
+    /// of programs that log a lot; buffering reduces the number of write calls.
+    ///
+    /// If this method is not used, stderr as log target follows the buffer settings for files
+    /// (see [`Logger::use_buffering`](crate::Logger::use_buffering)), and duplicated log lines
+    /// are written with [`WriteMode::Direct`](crate::WriteMode::Direct).
+    #[must_use]
+    pub fn write_mode_for_stderr(mut self, write_mode: WriteMode) -> Self {
+        self.o_write_mode_for_stderr = Some(write_mode);
+        self
+    }
+
+    /// Determines how log lines are written to stdout, both if stdout is the log target
+    /// and if log lines are duplicated to stdout.
+    ///
+    /// See [`Logger::write_mode_for_stderr`](crate::Logger::write_mode_for_stderr).
+    #[must_use]
+    pub fn write_mode_for_stdout(mut self, write_mode: WriteMode) -> Self {
+        self.o_write_mode_for_stdout = Some(write_mode);
         self
     }
 
@@ -352,6 +832,210 @@ impl Logger {
     /// Regarding the default, see [`Logger::format`](crate::Logger::format).
     pub fn format_for_writer(mut self, format: FormatFunction) -> Self {
         self.format_for_writer = format;
+        self.format_names.writer = CUSTOM_FORMAT.to_string();
+        self
+    }
+
+    /// Shows or hides the module path in the log lines of the provided human-readable
+    /// format functions (e.g. [`opt_format`](crate::opt_format) or
+    /// [`colored_detailed_format`](crate::colored_detailed_format)),
+    /// regardless of whether the format function shows it by default.
+    ///
+    /// The module path is shown in brackets after the level, like in
+    /// [`default_format`](crate::default_format).
+    ///
+    /// Note that these toggles are global for the process and
+    /// don't affect [`json_format`](crate::json_format) or your own format functions.
+    #[must_use]
+    pub fn show_module(mut self, show: bool) -> Self {
+        self.format_settings
+            .field_overrides
+            .set(Fields::MODULE, show);
+        self
+    }
+
+    /// Shows or hides the source file and line in the log lines of the provided
+    /// human-readable format functions.
+    ///
+    /// The location is shown like in [`detailed_format`](crate::detailed_format)
+    /// if the module path is shown, and otherwise like in [`opt_format`](crate::opt_format).
+    /// See [`Logger::show_module`].
+    #[must_use]
+    pub fn show_file_line(mut self, show: bool) -> Self {
+        self.format_settings
+            .field_overrides
+            .set(Fields::FILE_LINE, show);
+        self
+    }
+
+    /// Shows or hides the name of the logging thread in the log lines of the provided
+    /// human-readable format functions.
+    ///
+    /// The thread name is shown like in [`with_thread`](crate::with_thread).
+    /// See [`Logger::show_module`].
+    #[must_use]
+    pub fn show_thread(mut self, show: bool) -> Self {
+        self.format_settings
+            .field_overrides
+            .set(Fields::THREAD, show);
+        self
+    }
+
+    /// Shows or hides the time since the start of the logger in the log lines of the provided
+    /// human-readable format functions.
+    ///
+    /// The time is shown like in [`elapsed_format`](crate::elapsed_format).
+    /// See [`Logger::show_module`].
+    #[must_use]
+    pub fn show_elapsed(mut self, show: bool) -> Self {
+        self.format_settings
+            .field_overrides
+            .set(Fields::ELAPSED, show);
+        self
+    }
+
+    /// Shows or hides the time since the previous record in the log lines of the provided
+    /// human-readable format functions.
+    ///
+    /// The time is shown like in [`elapsed_format`](crate::elapsed_format).
+    /// See [`Logger::show_module`].
+    #[must_use]
+    pub fn show_delta(mut self, show: bool) -> Self {
+        self.format_settings
+            .field_overrides
+            .set(Fields::DELTA, show);
+        self
+    }
+
+    /// Shows or hides the sequence number of the records in the log lines of the provided
+    /// format functions; by default, none of them shows it.
+    ///
+    /// The sequence number is shown at the beginning of the log line, e.g.
+    /// `#42 [2016-01-13 15:25:01.640870 +01:00] INFO [src/foo/bar:26] Task successfully read`.
+    /// Unlike the other toggles, this one also applies to [`json_format`](crate::json_format),
+    /// which then adds the field `seq`.
+    ///
+    /// See [`DeferredNow::sequence_number`](crate::DeferredNow::sequence_number)
+    /// for how the numbers are assigned, and for using them in your own format functions.
+    #[must_use]
+    pub fn show_sequence_number(mut self, show: bool) -> Self {
+        self.format_settings
+            .field_overrides
+            .set(Fields::SEQUENCE_NUMBER, show);
+        self
+    }
+
+    /// Shows or hides the target of the log call in the log lines of the provided
+    /// human-readable format functions; by default, none of them shows it.
+    ///
+    /// The target is shown in angle brackets before the message, e.g.
+    /// `INFO [my_prog::db] <sql> Task successfully read from conf.json`.
+    /// See [`Logger::show_module`].
+    #[must_use]
+    pub fn show_target(mut self, show: bool) -> Self {
+        self.format_settings
+            .field_overrides
+            .set(Fields::TARGET, show);
+        self
+    }
+
+    /// Shortens long module paths in the log lines of the provided human-readable
+    /// format functions, see [`ShortModulePath`](crate::ShortModulePath).
+    ///
+    /// Like the toggles of the shown fields, this setting is global for the process.
+    /// See [`Logger::show_module`].
+    #[must_use]
+    pub fn shorten_module_path(mut self, short_module_path: ShortModulePath) -> Self {
+        self.format_settings.o_short_module_path = Some(short_module_path);
+        self
+    }
+
+    /// Pads the level and the module path in the log lines of the provided human-readable
+    /// format functions to the given widths (in characters), so that the following columns,
+    /// usually the messages, line up vertically, e.g.
+    ///
+    /// ```text
+    /// INFO  [my_prog]            Task successfully read from conf.json
+    /// ERROR [my_prog::db::pool]   No connection available
+    /// ```
+    ///
+    /// A width of `0` switches off the padding of the column; a level or module path
+    /// that is longer than its width is written completely.
+    /// Combine this with [`Logger::shorten_module_path`] to keep the module column narrow.
+    ///
+    /// Like the toggles of the shown fields, this setting is global for the process.
+    /// See [`Logger::show_module`].
+    #[must_use]
+    pub fn align_columns(mut self, level_width: usize, module_width: usize) -> Self {
+        self.format_settings.level_width = level_width;
+        self.format_settings.module_width = module_width;
+        self
+    }
+
+    /// Makes all provided format functions, including [`json_format`](crate::json_format),
+    /// write their timestamps in the given format, see [`TimestampFormat`](crate::TimestampFormat).
+    ///
+    /// Like the toggles of the shown fields, this setting is global for the process.
+    /// See [`Logger::show_module`].
+    #[must_use]
+    pub fn timestamp_format(mut self, timestamp_format: TimestampFormat) -> Self {
+        self.format_settings.o_timestamp_format = Some(timestamp_format);
+        self
+    }
+
+    /// Truncates messages that are longer than the given number of bytes, before they are
+    /// written to any output, to protect against accidentally logging huge payloads.
+    ///
+    /// A truncated message ends with an ellipsis and the number of omitted bytes,
+    /// e.g. `Received payload {"data":[1,2,3,...[123456 bytes omitted]`.
+    /// Truncation respects character boundaries, so the kept part can be slightly shorter.
+    ///
+    /// By default, messages are not truncated.
+    #[must_use]
+    pub fn max_message_length(mut self, max_message_length: usize) -> Self {
+        self.o_max_message_length = Some(max_message_length);
+        self
+    }
+
+    /// Registers a middleware that can inspect, modify, or drop the log records
+    /// before they are formatted and written.
+    ///
+    /// The middlewares are applied in the order of their registration,
+    /// before the message is truncated (see [`Logger::max_message_length`]).
+    /// See [`RecordMiddleware`] for an example.
+    #[must_use]
+    pub fn add_middleware<M: RecordMiddleware + 'static>(mut self, middleware: M) -> Self {
+        self.middlewares.push(Box::new(middleware));
+        self
+    }
+
+    /// Defines where `flexi_logger` reports the problems it encounters itself,
+    /// e.g. when writing, rotating, or cleaning up log files fails.
+    ///
+    /// By default, such problems are reported to stderr, which is not appropriate e.g.
+    /// for libraries that are embedded in GUI applications.
+    ///
+    /// The setting applies to all writers, including the additional writers,
+    /// from the start of the logger on.
+    #[must_use]
+    pub fn error_channel(mut self, error_channel: ErrorChannel) -> Self {
+        self.error_channel = error_channel;
+        self
+    }
+
+    /// Makes all timestamps use the given offset from UTC, in seconds east of UTC
+    /// (e.g. `3600` for `+01:00`), rather than the offset of the local timezone.
+    ///
+    /// This is useful where the local offset cannot be determined reliably, e.g. in
+    /// minimal containers without timezone data. If the local offset cannot be determined
+    /// and no offset is given here, `flexi_logger` writes its timestamps in UTC and reports
+    /// this once to the [`error_channel`](crate::Logger::error_channel).
+    ///
+    /// The offset must be less than a day; otherwise `build()` and the `start` methods fail
+    /// with [`FlexiLoggerError::UtcOffset`](crate::FlexiLoggerError::UtcOffset).
+    #[must_use]
+    pub fn assume_utc_offset(mut self, offset_seconds: i32) -> Self {
+        self.format_settings.o_utc_offset = Some(offset_seconds);
         self
     }
 
@@ -361,8 +1045,8 @@ impl Logger {
     /// The palette given here overrides the default palette.
     ///
     /// The palette is specified in form of a String that contains a semicolon-separated list
-    /// of numbers (0..=255) and/or dashes (´-´).
-    /// The first five values denote the fixed color that is
+    /// of numbers (0..=255), RGB colors (`#rrggbb`), and/or dashes (´-´).
+    /// The first five values denote the color that is
     /// used for coloring `error`, `warn`, `info`, `debug`, and `trace` messages.
     ///
     /// The String `"196;208;-;7;8"` describes the default palette, where color 196 is
@@ -376,24 +1060,119 @@ impl Logger {
     /// For your convenience, if you want to specify your own palette,
     /// you can produce a colored list with all 255 colors with `cargo run --example colors`.
     ///
+    /// Use [`palette`](crate::Logger::palette) if you need text attributes, like bold
+    /// or underline, or separate styles for the level token, the timestamp, and the message.
+    ///
     /// Only available with feature `colors`.
     #[cfg(feature = "colors")]
     #[must_use]
     pub fn set_palette(mut self, palette: String) -> Self {
-        self.o_palette = Some(palette);
+        self.o_palette = Some(PaletteInput::String(palette));
+        self
+    }
+
+    /// Sets the color palette for the provided coloring format functions,
+    /// see [`Palette`](crate::Palette).
+    ///
+    /// The palette given here overrides the default palette.
+    /// It can be overridden at runtime by setting the environment variable
+    /// `FLEXI_LOGGER_PALETTE`, see [`set_palette`](crate::Logger::set_palette).
+    ///
+    /// Only available with feature `colors`.
+    #[cfg(feature = "colors")]
+    #[must_use]
+    pub fn palette(mut self, palette: Palette) -> Self {
+        self.o_palette = Some(PaletteInput::Palette(palette));
+        self
+    }
+
+    /// Makes the provided coloring format functions color only the level token
+    /// (like `ERROR` or `WARN`), rather than the whole line.
+    ///
+    /// Whole-line coloring can make long, wrapped messages hard to read.
+    /// This setting affects the adaptive formats and all `colored_*` format functions.
+    ///
+    /// Only available with feature `colors`.
+    #[cfg(feature = "colors")]
+    #[must_use]
+    pub fn colorize_level_only(mut self, level_only: bool) -> Self {
+        self.format_settings.colorize_level_only = level_only;
         self
     }
 
     /// Specifies a folder for the log files.
     ///
     /// This parameter only has an effect if `log_to_file()` is used, too.
-    /// The specified folder will be created if it does not exist.
+    /// The specified folder will be created if it does not exist, including all missing
+    /// parent folders, see [`create_directory`](crate::Logger::create_directory).
     /// By default, the log files are created in the folder where the program was started.
     pub fn directory<S: Into<PathBuf>>(mut self, directory: S) -> Self {
         self.flwb = self.flwb.directory(directory);
         self
     }
 
+    /// Uses the platform's usual per-user folder for log files, with a subfolder
+    /// for the given application, e.g. `~/.local/state/<app_name>` on linux.
+    ///
+    /// See [`FileLogWriterBuilder::use_platform_log_dir`](crate::writers::FileLogWriterBuilder::use_platform_log_dir).
+    ///
+    /// This parameter only has an effect if `log_to_file()` is used, too.
+    pub fn use_platform_log_dir<S: AsRef<str>>(mut self, app_name: S) -> Self {
+        self.flwb = self.flwb.use_platform_log_dir(app_name);
+        self
+    }
+
+    /// With true (the default), a missing folder for the log files is created,
+    /// including all missing parent folders.
+    ///
+    /// With false, the initialization fails with `FlexiLoggerError::OutputMissingDirectory`
+    /// if the folder does not exist, which can be preferable to surprising folder creations
+    /// on the first deployment.
+    ///
+    /// This parameter only has an effect if `log_to_file()` is used, too.
+    #[must_use]
+    pub fn create_directory(mut self, create_directory: bool) -> Self {
+        self.flwb = self.flwb.create_directory(create_directory);
+        self
+    }
+
+    /// Specifies the permissions (e.g. `0o750`) of the folders that are created for the
+    /// log files.
+    ///
+    /// This parameter only has an effect if `log_to_file()` is used, too.
+    ///
+    /// Only available on unix.
+    #[cfg(unix)]
+    #[must_use]
+    pub fn directory_mode(mut self, mode: u32) -> Self {
+        self.flwb = self.flwb.directory_mode(mode);
+        self
+    }
+
+    /// Specifies the permissions (e.g. `0o600`) of the log files that are created.
+    ///
+    /// This parameter only has an effect if `log_to_file()` is used, too.
+    ///
+    /// Only available on unix.
+    #[cfg(unix)]
+    #[must_use]
+    pub fn file_mode(mut self, mode: u32) -> Self {
+        self.flwb = self.flwb.file_mode(mode);
+        self
+    }
+
+    /// Configures the log files as an audit log, which is appended to a file with a fixed name,
+    /// synced with every record, and never truncated or removed.
+    ///
+    /// This option only has an effect if `log_to_file()` is used, too.
+    /// See [`FileLogWriterBuilder::audit`](crate::writers::FileLogWriterBuilder::audit)
+    /// for details.
+    #[must_use]
+    pub fn audit(mut self) -> Self {
+        self.flwb = self.flwb.audit();
+        self
+    }
+
     /// Specifies a suffix for the log files.
     ///
     /// This parameter only has an effect if `log_to_file()` is used, too.
@@ -432,6 +1211,85 @@ impl Logger {
         self
     }
 
+    /// With true, the cleanup also considers the rotated files that were written
+    /// with another discriminant, or without discriminant.
+    ///
+    /// See [`FileLogWriterBuilder::cleanup_other_discriminants`](crate::writers::FileLogWriterBuilder::cleanup_other_discriminants).
+    #[must_use]
+    pub fn cleanup_other_discriminants(mut self, cleanup_other_discriminants: bool) -> Self {
+        self.flwb = self
+            .flwb
+            .cleanup_other_discriminants(cleanup_other_discriminants);
+        self
+    }
+
+    /// With true, the cleanup only considers files whose names match exactly
+    /// the names that the configured [`Naming`](crate::Naming) generates.
+    ///
+    /// See [`FileLogWriterBuilder::strict_cleanup`](crate::writers::FileLogWriterBuilder::strict_cleanup).
+    #[must_use]
+    pub fn strict_cleanup(mut self, strict_cleanup: bool) -> Self {
+        self.flwb = self.flwb.strict_cleanup(strict_cleanup);
+        self
+    }
+
+    /// Defines how long [`LoggerHandle::shutdown`](crate::LoggerHandle::shutdown)
+    /// waits for the cleanup thread to finish its pending work.
+    ///
+    /// See [`FileLogWriterBuilder::cleanup_timeout`](crate::writers::FileLogWriterBuilder::cleanup_timeout).
+    #[must_use]
+    pub fn cleanup_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.flwb = self.flwb.cleanup_timeout(timeout);
+        self
+    }
+
+    /// With true, rotation and cleanup take an advisory lock (a file `.<basename>.lock`
+    /// in the log directory), so that several instances of a program that share the log
+    /// directory don't rename or delete each other's files concurrently.
+    ///
+    /// This option only has an effect if `log_to_file()` and `rotate()` are used, too,
+    /// and is usually combined with `append()` and `suppress_timestamp()`.
+    /// See [`FileLogWriterBuilder::use_rotation_lock`](crate::writers::FileLogWriterBuilder::use_rotation_lock).
+    #[must_use]
+    pub fn use_rotation_lock(mut self, use_rotation_lock: bool) -> Self {
+        self.flwb = self.flwb.use_rotation_lock(use_rotation_lock);
+        self
+    }
+
+    /// Defines what happens with the log records while the disk is full,
+    /// see [`DiskFull`](crate::DiskFull).
+    ///
+    /// This option only has an effect if `log_to_file()` is used, too.
+    #[must_use]
+    pub fn on_disk_full(mut self, disk_full: DiskFull) -> Self {
+        self.flwb = self.flwb.on_disk_full(disk_full);
+        self
+    }
+
+    /// Defines when the written data of the log file are forced to the storage device,
+    /// see [`SyncPolicy`](crate::SyncPolicy).
+    ///
+    /// This option only has an effect if `log_to_file()` is used, too.
+    #[must_use]
+    pub fn sync_policy(mut self, sync_policy: SyncPolicy) -> Self {
+        self.flwb = self.flwb.sync_policy(sync_policy);
+        self
+    }
+
+    /// Removes the oldest rotated log files early when the free space in the log directory
+    /// drops below the given number of bytes.
+    ///
+    /// This option only has an effect if `log_to_file()` and `rotate()` are used, too.
+    /// See [`FileLogWriterBuilder::min_free_space`](crate::writers::FileLogWriterBuilder::min_free_space).
+    ///
+    /// Only available with optional crate feature `free_space`.
+    #[cfg(feature = "free_space")]
+    #[must_use]
+    pub fn min_free_space(mut self, min_free_space: u64) -> Self {
+        self.flwb = self.flwb.min_free_space(min_free_space);
+        self
+    }
+
     /// Prevent indefinite growth of the log file by applying file rotation
     /// and a clean-up strategy for older log files.
     ///
@@ -442,10 +1300,10 @@ impl Logger {
     /// Note that also the filename pattern changes:
     ///
     /// - by default, no timestamp is added to the filename
-    /// - the logs are always written to a file with infix `_rCURRENT`
+    /// - the logs are always written to the file without infix, like `my_prog.log`
     /// - when the rotation criterion is fulfilled, it is closed and renamed to a file
-    ///   with another infix (see `Naming`),
-    ///   and then the logging continues again to the (fresh) file with infix `_rCURRENT`.
+    ///   with a number or a timestamp (see `Naming`),
+    ///   and then the logging continues again to the (fresh) file without infix.
     ///
     /// Example:
     ///
@@ -453,10 +1311,10 @@ impl Logger {
     /// you will find files like
     ///
     /// ```text
-    /// my_prog_r00000.log
-    /// my_prog_r00001.log
-    /// my_prog_r00002.log
-    /// my_prog_rCURRENT.log
+    /// my_prog.0.log
+    /// my_prog.1.log
+    /// my_prog.2.log
+    /// my_prog.log
     /// ```
     ///
     /// ## Parameters
@@ -477,6 +1335,10 @@ impl Logger {
     ///
     /// This option only has an effect if `log_to_file()` is used, too.
     /// This option will hardly make an effect if `suppress_timestamp()` is not used.
+    ///
+    /// The file is then opened in append mode (`O_APPEND`), and every record is written
+    /// with a single write call, so that several processes (or a forked child) can
+    /// append to the same file without interleaving partial lines.
     #[must_use]
     pub fn append(mut self) -> Self {
         self.flwb = self.flwb.append();
@@ -485,6 +1347,11 @@ impl Logger {
 
     /// The specified String is added to the log file name after the program name.
     ///
+    /// The placeholders `{pid}`, `{hostname}`, and `{exe}` are replaced with the process id,
+    /// the host name, and the program name, so that several instances of a program
+    /// that write into a shared directory get unique, identifiable files,
+    /// e.g. with `discriminant("{hostname}_{pid}")`.
+    ///
     /// This option only has an effect if `log_to_file()` is used, too.
     pub fn discriminant<S: Into<String>>(mut self, discriminant: S) -> Self {
         self.flwb = self.flwb.discriminant(discriminant);
@@ -494,17 +1361,41 @@ impl Logger {
     /// The specified String is used as the basename of the log file name,
     /// instead of the program name.
     ///
+    /// The program name is taken from the first command line argument;
+    /// it is not always a good basename, e.g. cargo appends a hash to the names of test binaries,
+    /// and programs that are started via a wrapper can see the wrapper's name.
+    /// With a basename (and [`suppress_timestamp`](crate::Logger::suppress_timestamp)),
+    /// the file name does not depend on the program name at all.
+    ///
+    /// The placeholders `{pid}`, `{hostname}`, and `{exe}` are supported,
+    /// see [`discriminant`](crate::Logger::discriminant).
+    ///
     /// This option only has an effect if `log_to_file()` is used, too.
     pub fn basename<S: Into<String>>(mut self, basename: S) -> Self {
         self.flwb = self.flwb.basename(basename);
         self
     }
 
-    /// The specified path will be used on linux systems to create a symbolic link
-    /// to the current log file.
+    /// Writes the log to exactly the specified file, e.g. `/var/log/myapp/app.log`,
+    /// instead of assembling the file name from directory, basename, discriminant, timestamp,
+    /// and suffix.
+    ///
+    /// See [`FileLogWriterBuilder::log_file_path`](crate::writers::FileLogWriterBuilder::log_file_path).
+    ///
+    /// This option only has an effect if `log_to_file()` is used, too.
+    pub fn log_file_path<P: Into<PathBuf>>(mut self, file_path: P) -> Self {
+        self.flwb = self.flwb.log_file_path(file_path);
+        self
+    }
+
+    /// The specified path will be used to create a symbolic link to the current log file.
+    ///
+    /// Where no symbolic link can be created (e.g. on windows without the respective privilege),
+    /// a hard link is used instead, and if that fails, too, a pointer file with the suffix `.txt`
+    /// (e.g. `current_run.txt` for `current_run`), which contains the path of the current
+    /// log file.
     ///
-    /// This option has no effect on filesystems where symlinks are not supported,
-    /// and it only has an effect if `log_to_file()` is used, too.
+    /// This option only has an effect if `log_to_file()` is used, too.
     ///
     /// ### Example
     ///
@@ -522,6 +1413,128 @@ impl Logger {
         self
     }
 
+    /// Maintains two additional links in the log directory:
+    /// `latest.<suffix>` always points to the current log file (i.e., with rotation,
+    /// to the file without infix), and, with rotation, `latest_rotated.<suffix>` points to the
+    /// most recently rotated file, which is handy for scripts that process the last
+    /// completed log file.
+    ///
+    /// The links are created like the one of [`create_symlink`](crate::Logger::create_symlink).
+    /// Note that `latest_rotated.<suffix>` can become stale if the rotated files are
+    /// compressed or encrypted.
+    ///
+    /// This option only has an effect if `log_to_file()` is used, too.
+    #[must_use]
+    pub fn create_latest_links(mut self) -> Self {
+        self.flwb = self.flwb.create_latest_links();
+        self
+    }
+
+    /// Writes the given header at the top of every new log file,
+    /// e.g. with the application version, the start time, the host, and the command line.
+    ///
+    /// This parameter only has an effect if `log_to_file()` is used, too.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// # use flexi_logger::{Logger, writers::FileHeader};
+    /// Logger::with_str("info")
+    ///     .log_to_file()
+    ///     .file_header(FileHeader::standard("my_app", env!("CARGO_PKG_VERSION")))
+    /// #   ;
+    /// ```
+    #[must_use]
+    pub fn file_header<H: Into<FileHeader>>(mut self, header: H) -> Self {
+        self.flwb = self.flwb.file_header(header);
+        self
+    }
+
+    /// Writes the given footer at the end of a log file when the file is closed
+    /// due to rotation or due to [`LoggerHandle::shutdown`](crate::LoggerHandle::shutdown).
+    ///
+    /// A log file without footer was thus not closed cleanly, e.g. because the program crashed.
+    ///
+    /// This parameter only has an effect if `log_to_file()` is used, too.
+    #[must_use]
+    pub fn file_footer<F: Into<FileFooter>>(mut self, footer: F) -> Self {
+        self.flwb = self.flwb.file_footer(footer);
+        self
+    }
+
+    /// Registers a callback that is invoked after each rotation of the log file,
+    /// and after the cleanup has compressed or removed a rotated file.
+    ///
+    /// This parameter only has an effect if `log_to_file()` and `rotate()` are used, too.
+    ///
+    /// See [`FileLogWriterBuilder::on_rotation`](crate::writers::FileLogWriterBuilder::on_rotation)
+    /// for details.
+    ///
+    /// ### Example
+    ///
+    /// ```rust
+    /// # use flexi_logger::{Cleanup, Criterion, Logger, Naming, writers::RotationEvent};
+    /// Logger::with_str("info")
+    ///     .log_to_file()
+    ///     .rotate(Criterion::Size(1_000_000), Naming::Numbers, Cleanup::KeepLogFiles(7))
+    ///     .on_rotation(|event| {
+    ///         if let RotationEvent::Rotated { rotated_file, .. } = event {
+    ///             println!("{} is ready for upload", rotated_file.display());
+    ///         }
+    ///     })
+    /// #   ;
+    /// ```
+    #[must_use]
+    pub fn on_rotation<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&RotationEvent) + Send + Sync + 'static,
+    {
+        self.flwb = self.flwb.on_rotation(callback);
+        self
+    }
+
+    /// Encrypts the rotated log files for the owner of a public key.
+    ///
+    /// Only available with optional crate feature `encrypt`.
+    ///
+    /// This parameter only has an effect if `log_to_file()` and `rotate()` are used, too.
+    /// See [`Encryption`](crate::writers::Encryption) for details.
+    #[cfg(feature = "encrypt")]
+    #[must_use]
+    pub fn encrypt_rotated_files(mut self, encryption: crate::writers::Encryption) -> Self {
+        self.flwb = self.flwb.encrypt_rotated_files(encryption);
+        self
+    }
+
+    /// Complements the log files with a hash chain in side-car files, keyed with the given
+    /// secret key, so that auditors can detect modified or truncated log files.
+    ///
+    /// Only available with optional crate feature `integrity`.
+    ///
+    /// This parameter only has an effect if `log_to_file()` is used, too.
+    /// See [`FileLogWriterBuilder::use_hash_chain`](crate::writers::FileLogWriterBuilder::use_hash_chain)
+    /// for details.
+    #[cfg(feature = "integrity")]
+    #[must_use]
+    pub fn use_hash_chain<K: Into<Vec<u8>>>(mut self, key: K) -> Self {
+        self.flwb = self.flwb.use_hash_chain(key);
+        self
+    }
+
+    /// Uploads the rotated log files to an S3-compatible bucket and deletes them locally
+    /// afterwards.
+    ///
+    /// Only available with optional crate feature `archive-s3`.
+    ///
+    /// This parameter only has an effect if `log_to_file()` and `rotate()` are used, too.
+    /// See [`S3Archive`](crate::writers::S3Archive) for details.
+    #[cfg(feature = "archive-s3")]
+    #[must_use]
+    pub fn archive_to_s3(mut self, archive: crate::writers::S3Archive) -> Self {
+        self.flwb = self.flwb.archive_to_s3(archive);
+        self
+    }
+
     /// Registers a `LogWriter` implementation under the given target name.
     ///
     /// The target name must not start with an underscore.
@@ -536,6 +1549,52 @@ impl Logger {
         self
     }
 
+    /// Registers a `LogWriter` implementation under the given target name,
+    /// like [`Logger::add_writer`], but log calls that address this writer are
+    /// also written to the default target, as if `_Default` was specified additionally.
+    ///
+    /// This is useful e.g. for an alert writer, if the alerts should also appear in the
+    /// main log file. As with `_Default`, the log specification then applies to
+    /// the default target.
+    ///
+    /// The target name must not start with an underscore.
+    ///
+    /// See module [`writers`](crate::writers).
+    #[must_use]
+    pub fn add_writer_with_fallthrough<S: Into<String>>(
+        mut self,
+        target_name: S,
+        writer: Box<dyn LogWriter>,
+    ) -> Self {
+        let target_name = target_name.into();
+        self.fallthrough_writers.insert(target_name.clone());
+        self.other_writers.insert(target_name, writer);
+        self
+    }
+
+    /// Lowers the maximum level of the additional writer with the given target name
+    /// below the writer's own [`max_log_level`](crate::writers::LogWriter::max_log_level).
+    ///
+    /// `flexi_logger` sets `log::set_max_level` to the highest level that the log specification
+    /// or any of the additional writers accepts, and recomputes it with every change
+    /// of the log specification.
+    /// A writer that reports `Trace` as its maximum level thus makes all `trace!` calls
+    /// reach the logger, which is costly in hot loops.
+    /// With this method, such a writer can be restricted, so that disabled log calls
+    /// are discarded cheaply by the `log` macros.
+    ///
+    /// The override can be changed at runtime with
+    /// [`LoggerHandle::set_max_level_for_writer`](crate::LoggerHandle::set_max_level_for_writer).
+    #[must_use]
+    pub fn max_level_for_writer<S: Into<String>>(
+        mut self,
+        target_name: S,
+        max_level: log::LevelFilter,
+    ) -> Self {
+        self.writer_levels.insert(target_name.into(), max_level);
+        self
+    }
+
     /// Define if buffering should be used.
     ///
     /// By default, every log line is directly written to the output, without buffering.
@@ -571,6 +1630,13 @@ impl Logger {
     /// Activates buffering and flushing (like with [`Logger::buffer_and_flush`],
     /// but with user-defined buffer capacity and flush frequency.
     ///
+    /// The log lines are collected in the buffer and are written in batches,
+    /// with a single write call per batch: a batch is written when the next log line
+    /// does not fit anymore into the buffer, or when the flusher thread flushes the buffer.
+    /// So `capacity` is the maximum batch size, and `wait` is the maximum time
+    /// a log line remains in the buffer.
+    /// Under heavy load, a larger capacity reduces the number of write calls.
+    ///
     /// Note that flushing uses an extra thread (with minimal stack).
     #[must_use]
     pub fn buffer_and_flush_with(mut self, capacity: usize, wait: std::time::Duration) -> Self {
@@ -579,12 +1645,73 @@ impl Logger {
         self
     }
 
+    /// Reduces the contention on the log file when many threads log concurrently,
+    /// by collecting the records in the given number of staging buffers.
+    ///
+    /// This option only has an effect if `log_to_file()` is used, too, and should be combined
+    /// with [`Logger::buffer_and_flush`] or [`Logger::buffer_and_flush_with`].
+    /// See [`FileLogWriterBuilder::sharded`](crate::writers::FileLogWriterBuilder::sharded).
+    #[must_use]
+    pub fn sharded(mut self, no_of_shards: usize) -> Self {
+        self.flwb = self.flwb.sharded(no_of_shards);
+        self
+    }
+
     /// Use Windows line endings, rather than just `\n`.
     #[must_use]
     pub fn use_windows_line_ending(mut self) -> Self {
         self.flwb = self.flwb.use_windows_line_ending();
         self
     }
+
+    /// Allows environment variables to override the settings of this builder
+    /// when the logger is started, so that e.g. containerized deployments can tune logging
+    /// without code changes.
+    ///
+    /// The following variables are evaluated, if set:
+    ///
+    /// * `FLEXI_LOGGER_DIRECTORY`, `FLEXI_LOGGER_BASENAME`, `FLEXI_LOGGER_DISCRIMINANT`,
+    ///   `FLEXI_LOGGER_SUFFIX`: override the respective file options
+    /// * `FLEXI_LOGGER_ROTATE_SIZE`: the size in bytes at which the log file is rotated;
+    ///   activates rotation (with `Naming::Numbers` and `Cleanup::Never`)
+    ///   if it was not configured
+    /// * `FLEXI_LOGGER_KEEP_LOG_FILES`: the number of rotated log files to keep;
+    ///   requires rotation
+    /// * `FLEXI_LOGGER_FORMAT`: the format for all outputs, one of `default`, `detailed`, `opt`,
+    ///   `with_thread`, or `elapsed` (colored on terminals with feature `colors`)
+    /// * `FLEXI_LOGGER_DUPLICATE_TO_STDERR`, `FLEXI_LOGGER_DUPLICATE_TO_STDOUT`:
+    ///   one of `none`, `error`, `warn`, `info`, `debug`, `trace`, or `all`
+    ///
+    /// Note that `FLEXI_LOGGER_PALETTE` is always evaluated, see
+    /// [`set_palette`](crate::Logger::set_palette).
+    ///
+    /// Invalid values let [`start`](crate::Logger::start) or [`build`](crate::Logger::build)
+    /// fail with `FlexiLoggerError::EnvOverride`.
+    #[must_use]
+    pub fn use_env_overrides(mut self) -> Self {
+        self.use_env_overrides = true;
+        self
+    }
+
+    /// Sets the delay with which changes to the specfile are taken into account
+    /// (default: 100 milliseconds).
+    ///
+    /// The specfile is watched with filesystem notifications (`inotify`, `FSEvents`, etc),
+    /// so there is no periodic wakeup in idle processes.
+    /// Since editors often write a file in several steps, `flexi_logger` waits until the
+    /// specfile was not modified for the given time before it re-reads it.
+    ///
+    /// This parameter only has an effect if
+    /// [`start_with_specfile()`](crate::Logger::start_with_specfile) or
+    /// [`build_with_specfile()`](crate::Logger::build_with_specfile) is used.
+    ///
+    /// This method is only available with feature `specfile`.
+    #[cfg(feature = "specfile")]
+    #[must_use]
+    pub fn specfile_debounce_delay(mut self, delay: std::time::Duration) -> Self {
+        self.specfile_debounce_delay = delay;
+        self
+    }
 }
 
 /// Alternative set of methods to control the behavior of the Logger.
@@ -687,11 +1814,118 @@ impl Logger {
     ///
     /// Several variants of `FlexiLoggerError` can occur.
     pub fn start(self) -> Result<LoggerHandle, FlexiLoggerError> {
+        let log_panics = self.log_panics;
+        let o_self_diagnostics = self.o_self_diagnostics.clone();
+        #[cfg(unix)]
+        let captured_streams = self.captured_streams;
         let (boxed_logger, handle) = self.build()?;
         log::set_boxed_logger(boxed_logger)?;
+        if log_panics {
+            crate::error_chain::log_panics();
+        }
+        start_self_diagnostics(o_self_diagnostics)?;
+        #[cfg(unix)]
+        start_capture(captured_streams, &handle)?;
         Ok(handle)
     }
 
+    // Detects settings that contradict each other.
+    fn validate(&self) -> Result<(), FlexiLoggerError> {
+        if self.flwb.appends() && !self.writes_files() {
+            return Err(FlexiLoggerError::FileConfigConflict(
+                "append() only has an effect with log_to_file() or a similar log target; \
+                 remove append() or change the log target"
+                    .to_string(),
+            ));
+        }
+        if self.writes_files() {
+            self.flwb.validate()?;
+        }
+        #[cfg(unix)]
+        {
+            let (stdout, stderr) = self.captured_streams;
+            if (stdout || stderr) && !self.writes_files() {
+                return Err(FlexiLoggerError::FileConfigConflict(
+                    "capture_stdout() and capture_stderr() need log_to_file() \
+                     or a similar log target"
+                        .to_string(),
+                ));
+            }
+            if (stdout && !matches!(self.duplicate_out, Duplicate::None))
+                || (stderr && !matches!(self.duplicate_err, Duplicate::None))
+            {
+                return Err(FlexiLoggerError::FileConfigConflict(
+                    "a captured stream cannot be used for duplication, \
+                     because the duplicated lines would be written to the log file again"
+                        .to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    fn writes_files(&self) -> bool {
+        matches!(
+            self.log_target,
+            LogTarget::File | LogTarget::FilePerThread | LogTarget::FileAndWriter(_)
+        )
+    }
+
+    /// Describes what the logger would do if it was started, without touching the filesystem
+    /// and without changing any global state.
+    ///
+    /// Applications can e.g. print the returned [`EffectiveConfig`](crate::EffectiveConfig)
+    /// at startup, and tests can check it.
+    ///
+    /// Settings from the environment (see [`use_env_overrides`](crate::Logger::use_env_overrides))
+    /// are not taken into account.
+    ///
+    /// # Errors
+    ///
+    /// The variants of `FlexiLoggerError` that `build()` returns for contradicting settings.
+    pub fn build_config(&self) -> Result<EffectiveConfig, FlexiLoggerError> {
+        self.validate()?;
+        let format_for_stderr = self.format_names.stderr.clone();
+        let format_for_stdout = self.format_names.stdout.clone();
+        // adaptive formats are only resolved in build()
+        #[cfg(feature = "atty")]
+        let format_for_stderr = self
+            .o_adaptive_format_for_stderr
+            .map_or(format_for_stderr, AdaptiveFormat::name);
+        #[cfg(feature = "atty")]
+        let format_for_stdout = self
+            .o_adaptive_format_for_stdout
+            .map_or(format_for_stdout, AdaptiveFormat::name);
+
+        let mut writers: Vec<String> = self.other_writers.keys().cloned().collect();
+        writers.sort();
+        Ok(EffectiveConfig {
+            spec: self.spec.to_string(),
+            target: match self.log_target {
+                LogTarget::StdErr => "stderr",
+                LogTarget::StdOut => "stdout",
+                LogTarget::File => "file",
+                LogTarget::FilePerThread => "file_per_thread",
+                LogTarget::Writer(_) => "writer",
+                LogTarget::FileAndWriter(_) => "file_and_writer",
+                LogTarget::DevNull => "devnull",
+            }
+            .to_string(),
+            file: if self.writes_files() {
+                Some(self.flwb.effective_config())
+            } else {
+                None
+            },
+            format_for_file: self.format_names.file.clone(),
+            format_for_stderr,
+            format_for_stdout,
+            format_for_writer: self.format_names.writer.clone(),
+            duplicate_to_stderr: format!("{:?}", self.duplicate_err),
+            duplicate_to_stdout: format!("{:?}", self.duplicate_out),
+            writers,
+        })
+    }
+
     /// Builds a boxed logger and a `LoggerHandle` for it,
     /// but does not initialize the global logger.
     ///
@@ -705,23 +1939,81 @@ impl Logger {
     /// # Errors
     ///
     /// Several variants of `FlexiLoggerError` can occur.
-    pub fn build(mut self) -> Result<(Box<dyn log::Log>, LoggerHandle), FlexiLoggerError> {
-        let max_level = self.spec.max_level();
-        let spec = Arc::new(RwLock::new(self.spec));
-        let other_writers = Arc::new(self.other_writers);
+    pub fn build(self) -> Result<(Box<dyn log::Log>, LoggerHandle), FlexiLoggerError> {
+        let (spec, backend, o_flush_wait) = self.build_backend()?;
+        let max_level = spec.max_level();
+        let spec = Arc::new(RwLock::new(spec));
+        let backend = Arc::new(RwLock::new(Arc::new(backend)));
+        let flexi_logger = FlexiLogger::new(Arc::clone(&spec), Arc::clone(&backend));
+        if let Some(wait_time) = o_flush_wait {
+            start_flusher(&backend, wait_time)?;
+        }
+
+        let handle = LoggerHandle::new(spec, backend);
+        handle.reconfigure(max_level);
+        Ok((Box::new(flexi_logger), handle))
+    }
+
+    // Creates the writers and applies the global settings;
+    // also returns the interval for the flusher thread, if one is needed.
+    pub(crate) fn build_backend(
+        mut self,
+    ) -> Result<(LogSpecification, Backend, Option<std::time::Duration>), FlexiLoggerError> {
+        if self.use_env_overrides {
+            self = self.apply_env_overrides(|name| std::env::var(name).ok())?;
+        }
+        self.validate()?;
+        crate::error_channel::set(self.error_channel.clone());
+        if let Some(offset) = self.format_settings.o_utc_offset {
+            if offset.abs() >= 86_400 {
+                return Err(FlexiLoggerError::UtcOffset(offset));
+            }
+        }
+        // Instant::now() panics in web builds, so the clock starts with the first use there
+        #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+        crate::deferred_now::start_clock();
+        // the writers are created (and e.g. name their files) with the settings of this logger
+        let format_settings = self.format_settings;
+        crate::formats::with_format_settings(format_settings, move || self.build_writers())
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn build_writers(
+        mut self,
+    ) -> Result<(LogSpecification, Backend, Option<std::time::Duration>), FlexiLoggerError> {
+        #[cfg(feature = "atty")]
+        {
+            if self.format_settings.color_choice != ColorChoice::Never {
+                // also covers the explicitly colored format functions
+                crate::formats::ansi_support();
+            }
+            if let Some(adaptive_format) = self.o_adaptive_format_for_stderr {
+                self.format_for_stderr = adaptive_format.format_function(Stream::StdErr);
+            }
+            if let Some(adaptive_format) = self.o_adaptive_format_for_stdout {
+                self.format_for_stdout = adaptive_format.format_function(Stream::StdOut);
+            }
+        }
+        let (stderr, stdout, o_flush_wait) = self.std_writers();
 
         #[cfg(feature = "colors")]
         crate::formats::set_palette(&self.o_palette)?;
 
+        let per_thread = matches!(self.log_target, LogTarget::FilePerThread);
         let primary_writer = Arc::new(match self.log_target {
-            LogTarget::File => {
-                self.flwb = self.flwb.format(self.format_for_file);
+            LogTarget::File | LogTarget::FilePerThread => {
+                self.flwb = self
+                    .flwb
+                    .format(self.format_for_file)
+                    .per_thread(per_thread);
                 PrimaryWriter::multi(
                     self.duplicate_err,
                     self.duplicate_out,
-                    self.format_for_stderr,
-                    self.format_for_stdout,
-                    vec![Box::new(self.flwb.try_build()?)],
+                    stderr,
+                    stdout,
+                    Some(self.flwb.try_build()?),
+                    vec![],
+                    self.broken_pipe,
                 )
             }
             LogTarget::Writer(mut w) => {
@@ -729,9 +2021,11 @@ impl Logger {
                 PrimaryWriter::multi(
                     self.duplicate_err,
                     self.duplicate_out,
-                    self.format_for_stderr,
-                    self.format_for_stdout,
+                    stderr,
+                    stdout,
+                    None,
                     vec![w],
+                    self.broken_pipe,
                 )
             }
             LogTarget::FileAndWriter(mut w) => {
@@ -740,52 +2034,82 @@ impl Logger {
                 PrimaryWriter::multi(
                     self.duplicate_err,
                     self.duplicate_out,
-                    self.format_for_stderr,
-                    self.format_for_stdout,
-                    vec![Box::new(self.flwb.try_build()?), w],
+                    stderr,
+                    stdout,
+                    Some(self.flwb.try_build()?),
+                    vec![w],
+                    self.broken_pipe,
                 )
             }
-            LogTarget::StdOut => {
-                PrimaryWriter::stdout(self.format_for_stdout, self.flwb.buffersize())
-            }
-            LogTarget::StdErr => {
-                PrimaryWriter::stderr(self.format_for_stderr, self.flwb.buffersize())
-            }
+            LogTarget::StdOut => PrimaryWriter::stdout(stdout),
+            LogTarget::StdErr => PrimaryWriter::stderr(stderr),
             LogTarget::DevNull => PrimaryWriter::black_hole(
                 self.duplicate_err,
                 self.duplicate_out,
-                self.format_for_stderr,
-                self.format_for_stdout,
+                stderr,
+                stdout,
+                self.broken_pipe,
             ),
         });
 
-        let flexi_logger = FlexiLogger::new(
-            Arc::clone(&spec),
-            Arc::clone(&primary_writer),
-            Arc::clone(&other_writers),
-        );
-
-        if let Some(wait_time) = self.o_flush_wait {
-            let pw = Arc::clone(&primary_writer);
-            let ows = Arc::clone(&other_writers);
-            std::thread::Builder::new()
-                .name("flexi_logger-flusher".to_string())
-                .stack_size(128)
-                .spawn(move || {
-                    let (_sender, receiver): (Sender<()>, Receiver<()>) = channel();
-                    loop {
-                        receiver.recv_timeout(wait_time).ok();
-                        pw.flush().ok();
-                        for w in ows.values() {
-                            w.flush().ok();
-                        }
-                    }
-                })?;
+        let backend = Backend {
+            primary_writer,
+            other_writers: self
+                .other_writers
+                .into_iter()
+                .map(|(name, writer)| (name, Arc::from(writer)))
+                .collect(),
+            fallthrough_writers: self.fallthrough_writers,
+            o_max_message_length: self.o_max_message_length,
+            middlewares: Arc::new(self.middlewares),
+            writer_levels: self.writer_levels,
+            format_settings: self.format_settings,
+            shut_down: Arc::new(AtomicBool::new(false)),
+        };
+        if let Err(e) = backend.health_check() {
+            backend.shutdown();
+            return Err(e);
         }
+        Ok((self.spec, backend, o_flush_wait))
+    }
 
-        let handle = LoggerHandle::new(spec, primary_writer, other_writers);
-        handle.reconfigure(max_level);
-        Ok((Box::new(flexi_logger), handle))
+    // Stderr and stdout as log target follow the buffer settings for files,
+    // unless a write mode is set explicitly.
+    // Also returns the interval for the flusher thread, if one is needed.
+    fn std_writers(&self) -> (StdErrWriter, StdOutWriter, Option<std::time::Duration>) {
+        let default_write_mode = |is_log_target: bool| match self.flwb.buffersize() {
+            Some(capacity) if is_log_target => WriteMode::Buffer(*capacity),
+            _ => WriteMode::Direct,
+        };
+        let write_mode_for_stderr = self
+            .o_write_mode_for_stderr
+            .unwrap_or_else(|| default_write_mode(matches!(self.log_target, LogTarget::StdErr)));
+        let write_mode_for_stdout = self
+            .o_write_mode_for_stdout
+            .unwrap_or_else(|| default_write_mode(matches!(self.log_target, LogTarget::StdOut)));
+        // the flusher thread also syncs the log file with SyncPolicy::Interval
+        let o_sync_interval = if self.writes_files() {
+            self.flwb.sync_interval()
+        } else {
+            None
+        };
+        let o_flush_wait = [
+            self.o_flush_wait,
+            write_mode_for_stderr.flush_interval(),
+            write_mode_for_stdout.flush_interval(),
+            o_sync_interval,
+        ]
+        .iter()
+        .flatten()
+        .min()
+        .copied();
+        (
+            StdErrWriter::new(self.format_for_stderr, &write_mode_for_stderr)
+                .style_overrides(self.o_style_overrides_for_stderr.clone()),
+            StdOutWriter::new(self.format_for_stdout, &write_mode_for_stdout)
+                .style_overrides(self.o_style_overrides_for_stdout.clone()),
+            o_flush_wait,
+        )
     }
 
     /// Consumes the Logger object and initializes `flexi_logger` in a way that
@@ -817,8 +2141,14 @@ impl Logger {
     /// will create the file `logspecification.toml` (if it does not yet exist) with this content:
     ///
     /// ```toml
+    /// ### Optional settings for the output:
+    /// #format = 'detailed'
+    /// #duplicate_to_stderr = 'warn'
+    /// #duplicate_to_stdout = 'none'
+    /// #palette = '196;208;-;7;8'
+    ///
     /// ### Optional: Default log level
-    /// global_level = 'info'
+    /// global_level = "info"
     /// ### Optional: specify a regular expression to suppress all messages that don't match
     /// #global_pattern = 'foo'
     ///
@@ -832,12 +2162,34 @@ impl Logger {
     /// You can subsequently edit and modify the file according to your needs,
     /// while the program is running, and it will immediately take your changes into account.
     ///
-    /// Currently only toml-files are supported, the file suffix thus must be `.toml`.
+    /// Besides the log specification, the file can optionally control the output:
+    ///
+    /// * `format` switches the format of the log file and of stderr and stdout to one of
+    ///   `default`, `detailed`, `opt`, `with_thread`, or `elapsed` (the output to stderr and stdout is
+    ///   colored if feature `colors` is active and the output goes to a terminal);
+    ///   the format of an additional writer is not changed,
+    /// * `duplicate_to_stderr` and `duplicate_to_stdout` change the duplication
+    ///   (see [`duplicate_to_stderr()`](crate::Logger::duplicate_to_stderr)), with one of
+    ///   `none`, `error`, `warn`, `info`, `debug`, `trace`, or `all`;
+    ///   this has no effect if stderr or stdout is the log target,
+    /// * `palette` changes the colors (see [`set_palette()`](crate::Logger::set_palette));
+    ///   it is ignored if feature `colors` is not active.
+    ///
+    /// Settings that are not specified in the file remain unchanged.
+    ///
+    /// Instead of toml, you can also use json or yaml, with the same structure
+    /// (see [`LogSpecification::from_json`](crate::LogSpecification::from_json) and
+    /// [`LogSpecification::from_yaml`](crate::LogSpecification::from_yaml)).
+    /// The format is chosen based on the file suffix, which must be `.toml`, `.json`,
+    /// `.yaml`, or `.yml`.
+    /// A json or yaml file that is created by `flexi_logger` contains no comments.
     ///
     /// The initial spec remains valid if the file cannot be read.
     ///
     /// If you update the specfile subsequently while the program is running, `flexi_logger`
-    /// re-reads it automatically and adapts its behavior according to the new content.
+    /// re-reads it automatically and adapts its behavior according to the new content
+    /// (with feature `specfile`; the file is watched with filesystem notifications, see
+    /// [`specfile_debounce_delay()`](crate::Logger::specfile_debounce_delay)).
     /// If the file cannot be read anymore, e.g. because the format is not correct, the
     /// previous logspec remains active.
     /// If the file is corrected subsequently, the log spec update will work again.
@@ -855,9 +2207,24 @@ impl Logger {
         self,
         specfile: P,
     ) -> Result<LoggerHandle, FlexiLoggerError> {
+        #[cfg(feature = "specfile")]
+        let debounce_delay = self.specfile_debounce_delay;
+        let log_panics = self.log_panics;
+        let o_self_diagnostics = self.o_self_diagnostics.clone();
+        #[cfg(unix)]
+        let captured_streams = self.captured_streams;
         // Make logging work, before caring for the specfile
         let (boxed_logger, handle) = self.build()?;
         log::set_boxed_logger(boxed_logger)?;
+        if log_panics {
+            crate::error_chain::log_panics();
+        }
+        start_self_diagnostics(o_self_diagnostics)?;
+        #[cfg(unix)]
+        start_capture(captured_streams, &handle)?;
+        #[cfg(feature = "specfile")]
+        setup_specfile(specfile, handle.clone(), debounce_delay)?;
+        #[cfg(not(feature = "specfile"))]
         setup_specfile(specfile, handle.clone())?;
         Ok(handle)
     }
@@ -885,19 +2252,61 @@ impl Logger {
         self,
         specfile: P,
     ) -> Result<(Box<dyn log::Log>, LoggerHandle), FlexiLoggerError> {
+        #[cfg(feature = "specfile")]
+        let debounce_delay = self.specfile_debounce_delay;
         let (boxed_log, handle) = self.build()?;
+        #[cfg(feature = "specfile")]
+        setup_specfile(specfile, handle.clone(), debounce_delay)?;
+        #[cfg(not(feature = "specfile"))]
         setup_specfile(specfile, handle.clone())?;
         Ok((boxed_log, handle))
     }
 }
 
+fn start_self_diagnostics(
+    o_self_diagnostics: Option<(log::Level, String)>,
+) -> Result<(), FlexiLoggerError> {
+    if let Some((level, target)) = o_self_diagnostics {
+        crate::diagnostics::start(level, target)?;
+        crate::diagnostics::emit(format_args!("event=started"));
+    }
+    Ok(())
+}
+
+// Redirects the captured streams into the log file.
+#[cfg(unix)]
+fn start_capture(
+    captured_streams: (bool, bool),
+    handle: &LoggerHandle,
+) -> Result<(), FlexiLoggerError> {
+    let (stdout, stderr) = captured_streams;
+    if stdout {
+        crate::std_capture::start(crate::std_capture::STDOUT, handle.clone())?;
+    }
+    if stderr {
+        crate::std_capture::start(crate::std_capture::STDERR, handle.clone())?;
+    }
+    Ok(())
+}
+
 #[cfg(feature = "specfile_without_notification")]
 fn setup_specfile<P: AsRef<std::path::Path>>(
     specfile: P,
     mut handle: LoggerHandle,
+    #[cfg(feature = "specfile")] debounce_delay: std::time::Duration,
 ) -> Result<(), FlexiLoggerError> {
+    if cfg!(all(target_arch = "wasm32", target_os = "unknown")) {
+        return Err(FlexiLoggerError::Unsupported("the specfile in a web build"));
+    }
     let specfile = specfile.as_ref().to_owned();
-    synchronize_handle_with_specfile(&mut handle, &specfile)?;
+    let format = SpecfileFormat::of(&specfile)?;
+    synchronize_handle_with_specfile(&mut handle, &specfile, format)?;
+    handle.set_specfile(
+        specfile
+            .canonicalize()
+            .map_err(FlexiLoggerError::SpecfileIo)?,
+        format,
+    );
 
     #[cfg(feature = "specfile")]
     {
@@ -908,8 +2317,7 @@ fn setup_specfile<P: AsRef<std::path::Path>>(
 
         // Watch the parent folder of the specfile, using debounced events
         let (tx, rx) = std::sync::mpsc::channel();
-        let debouncing_delay = std::time::Duration::from_millis(1000);
-        let mut watcher = watcher(tx, debouncing_delay)?;
+        let mut watcher = watcher(tx, debounce_delay)?;
         watcher.watch(&specfile.parent().unwrap(), RecursiveMode::NonRecursive)?;
 
         // in a separate thread, reread the specfile when it was updated
@@ -923,29 +2331,32 @@ fn setup_specfile<P: AsRef<std::path::Path>>(
                         Ok(debounced_event) => {
                             // println!("got debounced event {:?}", debounced_event);
                             match debounced_event {
+                                // editors and persist_spec_to_specfile() replace the file
                                 DebouncedEvent::Create(ref path)
-                                | DebouncedEvent::Write(ref path) => {
+                                | DebouncedEvent::Write(ref path)
+                                | DebouncedEvent::Rename(_, ref path) => {
                                     if path.canonicalize().map(|x| x == specfile).unwrap_or(false) {
-                                        match log_spec_string_from_file(&specfile)
+                                        if let Err(e) = log_spec_string_from_file(&specfile)
                                             .map_err(FlexiLoggerError::SpecfileIo)
-                                            .and_then(|s| LogSpecification::from_toml(&s))
+                                            .and_then(|s| format.parse(&s))
+                                            .and_then(|ff| apply_specfile_content(&mut handle, ff))
                                         {
-                                            Ok(spec) => handle.set_new_spec(spec),
-                                            Err(e) => eprintln!(
-                                            "[flexi_logger] rereading the log specification file \
+                                            crate::error_channel::report(format_args!(
+                                                "rereading the log specification file \
                                          failed with {:?}, \
                                          continuing with previous log specification",
-                                            e
-                                        ),
+                                                e
+                                            ));
                                         }
                                     }
                                 }
                                 _event => {}
                             }
                         }
-                        Err(e) => {
-                            eprintln!("[flexi_logger] error while watching the specfile: {:?}", e)
-                        }
+                        Err(e) => crate::error_channel::report(format_args!(
+                            "error while watching the specfile: {:?}",
+                            e
+                        )),
                     }
                 }
             })?;
@@ -959,22 +2370,11 @@ fn setup_specfile<P: AsRef<std::path::Path>>(
 pub(crate) fn synchronize_handle_with_specfile(
     handle: &mut LoggerHandle,
     specfile: &std::path::PathBuf,
+    format: SpecfileFormat,
 ) -> Result<(), FlexiLoggerError> {
-    if specfile
-        .extension()
-        .unwrap_or_else(|| std::ffi::OsStr::new(""))
-        .to_str()
-        .unwrap_or("")
-        != "toml"
-    {
-        return Err(FlexiLoggerError::SpecfileExtension(
-            "only spec files with extension toml are supported",
-        ));
-    }
-
     if std::path::Path::is_file(specfile) {
         let s = log_spec_string_from_file(specfile).map_err(FlexiLoggerError::SpecfileIo)?;
-        handle.set_new_spec(LogSpecification::from_toml(&s)?);
+        apply_specfile_content(handle, format.parse(&s)?)?;
     } else {
         if let Some(specfolder) = specfile.parent() {
             std::fs::DirBuilder::new()
@@ -988,15 +2388,229 @@ pub(crate) fn synchronize_handle_with_specfile(
             .open(specfile)
             .map_err(FlexiLoggerError::SpecfileIo)?;
 
-        handle
-            .current_spec()
-            .read()
-            .map_err(|_e| FlexiLoggerError::Poison)?
-            .to_toml(&mut file)?;
+        format.write(&handle.current_spec(), &mut file)?;
+    }
+    Ok(())
+}
+
+// Writes the given log specification into the specfile,
+// and keeps the writer settings that the file contains.
+#[cfg(feature = "specfile_without_notification")]
+pub(crate) fn write_spec_to_specfile(
+    specfile: &std::path::Path,
+    format: SpecfileFormat,
+    spec: &LogSpecification,
+) -> Result<(), FlexiLoggerError> {
+    let settings = if specfile.is_file() {
+        format.parse(&log_spec_string_from_file(specfile).map_err(FlexiLoggerError::SpecfileIo)?)?
+    } else {
+        LogSpecFileFormat::default()
+    };
+    let mut buffer = Vec::<u8>::new();
+    format.write_with_settings(spec, &settings, &mut buffer)?;
+    // replace the file in one step, so that neither the watcher nor a crash
+    // leave a partial content behind
+    let mut tmp_name = specfile
+        .file_name()
+        .unwrap_or_else(|| std::ffi::OsStr::new("specfile"))
+        .to_os_string();
+    tmp_name.push(format!(".{}.tmp", std::process::id()));
+    let tmp_file = specfile.with_file_name(tmp_name);
+    std::fs::write(&tmp_file, buffer)
+        .and_then(|()| std::fs::rename(&tmp_file, specfile))
+        .map_err(|e| {
+            std::fs::remove_file(&tmp_file).ok();
+            FlexiLoggerError::SpecfileIo(e)
+        })
+}
+
+// The name that build_config() shows for format functions that were given directly.
+const CUSTOM_FORMAT: &str = "custom";
+
+// The names of the formats; a format that is chosen by name, e.g. in the configuration file,
+// keeps its name, a format function that is given directly is shown as `custom`.
+struct FormatNames {
+    file: String,
+    stderr: String,
+    stdout: String,
+    writer: String,
+}
+impl FormatNames {
+    fn custom() -> Self {
+        Self {
+            file: CUSTOM_FORMAT.to_string(),
+            stderr: CUSTOM_FORMAT.to_string(),
+            stdout: CUSTOM_FORMAT.to_string(),
+            writer: CUSTOM_FORMAT.to_string(),
+        }
+    }
+}
+impl Default for FormatNames {
+    fn default() -> Self {
+        Self {
+            file: "default".to_string(),
+            stderr: "default".to_string(),
+            stdout: "default".to_string(),
+            writer: "default".to_string(),
+        }
+    }
+}
+
+// The supported formats of the specfile, determined by the file extension.
+#[cfg(feature = "specfile_without_notification")]
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum SpecfileFormat {
+    Toml,
+    Json,
+    Yaml,
+}
+#[cfg(feature = "specfile_without_notification")]
+impl SpecfileFormat {
+    pub(crate) fn of(specfile: &std::path::Path) -> Result<Self, FlexiLoggerError> {
+        match specfile
+            .extension()
+            .unwrap_or_else(|| std::ffi::OsStr::new(""))
+            .to_str()
+            .unwrap_or("")
+        {
+            "toml" => Ok(Self::Toml),
+            "json" => Ok(Self::Json),
+            "yaml" | "yml" => Ok(Self::Yaml),
+            _ => Err(FlexiLoggerError::SpecfileExtension(
+                "only spec files with extension toml, json, yaml, or yml are supported",
+            )),
+        }
+    }
+
+    pub(crate) fn parse(self, s: &str) -> Result<LogSpecFileFormat, FlexiLoggerError> {
+        match self {
+            Self::Toml => LogSpecFileFormat::from_toml(s),
+            Self::Json => LogSpecFileFormat::from_json(s),
+            Self::Yaml => LogSpecFileFormat::from_yaml(s),
+        }
+    }
+
+    pub(crate) fn write(
+        self,
+        spec: &LogSpecification,
+        w: &mut dyn std::io::Write,
+    ) -> Result<(), FlexiLoggerError> {
+        self.write_with_settings(spec, &LogSpecFileFormat::default(), w)
+    }
+
+    // Writes the log specification together with the writer settings
+    // that are given in `settings`.
+    pub(crate) fn write_with_settings(
+        self,
+        spec: &LogSpecification,
+        settings: &LogSpecFileFormat,
+        w: &mut dyn std::io::Write,
+    ) -> Result<(), FlexiLoggerError> {
+        match self {
+            Self::Toml => {
+                // top-level keys must precede the tables of the log specification
+                w.write_all(b"### Optional settings for the output:\n")
+                    .map_err(FlexiLoggerError::SpecfileIo)?;
+                for (key, o_value, example) in &[
+                    ("format", &settings.format, "detailed"),
+                    ("duplicate_to_stderr", &settings.duplicate_to_stderr, "warn"),
+                    ("duplicate_to_stdout", &settings.duplicate_to_stdout, "none"),
+                    ("palette", &settings.palette, "196;208;-;7;8"),
+                ] {
+                    match o_value {
+                        Some(value) => writeln!(w, "{} = {}", key, toml_string(value)),
+                        None => writeln!(w, "#{} = '{}'", key, example),
+                    }
+                    .map_err(FlexiLoggerError::SpecfileIo)?;
+                }
+                w.write_all(b"\n").map_err(FlexiLoggerError::SpecfileIo)?;
+                spec.to_toml(w)
+            }
+            Self::Json => {
+                serde_json::to_writer_pretty(&mut *w, &settings.with_spec(spec))?;
+                w.write_all(b"\n").map_err(FlexiLoggerError::SpecfileIo)
+            }
+            Self::Yaml => Ok(serde_yaml::to_writer(w, &settings.with_spec(spec))?),
+        }
+    }
+}
+
+// Applies the content of a specfile: the log specification,
+// and those writer settings that are specified.
+// All values are validated before anything is changed.
+#[cfg(feature = "specfile_without_notification")]
+fn apply_specfile_content(
+    handle: &mut LoggerHandle,
+    mut logspec_ff: LogSpecFileFormat,
+) -> Result<(), FlexiLoggerError> {
+    let o_formats = match logspec_ff.format.take() {
+        Some(name) => Some(
+            crate::formats::with_format_settings(handle.format_settings(), || {
+                crate::formats::formats_for_name(&name)
+            })
+            .ok_or_else(|| {
+                FlexiLoggerError::SpecfileValue(format!("unknown format \"{}\"", name))
+            })?,
+        ),
+        None => None,
+    };
+    let o_duplicate_err = match logspec_ff.duplicate_to_stderr.take() {
+        Some(s) => unless_captured(Duplicate::from_name(&s)?, "duplicate_to_stderr", false),
+        None => None,
+    };
+    let o_duplicate_out = match logspec_ff.duplicate_to_stdout.take() {
+        Some(s) => unless_captured(Duplicate::from_name(&s)?, "duplicate_to_stdout", true),
+        None => None,
+    };
+    #[cfg(feature = "colors")]
+    let o_palette = logspec_ff.palette.take().map(PaletteInput::String);
+    let spec = LogSpecification::from_file_format(logspec_ff)?;
+
+    #[cfg(feature = "colors")]
+    {
+        if o_palette.is_some() {
+            crate::formats::set_palette(&o_palette)?;
+        }
     }
+    let primary_writer = handle.primary_writer();
+    if let Some((format_for_file, format_for_stderr, format_for_stdout)) = o_formats {
+        primary_writer.set_formats(format_for_file, format_for_stderr, format_for_stdout);
+    }
+    if let Some(duplicate) = o_duplicate_err {
+        primary_writer.set_duplicate_to_stderr(duplicate);
+    }
+    if let Some(duplicate) = o_duplicate_out {
+        primary_writer.set_duplicate_to_stdout(duplicate);
+    }
+    handle.set_new_spec(spec);
     Ok(())
 }
 
+// Rejects duplication to a captured stream, like Logger::validate() does at startup,
+// because the duplicated lines would be written to the log file again;
+// the rejected value is reported and the current duplication is kept.
+#[cfg(feature = "specfile_without_notification")]
+#[cfg_attr(not(unix), allow(unused_variables))]
+fn unless_captured(duplicate: Duplicate, name: &str, stdout: bool) -> Option<Duplicate> {
+    #[cfg(unix)]
+    {
+        let fd = if stdout {
+            crate::std_capture::STDOUT
+        } else {
+            crate::std_capture::STDERR
+        };
+        if !matches!(duplicate, Duplicate::None) && crate::std_capture::is_captured(fd) {
+            crate::error_channel::report(format_args!(
+                "the specfile value {} = {:?} is ignored, \
+                 because the stream is captured into the log file",
+                name, duplicate
+            ));
+            return None;
+        }
+    }
+    Some(duplicate)
+}
+
 #[cfg(feature = "specfile_without_notification")]
 pub(crate) fn log_spec_string_from_file<P: AsRef<std::path::Path>>(
     specfile: P,
@@ -1008,7 +2622,10 @@ pub(crate) fn log_spec_string_from_file<P: AsRef<std::path::Path>>(
 }
 
 /// Used to control which messages are to be duplicated to stderr, when `log_to_file()` is used.
-#[derive(Debug)]
+///
+/// In configuration files, the values are written in lower case, e.g. `"warn"`,
+/// or, for [`Duplicate::Between`], as range, e.g. `"warn..info"`.
+#[derive(Clone, Debug)]
 pub enum Duplicate {
     /// No messages are duplicated.
     None,
@@ -1024,4 +2641,96 @@ pub enum Duplicate {
     Trace,
     /// All messages are duplicated.
     All,
+    /// The messages with a level between the two given levels, including these,
+    /// are duplicated.
+    ///
+    /// This allows e.g. a command line tool to write errors to stderr,
+    /// and warnings and infos to stdout:
+    ///
+    /// ```rust
+    /// # use flexi_logger::{Duplicate, Level, Logger};
+    /// let logger = Logger::with_str("info")
+    ///     .log_to_file()
+    ///     .duplicate_to_stderr(Duplicate::Error)
+    ///     .duplicate_to_stdout(Duplicate::Between(Level::Warn, Level::Info));
+    /// ```
+    ///
+    /// In the specfile, in configuration files, and in the environment,
+    /// this is written as e.g. `warn..info`.
+    Between(log::Level, log::Level),
+}
+impl Duplicate {
+    // Used for the values in the specfile and in the environment.
+    pub(crate) fn from_name(s: &str) -> Result<Self, FlexiLoggerError> {
+        let unknown =
+            || FlexiLoggerError::SpecfileValue(format!("unknown duplication level \"{}\"", s));
+        let lower = s.to_lowercase();
+        if let Some(index) = lower.find("..") {
+            return Ok(Self::Between(
+                lower[..index].trim().parse().map_err(|_| unknown())?,
+                lower[index + 2..].trim().parse().map_err(|_| unknown())?,
+            ));
+        }
+        match lower.as_str() {
+            "none" | "off" => Ok(Self::None),
+            "error" => Ok(Self::Error),
+            "warn" => Ok(Self::Warn),
+            "info" => Ok(Self::Info),
+            "debug" => Ok(Self::Debug),
+            "trace" => Ok(Self::Trace),
+            "all" => Ok(Self::All),
+            _ => Err(unknown()),
+        }
+    }
+}
+#[cfg(feature = "config_file")]
+impl<'de> serde::Deserialize<'de> for Duplicate {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name: String = serde::Deserialize::deserialize(deserializer)?;
+        Self::from_name(&name).map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{Duplicate, FlexiLoggerError, Logger};
+    use std::collections::HashMap;
+
+    fn env(vars: &[(&'static str, &'static str)]) -> impl Fn(&str) -> Option<String> {
+        let vars: HashMap<&str, &str> = vars.iter().copied().collect();
+        move |name| vars.get(name).map(|value| (*value).to_string())
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        // invalid values let the initialization fail
+        for vars in &[
+            [("FLEXI_LOGGER_FORMAT", "fancy")],
+            [("FLEXI_LOGGER_ROTATE_SIZE", "big")],
+            [("FLEXI_LOGGER_KEEP_LOG_FILES", "2")],
+            [("FLEXI_LOGGER_DUPLICATE_TO_STDERR", "warn..nonsense")],
+        ] {
+            match Logger::with_str("info").apply_env_overrides(env(vars)) {
+                Err(FlexiLoggerError::EnvOverride(_)) => {}
+                _ => panic!("invalid value in {:?} was not detected", vars),
+            }
+        }
+
+        let logger = Logger::with_str("info")
+            .log_to_file()
+            .apply_env_overrides(env(&[
+                ("FLEXI_LOGGER_FORMAT", "detailed"),
+                ("FLEXI_LOGGER_DIRECTORY", "log_files/env_overrides"),
+                ("FLEXI_LOGGER_ROTATE_SIZE", "100000"),
+                ("FLEXI_LOGGER_KEEP_LOG_FILES", "2"),
+                ("FLEXI_LOGGER_DUPLICATE_TO_STDERR", "warn..info"),
+            ]))
+            .unwrap();
+        match logger.duplicate_err {
+            Duplicate::Between(log::Level::Warn, log::Level::Info) => {}
+            ref d => panic!("unexpected duplication {:?}", d),
+        }
+        logger.build().unwrap();
+        assert!(std::path::Path::new("log_files/env_overrides").is_dir());
+    }
 }
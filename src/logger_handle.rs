@@ -1,8 +1,17 @@
+use crate::flexi_logger::{current_backend, modify_backend, start_flusher, SharedBackend};
 use crate::log_specification::LogSpecification;
+#[cfg(feature = "specfile_without_notification")]
+use crate::logger::SpecfileFormat;
+#[cfg(feature = "specfile_without_notification")]
 use crate::primary_writer::PrimaryWriter;
-use crate::writers::LogWriter;
-use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use crate::stats::LogStats;
+use crate::writers::{CleanupCandidate, LogWriter};
+use crate::{FlexiLoggerError, Logger};
+#[cfg(feature = "specfile_without_notification")]
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex, RwLock};
+
+type SpecChangeCallback = Arc<dyn Fn(&LogSpecification) + Send + Sync>;
 
 /// Allows reconfiguring the logger programmatically.
 ///
@@ -53,50 +62,187 @@ use std::sync::{Arc, RwLock};
 pub struct LoggerHandle {
     spec: Arc<RwLock<LogSpecification>>,
     spec_stack: Vec<LogSpecification>,
-    primary_writer: Arc<PrimaryWriter>,
-    other_writers: Arc<HashMap<String, Box<dyn LogWriter>>>,
+    // shared with the logger, and exchanged with `replace()`
+    backend: SharedBackend,
+    // shared with the clones of the handle, e.g. the one that watches the specfile
+    spec_change_callbacks: Arc<Mutex<Vec<SpecChangeCallback>>>,
+    // the max levels of the active thread specs
+    thread_spec_levels: Arc<Mutex<Vec<log::LevelFilter>>>,
+    // the specfile, if the logger was started with one
+    #[cfg(feature = "specfile_without_notification")]
+    o_specfile: Arc<Mutex<Option<(PathBuf, SpecfileFormat)>>>,
 }
 impl LoggerHandle {
-    pub(crate) fn new(
-        spec: Arc<RwLock<LogSpecification>>,
-        primary_writer: Arc<PrimaryWriter>,
-        other_writers: Arc<HashMap<String, Box<dyn LogWriter>>>,
-    ) -> Self {
+    pub(crate) fn new(spec: Arc<RwLock<LogSpecification>>, backend: SharedBackend) -> Self {
         Self {
             spec,
             spec_stack: Vec::default(),
-            primary_writer,
-            other_writers,
+            backend,
+            spec_change_callbacks: Arc::new(Mutex::new(Vec::new())),
+            thread_spec_levels: Arc::new(Mutex::new(Vec::new())),
+            #[cfg(feature = "specfile_without_notification")]
+            o_specfile: Arc::new(Mutex::new(None)),
         }
     }
 
     #[cfg(feature = "specfile_without_notification")]
-    pub(crate) fn current_spec(&self) -> Arc<RwLock<LogSpecification>> {
-        Arc::clone(&self.spec)
+    pub(crate) fn set_specfile(&self, specfile: PathBuf, format: SpecfileFormat) {
+        *self.o_specfile.lock().unwrap(/* catch and expose error? */) = Some((specfile, format));
     }
 
     //
     pub(crate) fn reconfigure(&self, mut max_level: log::LevelFilter) {
-        for w in self.other_writers.as_ref().values() {
-            max_level = std::cmp::max(max_level, w.max_log_level());
+        let backend = current_backend(&self.backend);
+        for (name, w) in &backend.other_writers {
+            max_level = std::cmp::max(max_level, backend.writer_max_level(name, &**w));
+        }
+        if let Ok(thread_spec_levels) = self.thread_spec_levels.lock() {
+            for level in thread_spec_levels.iter() {
+                max_level = std::cmp::max(max_level, *level);
+            }
         }
         log::set_max_level(max_level);
     }
 
+    // Used to apply the writer settings of a specfile.
+    #[cfg(feature = "specfile_without_notification")]
+    pub(crate) fn primary_writer(&self) -> Arc<PrimaryWriter> {
+        Arc::clone(&current_backend(&self.backend).primary_writer)
+    }
+
+    // Used to resolve the format names of a specfile with the color choice of this logger.
+    #[cfg(feature = "specfile_without_notification")]
+    pub(crate) fn format_settings(&self) -> crate::formats::FormatSettings {
+        current_backend(&self.backend).format_settings
+    }
+
     /// Replaces the active `LogSpecification`.
     pub fn set_new_spec(&mut self, new_spec: LogSpecification) {
         let max_level = new_spec.max_level();
         self.spec.write().unwrap(/* catch and expose error? */).update_from(new_spec);
         self.reconfigure(max_level);
+        self.notify_spec_change();
+    }
+
+    /// Registers a callback that is invoked whenever the effective `LogSpecification` changes,
+    /// be it with the methods of this handle (including the temporary specifications),
+    /// or via the specfile.
+    ///
+    /// The callback is called with the new effective `LogSpecification`,
+    /// in the thread that changed the specification.
+    /// This allows mirroring the log level into other subsystems,
+    /// e.g. for enabling the verbose mode of a C library.
+    pub fn on_spec_change<F>(&self, callback: F)
+    where
+        F: Fn(&LogSpecification) + Send + Sync + 'static,
+    {
+        self.spec_change_callbacks
+            .lock()
+            .unwrap(/* catch and expose error? */)
+            .push(Arc::new(callback));
+    }
+
+    fn notify_spec_change(&self) {
+        if crate::diagnostics::is_active() {
+            crate::diagnostics::emit(format_args!(
+                "event=spec_changed spec={:?}",
+                self.current_spec().to_string()
+            ));
+        }
+        // the callbacks are invoked without holding the lock,
+        // so that they can e.g. register further callbacks
+        let callbacks: Vec<SpecChangeCallback> = self
+            .spec_change_callbacks
+            .lock()
+            .unwrap(/* catch and expose error? */)
+            .clone();
+        if !callbacks.is_empty() {
+            let current_spec = self.current_spec();
+            for callback in callbacks.iter() {
+                callback(&current_spec);
+            }
+        }
+    }
+
+    /// Lowers the maximum level of the additional writer with the given name,
+    /// see [`Logger::max_level_for_writer`](crate::Logger::max_level_for_writer).
+    ///
+    /// `log::set_max_level` is recomputed immediately.
+    /// `None` removes the override, so that the writer's own maximum level applies again.
+    pub fn set_max_level_for_writer(&self, name: &str, o_max_level: Option<log::LevelFilter>) {
+        modify_backend(&self.backend, |backend| {
+            match o_max_level {
+                Some(max_level) => backend.writer_levels.insert(name.to_string(), max_level),
+                None => backend.writer_levels.remove(name),
+            };
+        });
+        self.reconfigure(self.spec.read().unwrap(/* catch and expose error? */).max_level());
+    }
+
+    /// Registers a `LogWriter` implementation under the given target name
+    /// while the logger is running, like [`Logger::add_writer`](crate::Logger::add_writer)
+    /// does before the start.
+    ///
+    /// This allows e.g. plugins that are loaded at runtime to register their own
+    /// log destinations. A writer that was registered under the same name before
+    /// is replaced, and shut down.
+    /// `log::set_max_level` is recomputed immediately.
+    ///
+    /// The target name must not start with an underscore.
+    pub fn add_writer<S: Into<String>>(&self, target_name: S, writer: Box<dyn LogWriter>) {
+        let writer: Arc<dyn LogWriter> = Arc::from(writer);
+        let o_replaced = modify_backend(&self.backend, |backend| {
+            backend.other_writers.insert(target_name.into(), writer)
+        });
+        if let Some(replaced) = o_replaced {
+            replaced.flush().ok();
+            replaced.shutdown();
+        }
+        self.reconfigure(self.spec.read().unwrap(/* catch and expose error? */).max_level());
+    }
+
+    /// Removes the `LogWriter` with the given target name, flushes it, and shuts it down.
+    ///
+    /// Subsequent log calls that address the target name are treated like calls
+    /// that address an unknown writer; log calls that run at the same time in other threads
+    /// can still reach the removed writer.
+    /// `log::set_max_level` is recomputed immediately.
+    ///
+    /// Returns false if no writer with the given name was registered.
+    pub fn remove_writer(&self, target_name: &str) -> bool {
+        let o_removed = modify_backend(&self.backend, |backend| {
+            backend.other_writers.remove(target_name)
+        });
+        match o_removed {
+            Some(removed) => {
+                removed.flush().ok();
+                removed.shutdown();
+                self.reconfigure(
+                    self.spec.read().unwrap(/* catch and expose error? */).max_level(),
+                );
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Returns the maximum level that is currently set with `log::set_max_level`.
+    ///
+    /// It is the highest level that the log specification, the additional writers,
+    /// and the active thread specifications accept; all log calls with a higher level
+    /// are discarded by the `log` macros without reaching the logger.
+    #[must_use]
+    pub fn effective_max_level(&self) -> log::LevelFilter {
+        log::max_level()
     }
 
     /// Tries to replace the active `LogSpecification` with the result from parsing the given String.
     pub fn parse_new_spec(&mut self, spec: &str) {
         self.set_new_spec(LogSpecification::parse(spec).unwrap_or_else(|e| {
-            eprintln!(
-                "[flexi_logger] LoggerHandle::parse_new_spec(): failed with {}",
+            crate::error_channel::report(format_args!(
+                "LoggerHandle::parse_new_spec(): failed with {}",
                 e
-            );
+            ));
             LogSpecification::off()
         }))
     }
@@ -114,15 +260,82 @@ impl LoggerHandle {
         self.spec_stack
             .push(self.spec.read().unwrap(/* catch and expose error? */).clone());
         self.set_new_spec(LogSpecification::parse(new_spec).unwrap_or_else(|e| {
-            eprintln!(
-                "[flexi_logger] LoggerHandle::parse_new_spec(): failed with {}, \
+            crate::error_channel::report(format_args!(
+                "LoggerHandle::parse_new_spec(): failed with {}, \
                  falling back to empty log spec",
                 e
-            );
+            ));
             LogSpecification::off()
         }));
     }
 
+    /// Tries to replace the active `LogSpecification` with the result from parsing the given String,
+    /// like [`parse_and_push_temp_spec()`](crate::LoggerHandle::parse_and_push_temp_spec),
+    /// and returns a guard that reverts to the previous `LogSpecification` when it is dropped.
+    ///
+    /// So a forgotten [`pop_temp_spec()`](crate::LoggerHandle::pop_temp_spec) cannot leave
+    /// the process with the temporary specification.
+    /// The guard gives access to the handle; temporary specifications that are pushed
+    /// via the guard and not popped are reverted as well.
+    ///
+    /// ```rust
+    /// # use flexi_logger::Logger;
+    /// # let mut logger = Logger::with_str("info")
+    /// #     .start()
+    /// #     .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+    /// {
+    ///     let _guard = logger.scoped_spec("mymod=trace");
+    ///     // critical calls
+    /// }
+    /// // Continue with the log spec you had before.
+    /// ```
+    pub fn scoped_spec(&mut self, spec: &str) -> ScopedSpecGuard<'_> {
+        let depth = self.spec_stack.len();
+        self.parse_and_push_temp_spec(spec);
+        ScopedSpecGuard {
+            handle: self,
+            depth,
+        }
+    }
+
+    /// Activates the given `LogSpecification` only for the current thread,
+    /// until the returned guard is dropped.
+    ///
+    /// While the guard exists, the log records of the current thread are filtered with
+    /// this specification instead of the one that is effective for all other threads.
+    /// This allows e.g. a server to turn on trace logging for the worker thread of a single
+    /// request, without flooding the log with the traces of all other requests.
+    ///
+    /// Thread specifications can be nested; dropping the guard reactivates the previous one.
+    /// The guard is bound to the thread and cannot be sent to other threads.
+    ///
+    /// ```rust
+    /// # use flexi_logger::{LogSpecification, Logger};
+    /// # let logger = Logger::with_str("info")
+    /// #     .start()
+    /// #     .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+    /// {
+    ///     let _guard = logger.thread_spec(LogSpecification::parse("trace").unwrap());
+    ///     // handle the request
+    /// }
+    /// ```
+    #[must_use]
+    pub fn thread_spec(&self, spec: LogSpecification) -> ThreadSpecGuard {
+        let max_level = spec.max_level();
+        let o_previous_spec = crate::flexi_logger::replace_thread_spec(Some(Arc::new(spec)));
+        self.thread_spec_levels
+            .lock()
+            .unwrap(/* catch and expose error? */)
+            .push(max_level);
+        self.reconfigure(self.spec.read().unwrap(/* catch and expose error? */).max_level());
+        ThreadSpecGuard {
+            handle: self.clone(),
+            max_level,
+            o_previous_spec,
+            _not_send: std::marker::PhantomData,
+        }
+    }
+
     /// Reverts to the previous `LogSpecification`, if any.
     pub fn pop_temp_spec(&mut self) {
         if let Some(previous_spec) = self.spec_stack.pop() {
@@ -130,12 +343,95 @@ impl LoggerHandle {
         }
     }
 
+    /// Returns a copy of the currently effective `LogSpecification`.
+    ///
+    /// This reflects all changes done with the methods of this handle, including the temporary
+    /// specifications that were pushed with
+    /// [`push_temp_spec()`](crate::LoggerHandle::push_temp_spec) and not yet popped,
+    /// and changes done via the specfile.
+    /// Use its `Display` implementation to get a readable representation,
+    /// e.g. for an admin endpoint.
+    #[must_use]
+    pub fn current_spec(&self) -> LogSpecification {
+        self.spec.read().unwrap(/* catch and expose error? */).clone()
+    }
+
+    /// Writes the currently effective log specification into the specfile,
+    /// so that changes done with the methods of this handle survive a restart of the program.
+    ///
+    /// The writer settings that the specfile contains (e.g. `format`) are kept.
+    /// Note that comments in the specfile are not preserved.
+    ///
+    /// This method is only available with feature `specfile`.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::NoSpecfile` if the logger was not started with
+    /// [`Logger::start_with_specfile`](crate::Logger::start_with_specfile)
+    /// or [`Logger::build_with_specfile`](crate::Logger::build_with_specfile),
+    /// `FlexiLoggerError::SpecfileIo` if the specfile cannot be read or written,
+    /// and the parse errors of the specfile's format if its current content is invalid.
+    #[cfg(feature = "specfile_without_notification")]
+    pub fn persist_spec_to_specfile(&self) -> Result<(), FlexiLoggerError> {
+        let (specfile, format) = self
+            .o_specfile
+            .lock()
+            .map_err(|_| FlexiLoggerError::Poison)?
+            .clone()
+            .ok_or(FlexiLoggerError::NoSpecfile)?;
+        crate::logger::write_spec_to_specfile(&specfile, format, &self.current_spec())
+    }
+
+    /// Returns the number of temporary specifications that were pushed with
+    /// [`push_temp_spec()`](crate::LoggerHandle::push_temp_spec)
+    /// or [`parse_and_push_temp_spec()`](crate::LoggerHandle::parse_and_push_temp_spec)
+    /// and not yet popped.
+    #[must_use]
+    pub fn temp_spec_depth(&self) -> usize {
+        self.spec_stack.len()
+    }
+
+    /// Returns a snapshot of the logging statistics of the program,
+    /// e.g. for exporting them via a metrics endpoint.
+    ///
+    /// The counters cover all writers: records per level, bytes written to files,
+    /// stderr, and stdout, records dropped by writers with bounded buffers,
+    /// write errors, and rotations of log files.
+    #[must_use]
+    pub fn stats(&self) -> LogStats {
+        LogStats::snapshot()
+    }
+
+    /// Checks all writers, see [`LogWriter::health_check`](crate::writers::LogWriter::health_check),
+    /// e.g. for the readiness probe of a service.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::WriterHealthCheck` for the first writer that reports an error;
+    /// the primary writer is reported with the name `_Default`.
+    pub fn health_check(&self) -> Result<(), FlexiLoggerError> {
+        current_backend(&self.backend).health_check()
+    }
+
+    /// Writes preformatted output directly to the log file, bypassing the format function,
+    /// e.g. the output of a child process or of a library with its own logging.
+    ///
+    /// The bytes take part in rotation, cleanup, and buffering like the formatted records.
+    /// Every call should hand over complete lines, because the file may be rotated
+    /// between two calls; a missing line break at the end is added.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::NoFileLog` if the logger does not write to a file,
+    /// `FlexiLoggerError::OutputIo` if writing fails.
+    pub fn write_raw(&self, buf: &[u8]) -> Result<(), FlexiLoggerError> {
+        let backend = current_backend(&self.backend);
+        backend.with_settings(|| backend.primary_writer.write_raw(buf))
+    }
+
     /// Flush all writers.
     pub fn flush(&self) {
-        self.primary_writer.flush().ok();
-        for writer in self.other_writers.values() {
-            writer.flush().ok();
-        }
+        current_backend(&self.backend).flush();
     }
 
     /// Shutdown all participating writers.
@@ -151,20 +447,148 @@ impl LoggerHandle {
     ///   without correctly shutting down the cleanup-thread, then you might stop the cleanup-thread
     ///   while it is compressing a log file, which can leave unexpected files in the filesystem)
     ///
+    /// The cleanup-thread is given the time that is configured with
+    /// [`Logger::cleanup_timeout`](crate::Logger::cleanup_timeout) to finish its pending work.
+    ///
+    /// All writers are flushed before they are shut down.
+    /// Repeated calls, also from clones of this handle, have no further effect.
+    ///
     /// See also [`LogWriter::shutdown`](crate::writers::LogWriter::shutdown).
     pub fn shutdown(&self) {
-        self.primary_writer.flush().ok();
-        if let PrimaryWriter::Multi(writer) = &*self.primary_writer {
-            writer.shutdown();
-        }
-        for writer in self.other_writers.values() {
-            writer.shutdown();
+        crate::diagnostics::emit(format_args!("event=shutdown"));
+        crate::diagnostics::drain();
+        #[cfg(unix)]
+        crate::std_capture::stop();
+        current_backend(&self.backend).shutdown();
+    }
+
+    /// Returns what the cleanup of the file log writer would do now with the rotated files,
+    /// under the configured [`Cleanup`](crate::Cleanup) strategy, without doing it.
+    ///
+    /// This helps verifying retention settings before enabling them in production.
+    /// Additional writers are not considered; use
+    /// [`FileLogWriter::list_cleanup_candidates`](crate::writers::FileLogWriter::list_cleanup_candidates)
+    /// for them.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::OutputIo` if the files cannot be listed,
+    /// `FlexiLoggerError::Poison` if the file log writer is unusable after a panic.
+    pub fn list_cleanup_candidates(&self) -> Result<Vec<CleanupCandidate>, FlexiLoggerError> {
+        current_backend(&self.backend)
+            .primary_writer
+            .cleanup_candidates()
+    }
+
+    /// Waits until the cleanup-thread of the file log writer has done the work that was
+    /// requested so far (compressing and deleting rotated log files),
+    /// but not longer than the given timeout.
+    ///
+    /// Returns false if the timeout elapsed before the cleanup was finished.
+    /// Additional writers are not considered;
+    /// use [`FileLogWriter::wait_for_cleanup`](crate::writers::FileLogWriter::wait_for_cleanup)
+    /// for them.
+    #[must_use]
+    pub fn wait_for_cleanup(&self, timeout: std::time::Duration) -> bool {
+        current_backend(&self.backend)
+            .primary_writer
+            .wait_for_cleanup(timeout)
+    }
+
+    /// Replaces the complete configuration of the running logger with the one of the
+    /// given `Logger`: log specification, log target, writers, and formats.
+    ///
+    /// Since `log::set_logger` can be called only once per process, a long-running program
+    /// can use this method to apply a changed configuration, e.g. after receiving `SIGHUP`,
+    /// without a restart.
+    ///
+    /// The new writers are created first; if this fails, the running logger remains
+    /// unchanged and the error is returned.
+    /// Otherwise the new writers take over atomically, and the previous writers are flushed
+    /// and shut down. Temporary log specifications are discarded.
+    ///
+    /// Settings that are global to the process, like the error channel,
+    /// are also taken from the given `Logger`; watching a specfile is not supported here,
+    /// a specfile watcher of the initial configuration remains active.
+    ///
+    /// # Errors
+    ///
+    /// The same as for [`Logger::start`](crate::Logger::start).
+    pub fn replace(&mut self, logger: Logger) -> Result<(), FlexiLoggerError> {
+        let (spec, backend, o_flush_wait) = logger.build_backend()?;
+        let old_backend = {
+            let mut guard = self.backend.write().map_err(|_| FlexiLoggerError::Poison)?;
+            std::mem::replace(&mut *guard, Arc::new(backend))
+        };
+        if let Some(wait_time) = o_flush_wait {
+            start_flusher(&self.backend, wait_time)?;
         }
+        old_backend.shutdown();
+        self.spec_stack.clear();
+        self.set_new_spec(spec);
+        Ok(())
     }
 
     // Allows checking the logs written so far to the writer
     #[doc(hidden)]
     pub fn validate_logs(&self, expected: &[(&'static str, &'static str, &'static str)]) {
-        self.primary_writer.validate_logs(expected)
+        current_backend(&self.backend)
+            .primary_writer
+            .validate_logs(expected)
+    }
+}
+
+/// Reverts a temporary log specification when it is dropped.
+///
+/// Is returned by [`LoggerHandle::scoped_spec`](crate::LoggerHandle::scoped_spec),
+/// and dereferences to the `LoggerHandle`.
+pub struct ScopedSpecGuard<'a> {
+    handle: &'a mut LoggerHandle,
+    // the depth of the spec stack before the temporary spec was pushed
+    depth: usize,
+}
+impl std::ops::Deref for ScopedSpecGuard<'_> {
+    type Target = LoggerHandle;
+    fn deref(&self) -> &LoggerHandle {
+        self.handle
+    }
+}
+impl std::ops::DerefMut for ScopedSpecGuard<'_> {
+    fn deref_mut(&mut self) -> &mut LoggerHandle {
+        self.handle
+    }
+}
+impl Drop for ScopedSpecGuard<'_> {
+    fn drop(&mut self) {
+        while self.handle.spec_stack.len() > self.depth {
+            self.handle.pop_temp_spec();
+        }
+    }
+}
+
+/// Deactivates a thread-specific log specification when it is dropped.
+///
+/// Is returned by [`LoggerHandle::thread_spec`](crate::LoggerHandle::thread_spec).
+pub struct ThreadSpecGuard {
+    handle: LoggerHandle,
+    max_level: log::LevelFilter,
+    o_previous_spec: Option<Arc<LogSpecification>>,
+    // the guard must be dropped in the thread where it was created
+    _not_send: std::marker::PhantomData<*const ()>,
+}
+impl Drop for ThreadSpecGuard {
+    fn drop(&mut self) {
+        crate::flexi_logger::replace_thread_spec(self.o_previous_spec.take());
+        if let Ok(mut thread_spec_levels) = self.handle.thread_spec_levels.lock() {
+            if let Some(pos) = thread_spec_levels
+                .iter()
+                .position(|level| *level == self.max_level)
+            {
+                thread_spec_levels.remove(pos);
+            }
+        }
+        if let Ok(spec) = self.handle.spec.read() {
+            self.handle.reconfigure(spec.max_level());
+        }
     }
 }
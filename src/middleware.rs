@@ -0,0 +1,76 @@
+/// A modifiable copy of a log record, as it is handed to [`RecordMiddleware`]s.
+///
+/// After all middlewares have been applied, the record is formatted and written
+/// based on these values; changing the `target` can thus also reroute the record
+/// to another writer, e.g. by setting it to `"{Sec}"`.
+#[derive(Clone, Debug)]
+pub struct RecordDraft {
+    /// The level of the record.
+    pub level: log::Level,
+    /// The target of the record.
+    pub target: String,
+    /// The rendered message of the record.
+    pub message: String,
+    /// The module path of the record.
+    pub module_path: Option<String>,
+    /// The source file of the record.
+    pub file: Option<String>,
+    /// The line in the source file of the record.
+    pub line: Option<u32>,
+}
+impl RecordDraft {
+    pub(crate) fn from_record(record: &log::Record) -> Self {
+        Self {
+            level: record.level(),
+            target: record.target().to_string(),
+            message: record.args().to_string(),
+            module_path: record.module_path().map(ToString::to_string),
+            file: record.file().map(ToString::to_string),
+            line: record.line(),
+        }
+    }
+}
+
+/// Inspects, modifies, or drops the log records before they are formatted and written.
+///
+/// Middlewares are registered with [`Logger::add_middleware`](crate::Logger::add_middleware)
+/// and are applied in the order of their registration.
+/// They only see the records that would be written,
+/// i.e., that pass the log specification and the levels of the addressed writers.
+/// They can e.g. redact sensitive values, add information to the message,
+/// or reroute records to a different target.
+///
+/// Closures of type `Fn(&mut RecordDraft) -> bool` implement this trait.
+///
+/// ## Example
+///
+/// ```rust
+/// use flexi_logger::{Logger, RecordDraft};
+///
+/// let logger = Logger::with_str("info")
+///     // drop the heartbeats
+///     .add_middleware(|record: &mut RecordDraft| !record.message.starts_with("heartbeat"))
+///     // hide the passwords
+///     .add_middleware(|record: &mut RecordDraft| {
+///         if record.message.contains("password") {
+///             record.message = "<redacted>".to_string();
+///         }
+///         true
+///     });
+/// ```
+pub trait RecordMiddleware: Send + Sync {
+    /// Processes the record.
+    ///
+    /// Returns `false` if the record is to be dropped;
+    /// the subsequent middlewares are then not applied.
+    fn process(&self, record: &mut RecordDraft) -> bool;
+}
+
+impl<F> RecordMiddleware for F
+where
+    F: Fn(&mut RecordDraft) -> bool + Send + Sync,
+{
+    fn process(&self, record: &mut RecordDraft) -> bool {
+        self(record)
+    }
+}
@@ -1,9 +1,38 @@
 /// Criterion when to rotate the log file.
 ///
 /// Used in [`Logger::rotate`](crate::Logger::rotate).
-#[derive(Copy, Clone, Debug)]
+///
+/// ## Example
+///
+/// Rotate daily, but also when the file exceeds 1 GiB, but never more often
+/// than every ten minutes:
+///
+/// ```rust
+/// # use flexi_logger::{Age, Criterion};
+/// # use std::time::Duration;
+/// let criterion = Criterion::MinInterval(
+///     Duration::from_secs(600),
+///     &Criterion::Any(&[Criterion::Age(Age::Day), Criterion::Size(1_073_741_824)]),
+/// );
+/// ```
+///
+/// The combining variants refer to `'static` criteria, so that `Criterion` remains `Copy`.
+/// Criteria that are only known at runtime can be leaked,
+/// e.g. with `Box::leak(criteria.into_boxed_slice())`.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(
+    feature = "config_file",
+    derive(serde_derive::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Criterion {
     /// Rotate the log file when it exceeds the specified size in bytes.
+    ///
+    /// The size includes the records that are still in the buffer
+    /// (see [`Logger::use_buffering`](crate::Logger::use_buffering)).
+    /// With [`Logger::sharded`](crate::Logger::sharded), the records of a staging buffer
+    /// are distributed over the files such that the rotation happens after the record
+    /// with which the size is exceeded.
     Size(u64),
     /// Rotate the log file when it has become older than the specified age.
     ///
@@ -53,11 +82,86 @@ pub enum Criterion {
     ///
     /// See documentation for Age and Size.
     AgeOrSize(Age, u64),
+    /// Rotate the log file when it has reached the specified number of lines.
+    ///
+    /// Every line ending is counted, so a record with a multi-line message counts
+    /// as several lines, and so do the lines of a file header.
+    /// Like with `Size`, buffered records are counted immediately.
+    Lines(u64),
+    /// Rotate the log file when any of the specified criteria is fulfilled.
+    Any(
+        #[cfg_attr(
+            feature = "config_file",
+            serde(deserialize_with = "deserialize_criteria")
+        )]
+        &'static [Criterion],
+    ),
+    /// Rotate the log file when all of the specified criteria are fulfilled.
+    All(
+        #[cfg_attr(
+            feature = "config_file",
+            serde(deserialize_with = "deserialize_criteria")
+        )]
+        &'static [Criterion],
+    ),
+    /// Rotate the log file when the specified criterion is fulfilled, but only if the
+    /// current file was opened at least the specified interval ago.
+    MinInterval(
+        std::time::Duration,
+        #[cfg_attr(
+            feature = "config_file",
+            serde(deserialize_with = "deserialize_criterion")
+        )]
+        &'static Criterion,
+    ),
+}
+impl Criterion {
+    // Returns true if the criterion contains an `Any` or `All` without criteria.
+    pub(crate) fn has_empty_composition(&self) -> bool {
+        match self {
+            Self::Any(criteria) | Self::All(criteria) => {
+                criteria.is_empty() || criteria.iter().any(Self::has_empty_composition)
+            }
+            Self::MinInterval(_, criterion) => criterion.has_empty_composition(),
+            Self::Size(_) | Self::Age(_) | Self::AgeOrSize(_, _) | Self::Lines(_) => false,
+        }
+    }
+}
+
+// The values of a configuration file are leaked where `'static` references are needed;
+// configuration files are read only once or a few times.
+#[cfg(feature = "config_file")]
+fn deserialize_criteria<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<&'static [Criterion], D::Error> {
+    let criteria: Vec<Criterion> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(Box::leak(criteria.into_boxed_slice()))
+}
+
+#[cfg(feature = "config_file")]
+fn deserialize_criterion<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<&'static Criterion, D::Error> {
+    let criterion: Criterion = serde::Deserialize::deserialize(deserializer)?;
+    Ok(Box::leak(Box::new(criterion)))
+}
+
+#[cfg(feature = "config_file")]
+fn deserialize_path<'de, D: serde::Deserializer<'de>>(
+    deserializer: D,
+) -> Result<&'static std::path::Path, D::Error> {
+    let path: std::path::PathBuf = serde::Deserialize::deserialize(deserializer)?;
+    Ok(Box::leak(path.into_boxed_path()))
 }
 
 /// The age after which a log file rotation will be triggered,
 /// when [`Criterion::Age`](crate::Criterion::Age) is chosen.
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(
+    feature = "config_file",
+    derive(serde_derive::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Age {
     /// Rotate the log file when the local clock has started a new day since the
     /// current file had been created.
@@ -75,18 +179,79 @@ pub enum Age {
 
 /// The naming convention for rotated log files.
 ///
-/// With file rotation, the logs are written to a file with infix `_rCURRENT`.
-/// When rotation happens, the CURRENT log file will be renamed to a file with
-/// another infix of the form `"_r..."`. `Naming` defines which other infix will be used.
+/// With file rotation, the logs are written to the file without infix, like `foo.log`.
+/// When rotation happens, this file will be renamed to a file with a number
+/// or a timestamp. `Naming` defines which name will be used.
 ///
 /// Used in [`Logger::rotate`](crate::Logger::rotate).
 #[derive(Copy, Clone, Debug)]
+#[cfg_attr(
+    feature = "config_file",
+    derive(serde_derive::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Naming {
-    /// File rotation rotates to files with a timestamp-infix, like `"r2020-01-27_14-41-08"`.
+    /// File rotation rotates to files with a timestamp-infix, like `foo_r2020-01-27_14-41-08.log`.
     Timestamps,
-    /// File rotation rotates to files with a number-infix.
+    /// File rotation rotates to files with a number-infix, like `foo.3.log`.
+    ///
+    /// Is equivalent to
+    /// `Naming::NumbersWith { width: 1, start: 0, order: NumberOrder::Ascending }`.
     Numbers,
+    /// File rotation rotates to files with a number-infix, with more control over the numbers.
+    ///
+    /// ## Example
+    ///
+    /// `Naming::NumbersWith { width: 3, start: 1, order: NumberOrder::Shifting }`
+    /// lets the youngest rotated file always be `foo.001.log`,
+    /// and the older ones `foo.002.log`, `foo.003.log`, and so on.
+    NumbersWith {
+        /// The minimal number of digits; smaller numbers are padded with leading zeros.
+        width: usize,
+        /// The number of the first rotated file.
+        start: u32,
+        /// Defines which rotated file gets which number.
+        order: NumberOrder,
+    },
+    /// File rotation rotates to files with a number-suffix, like logrotate does:
+    /// the youngest rotated file is always `foo.log.1`, and with each rotation the older files
+    /// are renamed to the next higher number (`foo.log.2` to `foo.log.3`,
+    /// `foo.log.1` to `foo.log.2`, and so on).
+    Logrotate,
 }
+impl Naming {
+    // Returns width, start index, and order of the numbers, if the rotated files are numbered.
+    pub(crate) fn numbering(&self) -> Option<(usize, u32, NumberOrder)> {
+        match *self {
+            Self::Timestamps => None,
+            Self::Numbers => Some((1, 0, NumberOrder::Ascending)),
+            Self::Logrotate => Some((0, 1, NumberOrder::Shifting)),
+            Self::NumbersWith {
+                width,
+                start,
+                order,
+            } => Some((width, start, order)),
+        }
+    }
+}
+
+/// Defines the order of the numbers of rotated log files with
+/// [`Naming::NumbersWith`](crate::Naming::NumbersWith).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "config_file",
+    derive(serde_derive::Deserialize),
+    serde(rename_all = "snake_case")
+)]
+pub enum NumberOrder {
+    /// Each rotated file gets the next higher number, the youngest has the highest number
+    /// (like with [`Naming::Numbers`](crate::Naming::Numbers)).
+    Ascending,
+    /// The youngest rotated file gets the start number, and with each rotation
+    /// the older files are renamed to the next higher number (like with logrotate).
+    Shifting,
+}
+
 /// Defines the strategy for handling older log files.
 ///
 /// Is used in [`Logger::rotate`](crate::Logger::rotate).
@@ -97,7 +262,12 @@ pub enum Naming {
 /// [`Logger::cleanup_in_background_thread`](crate::Logger::cleanup_in_background_thread)
 /// if you want to control whether this extra thread is created and used.
 #[allow(deprecated)]
-#[derive(Copy, Clone, Debug)]
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(
+    feature = "config_file",
+    derive(serde_derive::Deserialize),
+    serde(rename_all = "snake_case")
+)]
 pub enum Cleanup {
     /// Older log files are not touched - they remain for ever.
     Never,
@@ -113,6 +283,7 @@ pub enum Cleanup {
     /// Outdated
     #[cfg(feature = "compress")]
     #[deprecated(since = "0.16.0", note = "use KeepCompressedFiles instead")]
+    #[cfg_attr(feature = "config_file", serde(skip_deserializing))]
     KeepZipFiles(usize),
     /// Allows keeping some files as text files and some as compressed files.
     ///
@@ -128,18 +299,149 @@ pub enum Cleanup {
     /// Outdated
     #[deprecated(since = "0.16.0", note = "use KeepLogAndCompressedFiles instead")]
     #[cfg(feature = "compress")]
+    #[cfg_attr(feature = "config_file", serde(skip_deserializing))]
     KeepLogAndZipFiles(usize, usize),
+    /// The specified number of rotated log files are kept.
+    /// Older files are moved into the specified directory, where e.g. an external archiver
+    /// can pick them up, instead of being deleted.
+    ///
+    /// The directory is created if necessary. Files that exist there are never overwritten;
+    /// if the name of a moved file is taken, a number is appended to the first part of the name,
+    /// e.g. `foo-1.r00003.log`. Files are copied and removed if the directory is
+    /// on another file system.
+    ///
+    /// The directory is given as `&'static Path`, so that `Cleanup` remains `Copy`,
+    /// e.g. `Cleanup::MoveToDirectory(Path::new("log_archive"), 3)`;
+    /// a directory that is only known at runtime can be leaked,
+    /// e.g. with `Box::leak(path_buf.into_boxed_path())`.
+    MoveToDirectory(
+        #[cfg_attr(feature = "config_file", serde(deserialize_with = "deserialize_path"))]
+        &'static std::path::Path,
+        usize,
+    ),
 }
 
 impl Cleanup {
     // Returns true if some cleanup is to be done.
     #[must_use]
-    #[allow(clippy::match_like_matches_macro)]
     pub(crate) fn do_cleanup(&self) -> bool {
-        // !matches!(self, Self::Never) would be nicer, but is not possible with 1.37
+        !matches!(self, Self::Never)
+    }
+}
+
+/// Defines what a file log writer does with the log records while the disk is full.
+///
+/// In any case, the problem is reported once to the
+/// [`ErrorChannel`](crate::ErrorChannel), and writing to the file resumes automatically
+/// as soon as it succeeds again; then a line like
+/// `[flexi_logger] resumed after 42 dropped records (disk full)` is written to the file.
+/// A record that could be written only partially is removed from the file again,
+/// and the records that were still in the write buffer are dropped in any case.
+///
+/// Used in [`Logger::on_disk_full`](crate::Logger::on_disk_full).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DiskFull {
+    /// The records are dropped (default).
+    Drop,
+    /// The records are written to stderr instead.
+    Stderr,
+}
+
+/// Defines what the logger does when the duplication of a record to stdout or stderr fails
+/// because the reading end of the pipe was closed (`EPIPE`), e.g. when the output of
+/// a long-running program is piped into `head`.
+///
+/// In any case, the record is still written to the log file and to the additional writers,
+/// and the logger does not panic.
+///
+/// Used in [`Logger::on_broken_pipe`](crate::Logger::on_broken_pipe).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BrokenPipe {
+    /// The duplication to the affected stream is switched off, and the problem is reported
+    /// once to the [`ErrorChannel`](crate::ErrorChannel) (default).
+    ///
+    /// A later change of the duplication, e.g. via the specfile, switches it on again.
+    StopDuplicating,
+    /// The failure is silently ignored, and the duplication is tried again with every record.
+    Ignore,
+}
+
+/// Defines when a file log writer forces the written data of the log file
+/// to the storage device (with `fsync`, or rather its variant `fdatasync`),
+/// so that the log survives a power loss or a crash of the operating system.
+///
+/// Syncing is expensive; the data are flushed to the file before, so syncing also
+/// overrides buffering.
+///
+/// Used in [`Logger::sync_policy`](crate::Logger::sync_policy).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SyncPolicy {
+    /// Sync after every record.
+    EveryRecord,
+    /// Sync as soon as the given number of bytes was written since the last sync.
+    EveryNBytes(u64),
+    /// Sync with the first record that is written when the given interval has passed
+    /// since the last sync, and with the flushes that follow such an interval.
+    ///
+    /// The logger's flusher thread flushes at least with this interval, so that
+    /// the written data are synced also if no further records follow.
+    /// A standalone [`FileLogWriter`](crate::writers::FileLogWriter) syncs such data
+    /// when it is flushed.
+    Interval(std::time::Duration),
+    /// Leave it to the operating system when the data are written to the device (default).
+    Never,
+}
+
+/// Describes how log lines are written to stderr or stdout.
+///
+/// Used in [`Logger::write_mode_for_stderr`](crate::Logger::write_mode_for_stderr)
+/// and [`Logger::write_mode_for_stdout`](crate::Logger::write_mode_for_stdout).
+#[derive(Copy, Clone, Debug)]
+pub enum WriteMode {
+    /// Each log line is written immediately, with a single write call
+    /// (i.e., the output is effectively line-buffered).
+    ///
+    /// This is the default for duplicated output, and for stderr or stdout as log target
+    /// unless buffering is activated with
+    /// [`Logger::use_buffering`](crate::Logger::use_buffering) or one of its variants.
+    Direct,
+    /// Log lines are collected in a buffer with the given capacity in bytes and are written
+    /// when the buffer is full, or when the log is flushed explicitly.
+    ///
+    /// **Note** that you should keep the [`LoggerHandle`](crate::LoggerHandle)
+    /// and call [`shutdown`](crate::LoggerHandle::shutdown) at the very end of your program
+    /// to ensure that all buffered log lines are written.
+    Buffer(usize),
+    /// Like `Buffer`, and in addition the buffer is flushed regularly with the given interval.
+    ///
+    /// Note that flushing uses an extra thread (with minimal stack).
+    BufferAndFlush(usize, std::time::Duration),
+}
+/// Describes when a [`FlushingWriter`](crate::writers::FlushingWriter) flushes its inner writer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FlushPolicy {
+    /// Flush after every record.
+    EveryRecord,
+    /// Flush after every n-th record.
+    EveryNRecords(usize),
+    /// Flush regularly with the given interval, in a background thread (with minimal stack),
+    /// and with every record that arrives when the interval has passed.
+    Interval(std::time::Duration),
+    /// Flush only when the writer is shut down; other flush requests are ignored.
+    OnShutdown,
+}
+
+impl WriteMode {
+    pub(crate) fn buffer_capacity(&self) -> Option<usize> {
+        match self {
+            Self::Direct => None,
+            Self::Buffer(capacity) | Self::BufferAndFlush(capacity, _) => Some(*capacity),
+        }
+    }
+    pub(crate) fn flush_interval(&self) -> Option<std::time::Duration> {
         match self {
-            Self::Never => false,
-            _ => true,
+            Self::Direct | Self::Buffer(_) => None,
+            Self::BufferAndFlush(_, interval) => Some(*interval),
         }
     }
 }
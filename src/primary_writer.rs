@@ -1,11 +1,19 @@
 use crate::deferred_now::DeferredNow;
+#[cfg(feature = "atty")]
+use crate::formats::Stream;
+use crate::formats::{
+    format_with_fallback, with_style_overrides, ReplaceableFormat, StyleOverrides,
+};
 use crate::logger::Duplicate;
-use crate::writers::LogWriter;
-use crate::FormatFunction;
+use crate::writers::{CleanupCandidate, FileLogWriter, LogWriter};
+#[cfg(feature = "atty")]
+use crate::AdaptiveFormat;
+use crate::{BrokenPipe, FlexiLoggerError, FormatFunction, WriteMode};
 use log::Record;
 use std::cell::RefCell;
 use std::io::{BufWriter, Write};
-use std::sync::Mutex;
+use std::sync::atomic::{AtomicU16, Ordering};
+use std::sync::{Arc, Mutex};
 
 // Writes either to stdout, or to stderr,
 // or to a file (with optional duplication to stderr),
@@ -20,38 +28,45 @@ impl PrimaryWriter {
     pub fn multi(
         duplicate_stderr: Duplicate,
         duplicate_stdout: Duplicate,
-        format_for_stderr: FormatFunction,
-        format_for_stdout: FormatFunction,
+        stderr: StdErrWriter,
+        stdout: StdOutWriter,
+        o_file_writer: Option<FileLogWriter>,
         writers: Vec<Box<dyn LogWriter>>,
+        broken_pipe: BrokenPipe,
     ) -> Self {
         Self::Multi(MultiWriter {
-            duplicate_stderr,
-            duplicate_stdout,
-            format_for_stderr,
-            format_for_stdout,
+            duplicate_stderr: AtomicDuplicate::new(&duplicate_stderr),
+            duplicate_stdout: AtomicDuplicate::new(&duplicate_stdout),
+            stderr,
+            stdout,
+            o_file_writer,
             writers,
+            broken_pipe,
         })
     }
-    pub fn stderr(format: FormatFunction, o_buffer_capacity: &Option<usize>) -> Self {
-        Self::StdErr(StdErrWriter::new(format, o_buffer_capacity))
+    pub fn stderr(stderr: StdErrWriter) -> Self {
+        Self::StdErr(stderr)
     }
 
-    pub fn stdout(format: FormatFunction, o_buffer_capacity: &Option<usize>) -> Self {
-        Self::StdOut(StdOutWriter::new(format, o_buffer_capacity))
+    pub fn stdout(stdout: StdOutWriter) -> Self {
+        Self::StdOut(stdout)
     }
 
     pub fn black_hole(
         duplicate_err: Duplicate,
         duplicate_out: Duplicate,
-        format_for_stderr: FormatFunction,
-        format_for_stdout: FormatFunction,
+        stderr: StdErrWriter,
+        stdout: StdOutWriter,
+        broken_pipe: BrokenPipe,
     ) -> Self {
         Self::multi(
             duplicate_err,
             duplicate_out,
-            format_for_stderr,
-            format_for_stdout,
+            stderr,
+            stdout,
+            None,
             vec![],
+            broken_pipe,
         )
     }
 
@@ -78,45 +93,199 @@ impl PrimaryWriter {
             w.validate_logs(expected);
         }
     }
+
+    // Checks the file writer and the custom writers, see `LogWriter::health_check()`.
+    pub fn health_check(&self) -> std::io::Result<()> {
+        match *self {
+            Self::StdErr(_) | Self::StdOut(_) => Ok(()),
+            Self::Multi(ref w) => w.all_writers().try_for_each(LogWriter::health_check),
+        }
+    }
+
+    // Writes preformatted output to the file writer, bypassing the format function;
+    // a missing line break at the end is added.
+    pub fn write_raw(&self, buf: &[u8]) -> Result<(), FlexiLoggerError> {
+        match *self {
+            Self::Multi(MultiWriter {
+                o_file_writer: Some(ref file_writer),
+                ..
+            }) => match buf.last() {
+                None => Ok(()),
+                Some(b'\n') => file_writer.write_raw(buf),
+                Some(_) => {
+                    let mut line = Vec::with_capacity(buf.len() + 1);
+                    line.extend_from_slice(buf);
+                    line.push(b'\n');
+                    file_writer.write_raw(&line)
+                }
+            }
+            .map_err(FlexiLoggerError::OutputIo),
+            _ => Err(FlexiLoggerError::NoFileLog),
+        }
+    }
+
+    // Lists what the cleanup of the file writer would do, if there is one.
+    pub fn cleanup_candidates(&self) -> Result<Vec<CleanupCandidate>, FlexiLoggerError> {
+        match *self {
+            Self::Multi(MultiWriter {
+                o_file_writer: Some(ref file_writer),
+                ..
+            }) => file_writer.list_cleanup_candidates(),
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    // Waits for the cleanup thread of the file writer, if there is one.
+    pub fn wait_for_cleanup(&self, timeout: std::time::Duration) -> bool {
+        match *self {
+            Self::Multi(MultiWriter {
+                o_file_writer: Some(ref file_writer),
+                ..
+            }) => file_writer.wait_for_cleanup(timeout),
+            _ => true,
+        }
+    }
+
+    // Replaces the format functions for files, stderr, and stdout.
+    #[cfg(feature = "specfile_without_notification")]
+    pub fn set_formats(
+        &self,
+        format_for_file: FormatFunction,
+        format_for_stderr: FormatFunction,
+        format_for_stdout: FormatFunction,
+    ) {
+        match *self {
+            Self::StdErr(ref w) => w.format.store(format_for_stderr),
+            Self::StdOut(ref w) => w.format.store(format_for_stdout),
+            Self::Multi(ref w) => {
+                w.stderr.format.store(format_for_stderr);
+                w.stdout.format.store(format_for_stdout);
+                if let Some(ref file_writer) = w.o_file_writer {
+                    file_writer.set_format(format_for_file);
+                }
+            }
+        }
+    }
+
+    // Changes the duplication to stderr; has no effect if stderr or stdout is the primary target.
+    #[cfg(feature = "specfile_without_notification")]
+    pub fn set_duplicate_to_stderr(&self, duplicate: Duplicate) {
+        if let Self::Multi(ref w) = *self {
+            w.duplicate_stderr.store(&duplicate);
+        }
+    }
+
+    // Changes the duplication to stdout; has no effect if stderr or stdout is the primary target.
+    #[cfg(feature = "specfile_without_notification")]
+    pub fn set_duplicate_to_stdout(&self, duplicate: Duplicate) {
+        if let Self::Multi(ref w) = *self {
+            w.duplicate_stdout.store(&duplicate);
+        }
+    }
 }
 
-// `StdErrWriter` writes logs to stderr.
-pub(crate) struct StdErrWriter {
-    format: FormatFunction,
+/// A `LogWriter` that writes to stderr.
+///
+/// `flexi_logger` uses it for the log target [`LogTarget::StdErr`](crate::LogTarget::StdErr)
+/// and for the duplication to stderr; it can also be used like any other writer,
+/// e.g. with [`Logger::add_writer`](crate::Logger::add_writer) or in a
+/// [`TeeWriter`](crate::writers::TeeWriter).
+///
+/// In web builds (`wasm32` with crate feature `wasm-bindgen`), it writes to the console
+/// of the browser instead, errors with `console.error`, warnings with `console.warn`,
+/// and all other records with `console.log`.
+pub struct StdErrWriter {
+    format: ReplaceableFormat,
     writer: ErrWriter,
+    max_log_level: log::LevelFilter,
+    o_style_overrides: Option<Arc<StyleOverrides>>,
 }
+#[cfg_attr(
+    all(target_arch = "wasm32", feature = "wasm-bindgen"),
+    allow(dead_code)
+)]
 enum ErrWriter {
     Unbuffered(std::io::Stderr),
     Buffered(Mutex<BufWriter<std::io::Stderr>>),
+    // the console of the browser, in web builds
+    #[cfg(all(target_arch = "wasm32", feature = "wasm-bindgen"))]
+    Console,
+}
+impl ErrWriter {
+    #[cfg(not(all(target_arch = "wasm32", feature = "wasm-bindgen")))]
+    fn new(write_mode: &WriteMode) -> Self {
+        match write_mode.buffer_capacity() {
+            Some(capacity) => Self::Buffered(Mutex::new(BufWriter::with_capacity(
+                capacity,
+                std::io::stderr(),
+            ))),
+            None => Self::Unbuffered(std::io::stderr()),
+        }
+    }
+    #[cfg(all(target_arch = "wasm32", feature = "wasm-bindgen"))]
+    fn new(_write_mode: &WriteMode) -> Self {
+        Self::Console
+    }
 }
 impl StdErrWriter {
-    fn new(format: FormatFunction, o_buffer_capacity: &Option<usize>) -> Self {
-        match o_buffer_capacity {
-            Some(capacity) => Self {
-                format,
-                writer: ErrWriter::Buffered(Mutex::new(BufWriter::with_capacity(
-                    *capacity,
-                    std::io::stderr(),
-                ))),
-            },
-            None => Self {
-                format,
-                writer: ErrWriter::Unbuffered(std::io::stderr()),
-            },
+    /// Creates a `StdErrWriter` with the given format function and write mode.
+    ///
+    /// With a buffering write mode, the buffer is flushed when it is full, and with
+    /// [`LogWriter::flush`](crate::writers::LogWriter::flush); a flush interval of the
+    /// write mode has only an effect on the writers that `flexi_logger` creates itself.
+    #[must_use]
+    pub fn new(format: FormatFunction, write_mode: &WriteMode) -> Self {
+        Self {
+            format: ReplaceableFormat::new(format),
+            writer: ErrWriter::new(write_mode),
+            max_log_level: log::LevelFilter::Trace,
+            o_style_overrides: None,
         }
     }
+
+    /// Creates a `StdErrWriter` that uses colors if stderr is a tty,
+    /// see [`AdaptiveFormat`](crate::AdaptiveFormat).
+    ///
+    /// Only available with optional crate feature `atty`.
+    #[cfg(feature = "atty")]
+    #[must_use]
+    pub fn with_adaptive_format(adaptive_format: AdaptiveFormat, write_mode: &WriteMode) -> Self {
+        Self::new(adaptive_format.format_function(Stream::StdErr), write_mode)
+    }
+
+    /// Restricts the writer to records up to the given level; the default is `Trace`.
+    #[must_use]
+    pub fn level_threshold(mut self, max_log_level: log::LevelFilter) -> Self {
+        self.max_log_level = max_log_level;
+        self
+    }
+
+    // Sets the palette and timestamp format that replace the global ones for this stream.
+    pub(crate) fn style_overrides(
+        mut self,
+        o_style_overrides: Option<Arc<StyleOverrides>>,
+    ) -> Self {
+        self.o_style_overrides = o_style_overrides;
+        self
+    }
+}
+
+impl LogWriter for StdErrWriter {
     #[inline]
     fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
-        match &self.writer {
+        let format = self.format.load();
+        with_style_overrides(self.o_style_overrides.as_ref(), || match &self.writer {
             ErrWriter::Unbuffered(stderr) => {
                 let mut w = stderr.lock();
-                write_buffered(self.format, now, record, &mut w)
+                write_buffered(format, now, record, &mut w)
             }
             ErrWriter::Buffered(mbuf_w) => {
                 let mut w = mbuf_w.lock().map_err(|e| poison_err("stderr", &e))?;
-                write_buffered(self.format, now, record, &mut *w)
+                write_buffered(format, now, record, &mut *w)
             }
-        }
+            #[cfg(all(target_arch = "wasm32", feature = "wasm-bindgen"))]
+            ErrWriter::Console => crate::wasm_console::write(format, now, record),
+        })
     }
 
     #[inline]
@@ -130,51 +299,125 @@ impl StdErrWriter {
                 let mut w = mbuf_w.lock().map_err(|e| poison_err("stderr", &e))?;
                 w.flush()
             }
+            #[cfg(all(target_arch = "wasm32", feature = "wasm-bindgen"))]
+            ErrWriter::Console => Ok(()),
         }
     }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.max_log_level
+    }
+
+    fn format(&mut self, format: FormatFunction) {
+        self.format.store(format);
+    }
 }
 
 fn poison_err(s: &'static str, _e: &dyn std::error::Error) -> std::io::Error {
     std::io::Error::new(std::io::ErrorKind::Other, s)
 }
 
-// `StdOutWriter` writes logs to stdout.
-pub(crate) struct StdOutWriter {
-    format: FormatFunction,
+/// A `LogWriter` that writes to stdout.
+///
+/// `flexi_logger` uses it for the log target [`LogTarget::StdOut`](crate::LogTarget::StdOut)
+/// and for the duplication to stdout; it can also be used like any other writer,
+/// e.g. with [`Logger::add_writer`](crate::Logger::add_writer) or in a
+/// [`TeeWriter`](crate::writers::TeeWriter).
+///
+/// In web builds, it writes to the console of the browser, like the
+/// [`StdErrWriter`](crate::writers::StdErrWriter).
+pub struct StdOutWriter {
+    format: ReplaceableFormat,
     writer: OutWriter,
+    max_log_level: log::LevelFilter,
+    o_style_overrides: Option<Arc<StyleOverrides>>,
 }
+#[cfg_attr(
+    all(target_arch = "wasm32", feature = "wasm-bindgen"),
+    allow(dead_code)
+)]
 enum OutWriter {
     Unbuffered(std::io::Stdout),
     Buffered(Mutex<BufWriter<std::io::Stdout>>),
+    // the console of the browser, in web builds
+    #[cfg(all(target_arch = "wasm32", feature = "wasm-bindgen"))]
+    Console,
+}
+impl OutWriter {
+    #[cfg(not(all(target_arch = "wasm32", feature = "wasm-bindgen")))]
+    fn new(write_mode: &WriteMode) -> Self {
+        match write_mode.buffer_capacity() {
+            Some(capacity) => Self::Buffered(Mutex::new(BufWriter::with_capacity(
+                capacity,
+                std::io::stdout(),
+            ))),
+            None => Self::Unbuffered(std::io::stdout()),
+        }
+    }
+    #[cfg(all(target_arch = "wasm32", feature = "wasm-bindgen"))]
+    fn new(_write_mode: &WriteMode) -> Self {
+        Self::Console
+    }
 }
 impl StdOutWriter {
-    fn new(format: FormatFunction, o_buffer_capacity: &Option<usize>) -> Self {
-        match o_buffer_capacity {
-            Some(capacity) => Self {
-                format,
-                writer: OutWriter::Buffered(Mutex::new(BufWriter::with_capacity(
-                    *capacity,
-                    std::io::stdout(),
-                ))),
-            },
-            None => Self {
-                format,
-                writer: OutWriter::Unbuffered(std::io::stdout()),
-            },
+    /// Creates a `StdOutWriter` with the given format function and write mode.
+    ///
+    /// With a buffering write mode, the buffer is flushed when it is full, and with
+    /// [`LogWriter::flush`](crate::writers::LogWriter::flush); a flush interval of the
+    /// write mode has only an effect on the writers that `flexi_logger` creates itself.
+    #[must_use]
+    pub fn new(format: FormatFunction, write_mode: &WriteMode) -> Self {
+        Self {
+            format: ReplaceableFormat::new(format),
+            writer: OutWriter::new(write_mode),
+            max_log_level: log::LevelFilter::Trace,
+            o_style_overrides: None,
         }
     }
+
+    /// Creates a `StdOutWriter` that uses colors if stdout is a tty,
+    /// see [`AdaptiveFormat`](crate::AdaptiveFormat).
+    ///
+    /// Only available with optional crate feature `atty`.
+    #[cfg(feature = "atty")]
+    #[must_use]
+    pub fn with_adaptive_format(adaptive_format: AdaptiveFormat, write_mode: &WriteMode) -> Self {
+        Self::new(adaptive_format.format_function(Stream::StdOut), write_mode)
+    }
+
+    /// Restricts the writer to records up to the given level; the default is `Trace`.
+    #[must_use]
+    pub fn level_threshold(mut self, max_log_level: log::LevelFilter) -> Self {
+        self.max_log_level = max_log_level;
+        self
+    }
+
+    // Sets the palette and timestamp format that replace the global ones for this stream.
+    pub(crate) fn style_overrides(
+        mut self,
+        o_style_overrides: Option<Arc<StyleOverrides>>,
+    ) -> Self {
+        self.o_style_overrides = o_style_overrides;
+        self
+    }
+}
+
+impl LogWriter for StdOutWriter {
     #[inline]
     fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
-        match &self.writer {
+        let format = self.format.load();
+        with_style_overrides(self.o_style_overrides.as_ref(), || match &self.writer {
             OutWriter::Unbuffered(stdout) => {
                 let mut w = stdout.lock();
-                write_buffered(self.format, now, record, &mut w)
+                write_buffered(format, now, record, &mut w)
             }
             OutWriter::Buffered(mbuf_w) => {
                 let mut w = mbuf_w.lock().map_err(|e| poison_err("stdout", &e))?;
-                write_buffered(self.format, now, record, &mut *w)
+                write_buffered(format, now, record, &mut *w)
             }
-        }
+            #[cfg(all(target_arch = "wasm32", feature = "wasm-bindgen"))]
+            OutWriter::Console => crate::wasm_console::write(format, now, record),
+        })
     }
 
     #[inline]
@@ -188,51 +431,82 @@ impl StdOutWriter {
                 let mut w = mbuf_w.lock().map_err(|e| poison_err("stdout", &e))?;
                 w.flush()
             }
+            #[cfg(all(target_arch = "wasm32", feature = "wasm-bindgen"))]
+            OutWriter::Console => Ok(()),
         }
     }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.max_log_level
+    }
+
+    fn format(&mut self, format: FormatFunction) {
+        self.format.store(format);
+    }
 }
 
-// The `MultiWriter` writes logs to stderr or to a set of `Writer`s, and in the latter case
-// can duplicate messages to stderr.
+// The `MultiWriter` writes logs to a file and/or to a set of `Writer`s,
+// and can duplicate messages to stderr and stdout.
+// Duplication and formats can be changed at runtime via the specfile.
 pub(crate) struct MultiWriter {
-    duplicate_stderr: Duplicate,
-    duplicate_stdout: Duplicate,
-    format_for_stderr: FormatFunction,
-    format_for_stdout: FormatFunction,
+    duplicate_stderr: AtomicDuplicate,
+    duplicate_stdout: AtomicDuplicate,
+    stderr: StdErrWriter,
+    stdout: StdOutWriter,
+    o_file_writer: Option<FileLogWriter>,
     writers: Vec<Box<dyn LogWriter>>,
+    broken_pipe: BrokenPipe,
+}
+impl MultiWriter {
+    fn all_writers(&self) -> impl Iterator<Item = &dyn LogWriter> {
+        self.o_file_writer
+            .iter()
+            .map(|w| w as &dyn LogWriter)
+            .chain(self.writers.iter().map(AsRef::as_ref))
+    }
+
+    // Writes the record to stderr or stdout, if the duplication asks for it;
+    // a closed pipe is handled according to the `BrokenPipe` policy and never keeps
+    // the record from being written to the other writers.
+    fn duplicate(
+        &self,
+        duplicate: &AtomicDuplicate,
+        writer: &dyn LogWriter,
+        stream: &'static str,
+        now: &mut DeferredNow,
+        record: &Record,
+    ) -> std::io::Result<()> {
+        if !duplicates(&duplicate.load(), record.level()) {
+            return Ok(());
+        }
+        match writer.write(now, record) {
+            Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => {
+                // another thread might have stopped the duplication meanwhile
+                if self.broken_pipe == BrokenPipe::StopDuplicating && duplicate.stop() {
+                    crate::error_channel::report(format_args!(
+                        "the pipe of {} was closed, duplication to {} is stopped",
+                        stream, stream
+                    ));
+                }
+                Ok(())
+            }
+            result => result,
+        }
+    }
 }
 
 impl LogWriter for MultiWriter {
     fn validate_logs(&self, expected: &[(&'static str, &'static str, &'static str)]) {
-        for writer in &self.writers {
-            (*writer).validate_logs(expected);
+        for writer in self.all_writers() {
+            writer.validate_logs(expected);
         }
     }
 
     fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
-        if match self.duplicate_stderr {
-            Duplicate::Error => record.level() == log::Level::Error,
-            Duplicate::Warn => record.level() <= log::Level::Warn,
-            Duplicate::Info => record.level() <= log::Level::Info,
-            Duplicate::Debug => record.level() <= log::Level::Debug,
-            Duplicate::Trace | Duplicate::All => true,
-            Duplicate::None => false,
-        } {
-            write_buffered(self.format_for_stderr, now, record, &mut std::io::stderr())?;
-        }
-
-        if match self.duplicate_stdout {
-            Duplicate::Error => record.level() == log::Level::Error,
-            Duplicate::Warn => record.level() <= log::Level::Warn,
-            Duplicate::Info => record.level() <= log::Level::Info,
-            Duplicate::Debug => record.level() <= log::Level::Debug,
-            Duplicate::Trace | Duplicate::All => true,
-            Duplicate::None => false,
-        } {
-            write_buffered(self.format_for_stdout, now, record, &mut std::io::stdout())?;
-        }
-
-        for writer in &self.writers {
+        self.duplicate(&self.duplicate_stderr, &self.stderr, "stderr", now, record)?;
+        self.duplicate(&self.duplicate_stdout, &self.stdout, "stdout", now, record)?;
+
+        for writer in self.all_writers() {
             writer.write(now, record)?;
         }
         Ok(())
@@ -240,40 +514,118 @@ impl LogWriter for MultiWriter {
 
     /// Provides the maximum log level that is to be written.
     fn max_log_level(&self) -> log::LevelFilter {
-        self.writers
-            .iter()
-            .map(|w| w.max_log_level())
+        self.all_writers()
+            .map(LogWriter::max_log_level)
             .max()
             .unwrap()
     }
 
     fn flush(&self) -> std::io::Result<()> {
-        for writer in &self.writers {
+        for writer in self.all_writers() {
             writer.flush()?;
         }
-        if let Duplicate::None = self.duplicate_stderr {
-            std::io::stderr().flush()?;
-        }
-        if let Duplicate::None = self.duplicate_stdout {
-            std::io::stdout().flush()?;
-        }
-        // maybe nicer, but doesn't work with rustc 1.37:
-        // if !matches!(self.duplicate_stderr, Duplicate::None) {
-        //     std::io::stderr().flush()?;
-        // }
-        // if !matches!(self.duplicate_stdout, Duplicate::None) {
-        //     std::io::stdout().flush()?;
-        // }
-        Ok(())
+        // the duplication can be switched off at runtime, so the std writers are always flushed,
+        // in order not to lose buffered lines; a closed pipe is no reason to complain here
+        ignore_broken_pipe(self.stderr.flush())?;
+        ignore_broken_pipe(self.stdout.flush())
     }
 
     fn shutdown(&self) {
-        for writer in &self.writers {
+        for writer in self.all_writers() {
             writer.shutdown();
         }
     }
 }
 
+fn ignore_broken_pipe(result: std::io::Result<()>) -> std::io::Result<()> {
+    match result {
+        Err(e) if e.kind() == std::io::ErrorKind::BrokenPipe => Ok(()),
+        result => result,
+    }
+}
+
+// The duplication to stderr or stdout, which can be changed at runtime
+// without locking on each record.
+struct AtomicDuplicate(AtomicU16);
+impl AtomicDuplicate {
+    fn new(duplicate: &Duplicate) -> Self {
+        Self(AtomicU16::new(Self::encode(duplicate)))
+    }
+
+    fn load(&self) -> Duplicate {
+        let code = self.0.load(Ordering::Relaxed);
+        match code {
+            0 => Duplicate::None,
+            1 => Duplicate::Error,
+            2 => Duplicate::Warn,
+            3 => Duplicate::Info,
+            4 => Duplicate::Debug,
+            5 => Duplicate::Trace,
+            6 => Duplicate::All,
+            _ => Duplicate::Between(level_of(code >> 4), level_of(code)),
+        }
+    }
+
+    #[cfg(feature = "specfile_without_notification")]
+    fn store(&self, duplicate: &Duplicate) {
+        self.0.store(Self::encode(duplicate), Ordering::Relaxed);
+    }
+
+    // Switches the duplication off; returns false if it was already off.
+    fn stop(&self) -> bool {
+        self.0.swap(0, Ordering::Relaxed) != 0
+    }
+
+    fn encode(duplicate: &Duplicate) -> u16 {
+        match duplicate {
+            Duplicate::None => 0,
+            Duplicate::Error => 1,
+            Duplicate::Warn => 2,
+            Duplicate::Info => 3,
+            Duplicate::Debug => 4,
+            Duplicate::Trace => 5,
+            Duplicate::All => 6,
+            Duplicate::Between(level1, level2) => {
+                0x100 | (code_of(*level1) << 4) | code_of(*level2)
+            }
+        }
+    }
+}
+
+fn code_of(level: log::Level) -> u16 {
+    match level {
+        log::Level::Error => 1,
+        log::Level::Warn => 2,
+        log::Level::Info => 3,
+        log::Level::Debug => 4,
+        log::Level::Trace => 5,
+    }
+}
+
+fn level_of(code: u16) -> log::Level {
+    match code & 0xf {
+        1 => log::Level::Error,
+        2 => log::Level::Warn,
+        3 => log::Level::Info,
+        4 => log::Level::Debug,
+        _ => log::Level::Trace,
+    }
+}
+
+fn duplicates(duplicate: &Duplicate, level: log::Level) -> bool {
+    match duplicate {
+        Duplicate::Error => level == log::Level::Error,
+        Duplicate::Warn => level <= log::Level::Warn,
+        Duplicate::Info => level <= log::Level::Info,
+        Duplicate::Debug => level <= log::Level::Debug,
+        Duplicate::Trace | Duplicate::All => true,
+        Duplicate::None => false,
+        Duplicate::Between(level1, level2) => {
+            std::cmp::min(*level1, *level2) <= level && level <= std::cmp::max(*level1, *level2)
+        }
+    }
+}
+
 // Use a thread-local buffer for writing to stderr or stdout
 fn write_buffered(
     format_function: FormatFunction,
@@ -285,16 +637,15 @@ fn write_buffered(
 
     buffer_with(|tl_buf| match tl_buf.try_borrow_mut() {
         Ok(mut buffer) => {
-            (format_function)(&mut *buffer, now, record)
-                .unwrap_or_else(|e| write_err(ERR_FORMATTING, &e));
+            format_with_fallback(format_function, &mut *buffer, now, record);
             buffer
                 .write_all(b"\n")
                 .unwrap_or_else(|e| write_err(ERR_FORMATTING, &e));
 
-            result = w.write_all(&*buffer).map_err(|e| {
-                write_err(ERR_WRITING, &e);
-                e
-            });
+            result = w.write_all(&*buffer).map_err(report_write_err);
+            if result.is_ok() {
+                crate::stats::count_bytes_written(buffer.len());
+            }
 
             buffer.clear();
         }
@@ -304,16 +655,15 @@ fn write_buffered(
             // we print the inner calls, in chronological order, before finally the
             // outer most message is printed
             let mut tmp_buf = Vec::<u8>::with_capacity(200);
-            (format_function)(&mut tmp_buf, now, record)
-                .unwrap_or_else(|e| write_err(ERR_FORMATTING, &e));
+            format_with_fallback(format_function, &mut tmp_buf, now, record);
             tmp_buf
                 .write_all(b"\n")
                 .unwrap_or_else(|e| write_err(ERR_FORMATTING, &e));
 
-            result = w.write_all(&tmp_buf).map_err(|e| {
-                write_err(ERR_WRITING, &e);
-                e
-            });
+            result = w.write_all(&tmp_buf).map_err(report_write_err);
+            if result.is_ok() {
+                crate::stats::count_bytes_written(tmp_buf.len());
+            }
         }
     });
     result
@@ -333,5 +683,87 @@ const ERR_FORMATTING: &str = "formatting failed with ";
 const ERR_WRITING: &str = "writing failed with ";
 
 fn write_err(msg: &str, err: &std::io::Error) {
-    eprintln!("[flexi_logger] {} with {}", msg, err);
+    crate::error_channel::report(format_args!("{} with {}", msg, err));
+}
+
+// A closed pipe is left to the caller, see `BrokenPipe`.
+fn report_write_err(err: std::io::Error) -> std::io::Error {
+    if err.kind() != std::io::ErrorKind::BrokenPipe {
+        write_err(ERR_WRITING, &err);
+    }
+    err
+}
+
+#[cfg(test)]
+mod test {
+    use super::{duplicates, PrimaryWriter, StdErrWriter, StdOutWriter};
+    use crate::writers::LogWriter;
+    use crate::{default_format, BrokenPipe, DeferredNow, Duplicate, WriteMode};
+    use log::Level;
+
+    #[test]
+    fn test_duplicates_between() {
+        for duplicate in &[
+            Duplicate::Between(Level::Warn, Level::Info),
+            Duplicate::Between(Level::Info, Level::Warn),
+            Duplicate::from_name("Warn..Info").unwrap(),
+        ] {
+            assert!(!duplicates(duplicate, Level::Error));
+            assert!(duplicates(duplicate, Level::Warn));
+            assert!(duplicates(duplicate, Level::Info));
+            assert!(!duplicates(duplicate, Level::Debug));
+        }
+        assert!(Duplicate::from_name("warn..nonsense").is_err());
+    }
+
+    struct ClosedPipe;
+    impl LogWriter for ClosedPipe {
+        fn write(&self, _now: &mut DeferredNow, _record: &log::Record) -> std::io::Result<()> {
+            Err(std::io::ErrorKind::BrokenPipe.into())
+        }
+        fn flush(&self) -> std::io::Result<()> {
+            Ok(())
+        }
+        fn max_log_level(&self) -> log::LevelFilter {
+            log::LevelFilter::Trace
+        }
+    }
+
+    #[test]
+    fn test_broken_pipe() {
+        for (broken_pipe, still_duplicating) in &[
+            (BrokenPipe::StopDuplicating, false),
+            (BrokenPipe::Ignore, true),
+        ] {
+            let writer = PrimaryWriter::black_hole(
+                Duplicate::All,
+                Duplicate::None,
+                StdErrWriter::new(default_format, &WriteMode::Direct),
+                StdOutWriter::new(default_format, &WriteMode::Direct),
+                *broken_pipe,
+            );
+            let w = match writer {
+                PrimaryWriter::Multi(w) => w,
+                _ => unreachable!(),
+            };
+            for _ in 0..2 {
+                assert!(w
+                    .duplicate(
+                        &w.duplicate_stderr,
+                        &ClosedPipe,
+                        "stderr",
+                        &mut DeferredNow::new(),
+                        &log::Record::builder()
+                            .args(format_args!("to a closed pipe"))
+                            .level(Level::Error)
+                            .build(),
+                    )
+                    .is_ok());
+            }
+            assert_eq!(
+                duplicates(&w.duplicate_stderr.load(), Level::Error),
+                *still_duplicating
+            );
+        }
+    }
 }
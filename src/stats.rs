@@ -0,0 +1,133 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// The counters behind `LoggerHandle::stats()`.
+//
+// They are process-global, like the logger itself, so that also the writers,
+// which do not know the logger, can update them cheaply.
+struct Counters {
+    records: [AtomicUsize; 5],
+    bytes_written: AtomicUsize,
+    records_dropped: AtomicUsize,
+    write_errors: AtomicUsize,
+    rotations: AtomicUsize,
+}
+
+#[allow(clippy::declare_interior_mutable_const)]
+const ZERO: AtomicUsize = AtomicUsize::new(0);
+
+static COUNTERS: Counters = Counters {
+    records: [ZERO, ZERO, ZERO, ZERO, ZERO],
+    bytes_written: ZERO,
+    records_dropped: ZERO,
+    write_errors: ZERO,
+    rotations: ZERO,
+};
+
+pub(crate) fn count_record(level: log::Level) {
+    COUNTERS.records[level as usize - 1].fetch_add(1, Ordering::Relaxed);
+    #[cfg(feature = "metrics")]
+    metrics::increment_counter!("log_messages_total", "level" => level_label(level));
+}
+
+#[cfg(feature = "metrics")]
+fn level_label(level: log::Level) -> &'static str {
+    match level {
+        log::Level::Error => "error",
+        log::Level::Warn => "warn",
+        log::Level::Info => "info",
+        log::Level::Debug => "debug",
+        log::Level::Trace => "trace",
+    }
+}
+pub(crate) fn count_bytes_written(no_of_bytes: usize) {
+    COUNTERS
+        .bytes_written
+        .fetch_add(no_of_bytes, Ordering::Relaxed);
+}
+pub(crate) fn count_dropped_records(no_of_records: usize) {
+    COUNTERS
+        .records_dropped
+        .fetch_add(no_of_records, Ordering::Relaxed);
+    crate::diagnostics::emit(format_args!(
+        "event=records_dropped count={}",
+        no_of_records
+    ));
+}
+pub(crate) fn count_write_error() {
+    COUNTERS.write_errors.fetch_add(1, Ordering::Relaxed);
+}
+pub(crate) fn count_rotation() {
+    COUNTERS.rotations.fetch_add(1, Ordering::Relaxed);
+}
+
+/// A snapshot of the logging statistics of the program,
+/// as returned by [`LoggerHandle::stats`](crate::LoggerHandle::stats).
+///
+/// All counters start with the program and only increase
+/// (on 32-bit platforms, they can wrap around).
+///
+/// With optional crate feature `metrics`, each record that is counted here
+/// also increments the counter `log_messages_total{level="..."}` in the
+/// [`metrics`](https://docs.rs/metrics) facade, so that e.g. the rate of error logs
+/// can be exported to Prometheus with the recorder of your choice.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LogStats {
+    records: [u64; 5],
+    bytes_written: u64,
+    records_dropped: u64,
+    write_errors: u64,
+    rotations: u64,
+}
+impl LogStats {
+    pub(crate) fn snapshot() -> Self {
+        let get = |counter: &AtomicUsize| counter.load(Ordering::Relaxed) as u64;
+        let mut records = [0; 5];
+        for (record, counter) in records.iter_mut().zip(COUNTERS.records.iter()) {
+            *record = get(counter);
+        }
+        Self {
+            records,
+            bytes_written: get(&COUNTERS.bytes_written),
+            records_dropped: get(&COUNTERS.records_dropped),
+            write_errors: get(&COUNTERS.write_errors),
+            rotations: get(&COUNTERS.rotations),
+        }
+    }
+
+    /// Number of records of the given level that were written to at least one writer.
+    #[must_use]
+    pub fn records(&self, level: log::Level) -> u64 {
+        self.records[level as usize - 1]
+    }
+
+    /// Number of records of all levels that were written to at least one writer.
+    #[must_use]
+    pub fn total_records(&self) -> u64 {
+        self.records.iter().sum()
+    }
+
+    /// Number of bytes that were written to log files, to stderr, and to stdout.
+    #[must_use]
+    pub fn bytes_written(&self) -> u64 {
+        self.bytes_written
+    }
+
+    /// Number of records that writers with bounded buffers dropped,
+    /// e.g. because the receiver was not reachable or too slow.
+    #[must_use]
+    pub fn records_dropped(&self) -> u64 {
+        self.records_dropped
+    }
+
+    /// Number of failed attempts to write a record to a writer.
+    #[must_use]
+    pub fn write_errors(&self) -> u64 {
+        self.write_errors
+    }
+
+    /// Number of rotations of log files.
+    #[must_use]
+    pub fn rotations(&self) -> u64 {
+        self.rotations
+    }
+}
@@ -0,0 +1,110 @@
+// Redirection of the own stdout and stderr of the process into the log file,
+// see `Logger::capture_stdout()` and `Logger::capture_stderr()`.
+use crate::LoggerHandle;
+use libc::{c_int, dup, dup2, pipe};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::sync::mpsc::{channel, Receiver};
+use std::sync::Mutex;
+use std::time::Duration;
+
+pub(crate) const STDOUT: c_int = libc::STDOUT_FILENO;
+pub(crate) const STDERR: c_int = libc::STDERR_FILENO;
+
+// A standard stream that is redirected into a pipe.
+struct Capture {
+    fd: c_int,
+    // a duplicate of the stream as it was before the redirection
+    original: File,
+    // disconnects when the reader thread ends
+    reader_ended: Receiver<()>,
+}
+
+lazy_static::lazy_static! {
+    static ref CAPTURES: Mutex<Vec<Capture>> = Mutex::new(Vec::new());
+}
+
+// Redirects the given standard stream into a pipe; a reader thread writes what arrives
+// there line by line to the log file, or, if that fails, to the original stream.
+pub(crate) fn start(fd: c_int, handle: LoggerHandle) -> std::io::Result<()> {
+    let mut fds: [c_int; 2] = [0; 2];
+    if unsafe { pipe(fds.as_mut_ptr()) } != 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    // the files take ownership of the file descriptors and close them when they are dropped
+    let (read_end, write_end) = unsafe { (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1])) };
+    let original_fd = unsafe { dup(fd) };
+    if original_fd < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    let original = unsafe { File::from_raw_fd(original_fd) };
+    let mut fallback = original.try_clone()?;
+    let (reader_ending, reader_ended) = channel::<()>();
+
+    std::thread::Builder::new()
+        .name(format!(
+            "flexi_logger-capture-{}",
+            if fd == STDOUT { "stdout" } else { "stderr" }
+        ))
+        .spawn(move || {
+            let _reader_ending = reader_ending;
+            let mut read_end = BufReader::new(read_end);
+            let mut line = Vec::new();
+            loop {
+                line.clear();
+                match read_end.read_until(b'\n', &mut line) {
+                    Ok(0) | Err(_) => break,
+                    Ok(_) => {
+                        // not reported to the error channel, which might be the captured stream
+                        if handle.write_raw(&line).is_err() {
+                            fallback.write_all(&line).ok();
+                        }
+                    }
+                }
+            }
+        })?;
+
+    // the reader thread ends when the write end is closed, also if redirecting fails
+    if unsafe { dup2(write_end.as_raw_fd(), fd) } < 0 {
+        return Err(std::io::Error::last_os_error());
+    }
+    CAPTURES
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .push(Capture {
+            fd,
+            original,
+            reader_ended,
+        });
+    Ok(())
+}
+
+// Tells if the given standard stream is currently redirected.
+pub(crate) fn is_captured(fd: c_int) -> bool {
+    CAPTURES
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .iter()
+        .any(|capture| capture.fd == fd)
+}
+
+// Restores the redirected streams and waits until the captured output is written,
+// but not longer than a second per stream: the pipe stays open, and its reader thread
+// keeps running, as long as e.g. a child process holds the inherited write end.
+pub(crate) fn stop() {
+    std::io::stdout().flush().ok();
+    let captures = std::mem::take(
+        &mut *CAPTURES
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner),
+    );
+    for capture in captures {
+        // closes the last write end of the pipe, unless a child process inherited it
+        unsafe { dup2(capture.original.as_raw_fd(), capture.fd) };
+        capture
+            .reader_ended
+            .recv_timeout(Duration::from_secs(1))
+            .ok();
+    }
+}
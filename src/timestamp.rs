@@ -0,0 +1,399 @@
+// flexi_logger handles timestamps with crate chrono (default) or, if only crate feature `time`
+// is enabled, with crate time. All code that depends on this choice lives in this module;
+// the rest of flexi_logger does its own formatting, based on the `Parts` of a timestamp,
+// so that the output is the same with both crates.
+// Enabling feature `time` does not change the public API that comes with feature `chrono`,
+// it only adds `DeferredNow::now_time()`.
+use std::fmt;
+#[cfg(not(feature = "chrono"))]
+use std::sync::atomic::{AtomicBool, Ordering};
+
+#[cfg(not(any(feature = "chrono", feature = "time")))]
+compile_error!("flexi_logger needs one of the crate features `chrono` (default) and `time`");
+
+/// The type of the timestamps that [`DeferredNow::now`](crate::DeferredNow::now) returns.
+///
+/// Is only available with crate feature `chrono` (default);
+/// with crate feature `time`, [`DeferredNow::now_time`](crate::DeferredNow::now_time)
+/// provides the timestamp as `time::OffsetDateTime`.
+#[cfg(feature = "chrono")]
+pub type Timestamp = chrono::DateTime<chrono::Local>;
+
+// Without crate feature `chrono`, the timestamps are handled internally with crate time.
+#[cfg(not(feature = "chrono"))]
+pub(crate) type Timestamp = time::OffsetDateTime;
+
+// The offset, in seconds east of UTC, that the current logger uses instead of the local offset,
+// see `Logger::assume_utc_offset()`; None if the local offset is used.
+fn assumed_utc_offset() -> Option<i32> {
+    let o_offset = crate::formats::current_format_settings().o_utc_offset;
+    #[cfg(not(feature = "chrono"))]
+    {
+        if LOCAL_OFFSET_INDETERMINATE.load(Ordering::Relaxed) {
+            return o_offset.or(Some(0));
+        }
+    }
+    o_offset
+}
+
+// Is set when the local offset could not be determined;
+// from then on, UTC is used instead of the local offset.
+#[cfg(not(feature = "chrono"))]
+static LOCAL_OFFSET_INDETERMINATE: AtomicBool = AtomicBool::new(false);
+
+// The local offset cannot be determined (e.g. the time crate refuses to do it in
+// multi-threaded programs on some platforms): continue with UTC, and report this only once.
+#[cfg(not(feature = "chrono"))]
+fn fall_back_to_utc(e: &time::IndeterminateOffset) {
+    if !LOCAL_OFFSET_INDETERMINATE.swap(true, Ordering::Relaxed) {
+        crate::error_channel::report(format_args!(
+            "the local UTC offset cannot be determined ({}), timestamps are written in UTC; \
+             use Logger::assume_utc_offset() to set the offset explicitly",
+            e
+        ));
+    }
+}
+
+// The current time, in the local timezone.
+pub(crate) fn now() -> Timestamp {
+    #[cfg(feature = "chrono")]
+    return match assumed_utc_offset() {
+        None => chrono::Local::now(),
+        Some(offset) => chrono::DateTime::<chrono::Local>::from_utc(
+            chrono::Utc::now().naive_utc(),
+            chrono::FixedOffset::east(offset),
+        ),
+    };
+    #[cfg(not(feature = "chrono"))]
+    return match assumed_utc_offset() {
+        None => time::OffsetDateTime::try_now_local().unwrap_or_else(|e| {
+            fall_back_to_utc(&e);
+            time::OffsetDateTime::now_utc()
+        }),
+        Some(offset) => time::OffsetDateTime::now_utc().to_offset(time::UtcOffset::seconds(offset)),
+    };
+}
+
+// Converts a file time into a timestamp in the local timezone.
+#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+pub(crate) fn from_system_time(system_time: std::time::SystemTime) -> Timestamp {
+    #[cfg(feature = "chrono")]
+    return match assumed_utc_offset() {
+        None => system_time.into(),
+        Some(offset) => chrono::DateTime::<chrono::Local>::from_utc(
+            chrono::DateTime::<chrono::Utc>::from(system_time).naive_utc(),
+            chrono::FixedOffset::east(offset),
+        ),
+    };
+    #[cfg(not(feature = "chrono"))]
+    {
+        let offset = match assumed_utc_offset() {
+            None => time::UtcOffset::try_current_local_offset().unwrap_or_else(|e| {
+                fall_back_to_utc(&e);
+                time::UtcOffset::UTC
+            }),
+            Some(offset) => time::UtcOffset::seconds(offset),
+        };
+        time::OffsetDateTime::from(system_time).to_offset(offset)
+    }
+}
+
+// The timestamp as `time::OffsetDateTime`, for `DeferredNow::now_time()`.
+#[cfg(feature = "time")]
+pub(crate) fn to_time(timestamp: &Timestamp) -> time::OffsetDateTime {
+    #[cfg(feature = "chrono")]
+    return time::OffsetDateTime::from_unix_timestamp(timestamp.timestamp()).to_offset(
+        time::UtcOffset::seconds(timestamp.offset().local_minus_utc()),
+    ) + time::Duration::nanoseconds(i64::from(timestamp.timestamp_subsec_nanos()));
+    #[cfg(not(feature = "chrono"))]
+    return *timestamp;
+}
+
+// Seconds and subsecond nanoseconds since the unix epoch.
+#[cfg(feature = "fluent_writer")]
+pub(crate) fn unix_timestamp(timestamp: &Timestamp) -> (i64, u32) {
+    #[cfg(feature = "chrono")]
+    return (timestamp.timestamp(), timestamp.timestamp_subsec_nanos());
+    #[cfg(not(feature = "chrono"))]
+    return (timestamp.timestamp(), timestamp.nanosecond());
+}
+
+// The calendar and clock fields of a timestamp.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct Parts {
+    pub(crate) year: i32,
+    pub(crate) month: u8,
+    pub(crate) day: u8,
+    pub(crate) hour: u8,
+    pub(crate) minute: u8,
+    pub(crate) second: u8,
+    pub(crate) microsecond: u32,
+    // in seconds, east of UTC
+    pub(crate) utc_offset: i32,
+}
+impl Parts {
+    // The fields in the timezone of the timestamp.
+    pub(crate) fn of(timestamp: &Timestamp) -> Self {
+        #[cfg(feature = "chrono")]
+        return Self::of_chrono(timestamp, timestamp.offset().local_minus_utc());
+        #[cfg(not(feature = "chrono"))]
+        return Self::of_time(timestamp);
+    }
+
+    // The fields in UTC.
+    #[cfg(feature = "archive-s3")]
+    pub(crate) fn utc_of(timestamp: &Timestamp) -> Self {
+        #[cfg(feature = "chrono")]
+        return Self::of_chrono(&timestamp.with_timezone(&chrono::Utc), 0);
+        #[cfg(not(feature = "chrono"))]
+        return Self::of_time(&timestamp.to_offset(time::UtcOffset::UTC));
+    }
+
+    #[cfg(feature = "chrono")]
+    #[allow(clippy::cast_possible_truncation)]
+    fn of_chrono<T: chrono::Datelike + chrono::Timelike>(date_time: &T, utc_offset: i32) -> Self {
+        Self {
+            year: date_time.year(),
+            month: date_time.month() as u8,
+            day: date_time.day() as u8,
+            hour: date_time.hour() as u8,
+            minute: date_time.minute() as u8,
+            second: date_time.second() as u8,
+            // a leap second is represented with a nanosecond value beyond 999_999_999
+            microsecond: std::cmp::min(date_time.nanosecond() / 1_000, 999_999),
+            utc_offset,
+        }
+    }
+
+    #[cfg(not(feature = "chrono"))]
+    fn of_time(date_time: &time::OffsetDateTime) -> Self {
+        Self {
+            year: date_time.year(),
+            month: date_time.month(),
+            day: date_time.day(),
+            hour: date_time.hour(),
+            minute: date_time.minute(),
+            second: date_time.second(),
+            microsecond: date_time.microsecond(),
+            utc_offset: date_time.offset().as_seconds(),
+        }
+    }
+
+    pub(crate) fn same_day(&self, other: &Self) -> bool {
+        (self.year, self.month, self.day) == (other.year, other.month, other.day)
+    }
+
+    // The seconds since the unix epoch.
+    fn unix_seconds(&self) -> i64 {
+        // days since 1970-01-01 of the proleptic gregorian calendar,
+        // see http://howardhinnant.github.io/date_algorithms.html#days_from_civil
+        let month = i64::from(self.month);
+        let year = i64::from(self.year) - i64::from(month <= 2);
+        let era = if year >= 0 { year } else { year - 399 } / 400;
+        let year_of_era = year - era * 400;
+        let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5
+            + i64::from(self.day)
+            - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        let days = era * 146_097 + day_of_era - 719_468;
+        days * 86_400
+            + i64::from(self.hour) * 3_600
+            + i64::from(self.minute) * 60
+            + i64::from(self.second)
+            - i64::from(self.utc_offset)
+    }
+}
+
+/// Ready-made layouts for the timestamps of the provided format functions.
+///
+/// Used in [`Logger::timestamp_format`](crate::Logger::timestamp_format).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TimestampFormat {
+    /// `2016-01-13 15:25:01.640870 +01:00`, the default of the human-readable formats.
+    Default,
+    /// `2016-01-13 15:25:01.640 +01:00`, like `Default`, but with milliseconds.
+    DefaultMillis,
+    /// `2016-01-13T15:25:01.640+01:00`, RFC 3339 with milliseconds.
+    Rfc3339Millis,
+    /// `2016-01-13T15:25:01.640870+01:00`, RFC 3339 with microseconds,
+    /// the default of [`json_format`](crate::json_format).
+    Rfc3339Micros,
+    /// `1452695101`, the seconds since the Unix epoch.
+    UnixSeconds,
+    /// `1452695101640`, the milliseconds since the Unix epoch.
+    UnixMillis,
+}
+impl TimestampFormat {
+    fn layout(self) -> Layout {
+        match self {
+            Self::Default => Layout::LogLine,
+            Self::DefaultMillis => Layout::LogLineMillis,
+            Self::Rfc3339Millis => Layout::Rfc3339Millis,
+            Self::Rfc3339Micros => Layout::Rfc3339,
+            Self::UnixSeconds => Layout::UnixSeconds,
+            Self::UnixMillis => Layout::UnixMillis,
+        }
+    }
+}
+
+// The layout of the timestamp format that was chosen with `Logger::timestamp_format()`,
+// or the given one;
+// the timestamp format of the stream that is currently written takes precedence.
+pub(crate) fn chosen_layout_or(layout: Layout) -> Layout {
+    crate::formats::timestamp_format_override()
+        .or_else(|| crate::formats::current_format_settings().o_timestamp_format)
+        .map_or(layout, TimestampFormat::layout)
+}
+
+// The layouts in which flexi_logger writes timestamps.
+#[derive(Clone, Copy)]
+pub(crate) enum Layout {
+    // `2016-01-13 15:25:01.640870 +01:00`, used in log lines
+    LogLine,
+    // `2016-01-13 15:25:01.640 +01:00`
+    LogLineMillis,
+    // `2016-01-13T15:25:01.640870+01:00`
+    Rfc3339,
+    // `2016-01-13T15:25:01.640+01:00`
+    Rfc3339Millis,
+    // like `Rfc3339`, but with `Z` instead of `+00:00`
+    #[cfg_attr(not(feature = "sentry_writer"), allow(dead_code))]
+    Rfc3339Z,
+    // `2016-01-13_15-25-01`, used in file names
+    FileName,
+    // `1452695101`
+    UnixSeconds,
+    // `1452695101640`
+    UnixMillis,
+}
+impl Layout {
+    // Numbers are not quoted in json.
+    pub(crate) fn is_numeric(self) -> bool {
+        matches!(self, Self::UnixSeconds | Self::UnixMillis)
+    }
+}
+
+// Formats the timestamp lazily in the given layout.
+pub(crate) fn format(timestamp: &Timestamp, layout: Layout) -> impl fmt::Display {
+    Formatted(Parts::of(timestamp), layout)
+}
+
+struct Formatted(Parts, Layout);
+impl fmt::Display for Formatted {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let parts = &self.0;
+        let (date_time_sep, offset_sep) = match self.1 {
+            Layout::LogLine | Layout::LogLineMillis => (' ', " "),
+            Layout::Rfc3339 | Layout::Rfc3339Z | Layout::Rfc3339Millis => ('T', ""),
+            Layout::FileName => {
+                return write!(
+                    f,
+                    "{:04}-{:02}-{:02}_{:02}-{:02}-{:02}",
+                    parts.year, parts.month, parts.day, parts.hour, parts.minute, parts.second
+                );
+            }
+            Layout::UnixSeconds => return write!(f, "{}", parts.unix_seconds()),
+            Layout::UnixMillis => {
+                return write!(
+                    f,
+                    "{}{:03}",
+                    parts.unix_seconds(),
+                    parts.microsecond / 1_000
+                );
+            }
+        };
+        write!(
+            f,
+            "{:04}-{:02}-{:02}{}{:02}:{:02}:{:02}",
+            parts.year,
+            parts.month,
+            parts.day,
+            date_time_sep,
+            parts.hour,
+            parts.minute,
+            parts.second,
+        )?;
+        match self.1 {
+            Layout::LogLineMillis | Layout::Rfc3339Millis => {
+                write!(f, ".{:03}{}", parts.microsecond / 1_000, offset_sep)?;
+            }
+            _ => write!(f, ".{:06}{}", parts.microsecond, offset_sep)?,
+        }
+        if let Layout::Rfc3339Z = self.1 {
+            if parts.utc_offset == 0 {
+                return f.write_str("Z");
+            }
+        }
+        let sign = if parts.utc_offset < 0 { '-' } else { '+' };
+        let offset = parts.utc_offset.abs();
+        write!(
+            f,
+            "{}{:02}:{:02}",
+            sign,
+            offset / 3600,
+            (offset % 3600) / 60
+        )
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Formatted, Layout, Parts};
+
+    #[test]
+    fn layouts() {
+        let parts = Parts {
+            year: 2016,
+            month: 1,
+            day: 13,
+            hour: 15,
+            minute: 25,
+            second: 1,
+            microsecond: 640_870,
+            utc_offset: 3600,
+        };
+        let format = |parts, layout| Formatted(parts, layout).to_string();
+        assert_eq!(
+            format(parts, Layout::LogLine),
+            "2016-01-13 15:25:01.640870 +01:00"
+        );
+        assert_eq!(
+            format(parts, Layout::Rfc3339),
+            "2016-01-13T15:25:01.640870+01:00"
+        );
+        assert_eq!(
+            format(parts, Layout::Rfc3339Z),
+            "2016-01-13T15:25:01.640870+01:00"
+        );
+        assert_eq!(format(parts, Layout::FileName), "2016-01-13_15-25-01");
+        assert_eq!(
+            format(parts, Layout::LogLineMillis),
+            "2016-01-13 15:25:01.640 +01:00"
+        );
+        assert_eq!(
+            format(parts, Layout::Rfc3339Millis),
+            "2016-01-13T15:25:01.640+01:00"
+        );
+        assert_eq!(format(parts, Layout::UnixSeconds), "1452695101");
+        assert_eq!(format(parts, Layout::UnixMillis), "1452695101640");
+
+        let utc = Parts {
+            utc_offset: 0,
+            ..parts
+        };
+        assert_eq!(
+            format(utc, Layout::Rfc3339),
+            "2016-01-13T15:25:01.640870+00:00"
+        );
+        assert_eq!(format(utc, Layout::Rfc3339Z), "2016-01-13T15:25:01.640870Z");
+        let west = Parts {
+            utc_offset: -(9 * 3600 + 30 * 60),
+            ..parts
+        };
+        assert_eq!(
+            format(west, Layout::LogLine),
+            "2016-01-13 15:25:01.640870 -09:30"
+        );
+        assert_eq!(format(west, Layout::UnixSeconds), "1452732901");
+    }
+}
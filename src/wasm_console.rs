@@ -0,0 +1,36 @@
+// In web builds (wasm32 with crate feature `wasm-bindgen`), there is no stderr or stdout,
+// so `StdErrWriter` and `StdOutWriter` write to the console of the browser instead.
+use crate::deferred_now::DeferredNow;
+use crate::formats::format_with_fallback;
+use crate::FormatFunction;
+use log::{Level, Record};
+use wasm_bindgen::prelude::wasm_bindgen;
+
+#[wasm_bindgen]
+extern "C" {
+    #[wasm_bindgen(js_namespace = console, js_name = error)]
+    fn console_error(s: &str);
+    #[wasm_bindgen(js_namespace = console, js_name = warn)]
+    fn console_warn(s: &str);
+    #[wasm_bindgen(js_namespace = console, js_name = log)]
+    fn console_log(s: &str);
+}
+
+// Errors go to console.error and warnings to console.warn, so that the level filter
+// of the browser's developer tools applies; all other records go to console.log.
+pub(crate) fn write(
+    format: FormatFunction,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> std::io::Result<()> {
+    let mut buffer = Vec::<u8>::with_capacity(200);
+    format_with_fallback(format, &mut buffer, now, record);
+    let line = String::from_utf8_lossy(&buffer);
+    match record.level() {
+        Level::Error => console_error(&line),
+        Level::Warn => console_warn(&line),
+        _ => console_log(&line),
+    }
+    crate::stats::count_bytes_written(buffer.len());
+    Ok(())
+}
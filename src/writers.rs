@@ -5,7 +5,31 @@
 //! ([`FileLogWriter`](crate::writers::FileLogWriter))
 //! or to the syslog
 //! ([`SyslogWriter`](crate::writers::SyslogWriter)).
-//! You can also use your own implementations of `LogWriter`.
+//! With feature `fluent_writer`, log records can also be shipped to Fluentd or fluent-bit
+//! ([`FluentWriter`](crate::writers::FluentWriter)),
+//! with feature `kafka_writer` to Apache Kafka
+//! ([`KafkaWriter`](crate::writers::KafkaWriter)),
+//! with feature `http_writer` in batches to an HTTP endpoint
+//! ([`HttpWriter`](crate::writers::HttpWriter)),
+//! with feature `sqlite_writer` into a local `SQLite` database
+//! ([`SqliteWriter`](crate::writers::SqliteWriter)),
+//! with feature `sentry_writer` as error reports to Sentry or a webhook
+//! ([`SentryWriter`](crate::writers::SentryWriter)),
+//! with feature `logcat_writer` to the Android log
+//! ([`LogcatWriter`](crate::writers::LogcatWriter)),
+//! and with feature `oslog_writer` to Apple's unified logging
+//! ([`OsLogWriter`](crate::writers::OsLogWriter)).
+//! [`StdErrWriter`](crate::writers::StdErrWriter) and
+//! [`StdOutWriter`](crate::writers::StdOutWriter) write to stderr and stdout.
+//! You can also use your own implementations of `LogWriter`,
+//! combine several writers into one with a [`TeeWriter`](crate::writers::TeeWriter),
+//! restrict what a writer writes with a [`FilteredWriter`](crate::writers::FilteredWriter),
+//! and control when a writer is flushed with a [`FlushingWriter`](crate::writers::FlushingWriter).
+//!
+//! Writers that are based on async I/O, e.g. of tokio or async-std, can implement
+//! [`AsyncLogWriter`](crate::writers::AsyncLogWriter) instead, and be wrapped into an
+//! [`AsyncWriterAdapter`](crate::writers::AsyncWriterAdapter), which bridges the synchronous
+//! log calls through a background thread into the async writer.
 //!
 //! Such log writers can be used in two ways:
 //!
@@ -28,6 +52,14 @@
 //!   a comma-separated list of target names, within braces, is not sent to the default logger,
 //!   but to the loggers specified explicitly in the list.
 //!   In such a list you can again specify the default logger with the target name `_Default`.
+//!   Writers that are registered with
+//!   [`Logger::add_writer_with_fallthrough()`](crate::Logger::add_writer_with_fallthrough)
+//!   write to the default logger as well, as if `_Default` was specified.
+//!
+//!   Whitespace around the names is ignored, as are empty names, so `{Alert, Security}`
+//!   addresses the writers `Alert` and `Security`.
+//!   Names for which no writer is registered are reported once on stderr,
+//!   and are otherwise ignored; the other names in the list are served nevertheless.
 //!
 //!   These log calls will not be affected by the value of `flexi_logger`'s log specification;
 //!   they will always be written, as you might want it for alerts or auditing.
@@ -89,9 +121,65 @@
 //!
 //!   ```
 //!
+//! A [`FileLogWriter`](crate::writers::FileLogWriter) can also be used on its own,
+//! without `flexi_logger` becoming the global logger, e.g. from within another logging framework
+//! that only needs a destination with rotation and cleanup of the log files.
+//! Build it with [`FileLogWriter::builder()`](crate::writers::FileLogWriter::builder), and then
+//! either hand it `log::Record`s with [`LogWriter::write`](crate::writers::LogWriter::write),
+//! which applies its format function, or write preformatted records with
+//! [`FileLogWriter::write_raw`](crate::writers::FileLogWriter::write_raw),
+//! or with `std::io::Write` on a [`RawWriter`](crate::writers::RawWriter),
+//! which hands over complete lines.
+//! Since `FileLogWriter` is `Send` and `Sync`, it can be shared between threads in an `Arc`.
+//! The host framework can drive the files itself, with
+//! [`FileLogWriter::rotate_now`](crate::writers::FileLogWriter::rotate_now) and
+//! [`FileLogWriter::reopen`](crate::writers::FileLogWriter::reopen) (e.g. for `logrotate`),
+//! also via a [`FileLogWriterHandle`](crate::writers::FileLogWriterHandle).
+//! Call [`LogWriter::shutdown`](crate::writers::LogWriter::shutdown) when you are done.
+//!
+//! ```rust
+//! use flexi_logger::writers::{FileLogWriter, LogWriter};
+//! use flexi_logger::{Cleanup, Criterion, Naming};
+//! use std::io::Write;
+//!
+//! let file_log_writer = FileLogWriter::builder()
+//!     .directory("log_files/standalone_doc")
+//!     .rotate(Criterion::Size(10_000), Naming::Numbers, Cleanup::KeepLogFiles(3))
+//!     .try_build()
+//!     .unwrap();
+//!
+//! // e.g. within the event handling of another logging framework
+//! file_log_writer
+//!     .write_raw(b"2021-01-22 10:15:00 INFO [my_app] preformatted line\n")
+//!     .unwrap();
+//! let mut raw_writer = file_log_writer.raw_writer();
+//! writeln!(raw_writer, "2021-01-22 10:15:01 INFO [my_app] line {}", 2).unwrap();
+//! drop(raw_writer);
+//!
+//! file_log_writer.shutdown();
+//! ```
+//!
 
+mod async_log_writer;
 mod file_log_writer;
+mod filtered_writer;
+#[cfg(feature = "fluent_writer")]
+mod fluent_writer;
+mod flushing_writer;
+#[cfg(feature = "http_writer")]
+mod http_writer;
+#[cfg(feature = "kafka_writer")]
+mod kafka_writer;
 mod log_writer;
+#[cfg(all(feature = "logcat_writer", target_os = "android"))]
+mod logcat_writer;
+#[cfg(all(feature = "oslog_writer", any(target_os = "macos", target_os = "ios")))]
+mod oslog_writer;
+#[cfg(feature = "sentry_writer")]
+mod sentry_writer;
+#[cfg(feature = "sqlite_writer")]
+mod sqlite_writer;
+mod tee_writer;
 
 #[cfg(feature = "syslog_writer")]
 mod syslog_writer;
@@ -101,5 +189,39 @@ pub use self::syslog_writer::{
     LevelToSyslogSeverity, SyslogConnector, SyslogFacility, SyslogSeverity, SyslogWriter,
 };
 
-pub use self::file_log_writer::{FileLogWriter, FileLogWriterBuilder};
+pub use self::async_log_writer::{
+    AsyncLogWriter, AsyncWriteFuture, AsyncWriterAdapter, AsyncWriterAdapterBuilder, BlockOn,
+};
+#[cfg(feature = "integrity")]
+pub use self::file_log_writer::verify_hash_chain;
+#[cfg(feature = "encrypt")]
+pub use self::file_log_writer::Encryption;
+pub use self::file_log_writer::{
+    CleanupCandidate, CloseReason, FileFooter, FileHeader, FileLogWriter, FileLogWriterBuilder,
+    FileLogWriterHandle, RawWriter, RotationCallback, RotationEvent,
+};
+#[cfg(feature = "archive-s3")]
+pub use self::file_log_writer::{S3Archive, S3ArchiveBuilder};
+pub use self::filtered_writer::{FilteredWriter, RecordFilter};
+#[cfg(feature = "fluent_writer")]
+pub use self::fluent_writer::{FluentWriter, FluentWriterBuilder};
+pub use self::flushing_writer::FlushingWriter;
+#[cfg(feature = "http_writer")]
+pub use self::http_writer::{HttpWriter, HttpWriterBuilder};
+#[cfg(feature = "kafka_writer")]
+pub use self::kafka_writer::{
+    DeliveryFailureCallback, KafkaPartitioning, KafkaWriter, KafkaWriterBuilder,
+};
 pub use self::log_writer::LogWriter;
+#[cfg(all(feature = "logcat_writer", target_os = "android"))]
+pub use self::logcat_writer::LogcatWriter;
+#[cfg(all(feature = "oslog_writer", any(target_os = "macos", target_os = "ios")))]
+pub use self::oslog_writer::OsLogWriter;
+#[cfg(feature = "sentry_writer")]
+pub use self::sentry_writer::{SentryWriter, SentryWriterBuilder};
+#[cfg(feature = "sqlite_writer")]
+pub use self::sqlite_writer::{SqliteWriter, SqliteWriterBuilder};
+pub use self::tee_writer::TeeWriter;
+pub use crate::primary_writer::{StdErrWriter, StdOutWriter};
+#[cfg(feature = "kafka_writer")]
+pub use kafka::producer::RequiredAcks;
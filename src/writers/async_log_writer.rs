@@ -0,0 +1,336 @@
+use crate::deferred_now::DeferredNow;
+use crate::formats::format_with_fallback;
+use crate::writers::LogWriter;
+use crate::{default_format, FlexiLoggerError, FormatFunction};
+use log::Record;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::mpsc::{channel, sync_channel, Sender, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+use std::thread::{JoinHandle, Thread};
+
+/// The future type that is returned by the methods of [`AsyncLogWriter`].
+pub type AsyncWriteFuture<'a> = Pin<Box<dyn Future<Output = std::io::Result<()>> + Send + 'a>>;
+
+/// Signature of a function that drives an [`AsyncWriteFuture`] to completion.
+///
+/// It is called on the background thread of the [`AsyncWriterAdapter`].
+/// With tokio, e.g., this would typically be
+/// `Box::new(move |future| runtime_handle.block_on(future))`.
+pub type BlockOn = Box<dyn Fn(AsyncWriteFuture<'_>) -> std::io::Result<()> + Send>;
+
+/// Writes to a single log output stream, using asynchronous I/O.
+///
+/// The trait is object-safe and independent of a concrete async runtime,
+/// so it can be implemented e.g. on top of tokio's or async-std's network types.
+///
+/// An `AsyncLogWriter` is not used directly by `flexi_logger`;
+/// it has to be wrapped into an [`AsyncWriterAdapter`], which implements [`LogWriter`],
+/// formats the log records in the calling thread,
+/// and hands the formatted lines over to a background thread that calls the `AsyncLogWriter`.
+pub trait AsyncLogWriter: Send {
+    /// Writes out a formatted log line, including its line ending.
+    fn write_async<'a>(&'a mut self, buffer: &'a [u8]) -> AsyncWriteFuture<'a>;
+
+    /// Flushes any buffered data.
+    fn flush_async(&mut self) -> AsyncWriteFuture<'_>;
+
+    /// Cleanup open resources, if necessary.
+    ///
+    /// The default implementation does nothing.
+    fn shutdown_async(&mut self) -> AsyncWriteFuture<'_> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+// Upper limit for the number of line buffers that are kept for reuse.
+const MAX_POOLED_BUFFERS: usize = 64;
+
+// Default for the number of log lines that can wait for the background thread.
+const DEFAULT_CHANNEL_CAPACITY: usize = 10_000;
+
+enum Message {
+    Write(Vec<u8>),
+    Flush(Sender<std::io::Result<()>>),
+    Shutdown,
+}
+
+/// A [`LogWriter`] that bridges the synchronous log calls into an [`AsyncLogWriter`].
+///
+/// The log lines are formatted in the calling thread and then sent through a channel
+/// to a background thread, which drives the futures of the `AsyncLogWriter`
+/// with the configured [`BlockOn`] function.
+/// The channel has a limited capacity; if the background thread falls behind
+/// and the channel is full, further log lines are dropped (and counted, see
+/// [`LoggerHandle::stats`](crate::LoggerHandle::stats)) rather than blocking the logging threads.
+///
+/// See [writers](crate::writers) for guidance how to use additional log writers.
+pub struct AsyncWriterAdapter {
+    format: FormatFunction,
+    max_log_level: log::LevelFilter,
+    sender: Mutex<SyncSender<Message>>,
+    // the background thread returns the line buffers here, for reuse
+    buffer_pool: Arc<Mutex<Vec<Vec<u8>>>>,
+    o_join_handle: Mutex<Option<JoinHandle<()>>>,
+}
+impl AsyncWriterAdapter {
+    /// Instantiates a builder for an `AsyncWriterAdapter` around the given `AsyncLogWriter`.
+    #[must_use]
+    pub fn builder(writer: Box<dyn AsyncLogWriter>) -> AsyncWriterAdapterBuilder {
+        AsyncWriterAdapterBuilder {
+            writer,
+            format: default_format,
+            max_log_level: log::LevelFilter::Trace,
+            o_block_on: None,
+            channel_capacity: DEFAULT_CHANNEL_CAPACITY,
+        }
+    }
+}
+
+impl LogWriter for AsyncWriterAdapter {
+    fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        let mut buffer = self
+            .buffer_pool
+            .lock()
+            .ok()
+            .and_then(|mut pool| pool.pop())
+            .unwrap_or_else(|| Vec::with_capacity(200));
+        format_with_fallback(self.format, &mut buffer, now, record);
+        buffer.push(b'\n');
+        match self
+            .sender
+            .lock()
+            .map_err(|_| broken_channel())?
+            .try_send(Message::Write(buffer))
+        {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(_)) => {
+                crate::stats::count_dropped_records(1);
+                crate::error_channel::report(format_args!(
+                    "AsyncWriterAdapter is overloaded and drops a log line"
+                ));
+                Ok(())
+            }
+            Err(TrySendError::Disconnected(_)) => Err(broken_channel()),
+        }
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        let (result_sender, result_receiver) = channel();
+        self.send(Message::Flush(result_sender))?;
+        result_receiver.recv().map_err(|_| broken_channel())?
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.max_log_level
+    }
+
+    fn format(&mut self, format: FormatFunction) {
+        self.format = format;
+    }
+
+    fn shutdown(&self) {
+        self.send(Message::Shutdown).ok();
+        if let Ok(mut o_join_handle) = self.o_join_handle.lock() {
+            if let Some(join_handle) = o_join_handle.take() {
+                join_handle.join().ok();
+            }
+        }
+    }
+}
+impl AsyncWriterAdapter {
+    // Waits if the channel is full; control messages must not be dropped.
+    fn send(&self, message: Message) -> std::io::Result<()> {
+        self.sender
+            .lock()
+            .map_err(|_| broken_channel())?
+            .send(message)
+            .map_err(|_| broken_channel())
+    }
+}
+
+fn broken_channel() -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::BrokenPipe,
+        "the background thread of the AsyncWriterAdapter is gone",
+    )
+}
+
+/// Builder for [`AsyncWriterAdapter`].
+#[allow(clippy::module_name_repetitions)]
+pub struct AsyncWriterAdapterBuilder {
+    writer: Box<dyn AsyncLogWriter>,
+    format: FormatFunction,
+    max_log_level: log::LevelFilter,
+    o_block_on: Option<BlockOn>,
+    channel_capacity: usize,
+}
+impl AsyncWriterAdapterBuilder {
+    /// Makes the writer use the given format function for the log lines.
+    ///
+    /// Defaults to [`default_format`](crate::default_format).
+    #[must_use]
+    pub fn format(mut self, format: FormatFunction) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the maximum log level that is to be written.
+    ///
+    /// Defaults to `log::LevelFilter::Trace`.
+    #[must_use]
+    pub fn max_log_level(mut self, max_log_level: log::LevelFilter) -> Self {
+        self.max_log_level = max_log_level;
+        self
+    }
+
+    /// Sets the function that drives the futures of the `AsyncLogWriter` to completion.
+    ///
+    /// Use this to run the futures within your async runtime, which is necessary if the
+    /// `AsyncLogWriter` uses I/O types that depend on a runtime context (like tokio's).
+    ///
+    /// By default, a minimal executor is used that parks the background thread
+    /// until the future is woken up; this is sufficient for futures that do not depend on
+    /// a runtime context.
+    #[must_use]
+    pub fn block_on(mut self, block_on: BlockOn) -> Self {
+        self.o_block_on = Some(block_on);
+        self
+    }
+
+    /// Sets the number of log lines that can wait for the background thread;
+    /// when the channel is full, further log lines are dropped.
+    ///
+    /// Defaults to 10,000.
+    #[must_use]
+    pub fn channel_capacity(mut self, channel_capacity: usize) -> Self {
+        self.channel_capacity = channel_capacity;
+        self
+    }
+
+    /// Spawns the background thread and produces the `AsyncWriterAdapter`.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::OutputIo` if the background thread cannot be spawned.
+    pub fn try_build(self) -> Result<AsyncWriterAdapter, FlexiLoggerError> {
+        let mut writer = self.writer;
+        let block_on = self.o_block_on.unwrap_or_else(|| Box::new(park_on));
+        let (sender, receiver) = sync_channel::<Message>(self.channel_capacity);
+        let buffer_pool = Arc::new(Mutex::new(Vec::new()));
+        let buffer_pool_for_thread = Arc::clone(&buffer_pool);
+
+        let join_handle = std::thread::Builder::new()
+            .name("flexi_logger-async_writer".to_string())
+            .spawn(move || {
+                for message in receiver {
+                    match message {
+                        Message::Write(mut buffer) => {
+                            block_on(writer.write_async(&buffer)).unwrap_or_else(|e| {
+                                crate::error_channel::report(format_args!(
+                                    "AsyncLogWriter: writing failed with {}",
+                                    e
+                                ));
+                            });
+                            if let Ok(mut pool) = buffer_pool_for_thread.lock() {
+                                if pool.len() < MAX_POOLED_BUFFERS {
+                                    buffer.clear();
+                                    pool.push(buffer);
+                                }
+                            }
+                        }
+                        Message::Flush(result_sender) => {
+                            result_sender.send(block_on(writer.flush_async())).ok();
+                        }
+                        Message::Shutdown => {
+                            break;
+                        }
+                    }
+                }
+                block_on(writer.flush_async()).ok();
+                block_on(writer.shutdown_async()).ok();
+            })?;
+
+        Ok(AsyncWriterAdapter {
+            format: self.format,
+            max_log_level: self.max_log_level,
+            sender: Mutex::new(sender),
+            buffer_pool,
+            o_join_handle: Mutex::new(Some(join_handle)),
+        })
+    }
+}
+
+// Minimal executor: polls the future and parks the current thread until it is woken up.
+fn park_on(mut future: AsyncWriteFuture<'_>) -> std::io::Result<()> {
+    let thread = std::thread::current();
+    let waker = unsafe { Waker::from_raw(raw_waker(&thread)) };
+    let mut context = Context::from_waker(&waker);
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(result) => return result,
+            Poll::Pending => std::thread::park(),
+        }
+    }
+}
+
+// The waker only borrows the `Thread`, which outlives it in `park_on`;
+// clones that are kept by the future therefore get their own boxed `Thread`.
+fn raw_waker(thread: &Thread) -> RawWaker {
+    let data: *const Thread = thread;
+    RawWaker::new(data.cast::<()>(), &BORROWED_VTABLE)
+}
+
+static BORROWED_VTABLE: RawWakerVTable =
+    RawWakerVTable::new(clone_waker, wake_by_ref, wake_by_ref, |_| {});
+static OWNED_VTABLE: RawWakerVTable =
+    RawWakerVTable::new(clone_waker, wake_owned, wake_by_ref, drop_owned);
+
+unsafe fn clone_waker(data: *const ()) -> RawWaker {
+    let thread = Box::new((*data.cast::<Thread>()).clone());
+    RawWaker::new(Box::into_raw(thread) as *const (), &OWNED_VTABLE)
+}
+unsafe fn wake_by_ref(data: *const ()) {
+    (*data.cast::<Thread>()).unpark();
+}
+unsafe fn wake_owned(data: *const ()) {
+    wake_by_ref(data);
+    drop_owned(data);
+}
+unsafe fn drop_owned(data: *const ()) {
+    drop(Box::from_raw(data as *mut Thread));
+}
+
+#[cfg(test)]
+mod test {
+    use super::{park_on, AsyncWriteFuture};
+    use std::future::Future;
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    // A future that is pending a few times, and wakes itself up from another thread.
+    struct Delayed(u8);
+    impl Future for Delayed {
+        type Output = std::io::Result<()>;
+        fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+            if self.0 == 0 {
+                Poll::Ready(Ok(()))
+            } else {
+                self.0 -= 1;
+                let waker = cx.waker().clone();
+                std::thread::spawn(move || {
+                    std::thread::sleep(std::time::Duration::from_millis(5));
+                    waker.wake();
+                });
+                Poll::Pending
+            }
+        }
+    }
+
+    #[test]
+    fn test_park_on() {
+        let future: AsyncWriteFuture = Box::pin(Delayed(3));
+        park_on(future).unwrap();
+    }
+}
@@ -1,18 +1,47 @@
 mod builder;
+mod cleanup_candidate;
 mod config;
+#[cfg(feature = "encrypt")]
+mod encryption;
+mod footer;
+#[cfg(feature = "integrity")]
+mod hash_chain;
+mod header;
+mod record_boundaries;
+mod rotation_event;
+mod rotation_lock;
+#[cfg(feature = "archive-s3")]
+mod s3_archive;
+mod shards;
 mod state;
+mod structured_file;
+mod sync_state;
 
 pub use self::builder::FileLogWriterBuilder;
+pub use self::cleanup_candidate::CleanupCandidate;
+#[cfg(feature = "encrypt")]
+pub use self::encryption::Encryption;
+pub use self::footer::{CloseReason, FileFooter};
+#[cfg(feature = "integrity")]
+pub use self::hash_chain::verify_hash_chain;
+pub use self::header::FileHeader;
+pub use self::rotation_event::{RotationCallback, RotationEvent};
+#[cfg(feature = "archive-s3")]
+pub use self::s3_archive::{S3Archive, S3ArchiveBuilder};
 
 use self::config::{Config, FilenameConfig, RotationConfig};
+use crate::formats::{format_with_fallback, ReplaceableFormat};
 use crate::primary_writer::buffer_with;
 use crate::writers::LogWriter;
-use crate::{DeferredNow, FormatFunction};
+use crate::{DeferredNow, FlexiLoggerError, FormatFunction};
 use log::Record;
-use state::State;
+use shards::Shards;
+use state::{State, States};
 use std::io::Write;
 use std::path::PathBuf;
-use std::sync::Mutex;
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 const WINDOWS_LINE_ENDING: &[u8] = b"\r\n";
 const UNIX_LINE_ENDING: &[u8] = b"\n";
@@ -22,29 +51,88 @@ const UNIX_LINE_ENDING: &[u8] = b"\n";
 /// See [writers](crate::writers) for usage guidance.
 #[allow(clippy::module_name_repetitions)]
 pub struct FileLogWriter {
-    format: FormatFunction,
+    // the format can be changed at runtime via the specfile
+    format: ReplaceableFormat,
+    // the format of the optional structured file
+    o_structured_format: Option<FormatFunction>,
     line_ending: &'static [u8],
-    // the state needs to be mutable; since `Log.log()` requires an unmutable self,
-    // which translates into a non-mutating `LogWriter::write()`,
-    // we need internal mutability and thread-safety.
-    state: Mutex<State>,
+    // the files and their staging buffers, shared with the handles
+    handle: FileLogWriterHandle,
     max_log_level: log::LevelFilter,
 }
 impl FileLogWriter {
     pub(crate) fn new(
         format: FormatFunction,
+        o_structured_format: Option<FormatFunction>,
         line_ending: &'static [u8],
-        state: Mutex<State>,
+        states: States,
+        o_shards: Option<Shards>,
         max_log_level: log::LevelFilter,
     ) -> FileLogWriter {
         FileLogWriter {
-            format,
+            format: ReplaceableFormat::new(format),
+            o_structured_format,
             line_ending,
-            state,
+            handle: FileLogWriterHandle {
+                states: Arc::new(states),
+                o_shards: o_shards.map(Arc::new),
+            },
             max_log_level,
         }
     }
 
+    // Writes the formatted record, either directly or via the shard of the current thread;
+    // with a structured file, there are no shards.
+    fn write_record(&self, record: &[u8], o_structured: Option<&[u8]>) -> std::io::Result<()> {
+        match self.handle.o_shards {
+            Some(ref shards) => {
+                shards.append(record, |records| self.handle.write_to_file(records, None))
+            }
+            None => self.handle.write_to_file(record, o_structured),
+        }
+    }
+
+    // Formats the record for the structured file, if there is one.
+    fn format_structured(&self, now: &mut DeferredNow, record: &Record) -> Option<Vec<u8>> {
+        self.o_structured_format.map(|format| {
+            let mut buffer = Vec::<u8>::with_capacity(200);
+            format_with_fallback(format, &mut buffer, now, record);
+            buffer
+                .write_all(self.line_ending)
+                .unwrap_or_else(|e| write_err(ERR_2, &e));
+            buffer
+        })
+    }
+
+    /// Writes preformatted output as one record, bypassing the format function,
+    /// e.g. a record of another logging framework.
+    ///
+    /// The bytes should consist of complete lines, including the line ending,
+    /// because the file may be rotated between two calls.
+    /// See [`raw_writer`](crate::writers::FileLogWriter::raw_writer) for output that
+    /// does not arrive in complete lines.
+    ///
+    /// # Errors
+    ///
+    /// `std::io::Error` if writing fails.
+    pub fn write_raw(&self, record: &[u8]) -> std::io::Result<()> {
+        // raw output is written directly, after the records that wait in the shards
+        self.handle.drain_shards()?;
+        self.handle.write_to_file(record, None)
+    }
+
+    /// Returns an `std::io::Write` for preformatted output, which hands over
+    /// complete lines as records, see [`RawWriter`](crate::writers::RawWriter).
+    ///
+    /// Each thread should use its own `RawWriter`.
+    #[must_use]
+    pub fn raw_writer(&self) -> RawWriter<'_> {
+        RawWriter {
+            file_log_writer: self,
+            buffer: Vec::new(),
+        }
+    }
+
     /// Instantiates a builder for `FileLogWriter`.
     #[must_use]
     pub fn builder() -> FileLogWriterBuilder {
@@ -54,31 +142,209 @@ impl FileLogWriter {
     /// Returns a reference to its configured output format function.
     #[inline]
     pub fn format(&self) -> FormatFunction {
-        self.format
+        self.format.load()
+    }
+
+    // Replaces the format function.
+    #[cfg(feature = "specfile_without_notification")]
+    pub(crate) fn set_format(&self, format: FormatFunction) {
+        self.format.store(format);
     }
 
     #[doc(hidden)]
     pub fn current_filename(&self) -> PathBuf {
-        self.state.lock().unwrap().current_filename()
+        self.current_path()
+    }
+
+    /// Returns the path of the file that is currently written to.
+    ///
+    /// With [`per_thread`](crate::writers::FileLogWriterBuilder::per_thread),
+    /// this is the file of the calling thread.
+    #[must_use]
+    pub fn current_path(&self) -> PathBuf {
+        self.handle.current_path()
+    }
+
+    /// Closes the log file and opens it again under its configured path.
+    ///
+    /// See [`FileLogWriterHandle::reopen`](crate::writers::FileLogWriterHandle::reopen).
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::OutputIo` if the file cannot be opened.
+    pub fn reopen(&self) -> Result<(), FlexiLoggerError> {
+        self.handle.reopen()
+    }
+
+    /// Rotates the log file now, regardless of the rotation criterion.
+    ///
+    /// See [`FileLogWriterHandle::rotate_now`](crate::writers::FileLogWriterHandle::rotate_now).
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::RotationConfigConflict` if the `FileLogWriter` has no rotation,
+    /// `FlexiLoggerError::OutputIo` if the rotation fails.
+    pub fn rotate_now(&self) -> Result<(), FlexiLoggerError> {
+        self.handle.rotate_now()
+    }
+
+    /// Waits until the cleanup thread has done the work that was requested so far.
+    ///
+    /// See [`FileLogWriterHandle::wait_for_cleanup`](crate::writers::FileLogWriterHandle::wait_for_cleanup).
+    #[must_use]
+    pub fn wait_for_cleanup(&self, timeout: Duration) -> bool {
+        self.handle.wait_for_cleanup(timeout)
+    }
+
+    /// Returns what the cleanup would do now with the rotated files, without doing it.
+    ///
+    /// See [`FileLogWriterHandle::list_cleanup_candidates`](crate::writers::FileLogWriterHandle::list_cleanup_candidates).
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::OutputIo` if the files cannot be listed.
+    pub fn list_cleanup_candidates(&self) -> Result<Vec<CleanupCandidate>, FlexiLoggerError> {
+        self.handle.list_cleanup_candidates()
+    }
+
+    /// Returns a handle that allows controlling the files of this `FileLogWriter`
+    /// from other places, e.g. after the `FileLogWriter` was handed over to a logger.
+    #[must_use]
+    pub fn handle(&self) -> FileLogWriterHandle {
+        self.handle.clone()
+    }
+}
+
+/// Controls the files of a [`FileLogWriter`](crate::writers::FileLogWriter).
+///
+/// Is obtained with [`FileLogWriter::handle`](crate::writers::FileLogWriter::handle),
+/// and can be cloned and shared between threads; this allows e.g. a host framework,
+/// or a signal handling thread that cooperates with `logrotate`,
+/// to drive the rotation of the log files itself.
+#[derive(Clone)]
+#[allow(clippy::module_name_repetitions)]
+pub struct FileLogWriterHandle {
+    // the state needs to be mutable; since `Log.log()` requires an unmutable self,
+    // which translates into a non-mutating `LogWriter::write()`,
+    // we need internal mutability and thread-safety, which `States` provides.
+    states: Arc<States>,
+    // optional staging buffers that reduce the contention on `states`
+    o_shards: Option<Arc<Shards>>,
+}
+impl FileLogWriterHandle {
+    fn write_to_file(&self, records: &[u8], o_structured: Option<&[u8]>) -> std::io::Result<()> {
+        self.states
+            .with_current(|state| state.write_buffer(records, o_structured))
+            .unwrap()
+    }
+
+    // Writes the records from the shards, if any, to the file.
+    fn drain_shards(&self) -> std::io::Result<()> {
+        match self.o_shards {
+            Some(ref shards) => shards.drain(|records| self.write_to_file(records, None)),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the path of the file that is currently written to.
+    ///
+    /// With [`per_thread`](crate::writers::FileLogWriterBuilder::per_thread),
+    /// this is the file of the calling thread.
+    #[must_use]
+    pub fn current_path(&self) -> PathBuf {
+        self.states
+            .with_current(|state| state.current_filename())
+            .unwrap()
+    }
+
+    /// Closes the log file and opens it again under its configured path;
+    /// a file that still exists is appended to.
+    ///
+    /// Call this method after an external tool like `logrotate` has renamed the log file
+    /// (e.g. from the handler of the signal that `logrotate` sends in its `postrotate` script),
+    /// so that the subsequent records go to a new file with the original name.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::OutputIo` if the file cannot be opened,
+    /// `FlexiLoggerError::Poison` if the `FileLogWriter` is unusable after a panic.
+    pub fn reopen(&self) -> Result<(), FlexiLoggerError> {
+        self.drain_shards()?;
+        self.states.reopen()
+    }
+
+    /// Rotates the log file now, regardless of the rotation criterion,
+    /// in the same way as if the criterion was met.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::RotationConfigConflict` if the `FileLogWriter` was built without
+    /// [`rotate`](crate::writers::FileLogWriterBuilder::rotate),
+    /// `FlexiLoggerError::OutputIo` if the rotation fails,
+    /// `FlexiLoggerError::Poison` if the `FileLogWriter` is unusable after a panic.
+    pub fn rotate_now(&self) -> Result<(), FlexiLoggerError> {
+        self.drain_shards()?;
+        self.states.rotate_now()
+    }
+
+    /// Returns what the cleanup would do now with the rotated files, under the configured
+    /// [`Cleanup`](crate::Cleanup) strategy, without doing it.
+    ///
+    /// This allows verifying the retention settings before they are enabled.
+    /// The candidates are returned in the order in which the cleanup would process them.
+    /// Without rotation, the list is empty.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::OutputIo` if the files cannot be listed,
+    /// `FlexiLoggerError::Poison` if the `FileLogWriter` is unusable after a panic.
+    pub fn list_cleanup_candidates(&self) -> Result<Vec<CleanupCandidate>, FlexiLoggerError> {
+        self.states.cleanup_candidates()
+    }
+
+    /// Waits until the cleanup thread has done the work that was requested so far,
+    /// i.e., the compression and deletion of the rotated files,
+    /// but not longer than the given timeout.
+    ///
+    /// Returns false if the timeout elapsed before the cleanup was finished.
+    /// Returns true immediately if the cleanup is not done in a background thread
+    /// (see [`cleanup_in_background_thread`](crate::writers::FileLogWriterBuilder::cleanup_in_background_thread)).
+    #[must_use]
+    pub fn wait_for_cleanup(&self, timeout: Duration) -> bool {
+        // the lock is not held while waiting, so that logging can continue
+        let ack_receivers = self.states.cleanup_acks();
+        let deadline = Instant::now() + timeout;
+        ack_receivers.iter().all(|ack_receiver| {
+            let now = Instant::now();
+            let remaining = if now < deadline {
+                deadline - now
+            } else {
+                Duration::from_secs(0)
+            };
+            // a disconnected channel means that the cleanup thread is gone
+            match ack_receiver.recv_timeout(remaining) {
+                Err(RecvTimeoutError::Timeout) => false,
+                Ok(()) | Err(RecvTimeoutError::Disconnected) => true,
+            }
+        })
     }
 }
 
 impl LogWriter for FileLogWriter {
     #[inline]
     fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        let format = self.format();
+        let o_structured = self.format_structured(now, record);
+        let o_structured = o_structured.as_ref().map(|buffer| &buffer[..]);
         buffer_with(|tl_buf| match tl_buf.try_borrow_mut() {
             Ok(mut buffer) => {
-                (self.format)(&mut *buffer, now, record).unwrap_or_else(|e| write_err(ERR_1, &e));
-
-                let mut state_guard = self.state.lock().unwrap();
-                let state = &mut *state_guard;
+                format_with_fallback(format, &mut *buffer, now, record);
 
                 buffer
                     .write_all(self.line_ending)
                     .unwrap_or_else(|e| write_err(ERR_2, &e));
 
-                state
-                    .write_buffer(&*buffer)
+                self.write_record(&*buffer, o_structured)
                     .unwrap_or_else(|e| write_err(ERR_2, &e));
                 buffer.clear();
             }
@@ -88,17 +354,13 @@ impl LogWriter for FileLogWriter {
                 // we print the inner calls, in chronological order, before finally the
                 // outer most message is printed
                 let mut tmp_buf = Vec::<u8>::with_capacity(200);
-                (self.format)(&mut tmp_buf, now, record).unwrap_or_else(|e| write_err(ERR_1, &e));
-
-                let mut state_guard = self.state.lock().unwrap();
-                let state = &mut *state_guard;
+                format_with_fallback(format, &mut tmp_buf, now, record);
 
                 tmp_buf
                     .write_all(self.line_ending)
                     .unwrap_or_else(|e| write_err(ERR_2, &e));
 
-                state
-                    .write_buffer(&tmp_buf)
+                self.write_record(&tmp_buf, o_structured)
                     .unwrap_or_else(|e| write_err(ERR_2, &e));
             }
         });
@@ -108,11 +370,8 @@ impl LogWriter for FileLogWriter {
 
     #[inline]
     fn flush(&self) -> std::io::Result<()> {
-        if let Ok(ref mut state) = self.state.lock() {
-            state.flush()
-        } else {
-            Ok(())
-        }
+        self.handle.drain_shards()?;
+        self.handle.states.flush()
     }
 
     #[inline]
@@ -120,33 +379,95 @@ impl LogWriter for FileLogWriter {
         self.max_log_level
     }
 
+    // The current log file must be writable.
+    fn health_check(&self) -> std::io::Result<()> {
+        std::fs::OpenOptions::new()
+            .append(true)
+            .open(self.current_path())
+            .map(|_| ())
+    }
+
     #[doc(hidden)]
     fn validate_logs(&self, expected: &[(&'static str, &'static str, &'static str)]) {
-        if let Ok(ref mut state) = self.state.lock() {
-            state.validate_logs(expected)
-        }
+        self.handle.drain_shards().ok();
+        self.handle
+            .states
+            .with_current(|state| state.validate_logs(expected))
+            .ok();
     }
 
     fn shutdown(&self) {
-        // do nothing in case of poison errors
-        if let Ok(ref mut state) = self.state.lock() {
-            state.shutdown();
+        self.handle
+            .drain_shards()
+            .unwrap_or_else(|e| write_err(ERR_2, &e));
+        self.handle.states.shutdown();
+    }
+}
+
+impl Drop for FileLogWriter {
+    fn drop(&mut self) {
+        // the records in the staging buffers would otherwise be lost
+        self.handle
+            .drain_shards()
+            .unwrap_or_else(|e| write_err(ERR_2, &e));
+    }
+}
+
+/// An `std::io::Write` for preformatted output to a [`FileLogWriter`],
+/// see [`FileLogWriter::raw_writer`](crate::writers::FileLogWriter::raw_writer).
+///
+/// Collects what is written until a line is complete, and hands over the complete lines
+/// as one record, so that lines are neither split by rotation nor mixed with the output
+/// of other threads. An incomplete last line is handed over by `flush` and when the
+/// `RawWriter` is dropped.
+pub struct RawWriter<'a> {
+    file_log_writer: &'a FileLogWriter,
+    buffer: Vec<u8>,
+}
+impl Write for RawWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.buffer.extend_from_slice(buf);
+        if let Some(pos) = self.buffer.iter().rposition(|b| *b == b'\n') {
+            let incomplete = self.buffer.split_off(pos + 1);
+            let lines = std::mem::replace(&mut self.buffer, incomplete);
+            self.file_log_writer.write_raw(&lines)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.hand_over_incomplete_line()?;
+        LogWriter::flush(self.file_log_writer)
+    }
+}
+impl RawWriter<'_> {
+    fn hand_over_incomplete_line(&mut self) -> std::io::Result<()> {
+        if self.buffer.is_empty() {
+            Ok(())
+        } else {
+            let line = std::mem::take(&mut self.buffer);
+            self.file_log_writer.write_raw(&line)
         }
     }
 }
+impl Drop for RawWriter<'_> {
+    fn drop(&mut self) {
+        self.hand_over_incomplete_line()
+            .unwrap_or_else(|e| write_err(ERR_2, &e));
+    }
+}
 
-const ERR_1: &str = "FileLogWriter: formatting failed with ";
 const ERR_2: &str = "FileLogWriter: writing failed with ";
 
 fn write_err(msg: &str, err: &std::io::Error) {
-    eprintln!("[flexi_logger] {} with {}", msg, err);
+    crate::error_channel::report(format_args!("{} with {}", msg, err));
 }
 
 #[cfg(test)]
 mod test {
+    use crate::timestamp::{self, Layout};
     use crate::writers::LogWriter;
     use crate::{Cleanup, Criterion, DeferredNow, Naming};
-    use chrono::Local;
 
     use std::ops::Add;
     use std::path::{Path, PathBuf};
@@ -166,100 +487,103 @@ mod test {
     #[test]
     fn test_rotate_no_append_numbers() {
         // we use timestamp as discriminant to allow repeated runs
-        let ts = Local::now()
-            .format("false-numbers-%Y-%m-%d_%H-%M-%S")
-            .to_string();
+        let ts = format!(
+            "false-numbers-{}",
+            timestamp::format(&timestamp::now(), Layout::FileName)
+        );
         let naming = Naming::Numbers;
 
         // ensure we start with -/-/-
-        assert!(not_exists("00000", &ts));
-        assert!(not_exists("00001", &ts));
-        assert!(not_exists("CURRENT", &ts));
+        assert!(not_exists(".0", &ts));
+        assert!(not_exists(".1", &ts));
+        assert!(not_exists("", &ts));
 
         // ensure this produces -/-/ONE
         write_loglines(false, naming, &ts, &[ONE]);
-        assert!(not_exists("00000", &ts));
-        assert!(not_exists("00001", &ts));
-        assert!(contains("CURRENT", &ts, ONE));
+        assert!(not_exists(".0", &ts));
+        assert!(not_exists(".1", &ts));
+        assert!(contains("", &ts, ONE));
 
         // ensure this produces ONE/-/TWO
         write_loglines(false, naming, &ts, &[TWO]);
-        assert!(contains("00000", &ts, ONE));
-        assert!(not_exists("00001", &ts));
-        assert!(contains("CURRENT", &ts, TWO));
+        assert!(contains(".0", &ts, ONE));
+        assert!(not_exists(".1", &ts));
+        assert!(contains("", &ts, TWO));
 
         // ensure this also produces ONE/-/TWO
-        remove("CURRENT", &ts);
-        assert!(not_exists("CURRENT", &ts));
+        remove("", &ts);
+        assert!(not_exists("", &ts));
         write_loglines(false, naming, &ts, &[TWO]);
-        assert!(contains("00000", &ts, ONE));
-        assert!(not_exists("00001", &ts));
-        assert!(contains("CURRENT", &ts, TWO));
+        assert!(contains(".0", &ts, ONE));
+        assert!(not_exists(".1", &ts));
+        assert!(contains("", &ts, TWO));
 
         // ensure this produces ONE/TWO/THREE
         write_loglines(false, naming, &ts, &[THREE]);
-        assert!(contains("00000", &ts, ONE));
-        assert!(contains("00001", &ts, TWO));
-        assert!(contains("CURRENT", &ts, THREE));
+        assert!(contains(".0", &ts, ONE));
+        assert!(contains(".1", &ts, TWO));
+        assert!(contains("", &ts, THREE));
     }
 
     #[allow(clippy::cognitive_complexity)]
     #[test]
     fn test_rotate_with_append_numbers() {
         // we use timestamp as discriminant to allow repeated runs
-        let ts = Local::now()
-            .format("true-numbers-%Y-%m-%d_%H-%M-%S")
-            .to_string();
+        let ts = format!(
+            "true-numbers-{}",
+            timestamp::format(&timestamp::now(), Layout::FileName)
+        );
         let naming = Naming::Numbers;
 
         // ensure we start with -/-/-
-        assert!(not_exists("00000", &ts));
-        assert!(not_exists("00001", &ts));
-        assert!(not_exists("CURRENT", &ts));
+        assert!(not_exists(".0", &ts));
+        assert!(not_exists(".1", &ts));
+        assert!(not_exists("", &ts));
 
         // ensure this produces 12/-/3
         write_loglines(true, naming, &ts, &[ONE, TWO, THREE]);
-        assert!(contains("00000", &ts, ONE));
-        assert!(contains("00000", &ts, TWO));
-        assert!(not_exists("00001", &ts));
-        assert!(contains("CURRENT", &ts, THREE));
+        assert!(contains(".0", &ts, ONE));
+        assert!(contains(".0", &ts, TWO));
+        assert!(not_exists(".1", &ts));
+        assert!(contains("", &ts, THREE));
 
         // ensure this produces 12/34/56
         write_loglines(true, naming, &ts, &[FOUR, FIVE, SIX]);
-        assert!(contains("00000", &ts, ONE));
-        assert!(contains("00000", &ts, TWO));
-        assert!(contains("00001", &ts, THREE));
-        assert!(contains("00001", &ts, FOUR));
-        assert!(contains("CURRENT", &ts, FIVE));
-        assert!(contains("CURRENT", &ts, SIX));
+        assert!(contains(".0", &ts, ONE));
+        assert!(contains(".0", &ts, TWO));
+        assert!(contains(".1", &ts, THREE));
+        assert!(contains(".1", &ts, FOUR));
+        assert!(contains("", &ts, FIVE));
+        assert!(contains("", &ts, SIX));
 
         // ensure this also produces 12/34/56
-        remove("CURRENT", &ts);
-        remove("00001", &ts);
-        assert!(not_exists("CURRENT", &ts));
+        remove("", &ts);
+        remove(".1", &ts);
+        assert!(not_exists("", &ts));
         write_loglines(true, naming, &ts, &[THREE, FOUR, FIVE, SIX]);
-        assert!(contains("00000", &ts, ONE));
-        assert!(contains("00000", &ts, TWO));
-        assert!(contains("00001", &ts, THREE));
-        assert!(contains("00001", &ts, FOUR));
-        assert!(contains("CURRENT", &ts, FIVE));
-        assert!(contains("CURRENT", &ts, SIX));
+        assert!(contains(".0", &ts, ONE));
+        assert!(contains(".0", &ts, TWO));
+        assert!(contains(".1", &ts, THREE));
+        assert!(contains(".1", &ts, FOUR));
+        assert!(contains("", &ts, FIVE));
+        assert!(contains("", &ts, SIX));
 
         // ensure this produces 12/34/56/78/9
         write_loglines(true, naming, &ts, &[SEVEN, EIGHT, NINE]);
-        assert!(contains("00002", &ts, FIVE));
-        assert!(contains("00002", &ts, SIX));
-        assert!(contains("00003", &ts, SEVEN));
-        assert!(contains("00003", &ts, EIGHT));
-        assert!(contains("CURRENT", &ts, NINE));
+        assert!(contains(".2", &ts, FIVE));
+        assert!(contains(".2", &ts, SIX));
+        assert!(contains(".3", &ts, SEVEN));
+        assert!(contains(".3", &ts, EIGHT));
+        assert!(contains("", &ts, NINE));
     }
 
     #[test]
     fn test_rotate_no_append_timestamps() {
         // we use timestamp as discriminant to allow repeated runs
-        let ts = Local::now()
-            .format("false-timestamps-%Y-%m-%d_%H-%M-%S")
-            .to_string();
+        let ts = format!(
+            "false-timestamps-{}",
+            timestamp::format(&timestamp::now(), Layout::FileName)
+        );
 
         let basename = String::from(DIRECTORY).add("/").add(
             &Path::new(&std::env::args().next().unwrap())
@@ -270,32 +594,33 @@ mod test {
 
         // ensure we start with -/-/-
         assert!(list_rotated_files(&basename, &ts).is_empty());
-        assert!(not_exists("CURRENT", &ts));
+        assert!(not_exists("", &ts));
 
         // ensure this produces -/-/ONE
         write_loglines(false, naming, &ts, &[ONE]);
         assert!(list_rotated_files(&basename, &ts).is_empty());
-        assert!(contains("CURRENT", &ts, ONE));
+        assert!(contains("", &ts, ONE));
 
         std::thread::sleep(std::time::Duration::from_secs(2));
         // ensure this produces ONE/-/TWO
         write_loglines(false, naming, &ts, &[TWO]);
         assert_eq!(list_rotated_files(&basename, &ts).len(), 1);
-        assert!(contains("CURRENT", &ts, TWO));
+        assert!(contains("", &ts, TWO));
 
         std::thread::sleep(std::time::Duration::from_secs(2));
         // ensure this produces ONE/TWO/THREE
         write_loglines(false, naming, &ts, &[THREE]);
         assert_eq!(list_rotated_files(&basename, &ts).len(), 2);
-        assert!(contains("CURRENT", &ts, THREE));
+        assert!(contains("", &ts, THREE));
     }
 
     #[test]
     fn test_rotate_with_append_timestamps() {
         // we use timestamp as discriminant to allow repeated runs
-        let ts = Local::now()
-            .format("true-timestamps-%Y-%m-%d_%H-%M-%S")
-            .to_string();
+        let ts = format!(
+            "true-timestamps-{}",
+            timestamp::format(&timestamp::now(), Layout::FileName)
+        );
 
         let basename = String::from(DIRECTORY).add("/").add(
             &Path::new(&std::env::args().next().unwrap())
@@ -306,23 +631,23 @@ mod test {
 
         // ensure we start with -/-/-
         assert!(list_rotated_files(&basename, &ts).is_empty());
-        assert!(not_exists("CURRENT", &ts));
+        assert!(not_exists("", &ts));
 
         // ensure this produces 12/-/3
         write_loglines(true, naming, &ts, &[ONE, TWO, THREE]);
         assert_eq!(list_rotated_files(&basename, &ts).len(), 1);
-        assert!(contains("CURRENT", &ts, THREE));
+        assert!(contains("", &ts, THREE));
 
         // // ensure this produces 12/34/56
         write_loglines(true, naming, &ts, &[FOUR, FIVE, SIX]);
-        assert!(contains("CURRENT", &ts, FIVE));
-        assert!(contains("CURRENT", &ts, SIX));
+        assert!(contains("", &ts, FIVE));
+        assert!(contains("", &ts, SIX));
         assert_eq!(list_rotated_files(&basename, &ts).len(), 2);
 
         // // ensure this produces 12/34/56/78/9
         // write_loglines(true, naming, &ts, &[SEVEN, EIGHT, NINE]);
         // assert_eq!(list_rotated_files(&basename, &ts).len(), 4);
-        // assert!(contains("CURRENT", &ts, NINE));
+        // assert!(contains("", &ts, NINE));
     }
 
     #[test]
@@ -411,7 +736,6 @@ mod test {
             .to_string();
         s_filename += "_";
         s_filename += discr;
-        s_filename += "_r";
         s_filename += infix;
         s_filename += ".log";
         let mut path_buf = PathBuf::from(DIRECTORY);
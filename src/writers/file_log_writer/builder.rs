@@ -1,24 +1,40 @@
 use crate::flexi_error::FlexiLoggerError;
 use crate::formats::default_format;
-use crate::FormatFunction;
-use crate::{Cleanup, Criterion, Naming};
-use chrono::Local;
+use crate::timestamp::{self, Layout};
+use crate::{Cleanup, Criterion, DiskFull, Naming, SyncPolicy};
+use crate::{EffectiveFileConfig, FormatFunction};
 use std::env;
+use std::ffi::OsString;
 use std::path::{Path, PathBuf};
-use std::sync::Mutex;
+use std::sync::{Arc, Mutex};
 
-use super::{Config, FileLogWriter, RotationConfig, State};
+use super::state::{cleanup_at_startup, current_infix, get_filepath};
+use super::{
+    Config, FileFooter, FileHeader, FileLogWriter, FilenameConfig, RotationConfig, RotationEvent,
+    Shards, State, States,
+};
 
 /// Builder for `FileLogWriter`.
-#[allow(clippy::module_name_repetitions)]
+#[allow(clippy::module_name_repetitions, clippy::struct_excessive_bools)]
 pub struct FileLogWriterBuilder {
     basename: Option<String>,
     discriminant: Option<String>,
+    o_file_path: Option<PathBuf>,
     config: Config,
     format: FormatFunction,
+    o_structured_format: Option<FormatFunction>,
     o_rotation_config: Option<RotationConfig>,
     max_log_level: log::LevelFilter,
     cleanup_in_background_thread: bool,
+    cleanup_other_discriminants: bool,
+    create_directory: bool,
+    #[cfg(unix)]
+    o_directory_mode: Option<u32>,
+    per_thread: bool,
+    o_no_of_shards: Option<usize>,
+    audit: bool,
+    #[cfg(feature = "archive-s3")]
+    o_s3_archive: Option<super::S3Archive>,
 }
 
 /// Simple methods for influencing the behavior of the `FileLogWriter`.
@@ -27,11 +43,22 @@ impl FileLogWriterBuilder {
         FileLogWriterBuilder {
             basename: None,
             discriminant: None,
+            o_file_path: None,
             o_rotation_config: None,
             config: Config::default(),
             format: default_format,
+            o_structured_format: None,
             max_log_level: log::LevelFilter::Trace,
             cleanup_in_background_thread: true,
+            cleanup_other_discriminants: false,
+            create_directory: true,
+            #[cfg(unix)]
+            o_directory_mode: None,
+            per_thread: false,
+            o_no_of_shards: None,
+            audit: false,
+            #[cfg(feature = "archive-s3")]
+            o_s3_archive: None,
         }
     }
 
@@ -50,15 +77,123 @@ impl FileLogWriterBuilder {
         self
     }
 
+    /// Makes the `FileLogWriter` write each record additionally, with the given format,
+    /// to a structured file, which has the same name as the log file, but the suffix `.jsonl`.
+    ///
+    /// So operators can read and grep the human-readable log file, while pipelines ingest
+    /// the records from the structured file, e.g. in the format
+    /// [`formats::json_format`](crate::json_format).
+    ///
+    /// The structured file is rotated together with its log file, and it is compressed,
+    /// encrypted, moved, and removed by the cleanup together with its log file.
+    /// Rotation by size only considers the size of the log file.
+    /// Header and footer are only written to the log file.
+    ///
+    /// [`FileLogWriterBuilder::sharded`] cannot be combined with a structured file,
+    /// since both files need to see the same records, and the suffix of the log files
+    /// must not be `jsonl`; `try_build()` rejects these settings with
+    /// `FlexiLoggerError::FileConfigConflict`.
+    #[must_use]
+    pub fn structured_file(mut self, format: FormatFunction) -> Self {
+        self.o_structured_format = Some(format);
+        self
+    }
+
     /// Specifies a folder for the log files.
     ///
-    /// If the specified folder does not exist, the initialization will fail.
+    /// If the specified folder does not exist, it is created, including all missing parent
+    /// folders, unless this is switched off with
+    /// [`create_directory`](crate::writers::FileLogWriterBuilder::create_directory).
     /// By default, the log files are created in the folder where the program was started.
     pub fn directory<P: Into<PathBuf>>(mut self, directory: P) -> Self {
         self.config.filename_config.directory = directory.into();
         self
     }
 
+    /// Uses the platform's usual per-user folder for log files, with a subfolder
+    /// for the given application:
+    ///
+    /// * on Windows `%LOCALAPPDATA%\<app_name>\logs`
+    /// * on macOS `~/Library/Logs/<app_name>`
+    /// * on other platforms `$XDG_STATE_HOME/<app_name>`,
+    ///   or `~/.local/state/<app_name>` if `XDG_STATE_HOME` is not set.
+    ///
+    /// If the folder cannot be determined, because the respective environment variable
+    /// is not set, the folder for the log files remains unchanged.
+    pub fn use_platform_log_dir<S: AsRef<str>>(mut self, app_name: S) -> Self {
+        if let Some(directory) = platform_log_dir(app_name.as_ref()) {
+            self.config.filename_config.directory = directory;
+        }
+        self
+    }
+
+    /// With true (the default), a missing folder for the log files is created,
+    /// including all missing parent folders.
+    ///
+    /// With false, the initialization fails with `FlexiLoggerError::OutputMissingDirectory`
+    /// if the folder does not exist.
+    #[must_use]
+    pub fn create_directory(mut self, create_directory: bool) -> Self {
+        self.create_directory = create_directory;
+        self
+    }
+
+    /// Specifies the permissions (e.g. `0o750`) of the folders that are created for the
+    /// log files.
+    ///
+    /// By default, the permissions are derived from the process's umask.
+    ///
+    /// Only available on unix.
+    #[cfg(unix)]
+    #[must_use]
+    pub fn directory_mode(mut self, mode: u32) -> Self {
+        self.o_directory_mode = Some(mode);
+        self
+    }
+
+    /// Specifies the permissions (e.g. `0o600`) of the log files that are created.
+    ///
+    /// By default, the permissions are derived from the process's umask.
+    /// The permissions of existing files are not changed.
+    ///
+    /// Only available on unix.
+    #[cfg(unix)]
+    #[must_use]
+    pub fn file_mode(mut self, mode: u32) -> Self {
+        self.config.o_file_mode = Some(mode);
+        self
+    }
+
+    /// Configures the `FileLogWriter` for an audit log, whose records must not get lost,
+    /// e.g. by a restart that truncates the file:
+    ///
+    /// * the file name has no timestamp, so that the same file is used across restarts
+    /// * the file is appended to, and never truncated
+    /// * every record is synced to the storage device
+    ///   (see [`SyncPolicy::EveryRecord`](crate::SyncPolicy::EveryRecord))
+    /// * on unix, the file is created with the permissions `0o600`
+    ///
+    /// With optional crate feature `integrity`, consider adding a hash chain,
+    /// see [`use_hash_chain`](crate::writers::FileLogWriterBuilder::use_hash_chain).
+    ///
+    /// Rotation is not activated, but can be added, as long as no cleanup is used,
+    /// so that no log file is ever removed.
+    /// Settings that would truncate or remove log files are rejected by `try_build()` with
+    /// `FlexiLoggerError::FileConfigConflict` or `FlexiLoggerError::RotationConfigConflict`.
+    /// The other settings can still be changed after calling this method.
+    #[must_use]
+    pub fn audit(mut self) -> Self {
+        self.audit = true;
+        self.config.filename_config.use_timestamp = false;
+        self.config.append = true;
+        self.config.sync_policy = SyncPolicy::EveryRecord;
+        #[cfg(unix)]
+        {
+            self.config.o_file_mode = Some(0o600);
+        }
+        self
+    }
+
     /// Specifies a suffix for the log files. The default is "log".
     pub fn suffix<S: Into<String>>(mut self, suffix: S) -> Self {
         self.config.filename_config.suffix = suffix.into();
@@ -90,6 +225,106 @@ impl FileLogWriterBuilder {
         self
     }
 
+    /// With true, the cleanup also considers the rotated files that were written
+    /// with another discriminant, or without discriminant, e.g. by earlier program runs
+    /// that used the process id as discriminant.
+    ///
+    /// The [`Cleanup`](crate::Cleanup) limits then apply to all these files together,
+    /// which are ordered by their modification time.
+    /// A discriminant is recognized by the underscore that separates it from the basename,
+    /// so the files of other programs are only considered if their basename consists of
+    /// this basename, an underscore, and more.
+    #[must_use]
+    pub fn cleanup_other_discriminants(mut self, cleanup_other_discriminants: bool) -> Self {
+        self.cleanup_other_discriminants = cleanup_other_discriminants;
+        self
+    }
+
+    /// With true, the cleanup only considers files whose names match exactly
+    /// the names that the configured [`Naming`](crate::Naming) generates,
+    /// including the number or timestamp.
+    ///
+    /// The cleanup only ever considers files whose names consist of the configured basename,
+    /// discriminant, and suffix, a rotation infix, and the extensions of compressed and encrypted
+    /// files; with the strict mode, a misconfigured directory can also not lead to the removal
+    /// of files of other applications that happen to follow a similar naming scheme.
+    ///
+    /// Cannot be combined with
+    /// [`cleanup_other_discriminants`](crate::writers::FileLogWriterBuilder::cleanup_other_discriminants).
+    #[must_use]
+    pub fn strict_cleanup(mut self, strict_cleanup: bool) -> Self {
+        self.config.filename_config.strict_cleanup = strict_cleanup;
+        self
+    }
+
+    /// Defines how long [`LogWriter::shutdown`](crate::writers::LogWriter::shutdown)
+    /// waits for the cleanup thread to finish its pending work, e.g. the compression
+    /// of a rotated file. Defaults to 30 seconds.
+    ///
+    /// If the cleanup thread does not finish in time, this is reported to the error channel,
+    /// and the thread is left running, so that it ends with the process.
+    #[must_use]
+    pub fn cleanup_timeout(mut self, timeout: std::time::Duration) -> Self {
+        self.config.cleanup_timeout = timeout;
+        self
+    }
+
+    /// With true, rotation and cleanup take an advisory lock (an exclusive lock
+    /// of the operating system on the file `.<basename>.lock` in the log directory),
+    /// so that several processes that write log files with the same
+    /// name into the same directory don't rename or delete each other's files concurrently.
+    ///
+    /// A process that finds that its file was already rotated by another process
+    /// just continues with the new file.
+    /// The lock is released when the rotation or cleanup is done, or when the process ends.
+    /// The lock file is left in place.
+    ///
+    /// The lock is only supported on unix and windows.
+    ///
+    /// This is usually combined with `append()` and `suppress_timestamp()`.
+    /// By default, no lock is used.
+    #[must_use]
+    pub fn use_rotation_lock(mut self, use_rotation_lock: bool) -> Self {
+        self.config.use_rotation_lock = use_rotation_lock;
+        self
+    }
+
+    /// Defines what happens with the log records while the disk is full.
+    ///
+    /// By default, they are dropped, see [`DiskFull`](crate::DiskFull).
+    #[must_use]
+    pub fn on_disk_full(mut self, disk_full: DiskFull) -> Self {
+        self.config.disk_full = disk_full;
+        self
+    }
+
+    /// Defines when the written data are forced to the storage device.
+    ///
+    /// By default, this is left to the operating system, see [`SyncPolicy`](crate::SyncPolicy).
+    #[must_use]
+    pub fn sync_policy(mut self, sync_policy: SyncPolicy) -> Self {
+        self.config.sync_policy = sync_policy;
+        self
+    }
+
+    /// Removes the oldest rotated log files, regardless of the cleanup strategy,
+    /// when the free space in the log directory drops below the given number of bytes,
+    /// so that logging does not fill up the disk.
+    ///
+    /// The free space is checked at most once per second, when a record is written.
+    /// The current log file is never removed.
+    /// Each such emergency cleanup is reported to the [`ErrorChannel`](crate::ErrorChannel).
+    ///
+    /// This option only has an effect if rotation is used, too.
+    ///
+    /// Only available with optional crate feature `free_space`.
+    #[cfg(feature = "free_space")]
+    #[must_use]
+    pub fn min_free_space(mut self, min_free_space: u64) -> Self {
+        self.config.o_min_free_space = Some(min_free_space);
+        self
+    }
+
     /// Use rotation to prevent indefinite growth of log files.
     ///
     /// By default, the log file is fixed while your program is running and will grow indefinitely.
@@ -99,10 +334,10 @@ impl FileLogWriterBuilder {
     /// Note that also the filename pattern changes:
     ///
     /// - by default, no timestamp is added to the filename
-    /// - the logs are always written to a file with infix `_rCURRENT`
+    /// - the logs are always written to the file without infix, like `my_prog.log`
     /// - when the rotation criterion is fulfilled, it is closed and renamed to a file
-    ///   with another infix (see `Naming`),
-    ///   and then the logging continues again to the (fresh) file with infix `_rCURRENT`.
+    ///   with a number or a timestamp (see `Naming`),
+    ///   and then the logging continues again to the (fresh) file without infix.
     ///
     /// Example:
     ///
@@ -110,10 +345,10 @@ impl FileLogWriterBuilder {
     /// you will find files like
     ///
     /// ```text
-    /// my_prog_r00000.log
-    /// my_prog_r00001.log
-    /// my_prog_r00002.log
-    /// my_prog_rCURRENT.log
+    /// my_prog.0.log
+    /// my_prog.1.log
+    /// my_prog.2.log
+    /// my_prog.log
     /// ```
     ///
     /// The cleanup parameter allows defining the strategy for dealing with older files.
@@ -129,8 +364,44 @@ impl FileLogWriterBuilder {
         self
     }
 
+    // Rotates over the given size, keeping naming, cleanup, and a configured age;
+    // activates rotation with Naming::Numbers and Cleanup::Never if it was not configured.
+    pub(crate) fn rotate_over_size(self, size: u64) -> Self {
+        let (criterion, naming, cleanup) = match self.o_rotation_config {
+            Some(ref rotation_config) => (
+                match rotation_config.criterion {
+                    Criterion::Age(age) | Criterion::AgeOrSize(age, _) => {
+                        Criterion::AgeOrSize(age, size)
+                    }
+                    Criterion::Size(_) | Criterion::Lines(_) => Criterion::Size(size),
+                    // is called once, when the logger is configured
+                    Criterion::Any(_) | Criterion::All(_) | Criterion::MinInterval(_, _) => {
+                        Criterion::Any(Box::leak(Box::new([
+                            rotation_config.criterion,
+                            Criterion::Size(size),
+                        ])))
+                    }
+                },
+                rotation_config.naming,
+                rotation_config.cleanup,
+            ),
+            None => (Criterion::Size(size), Naming::Numbers, Cleanup::Never),
+        };
+        self.rotate(criterion, naming, cleanup)
+    }
+
+    // Replaces the cleanup strategy; returns None if no rotation is configured.
+    pub(crate) fn rotation_cleanup(mut self, cleanup: Cleanup) -> Option<Self> {
+        self.o_rotation_config.as_mut()?.cleanup = cleanup;
+        Some(self)
+    }
+
     /// Makes the logger append to the given file, if it exists; by default, the file would be
     /// truncated.
+    ///
+    /// The file is then opened in append mode (`O_APPEND`), and every record is written
+    /// with a single write call, so that several processes (or a forked child) can
+    /// append to the same file without interleaving partial lines.
     #[must_use]
     pub fn append(mut self) -> Self {
         self.config.append = true;
@@ -138,25 +409,194 @@ impl FileLogWriterBuilder {
     }
 
     /// The specified String is added to the log file name.
+    ///
+    /// The placeholders `{pid}`, `{hostname}`, and `{exe}` are replaced with the process id,
+    /// the host name, and the program name.
     pub fn discriminant<S: Into<String>>(mut self, discriminant: S) -> Self {
         self.discriminant = Some(discriminant.into());
         self
     }
 
+    /// With true, each thread writes its records into its own file, which has the name of the
+    /// thread (or, for unnamed threads, `thread-<id>`) as an additional infix,
+    /// like `myprog_worker-1.log`;
+    /// if several threads have the same name, the thread id is added.
+    ///
+    /// All files share the other settings, including rotation and cleanup.
+    /// The file of a thread is closed when the `FileLogWriter` is flushed after the thread
+    /// has ended, or when the `FileLogWriter` is shut down.
+    #[must_use]
+    pub fn per_thread(mut self, per_thread: bool) -> Self {
+        self.per_thread = per_thread;
+        self
+    }
+
+    /// Reduces the contention between many threads that log concurrently,
+    /// by collecting the formatted records in the given number of staging buffers,
+    /// which are written to the file when they are full, and when the `FileLogWriter` is flushed.
+    ///
+    /// Each thread uses always the same staging buffer, so the records of a thread keep
+    /// their order, but records of different threads can appear reordered in the file.
+    /// The size of the staging buffers is the buffer capacity
+    /// (see [`FileLogWriterBuilder::buffer_with_capacity`]),
+    /// or 8 kB if buffering is not active.
+    ///
+    /// Like with buffering, log lines become visible in the file with a delay, so you should
+    /// combine this option with regular flushing
+    /// (see [`Logger::buffer_and_flush`](crate::Logger::buffer_and_flush)),
+    /// and call [`LogWriter::shutdown`](super::LogWriter::shutdown) at the end of your program.
+    ///
+    /// Cannot be combined with [`FileLogWriterBuilder::per_thread`]
+    /// or [`FileLogWriterBuilder::structured_file`].
+    #[must_use]
+    pub fn sharded(mut self, no_of_shards: usize) -> Self {
+        self.o_no_of_shards = Some(no_of_shards);
+        self
+    }
+
     /// The specified String is used as the basename of the log file name,
-    /// instead of the program name.
+    /// instead of the program name, which is taken from the first command line argument.
+    ///
+    /// The placeholders `{pid}`, `{hostname}`, and `{exe}` are replaced with the process id,
+    /// the host name, and the program name.
     pub fn basename<S: Into<String>>(mut self, basename: S) -> Self {
         self.basename = Some(basename.into());
         self
     }
 
-    /// The specified String will be used on linux systems to create in the current folder
-    /// a symbolic link to the current log file.
+    /// Writes the log to exactly the specified file, e.g. `/var/log/myapp/app.log`.
+    ///
+    /// The file name is then not assembled from directory, basename, discriminant, timestamp,
+    /// and suffix; these settings are ignored.
+    /// The file name must have an extension.
+    ///
+    /// With rotation, the current file keeps its name, and the rotated files get names
+    /// like `app.1.log`, or `app.log.1` with [`Naming::Logrotate`](crate::Naming::Logrotate).
+    pub fn log_file_path<P: Into<PathBuf>>(mut self, file_path: P) -> Self {
+        self.o_file_path = Some(file_path.into());
+        self.config.filename_config.use_timestamp = false;
+        self
+    }
+
+    /// The specified path will be used to create a symbolic link to the current log file.
+    ///
+    /// Where no symbolic link can be created (e.g. on windows without the respective privilege),
+    /// a hard link is used instead, and if that fails, too, a pointer file with the suffix `.txt`
+    /// (e.g. `current_run.txt` for `current_run`), which contains the path of the current
+    /// log file.
     pub fn create_symlink<P: Into<PathBuf>>(mut self, symlink: P) -> Self {
         self.config.o_create_symlink = Some(symlink.into());
         self
     }
 
+    /// Maintains two additional links in the log directory:
+    /// `latest.<suffix>` always points to the current log file, and, with rotation,
+    /// `latest_rotated.<suffix>` points to the most recently rotated file.
+    ///
+    /// The links are created like the one of
+    /// [`create_symlink`](crate::writers::FileLogWriterBuilder::create_symlink).
+    #[must_use]
+    pub fn create_latest_links(mut self) -> Self {
+        self.config.latest_links = true;
+        self
+    }
+
+    /// Writes the given header at the top of every new log file,
+    /// i.e., of the initial file and of every file that is started due to rotation.
+    ///
+    /// When appending to an existing non-empty file, no header is written.
+    ///
+    /// See [`FileHeader`](crate::writers::FileHeader) for the ways to define the header.
+    #[must_use]
+    pub fn file_header<H: Into<FileHeader>>(mut self, header: H) -> Self {
+        self.config.o_header = Some(header.into());
+        self
+    }
+
+    /// Writes the given footer at the end of a log file when the file is closed
+    /// due to rotation or due to shutdown, so that a file that was closed cleanly
+    /// can be distinguished from a file that ends due to a crash.
+    ///
+    /// See [`FileFooter`](crate::writers::FileFooter) for the ways to define the footer.
+    #[must_use]
+    pub fn file_footer<F: Into<FileFooter>>(mut self, footer: F) -> Self {
+        self.config.o_footer = Some(footer.into());
+        self
+    }
+
+    /// Registers a callback that is invoked after each rotation with the paths of the
+    /// rotated file and of the new file, and after the cleanup has compressed or removed a file.
+    ///
+    /// This allows e.g. uploading rotated files or updating monitoring
+    /// without polling the log directory.
+    ///
+    /// The callback is invoked from the thread that causes the rotation or,
+    /// for events of the cleanup, possibly from the cleanup thread.
+    /// It must not log to the `FileLogWriter` that invokes it.
+    ///
+    /// See [`RotationEvent`](crate::writers::RotationEvent).
+    #[must_use]
+    pub fn on_rotation<F>(mut self, callback: F) -> Self
+    where
+        F: Fn(&RotationEvent) + Send + Sync + 'static,
+    {
+        self.config.o_rotation_callback = Some(Arc::new(callback));
+        self
+    }
+
+    /// Encrypts the rotated files, so that they don't expose their content
+    /// to others who have access to the log directory.
+    ///
+    /// Only available with optional crate feature `encrypt`.
+    ///
+    /// See [`Encryption`](crate::writers::Encryption) for details.
+    #[cfg(feature = "encrypt")]
+    #[must_use]
+    pub fn encrypt_rotated_files(mut self, encryption: super::Encryption) -> Self {
+        self.config.o_encryption = Some(encryption);
+        self
+    }
+
+    /// Lets every line that is written to the log files extend a hash chain,
+    /// so that later modifications or truncations of the files can be detected.
+    ///
+    /// Only available with optional crate feature `integrity`.
+    ///
+    /// The running hash, the HMAC-SHA256 with the given secret key of the previous hash
+    /// and the line, is written for each line to a side-car file with the additional
+    /// suffix `.sha256`, which is rotated and removed along with its log file.
+    /// Without the key, the chain cannot be recomputed after a modification of the files.
+    /// The hashes are written to the side-car file only after the lines are flushed
+    /// to the log file.
+    /// The first line of a side-car file contains the last hash of the previous file,
+    /// so that also missing files can be detected.
+    /// A new chain is started whenever the program starts (unless it appends to an existing
+    /// file), or if the file is written by several processes.
+    ///
+    /// Use [`verify_hash_chain`](crate::writers::verify_hash_chain), with the same key,
+    /// to verify the files.
+    #[cfg(feature = "integrity")]
+    #[must_use]
+    pub fn use_hash_chain<K: Into<Vec<u8>>>(mut self, key: K) -> Self {
+        self.config.o_hash_chain = Some(super::hash_chain::HashChain::new(key.into()));
+        self
+    }
+
+    /// Uploads the rotated files to an S3-compatible bucket and deletes them locally afterwards.
+    ///
+    /// Only available with optional crate feature `archive-s3`.
+    ///
+    /// See [`S3Archive`](crate::writers::S3Archive) for details.
+    /// A callback registered with
+    /// [`on_rotation`](crate::writers::FileLogWriterBuilder::on_rotation)
+    /// is still invoked, before the file is moved to the pending uploads.
+    #[cfg(feature = "archive-s3")]
+    #[must_use]
+    pub fn archive_to_s3(mut self, archive: super::S3Archive) -> Self {
+        self.o_s3_archive = Some(archive);
+        self
+    }
+
     /// Use Windows line endings, rather than just `\n`.
     #[must_use]
     pub fn use_windows_line_ending(mut self) -> Self {
@@ -191,6 +631,9 @@ impl FileLogWriterBuilder {
 
     /// Activates buffering, and uses a buffer with the specified capacity.
     ///
+    /// Log lines are then written in batches of up to `capacity` bytes, with a single write call
+    /// per batch; see [`Logger::buffer_and_flush_with`](crate::Logger::buffer_and_flush_with).
+    ///
     /// See [`Logger::use_buffering`](crate::Logger::use_buffering)
     #[must_use]
     pub fn buffer_with_capacity(mut self, capacity: usize) -> Self {
@@ -198,48 +641,297 @@ impl FileLogWriterBuilder {
         self
     }
 
+    pub(crate) fn appends(&self) -> bool {
+        self.config.append
+    }
+
     #[must_use]
     pub(crate) fn buffersize(&self) -> &Option<usize> {
         &self.config.o_buffersize
     }
 
-    /// Produces the `FileLogWriter`.
-    ///
-    /// # Errors
-    ///
-    /// `FlexiLoggerError::Io`.
-    pub fn try_build(mut self) -> Result<FileLogWriter, FlexiLoggerError> {
+    // The interval of SyncPolicy::Interval, which the flusher thread has to observe.
+    #[must_use]
+    pub(crate) fn sync_interval(&self) -> Option<std::time::Duration> {
+        match self.config.sync_policy {
+            SyncPolicy::Interval(interval) => Some(interval),
+            _ => None,
+        }
+    }
+
+    // Detects settings that contradict each other.
+    pub(crate) fn validate(&self) -> Result<(), FlexiLoggerError> {
+        if let Some(ref file_path) = self.o_file_path {
+            if file_path.file_stem().is_none() || file_path.extension().is_none() {
+                return Err(FlexiLoggerError::FileConfigConflict(format!(
+                    "the log file path {} needs a file name with an extension",
+                    file_path.display()
+                )));
+            }
+            if self.per_thread {
+                return Err(FlexiLoggerError::FileConfigConflict(
+                    "a log file path cannot be combined with per_thread".to_string(),
+                ));
+            }
+        }
+        if self.o_no_of_shards.is_some() && (self.per_thread || self.o_structured_format.is_some())
+        {
+            return Err(FlexiLoggerError::FileConfigConflict(
+                "sharded cannot be combined with per_thread or a structured file".to_string(),
+            ));
+        }
+        if self.o_structured_format.is_some()
+            && self.config.filename_config.suffix == super::structured_file::SUFFIX
+        {
+            return Err(FlexiLoggerError::FileConfigConflict(format!(
+                "the suffix of the log files cannot be {} if a structured file is used",
+                super::structured_file::SUFFIX
+            )));
+        }
+        if self.audit {
+            if !self.config.append || self.config.filename_config.use_timestamp {
+                return Err(FlexiLoggerError::FileConfigConflict(
+                    "an audit log must be appended to a file with a fixed name; \
+                     remove the calls of o_append(false) and o_timestamp(true)"
+                        .to_string(),
+                ));
+            }
+            if let Some(ref rotation_config) = self.o_rotation_config {
+                if rotation_config.cleanup.do_cleanup() {
+                    return Err(FlexiLoggerError::RotationConfigConflict(
+                        "the files of an audit log must not be removed; use Cleanup::Never"
+                            .to_string(),
+                    ));
+                }
+            }
+        }
+        if let Some(ref rotation_config) = self.o_rotation_config {
+            if rotation_config.criterion.has_empty_composition() {
+                return Err(FlexiLoggerError::RotationConfigConflict(
+                    "Criterion::Any and Criterion::All need at least one criterion".to_string(),
+                ));
+            }
+            if self.cleanup_other_discriminants && self.config.filename_config.strict_cleanup {
+                return Err(FlexiLoggerError::RotationConfigConflict(
+                    "strict_cleanup cannot be combined with cleanup_other_discriminants"
+                        .to_string(),
+                ));
+            }
+            if let Cleanup::MoveToDirectory(directory, _) = rotation_config.cleanup {
+                if resolved(directory) == resolved(&self.config.filename_config.directory) {
+                    return Err(FlexiLoggerError::RotationConfigConflict(
+                        "Cleanup::MoveToDirectory needs another directory than the log directory"
+                            .to_string(),
+                    ));
+                }
+            }
+            if rotation_config.cleanup.do_cleanup() && self.config.filename_config.use_timestamp {
+                return Err(FlexiLoggerError::RotationConfigConflict(
+                    "the cleanup would not find the files of earlier program runs, \
+                     because the log file name contains a timestamp; \
+                     remove the call of o_timestamp(true), or use Cleanup::Never"
+                        .to_string(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // The basename of the log files, with discriminant, but without timestamp.
+    fn resolve_basename(&self) -> String {
+        if self.o_file_path.is_some() {
+            return self.resolve_shared_basename();
+        }
+        let mut basename = self.resolve_shared_basename();
+        if let Some(ref discriminant) = self.discriminant {
+            basename += &format!(
+                "_{}",
+                replace_placeholders(discriminant, &executable_name())
+            );
+        }
+        basename
+    }
+
+    // The basename of the log files, without discriminant and timestamp.
+    fn resolve_shared_basename(&self) -> String {
+        if let Some(ref file_path) = self.o_file_path {
+            return file_path
+                .file_stem()
+                .map_or_else(String::new, |stem| stem.to_string_lossy().to_string());
+        }
+        let exe = executable_name();
+        match self.basename {
+            Some(ref basename) => replace_placeholders(basename, &exe),
+            None => exe,
+        }
+    }
+
+    // Describes the log files, without touching the filesystem.
+    pub(crate) fn effective_config(&self) -> EffectiveFileConfig {
+        let mut filename_config = self.config.filename_config.clone();
+        self.apply_file_path(&mut filename_config);
+        filename_config.file_basename = self.resolve_basename();
+        if filename_config.use_timestamp {
+            filename_config.file_basename += "_<timestamp>";
+        }
+        let path = |o_infix| {
+            get_filepath(o_infix, &filename_config)
+                .display()
+                .to_string()
+        };
+        EffectiveFileConfig {
+            path: path(
+                self.o_rotation_config
+                    .as_ref()
+                    .and_then(|rotation_config| current_infix(rotation_config.naming)),
+            ),
+            rotation: self.o_rotation_config.as_ref().map(|rotation_config| {
+                format!(
+                    "{:?}, {:?}, {:?}",
+                    rotation_config.criterion, rotation_config.naming, rotation_config.cleanup
+                )
+            }),
+            rotated_path: self.o_rotation_config.as_ref().map(|rotation_config| {
+                match rotation_config.naming {
+                    Naming::Numbers | Naming::NumbersWith { .. } => path(Some(".<number>")),
+                    Naming::Timestamps => path(Some("_r<timestamp>")),
+                    Naming::Logrotate => path(None) + ".<number>",
+                }
+            }),
+            append: self.config.append,
+            symlink: self
+                .config
+                .o_create_symlink
+                .as_ref()
+                .map(|symlink| symlink.display().to_string()),
+        }
+    }
+
+    // Takes directory and suffix from the log file path, if one is given.
+    fn apply_file_path(&self, filename_config: &mut FilenameConfig) {
+        if let Some(ref file_path) = self.o_file_path {
+            filename_config.directory = file_path
+                .parent()
+                .filter(|parent| !parent.as_os_str().is_empty())
+                .map_or_else(|| PathBuf::from("."), Path::to_path_buf);
+            filename_config.suffix = file_path.extension().map_or_else(String::new, |extension| {
+                extension.to_string_lossy().to_string()
+            });
+            filename_config.use_timestamp = false;
+        }
+    }
+
+    // Makes sure the folder for the log files exists.
+    fn prepare_directory(&self) -> Result<(), FlexiLoggerError> {
         // make sure the folder exists or create it
         let p_directory = Path::new(&self.config.filename_config.directory);
-        std::fs::create_dir_all(&p_directory)?;
-        if !std::fs::metadata(&p_directory)?.is_dir() {
+        if !p_directory.exists() {
+            if !self.create_directory {
+                return Err(FlexiLoggerError::OutputMissingDirectory(
+                    p_directory.to_path_buf(),
+                ));
+            }
+            let mut dir_builder = std::fs::DirBuilder::new();
+            dir_builder.recursive(true);
+            #[cfg(unix)]
+            {
+                if let Some(mode) = self.o_directory_mode {
+                    use std::os::unix::fs::DirBuilderExt;
+                    dir_builder.mode(mode);
+                }
+            }
+            dir_builder.create(&p_directory).map_err(|e| {
+                FlexiLoggerError::OutputCreateDirectory(p_directory.to_path_buf(), e)
+            })?;
+        }
+        let metadata = std::fs::metadata(&p_directory).map_err(|source| FlexiLoggerError::Io {
+            path: p_directory.to_path_buf(),
+            source,
+        })?;
+        if !metadata.is_dir() {
             return Err(FlexiLoggerError::OutputBadDirectory);
         };
+        Ok(())
+    }
 
-        if let Some(basename) = self.basename {
-            self.config.filename_config.file_basename = basename;
-        } else {
-            let arg0 = env::args().next().unwrap_or_else(|| "rs".to_owned());
-            self.config.filename_config.file_basename =
-                Path::new(&arg0).file_stem().unwrap(/*cannot fail*/).to_string_lossy().to_string();
+    /// Produces the `FileLogWriter`.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::OutputMissingDirectory` or `FlexiLoggerError::OutputCreateDirectory`
+    /// if the folder for the log files does not exist or cannot be created,
+    /// `FlexiLoggerError::Io` if it cannot be accessed,
+    /// `FlexiLoggerError::OutputBadDirectory` if it is no directory,
+    /// `FlexiLoggerError::RotationConfigConflict` if a cleanup is combined with a timestamp
+    /// in the file name,
+    /// `FlexiLoggerError::Unsupported` in a web build (`wasm32-unknown-unknown`),
+    /// `FlexiLoggerError::OutputIo`.
+    pub fn try_build(mut self) -> Result<FileLogWriter, FlexiLoggerError> {
+        if cfg!(all(target_arch = "wasm32", target_os = "unknown")) {
+            return Err(FlexiLoggerError::Unsupported(
+                "writing log files in a web build",
+            ));
         }
+        let mut filename_config = self.config.filename_config.clone();
+        self.apply_file_path(&mut filename_config);
+        self.config.filename_config = filename_config;
+        self.validate()?;
+        self.prepare_directory()?;
 
-        if let Some(discriminant) = self.discriminant {
-            self.config.filename_config.file_basename += &format!("_{}", discriminant);
-        }
+        let mut basename = self.resolve_basename();
         if self.config.filename_config.use_timestamp {
-            self.config.filename_config.file_basename +=
-                &Local::now().format("_%Y-%m-%d_%H-%M-%S").to_string();
+            basename.push('_');
+            basename.push_str(&timestamp::format(&timestamp::now(), Layout::FileName).to_string());
         };
+        self.config.filename_config.file_basename = basename;
+        if self.cleanup_other_discriminants {
+            self.config.filename_config.o_shared_basename = Some(self.resolve_shared_basename());
+        }
 
+        #[cfg(feature = "archive-s3")]
+        {
+            if let Some(archive) = self.o_s3_archive.take() {
+                let archive_callback = archive.start(&self.config.filename_config.directory)?;
+                self.config.o_rotation_callback = Some(match self.config.o_rotation_callback {
+                    None => archive_callback,
+                    Some(callback) => Arc::new(move |event| {
+                        callback(event);
+                        archive_callback(event);
+                    }),
+                });
+            }
+        }
+
+        let shard_capacity = self
+            .config
+            .o_buffersize
+            .unwrap_or(crate::DEFAULT_BUFFER_CAPACITY);
+        self.config.structured_file = self.o_structured_format.is_some();
+        let o_shards = self
+            .o_no_of_shards
+            .map(|no_of_shards| Shards::new(no_of_shards, shard_capacity));
+        let states = if self.per_thread {
+            States::per_thread(
+                self.config.clone(),
+                self.o_rotation_config,
+                self.cleanup_in_background_thread,
+            )
+        } else {
+            if let Some(ref rotation_config) = self.o_rotation_config {
+                cleanup_at_startup(&self.config, rotation_config);
+            }
+            States::Single(Mutex::new(State::try_new(
+                self.config.clone(),
+                self.o_rotation_config,
+                self.cleanup_in_background_thread,
+            )?))
+        };
         Ok(FileLogWriter::new(
             self.format,
+            self.o_structured_format,
             self.config.line_ending,
-            Mutex::new(State::try_new(
-                self.config,
-                self.o_rotation_config,
-                self.cleanup_in_background_thread,
-            )?),
+            states,
+            o_shards,
             self.max_log_level,
         ))
     }
@@ -259,7 +951,8 @@ impl FileLogWriterBuilder {
 
     /// Specifies a folder for the log files.
     ///
-    /// If the specified folder does not exist, the initialization will fail.
+    /// If the specified folder does not exist, it is created, unless this is switched off with
+    /// [`create_directory`](crate::writers::FileLogWriterBuilder::create_directory).
     /// With None, the log files are created in the folder where the program was started.
     pub fn o_directory<P: Into<PathBuf>>(mut self, directory: Option<P>) -> Self {
         self.config.filename_config.directory =
@@ -329,3 +1022,132 @@ impl FileLogWriterBuilder {
         self
     }
 }
+
+// The name of the executable, without extension.
+fn executable_name() -> String {
+    let arg0 = env::args().next().unwrap_or_else(|| "rs".to_owned());
+    Path::new(&arg0).file_stem().unwrap(/*cannot fail*/).to_string_lossy().to_string()
+}
+
+// Resolves the path as far as it exists, so that e.g. `log_files` and `./log_files/`
+// or a symbolic link to it compare equal; the rest of the path is appended as is.
+fn resolved(path: &Path) -> PathBuf {
+    let mut existing = path;
+    let mut rest = Vec::new();
+    loop {
+        if let Ok(canonical) = existing.canonicalize() {
+            return rest
+                .iter()
+                .rev()
+                .fold(canonical, |path, name| path.join(name));
+        }
+        match (existing.parent(), existing.file_name()) {
+            (Some(parent), Some(name)) => {
+                rest.push(name);
+                existing = if parent.as_os_str().is_empty() {
+                    Path::new(".")
+                } else {
+                    parent
+                };
+            }
+            _ => return path.to_path_buf(),
+        }
+    }
+}
+
+// Replaces the placeholders `{pid}`, `{hostname}`, and `{exe}`.
+fn replace_placeholders(input: &str, exe: &str) -> String {
+    let mut output = input
+        .replace("{pid}", &std::process::id().to_string())
+        .replace("{exe}", exe);
+    if output.contains("{hostname}") {
+        output = output.replace("{hostname}", &hostname());
+    }
+    output
+}
+
+// Determines the per-user folder for log files of the platform without additional dependencies.
+fn platform_log_dir(app_name: &str) -> Option<PathBuf> {
+    platform_log_dir_from(app_name, env::var_os)
+}
+
+// Determines the per-user folder for log files from the given environment variables.
+fn platform_log_dir_from<F>(app_name: &str, var: F) -> Option<PathBuf>
+where
+    F: Fn(&'static str) -> Option<OsString>,
+{
+    // the variables must contain absolute paths, relative paths are ignored
+    let absolute_var = |name: &'static str| {
+        var(name)
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+    };
+    // on Windows, the application folder is shared with other data
+    #[cfg(windows)]
+    let (o_base, o_subfolder) = (absolute_var("LOCALAPPDATA"), Some("logs"));
+    #[cfg(target_os = "macos")]
+    let (o_base, o_subfolder) = (
+        absolute_var("HOME").map(|home| home.join("Library").join("Logs")),
+        None::<&str>,
+    );
+    #[cfg(not(any(windows, target_os = "macos")))]
+    let (o_base, o_subfolder) = (
+        absolute_var("XDG_STATE_HOME")
+            .or_else(|| absolute_var("HOME").map(|home| home.join(".local").join("state"))),
+        None::<&str>,
+    );
+    o_base.map(|base| {
+        let directory = base.join(app_name);
+        match o_subfolder {
+            Some(subfolder) => directory.join(subfolder),
+            None => directory,
+        }
+    })
+}
+
+// Determines the host name without additional dependencies.
+pub(super) fn hostname() -> String {
+    #[cfg(windows)]
+    let o_hostname = env::var("COMPUTERNAME").ok();
+    #[cfg(not(windows))]
+    let o_hostname = std::fs::read_to_string("/proc/sys/kernel/hostname")
+        .or_else(|_| std::fs::read_to_string("/etc/hostname"))
+        .ok()
+        .or_else(|| env::var("HOSTNAME").ok());
+    o_hostname
+        .map(|hostname| hostname.trim().to_string())
+        .filter(|hostname| !hostname.is_empty())
+        .unwrap_or_else(|| "unknown_host".to_string())
+}
+
+#[cfg(all(test, not(any(windows, target_os = "macos"))))]
+mod test {
+    use super::platform_log_dir_from;
+    use std::ffi::OsString;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_platform_log_dir() {
+        let env = |state_home: Option<&'static str>| {
+            move |name: &'static str| match name {
+                "XDG_STATE_HOME" => state_home.map(OsString::from),
+                "HOME" => Some(OsString::from("/home/me")),
+                _ => None,
+            }
+        };
+        assert_eq!(
+            platform_log_dir_from("myapp", env(Some("/var/state"))),
+            Some(PathBuf::from("/var/state/myapp"))
+        );
+        // relative paths are ignored
+        assert_eq!(
+            platform_log_dir_from("myapp", env(Some("state"))),
+            Some(PathBuf::from("/home/me/.local/state/myapp"))
+        );
+        assert_eq!(
+            platform_log_dir_from("myapp", env(None)),
+            Some(PathBuf::from("/home/me/.local/state/myapp"))
+        );
+        assert_eq!(platform_log_dir_from("myapp", |_| None), None);
+    }
+}
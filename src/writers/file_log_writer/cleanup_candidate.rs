@@ -0,0 +1,37 @@
+use std::path::PathBuf;
+
+/// Describes what the cleanup would do with a rotated file.
+///
+/// Is returned by
+/// [`LoggerHandle::list_cleanup_candidates`](crate::LoggerHandle::list_cleanup_candidates)
+/// and [`FileLogWriter::list_cleanup_candidates`](crate::writers::FileLogWriter::list_cleanup_candidates).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CleanupCandidate {
+    /// The file would be compressed, and then removed.
+    ///
+    /// Only used with optional crate feature `compress`.
+    Compress {
+        /// The path of the log file.
+        file: PathBuf,
+    },
+    /// The file would be encrypted, and then removed.
+    ///
+    /// Only used with optional crate feature `encrypt`.
+    Encrypt {
+        /// The path of the file, after a compression that precedes the encryption.
+        file: PathBuf,
+    },
+    /// The file would be moved into the directory,
+    /// see [`Cleanup::MoveToDirectory`](crate::Cleanup::MoveToDirectory).
+    Move {
+        /// The path of the log file or compressed file.
+        file: PathBuf,
+        /// The directory into which the file would be moved.
+        directory: PathBuf,
+    },
+    /// The file would be removed.
+    Remove {
+        /// The path of the log file or compressed file.
+        file: PathBuf,
+    },
+}
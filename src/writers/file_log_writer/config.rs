@@ -1,7 +1,10 @@
-use crate::{Cleanup, Criterion, Naming};
+use super::{FileFooter, FileHeader, RotationCallback};
+use crate::{Cleanup, Criterion, DiskFull, Naming, SyncPolicy};
 use std::path::PathBuf;
+use std::time::Duration;
 
 // Describes how rotation should work
+#[derive(Clone)]
 pub(crate) struct RotationConfig {
     // Defines if rotation should be based on size or date
     pub(crate) criterion: Criterion,
@@ -16,16 +19,41 @@ pub(crate) struct FilenameConfig {
     pub(crate) file_basename: String,
     pub(crate) suffix: String,
     pub(crate) use_timestamp: bool,
+    // the basename without discriminant, if the cleanup also considers
+    // the files of other discriminants
+    pub(crate) o_shared_basename: Option<String>,
+    // if the cleanup only considers files whose rotation infix has the format of the naming
+    pub(crate) strict_cleanup: bool,
 }
 
 // The immutable configuration of a FileLogWriter.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone)]
 pub(crate) struct Config {
     pub(crate) print_message: bool,
     pub(crate) append: bool,
     pub(crate) o_buffersize: Option<usize>,
     pub(crate) filename_config: FilenameConfig,
     pub(crate) o_create_symlink: Option<PathBuf>,
+    pub(crate) latest_links: bool,
     pub(crate) line_ending: &'static [u8],
+    pub(crate) use_rotation_lock: bool,
+    pub(crate) o_header: Option<FileHeader>,
+    pub(crate) o_footer: Option<FileFooter>,
+    pub(crate) o_rotation_callback: Option<RotationCallback>,
+    pub(crate) structured_file: bool,
+    pub(crate) disk_full: DiskFull,
+    pub(crate) sync_policy: SyncPolicy,
+    // how long the shutdown waits for the cleanup thread
+    pub(crate) cleanup_timeout: Duration,
+    #[cfg(unix)]
+    pub(crate) o_file_mode: Option<u32>,
+    #[cfg(feature = "free_space")]
+    pub(crate) o_min_free_space: Option<u64>,
+    #[cfg(feature = "encrypt")]
+    pub(crate) o_encryption: Option<super::Encryption>,
+    #[cfg(feature = "integrity")]
+    pub(crate) o_hash_chain: Option<super::hash_chain::HashChain>,
 }
 impl Config {
     // Factory method; uses the same defaults as Logger.
@@ -37,11 +65,42 @@ impl Config {
                 file_basename: String::new(),
                 suffix: "log".to_string(),
                 use_timestamp: true,
+                o_shared_basename: None,
+                strict_cleanup: false,
             },
             append: false,
             o_buffersize: None,
             o_create_symlink: None,
+            latest_links: false,
             line_ending: super::UNIX_LINE_ENDING,
+            use_rotation_lock: false,
+            o_header: None,
+            o_footer: None,
+            o_rotation_callback: None,
+            structured_file: false,
+            disk_full: DiskFull::Drop,
+            sync_policy: SyncPolicy::Never,
+            cleanup_timeout: Duration::from_secs(30),
+            #[cfg(unix)]
+            o_file_mode: None,
+            #[cfg(feature = "free_space")]
+            o_min_free_space: None,
+            #[cfg(feature = "encrypt")]
+            o_encryption: None,
+            #[cfg(feature = "integrity")]
+            o_hash_chain: None,
+        }
+    }
+
+    // Returns true if the rotated files are to be encrypted.
+    pub fn encrypts(&self) -> bool {
+        #[cfg(feature = "encrypt")]
+        {
+            self.o_encryption.is_some()
+        }
+        #[cfg(not(feature = "encrypt"))]
+        {
+            false
         }
     }
 }
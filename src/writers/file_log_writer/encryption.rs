@@ -0,0 +1,83 @@
+use crate::FlexiLoggerError;
+use std::fs::File;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// Describes how the [`FileLogWriter`](crate::writers::FileLogWriter) encrypts its rotated files.
+///
+/// Only available with optional crate feature `encrypt`.
+///
+/// The files are encrypted with [age](https://age-encryption.org) for an X25519 public key
+/// (`age1...`), so that only the owner of the corresponding secret key can read them,
+/// e.g. with `age --decrypt -i key.txt my_prog.1.log.age`.
+/// The host that writes the logs only needs the public key.
+///
+/// The encryption is done by the cleanup, right after a rotation
+/// (and, with compression, after the compression); the encrypted file gets the additional
+/// suffix `.age`, the unencrypted file is removed.
+/// Encrypted files are counted and removed by the cleanup like the other files,
+/// but they are not compressed anymore, so use
+/// [`Cleanup::KeepCompressedFiles`](crate::Cleanup::KeepCompressedFiles)
+/// if you want to have compressed and encrypted files.
+///
+/// See [`Logger::encrypt_rotated_files`](crate::Logger::encrypt_rotated_files) and
+/// [`FileLogWriterBuilder::encrypt_rotated_files`](crate::writers::FileLogWriterBuilder::encrypt_rotated_files).
+///
+/// ## Example
+///
+/// ```rust
+/// use flexi_logger::writers::Encryption;
+///
+/// let encryption =
+///     Encryption::with_public_key("age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8p")
+///         .unwrap();
+/// ```
+#[derive(Clone)]
+pub struct Encryption(Arc<dyn Fn() -> String + Send + Sync>);
+impl Encryption {
+    /// Encrypts the files for the given public key.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::Encryption` if the public key is invalid.
+    pub fn with_public_key<S: Into<String>>(public_key: S) -> Result<Self, FlexiLoggerError> {
+        let public_key = public_key.into();
+        parse_public_key(&public_key)?;
+        Ok(Self(Arc::new(move || public_key.clone())))
+    }
+
+    /// Encrypts each file for the public key that the given function returns,
+    /// which allows e.g. using a key that is rotated by a key management system.
+    ///
+    /// If the returned key is invalid, the file stays unencrypted,
+    /// and the encryption is retried with the next cleanup.
+    pub fn from_fn<F>(f: F) -> Self
+    where
+        F: Fn() -> String + Send + Sync + 'static,
+    {
+        Self(Arc::new(f))
+    }
+
+    // Writes the encrypted file and removes the original; returns the path of the encrypted file.
+    pub(crate) fn encrypt_file(&self, file: &Path) -> Result<PathBuf, std::io::Error> {
+        let recipient = parse_public_key(&(self.0)())
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidInput, e.to_string()))?;
+        let mut encrypted_file = file.as_os_str().to_os_string();
+        encrypted_file.push(".age");
+        let encrypted_file = PathBuf::from(encrypted_file);
+
+        let mut writer = age::Encryptor::with_recipients(vec![Box::new(recipient)])
+            .wrap_output(File::create(&encrypted_file)?)?;
+        std::io::copy(&mut File::open(file)?, &mut writer)?;
+        writer.finish()?;
+        std::fs::remove_file(file)?;
+        Ok(encrypted_file)
+    }
+}
+
+fn parse_public_key(public_key: &str) -> Result<age::x25519::Recipient, FlexiLoggerError> {
+    age::x25519::Recipient::from_str(public_key.trim()).map_err(|e| {
+        FlexiLoggerError::Encryption(format!("invalid public key \"{}\": {}", public_key, e))
+    })
+}
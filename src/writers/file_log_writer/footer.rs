@@ -0,0 +1,97 @@
+use crate::timestamp::{self, Layout};
+use std::io::Write;
+use std::sync::Arc;
+
+/// The reason why the [`FileLogWriter`](crate::writers::FileLogWriter) closes a log file,
+/// see [`FileFooter`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CloseReason {
+    /// The file is closed because it is rotated.
+    Rotation,
+    /// The file is closed because the `FileLogWriter` is shut down.
+    Shutdown,
+}
+impl std::fmt::Display for CloseReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Rotation => f.write_str("rotation"),
+            Self::Shutdown => f.write_str("shutdown"),
+        }
+    }
+}
+
+/// A footer that the [`FileLogWriter`](crate::writers::FileLogWriter) writes at the end
+/// of a log file when it closes the file due to rotation or shutdown.
+///
+/// A file that ends without the footer was thus not closed cleanly,
+/// e.g. because the program crashed.
+///
+/// Note that the footer is only written on shutdown if
+/// [`LoggerHandle::shutdown`](crate::LoggerHandle::shutdown) is called.
+///
+/// See [`Logger::file_footer`](crate::Logger::file_footer) and
+/// [`FileLogWriterBuilder::file_footer`](crate::writers::FileLogWriterBuilder::file_footer).
+///
+/// ## Example
+///
+/// ```rust
+/// use flexi_logger::writers::FileFooter;
+///
+/// let fixed = FileFooter::from("===== end of file =====");
+/// let dynamic = FileFooter::from_fn(|w, reason| writeln!(w, "===== closed ({}) =====", reason));
+/// let standard = FileFooter::standard();
+/// ```
+#[derive(Clone)]
+#[allow(clippy::type_complexity)]
+pub struct FileFooter(
+    Arc<dyn Fn(&mut dyn Write, CloseReason) -> std::io::Result<()> + Send + Sync>,
+);
+impl FileFooter {
+    /// Creates a footer that is produced by the given function whenever a file is closed.
+    pub fn from_fn<F>(f: F) -> Self
+    where
+        F: Fn(&mut dyn Write, CloseReason) -> std::io::Result<()> + Send + Sync + 'static,
+    {
+        Self(Arc::new(f))
+    }
+
+    /// Creates a footer with the time of closing the file and the reason, like
+    ///
+    /// ```text
+    /// ===== log file closed cleanly at 2021-01-14 10:00:00.123456 +01:00 due to rotation =====
+    /// ```
+    #[must_use]
+    pub fn standard() -> Self {
+        Self::from_fn(|w, reason| {
+            write!(
+                w,
+                "===== log file closed cleanly at {} due to {} =====",
+                timestamp::format(&timestamp::now(), Layout::LogLine),
+                reason
+            )
+        })
+    }
+
+    pub(crate) fn write(
+        &self,
+        w: &mut dyn Write,
+        reason: CloseReason,
+        line_ending: &[u8],
+    ) -> std::io::Result<()> {
+        let mut buffer = Vec::with_capacity(200);
+        (self.0)(&mut buffer, reason)?;
+        super::header::write_completed(buffer, w, line_ending)
+    }
+}
+
+impl From<String> for FileFooter {
+    fn from(text: String) -> Self {
+        Self::from_fn(move |w, _| w.write_all(text.as_bytes()))
+    }
+}
+
+impl From<&str> for FileFooter {
+    fn from(text: &str) -> Self {
+        Self::from(text.to_string())
+    }
+}
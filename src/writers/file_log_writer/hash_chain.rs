@@ -0,0 +1,316 @@
+use crate::FlexiLoggerError;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+type Hash = [u8; 32];
+
+// The hashes of the side-car file are buffered until the buffered lines of the log file
+// are flushed, but at most this many bytes.
+const MAX_PENDING_HASHES: usize = 64 * 1024;
+
+// The key and the head of the hash chain of a FileLogWriter; the head is the hash
+// of the last line that was written, and the anchor of the chain in the next file.
+#[derive(Clone)]
+pub(crate) struct HashChain {
+    key: Arc<Vec<u8>>,
+    head: Arc<Mutex<Hash>>,
+}
+impl HashChain {
+    pub fn new(key: Vec<u8>) -> Self {
+        Self {
+            key: Arc::new(key),
+            head: Arc::new(Mutex::new([0; 32])),
+        }
+    }
+
+    // A new chain with the same key.
+    pub fn new_chain(&self) -> Self {
+        Self {
+            key: Arc::clone(&self.key),
+            head: Arc::new(Mutex::new([0; 32])),
+        }
+    }
+
+    // Wraps the writer of the given log file such that every line that is written
+    // extends the chain, and the running hash is written to the side-car file.
+    // When an existing, non-empty file is continued, also the chain of its side-car
+    // is continued.
+    pub fn wrap(
+        &self,
+        inner: Box<dyn Write + Send>,
+        log_file: &Path,
+        continued: bool,
+    ) -> Result<Box<dyn Write + Send>, std::io::Error> {
+        let sidecar_path = sidecar_path(log_file);
+        let mut head = self.head.lock().map_err(|_| poisoned())?;
+        let o_last_hash = if continued {
+            last_hash(&sidecar_path)?
+        } else {
+            None
+        };
+        let mut sidecar = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .append(o_last_hash.is_some())
+            .truncate(o_last_hash.is_none())
+            .open(&sidecar_path)?;
+        match o_last_hash {
+            Some(last_hash) => *head = last_hash,
+            None => writeln!(sidecar, "{}", hex(&head))?,
+        }
+        Ok(Box::new(ChainingWriter {
+            inner,
+            sidecar,
+            key: Arc::clone(&self.key),
+            head: Arc::clone(&self.head),
+            partial_line: Vec::new(),
+            pending_hashes: Vec::new(),
+        }))
+    }
+}
+
+// Writes through to the log file, and extends the hash chain with each completed line.
+// The hashes are written to the side-car file only after the log file was flushed,
+// so that the side-car file never covers lines that are not yet in the log file.
+struct ChainingWriter {
+    inner: Box<dyn Write + Send>,
+    sidecar: File,
+    key: Arc<Vec<u8>>,
+    head: Arc<Mutex<Hash>>,
+    partial_line: Vec<u8>,
+    pending_hashes: Vec<u8>,
+}
+impl Write for ChainingWriter {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.inner.write_all(buf)?;
+        {
+            let mut head = self.head.lock().map_err(|_| poisoned())?;
+            for line in lines(buf) {
+                self.partial_line.extend_from_slice(line);
+                if self.partial_line.ends_with(b"\n") {
+                    *head = chain(&self.key, &head, &self.partial_line);
+                    writeln!(self.pending_hashes, "{}", hex(&head))?;
+                    self.partial_line.clear();
+                }
+            }
+        }
+        if self.pending_hashes.len() >= MAX_PENDING_HASHES {
+            self.flush()?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()?;
+        if !self.pending_hashes.is_empty() {
+            self.sidecar.write_all(&self.pending_hashes)?;
+            self.pending_hashes.clear();
+        }
+        self.sidecar.flush()
+    }
+}
+impl Drop for ChainingWriter {
+    fn drop(&mut self) {
+        self.flush().ok();
+    }
+}
+
+// Splits the buffer into lines, keeping the line endings; the last line can be incomplete.
+fn lines(buf: &[u8]) -> impl Iterator<Item = &[u8]> {
+    let mut rest = buf;
+    std::iter::from_fn(move || {
+        if rest.is_empty() {
+            return None;
+        }
+        let len = rest
+            .iter()
+            .position(|b| *b == b'\n')
+            .map_or(rest.len(), |pos| pos + 1);
+        let (line, remainder) = rest.split_at(len);
+        rest = remainder;
+        Some(line)
+    })
+}
+
+// The HMAC-SHA256, with the given key, of the previous hash and the line.
+fn chain(key: &[u8], previous: &Hash, line: &[u8]) -> Hash {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).unwrap(/* HMAC accepts keys of any size */);
+    mac.update(previous);
+    mac.update(line);
+    let mut hash = [0; 32];
+    hash.copy_from_slice(&mac.finalize().into_bytes());
+    hash
+}
+
+fn hex(hash: &Hash) -> String {
+    use std::fmt::Write as _;
+    let mut hex = String::with_capacity(64);
+    for b in hash {
+        write!(hex, "{:02x}", b).unwrap(/* writing to a String cannot fail */);
+    }
+    hex
+}
+
+fn parse_hex(s: &str) -> Option<Hash> {
+    let s = s.trim();
+    if s.len() != 64 || !s.is_ascii() {
+        return None;
+    }
+    let mut hash = [0; 32];
+    for (i, byte) in hash.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).ok()?;
+    }
+    Some(hash)
+}
+
+fn poisoned() -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::Other, "hash chain is poisoned")
+}
+
+// The side-car file of a log file `x.log`, or of its compressed or encrypted variants,
+// is `x.log.sha256`.
+pub(crate) fn sidecar_path(log_file: &Path) -> PathBuf {
+    let mut path = log_file.to_path_buf();
+    while path
+        .extension()
+        .map_or(false, |ext| ext == "gz" || ext == "age")
+    {
+        path.set_extension("");
+    }
+    let mut path = path.into_os_string();
+    path.push(".sha256");
+    PathBuf::from(path)
+}
+
+// Renames the side-car file along with its log file.
+pub(crate) fn rename_sidecar(from: &Path, to: &Path) -> Result<(), std::io::Error> {
+    match std::fs::rename(sidecar_path(from), sidecar_path(to)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+// Removes the side-car file of a removed log file.
+pub(crate) fn remove_sidecar(log_file: &Path) -> Result<(), std::io::Error> {
+    match std::fs::remove_file(sidecar_path(log_file)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+fn last_hash(sidecar_path: &Path) -> Result<Option<Hash>, std::io::Error> {
+    match File::open(sidecar_path) {
+        Ok(file) => {
+            let mut o_last = None;
+            for line in BufReader::new(file).lines() {
+                o_last = parse_hex(&line?);
+            }
+            Ok(o_last)
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Verifies the hash chain of the given uncompressed and unencrypted log files,
+/// which must be given in the order in which they were written,
+/// with the key that was used to write them.
+///
+/// Only available with optional crate feature `integrity`.
+///
+/// Each line of each log file is checked against the running hash in the file's side-car
+/// file (`<log file>.sha256`), and the chain of each file must start with the last hash
+/// of the previous file.
+/// See [`FileLogWriterBuilder::use_hash_chain`](crate::writers::FileLogWriterBuilder::use_hash_chain).
+///
+/// # Errors
+///
+/// `FlexiLoggerError::HashChain` if a file was modified, truncated, or is missing in the sequence;
+/// `FlexiLoggerError::OutputIo` if a file cannot be read.
+pub fn verify_hash_chain<P: AsRef<Path>>(
+    log_files: &[P],
+    key: &[u8],
+) -> Result<(), FlexiLoggerError> {
+    let mut o_previous_head: Option<Hash> = None;
+    for log_file in log_files {
+        let log_file = log_file.as_ref();
+        let broken = |description: String| {
+            FlexiLoggerError::HashChain(format!("{}: {}", log_file.display(), description))
+        };
+        let mut hashes = BufReader::new(File::open(sidecar_path(log_file))?).lines();
+        let mut head = hashes
+            .next()
+            .transpose()?
+            .as_deref()
+            .and_then(parse_hex)
+            .ok_or_else(|| broken("the side-car file has no anchor".to_string()))?;
+        if let Some(previous_head) = o_previous_head {
+            if head != previous_head {
+                return Err(broken(
+                    "the chain does not continue the previous file".to_string(),
+                ));
+            }
+        }
+
+        let mut content = Vec::new();
+        File::open(log_file)?.read_to_end(&mut content)?;
+        let mut no_of_lines = 0;
+        for line in lines(&content) {
+            no_of_lines += 1;
+            head = chain(key, &head, line);
+            match hashes.next().transpose()?.as_deref().and_then(parse_hex) {
+                Some(hash) if hash == head => {}
+                Some(_) => return Err(broken(format!("line {} was modified", no_of_lines))),
+                None => {
+                    return Err(broken(format!(
+                        "line {} is not covered by the hash chain",
+                        no_of_lines
+                    )))
+                }
+            }
+        }
+        if hashes.next().is_some() {
+            return Err(broken(format!(
+                "the file was truncated after line {}",
+                no_of_lines
+            )));
+        }
+        o_previous_head = Some(head);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::{chain, parse_hex, sidecar_path};
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_chain_depends_on_key() {
+        let previous = [0; 32];
+        assert_eq!(
+            chain(b"key", &previous, b"line\n"),
+            chain(b"key", &previous, b"line\n")
+        );
+        assert_ne!(
+            chain(b"key", &previous, b"line\n"),
+            chain(b"other key", &previous, b"line\n")
+        );
+    }
+
+    #[test]
+    fn test_sidecar_path_and_hex() {
+        let expected = PathBuf::from("logs/x.1.log.sha256");
+        assert_eq!(sidecar_path(Path::new("logs/x.1.log")), expected);
+        assert_eq!(sidecar_path(Path::new("logs/x.1.log.gz")), expected);
+        assert_eq!(sidecar_path(Path::new("logs/x.1.log.gz.age")), expected);
+
+        let hash = parse_hex(&"0f".repeat(32)).unwrap();
+        assert_eq!(hash, [15; 32]);
+        assert!(parse_hex("0f0f").is_none());
+    }
+}
@@ -0,0 +1,92 @@
+use crate::timestamp::{self, Layout};
+use std::io::Write;
+use std::sync::Arc;
+
+/// A header that the [`FileLogWriter`](crate::writers::FileLogWriter) writes at the top
+/// of every new log file, e.g. with metadata that support teams need for analyzing the log.
+///
+/// A header can be created from a fixed text, with a function that writes it,
+/// or with [`FileHeader::standard`].
+///
+/// See [`Logger::file_header`](crate::Logger::file_header) and
+/// [`FileLogWriterBuilder::file_header`](crate::writers::FileLogWriterBuilder::file_header).
+///
+/// ## Example
+///
+/// ```rust
+/// use flexi_logger::writers::FileHeader;
+///
+/// let fixed = FileHeader::from("My application, log format version 2");
+/// let dynamic = FileHeader::from_fn(|w| {
+///     writeln!(w, "My application, log format version 2")?;
+///     writeln!(w, "Working directory: {:?}", std::env::current_dir()?)
+/// });
+/// let standard = FileHeader::standard("my_app", env!("CARGO_PKG_VERSION"));
+/// ```
+#[derive(Clone)]
+#[allow(clippy::type_complexity)]
+pub struct FileHeader(Arc<dyn Fn(&mut dyn Write) -> std::io::Result<()> + Send + Sync>);
+impl FileHeader {
+    /// Creates a header that is produced by the given function whenever a new file is started.
+    pub fn from_fn<F>(f: F) -> Self
+    where
+        F: Fn(&mut dyn Write) -> std::io::Result<()> + Send + Sync + 'static,
+    {
+        Self(Arc::new(f))
+    }
+
+    /// Creates a header with the application name and version, the start time
+    /// (i.e., the time when this method is called), the host name, the process id,
+    /// and the command line, like
+    ///
+    /// ```text
+    /// ===== my_app 1.2.3 =====
+    /// Started at: 2021-01-14 10:00:00.123456 +01:00
+    /// Host: my_host, process id: 4711
+    /// Command line: my_app --verbose
+    /// ```
+    #[must_use]
+    pub fn standard(app_name: &str, app_version: &str) -> Self {
+        let header = format!(
+            "===== {} {} =====\nStarted at: {}\nHost: {}, process id: {}\nCommand line: {}\n",
+            app_name,
+            app_version,
+            timestamp::format(&timestamp::now(), Layout::LogLine),
+            super::builder::hostname(),
+            std::process::id(),
+            std::env::args().collect::<Vec<String>>().join(" "),
+        );
+        Self::from(header)
+    }
+
+    pub(crate) fn write(&self, w: &mut dyn Write, line_ending: &[u8]) -> std::io::Result<()> {
+        let mut buffer = Vec::with_capacity(200);
+        (self.0)(&mut buffer)?;
+        write_completed(buffer, w, line_ending)
+    }
+}
+
+// Writes the buffer, and completes its last line if necessary;
+// the buffer is written with a single call, to not be interleaved with other output.
+pub(super) fn write_completed(
+    mut buffer: Vec<u8>,
+    w: &mut dyn Write,
+    line_ending: &[u8],
+) -> std::io::Result<()> {
+    if !buffer.is_empty() && !buffer.ends_with(b"\n") {
+        buffer.extend_from_slice(line_ending);
+    }
+    w.write_all(&buffer)
+}
+
+impl From<String> for FileHeader {
+    fn from(text: String) -> Self {
+        Self::from_fn(move |w| w.write_all(text.as_bytes()))
+    }
+}
+
+impl From<&str> for FileHeader {
+    fn from(text: &str) -> Self {
+        Self::from(text.to_string())
+    }
+}
@@ -0,0 +1,159 @@
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{Seek, SeekFrom, Write};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Arc;
+
+// Shared between a TrackedFile and its RecordBoundaries.
+struct Tracking {
+    // the number of bytes in the file
+    len: AtomicU64,
+    // while true, the TrackedFile discards what is written to it
+    discard: AtomicBool,
+}
+
+// The log file underneath the optional BufWriter; it counts the bytes that reach the file,
+// and can discard the bytes that are still buffered above it.
+pub(crate) struct TrackedFile {
+    file: File,
+    tracking: Arc<Tracking>,
+}
+impl Write for TrackedFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.tracking.discard.load(Ordering::Relaxed) {
+            return Ok(buf.len());
+        }
+        let written = self.file.write(buf)?;
+        self.tracking
+            .len
+            .fetch_add(written as u64, Ordering::Relaxed);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        if self.tracking.discard.load(Ordering::Relaxed) {
+            return Ok(());
+        }
+        self.file.flush()
+    }
+}
+
+// Knows where the records end in the log file, so that after a failed write,
+// e.g. because the disk is full, the file can be truncated back to the end
+// of the last complete record.
+pub(crate) struct RecordBoundaries {
+    tracking: Arc<Tracking>,
+    // a second handle of the log file, for truncating it
+    file: File,
+    // the end of the bytes that were handed to the writer
+    submitted: u64,
+    // the ends of the records that have possibly not yet reached the file completely
+    pending_ends: VecDeque<u64>,
+    // the end of the last record that reached the file completely
+    last_complete: u64,
+}
+impl RecordBoundaries {
+    // Wraps the freshly opened log file.
+    pub fn track(file: File) -> std::io::Result<(TrackedFile, Self)> {
+        let len = file.metadata()?.len();
+        let tracking = Arc::new(Tracking {
+            len: AtomicU64::new(len),
+            discard: AtomicBool::new(false),
+        });
+        let boundaries = Self {
+            tracking: Arc::clone(&tracking),
+            file: file.try_clone()?,
+            submitted: len,
+            pending_ends: VecDeque::new(),
+            last_complete: len,
+        };
+        Ok((TrackedFile { file, tracking }, boundaries))
+    }
+
+    // Registers that a complete record (or header or notice) of the given length
+    // was handed to the writer.
+    pub fn register(&mut self, len: usize) {
+        self.submitted += len as u64;
+        self.pending_ends.push_back(self.submitted);
+        self.forget_complete_records();
+    }
+
+    // Registers that everything that was handed to the writer was flushed to the file.
+    pub fn register_flushed(&mut self) {
+        self.submitted = self.tracking.len.load(Ordering::Relaxed);
+        self.last_complete = self.submitted;
+        self.pending_ends.clear();
+    }
+
+    fn forget_complete_records(&mut self) {
+        let len = self.tracking.len.load(Ordering::Relaxed);
+        while let Some(&end) = self.pending_ends.front() {
+            if end > len {
+                break;
+            }
+            self.last_complete = end;
+            self.pending_ends.pop_front();
+        }
+    }
+
+    // Discards what is still buffered in the given writer, which wraps the tracked file,
+    // and truncates the file to the end of the last complete record;
+    // returns the number of registered records that were lost.
+    //
+    // If the file has grown beyond what was written through this writer,
+    // e.g. because several processes append to it, it is not truncated.
+    pub fn truncate_to_last_record(&mut self, writer: &mut dyn Write) -> std::io::Result<usize> {
+        self.tracking.discard.store(true, Ordering::Relaxed);
+        writer.flush().ok();
+        self.tracking.discard.store(false, Ordering::Relaxed);
+
+        self.forget_complete_records();
+        let lost = self.pending_ends.len();
+        self.pending_ends.clear();
+        let len = self.tracking.len.load(Ordering::Relaxed);
+        if len > self.last_complete && self.file.metadata()?.len() == len {
+            self.file.set_len(self.last_complete)?;
+            // the handles share the file position, which matters without append mode
+            self.file.seek(SeekFrom::Start(self.last_complete))?;
+            self.tracking
+                .len
+                .store(self.last_complete, Ordering::Relaxed);
+        }
+        self.submitted = self.tracking.len.load(Ordering::Relaxed);
+        self.last_complete = self.submitted;
+        Ok(lost)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::RecordBoundaries;
+    use std::io::{BufWriter, Write};
+
+    #[test]
+    fn test_truncate_to_last_record() {
+        let dir = std::path::Path::new("log_files/record_boundaries");
+        std::fs::create_dir_all(dir).unwrap();
+        let path = dir.join(format!("test_{}.log", std::process::id()));
+        let file = std::fs::File::create(&path).unwrap();
+        let (tracked_file, mut boundaries) = RecordBoundaries::track(file).unwrap();
+        let mut writer = BufWriter::with_capacity(16, tracked_file);
+
+        writer.write_all(b"first record\n").unwrap();
+        boundaries.register(13);
+        // the buffer overflows, so the first record and a part of the second reach the file
+        writer.write_all(b"second record\n").unwrap();
+        writer.get_mut().write_all(b"second").unwrap();
+        boundaries.register(14);
+
+        assert_eq!(boundaries.truncate_to_last_record(&mut writer).unwrap(), 1);
+        writer.write_all(b"third record\n").unwrap();
+        boundaries.register(13);
+        writer.flush().unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            "first record\nthird record\n"
+        );
+        std::fs::remove_file(&path).ok();
+    }
+}
@@ -0,0 +1,60 @@
+use std::path::Path;
+use std::sync::Arc;
+
+/// Signature of the callback that is registered with
+/// [`Logger::on_rotation`](crate::Logger::on_rotation) or
+/// [`FileLogWriterBuilder::on_rotation`](crate::writers::FileLogWriterBuilder::on_rotation).
+pub type RotationCallback = Arc<dyn Fn(&RotationEvent) + Send + Sync>;
+
+/// Describes what the [`FileLogWriter`](crate::writers::FileLogWriter) did with its files
+/// during rotation and cleanup.
+///
+/// The events are reported after the respective action is completed;
+/// events of the cleanup can be reported from the cleanup thread,
+/// see [`FileLogWriterBuilder::cleanup_in_background_thread`](crate::writers::FileLogWriterBuilder::cleanup_in_background_thread).
+#[derive(Debug)]
+pub enum RotationEvent<'a> {
+    /// A log file was rotated, and the logging continues in a new file.
+    Rotated {
+        /// The path of the rotated file.
+        rotated_file: &'a Path,
+        /// The path of the file that is now written.
+        current_file: &'a Path,
+    },
+    /// A rotated log file was compressed and then removed.
+    Compressed {
+        /// The path of the removed log file.
+        log_file: &'a Path,
+        /// The path of the compressed file.
+        compressed_file: &'a Path,
+    },
+    /// A rotated log file or compressed file was encrypted, and the original was removed.
+    ///
+    /// Only used with optional crate feature `encrypt`.
+    Encrypted {
+        /// The path of the removed file.
+        file: &'a Path,
+        /// The path of the encrypted file.
+        encrypted_file: &'a Path,
+    },
+    /// A rotated log file was moved by the cleanup,
+    /// see [`Cleanup::MoveToDirectory`](crate::Cleanup::MoveToDirectory).
+    Moved {
+        /// The previous path of the file.
+        file: &'a Path,
+        /// The path of the file in the target directory.
+        moved_file: &'a Path,
+    },
+    /// A rotated log file or compressed file was removed by the cleanup.
+    Removed {
+        /// The path of the removed file.
+        file: &'a Path,
+    },
+}
+
+pub(crate) fn notify(o_callback: &Option<RotationCallback>, event: &RotationEvent) {
+    crate::diagnostics::rotation(event);
+    if let Some(callback) = o_callback {
+        callback(event);
+    }
+}
@@ -0,0 +1,58 @@
+use std::fs::File;
+use std::path::Path;
+
+use super::FilenameConfig;
+
+// An advisory lock that serializes rotation and cleanup between processes that write
+// log files with the same name into the same directory.
+//
+// The lock is an exclusive lock of the operating system on the file `.<basename>.lock`
+// in the log directory; the file stays in place.
+// The operating system releases the lock when the lock is dropped, or when the process ends,
+// so a crashed process cannot leave a lock behind, and a process that holds the lock
+// keeps it for as long as it needs, e.g. for compressing large files.
+pub(crate) struct RotationLock {
+    file: File,
+}
+impl RotationLock {
+    // Waits until the lock is available.
+    #[cfg(any(unix, windows))]
+    pub(crate) fn acquire(filename_config: &FilenameConfig) -> Result<Self, std::io::Error> {
+        use fs2::FileExt;
+        use std::fs::OpenOptions;
+        let path = filename_config
+            .directory
+            .join(format!(".{}.lock", filename_config.file_basename));
+        let file = OpenOptions::new().write(true).create(true).open(&path)?;
+        file.lock_exclusive()?;
+        Ok(Self { file })
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    pub(crate) fn acquire(_filename_config: &FilenameConfig) -> Result<Self, std::io::Error> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "the rotation lock is not supported on this platform",
+        ))
+    }
+}
+impl Drop for RotationLock {
+    fn drop(&mut self) {
+        #[cfg(any(unix, windows))]
+        fs2::FileExt::unlock(&self.file).ok();
+    }
+}
+
+// Identifies the file with the given path, if possible, to detect if another process
+// has rotated the file that this process writes to.
+#[cfg(unix)]
+pub(crate) fn file_id(path: &Path) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    std::fs::metadata(path)
+        .ok()
+        .map(|metadata| (metadata.dev(), metadata.ino()))
+}
+#[cfg(not(unix))]
+pub(crate) fn file_id(_path: &Path) -> Option<(u64, u64)> {
+    None
+}
@@ -0,0 +1,98 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+
+// Staging buffers for the formatted records, to reduce the contention on the file state
+// when many threads log concurrently.
+//
+// Each thread uses always the same shard, so the records of a thread keep their order.
+// The records of different threads can be reordered within the buffering interval.
+pub(crate) struct Shards {
+    shards: Vec<Mutex<Vec<u8>>>,
+    capacity: usize,
+}
+impl Shards {
+    pub fn new(no_of_shards: usize, capacity: usize) -> Self {
+        Self {
+            shards: (0..no_of_shards.max(1))
+                .map(|_| Mutex::new(Vec::with_capacity(capacity)))
+                .collect(),
+            capacity,
+        }
+    }
+
+    // Appends the record to the shard of the current thread;
+    // if the shard is full, it is drained into `write`.
+    //
+    // The shard stays locked while it is written, so that the records of a thread
+    // cannot overtake each other.
+    pub fn append<F>(&self, record: &[u8], write: F) -> std::io::Result<()>
+    where
+        F: FnOnce(&[u8]) -> std::io::Result<()>,
+    {
+        let mut shard = self.shards[shard_index() % self.shards.len()]
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        shard.extend_from_slice(record);
+        if shard.len() >= self.capacity {
+            let result = write(&shard);
+            shard.clear();
+            result
+        } else {
+            Ok(())
+        }
+    }
+
+    // Drains all shards into `write`.
+    pub fn drain<F>(&self, mut write: F) -> std::io::Result<()>
+    where
+        F: FnMut(&[u8]) -> std::io::Result<()>,
+    {
+        let mut result = Ok(());
+        for shard in &self.shards {
+            let mut shard = shard
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            if !shard.is_empty() {
+                result = result.and(write(&shard));
+                shard.clear();
+            }
+        }
+        result
+    }
+}
+
+// Threads are assigned to the shards round-robin, when they log for the first time.
+fn shard_index() -> usize {
+    static NEXT_INDEX: AtomicUsize = AtomicUsize::new(0);
+    thread_local! {
+        static INDEX: usize = NEXT_INDEX.fetch_add(1, Ordering::Relaxed);
+    }
+    INDEX.with(|index| *index)
+}
+
+#[cfg(test)]
+mod test {
+    use super::Shards;
+
+    #[test]
+    fn test_shards_keep_order_per_thread() {
+        let shards = Shards::new(4, 20);
+        let mut written = Vec::new();
+        for i in 0..10 {
+            shards
+                .append(format!("line {}\n", i).as_bytes(), |buf| {
+                    written.extend_from_slice(buf);
+                    Ok(())
+                })
+                .unwrap();
+        }
+        shards
+            .drain(|buf| {
+                written.extend_from_slice(buf);
+                Ok(())
+            })
+            .unwrap();
+        let expected: String = (0..10).map(|i| format!("line {}\n", i)).collect();
+        assert_eq!(String::from_utf8(written).unwrap(), expected);
+    }
+}
@@ -1,16 +1,85 @@
-use crate::{Age, Cleanup, Criterion, FlexiLoggerError, Naming};
-use chrono::{DateTime, Datelike, Local, Timelike};
+use crate::timestamp::{self, Layout, Parts, Timestamp};
+use crate::{Age, Cleanup, Criterion, DiskFull, FlexiLoggerError, Naming, NumberOrder};
 use std::cmp::max;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
 use std::fs::{File, OpenOptions};
+use std::hash::{Hash, Hasher};
 use std::io::{BufRead, BufReader, BufWriter, Write};
 use std::ops::Add;
 use std::path::{Path, PathBuf};
+use std::sync::mpsc::RecvTimeoutError;
+use std::sync::{Arc, Mutex, MutexGuard, Weak};
+use std::thread::ThreadId;
+use std::time::{Duration, Instant};
 
-use super::{Config, FilenameConfig, RotationConfig};
+#[cfg(feature = "integrity")]
+use super::hash_chain;
+use super::record_boundaries::RecordBoundaries;
+use super::rotation_event::notify;
+use super::rotation_lock::{file_id, RotationLock};
+use super::structured_file;
+use super::sync_state::SyncState;
+use super::{CleanupCandidate, CloseReason, Config, FilenameConfig, RotationConfig, RotationEvent};
 
-const CURRENT_INFIX: &str = "";
-fn number_infix(idx: u32) -> String {
-    format!(".{}", idx)
+pub(super) const CURRENT_INFIX: &str = "";
+
+// The infix of the file that is written with rotation;
+// it is empty, so that the file keeps its name, like with Naming::Logrotate.
+pub(super) fn current_infix(naming: Naming) -> Option<&'static str> {
+    match naming {
+        Naming::Logrotate => None,
+        Naming::Timestamps | Naming::Numbers | Naming::NumbersWith { .. } => Some(CURRENT_INFIX),
+    }
+}
+
+// The path of the rotated file with the given number,
+// e.g. `foo.42.log`, or `foo.log.42` with Naming::Logrotate.
+fn numbered_filepath(idx: u32, naming: Naming, filename_config: &FilenameConfig) -> PathBuf {
+    let width = naming.numbering().map_or(1, |(width, _, _)| width);
+    if let Naming::Logrotate = naming {
+        let mut path = get_filepath(None, filename_config).into_os_string();
+        path.push(format!(".{:0>width$}", idx, width = width));
+        PathBuf::from(path)
+    } else {
+        get_filepath(
+            Some(&format!(".{:0>width$}", idx, width = width)),
+            filename_config,
+        )
+    }
+}
+
+// The text that precedes the number in the names of the rotated files.
+fn number_marker(naming: Naming, filename_config: &FilenameConfig) -> String {
+    if let Naming::Logrotate = naming {
+        format!(".{}.", filename_config.suffix)
+    } else {
+        ".".to_string()
+    }
+}
+
+// Splits the name of a numbered file into the part up to the number, the number,
+// and the part after the number, e.g. `foo.42.log.gz` into `foo.`, 42, and `.log.gz`;
+// the number is the last one that follows the marker and is followed by a dot or the end.
+fn split_numbered_filename(file: &Path, marker: &str) -> Option<(String, u32, String)> {
+    let filename = file.file_name()?.to_string_lossy();
+    filename.rmatch_indices(marker).find_map(|(index, _)| {
+        let start = index + marker.len();
+        let end = start
+            + filename[start..]
+                .chars()
+                .take_while(char::is_ascii_digit)
+                .count();
+        if end == start || !(end == filename.len() || filename[end..].starts_with('.')) {
+            return None;
+        }
+        let idx = filename[start..end].parse().ok()?;
+        Some((
+            filename[..start].to_string(),
+            idx,
+            filename[end..].to_string(),
+        ))
+    })
 }
 
 //  Describes the latest existing numbered log file.
@@ -34,10 +103,169 @@ enum RollState {
     Size(u64, u64), // max_size, current_size
     Age(Age),
     AgeOrSize(Age, u64, u64), // age, max_size, current_size
+    Lines(u64, u64),          // max_lines, current_lines
+    Any(Vec<RollState>),
+    All(Vec<RollState>),
+    MinInterval(Duration, Instant, Box<RollState>), // min_interval, file_opened_at, inner
+}
+impl RollState {
+    fn size_rotation_necessary(max_size: u64, current_size: u64) -> bool {
+        current_size > max_size
+    }
+
+    fn age_rotation_necessary(age: Age, created_at: &Timestamp) -> bool {
+        let created_at = Parts::of(created_at);
+        let now = Parts::of(&timestamp::now());
+        match age {
+            Age::Day => !created_at.same_day(&now),
+            Age::Hour => !created_at.same_day(&now) || created_at.hour != now.hour,
+            Age::Minute => {
+                !created_at.same_day(&now)
+                    || created_at.hour != now.hour
+                    || created_at.minute != now.minute
+            }
+            Age::Second => {
+                !created_at.same_day(&now)
+                    || created_at.hour != now.hour
+                    || created_at.minute != now.minute
+                    || created_at.second != now.second
+            }
+        }
+    }
+
+    fn rotation_necessary(&self, created_at: &Timestamp) -> bool {
+        match self {
+            Self::Size(max_size, current_size) => {
+                Self::size_rotation_necessary(*max_size, *current_size)
+            }
+            Self::Age(age) => Self::age_rotation_necessary(*age, created_at),
+            Self::AgeOrSize(age, max_size, current_size) => {
+                Self::size_rotation_necessary(*max_size, *current_size)
+                    || Self::age_rotation_necessary(*age, created_at)
+            }
+            Self::Lines(max_lines, current_lines) => current_lines >= max_lines,
+            Self::Any(roll_states) => roll_states
+                .iter()
+                .any(|roll_state| roll_state.rotation_necessary(created_at)),
+            Self::All(roll_states) => roll_states
+                .iter()
+                .all(|roll_state| roll_state.rotation_necessary(created_at)),
+            Self::MinInterval(min_interval, opened_at, roll_state) => {
+                opened_at.elapsed() >= *min_interval && roll_state.rotation_necessary(created_at)
+            }
+        }
+    }
+
+    // Counts the written bytes or lines.
+    fn register(&mut self, buf: &[u8]) {
+        match self {
+            Self::Size(_, current_size) | Self::AgeOrSize(_, _, current_size) => {
+                *current_size += buf.len() as u64;
+            }
+            Self::Lines(_, current_lines) => *current_lines += count_lines(buf),
+            Self::Age(_) => {}
+            Self::Any(roll_states) | Self::All(roll_states) => {
+                for roll_state in roll_states {
+                    roll_state.register(buf);
+                }
+            }
+            Self::MinInterval(_, _, roll_state) => roll_state.register(buf),
+        }
+    }
+
+    // Sets the counter for a new or reopened file, which can already contain the header,
+    // or records of other processes.
+    fn reset(&mut self, p_path: &Path) -> Result<(), std::io::Error> {
+        match self {
+            Self::Size(_, current_size) | Self::AgeOrSize(_, _, current_size) => {
+                *current_size = std::fs::metadata(p_path)?.len();
+            }
+            Self::Lines(_, current_lines) => *current_lines = count_lines_of_file(p_path)?,
+            Self::Age(_) => {}
+            Self::Any(roll_states) | Self::All(roll_states) => {
+                for roll_state in roll_states {
+                    roll_state.reset(p_path)?;
+                }
+            }
+            Self::MinInterval(_, opened_at, roll_state) => {
+                *opened_at = Instant::now();
+                roll_state.reset(p_path)?;
+            }
+        }
+        Ok(())
+    }
+
+    // Returns the length of the leading lines of buf with which the size limit is exceeded,
+    // or the line limit is reached, if further lines follow that must go into the next file.
+    // With All, the batch is only split if all parts can determine a split point.
+    fn split_point(&self, buf: &[u8]) -> Option<usize> {
+        let (max, mut current, count_lines) = match self {
+            Self::Size(max_size, current_size) | Self::AgeOrSize(_, max_size, current_size) => {
+                (*max_size, *current_size, false)
+            }
+            Self::Lines(max_lines, current_lines) => (*max_lines, *current_lines, true),
+            Self::Age(_) => return None,
+            Self::Any(roll_states) => {
+                return roll_states
+                    .iter()
+                    .filter_map(|roll_state| roll_state.split_point(buf))
+                    .min();
+            }
+            Self::All(roll_states) => {
+                return roll_states
+                    .iter()
+                    .map(|roll_state| roll_state.split_point(buf))
+                    .collect::<Option<Vec<usize>>>()
+                    .and_then(|split_points| split_points.into_iter().max());
+            }
+            Self::MinInterval(min_interval, opened_at, roll_state) => {
+                return if opened_at.elapsed() >= *min_interval {
+                    roll_state.split_point(buf)
+                } else {
+                    None
+                };
+            }
+        };
+        let mut start = 0;
+        for (idx, _) in buf.iter().enumerate().filter(|(_, byte)| **byte == b'\n') {
+            let end = idx + 1;
+            current += if count_lines { 1 } else { (end - start) as u64 };
+            start = end;
+            let limit_hit = if count_lines {
+                current >= max
+            } else {
+                current > max
+            };
+            if limit_hit && end < buf.len() {
+                return Some(end);
+            }
+        }
+        None
+    }
+}
+
+fn count_lines(buf: &[u8]) -> u64 {
+    buf.iter().filter(|byte| **byte == b'\n').count() as u64
+}
+
+fn count_lines_of_file(p_path: &Path) -> Result<u64, std::io::Error> {
+    let mut reader = BufReader::new(File::open(p_path)?);
+    let mut lines = 0;
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Ok(lines);
+        }
+        lines += count_lines(buf);
+        let len = buf.len();
+        reader.consume(len);
+    }
 }
 
 enum MessageToCleanupThread {
     Act,
+    // is acknowledged when the previously requested work is done
+    Wait(std::sync::mpsc::Sender<()>),
     Die,
 }
 struct CleanupThreadHandle {
@@ -46,53 +274,40 @@ struct CleanupThreadHandle {
 }
 
 struct RotationState {
+    naming: Naming,
     naming_state: NamingState,
     roll_state: RollState,
-    created_at: DateTime<Local>,
+    created_at: Timestamp,
+    // only used with the rotation lock
+    o_file_id: Option<(u64, u64)>,
     cleanup: Cleanup,
     o_cleanup_thread_handle: Option<CleanupThreadHandle>,
+    // when the free space in the log directory was last checked
+    #[cfg(feature = "free_space")]
+    o_free_space_checked_at: Option<std::time::Instant>,
 }
 impl RotationState {
-    fn size_rotation_necessary(max_size: u64, current_size: u64) -> bool {
-        current_size > max_size
-    }
-
-    fn age_rotation_necessary(&self, age: Age) -> bool {
-        let now = Local::now();
-        match age {
-            Age::Day => self.created_at.num_days_from_ce() != now.num_days_from_ce(),
-            Age::Hour => {
-                self.created_at.num_days_from_ce() != now.num_days_from_ce()
-                    || self.created_at.hour() != now.hour()
-            }
-            Age::Minute => {
-                self.created_at.num_days_from_ce() != now.num_days_from_ce()
-                    || self.created_at.hour() != now.hour()
-                    || self.created_at.minute() != now.minute()
-            }
-            Age::Second => {
-                self.created_at.num_days_from_ce() != now.num_days_from_ce()
-                    || self.created_at.hour() != now.hour()
-                    || self.created_at.minute() != now.minute()
-                    || self.created_at.second() != now.second()
-            }
-        }
+    fn rotation_necessary(&self) -> bool {
+        self.roll_state.rotation_necessary(&self.created_at)
     }
 
-    fn rotation_necessary(&self) -> bool {
-        match &self.roll_state {
-            RollState::Size(max_size, current_size) => {
-                Self::size_rotation_necessary(*max_size, *current_size)
-            }
-            RollState::Age(age) => self.age_rotation_necessary(*age),
-            RollState::AgeOrSize(age, max_size, current_size) => {
-                Self::size_rotation_necessary(*max_size, *current_size)
-                    || self.age_rotation_necessary(*age)
-            }
-        }
+    // Returns a receiver that is notified when the cleanup thread has done the work
+    // that was requested so far; None if no cleanup thread is running.
+    fn cleanup_ack(&self) -> Option<std::sync::mpsc::Receiver<()>> {
+        self.o_cleanup_thread_handle
+            .as_ref()
+            .and_then(|cleanup_thread_handle| {
+                let (ack_sender, ack_receiver) = std::sync::mpsc::channel();
+                cleanup_thread_handle
+                    .sender
+                    .send(MessageToCleanupThread::Wait(ack_sender))
+                    .ok()
+                    .map(|()| ack_receiver)
+            })
     }
 
-    fn shutdown(&mut self) {
+    fn shutdown(&mut self, cleanup_timeout: Duration) {
+        let o_ack_receiver = self.cleanup_ack();
         // this sets o_cleanup_thread_handle in self.state.o_rotation_state to None:
         let o_cleanup_thread_handle = self.o_cleanup_thread_handle.take();
         if let Some(cleanup_thread_handle) = o_cleanup_thread_handle {
@@ -100,6 +315,16 @@ impl RotationState {
                 .sender
                 .send(MessageToCleanupThread::Die)
                 .ok();
+            if let Some(ack_receiver) = o_ack_receiver {
+                if let Err(RecvTimeoutError::Timeout) = ack_receiver.recv_timeout(cleanup_timeout) {
+                    // the thread is left alone and ends with the process
+                    crate::error_channel::report(format_args!(
+                        "cleanup of log files did not finish within {:?}",
+                        cleanup_timeout
+                    ));
+                    return;
+                }
+            }
             cleanup_thread_handle.join_handle.join().ok();
         }
     }
@@ -107,28 +332,37 @@ impl RotationState {
 
 // Could not implement `std::convert::From` because other parameters are required.
 fn try_roll_state_from_criterion(
-    criterion: Criterion,
-    config: &Config,
+    criterion: &Criterion,
     p_path: &Path,
 ) -> Result<RollState, std::io::Error> {
-    Ok(match criterion {
+    Ok(match *criterion {
         Criterion::Age(age) => RollState::Age(age),
         Criterion::Size(size) => {
-            let written_bytes = if config.append {
-                std::fs::metadata(p_path)?.len()
-            } else {
-                0
-            };
+            let written_bytes = std::fs::metadata(p_path)?.len();
             RollState::Size(size, written_bytes)
         } // max_size, current_size
         Criterion::AgeOrSize(age, size) => {
-            let written_bytes = if config.append {
-                std::fs::metadata(&p_path)?.len()
-            } else {
-                0
-            };
+            let written_bytes = std::fs::metadata(&p_path)?.len();
             RollState::AgeOrSize(age, size, written_bytes)
         } // age, max_size, current_size
+        Criterion::Lines(lines) => RollState::Lines(lines, count_lines_of_file(p_path)?),
+        Criterion::Any(criteria) => RollState::Any(
+            criteria
+                .iter()
+                .map(|criterion| try_roll_state_from_criterion(criterion, p_path))
+                .collect::<Result<Vec<RollState>, std::io::Error>>()?,
+        ),
+        Criterion::All(criteria) => RollState::All(
+            criteria
+                .iter()
+                .map(|criterion| try_roll_state_from_criterion(criterion, p_path))
+                .collect::<Result<Vec<RollState>, std::io::Error>>()?,
+        ),
+        Criterion::MinInterval(min_interval, criterion) => RollState::MinInterval(
+            min_interval,
+            Instant::now(),
+            Box::new(try_roll_state_from_criterion(criterion, p_path)?),
+        ),
     })
 }
 
@@ -141,6 +375,16 @@ enum Inner {
 pub(crate) struct State {
     config: Config,
     inner: Inner,
+    // the optional structured file that accompanies the current log file
+    o_structured_file: Option<Box<dyn Write + Send>>,
+    // avoids repeated footers if shutdown is called more than once
+    footer_written: bool,
+    // where the records in the current log file end
+    o_boundaries: Option<RecordBoundaries>,
+    // while the disk is full: the number of records that could not be written
+    o_records_lost_to_full_disk: Option<usize>,
+    // with a sync policy: the data that still need to be synced
+    o_sync: Option<SyncState>,
 }
 impl State {
     pub fn try_new(
@@ -148,25 +392,54 @@ impl State {
         o_rotation_config: Option<RotationConfig>,
         cleanup_in_background_thread: bool,
     ) -> Result<Self, FlexiLoggerError> {
-        Ok(Self {
+        Ok(Self::new(
+            config,
+            o_rotation_config,
+            cleanup_in_background_thread,
+        ))
+    }
+
+    // Creates the state in the initial phase, the log file is opened with the first write.
+    fn new(
+        config: Config,
+        o_rotation_config: Option<RotationConfig>,
+        cleanup_in_background_thread: bool,
+    ) -> Self {
+        Self {
             inner: Inner::Initial(o_rotation_config, cleanup_in_background_thread),
             config,
-        })
+            o_structured_file: None,
+            footer_written: false,
+            o_boundaries: None,
+            o_records_lost_to_full_disk: None,
+            o_sync: None,
+        }
     }
 
+    #[allow(clippy::too_many_lines)]
     fn initialize(&mut self) -> Result<(), std::io::Error> {
         if let Inner::Initial(o_rotation_config, cleanup_in_background_thread) = &self.inner {
             match o_rotation_config {
                 None => {
-                    let (log_file, _created_at, _p_path) = open_log_file(&self.config, false)?;
+                    let (log_file, boundaries, _created_at, p_path) =
+                        open_log_file(&self.config, None)?;
+                    self.o_boundaries = Some(boundaries);
+                    self.o_structured_file = open_structured_file(&self.config, &p_path)?;
+                    self.o_sync = SyncState::open(self.config.sync_policy, &p_path)?;
                     self.inner = Inner::Active(None, log_file);
                 }
                 Some(rotate_config) => {
+                    let o_lock = if self.config.use_rotation_lock {
+                        Some(RotationLock::acquire(&self.config.filename_config)?)
+                    } else {
+                        None
+                    };
                     // first rotate, then open the log file
+                    let mut o_rotated_path = None;
                     let naming_state = match rotate_config.naming {
                         Naming::Timestamps => {
                             if !self.config.append {
-                                rotate_output_file_to_date(
+                                o_rotated_path = rotate_output_file_to_date(
                                     &get_creation_date(&get_filepath(
                                         Some(CURRENT_INFIX),
                                         &self.config.filename_config,
@@ -176,33 +449,58 @@ impl State {
                             }
                             NamingState::CreatedAt
                         }
-                        Naming::Numbers => {
-                            let mut rotation_state =
-                                get_highest_rotate_idx(&self.config.filename_config);
+                        Naming::Numbers | Naming::NumbersWith { .. } | Naming::Logrotate => {
+                            let mut rotation_state = get_highest_rotate_idx(
+                                &self.config.filename_config,
+                                rotate_config.naming,
+                            );
                             if !self.config.append {
-                                rotation_state =
-                                    rotate_output_file_to_idx(rotation_state, &self.config)?;
+                                let (idx_state, o_path) = rotate_output_file_to_idx(
+                                    rotation_state,
+                                    rotate_config.naming,
+                                    &self.config,
+                                )?;
+                                rotation_state = idx_state;
+                                o_rotated_path = o_path;
                             }
                             NamingState::IdxState(rotation_state)
                         }
                     };
-                    let (log_file, created_at, p_path) = open_log_file(&self.config, true)?;
+                    let (log_file, boundaries, created_at, p_path) =
+                        open_log_file(&self.config, current_infix(rotate_config.naming))?;
+                    self.o_boundaries = Some(boundaries);
+                    self.o_structured_file = open_structured_file(&self.config, &p_path)?;
+                    self.o_sync = SyncState::open(self.config.sync_policy, &p_path)?;
+                    if let Some(rotated_path) = o_rotated_path {
+                        crate::stats::count_rotation();
+                        link_latest_rotated(&self.config, &rotated_path);
+                        notify(
+                            &self.config.o_rotation_callback,
+                            &RotationEvent::Rotated {
+                                rotated_file: &rotated_path,
+                                current_file: &p_path,
+                            },
+                        );
+                    }
+
+                    let roll_state =
+                        try_roll_state_from_criterion(&rotate_config.criterion, &p_path)?;
+                    let o_file_id = o_lock.as_ref().and_then(|_| file_id(&p_path));
+                    // the cleanup takes the lock itself
+                    drop(o_lock);
 
-                    let roll_state = try_roll_state_from_criterion(
-                        rotate_config.criterion,
-                        &self.config,
-                        &p_path,
-                    )?;
                     let mut o_cleanup_thread_handle = None;
-                    if rotate_config.cleanup.do_cleanup() {
+                    if rotate_config.cleanup.do_cleanup() || self.config.encrypts() {
                         remove_or_compress_too_old_logfiles(
                             &None,
                             &rotate_config.cleanup,
-                            &self.config.filename_config,
+                            rotate_config.naming,
+                            &self.config,
                         )?;
                         if *cleanup_in_background_thread {
                             let cleanup = rotate_config.cleanup;
-                            let filename_config = self.config.filename_config.clone();
+                            let naming = rotate_config.naming;
+                            let config = self.config.clone();
                             let (sender, receiver) = std::sync::mpsc::channel();
                             let join_handle = std::thread::Builder::new()
                                 .name("flexi_logger-cleanup".to_string())
@@ -211,10 +509,17 @@ impl State {
                                     match receiver.recv() {
                                         Ok(MessageToCleanupThread::Act) => {
                                             remove_or_compress_too_old_logfiles_impl(
-                                                &cleanup,
-                                                &filename_config,
+                                                &cleanup, naming, &config,
                                             )
-                                            .ok();
+                                            .unwrap_or_else(|e| {
+                                                crate::error_channel::report(format_args!(
+                                                    "cleanup of log files failed with {}",
+                                                    e
+                                                ));
+                                            });
+                                        }
+                                        Ok(MessageToCleanupThread::Wait(ack_sender)) => {
+                                            ack_sender.send(()).ok();
                                         }
                                         Ok(MessageToCleanupThread::Die) | Err(_) => {
                                             return;
@@ -230,11 +535,15 @@ impl State {
                     }
                     self.inner = Inner::Active(
                         Some(RotationState {
+                            naming: rotate_config.naming,
                             naming_state,
                             roll_state,
                             created_at,
+                            o_file_id,
                             cleanup: rotate_config.cleanup,
                             o_cleanup_thread_handle,
+                            #[cfg(feature = "free_space")]
+                            o_free_space_checked_at: None,
                         }),
                         log_file,
                     );
@@ -244,89 +553,387 @@ impl State {
         Ok(())
     }
 
+    // A full disk is handled like in write_buffer().
     pub fn flush(&mut self) -> std::io::Result<()> {
+        match self.flush_to_disk() {
+            Err(ref e) if is_disk_full(e) => {
+                self.discard_partial_records();
+                Ok(())
+            }
+            result => result,
+        }
+    }
+
+    fn flush_to_disk(&mut self) -> std::io::Result<()> {
+        if let Some(ref mut structured_file) = self.o_structured_file {
+            structured_file.flush()?;
+        }
         if let Inner::Active(_, ref mut file) = self.inner {
-            file.flush()
-        } else {
-            Ok(())
+            file.flush()?;
         }
+        self.sync_if_due(0)
     }
 
-    // With rotation, the logger always writes into a file with infix `_rCURRENT`.
-    // On overflow, an existing `_rCURRENT` file is renamed to the next numbered file,
-    // before writing into `_rCURRENT` goes on.
+    // With rotation, the logger always writes into the file without infix.
+    // On overflow, this file is renamed to the next numbered or timestamped file,
+    // before writing into a fresh file without infix goes on.
     #[inline]
     fn mount_next_linewriter_if_necessary(&mut self) -> Result<(), FlexiLoggerError> {
-        if let Inner::Active(Some(ref mut rotation_state), ref mut file) = self.inner {
+        if let Inner::Active(Some(ref rotation_state), _) = self.inner {
             if rotation_state.rotation_necessary() {
+                self.rotate()?;
+            }
+        }
+        Ok(())
+    }
+
+    // Rotates the current log file, regardless of the rotation criterion.
+    fn rotate(&mut self) -> Result<(), FlexiLoggerError> {
+        if let Inner::Active(Some(ref mut rotation_state), ref mut file) = self.inner {
+            let o_lock = if self.config.use_rotation_lock {
+                Some(RotationLock::acquire(&self.config.filename_config)?)
+            } else {
+                None
+            };
+            let current_path = get_filepath(
+                current_infix(rotation_state.naming),
+                &self.config.filename_config,
+            );
+
+            // With the rotation lock, another process may have rotated our file already;
+            // then we only need to switch to the new file
+            let rotated_by_other_process = o_lock.is_some()
+                && rotation_state.o_file_id.is_some()
+                && rotation_state.o_file_id != file_id(&current_path);
+
+            let mut o_rotated_path = None;
+            if !rotated_by_other_process {
+                if let Some(ref footer) = self.config.o_footer {
+                    footer.write(file, CloseReason::Rotation, self.config.line_ending)?;
+                    file.flush()?;
+                }
+                if let Some(ref mut structured_file) = self.o_structured_file {
+                    structured_file.flush()?;
+                }
+                if let Some(ref mut sync_state) = self.o_sync {
+                    file.flush()?;
+                    sync_state.sync()?;
+                }
                 match rotation_state.naming_state {
                     NamingState::CreatedAt => {
-                        rotate_output_file_to_date(&rotation_state.created_at, &self.config)?;
+                        o_rotated_path =
+                            rotate_output_file_to_date(&rotation_state.created_at, &self.config)?;
                     }
                     NamingState::IdxState(ref mut idx_state) => {
-                        *idx_state = rotate_output_file_to_idx(*idx_state, &self.config)?;
+                        if o_lock.is_some() {
+                            // other processes may have added numbered files
+                            *idx_state = get_highest_rotate_idx(
+                                &self.config.filename_config,
+                                rotation_state.naming,
+                            );
+                        }
+                        let (new_idx_state, o_path) = rotate_output_file_to_idx(
+                            *idx_state,
+                            rotation_state.naming,
+                            &self.config,
+                        )?;
+                        *idx_state = new_idx_state;
+                        o_rotated_path = o_path;
                     }
                 }
+            }
 
-                let (line_writer, created_at, _) = open_log_file(&self.config, true)?;
-                *file = line_writer;
-                rotation_state.created_at = created_at;
-                if let RollState::Size(_, ref mut current_size)
-                | RollState::AgeOrSize(_, _, ref mut current_size) = rotation_state.roll_state
-                {
-                    *current_size = 0;
+            let (line_writer, boundaries, created_at, p_path) =
+                open_log_file(&self.config, current_infix(rotation_state.naming))?;
+            *file = line_writer;
+            self.o_boundaries = Some(boundaries);
+            self.o_structured_file = open_structured_file(&self.config, &p_path)?;
+            self.o_sync = SyncState::open(self.config.sync_policy, &p_path)?;
+            self.footer_written = false;
+            if let Some(rotated_path) = o_rotated_path {
+                crate::stats::count_rotation();
+                link_latest_rotated(&self.config, &rotated_path);
+                notify(
+                    &self.config.o_rotation_callback,
+                    &RotationEvent::Rotated {
+                        rotated_file: &rotated_path,
+                        current_file: &p_path,
+                    },
+                );
+            }
+            rotation_state.created_at = created_at;
+            rotation_state.roll_state.reset(&p_path)?;
+            if o_lock.is_some() {
+                rotation_state.o_file_id = file_id(&p_path);
+            }
+            // the cleanup takes the lock itself
+            drop(o_lock);
+
+            remove_or_compress_too_old_logfiles(
+                &rotation_state.o_cleanup_thread_handle,
+                &rotation_state.cleanup,
+                rotation_state.naming,
+                &self.config,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    // Writes the buffer to the log file, and the optional structured buffer
+    // to the structured file.
+    //
+    // While the disk is full, the records are dropped or written to stderr, see `DiskFull`.
+    pub fn write_buffer(
+        &mut self,
+        buf: &[u8],
+        o_structured_buf: Option<&[u8]>,
+    ) -> std::io::Result<()> {
+        let mut buf = buf;
+        loop {
+            match self.write_buffer_to_disk(buf, o_structured_buf) {
+                Ok(written) if written < buf.len() => buf = &buf[written..],
+                Ok(_) => return Ok(()),
+                Err(ref e) if is_disk_full(e) => {
+                    self.discard_partial_records();
+                    self.handle_full_disk(buf);
+                    return Ok(());
                 }
+                Err(e) => return Err(e),
+            }
+        }
+    }
 
-                remove_or_compress_too_old_logfiles(
-                    &rotation_state.o_cleanup_thread_handle,
-                    &rotation_state.cleanup,
-                    &self.config.filename_config,
-                )?;
+    // Discards the records that are still buffered, and truncates the log file back to the
+    // end of the last complete record, so that no partial record stays in the file;
+    // the discarded records are counted as lost.
+    fn discard_partial_records(&mut self) {
+        self.report_full_disk();
+        let o_result = match (&mut self.o_boundaries, &mut self.inner) {
+            (Some(boundaries), Inner::Active(_, file)) => {
+                Some(boundaries.truncate_to_last_record(&mut **file))
+            }
+            _ => None,
+        };
+        match o_result {
+            Some(Ok(lost)) => {
+                *self.o_records_lost_to_full_disk.get_or_insert(0) += lost;
+                crate::stats::count_dropped_records(lost);
             }
+            Some(Err(e)) => crate::error_channel::report(format_args!(
+                "truncating the log file to the last complete record failed with {}",
+                e
+            )),
+            None => {}
         }
+    }
 
-        Ok(())
+    // Reports the full disk when it is detected.
+    fn report_full_disk(&mut self) {
+        let disk_full = self.config.disk_full;
+        self.o_records_lost_to_full_disk.get_or_insert_with(|| {
+            crate::error_channel::report(format_args!(
+                "the disk is full, log records are {} until writing to the log file \
+                 succeeds again",
+                match disk_full {
+                    DiskFull::Drop => "dropped",
+                    DiskFull::Stderr => "written to stderr",
+                }
+            ));
+            0
+        });
     }
 
-    pub fn write_buffer(&mut self, buf: &[u8]) -> std::io::Result<()> {
+    fn handle_full_disk(&mut self, buf: &[u8]) {
+        self.report_full_disk();
+        *self.o_records_lost_to_full_disk.get_or_insert(0) += 1;
+        match self.config.disk_full {
+            DiskFull::Drop => crate::stats::count_dropped_records(1),
+            DiskFull::Stderr => {
+                std::io::stderr().write_all(buf).ok();
+            }
+        }
+    }
+
+    // Returns the number of written bytes, which is less than the length of buf if buf
+    // has to be split for the rotation.
+    fn write_buffer_to_disk(
+        &mut self,
+        buf: &[u8],
+        o_structured_buf: Option<&[u8]>,
+    ) -> std::io::Result<usize> {
         if let Inner::Initial(_, _) = self.inner {
             self.initialize()?;
         }
         // rotate if necessary
         self.mount_next_linewriter_if_necessary()
             .unwrap_or_else(|e| {
-                eprintln!("[flexi_logger] opening file failed with {}", e);
+                crate::error_channel::report(format_args!("opening file failed with {}", e));
             });
+        #[cfg(feature = "free_space")]
+        self.ensure_free_space();
+
+        // A batch of records from the staging buffers is only written up to the record
+        // with which the size or line limit is exceeded, so that the rotation is not delayed;
+        // a record with a structured record is always a single record
+        let buf = match (o_structured_buf, &self.inner) {
+            (None, Inner::Active(Some(rotation_state), _)) => {
+                match rotation_state.roll_state.split_point(buf) {
+                    Some(split_point) => &buf[..split_point],
+                    None => buf,
+                }
+            }
+            _ => buf,
+        };
 
         if let Inner::Active(ref mut o_rotation_state, ref mut log_file) = self.inner {
+            if let Some(lost_records) = self.o_records_lost_to_full_disk {
+                let mut notice = format!(
+                    "[flexi_logger] resumed after {} {} records (disk full)",
+                    lost_records,
+                    match self.config.disk_full {
+                        DiskFull::Drop => "dropped",
+                        DiskFull::Stderr => "redirected",
+                    }
+                )
+                .into_bytes();
+                notice.extend_from_slice(self.config.line_ending);
+                log_file.write_all(&notice)?;
+                if let Some(ref mut boundaries) = self.o_boundaries {
+                    boundaries.register(notice.len());
+                }
+                if let Some(ref mut rotation_state) = o_rotation_state {
+                    rotation_state.roll_state.register(&notice);
+                }
+                self.o_records_lost_to_full_disk = None;
+            }
+            // buf contains the complete record, including the line ending;
+            // a BufWriter keeps the record boundaries when it writes to the file
             log_file.write_all(buf)?;
+            if let Some(ref mut boundaries) = self.o_boundaries {
+                boundaries.register(buf.len());
+            }
+            crate::stats::count_bytes_written(buf.len());
+            if let (Some(structured_file), Some(structured_buf)) =
+                (&mut self.o_structured_file, o_structured_buf)
+            {
+                structured_file.write_all(structured_buf)?;
+                crate::stats::count_bytes_written(structured_buf.len());
+            }
+            // this includes what is still in the buffer of a BufWriter
             if let Some(ref mut rotation_state) = o_rotation_state {
-                if let RollState::Size(_, ref mut current_size)
-                | RollState::AgeOrSize(_, _, ref mut current_size) = rotation_state.roll_state
-                {
-                    *current_size += buf.len() as u64;
-                }
+                rotation_state.roll_state.register(buf);
             };
         }
+        self.sync_if_due(buf.len())?;
+        Ok(buf.len())
+    }
+
+    // Forces the written data to the storage device, if the sync policy demands it.
+    fn sync_if_due(&mut self, written: usize) -> std::io::Result<()> {
+        if let (Some(sync_state), Inner::Active(_, log_file)) = (&mut self.o_sync, &mut self.inner)
+        {
+            if sync_state.register(written) {
+                log_file.flush()?;
+                sync_state.sync()?;
+            }
+        }
         Ok(())
     }
 
-    pub fn current_filename(&self) -> PathBuf {
-        let o_infix = match &self.inner {
-            Inner::Initial(o_rotation_config, _) => {
-                if o_rotation_config.is_some() {
-                    Some(CURRENT_INFIX)
-                } else {
-                    None
+    // Removes the oldest rotated log files if the free space in the log directory is too low;
+    // checks at most once per second.
+    #[cfg(feature = "free_space")]
+    fn ensure_free_space(&mut self) {
+        if let (Some(min_free_space), Inner::Active(Some(ref mut rotation_state), _)) =
+            (self.config.o_min_free_space, &mut self.inner)
+        {
+            if let Some(checked_at) = rotation_state.o_free_space_checked_at {
+                if checked_at.elapsed() < std::time::Duration::from_secs(1) {
+                    return;
                 }
             }
-            Inner::Active(o_rotation_state, _) => {
-                if o_rotation_state.is_some() {
-                    Some(CURRENT_INFIX)
-                } else {
-                    None
+            rotation_state.o_free_space_checked_at = Some(std::time::Instant::now());
+            match remove_logfiles_for_free_space(
+                min_free_space,
+                rotation_state.naming,
+                &self.config,
+            ) {
+                Ok(0) => {}
+                Ok(no_of_files) => crate::error_channel::report(format_args!(
+                    "free space in the log directory dropped below {} bytes, \
+                     removed the {} oldest rotated log files",
+                    min_free_space, no_of_files
+                )),
+                Err(e) => crate::error_channel::report(format_args!(
+                    "emergency cleanup of log files failed with {}",
+                    e
+                )),
+            }
+        }
+    }
+
+    // Rotates the current log file now; fails if the writer was built without rotation.
+    pub fn rotate_now(&mut self) -> Result<(), FlexiLoggerError> {
+        if let Inner::Initial(_, _) = self.inner {
+            self.initialize()?;
+        }
+        match self.inner {
+            Inner::Active(Some(_), _) => self.rotate(),
+            _ => Err(FlexiLoggerError::RotationConfigConflict(
+                "rotate_now() requires a FileLogWriter with rotation; \
+                 use FileLogWriterBuilder::rotate()"
+                    .to_string(),
+            )),
+        }
+    }
+
+    // Closes the log file and opens it again under its configured path,
+    // e.g. after an external tool like logrotate has moved it away.
+    pub fn reopen(&mut self) -> Result<(), FlexiLoggerError> {
+        if let Inner::Active(ref mut o_rotation_state, ref mut file) = self.inner {
+            file.flush()?;
+            if let Some(ref mut structured_file) = self.o_structured_file {
+                structured_file.flush()?;
+            }
+            if let Some(ref mut sync_state) = self.o_sync {
+                sync_state.sync()?;
+            }
+            // a file that is still there must not be truncated
+            let append = std::mem::replace(&mut self.config.append, true);
+            let result = open_log_file(
+                &self.config,
+                o_rotation_state
+                    .as_ref()
+                    .and_then(|rotation_state| current_infix(rotation_state.naming)),
+            );
+            self.config.append = append;
+            let (log_file, boundaries, created_at, p_path) = result?;
+            *file = log_file;
+            self.o_boundaries = Some(boundaries);
+            self.o_structured_file = open_structured_file(&self.config, &p_path)?;
+            self.o_sync = SyncState::open(self.config.sync_policy, &p_path)?;
+            self.footer_written = false;
+            if let Some(ref mut rotation_state) = o_rotation_state {
+                rotation_state.created_at = created_at;
+                rotation_state.roll_state.reset(&p_path)?;
+                if rotation_state.o_file_id.is_some() {
+                    rotation_state.o_file_id = file_id(&p_path);
                 }
             }
+        }
+        // a writer that has not yet written anything opens its file anyway with the next write
+        Ok(())
+    }
+
+    pub fn current_filename(&self) -> PathBuf {
+        let o_infix = match &self.inner {
+            Inner::Initial(o_rotation_config, _) => o_rotation_config
+                .as_ref()
+                .and_then(|rotation_config| current_infix(rotation_config.naming)),
+            Inner::Active(o_rotation_state, _) => o_rotation_state
+                .as_ref()
+                .and_then(|rotation_state| current_infix(rotation_state.naming)),
         };
         get_filepath(o_infix, &self.config.filename_config)
     }
@@ -339,7 +946,7 @@ impl State {
             let path = get_filepath(
                 o_rotation_state
                     .as_ref()
-                    .map(|_| super::state::CURRENT_INFIX),
+                    .and_then(|rotation_state| current_infix(rotation_state.naming)),
                 &self.config.filename_config,
             );
             let f = File::open(path).unwrap();
@@ -362,17 +969,300 @@ impl State {
         }
     }
 
+    // Determines what the cleanup would do now.
+    pub fn cleanup_candidates(&self) -> Result<Vec<CleanupCandidate>, std::io::Error> {
+        match self.inner {
+            Inner::Initial(Some(ref rotation_config), _) => cleanup_candidates(
+                &rotation_config.cleanup,
+                rotation_config.naming,
+                &self.config,
+            ),
+            Inner::Active(Some(ref rotation_state), _) => {
+                cleanup_candidates(&rotation_state.cleanup, rotation_state.naming, &self.config)
+            }
+            _ => Ok(Vec::new()),
+        }
+    }
+
+    // Returns a receiver that is notified when the pending cleanup work is done;
+    // None if the cleanup is not done in a background thread.
+    pub fn cleanup_ack(&self) -> Option<std::sync::mpsc::Receiver<()>> {
+        match self.inner {
+            Inner::Active(Some(ref rotation_state), _) => rotation_state.cleanup_ack(),
+            _ => None,
+        }
+    }
+
     pub fn shutdown(&mut self) {
         if let Inner::Active(ref mut o_rotation_state, ref mut writer) = self.inner {
             if let Some(ref mut rotation_state) = o_rotation_state {
-                rotation_state.shutdown();
+                rotation_state.shutdown(self.config.cleanup_timeout);
+            }
+            if let Some(ref footer) = self.config.o_footer {
+                if !self.footer_written {
+                    footer
+                        .write(writer, CloseReason::Shutdown, self.config.line_ending)
+                        .unwrap_or_else(|e| {
+                            crate::error_channel::report(format_args!(
+                                "writing the file footer failed with {}",
+                                e
+                            ));
+                        });
+                    self.footer_written = true;
+                }
             }
             writer.flush().ok();
+            if let Some(ref mut structured_file) = self.o_structured_file {
+                structured_file.flush().ok();
+            }
+            if let Some(ref mut sync_state) = self.o_sync {
+                sync_state.sync().unwrap_or_else(|e| {
+                    crate::error_channel::report(format_args!(
+                        "syncing the log file failed with {}",
+                        e
+                    ));
+                });
+            }
+        }
+    }
+}
+
+// The number of separately locked buckets of the per-thread states.
+const NO_OF_BUCKETS: usize = 16;
+
+thread_local! {
+    // Dropped when the thread ends, which lets the state of the thread be evicted.
+    static THREAD_ALIVE: Arc<()> = Arc::new(());
+}
+
+// The states of a FileLogWriter: a single one, or, if a separate file is written
+// for each thread, one per thread.
+pub(crate) enum States {
+    Single(Mutex<State>),
+    PerThread {
+        // the template for the states of new threads
+        template: Mutex<StateTemplate>,
+        // the states of the threads, distributed over separately locked buckets,
+        // so that threads rarely wait for each other
+        buckets: Vec<Mutex<HashMap<ThreadId, ThreadState>>>,
+    },
+}
+
+pub(crate) struct StateTemplate {
+    config: Config,
+    o_rotation_config: Option<RotationConfig>,
+    cleanup_in_background_thread: bool,
+    used_infixes: HashSet<String>,
+}
+
+pub(crate) struct ThreadState {
+    alive: Weak<()>,
+    state: State,
+}
+
+impl States {
+    pub fn per_thread(
+        config: Config,
+        o_rotation_config: Option<RotationConfig>,
+        cleanup_in_background_thread: bool,
+    ) -> Self {
+        Self::PerThread {
+            template: Mutex::new(StateTemplate {
+                config,
+                o_rotation_config,
+                cleanup_in_background_thread,
+                used_infixes: HashSet::new(),
+            }),
+            buckets: (0..NO_OF_BUCKETS)
+                .map(|_| Mutex::new(HashMap::new()))
+                .collect(),
+        }
+    }
+
+    // Calls the function with the state that is responsible for the current thread.
+    pub fn with_current<R, F: FnOnce(&mut State) -> R>(&self, f: F) -> Result<R, FlexiLoggerError> {
+        match self {
+            Self::Single(state) => Ok(f(&mut *lock(state)?)),
+            Self::PerThread { template, buckets } => {
+                let thread = std::thread::current();
+                let bucket = &buckets[bucket_index(thread.id())];
+                if let Some(thread_state) = lock(bucket)?.get_mut(&thread.id()) {
+                    return Ok(f(&mut thread_state.state));
+                }
+
+                // the bucket is not locked while the new state is created
+                let state = lock(template)?.new_state(&thread);
+                self.evict_ended_threads();
+                let mut bucket = lock(bucket)?;
+                let thread_state = bucket.entry(thread.id()).or_insert(ThreadState {
+                    alive: THREAD_ALIVE
+                        .try_with(Arc::downgrade)
+                        .unwrap_or_else(|_| Weak::new()),
+                    state,
+                });
+                Ok(f(&mut thread_state.state))
+            }
         }
     }
+
+    // Calls the function with all states, until it fails.
+    fn try_for_each<F>(&self, mut f: F) -> Result<(), FlexiLoggerError>
+    where
+        F: FnMut(&mut State) -> Result<(), FlexiLoggerError>,
+    {
+        match self {
+            Self::Single(state) => f(&mut *lock(state)?),
+            Self::PerThread { buckets, .. } => {
+                for bucket in buckets {
+                    for thread_state in lock(bucket)?.values_mut() {
+                        f(&mut thread_state.state)?;
+                    }
+                }
+                Ok(())
+            }
+        }
+    }
+
+    // Closes the files of the threads that have ended.
+    fn evict_ended_threads(&self) {
+        if let Self::PerThread { buckets, .. } = self {
+            for bucket in buckets {
+                if let Ok(mut bucket) = bucket.lock() {
+                    bucket.retain(|_, thread_state| {
+                        if thread_state.alive.strong_count() > 0 {
+                            true
+                        } else {
+                            thread_state.state.shutdown();
+                            false
+                        }
+                    });
+                }
+            }
+        }
+    }
+
+    // Flushes all files; is also called regularly by the flusher thread,
+    // and closes on this occasion the files of the threads that have ended.
+    pub fn flush(&self) -> std::io::Result<()> {
+        self.evict_ended_threads();
+        match self.try_for_each(|state| Ok(state.flush()?)) {
+            Err(FlexiLoggerError::OutputIo(e)) => Err(e),
+            Ok(()) | Err(_) => Ok(()),
+        }
+    }
+
+    // Rotates all files.
+    pub fn rotate_now(&self) -> Result<(), FlexiLoggerError> {
+        self.try_for_each(State::rotate_now)
+    }
+
+    // Reopens all files.
+    pub fn reopen(&self) -> Result<(), FlexiLoggerError> {
+        self.try_for_each(State::reopen)
+    }
+
+    // Determines what the cleanup would do now, for all files.
+    pub fn cleanup_candidates(&self) -> Result<Vec<CleanupCandidate>, FlexiLoggerError> {
+        let mut candidates = Vec::new();
+        self.try_for_each(|state| {
+            candidates.extend(state.cleanup_candidates()?);
+            Ok(())
+        })?;
+        Ok(candidates)
+    }
+
+    // Returns receivers that are notified when the pending cleanup work is done.
+    pub fn cleanup_acks(&self) -> Vec<std::sync::mpsc::Receiver<()>> {
+        let mut acks = Vec::new();
+        self.try_for_each(|state| {
+            acks.extend(state.cleanup_ack());
+            Ok(())
+        })
+        .ok();
+        acks
+    }
+
+    pub fn shutdown(&self) {
+        // do nothing in case of poison errors
+        self.try_for_each(|state| {
+            state.shutdown();
+            Ok(())
+        })
+        .ok();
+    }
+}
+
+impl StateTemplate {
+    fn new_state(&mut self, thread: &std::thread::Thread) -> State {
+        let mut config = self.config.clone();
+        config.filename_config.file_basename += &thread_infix(thread, &mut self.used_infixes);
+        #[cfg(feature = "integrity")]
+        {
+            // each thread's files have their own chain
+            if let Some(ref hash_chain) = config.o_hash_chain {
+                config.o_hash_chain = Some(hash_chain.new_chain());
+            }
+        }
+        State::new(
+            config,
+            self.o_rotation_config.clone(),
+            self.cleanup_in_background_thread,
+        )
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> Result<MutexGuard<T>, FlexiLoggerError> {
+    mutex.lock().map_err(|_| FlexiLoggerError::Poison)
+}
+
+fn bucket_index(thread_id: ThreadId) -> usize {
+    let mut hasher = DefaultHasher::new();
+    thread_id.hash(&mut hasher);
+    #[allow(clippy::cast_possible_truncation)]
+    let index = hasher.finish() as usize % NO_OF_BUCKETS;
+    index
+}
+
+// Determines the file name infix for the given thread from its name, or its id;
+// the thread id is added if the name was already used by another thread.
+fn thread_infix(thread: &std::thread::Thread, used_infixes: &mut HashSet<String>) -> String {
+    let id: String = format!("{:?}", thread.id())
+        .chars()
+        .filter(char::is_ascii_digit)
+        .collect();
+    let mut infix = match thread.name() {
+        Some(name) => format!(
+            "_{}",
+            name.chars()
+                .map(|c| if c.is_ascii_alphanumeric() || c == '-' {
+                    c
+                } else {
+                    '_'
+                })
+                .collect::<String>()
+        ),
+        None => format!("_thread-{}", id),
+    };
+    if used_infixes.contains(&infix) {
+        infix = format!("{}-{}", infix, id);
+    }
+    used_infixes.insert(infix.clone());
+    infix
+}
+
+// ENOSPC on unix; ERROR_HANDLE_DISK_FULL and ERROR_DISK_FULL on windows
+fn is_disk_full(e: &std::io::Error) -> bool {
+    #[cfg(unix)]
+    const DISK_FULL_CODES: &[i32] = &[28];
+    #[cfg(windows)]
+    const DISK_FULL_CODES: &[i32] = &[39, 112];
+    #[cfg(not(any(unix, windows)))]
+    const DISK_FULL_CODES: &[i32] = &[];
+    e.raw_os_error()
+        .map_or(false, |code| DISK_FULL_CODES.contains(&code))
 }
 
-fn get_filepath(o_infix: Option<&str>, config: &FilenameConfig) -> PathBuf {
+pub(super) fn get_filepath(o_infix: Option<&str>, config: &FilenameConfig) -> PathBuf {
     let mut s_filename = String::with_capacity(
         config.file_basename.len() + o_infix.map_or(0, str::len) + 1 + config.suffix.len(),
     ) + &config.file_basename;
@@ -386,54 +1276,112 @@ fn get_filepath(o_infix: Option<&str>, config: &FilenameConfig) -> PathBuf {
     p_path
 }
 
+// The path of the link `latest.<suffix>` or `latest_rotated.<suffix>` in the log directory.
+fn latest_link(filename_config: &FilenameConfig, name: &str) -> PathBuf {
+    filename_config
+        .directory
+        .join(format!("{}.{}", name, filename_config.suffix))
+}
+
+fn link_latest_rotated(config: &Config, rotated_path: &Path) {
+    if config.latest_links {
+        self::platform::create_link(
+            &latest_link(&config.filename_config, "latest_rotated"),
+            rotated_path,
+        );
+    }
+}
+
 #[allow(clippy::type_complexity)]
 fn open_log_file(
     config: &Config,
-    with_rotation: bool,
-) -> Result<(Box<dyn Write + Send>, DateTime<Local>, PathBuf), std::io::Error> {
-    let o_infix = if with_rotation {
-        Some(CURRENT_INFIX)
-    } else {
-        None
-    };
+    o_infix: Option<&str>,
+) -> Result<(Box<dyn Write + Send>, RecordBoundaries, Timestamp, PathBuf), std::io::Error> {
     let p_path = get_filepath(o_infix, &config.filename_config);
     if config.print_message {
-        println!("Log is written to {}", &p_path.display());
+        // unlike println!, this does not panic if stdout is closed
+        writeln!(std::io::stdout(), "Log is written to {}", &p_path.display()).ok();
     }
-    if let Some(ref link) = config.o_create_symlink {
-        self::platform::create_symlink_if_possible(link, &p_path);
-    }
-
-    let log_file = OpenOptions::new()
+    // With append, the file is opened with O_APPEND (FILE_APPEND_DATA on windows),
+    // so that the records of several processes that append to the same file don't overwrite
+    // or interleave each other, since every record is written with a single call
+    let mut open_options = OpenOptions::new();
+    open_options
         .write(true)
         .create(true)
         .append(config.append)
-        .truncate(!config.append)
-        .open(&p_path)?;
+        .truncate(!config.append);
+    #[cfg(unix)]
+    {
+        if let Some(mode) = config.o_file_mode {
+            use std::os::unix::fs::OpenOptionsExt;
+            open_options.mode(mode);
+        }
+    }
+    let log_file = open_options.open(&p_path)?;
+    let is_empty = log_file.metadata()?.len() == 0;
+    // a hard link can only be created to an existing file
+    if let Some(ref link) = config.o_create_symlink {
+        self::platform::create_link(link, &p_path);
+    }
+    if config.latest_links {
+        self::platform::create_link(&latest_link(&config.filename_config, "latest"), &p_path);
+    }
 
+    let (log_file, mut boundaries) = RecordBoundaries::track(log_file)?;
     #[allow(clippy::option_if_let_else)]
-    let w: Box<dyn Write + Send> = if let Some(capacity) = config.o_buffersize {
+    let mut w: Box<dyn Write + Send> = if let Some(capacity) = config.o_buffersize {
         Box::new(BufWriter::with_capacity(capacity, log_file))
     } else {
         Box::new(log_file)
     };
-    Ok((w, get_creation_date(&p_path), p_path))
+
+    #[cfg(feature = "integrity")]
+    {
+        if let Some(ref hash_chain) = config.o_hash_chain {
+            w = hash_chain.wrap(w, &p_path, !is_empty)?;
+        }
+    }
+
+    // the header is only written to files that are new, truncated, or still empty
+    if let Some(ref header) = config.o_header {
+        if is_empty {
+            header.write(&mut w, config.line_ending)?;
+            w.flush()?;
+            boundaries.register_flushed();
+        }
+    }
+
+    Ok((w, boundaries, get_creation_date(&p_path), p_path))
+}
+
+fn open_structured_file(
+    config: &Config,
+    log_file: &Path,
+) -> Result<Option<Box<dyn Write + Send>>, std::io::Error> {
+    if config.structured_file {
+        structured_file::open(config, log_file).map(Some)
+    } else {
+        Ok(None)
+    }
 }
 
-fn get_highest_rotate_idx(filename_config: &FilenameConfig) -> IdxState {
-    match list_of_log_and_compressed_files(filename_config) {
+fn get_highest_rotate_idx(filename_config: &FilenameConfig, naming: Naming) -> IdxState {
+    let marker = number_marker(naming, filename_config);
+    match list_of_log_and_compressed_files(filename_config, naming, false) {
         Err(e) => {
-            eprintln!("[flexi_logger] listing rotated log files failed with {}", e);
+            crate::error_channel::report(format_args!(
+                "listing rotated log files failed with {}",
+                e
+            ));
             IdxState::Start // hope and pray ...??
         }
         Ok(files) => {
             let mut highest_idx = IdxState::Start;
             for file in files {
-                let filename = file.file_stem().unwrap(/*ok*/).to_string_lossy();
-                let mut it = filename.rsplit("_r");
-                match it.next() {
-                    Some(next) => {
-                        let idx: u32 = next.parse().unwrap_or(0);
+                // compressed or encrypted files have further extensions
+                match split_numbered_filename(&file, &marker) {
+                    Some((_, idx, _)) => {
                         highest_idx = match highest_idx {
                             IdxState::Start => IdxState::Idx(idx),
                             IdxState::Idx(prev) => IdxState::Idx(max(prev, idx)),
@@ -448,56 +1396,206 @@ fn get_highest_rotate_idx(filename_config: &FilenameConfig) -> IdxState {
 }
 
 #[allow(clippy::type_complexity)]
+// Lists the rotated files, the youngest first: log files, then compressed files,
+// each followed by their encrypted variants.
+// With Naming::Logrotate, the files are only ordered by their number.
+// With other_discriminants, also the files of other discriminants are listed, if configured;
+// their numbers are unrelated, so all files are ordered by their modification time.
 fn list_of_log_and_compressed_files(
     filename_config: &FilenameConfig,
-) -> std::result::Result<
-    std::iter::Chain<
-        std::iter::Chain<
-            std::vec::IntoIter<std::path::PathBuf>,
-            std::vec::IntoIter<std::path::PathBuf>,
-        >,
-        std::vec::IntoIter<std::path::PathBuf>,
-    >,
-    std::io::Error,
-> {
-    let fn_pattern = String::with_capacity(180)
-        .add(&filename_config.file_basename)
-        .add("_r[0-9]*")
-        .add(".");
-
-    let mut log_pattern = filename_config.directory.clone();
-    log_pattern.push(fn_pattern.clone().add(&filename_config.suffix));
-    let log_pattern = log_pattern.as_os_str().to_string_lossy();
-
-    let mut zip_pattern = filename_config.directory.clone();
-    zip_pattern.push(fn_pattern.clone().add("zip"));
-    let zip_pattern = zip_pattern.as_os_str().to_string_lossy();
-
-    let mut gz_pattern = filename_config.directory.clone();
-    gz_pattern.push(fn_pattern.add("gz"));
-    let gz_pattern = gz_pattern.as_os_str().to_string_lossy();
-
-    Ok(list_of_files(&log_pattern)
-        .chain(list_of_files(&gz_pattern))
-        .chain(list_of_files(&zip_pattern)))
-}
-
-fn list_of_files(pattern: &str) -> std::vec::IntoIter<PathBuf> {
+    naming: Naming,
+    other_discriminants: bool,
+) -> std::result::Result<std::vec::IntoIter<PathBuf>, std::io::Error> {
+    let marker = number_marker(naming, filename_config);
+    let (basename_pattern, o_shared_basename) = match filename_config.o_shared_basename {
+        Some(ref shared_basename) if other_discriminants => (
+            format!("{}*", glob::Pattern::escape(shared_basename)),
+            Some(shared_basename.as_str()),
+        ),
+        _ => (glob::Pattern::escape(&filename_config.file_basename), None),
+    };
+    let mut directory = glob::Pattern::escape(&filename_config.directory.to_string_lossy());
+    directory.push(std::path::MAIN_SEPARATOR);
+    let list = |fn_pattern: String| {
+        list_of_files(
+            &directory.clone().add(&fn_pattern),
+            naming,
+            &marker,
+            o_shared_basename.is_some(),
+        )
+        .filter(move |file| is_rotated_file(file, o_shared_basename, naming, filename_config))
+    };
+    if let Naming::Logrotate = naming {
+        return Ok(list(format!(
+            "{}.{}.[0-9]*",
+            basename_pattern, filename_config.suffix
+        ))
+        .collect::<Vec<PathBuf>>()
+        .into_iter());
+    }
+
+    let infix_pattern = if naming.numbering().is_some() {
+        ".[0-9]*"
+    } else {
+        "_r[0-9]*"
+    };
+    let fn_pattern = basename_pattern.add(infix_pattern).add(".");
+    let log_pattern = fn_pattern.clone().add(&filename_config.suffix);
+    let zip_pattern = fn_pattern.clone().add("zip");
+    let gz_pattern = fn_pattern.add("gz");
+
+    Ok(list(log_pattern.clone())
+        .chain(list(log_pattern.add(".age")))
+        .chain(list(gz_pattern.clone()))
+        .chain(list(gz_pattern.add(".age")))
+        .chain(list(zip_pattern))
+        .collect::<Vec<PathBuf>>()
+        .into_iter())
+}
+
+// Checks if the file has exactly the name that is generated for a rotated file:
+// `<basename>.<number>.<suffix>`, `<basename>_r<timestamp>.<suffix>` with Naming::Timestamps,
+// or `<basename>.<suffix>.<number>` with Naming::Logrotate,
+// optionally followed by `.gz` and `.age`; or `<basename><infix>.zip` of earlier versions.
+// With a shared basename, the basename can contain any discriminant.
+// With strict_cleanup, the infix must have the format that the naming generates.
+fn is_rotated_file(
+    file: &Path,
+    o_shared_basename: Option<&str>,
+    naming: Naming,
+    filename_config: &FilenameConfig,
+) -> bool {
+    let filename = match file.file_name() {
+        Some(filename) => filename.to_string_lossy(),
+        None => return false,
+    };
+    let dot_suffix = format!(".{}", filename_config.suffix);
+    let name = filename.strip_suffix(".age").unwrap_or(&filename);
+    // the numbers of Naming::Logrotate are the only distinction from other files,
+    // so they are always checked
+    let (o_head_and_infix, check_infix) = if let Naming::Logrotate = naming {
+        let name = name.strip_suffix(".gz").unwrap_or(name);
+        let o_head_and_infix = name.rfind('.').and_then(|index| {
+            name[..index]
+                .strip_suffix(&dot_suffix)
+                .map(|head| (head, &name[index + 1..]))
+        });
+        (o_head_and_infix, true)
+    } else {
+        let o_name = name.strip_suffix(".zip").or_else(|| {
+            name.strip_suffix(".gz")
+                .unwrap_or(name)
+                .strip_suffix(&dot_suffix)
+        });
+        let marker = if naming.numbering().is_some() {
+            "."
+        } else {
+            "_r"
+        };
+        let o_head_and_infix = o_name.and_then(|name| {
+            name.rfind(marker)
+                .map(|index| (&name[..index], &name[index + marker.len()..]))
+        });
+        (o_head_and_infix, filename_config.strict_cleanup)
+    };
+    match o_head_and_infix {
+        Some((head, infix)) => {
+            // a discriminant is separated by an underscore, so that the files of other
+            // programs whose basename only starts with the shared basename are not matched
+            let basename_matches = match o_shared_basename {
+                Some(shared_basename) => head
+                    .strip_prefix(shared_basename)
+                    .map_or(false, |rest| rest.is_empty() || rest.starts_with('_')),
+                None => head == filename_config.file_basename,
+            };
+            let infix_matches = if check_infix {
+                is_generated_infix(infix, naming)
+            } else {
+                infix.starts_with(|c: char| c.is_ascii_digit())
+            };
+            basename_matches && infix_matches
+        }
+        None => false,
+    }
+}
+
+// Checks if the infix of a rotated file name has the format that the naming generates,
+// e.g. `00042`, or `2021-01-14_08-56-52` with an optional `.restart-0001`.
+fn is_generated_infix(infix: &str, naming: Naming) -> bool {
+    let is_number = |s: &str| !s.is_empty() && s.chars().all(|c| c.is_ascii_digit());
+    match naming.numbering() {
+        Some((width, _, _)) => is_number(infix) && infix.len() >= width,
+        None => {
+            let (timestamp, restart) = infix.split_at(std::cmp::min(19, infix.len()));
+            timestamp.len() == 19
+                && timestamp.char_indices().all(|(index, c)| match index {
+                    4 | 7 | 13 | 16 => c == '-',
+                    10 => c == '_',
+                    _ => c.is_ascii_digit(),
+                })
+                && (restart.is_empty()
+                    || restart.strip_prefix(".restart-").map_or(false, is_number))
+        }
+    }
+}
+
+// Lists the files that match the pattern, the youngest first.
+fn list_of_files(
+    pattern: &str,
+    naming: Naming,
+    marker: &str,
+    by_modification: bool,
+) -> std::vec::IntoIter<PathBuf> {
     let mut log_files: Vec<PathBuf> = glob::glob(pattern)
         .unwrap(/* failure should be impossible */)
         .filter_map(Result::ok)
         .collect();
-    log_files.reverse();
+    if by_modification {
+        log_files.sort_by_key(|file| {
+            std::fs::metadata(file)
+                .and_then(|metadata| metadata.modified())
+                .unwrap_or(std::time::UNIX_EPOCH)
+        });
+        log_files.reverse();
+        return log_files.into_iter();
+    }
+    match naming.numbering() {
+        // numbers with different widths must not be compared as strings
+        Some((_, _, order)) => {
+            log_files
+                .sort_by_key(|file| split_numbered_filename(file, marker).map(|(_, idx, _)| idx));
+            if order == NumberOrder::Ascending {
+                log_files.reverse();
+            }
+        }
+        None => log_files.reverse(),
+    }
     log_files.into_iter()
 }
 
+// Applies the cleanup to the rotated files of earlier program runs when the writer is built,
+// and not only when the first record is written.
+pub(crate) fn cleanup_at_startup(config: &Config, rotation_config: &RotationConfig) {
+    if rotation_config.cleanup.do_cleanup() {
+        remove_or_compress_too_old_logfiles_impl(
+            &rotation_config.cleanup,
+            rotation_config.naming,
+            config,
+        )
+        .unwrap_or_else(|e| {
+            crate::error_channel::report(format_args!("cleanup of log files failed with {}", e));
+        });
+    }
+}
+
 fn remove_or_compress_too_old_logfiles(
     o_cleanup_thread_handle: &Option<CleanupThreadHandle>,
     cleanup_config: &Cleanup,
-    filename_config: &FilenameConfig,
+    naming: Naming,
+    config: &Config,
 ) -> Result<(), std::io::Error> {
-    o_cleanup_thread_handle.as_ref().map_or(
-        remove_or_compress_too_old_logfiles_impl(cleanup_config, filename_config),
+    o_cleanup_thread_handle.as_ref().map_or_else(
+        || remove_or_compress_too_old_logfiles_impl(cleanup_config, naming, config),
         |cleanup_thread_handle| {
             cleanup_thread_handle
                 .sender
@@ -508,15 +1606,158 @@ fn remove_or_compress_too_old_logfiles(
     )
 }
 
+// Moves a rotated log file, with its companion files, into the given directory.
+// Files that are already there are never overwritten; if the name is taken,
+// a number is appended to the first part of the name, e.g. `foo-1.r00003.log`.
+fn move_logfile(file: &Path, directory: &Path, config: &Config) -> Result<(), std::io::Error> {
+    std::fs::create_dir_all(directory)?;
+    let file_name = file
+        .file_name()
+        .unwrap(/* is a listed file */)
+        .to_string_lossy()
+        .to_string();
+    let mut number = 0_usize;
+    let moved_file = loop {
+        let candidate = if number == 0 {
+            directory.join(&file_name)
+        } else {
+            let (first, rest) = match file_name.find('.') {
+                Some(idx) => file_name.split_at(idx),
+                None => (file_name.as_str(), ""),
+            };
+            directory.join(format!("{}-{}{}", first, number, rest))
+        };
+        number += 1;
+        if companion_files(&candidate, config)
+            .iter()
+            .any(|companion| companion.exists())
+        {
+            continue;
+        }
+        match move_file(file, &candidate) {
+            Ok(()) => break candidate,
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    };
+    for (from, to) in companion_files(file, config)
+        .iter()
+        .zip(companion_files(&moved_file, config).iter())
+    {
+        if let Err(e) = move_file(from, to) {
+            if e.kind() != std::io::ErrorKind::NotFound {
+                return Err(e);
+            }
+        }
+    }
+    notify(
+        &config.o_rotation_callback,
+        &RotationEvent::Moved {
+            file,
+            moved_file: &moved_file,
+        },
+    );
+    Ok(())
+}
+
+// Removes a rotated log file, with its companion files.
+fn remove_logfile(file: &Path, config: &Config) -> Result<(), std::io::Error> {
+    std::fs::remove_file(file)?;
+    #[cfg(feature = "integrity")]
+    hash_chain::remove_sidecar(file)?;
+    if config.structured_file {
+        structured_file::remove(file)?;
+    }
+    notify(
+        &config.o_rotation_callback,
+        &RotationEvent::Removed { file },
+    );
+    Ok(())
+}
+
+// Removes the oldest rotated log files until the free space in the log directory
+// is at least min_free_space, or no rotated log file is left;
+// returns the number of removed files.
+#[cfg(feature = "free_space")]
+fn remove_logfiles_for_free_space(
+    min_free_space: u64,
+    naming: Naming,
+    config: &Config,
+) -> Result<usize, std::io::Error> {
+    let directory = &config.filename_config.directory;
+    if fs2::available_space(directory)? >= min_free_space {
+        return Ok(0);
+    }
+
+    let _o_lock = if config.use_rotation_lock {
+        Some(RotationLock::acquire(&config.filename_config)?)
+    } else {
+        None
+    };
+    let mut files: Vec<(std::time::SystemTime, PathBuf)> =
+        list_of_log_and_compressed_files(&config.filename_config, naming, true)?
+            .map(|file| {
+                let modified = std::fs::metadata(&file)
+                    .and_then(|metadata| metadata.modified())
+                    .unwrap_or(std::time::UNIX_EPOCH);
+                (modified, file)
+            })
+            .collect();
+    files.sort();
+
+    let mut no_of_files = 0;
+    for (_, file) in files {
+        remove_logfile(&file, config)?;
+        no_of_files += 1;
+        if fs2::available_space(directory)? >= min_free_space {
+            break;
+        }
+    }
+    Ok(no_of_files)
+}
+
 fn remove_or_compress_too_old_logfiles_impl(
     cleanup_config: &Cleanup,
-    filename_config: &FilenameConfig,
+    naming: Naming,
+    config: &Config,
 ) -> Result<(), std::io::Error> {
+    let _o_lock = if config.use_rotation_lock {
+        Some(RotationLock::acquire(&config.filename_config)?)
+    } else {
+        None
+    };
+
+    for candidate in cleanup_candidates(cleanup_config, naming, config)? {
+        match candidate {
+            // delete (log or log.gz, possibly encrypted)
+            CleanupCandidate::Remove { file } => remove_logfile(&file, config)?,
+            #[cfg(feature = "compress")]
+            CleanupCandidate::Compress { file } => compress_logfile(&file, naming, config)?,
+            #[cfg(feature = "encrypt")]
+            CleanupCandidate::Encrypt { file } => encrypt_logfile(&file, config)?,
+            CleanupCandidate::Move { file, directory } => move_logfile(&file, &directory, config)?,
+            #[allow(unreachable_patterns)]
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+// Determines what the cleanup would do with the rotated files, in the order of execution.
+pub(super) fn cleanup_candidates(
+    cleanup_config: &Cleanup,
+    naming: Naming,
+    config: &Config,
+) -> Result<Vec<CleanupCandidate>, std::io::Error> {
     let (log_limit, compress_limit) = match *cleanup_config {
         Cleanup::Never => {
-            return Ok(());
+            if !config.encrypts() {
+                return Ok(Vec::new());
+            }
+            // only encrypt
+            (usize::MAX, 0)
         }
-        Cleanup::KeepLogFiles(log_limit) => (log_limit, 0),
+        Cleanup::KeepLogFiles(log_limit) | Cleanup::MoveToDirectory(_, log_limit) => (log_limit, 0),
 
         #[cfg(feature = "compress")]
         #[allow(deprecated)]
@@ -530,32 +1771,103 @@ fn remove_or_compress_too_old_logfiles_impl(
         | Cleanup::KeepLogAndZipFiles(log_limit, compress_limit) => (log_limit, compress_limit),
     };
 
-    for (index, file) in list_of_log_and_compressed_files(&filename_config)?.enumerate() {
-        if index >= log_limit + compress_limit {
-            // delete (log or log.gz)
-            std::fs::remove_file(&file)?;
-        } else if index >= log_limit {
-            #[cfg(feature = "compress")]
-            {
-                // compress, if not yet compressed
-                if let Some(extension) = file.extension() {
-                    if extension != "gz" {
-                        let mut old_file = File::open(file.clone())?;
-                        let mut compressed_file = file.clone();
-                        compressed_file.set_extension("log.gz");
-                        let mut gz_encoder = flate2::write::GzEncoder::new(
-                            File::create(compressed_file)?,
-                            flate2::Compression::fast(),
-                        );
-                        std::io::copy(&mut old_file, &mut gz_encoder)?;
-                        gz_encoder.finish()?;
-                        std::fs::remove_file(&file)?;
-                    }
+    let mut candidates = Vec::new();
+    for (index, file) in
+        list_of_log_and_compressed_files(&config.filename_config, naming, true)?.enumerate()
+    {
+        if index >= log_limit.saturating_add(compress_limit) {
+            candidates.push(match *cleanup_config {
+                Cleanup::MoveToDirectory(directory, _) => CleanupCandidate::Move {
+                    file,
+                    directory: directory.to_path_buf(),
+                },
+                _ => CleanupCandidate::Remove { file },
+            });
+            continue;
+        }
+        // the path of the file after compression, for the encryption
+        let mut kept_file = file;
+        // compress, if not yet compressed; encrypted files cannot be compressed anymore
+        if index >= log_limit
+            && kept_file
+                .extension()
+                .map_or(false, |extension| extension != "gz" && extension != "age")
+        {
+            let compressed_file = compressed_path(&kept_file, naming);
+            candidates.push(CleanupCandidate::Compress { file: kept_file });
+            kept_file = compressed_file;
+        }
+        if config.encrypts() && kept_file.extension().map_or(true, |ext| ext != "age") {
+            candidates.push(CleanupCandidate::Encrypt { file: kept_file });
+        }
+    }
+    Ok(candidates)
+}
+
+// The path of the compressed file, e.g. `foo.2.log.gz`, or `foo.log.2.gz`
+// with Naming::Logrotate.
+fn compressed_path(file: &Path, naming: Naming) -> PathBuf {
+    if let Naming::Logrotate = naming {
+        // foo.log.2 -> foo.log.2.gz
+        let mut path = file.as_os_str().to_os_string();
+        path.push(".gz");
+        PathBuf::from(path)
+    } else {
+        file.with_extension("log.gz")
+    }
+}
+
+#[cfg(feature = "compress")]
+fn compress_logfile(file: &Path, naming: Naming, config: &Config) -> Result<(), std::io::Error> {
+    let compressed_file = compressed_path(file, naming);
+    compress_file(file, &compressed_file)?;
+    if config.structured_file {
+        let structured_file = structured_file::path(file);
+        if structured_file.exists() {
+            compress_file(&structured_file, &structured_file::path(&compressed_file))?;
+        }
+    }
+    notify(
+        &config.o_rotation_callback,
+        &RotationEvent::Compressed {
+            log_file: file,
+            compressed_file: &compressed_file,
+        },
+    );
+    Ok(())
+}
+
+#[cfg(feature = "compress")]
+fn compress_file(file: &Path, compressed_file: &Path) -> Result<(), std::io::Error> {
+    let mut old_file = File::open(file)?;
+    let mut gz_encoder =
+        flate2::write::GzEncoder::new(File::create(compressed_file)?, flate2::Compression::fast());
+    std::io::copy(&mut old_file, &mut gz_encoder)?;
+    gz_encoder.finish()?;
+    std::fs::remove_file(file)
+}
+
+#[cfg(feature = "encrypt")]
+fn encrypt_logfile(file: &Path, config: &Config) -> Result<(), std::io::Error> {
+    if let Some(ref encryption) = config.o_encryption {
+        // the file can have been taken away by the callback, e.g. for an upload
+        if file.exists() {
+            let encrypted_file = encryption.encrypt_file(file)?;
+            if config.structured_file {
+                let structured_file = structured_file::path(file);
+                if structured_file.exists() {
+                    encryption.encrypt_file(&structured_file)?;
                 }
             }
+            notify(
+                &config.o_rotation_callback,
+                &RotationEvent::Encrypted {
+                    file,
+                    encrypted_file: &encrypted_file,
+                },
+            );
         }
     }
-
     Ok(())
 }
 
@@ -565,14 +1877,18 @@ fn remove_or_compress_too_old_logfiles_impl(
 // The number is incremented in case of repeated collisions.
 // Cleaning up can leave some restart-files with higher numbers; if we still are in the same
 // second, we need to continue with the restart-incrementing.
+// Returns the path of the rotated file, if there was a file to rotate.
 fn rotate_output_file_to_date(
-    creation_date: &DateTime<Local>,
+    creation_date: &Timestamp,
     config: &Config,
-) -> Result<(), std::io::Error> {
+) -> Result<Option<PathBuf>, std::io::Error> {
     let current_path = get_filepath(Some(CURRENT_INFIX), &config.filename_config);
 
     let mut rotated_path = get_filepath(
-        Some(&creation_date.format("_r%Y-%m-%d_%H-%M-%S").to_string()),
+        Some(&format!(
+            "_r{}",
+            timestamp::format(creation_date, Layout::FileName)
+        )),
         &config.filename_config,
     );
 
@@ -616,11 +1932,18 @@ fn rotate_output_file_to_date(
     }
 
     match std::fs::rename(&current_path, &rotated_path) {
-        Ok(()) => Ok(()),
+        Ok(()) => {
+            #[cfg(feature = "integrity")]
+            hash_chain::rename_sidecar(&current_path, &rotated_path)?;
+            if config.structured_file {
+                structured_file::rename(&current_path, &rotated_path)?;
+            }
+            Ok(Some(rotated_path))
+        }
         Err(e) => {
             if e.kind() == std::io::ErrorKind::NotFound {
                 // current did not exist, so we had nothing to do
-                Ok(())
+                Ok(None)
             } else {
                 Err(e)
             }
@@ -628,36 +1951,118 @@ fn rotate_output_file_to_date(
     }
 }
 
-// Moves the current file to the name with the next rotate_idx and returns the next rotate_idx.
+// Moves the current file to the name with the next rotate_idx and returns the next rotate_idx,
+// and the path of the rotated file, if there was a file to rotate.
+// With NumberOrder::Shifting, the current file gets the start index, after all older
+// numbered files were moved to the next higher index.
 // The current file must be closed already.
 fn rotate_output_file_to_idx(
     idx_state: IdxState,
+    naming: Naming,
     config: &Config,
-) -> Result<IdxState, std::io::Error> {
+) -> Result<(IdxState, Option<PathBuf>), std::io::Error> {
+    let (_, start, order) = naming.numbering().unwrap_or((1, 0, NumberOrder::Ascending));
+    let current_path = get_filepath(current_infix(naming), &config.filename_config);
+    if !current_path.exists() {
+        // current did not exist, so we had nothing to do
+        return Ok((idx_state, None));
+    }
+
     let new_idx = match idx_state {
-        IdxState::Start => 0,
-        IdxState::Idx(idx) => idx + 1,
+        IdxState::Start => start,
+        IdxState::Idx(idx) => max(idx + 1, start),
+    };
+    let rotated_idx = match order {
+        NumberOrder::Ascending => new_idx,
+        NumberOrder::Shifting => {
+            shift_numbered_files(naming, config)?;
+            start
+        }
     };
 
-    match std::fs::rename(
-        get_filepath(Some(CURRENT_INFIX), &config.filename_config),
-        get_filepath(Some(&number_infix(new_idx)), &config.filename_config),
-    ) {
-        Ok(()) => Ok(IdxState::Idx(new_idx)),
-        Err(e) => {
-            if e.kind() == std::io::ErrorKind::NotFound {
-                // current did not exist, so we had nothing to do
-                Ok(idx_state)
-            } else {
-                Err(e)
+    let rotated_path = numbered_filepath(rotated_idx, naming, &config.filename_config);
+    rename_logfile(&current_path, &rotated_path, config)?;
+    Ok((IdxState::Idx(new_idx), Some(rotated_path)))
+}
+
+// Moves all numbered files with an index from start on to the next higher index,
+// the highest first, to make room for a new file with the start index.
+fn shift_numbered_files(naming: Naming, config: &Config) -> Result<(), std::io::Error> {
+    let (width, start, _) = naming.numbering().unwrap_or((1, 0, NumberOrder::Shifting));
+    let marker = number_marker(naming, &config.filename_config);
+    let mut files: Vec<(u32, String, String, PathBuf)> =
+        list_of_log_and_compressed_files(&config.filename_config, naming, false)?
+            .filter_map(|file| {
+                split_numbered_filename(&file, &marker)
+                    .map(|(head, idx, rest)| (idx, head, rest, file))
+            })
+            .filter(|(idx, _, _, _)| *idx >= start)
+            .collect();
+    // the highest first, so that no file is overwritten
+    files.sort_by(|a, b| b.0.cmp(&a.0));
+    for (idx, head, rest, file) in files {
+        let shifted_file = file.with_file_name(format!(
+            "{}{:0>width$}{}",
+            head,
+            idx + 1,
+            rest,
+            width = width
+        ));
+        rename_logfile(&file, &shifted_file, config)?;
+    }
+    Ok(())
+}
+
+// Renames a log file, with its companion files.
+fn rename_logfile(from: &Path, to: &Path, config: &Config) -> Result<(), std::io::Error> {
+    std::fs::rename(from, to)?;
+    #[cfg(feature = "integrity")]
+    hash_chain::rename_sidecar(from, to)?;
+    if config.structured_file {
+        structured_file::rename(from, to)?;
+    }
+    Ok(())
+}
+
+// The paths of the files that accompany a log file.
+fn companion_files(log_file: &Path, config: &Config) -> Vec<PathBuf> {
+    let mut companions = Vec::new();
+    if config.structured_file {
+        companions.push(structured_file::path(log_file));
+    }
+    #[cfg(feature = "integrity")]
+    companions.push(hash_chain::sidecar_path(log_file));
+    companions
+}
+
+// Moves a file without ever overwriting the target:
+// a hard link is created atomically or fails if the target exists;
+// where hard links are not possible, e.g. across file systems, the file is copied.
+fn move_file(from: &Path, to: &Path) -> Result<(), std::io::Error> {
+    match std::fs::hard_link(from, to) {
+        Ok(()) => {}
+        Err(e)
+            if e.kind() == std::io::ErrorKind::AlreadyExists
+                || e.kind() == std::io::ErrorKind::NotFound =>
+        {
+            return Err(e);
+        }
+        Err(_) => {
+            let mut source = File::open(from)?;
+            let mut target = OpenOptions::new().write(true).create_new(true).open(to)?;
+            if let Err(e) = std::io::copy(&mut source, &mut target).and_then(|_| target.sync_all())
+            {
+                std::fs::remove_file(to).ok();
+                return Err(e);
             }
         }
     }
+    std::fs::remove_file(from)
 }
 
 // See documentation of Criterion::Age.
 #[allow(unused_variables)]
-fn get_creation_date(path: &PathBuf) -> DateTime<Local> {
+fn get_creation_date(path: &PathBuf) -> Timestamp {
     // On windows, we know that try_get_creation_date() returns a result, but it is wrong.
     // On linux, we know that try_get_creation_date() returns an error.
     #[cfg(any(target_os = "windows", target_os = "linux"))]
@@ -672,45 +2077,124 @@ fn get_creation_date(path: &PathBuf) -> DateTime<Local> {
     }
 }
 
-fn get_fake_creation_date() -> DateTime<Local> {
-    Local::now()
+fn get_fake_creation_date() -> Timestamp {
+    timestamp::now()
 }
 
 #[cfg(not(any(target_os = "windows", target_os = "linux")))]
-fn try_get_creation_date(path: &PathBuf) -> Result<DateTime<Local>, FlexiLoggerError> {
-    Ok(std::fs::metadata(path)?.created()?.into())
+fn try_get_creation_date(path: &PathBuf) -> Result<Timestamp, FlexiLoggerError> {
+    Ok(timestamp::from_system_time(
+        std::fs::metadata(path)?.created()?,
+    ))
 }
 
 mod platform {
     use std::path::{Path, PathBuf};
 
-    pub fn create_symlink_if_possible(link: &PathBuf, path: &Path) {
-        linux_create_symlink(link, path);
-    }
-
-    #[cfg(target_os = "linux")]
-    fn linux_create_symlink(link: &PathBuf, logfile: &Path) {
+    // Lets the link point to the log file: with a symbolic link, if possible, otherwise
+    // with a hard link (e.g. on windows without the privilege to create symbolic links),
+    // and as the last resort with a pointer file next to the link, which contains the path
+    // of the log file.
+    pub fn create_link(link: &Path, logfile: &Path) {
         if std::fs::symlink_metadata(link).is_ok() {
-            // remove old symlink before creating a new one
+            // remove old link before creating a new one
             if let Err(e) = std::fs::remove_file(link) {
-                eprintln!(
-                    "[flexi_logger] deleting old symlink to log file failed with {:?}",
+                crate::error_channel::report(format_args!(
+                    "deleting old link to log file failed with {:?}",
                     e
-                );
+                ));
             }
         }
 
-        // create new symlink
-        if let Err(e) = std::os::unix::fs::symlink(&logfile, link) {
-            eprintln!(
-                "[flexi_logger] cannot create symlink {:?} for logfile \"{}\" due to {:?}",
+        // a relative target of a symbolic link would be resolved against the link's directory
+        let logfile = match std::env::current_dir() {
+            Ok(dir) => dir.join(logfile),
+            Err(_) => logfile.to_path_buf(),
+        };
+        if symlink(&logfile, link).is_ok() || std::fs::hard_link(&logfile, link).is_ok() {
+            return;
+        }
+        if let Err(e) = std::fs::write(pointer_file(link), logfile.display().to_string()) {
+            crate::error_channel::report(format_args!(
+                "cannot create link {:?} for logfile \"{}\" due to {:?}",
                 link,
                 &logfile.display(),
                 e
-            );
+            ));
         }
     }
 
-    #[cfg(not(target_os = "linux"))]
-    fn linux_create_symlink(_: &PathBuf, _: &Path) {}
+    // The pointer file for `current_run` is `current_run.txt`.
+    fn pointer_file(link: &Path) -> PathBuf {
+        let mut pointer_file = link.as_os_str().to_owned();
+        pointer_file.push(".txt");
+        PathBuf::from(pointer_file)
+    }
+
+    #[cfg(unix)]
+    fn symlink(original: &Path, link: &Path) -> std::io::Result<()> {
+        std::os::unix::fs::symlink(original, link)
+    }
+
+    #[cfg(windows)]
+    fn symlink(original: &Path, link: &Path) -> std::io::Result<()> {
+        std::os::windows::fs::symlink_file(original, link)
+    }
+
+    #[cfg(not(any(unix, windows)))]
+    fn symlink(_original: &Path, _link: &Path) -> std::io::Result<()> {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "symbolic links are not supported",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Config, Inner, State};
+    use std::io::Write;
+    use std::sync::{Arc, Mutex};
+
+    // A log file that fails with ENOSPC (or ERROR_DISK_FULL) while `full` is set.
+    #[derive(Clone, Default)]
+    struct Disk {
+        full: Arc<Mutex<bool>>,
+        content: Arc<Mutex<Vec<u8>>>,
+    }
+    impl Write for Disk {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            if *self.full.lock().unwrap() {
+                #[cfg(unix)]
+                return Err(std::io::Error::from_raw_os_error(28));
+                #[cfg(windows)]
+                return Err(std::io::Error::from_raw_os_error(112));
+            }
+            self.content.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[cfg(any(unix, windows))]
+    #[test]
+    fn test_disk_full() {
+        let disk = Disk::default();
+        let mut state = State::try_new(Config::default(), None, false).unwrap();
+        state.inner = Inner::Active(None, Box::new(disk.clone()));
+
+        state.write_buffer(b"1\n", None).unwrap();
+        *disk.full.lock().unwrap() = true;
+        state.write_buffer(b"2\n", None).unwrap();
+        state.write_buffer(b"3\n", None).unwrap();
+        *disk.full.lock().unwrap() = false;
+        state.write_buffer(b"4\n", None).unwrap();
+
+        assert_eq!(
+            String::from_utf8(disk.content.lock().unwrap().clone()).unwrap(),
+            "1\n[flexi_logger] resumed after 2 dropped records (disk full)\n4\n"
+        );
+    }
 }
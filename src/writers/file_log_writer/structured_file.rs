@@ -0,0 +1,105 @@
+use super::Config;
+use std::fs::OpenOptions;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+pub(crate) const SUFFIX: &str = "jsonl";
+
+// The structured file that accompanies a log file `x.log` is `x.jsonl`;
+// for a file `x.log.3` that was rotated with `Naming::Logrotate`, it is `x.jsonl.3`.
+// The structured files of compressed or encrypted log files are compressed or encrypted,
+// too, e.g. `x.jsonl.gz.age` for `x.log.gz.age`.
+pub(crate) fn path(log_file: &Path) -> PathBuf {
+    let mut path = log_file.to_path_buf();
+    let mut suffixes = Vec::new();
+    while let Some(ext) = path
+        .extension()
+        .filter(|ext| *ext == "gz" || *ext == "age")
+        .map(std::ffi::OsStr::to_os_string)
+    {
+        suffixes.push(ext);
+        path.set_extension("");
+    }
+    match path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_string())
+        .filter(|ext| !ext.is_empty() && ext.chars().all(|c| c.is_ascii_digit()))
+    {
+        Some(number) => {
+            path.set_extension("");
+            path.set_extension(format!("{}.{}", SUFFIX, number));
+        }
+        None => {
+            path.set_extension(SUFFIX);
+        }
+    }
+    let mut path = path.into_os_string();
+    for suffix in suffixes.iter().rev() {
+        path.push(".");
+        path.push(suffix);
+    }
+    PathBuf::from(path)
+}
+
+// Opens the structured file for the given log file, in the same mode as the log file.
+pub(crate) fn open(
+    config: &Config,
+    log_file: &Path,
+) -> Result<Box<dyn Write + Send>, std::io::Error> {
+    let file = OpenOptions::new()
+        .write(true)
+        .create(true)
+        .append(config.append)
+        .truncate(!config.append)
+        .open(path(log_file))?;
+    Ok(match config.o_buffersize {
+        Some(capacity) => Box::new(BufWriter::with_capacity(capacity, file)),
+        None => Box::new(file),
+    })
+}
+
+// Renames the structured file along with its log file.
+pub(crate) fn rename(from: &Path, to: &Path) -> Result<(), std::io::Error> {
+    match std::fs::rename(path(from), path(to)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+// Removes the structured file of a removed log file.
+pub(crate) fn remove(log_file: &Path) -> Result<(), std::io::Error> {
+    match std::fs::remove_file(path(log_file)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e),
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::path;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn test_path() {
+        assert_eq!(
+            path(Path::new("logs/x.1.log")),
+            PathBuf::from("logs/x.1.jsonl")
+        );
+        assert_eq!(
+            path(Path::new("logs/x.1.log.gz")),
+            PathBuf::from("logs/x.1.jsonl.gz")
+        );
+        assert_eq!(
+            path(Path::new("logs/x.1.log.gz.age")),
+            PathBuf::from("logs/x.1.jsonl.gz.age")
+        );
+        assert_eq!(
+            path(Path::new("logs/x_r2021-01-14_10-00-00.restart-0001.log")),
+            PathBuf::from("logs/x_r2021-01-14_10-00-00.restart-0001.jsonl")
+        );
+        assert_eq!(
+            path(Path::new("logs/x.log.3.gz")),
+            PathBuf::from("logs/x.jsonl.3.gz")
+        );
+    }
+}
@@ -0,0 +1,92 @@
+use crate::SyncPolicy;
+use std::fs::{File, OpenOptions};
+use std::path::Path;
+use std::time::Instant;
+
+// Keeps track of the data that was written to the log file, but not yet synced
+// to the storage device, and decides, according to the sync policy, when to sync.
+pub(crate) struct SyncState {
+    policy: SyncPolicy,
+    // a second handle to the log file, which allows syncing it
+    // regardless of the writers that are stacked on the first handle
+    file: File,
+    unsynced_bytes: u64,
+    last_sync: Instant,
+}
+impl SyncState {
+    // Returns None with SyncPolicy::Never.
+    pub fn open(policy: SyncPolicy, log_file: &Path) -> std::io::Result<Option<Self>> {
+        if policy == SyncPolicy::Never {
+            return Ok(None);
+        }
+        // opening for writing is required for syncing on windows; the file is not modified
+        Ok(Some(Self {
+            policy,
+            file: OpenOptions::new().write(true).open(log_file)?,
+            unsynced_bytes: 0,
+            last_sync: Instant::now(),
+        }))
+    }
+
+    // Registers the written bytes and returns true if the file needs to be synced now.
+    pub fn register(&mut self, written: usize) -> bool {
+        self.unsynced_bytes += written as u64;
+        self.unsynced_bytes > 0
+            && match self.policy {
+                SyncPolicy::EveryRecord => true,
+                SyncPolicy::EveryNBytes(n) => self.unsynced_bytes >= n,
+                SyncPolicy::Interval(interval) => self.last_sync.elapsed() >= interval,
+                SyncPolicy::Never => false,
+            }
+    }
+
+    // Syncs the file, if something was written since the last sync;
+    // the data must have been flushed to the file before.
+    pub fn sync(&mut self) -> std::io::Result<()> {
+        if self.unsynced_bytes > 0 {
+            self.file.sync_data()?;
+            self.unsynced_bytes = 0;
+            self.last_sync = Instant::now();
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::SyncState;
+    use crate::SyncPolicy;
+    use std::time::Duration;
+
+    #[test]
+    fn sync_policies() {
+        std::fs::create_dir_all("log_files/sync_state").unwrap();
+        let path = std::path::Path::new("log_files/sync_state/sync_state.log");
+        std::fs::write(path, b"").unwrap();
+
+        assert!(SyncState::open(SyncPolicy::Never, path).unwrap().is_none());
+
+        let mut sync_state = SyncState::open(SyncPolicy::EveryRecord, path)
+            .unwrap()
+            .unwrap();
+        assert!(!sync_state.register(0));
+        assert!(sync_state.register(10));
+        sync_state.sync().unwrap();
+        assert!(!sync_state.register(0));
+
+        let mut sync_state = SyncState::open(SyncPolicy::EveryNBytes(100), path)
+            .unwrap()
+            .unwrap();
+        assert!(!sync_state.register(60));
+        assert!(sync_state.register(60));
+        sync_state.sync().unwrap();
+        assert!(!sync_state.register(60));
+
+        let mut sync_state = SyncState::open(SyncPolicy::Interval(Duration::from_millis(20)), path)
+            .unwrap()
+            .unwrap();
+        assert!(!sync_state.register(10));
+        std::thread::sleep(Duration::from_millis(30));
+        assert!(sync_state.register(10));
+    }
+}
@@ -0,0 +1,144 @@
+use crate::deferred_now::DeferredNow;
+use crate::writers::log_writer::LogWriter;
+use crate::FormatFunction;
+use std::io::Result as IoResult;
+
+/// Signature of a user-defined filter for [`FilteredWriter`]; records for which
+/// it returns `false` are not written.
+pub type RecordFilter = Box<dyn Fn(&log::Record) -> bool + Send + Sync>;
+
+/// A `LogWriter` that applies filters before it delegates the records to an inner writer.
+///
+/// This allows per-destination filtering around any existing writer,
+/// e.g. to write only the records of some modules to a [`FileLogWriter`](crate::writers::FileLogWriter)
+/// within a [`TeeWriter`](crate::writers::TeeWriter).
+///
+/// A record is written only if it passes all configured filters:
+///
+/// * the level threshold, see [`level_threshold`](crate::writers::FilteredWriter::level_threshold)
+/// * the module prefixes, see [`module_prefixes`](crate::writers::FilteredWriter::module_prefixes)
+/// * the user-defined filter, see [`filter`](crate::writers::FilteredWriter::filter)
+///
+/// ## Example
+///
+/// ```rust
+/// use flexi_logger::writers::{FileLogWriter, FilteredWriter};
+///
+/// let db_writer = FilteredWriter::new(Box::new(
+///     FileLogWriter::builder()
+///         .directory("log_files")
+///         .discriminant("db")
+///         .try_build()
+///         .unwrap(),
+/// ))
+/// .level_threshold(log::LevelFilter::Debug)
+/// .module_prefixes(vec!["my_app::db".to_string()])
+/// .filter(Box::new(|record| !record.args().to_string().contains("password")));
+/// ```
+pub struct FilteredWriter {
+    inner: Box<dyn LogWriter>,
+    max_log_level: log::LevelFilter,
+    module_prefixes: Vec<String>,
+    o_filter: Option<RecordFilter>,
+}
+impl FilteredWriter {
+    /// Wraps the given writer, initially without any filters.
+    #[must_use]
+    pub fn new(inner: Box<dyn LogWriter>) -> Self {
+        Self {
+            inner,
+            max_log_level: log::LevelFilter::Trace,
+            module_prefixes: Vec::new(),
+            o_filter: None,
+        }
+    }
+
+    /// Sets the maximum level of the records that are written.
+    ///
+    /// The inner writer's own maximum level still applies.
+    #[must_use]
+    pub fn level_threshold(mut self, max_log_level: log::LevelFilter) -> Self {
+        self.max_log_level = max_log_level;
+        self
+    }
+
+    /// Restricts the written records to those whose module path (or, if it is missing,
+    /// whose target) is one of the given modules or one of their submodules.
+    ///
+    /// An empty list, which is the default, does not restrict anything.
+    #[must_use]
+    pub fn module_prefixes(mut self, module_prefixes: Vec<String>) -> Self {
+        self.module_prefixes = module_prefixes;
+        self
+    }
+
+    /// Sets a user-defined filter.
+    #[must_use]
+    pub fn filter(mut self, filter: RecordFilter) -> Self {
+        self.o_filter = Some(filter);
+        self
+    }
+
+    fn accepts(&self, record: &log::Record) -> bool {
+        record.level() <= LogWriter::max_log_level(self)
+            && (self.module_prefixes.is_empty() || {
+                let module = record.module_path().unwrap_or_else(|| record.target());
+                self.module_prefixes
+                    .iter()
+                    .any(|prefix| is_module_or_submodule(module, prefix))
+            })
+            && self.o_filter.as_ref().map_or(true, |filter| filter(record))
+    }
+}
+
+impl LogWriter for FilteredWriter {
+    fn write(&self, now: &mut DeferredNow, record: &log::Record) -> IoResult<()> {
+        if self.accepts(record) {
+            self.inner.write(now, record)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn flush(&self) -> IoResult<()> {
+        self.inner.flush()
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        std::cmp::min(self.max_log_level, self.inner.max_log_level())
+    }
+
+    fn format(&mut self, format: FormatFunction) {
+        self.inner.format(format);
+    }
+
+    fn health_check(&self) -> IoResult<()> {
+        self.inner.health_check()
+    }
+
+    fn shutdown(&self) {
+        self.inner.shutdown();
+    }
+
+    fn validate_logs(&self, expected: &[(&'static str, &'static str, &'static str)]) {
+        self.inner.validate_logs(expected);
+    }
+}
+
+fn is_module_or_submodule(module: &str, prefix: &str) -> bool {
+    module.starts_with(prefix)
+        && (module.len() == prefix.len() || module[prefix.len()..].starts_with("::"))
+}
+
+#[cfg(test)]
+mod test {
+    use super::is_module_or_submodule;
+
+    #[test]
+    fn test_is_module_or_submodule() {
+        assert!(is_module_or_submodule("my_app::db", "my_app::db"));
+        assert!(is_module_or_submodule("my_app::db::pool", "my_app::db"));
+        assert!(!is_module_or_submodule("my_app::dbx", "my_app::db"));
+        assert!(!is_module_or_submodule("my_app", "my_app::db"));
+    }
+}
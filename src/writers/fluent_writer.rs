@@ -0,0 +1,548 @@
+use crate::deferred_now::DeferredNow;
+use crate::writers::log_writer::LogWriter;
+use crate::FlexiLoggerError;
+use std::collections::VecDeque;
+use std::io::{Result as IoResult, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+const DEFAULT_MAX_BUFFERED_RECORDS: usize = 10_000;
+const DEFAULT_RECONNECT_INTERVAL: Duration = Duration::from_secs(1);
+// avoids that the background thread spins with a reconnect interval of zero
+const MIN_RETRY_INTERVAL: Duration = Duration::from_millis(10);
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+const FLUSH_TIMEOUT: Duration = Duration::from_secs(10);
+// the pending events are sent in messages of at most this many entries
+const MAX_ENTRIES_PER_MESSAGE: usize = 1000;
+
+/// A `LogWriter` that ships the log records to a Fluentd or fluent-bit instance,
+/// using the [Forward protocol](https://github.com/fluent/fluentd/wiki/Forward-Protocol-Specification-v1)
+/// (msgpack over TCP).
+///
+/// Only available with optional crate feature `fluent_writer`.
+///
+/// Each log record is sent as an event with the configured tag, an `EventTime` timestamp,
+/// and a record with the fields `level`, `target`, `module_path`, `file`, `line`, and `message`
+/// (the optional fields are omitted if they are not set).
+///
+/// The events are encoded in the logging thread, and sent by a background thread,
+/// so that logging does not wait for the network.
+/// If the receiver is not reachable, e.g. because a sidecar restarts,
+/// the events are kept in memory and sent after the connection was re-established.
+/// The number of kept events is bounded, see
+/// [`FluentWriterBuilder::max_buffered_records`](crate::writers::FluentWriterBuilder::max_buffered_records);
+/// if the limit is exceeded, the oldest events are dropped.
+/// Since the writer does not request acknowledgements, events that were sent on a connection
+/// that broke can get lost.
+///
+/// See the [writers](crate::writers) for guidance how to use additional log writers.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use flexi_logger::writers::FluentWriter;
+///
+/// let fluent_writer = FluentWriter::builder("localhost:24224")
+///     .tag("my_app")
+///     .try_build()
+///     .unwrap();
+/// ```
+pub struct FluentWriter {
+    max_log_level: log::LevelFilter,
+    // shared with the background thread, which is notified via the condvar
+    shared: Arc<(Mutex<Shared>, Condvar)>,
+    o_join_handle: Mutex<Option<JoinHandle<()>>>,
+}
+impl FluentWriter {
+    /// Instantiates a builder for a `FluentWriter` that sends to the given address,
+    /// e.g. `"localhost:24224"`.
+    #[must_use]
+    pub fn builder<S: Into<String>>(address: S) -> FluentWriterBuilder {
+        FluentWriterBuilder {
+            address: address.into(),
+            tag: String::from("flexi_logger"),
+            max_log_level: log::LevelFilter::Trace,
+            max_buffered_records: DEFAULT_MAX_BUFFERED_RECORDS,
+            reconnect_interval: DEFAULT_RECONNECT_INTERVAL,
+        }
+    }
+
+    fn lock_shared(&self) -> MutexGuard<Shared> {
+        self.shared
+            .0
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl LogWriter for FluentWriter {
+    fn write(&self, now: &mut DeferredNow, record: &log::Record) -> IoResult<()> {
+        let mut entry = Vec::with_capacity(200);
+        encode_entry(&mut entry, now, record);
+        self.lock_shared().push_back(entry);
+        self.shared.1.notify_all();
+        Ok(())
+    }
+
+    // Lets the background thread try to send the pending events,
+    // and waits until it has done so, but not longer than ten seconds.
+    fn flush(&self) -> IoResult<()> {
+        let mut shared = self.lock_shared();
+        if shared.pending.is_empty() {
+            return Ok(());
+        }
+        let round = shared.rounds;
+        shared.flush_requested = true;
+        self.shared.1.notify_all();
+        let deadline = Instant::now() + FLUSH_TIMEOUT;
+        while shared.rounds == round {
+            let now = Instant::now();
+            if now >= deadline {
+                break;
+            }
+            shared = self
+                .shared
+                .1
+                .wait_timeout(shared, deadline - now)
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+                .0;
+        }
+        Ok(())
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.max_log_level
+    }
+
+    fn shutdown(&self) {
+        self.lock_shared().shutdown = true;
+        self.shared.1.notify_all();
+        if let Ok(mut o_join_handle) = self.o_join_handle.lock() {
+            if let Some(join_handle) = o_join_handle.take() {
+                join_handle.join().ok();
+            }
+        }
+    }
+}
+
+impl Drop for FluentWriter {
+    fn drop(&mut self) {
+        // lets the background thread end, if shutdown() was not called
+        self.lock_shared().shutdown = true;
+        self.shared.1.notify_all();
+    }
+}
+
+/// Builder for [`FluentWriter`].
+#[allow(clippy::module_name_repetitions)]
+pub struct FluentWriterBuilder {
+    address: String,
+    tag: String,
+    max_log_level: log::LevelFilter,
+    max_buffered_records: usize,
+    reconnect_interval: Duration,
+}
+impl FluentWriterBuilder {
+    /// Sets the tag of the events, which is used by the receiver for routing.
+    ///
+    /// Defaults to `"flexi_logger"`.
+    #[must_use]
+    pub fn tag<S: Into<String>>(mut self, tag: S) -> Self {
+        self.tag = tag.into();
+        self
+    }
+
+    /// Sets the maximum log level that is to be written.
+    ///
+    /// Defaults to `log::LevelFilter::Trace`.
+    #[must_use]
+    pub fn max_log_level(mut self, max_log_level: log::LevelFilter) -> Self {
+        self.max_log_level = max_log_level;
+        self
+    }
+
+    /// Sets the maximum number of events that are kept in memory
+    /// while the receiver is not reachable.
+    ///
+    /// Defaults to 10,000.
+    #[must_use]
+    pub fn max_buffered_records(mut self, max_buffered_records: usize) -> Self {
+        self.max_buffered_records = max_buffered_records.max(1);
+        self
+    }
+
+    /// Sets the minimal time between two attempts to connect to the receiver.
+    ///
+    /// Defaults to one second.
+    #[must_use]
+    pub fn reconnect_interval(mut self, reconnect_interval: Duration) -> Self {
+        self.reconnect_interval = reconnect_interval;
+        self
+    }
+
+    /// Spawns the background thread and produces the `FluentWriter`.
+    ///
+    /// The connection is established lazily, so the receiver does not need to be
+    /// reachable yet.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::OutputIo` if the address cannot be resolved,
+    /// or if the background thread cannot be spawned.
+    pub fn try_build(self) -> Result<FluentWriter, FlexiLoggerError> {
+        self.address.to_socket_addrs()?;
+        let shared = Arc::new((
+            Mutex::new(Shared {
+                pending: VecDeque::new(),
+                max_buffered_records: self.max_buffered_records,
+                dropped: 0,
+                rounds: 0,
+                flush_requested: false,
+                shutdown: false,
+            }),
+            Condvar::new(),
+        ));
+        let mut connection = Connection {
+            address: self.address,
+            tag: self.tag,
+            o_stream: None,
+            o_last_failed_attempt: None,
+            reconnect_interval: self.reconnect_interval.max(MIN_RETRY_INTERVAL),
+        };
+        let shared_for_thread = Arc::clone(&shared);
+        let join_handle = std::thread::Builder::new()
+            .name("flexi_logger-fluent_writer".to_string())
+            .spawn(move || connection.run(&shared_for_thread))?;
+        Ok(FluentWriter {
+            max_log_level: self.max_log_level,
+            shared,
+            o_join_handle: Mutex::new(Some(join_handle)),
+        })
+    }
+}
+
+// The state that the logging threads share with the background thread.
+struct Shared {
+    // encoded entries that were not yet sent
+    pending: VecDeque<Vec<u8>>,
+    max_buffered_records: usize,
+    dropped: usize,
+    // is incremented whenever the background thread has tried to send the pending entries
+    rounds: u64,
+    flush_requested: bool,
+    shutdown: bool,
+}
+impl Shared {
+    fn push_back(&mut self, entry: Vec<u8>) {
+        self.make_room(1);
+        self.pending.push_back(entry);
+    }
+
+    // Puts the entries that could not be sent back in front of the newer ones.
+    fn push_front(&mut self, entries: Vec<Vec<u8>>) {
+        for entry in entries.into_iter().rev() {
+            self.pending.push_front(entry);
+        }
+        self.make_room(0);
+    }
+
+    // Drops the oldest entries, if necessary, to keep the limit.
+    fn make_room(&mut self, no_of_new_entries: usize) {
+        while !self.pending.is_empty()
+            && self.pending.len() + no_of_new_entries > self.max_buffered_records
+        {
+            self.pending.pop_front();
+            self.dropped += 1;
+            crate::stats::count_dropped_records(1);
+        }
+    }
+}
+
+// The connection to the receiver, which is owned by the background thread.
+struct Connection {
+    address: String,
+    tag: String,
+    o_stream: Option<TcpStream>,
+    o_last_failed_attempt: Option<Instant>,
+    reconnect_interval: Duration,
+}
+impl Connection {
+    fn run(&mut self, shared: &(Mutex<Shared>, Condvar)) {
+        let (mutex, condvar) = shared;
+        let lock = move || {
+            mutex
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner)
+        };
+        loop {
+            let (entries, dropped, shutdown) = {
+                let mut guard = lock();
+                loop {
+                    if guard.shutdown || guard.flush_requested {
+                        break;
+                    }
+                    if guard.pending.is_empty() {
+                        guard = condvar
+                            .wait(guard)
+                            .unwrap_or_else(std::sync::PoisonError::into_inner);
+                    } else {
+                        match self.time_until_reconnect() {
+                            None => break,
+                            Some(wait) => {
+                                guard = condvar
+                                    .wait_timeout(guard, wait)
+                                    .unwrap_or_else(std::sync::PoisonError::into_inner)
+                                    .0;
+                            }
+                        }
+                    }
+                }
+                guard.flush_requested = false;
+                let entries: Vec<Vec<u8>> = guard.pending.drain(..).collect();
+                let dropped = std::mem::take(&mut guard.dropped);
+                (entries, dropped, guard.shutdown)
+            };
+            if dropped > 0 {
+                crate::error_channel::report(format_args!(
+                    "FluentWriter dropped {} records while the receiver was not reachable",
+                    dropped
+                ));
+            }
+
+            // the lock is not held while sending
+            let no_of_sent = self.send(&entries);
+            let unsent: Vec<Vec<u8>> = entries.into_iter().skip(no_of_sent).collect();
+
+            let mut guard = lock();
+            if shutdown {
+                if !unsent.is_empty() || !guard.pending.is_empty() {
+                    crate::error_channel::report(format_args!(
+                        "FluentWriter could not send {} records before shutdown",
+                        unsent.len() + guard.pending.len()
+                    ));
+                }
+                guard.rounds += 1;
+                condvar.notify_all();
+                return;
+            }
+            guard.push_front(unsent);
+            guard.rounds += 1;
+            condvar.notify_all();
+        }
+    }
+
+    // Returns how long to wait before the next attempt to connect, if the last one failed.
+    fn time_until_reconnect(&self) -> Option<Duration> {
+        if self.o_stream.is_some() {
+            return None;
+        }
+        self.o_last_failed_attempt.and_then(|last_failed_attempt| {
+            let elapsed = last_failed_attempt.elapsed();
+            if elapsed < self.reconnect_interval {
+                Some(self.reconnect_interval - elapsed)
+            } else {
+                None
+            }
+        })
+    }
+
+    // Sends the entries in messages in Forward mode, and returns the number of entries
+    // that were sent; with a broken connection, the remaining entries can be sent again later.
+    fn send(&mut self, entries: &[Vec<u8>]) -> usize {
+        if entries.is_empty() || !self.connect() {
+            return 0;
+        }
+        let mut no_of_sent = 0;
+        for chunk in entries.chunks(MAX_ENTRIES_PER_MESSAGE) {
+            let mut message =
+                Vec::with_capacity(self.tag.len() + 10 + chunk.iter().map(Vec::len).sum::<usize>());
+            encode_array_len(&mut message, 2);
+            encode_str(&mut message, &self.tag);
+            encode_array_len(&mut message, chunk.len());
+            for entry in chunk {
+                message.extend_from_slice(entry);
+            }
+            let result = match self.o_stream {
+                Some(ref mut stream) => stream.write_all(&message).and_then(|()| stream.flush()),
+                None => Ok(()),
+            };
+            if let Err(e) = result {
+                crate::error_channel::report(format_args!("FluentWriter lost connection: {}", e));
+                self.o_stream = None;
+                self.o_last_failed_attempt = Some(Instant::now());
+                break;
+            }
+            no_of_sent += chunk.len();
+        }
+        no_of_sent
+    }
+
+    // Ensures there is a connection, unless the last failed attempt is too recent.
+    fn connect(&mut self) -> bool {
+        if self.o_stream.is_some() {
+            return true;
+        }
+        if self.time_until_reconnect().is_some() {
+            return false;
+        }
+        match self.try_connect() {
+            Ok(stream) => {
+                self.o_stream = Some(stream);
+                self.o_last_failed_attempt = None;
+                true
+            }
+            Err(_e) => {
+                self.o_last_failed_attempt = Some(Instant::now());
+                false
+            }
+        }
+    }
+
+    fn try_connect(&self) -> IoResult<TcpStream> {
+        let mut last_error = None;
+        for address in self.address.to_socket_addrs()? {
+            match TcpStream::connect_timeout(&address, CONNECT_TIMEOUT) {
+                Ok(stream) => {
+                    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+                    return Ok(stream);
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(last_error.unwrap_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::NotFound, "address did not resolve")
+        }))
+    }
+}
+
+// Encodes the entry `[time, record]` of a Forward mode message.
+fn encode_entry(buffer: &mut Vec<u8>, now: &mut DeferredNow, record: &log::Record) {
+    let (seconds, nanoseconds) = crate::timestamp::unix_timestamp(now.timestamp());
+    encode_array_len(buffer, 2);
+    #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+    encode_event_time(buffer, seconds as u32, nanoseconds);
+
+    let no_of_fields = 3
+        + usize::from(record.module_path().is_some())
+        + usize::from(record.file().is_some())
+        + usize::from(record.line().is_some());
+    encode_map_len(buffer, no_of_fields);
+    encode_str(buffer, "level");
+    encode_str(buffer, record.level().as_str());
+    encode_str(buffer, "target");
+    encode_str(buffer, record.target());
+    if let Some(module_path) = record.module_path() {
+        encode_str(buffer, "module_path");
+        encode_str(buffer, module_path);
+    }
+    if let Some(file) = record.file() {
+        encode_str(buffer, "file");
+        encode_str(buffer, file);
+    }
+    if let Some(line) = record.line() {
+        encode_str(buffer, "line");
+        encode_uint(buffer, u64::from(line));
+    }
+    encode_str(buffer, "message");
+    encode_str(buffer, &record.args().to_string());
+}
+
+// A minimal msgpack encoder for the few types that the Forward protocol needs.
+
+#[allow(clippy::cast_possible_truncation)]
+fn encode_array_len(buffer: &mut Vec<u8>, len: usize) {
+    if len < 16 {
+        buffer.push(0x90 | len as u8);
+    } else if len <= 0xffff {
+        buffer.push(0xdc);
+        buffer.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        buffer.push(0xdd);
+        buffer.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn encode_map_len(buffer: &mut Vec<u8>, len: usize) {
+    if len < 16 {
+        buffer.push(0x80 | len as u8);
+    } else if len <= 0xffff {
+        buffer.push(0xde);
+        buffer.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        buffer.push(0xdf);
+        buffer.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn encode_str(buffer: &mut Vec<u8>, s: &str) {
+    let len = s.len();
+    if len < 32 {
+        buffer.push(0xa0 | len as u8);
+    } else if len <= 0xff {
+        buffer.push(0xd9);
+        buffer.push(len as u8);
+    } else if len <= 0xffff {
+        buffer.push(0xda);
+        buffer.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        buffer.push(0xdb);
+        buffer.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+    buffer.extend_from_slice(s.as_bytes());
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn encode_uint(buffer: &mut Vec<u8>, value: u64) {
+    if value < 0x80 {
+        buffer.push(value as u8);
+    } else if value <= 0xff {
+        buffer.push(0xcc);
+        buffer.push(value as u8);
+    } else if value <= 0xffff {
+        buffer.push(0xcd);
+        buffer.extend_from_slice(&(value as u16).to_be_bytes());
+    } else if value <= 0xffff_ffff {
+        buffer.push(0xce);
+        buffer.extend_from_slice(&(value as u32).to_be_bytes());
+    } else {
+        buffer.push(0xcf);
+        buffer.extend_from_slice(&value.to_be_bytes());
+    }
+}
+
+// The EventTime extension type of the Forward protocol (fixext 8, type 0).
+fn encode_event_time(buffer: &mut Vec<u8>, seconds: u32, nanoseconds: u32) {
+    buffer.push(0xd7);
+    buffer.push(0x00);
+    buffer.extend_from_slice(&seconds.to_be_bytes());
+    buffer.extend_from_slice(&nanoseconds.to_be_bytes());
+}
+
+#[cfg(test)]
+mod test {
+    use super::{encode_array_len, encode_event_time, encode_map_len, encode_str, encode_uint};
+
+    #[test]
+    fn test_msgpack_encoding() {
+        let mut buffer = Vec::new();
+        encode_array_len(&mut buffer, 2);
+        encode_array_len(&mut buffer, 16);
+        encode_map_len(&mut buffer, 3);
+        encode_str(&mut buffer, "abc");
+        encode_str(&mut buffer, &"x".repeat(40));
+        encode_uint(&mut buffer, 5);
+        encode_uint(&mut buffer, 300);
+        encode_event_time(&mut buffer, 1, 2);
+
+        let mut expected = vec![0x92, 0xdc, 0, 16, 0x83, 0xa3, b'a', b'b', b'c', 0xd9, 40];
+        expected.extend_from_slice("x".repeat(40).as_bytes());
+        expected.extend_from_slice(&[5, 0xcd, 1, 44]);
+        expected.extend_from_slice(&[0xd7, 0, 0, 0, 0, 1, 0, 0, 0, 2]);
+        assert_eq!(buffer, expected);
+    }
+}
@@ -0,0 +1,193 @@
+use crate::deferred_now::DeferredNow;
+use crate::writers::log_writer::LogWriter;
+use crate::{FlushPolicy, FormatFunction};
+use std::io::Result as IoResult;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::{Arc, Mutex, RwLock, RwLockReadGuard, Weak};
+use std::time::Instant;
+
+/// A `LogWriter` that flushes its inner writer according to a [`FlushPolicy`](crate::FlushPolicy).
+///
+/// This allows choosing the trade-off between latency and durability for each writer
+/// individually, e.g. flushing a local file with every record, but a network writer only
+/// every few seconds.
+///
+/// Flush requests, like the ones of [`LoggerHandle::flush`](crate::LoggerHandle::flush)
+/// or of the flusher thread for buffered output, are passed on to the inner writer,
+/// except with `FlushPolicy::OnShutdown`.
+///
+/// ## Example
+///
+/// ```rust
+/// use flexi_logger::writers::{FileLogWriter, FlushingWriter};
+/// use flexi_logger::FlushPolicy;
+///
+/// let audit_writer = FlushingWriter::new(
+///     Box::new(
+///         FileLogWriter::builder()
+///             .directory("log_files")
+///             .discriminant("audit")
+///             .use_buffering(true)
+///             .try_build()
+///             .unwrap(),
+///     ),
+///     FlushPolicy::EveryRecord,
+/// );
+/// ```
+pub struct FlushingWriter {
+    // shared with the flusher thread, for FlushPolicy::Interval
+    inner: Arc<RwLock<Box<dyn LogWriter>>>,
+    policy: FlushPolicy,
+    // the records since the last flush, for FlushPolicy::EveryNRecords
+    unflushed_records: AtomicUsize,
+    // the time of the last flush, for FlushPolicy::Interval
+    last_flush: Mutex<Instant>,
+    // stops the flusher thread, for FlushPolicy::Interval, when dropped
+    _o_stop_flusher: Option<Sender<()>>,
+}
+impl FlushingWriter {
+    /// Wraps the given writer.
+    ///
+    /// With `FlushPolicy::Interval`, a background thread is started that lives as long as
+    /// the `FlushingWriter`.
+    #[must_use]
+    pub fn new(inner: Box<dyn LogWriter>, policy: FlushPolicy) -> Self {
+        let inner = Arc::new(RwLock::new(inner));
+        let o_stop_flusher = match policy {
+            FlushPolicy::Interval(interval) => start_flusher(Arc::downgrade(&inner), interval),
+            _ => None,
+        };
+        Self {
+            inner,
+            policy,
+            unflushed_records: AtomicUsize::new(0),
+            last_flush: Mutex::new(Instant::now()),
+            _o_stop_flusher: o_stop_flusher,
+        }
+    }
+
+    fn inner(&self) -> RwLockReadGuard<'_, Box<dyn LogWriter>> {
+        self.inner
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+
+    // Decides if a record that was just written needs to be flushed.
+    fn flush_is_due(&self) -> bool {
+        match self.policy {
+            FlushPolicy::EveryRecord => true,
+            FlushPolicy::EveryNRecords(n) => {
+                // counting and resetting is one step, so that concurrent records
+                // are neither lost nor trigger several flushes
+                let mut records = self.unflushed_records.load(Ordering::Acquire);
+                loop {
+                    let next = if records + 1 >= n { 0 } else { records + 1 };
+                    match self.unflushed_records.compare_exchange_weak(
+                        records,
+                        next,
+                        Ordering::AcqRel,
+                        Ordering::Acquire,
+                    ) {
+                        Ok(_) => return next == 0,
+                        Err(current) => records = current,
+                    }
+                }
+            }
+            FlushPolicy::Interval(interval) => {
+                let mut last_flush = self
+                    .last_flush
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner);
+                if last_flush.elapsed() >= interval {
+                    *last_flush = Instant::now();
+                    true
+                } else {
+                    false
+                }
+            }
+            FlushPolicy::OnShutdown => false,
+        }
+    }
+}
+
+// Flushes the writer regularly, until the writer or the sender is dropped.
+fn start_flusher(
+    inner: Weak<RwLock<Box<dyn LogWriter>>>,
+    interval: std::time::Duration,
+) -> Option<Sender<()>> {
+    let (sender, receiver): (Sender<()>, Receiver<()>) = channel();
+    std::thread::Builder::new()
+        .name("flexi_logger-flushing_writer".to_string())
+        .stack_size(128)
+        .spawn(move || {
+            while let Err(std::sync::mpsc::RecvTimeoutError::Timeout) =
+                receiver.recv_timeout(interval)
+            {
+                match inner.upgrade() {
+                    Some(inner) => inner
+                        .read()
+                        .unwrap_or_else(std::sync::PoisonError::into_inner)
+                        .flush()
+                        .unwrap_or_else(|e| {
+                            crate::error_channel::report(format_args!(
+                                "FlushingWriter: flushing failed with {}",
+                                e
+                            ));
+                        }),
+                    None => return,
+                }
+            }
+        })
+        .map_err(|e| {
+            crate::error_channel::report(format_args!(
+                "FlushingWriter: the flusher thread could not be started: {}",
+                e
+            ));
+        })
+        .ok()
+        .map(|_| sender)
+}
+
+impl LogWriter for FlushingWriter {
+    fn write(&self, now: &mut DeferredNow, record: &log::Record) -> IoResult<()> {
+        self.inner().write(now, record)?;
+        if self.flush_is_due() {
+            self.inner().flush()
+        } else {
+            Ok(())
+        }
+    }
+
+    fn flush(&self) -> IoResult<()> {
+        match self.policy {
+            FlushPolicy::OnShutdown => Ok(()),
+            _ => self.inner().flush(),
+        }
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.inner().max_log_level()
+    }
+
+    fn format(&mut self, format: FormatFunction) {
+        self.inner
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .format(format);
+    }
+
+    fn health_check(&self) -> IoResult<()> {
+        self.inner().health_check()
+    }
+
+    fn shutdown(&self) {
+        let inner = self.inner();
+        inner.flush().ok();
+        inner.shutdown();
+    }
+
+    fn validate_logs(&self, expected: &[(&'static str, &'static str, &'static str)]) {
+        self.inner().validate_logs(expected);
+    }
+}
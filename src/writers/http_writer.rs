@@ -0,0 +1,436 @@
+use crate::deferred_now::DeferredNow;
+use crate::formats::{format_with_fallback, json_format};
+use crate::writers::log_writer::LogWriter;
+use crate::{FlexiLoggerError, FormatFunction};
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use std::sync::mpsc::{channel, sync_channel, Receiver, RecvTimeoutError, Sender, SyncSender};
+use std::sync::Mutex;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// A `LogWriter` that accumulates the log records and sends them in batches with HTTP POST
+/// requests to a configurable endpoint.
+///
+/// Only available with optional crate feature `http_writer`.
+///
+/// The body of each request contains the batched records as newline-delimited JSON
+/// (`application/x-ndjson`), one record per line, formatted by default with
+/// [`json_format`](crate::json_format).
+/// This fits custom collectors and log shippers that accept ndjson;
+/// for the bulk API of Elasticsearch, e.g., use a format function that writes the
+/// action line (`{"index":{}}`) and a line break before the document.
+///
+/// A batch is sent when it has reached the configured number of records or bytes,
+/// when the configured interval has elapsed, or when the writer is flushed.
+/// Failed requests are retried with exponential backoff, if the failure is
+/// likely temporary (I/O errors, status codes 429 and 5xx);
+/// if all retries fail, the batch is dropped and the loss is reported on stderr.
+///
+/// The sending happens in a background thread, so the logging threads are not blocked by
+/// the HTTP requests. If the background thread falls behind, at most
+/// `max_buffered_records` records are kept in memory; further records are dropped.
+///
+/// See the [writers](crate::writers) for guidance how to use additional log writers.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use flexi_logger::writers::HttpWriter;
+/// use std::time::Duration;
+///
+/// let http_writer = HttpWriter::builder("http://localhost:8080/logs")
+///     .header("Authorization", "Bearer 0123456789")
+///     .max_batch_records(500)
+///     .batch_interval(Duration::from_secs(2))
+///     .try_build()
+///     .unwrap();
+/// ```
+pub struct HttpWriter {
+    format: FormatFunction,
+    max_log_level: log::LevelFilter,
+    sender: Mutex<SyncSender<Message>>,
+    o_join_handle: Mutex<Option<JoinHandle<()>>>,
+}
+impl HttpWriter {
+    /// Instantiates a builder for an `HttpWriter` that posts to the given URL.
+    #[must_use]
+    pub fn builder<S: Into<String>>(url: S) -> HttpWriterBuilder {
+        HttpWriterBuilder {
+            url: url.into(),
+            headers: Vec::new(),
+            format: json_format,
+            max_log_level: log::LevelFilter::Trace,
+            batching: Batching {
+                max_records: 1_000,
+                max_bytes: 1024 * 1024,
+                interval: Duration::from_secs(1),
+                max_retries: 5,
+                initial_backoff: Duration::from_millis(100),
+            },
+            max_buffered_records: 10_000,
+            timeout: Duration::from_secs(10),
+        }
+    }
+
+    fn send(&self, message: Message) -> IoResult<()> {
+        self.sender
+            .lock()
+            .map_err(|_| broken_channel())?
+            .send(message)
+            .map_err(|_| broken_channel())
+    }
+}
+
+impl LogWriter for HttpWriter {
+    fn write(&self, now: &mut DeferredNow, record: &log::Record) -> IoResult<()> {
+        let mut line = Vec::with_capacity(200);
+        format_with_fallback(self.format, &mut line, now, record);
+        line.push(b'\n');
+        match self
+            .sender
+            .lock()
+            .map_err(|_| broken_channel())?
+            .try_send(Message::Record(line))
+        {
+            Ok(()) => Ok(()),
+            Err(std::sync::mpsc::TrySendError::Full(_)) => {
+                crate::stats::count_dropped_records(1);
+                crate::error_channel::report(format_args!(
+                    "HttpWriter is overloaded and drops a record"
+                ));
+                Ok(())
+            }
+            Err(std::sync::mpsc::TrySendError::Disconnected(_)) => Err(broken_channel()),
+        }
+    }
+
+    fn flush(&self) -> IoResult<()> {
+        let (result_sender, result_receiver) = channel();
+        self.send(Message::Flush(result_sender))?;
+        result_receiver.recv().map_err(|_| broken_channel())
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.max_log_level
+    }
+
+    fn format(&mut self, format: FormatFunction) {
+        self.format = format;
+    }
+
+    fn shutdown(&self) {
+        self.send(Message::Shutdown).ok();
+        if let Ok(mut o_join_handle) = self.o_join_handle.lock() {
+            if let Some(join_handle) = o_join_handle.take() {
+                join_handle.join().ok();
+            }
+        }
+    }
+}
+
+/// Builder for [`HttpWriter`].
+///
+/// Only available with feature `http_writer`.
+#[allow(clippy::module_name_repetitions)]
+pub struct HttpWriterBuilder {
+    url: String,
+    headers: Vec<(String, String)>,
+    format: FormatFunction,
+    max_log_level: log::LevelFilter,
+    batching: Batching,
+    max_buffered_records: usize,
+    timeout: Duration,
+}
+impl HttpWriterBuilder {
+    /// Adds a header to the requests, e.g. for authentication.
+    #[must_use]
+    pub fn header<S: Into<String>, T: Into<String>>(mut self, name: S, value: T) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Makes the writer use the given format function for the records.
+    ///
+    /// The format function must produce one line of JSON per record,
+    /// or several lines if the endpoint expects that, like the bulk API of Elasticsearch.
+    ///
+    /// Defaults to [`json_format`](crate::json_format).
+    #[must_use]
+    pub fn format(mut self, format: FormatFunction) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the maximum log level that is to be written.
+    ///
+    /// Defaults to `log::LevelFilter::Trace`.
+    #[must_use]
+    pub fn max_log_level(mut self, max_log_level: log::LevelFilter) -> Self {
+        self.max_log_level = max_log_level;
+        self
+    }
+
+    /// Sets the maximum number of records in a batch.
+    ///
+    /// Defaults to 1,000.
+    #[must_use]
+    pub fn max_batch_records(mut self, max_batch_records: usize) -> Self {
+        self.batching.max_records = max_batch_records.max(1);
+        self
+    }
+
+    /// Sets the size in bytes above which a batch is sent.
+    ///
+    /// Defaults to 1 MB.
+    #[must_use]
+    pub fn max_batch_bytes(mut self, max_batch_bytes: usize) -> Self {
+        self.batching.max_bytes = max_batch_bytes.max(1);
+        self
+    }
+
+    /// Sets the maximum time a record waits in a batch before the batch is sent.
+    ///
+    /// Defaults to one second.
+    #[must_use]
+    pub fn batch_interval(mut self, batch_interval: Duration) -> Self {
+        self.batching.interval = batch_interval;
+        self
+    }
+
+    /// Sets how often a failed request is retried, and the wait time before the first retry,
+    /// which is doubled with every further retry.
+    ///
+    /// Defaults to 5 retries, starting with 100 milliseconds.
+    #[must_use]
+    pub fn retries(mut self, max_retries: usize, initial_backoff: Duration) -> Self {
+        self.batching.max_retries = max_retries;
+        self.batching.initial_backoff = initial_backoff;
+        self
+    }
+
+    /// Sets the maximum number of records that are kept in memory
+    /// while the background thread is busy.
+    ///
+    /// Defaults to 10,000.
+    #[must_use]
+    pub fn max_buffered_records(mut self, max_buffered_records: usize) -> Self {
+        self.max_buffered_records = max_buffered_records.max(1);
+        self
+    }
+
+    /// Sets the timeout for a single request.
+    ///
+    /// Defaults to 10 seconds.
+    #[must_use]
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Spawns the background thread and produces the `HttpWriter`.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::OutputIo` if the background thread cannot be spawned.
+    pub fn try_build(self) -> Result<HttpWriter, FlexiLoggerError> {
+        let (sender, receiver) = sync_channel::<Message>(self.max_buffered_records);
+        let agent = ureq::AgentBuilder::new().timeout(self.timeout).build();
+        let url = self.url;
+        let headers = self.headers;
+        let batching = self.batching;
+
+        let join_handle = std::thread::Builder::new()
+            .name("flexi_logger-http_writer".to_string())
+            .spawn(move || {
+                batching.run(&receiver, |body| {
+                    let mut request = agent.post(&url).set("Content-Type", "application/x-ndjson");
+                    for (name, value) in &headers {
+                        request = request.set(name, value);
+                    }
+                    match request.send_bytes(body) {
+                        Ok(_response) => Ok(()),
+                        Err(ureq::Error::Status(status, _response)) => Err(PostError {
+                            retryable: status == 429 || status >= 500,
+                            description: format!("status code {}", status),
+                        }),
+                        Err(ureq::Error::Transport(transport)) => Err(PostError {
+                            retryable: true,
+                            description: transport.to_string(),
+                        }),
+                    }
+                });
+            })?;
+
+        Ok(HttpWriter {
+            format: self.format,
+            max_log_level: self.max_log_level,
+            sender: Mutex::new(sender),
+            o_join_handle: Mutex::new(Some(join_handle)),
+        })
+    }
+}
+
+enum Message {
+    Record(Vec<u8>),
+    Flush(Sender<()>),
+    Shutdown,
+}
+
+struct PostError {
+    retryable: bool,
+    description: String,
+}
+
+struct Batching {
+    max_records: usize,
+    max_bytes: usize,
+    interval: Duration,
+    max_retries: usize,
+    initial_backoff: Duration,
+}
+impl Batching {
+    // Runs in the background thread: collects the records and posts the batches.
+    fn run<F>(&self, receiver: &Receiver<Message>, mut post: F)
+    where
+        F: FnMut(&[u8]) -> Result<(), PostError>,
+    {
+        let mut body = Vec::new();
+        let mut no_of_records = 0;
+        let mut deadline = Instant::now() + self.interval;
+        loop {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            match receiver.recv_timeout(timeout) {
+                Ok(Message::Record(line)) => {
+                    if no_of_records == 0 {
+                        deadline = Instant::now() + self.interval;
+                    }
+                    body.extend_from_slice(&line);
+                    no_of_records += 1;
+                    if no_of_records >= self.max_records || body.len() >= self.max_bytes {
+                        self.post_batch(&mut body, &mut no_of_records, &mut post);
+                    }
+                }
+                Ok(Message::Flush(result_sender)) => {
+                    self.post_batch(&mut body, &mut no_of_records, &mut post);
+                    result_sender.send(()).ok();
+                }
+                Err(RecvTimeoutError::Timeout) => {
+                    self.post_batch(&mut body, &mut no_of_records, &mut post);
+                    deadline = Instant::now() + self.interval;
+                }
+                Ok(Message::Shutdown) | Err(RecvTimeoutError::Disconnected) => {
+                    self.post_batch(&mut body, &mut no_of_records, &mut post);
+                    return;
+                }
+            }
+        }
+    }
+
+    fn post_batch<F>(&self, body: &mut Vec<u8>, no_of_records: &mut usize, post: &mut F)
+    where
+        F: FnMut(&[u8]) -> Result<(), PostError>,
+    {
+        if *no_of_records == 0 {
+            return;
+        }
+        let mut backoff = self.initial_backoff;
+        let mut retries = 0;
+        loop {
+            match post(body) {
+                Ok(()) => break,
+                Err(e) if e.retryable && retries < self.max_retries => {
+                    std::thread::sleep(backoff);
+                    backoff *= 2;
+                    retries += 1;
+                }
+                Err(e) => {
+                    crate::stats::count_dropped_records(*no_of_records);
+                    crate::error_channel::report(format_args!(
+                        "HttpWriter dropped a batch of {} records, \
+                         after {} retries, due to: {}",
+                        no_of_records, retries, e.description
+                    ));
+                    break;
+                }
+            }
+        }
+        body.clear();
+        *no_of_records = 0;
+    }
+}
+
+fn broken_channel() -> IoError {
+    IoError::new(
+        ErrorKind::BrokenPipe,
+        "HttpWriter: the background thread is not available",
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::{Batching, Message, PostError};
+    use std::sync::mpsc::{channel, sync_channel};
+    use std::time::Duration;
+
+    fn batching() -> Batching {
+        Batching {
+            max_records: 3,
+            max_bytes: 1024,
+            interval: Duration::from_secs(60),
+            max_retries: 2,
+            initial_backoff: Duration::from_millis(1),
+        }
+    }
+
+    #[test]
+    fn test_batches_and_retries() {
+        let (sender, receiver) = sync_channel(100);
+        for i in 0..4 {
+            sender
+                .send(Message::Record(format!("{{\"i\":{}}}\n", i).into_bytes()))
+                .unwrap();
+        }
+        let (flush_sender, flush_receiver) = channel();
+        sender.send(Message::Flush(flush_sender)).unwrap();
+        sender.send(Message::Shutdown).unwrap();
+
+        let mut attempts = 0;
+        let mut bodies = Vec::new();
+        batching().run(&receiver, |body| {
+            attempts += 1;
+            // the first batch succeeds only in the second attempt
+            if attempts == 1 {
+                return Err(PostError {
+                    retryable: true,
+                    description: String::from("temporarily unavailable"),
+                });
+            }
+            bodies.push(String::from_utf8(body.to_vec()).unwrap());
+            Ok(())
+        });
+
+        assert!(flush_receiver.recv().is_ok());
+        assert_eq!(attempts, 3);
+        assert_eq!(
+            bodies,
+            vec!["{\"i\":0}\n{\"i\":1}\n{\"i\":2}\n", "{\"i\":3}\n"]
+        );
+    }
+
+    #[test]
+    fn test_no_retry_for_permanent_errors() {
+        let (sender, receiver) = sync_channel(100);
+        sender.send(Message::Record(b"{}\n".to_vec())).unwrap();
+        drop(sender);
+
+        let mut attempts = 0;
+        batching().run(&receiver, |_body| {
+            attempts += 1;
+            Err(PostError {
+                retryable: false,
+                description: String::from("status code 400"),
+            })
+        });
+        assert_eq!(attempts, 1);
+    }
+}
@@ -0,0 +1,333 @@
+use crate::deferred_now::DeferredNow;
+use crate::formats::{default_format, format_with_fallback};
+use crate::writers::log_writer::LogWriter;
+use crate::{FlexiLoggerError, FormatFunction};
+use kafka::producer::{Producer, Record, RequiredAcks};
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use std::sync::mpsc::{channel, sync_channel, Sender, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+const DEFAULT_MAX_BUFFERED_RECORDS: usize = 10_000;
+
+/// Signature of a callback that is informed about log records that could not be delivered.
+///
+/// It is called with the formatted record and the reason of the failure,
+/// either on the background thread of the [`KafkaWriter`],
+/// or on the logging thread if the record was dropped because the buffer was full.
+///
+/// Only available with feature `kafka_writer`.
+pub type DeliveryFailureCallback = Box<dyn Fn(&[u8], &dyn std::error::Error) + Send + Sync>;
+
+/// Determines the message key of the published records, which the producer uses to choose
+/// the partition of the topic.
+///
+/// Only available with feature `kafka_writer`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KafkaPartitioning {
+    /// Records are published without key and are thus spread over the partitions.
+    None,
+    /// The log level (e.g. `ERROR`) is used as key,
+    /// so all records of a level go to the same partition.
+    ByLevel,
+    /// The target of the record (usually the module path) is used as key,
+    /// so all records of a target go to the same partition.
+    ByTarget,
+}
+impl KafkaPartitioning {
+    fn key<'a>(self, record: &'a log::Record) -> Option<&'a str> {
+        match self {
+            Self::None => None,
+            Self::ByLevel => Some(record.level().as_str()),
+            Self::ByTarget => Some(record.target()),
+        }
+    }
+}
+
+/// A `LogWriter` that publishes the formatted log records to a topic of Apache Kafka.
+///
+/// Only available with optional crate feature `kafka_writer`.
+///
+/// The records are formatted in the logging thread, with
+/// [`default_format`](crate::default_format) or the configured format function;
+/// use [`json_format`](crate::json_format) to publish JSON-structured records.
+/// They are then handed over through a bounded buffer to a background thread that
+/// publishes them.
+/// If the buffer is full, e.g. because the brokers are slow or not reachable,
+/// further records are dropped rather than blocking the application.
+///
+/// Records that could not be delivered are reported to the
+/// [`DeliveryFailureCallback`](crate::writers::DeliveryFailureCallback), if one is configured,
+/// and are otherwise reported on stderr.
+///
+/// See the [writers](crate::writers) for guidance how to use additional log writers.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use flexi_logger::json_format;
+/// use flexi_logger::writers::{KafkaPartitioning, KafkaWriter};
+///
+/// let kafka_writer = KafkaWriter::builder(vec!["localhost:9092".to_string()], "logs")
+///     .format(json_format)
+///     .partitioning(KafkaPartitioning::ByLevel)
+///     .on_delivery_failure(Box::new(|record, e| {
+///         eprintln!("lost {} bytes: {}", record.len(), e);
+///     }))
+///     .try_build()
+///     .unwrap();
+/// ```
+pub struct KafkaWriter {
+    format: FormatFunction,
+    max_log_level: log::LevelFilter,
+    partitioning: KafkaPartitioning,
+    sender: Mutex<SyncSender<Message>>,
+    o_delivery_failure_callback: Option<Arc<DeliveryFailureCallback>>,
+    o_join_handle: Mutex<Option<JoinHandle<()>>>,
+}
+impl KafkaWriter {
+    /// Instantiates a builder for a `KafkaWriter` that publishes to the given topic,
+    /// using the given bootstrap brokers (e.g. `"localhost:9092"`).
+    #[must_use]
+    pub fn builder<S: Into<String>>(hosts: Vec<String>, topic: S) -> KafkaWriterBuilder {
+        KafkaWriterBuilder {
+            hosts,
+            topic: topic.into(),
+            format: default_format,
+            max_log_level: log::LevelFilter::Trace,
+            partitioning: KafkaPartitioning::None,
+            max_buffered_records: DEFAULT_MAX_BUFFERED_RECORDS,
+            required_acks: RequiredAcks::One,
+            ack_timeout: Duration::from_secs(1),
+            o_delivery_failure_callback: None,
+        }
+    }
+
+    fn report_failure(&self, payload: &[u8], error: &IoError) {
+        report_failure(self.o_delivery_failure_callback.as_deref(), payload, error);
+    }
+}
+
+impl LogWriter for KafkaWriter {
+    fn write(&self, now: &mut DeferredNow, record: &log::Record) -> IoResult<()> {
+        let mut payload = Vec::with_capacity(200);
+        format_with_fallback(self.format, &mut payload, now, record);
+        let key = self.partitioning.key(record).map(String::from);
+
+        let sender = self.sender.lock().map_err(|_| broken_channel())?;
+        match sender.try_send(Message::Publish(key, payload)) {
+            Ok(()) => Ok(()),
+            Err(TrySendError::Full(Message::Publish(_, payload))) => {
+                self.report_failure(
+                    &payload,
+                    &IoError::new(ErrorKind::WouldBlock, "buffer of KafkaWriter is full"),
+                );
+                Ok(())
+            }
+            Err(_) => Err(broken_channel()),
+        }
+    }
+
+    fn flush(&self) -> IoResult<()> {
+        let (result_sender, result_receiver) = channel();
+        self.sender
+            .lock()
+            .map_err(|_| broken_channel())?
+            .send(Message::Flush(result_sender))
+            .map_err(|_| broken_channel())?;
+        result_receiver.recv().map_err(|_| broken_channel())
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.max_log_level
+    }
+
+    fn format(&mut self, format: FormatFunction) {
+        self.format = format;
+    }
+
+    fn shutdown(&self) {
+        if let Ok(sender) = self.sender.lock() {
+            sender.send(Message::Shutdown).ok();
+        }
+        if let Ok(mut o_join_handle) = self.o_join_handle.lock() {
+            if let Some(join_handle) = o_join_handle.take() {
+                join_handle.join().ok();
+            }
+        }
+    }
+}
+
+enum Message {
+    Publish(Option<String>, Vec<u8>),
+    Flush(Sender<()>),
+    Shutdown,
+}
+
+/// Builder for [`KafkaWriter`].
+///
+/// Only available with feature `kafka_writer`.
+#[allow(clippy::module_name_repetitions)]
+pub struct KafkaWriterBuilder {
+    hosts: Vec<String>,
+    topic: String,
+    format: FormatFunction,
+    max_log_level: log::LevelFilter,
+    partitioning: KafkaPartitioning,
+    max_buffered_records: usize,
+    required_acks: RequiredAcks,
+    ack_timeout: Duration,
+    o_delivery_failure_callback: Option<DeliveryFailureCallback>,
+}
+impl KafkaWriterBuilder {
+    /// Makes the writer use the given format function for the records.
+    ///
+    /// Defaults to [`default_format`](crate::default_format).
+    #[must_use]
+    pub fn format(mut self, format: FormatFunction) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Sets the maximum log level that is to be written.
+    ///
+    /// Defaults to `log::LevelFilter::Trace`.
+    #[must_use]
+    pub fn max_log_level(mut self, max_log_level: log::LevelFilter) -> Self {
+        self.max_log_level = max_log_level;
+        self
+    }
+
+    /// Determines how the records are distributed over the partitions of the topic.
+    ///
+    /// Defaults to [`KafkaPartitioning::None`](crate::writers::KafkaPartitioning::None).
+    #[must_use]
+    pub fn partitioning(mut self, partitioning: KafkaPartitioning) -> Self {
+        self.partitioning = partitioning;
+        self
+    }
+
+    /// Sets the maximum number of records that are kept in memory while they wait
+    /// to be published.
+    ///
+    /// Defaults to 10,000.
+    #[must_use]
+    pub fn max_buffered_records(mut self, max_buffered_records: usize) -> Self {
+        self.max_buffered_records = max_buffered_records.max(1);
+        self
+    }
+
+    /// Sets the acknowledgements the producer requires from the brokers,
+    /// and how long it waits for them.
+    ///
+    /// Defaults to [`RequiredAcks::One`](crate::writers::RequiredAcks::One) and one second.
+    #[must_use]
+    pub fn acks(mut self, required_acks: RequiredAcks, ack_timeout: Duration) -> Self {
+        self.required_acks = required_acks;
+        self.ack_timeout = ack_timeout;
+        self
+    }
+
+    /// Registers a callback that is informed about records that could not be delivered.
+    ///
+    /// By default, such failures are reported on stderr.
+    #[must_use]
+    pub fn on_delivery_failure(mut self, callback: DeliveryFailureCallback) -> Self {
+        self.o_delivery_failure_callback = Some(callback);
+        self
+    }
+
+    /// Connects to the brokers, spawns the background thread,
+    /// and produces the `KafkaWriter`.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::Kafka` if the producer cannot be created,
+    /// `FlexiLoggerError::OutputIo` if the background thread cannot be spawned.
+    pub fn try_build(self) -> Result<KafkaWriter, FlexiLoggerError> {
+        let mut producer = Producer::from_hosts(self.hosts)
+            .with_client_id(String::from("flexi_logger"))
+            .with_required_acks(self.required_acks)
+            .with_ack_timeout(self.ack_timeout)
+            .create()?;
+        let topic = self.topic;
+        let (sender, receiver) = sync_channel::<Message>(self.max_buffered_records);
+        let o_delivery_failure_callback = self.o_delivery_failure_callback.map(Arc::new);
+        let o_callback_for_thread = o_delivery_failure_callback.clone();
+
+        let join_handle = std::thread::Builder::new()
+            .name("flexi_logger-kafka_writer".to_string())
+            .spawn(move || {
+                for message in receiver {
+                    match message {
+                        Message::Publish(o_key, payload) => {
+                            let result = match o_key {
+                                Some(key) => producer.send(&Record::from_key_value(
+                                    &topic,
+                                    key.as_bytes(),
+                                    &payload[..],
+                                )),
+                                None => producer.send(&Record::from_value(&topic, &payload[..])),
+                            };
+                            if let Err(e) = result {
+                                report_failure(o_callback_for_thread.as_deref(), &payload, &e);
+                            }
+                        }
+                        Message::Flush(result_sender) => {
+                            // records are published synchronously, so there is nothing to do
+                            result_sender.send(()).ok();
+                        }
+                        Message::Shutdown => break,
+                    }
+                }
+            })?;
+
+        Ok(KafkaWriter {
+            format: self.format,
+            max_log_level: self.max_log_level,
+            partitioning: self.partitioning,
+            sender: Mutex::new(sender),
+            o_delivery_failure_callback,
+            o_join_handle: Mutex::new(Some(join_handle)),
+        })
+    }
+}
+
+fn report_failure(
+    o_callback: Option<&DeliveryFailureCallback>,
+    payload: &[u8],
+    error: &dyn std::error::Error,
+) {
+    match o_callback {
+        Some(callback) => callback(payload, error),
+        None => crate::error_channel::report(format_args!(
+            "KafkaWriter could not deliver record \"{}\": {}",
+            String::from_utf8_lossy(payload),
+            error
+        )),
+    }
+}
+
+fn broken_channel() -> IoError {
+    IoError::new(
+        ErrorKind::BrokenPipe,
+        "KafkaWriter: the background thread is not available",
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::KafkaPartitioning;
+
+    #[test]
+    fn test_partitioning_key() {
+        let record = log::Record::builder()
+            .level(log::Level::Warn)
+            .target("my_app::db")
+            .build();
+        assert_eq!(KafkaPartitioning::None.key(&record), None);
+        assert_eq!(KafkaPartitioning::ByLevel.key(&record), Some("WARN"));
+        assert_eq!(KafkaPartitioning::ByTarget.key(&record), Some("my_app::db"));
+    }
+}
@@ -34,7 +34,40 @@ pub trait LogWriter: Sync + Send {
         let _ = format;
     }
 
+    /// Checks if the writer is able to write, e.g. if its file can be written
+    /// or if its server can be reached.
+    ///
+    /// [`Logger::start`](crate::Logger::start) calls this method for all writers, and fails
+    /// with `FlexiLoggerError::WriterHealthCheck` if one of them reports an error,
+    /// so that a misconfigured writer is detected early, rather than by missing log records.
+    /// [`LoggerHandle::health_check`](crate::LoggerHandle::health_check) allows repeating the
+    /// check while the program is running.
+    ///
+    /// The default implementation does not check anything.
+    ///
+    /// # Errors
+    ///
+    /// `std::io::Error` describing why the writer cannot write.
+    fn health_check(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+
     /// Cleanup open resources, if necessary.
+    ///
+    /// Writers that buffer records in queues or background threads, or that hold network
+    /// or database connections, should write their pending records here and close
+    /// their connections cleanly.
+    ///
+    /// `flexi_logger` calls this method once per writer, directly after a final
+    /// [`flush`](crate::writers::LogWriter::flush):
+    /// for all registered writers with [`LoggerHandle::shutdown`](crate::LoggerHandle::shutdown)
+    /// (repeated calls, also from clones of the handle, have no further effect),
+    /// for the previous writers with [`LoggerHandle::replace`](crate::LoggerHandle::replace),
+    /// and for a single writer with
+    /// [`LoggerHandle::remove_writer`](crate::LoggerHandle::remove_writer).
+    /// Records that arrive after the shutdown may be discarded.
+    ///
+    /// The default implementation is a no-op.
     fn shutdown(&self) {}
 
     /// Takes a vec with three patterns per line that represent the log out,
@@ -0,0 +1,97 @@
+use crate::deferred_now::DeferredNow;
+use crate::writers::log_writer::LogWriter;
+use std::ffi::CString;
+use std::io::Result as IoResult;
+use std::os::raw::{c_char, c_int};
+
+#[link(name = "log")]
+extern "C" {
+    fn __android_log_write(prio: c_int, tag: *const c_char, text: *const c_char) -> c_int;
+}
+
+// The priorities of the Android log, see `android/log.h`.
+const ANDROID_LOG_VERBOSE: c_int = 2;
+const ANDROID_LOG_DEBUG: c_int = 3;
+const ANDROID_LOG_INFO: c_int = 4;
+const ANDROID_LOG_WARN: c_int = 5;
+const ANDROID_LOG_ERROR: c_int = 6;
+
+/// A `LogWriter` that writes the log records to the Android log, which is read with `logcat`.
+///
+/// Only available with optional crate feature `logcat_writer`, and only on Android.
+///
+/// The levels are mapped to the Android priorities `ERROR`, `WARN`, `INFO`, `DEBUG`,
+/// and (for `Trace`) `VERBOSE`.
+/// The Android log adds the timestamp, the priority, and the tag itself,
+/// so only the messages are written.
+///
+/// See the [writers](crate::writers) for guidance how to use additional log writers.
+///
+/// ## Example
+///
+/// ```rust,ignore
+/// use flexi_logger::{writers::LogcatWriter, LogTarget, Logger};
+///
+/// Logger::with_str("info")
+///     .log_target(LogTarget::Writer(Box::new(LogcatWriter::new("my_app"))))
+///     .start()
+///     .unwrap();
+/// ```
+pub struct LogcatWriter {
+    tag: CString,
+    max_log_level: log::LevelFilter,
+}
+impl LogcatWriter {
+    /// Creates a `LogcatWriter` that writes with the given tag, usually the name of the app.
+    #[must_use]
+    pub fn new<S: Into<String>>(tag: S) -> Self {
+        Self {
+            tag: c_string(tag.into()),
+            max_log_level: log::LevelFilter::Trace,
+        }
+    }
+
+    /// Restricts the writer to records up to the given level; the default is `Trace`.
+    #[must_use]
+    pub fn max_log_level(mut self, max_log_level: log::LevelFilter) -> Self {
+        self.max_log_level = max_log_level;
+        self
+    }
+}
+
+impl LogWriter for LogcatWriter {
+    fn write(&self, _now: &mut DeferredNow, record: &log::Record) -> IoResult<()> {
+        let priority = match record.level() {
+            log::Level::Error => ANDROID_LOG_ERROR,
+            log::Level::Warn => ANDROID_LOG_WARN,
+            log::Level::Info => ANDROID_LOG_INFO,
+            log::Level::Debug => ANDROID_LOG_DEBUG,
+            log::Level::Trace => ANDROID_LOG_VERBOSE,
+        };
+        let text = c_string(record.args().to_string());
+        // returns a negative errno value if writing fails
+        let result = unsafe { __android_log_write(priority, self.tag.as_ptr(), text.as_ptr()) };
+        if result < 0 {
+            Err(std::io::Error::from_raw_os_error(-result))
+        } else {
+            Ok(())
+        }
+    }
+
+    fn flush(&self) -> IoResult<()> {
+        Ok(())
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.max_log_level
+    }
+}
+
+// C strings end with the first NUL, so inner NULs are dropped rather than truncating the text.
+fn c_string(s: String) -> CString {
+    CString::new(s).unwrap_or_else(|e| {
+        let mut bytes = e.into_vec();
+        bytes.retain(|b| *b != 0);
+        CString::new(bytes).unwrap(/* all NULs were removed */)
+    })
+}
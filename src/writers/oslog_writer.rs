@@ -0,0 +1,76 @@
+use crate::deferred_now::DeferredNow;
+use crate::writers::log_writer::LogWriter;
+use oslog::{Level, OsLog};
+use std::io::Result as IoResult;
+
+/// A `LogWriter` that writes the log records to Apple's unified logging system (`os_log`),
+/// where they can be seen e.g. in the Console app, in Xcode, or with `log stream`.
+///
+/// Only available with optional crate feature `oslog_writer`, and only on macOS and iOS.
+///
+/// The levels are mapped to the `os_log` types `error` (for `Error`), `default` (for `Warn`),
+/// `info` (for `Info`), and `debug` (for `Debug` and `Trace`).
+/// The unified logging system adds the timestamp, the type, the subsystem, and the category
+/// itself, so only the messages are written.
+///
+/// See the [writers](crate::writers) for guidance how to use additional log writers.
+///
+/// ## Example
+///
+/// ```rust,ignore
+/// use flexi_logger::{writers::OsLogWriter, LogTarget, Logger};
+///
+/// Logger::with_str("info")
+///     .log_target(LogTarget::Writer(Box::new(OsLogWriter::new(
+///         "com.example.my_app",
+///         "core",
+///     ))))
+///     .start()
+///     .unwrap();
+/// ```
+pub struct OsLogWriter {
+    os_log: OsLog,
+    max_log_level: log::LevelFilter,
+}
+impl OsLogWriter {
+    /// Creates an `OsLogWriter` for the given subsystem, usually the bundle identifier
+    /// in reverse DNS notation, and the given category within the subsystem.
+    #[must_use]
+    pub fn new(subsystem: &str, category: &str) -> Self {
+        Self {
+            os_log: OsLog::new(subsystem, category),
+            max_log_level: log::LevelFilter::Trace,
+        }
+    }
+
+    /// Restricts the writer to records up to the given level; the default is `Trace`.
+    #[must_use]
+    pub fn max_log_level(mut self, max_log_level: log::LevelFilter) -> Self {
+        self.max_log_level = max_log_level;
+        self
+    }
+}
+
+impl LogWriter for OsLogWriter {
+    fn write(&self, _now: &mut DeferredNow, record: &log::Record) -> IoResult<()> {
+        let level = match record.level() {
+            log::Level::Error => Level::Error,
+            log::Level::Warn => Level::Default,
+            log::Level::Info => Level::Info,
+            log::Level::Debug | log::Level::Trace => Level::Debug,
+        };
+        let mut message = record.args().to_string();
+        // os_log works with C strings, which end with the first NUL
+        message.retain(|c| c != '\0');
+        self.os_log.with_level(level, &message);
+        Ok(())
+    }
+
+    fn flush(&self) -> IoResult<()> {
+        Ok(())
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.max_log_level
+    }
+}
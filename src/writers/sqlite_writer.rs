@@ -0,0 +1,307 @@
+use crate::deferred_now::DeferredNow;
+use crate::timestamp::Layout;
+use crate::writers::log_writer::LogWriter;
+use crate::FlexiLoggerError;
+use rusqlite::{params, Connection};
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, RecvTimeoutError, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+
+/// A `LogWriter` that inserts the log records into a table of a local `SQLite` database,
+/// so that the log can be queried with SQL rather than by parsing text files.
+///
+/// Only available with optional crate feature `sqlite_writer`.
+///
+/// The table (by default `log`) is created if it does not yet exist, with the columns
+///
+/// | column      | type    | content                                   |
+/// |-------------|---------|-------------------------------------------|
+/// | `timestamp` | TEXT    | RFC 3339, with microseconds               |
+/// | `level`     | TEXT    | e.g. `INFO`                               |
+/// | `target`    | TEXT    |                                           |
+/// | `module`    | TEXT    | can be NULL                               |
+/// | `file`      | TEXT    | can be NULL                               |
+/// | `line`      | INTEGER | can be NULL                               |
+/// | `message`   | TEXT    |                                           |
+///
+/// The records are inserted in batches, each in a single transaction, which is much faster
+/// than a transaction per record. A batch is written when it has reached the configured size,
+/// when the configured interval has elapsed (a background thread takes care of that
+/// also if no further records arrive), and when the writer is flushed or shut down.
+///
+/// See the [writers](crate::writers) for guidance how to use additional log writers.
+///
+/// ## Example
+///
+/// ```rust,no_run
+/// use flexi_logger::writers::SqliteWriter;
+///
+/// let sqlite_writer = SqliteWriter::builder("my_app_log.db")
+///     .batch_size(50)
+///     .try_build()
+///     .unwrap();
+/// ```
+pub struct SqliteWriter {
+    max_log_level: log::LevelFilter,
+    // shared with the timer thread
+    state: Arc<Mutex<SqliteState>>,
+    // dropping the sender stops the timer thread
+    o_timer: Mutex<Option<(Sender<()>, JoinHandle<()>)>>,
+}
+impl SqliteWriter {
+    /// Instantiates a builder for a `SqliteWriter` that writes to the given database file.
+    #[must_use]
+    pub fn builder<P: Into<PathBuf>>(path: P) -> SqliteWriterBuilder {
+        SqliteWriterBuilder {
+            path: path.into(),
+            table: String::from("log"),
+            max_log_level: log::LevelFilter::Trace,
+            batch_size: 100,
+            batch_interval: Duration::from_secs(1),
+        }
+    }
+}
+
+impl LogWriter for SqliteWriter {
+    fn write(&self, now: &mut DeferredNow, record: &log::Record) -> IoResult<()> {
+        let row = Row {
+            timestamp: crate::timestamp::format(now.timestamp(), Layout::Rfc3339).to_string(),
+            level: record.level().as_str(),
+            target: record.target().to_string(),
+            module: record.module_path().map(ToString::to_string),
+            file: record.file().map(ToString::to_string),
+            line: record.line(),
+            message: record.args().to_string(),
+        };
+        let mut state = self.state.lock().map_err(|_| poisoned())?;
+        state.pending.push(row);
+        if state.pending.len() >= state.batch_size
+            || state.last_commit.elapsed() >= state.batch_interval
+        {
+            state.commit()
+        } else {
+            Ok(())
+        }
+    }
+
+    fn flush(&self) -> IoResult<()> {
+        self.state.lock().map_err(|_| poisoned())?.commit()
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.max_log_level
+    }
+
+    fn shutdown(&self) {
+        if let Ok(mut o_timer) = self.o_timer.lock() {
+            if let Some((sender, join_handle)) = o_timer.take() {
+                drop(sender);
+                join_handle.join().ok();
+            }
+        }
+        if let Ok(mut state) = self.state.lock() {
+            state.commit().unwrap_or_else(|e| {
+                crate::error_channel::report(format_args!(
+                    "SqliteWriter: final commit failed with {}",
+                    e
+                ));
+            });
+        }
+    }
+}
+
+/// Builder for [`SqliteWriter`].
+///
+/// Only available with feature `sqlite_writer`.
+#[allow(clippy::module_name_repetitions)]
+pub struct SqliteWriterBuilder {
+    path: PathBuf,
+    table: String,
+    max_log_level: log::LevelFilter,
+    batch_size: usize,
+    batch_interval: Duration,
+}
+impl SqliteWriterBuilder {
+    /// Sets the name of the table.
+    ///
+    /// Defaults to `log`.
+    #[must_use]
+    pub fn table<S: Into<String>>(mut self, table: S) -> Self {
+        self.table = table.into();
+        self
+    }
+
+    /// Sets the maximum log level that is to be written.
+    ///
+    /// Defaults to `log::LevelFilter::Trace`.
+    #[must_use]
+    pub fn max_log_level(mut self, max_log_level: log::LevelFilter) -> Self {
+        self.max_log_level = max_log_level;
+        self
+    }
+
+    /// Sets the number of records that are inserted in one transaction.
+    ///
+    /// Defaults to 100.
+    #[must_use]
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Sets the time after which a batch is written even if it is not full,
+    /// also if no further records arrive.
+    ///
+    /// Defaults to one second.
+    #[must_use]
+    pub fn batch_interval(mut self, batch_interval: Duration) -> Self {
+        self.batch_interval = batch_interval;
+        self
+    }
+
+    /// Opens or creates the database, creates the table if necessary,
+    /// starts the thread that writes the batches after the batch interval,
+    /// and produces the `SqliteWriter`.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::Sqlite` if the database cannot be opened or the table
+    /// cannot be created,
+    /// `FlexiLoggerError::OutputIo` if the thread cannot be spawned.
+    pub fn try_build(self) -> Result<SqliteWriter, FlexiLoggerError> {
+        let table = quote_identifier(&self.table);
+        let connection = Connection::open(&self.path)?;
+        connection.execute_batch(&format!(
+            "CREATE TABLE IF NOT EXISTS {table} (\
+                timestamp TEXT NOT NULL, \
+                level TEXT NOT NULL, \
+                target TEXT NOT NULL, \
+                module TEXT, \
+                file TEXT, \
+                line INTEGER, \
+                message TEXT NOT NULL); \
+             CREATE INDEX IF NOT EXISTS {index} ON {table} (timestamp);",
+            table = table,
+            index = quote_identifier(&format!("{}_timestamp", self.table)),
+        ))?;
+        let state = Arc::new(Mutex::new(SqliteState {
+            insert: format!(
+                "INSERT INTO {} \
+                 (timestamp, level, target, module, file, line, message) \
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+                table
+            ),
+            connection,
+            pending: Vec::with_capacity(self.batch_size),
+            batch_size: self.batch_size,
+            batch_interval: self.batch_interval,
+            last_commit: Instant::now(),
+        }));
+
+        let (sender, receiver) = channel::<()>();
+        let state_for_timer = Arc::clone(&state);
+        let batch_interval = self.batch_interval;
+        let join_handle = std::thread::Builder::new()
+            .name("flexi_logger-sqlite_writer".to_string())
+            .spawn(move || loop {
+                match receiver.recv_timeout(batch_interval) {
+                    Err(RecvTimeoutError::Timeout) => {
+                        if let Ok(mut state) = state_for_timer.lock() {
+                            if state.last_commit.elapsed() >= state.batch_interval {
+                                state.commit().unwrap_or_else(|e| {
+                                    crate::error_channel::report(format_args!(
+                                        "SqliteWriter: commit failed with {}",
+                                        e
+                                    ));
+                                });
+                            }
+                        }
+                    }
+                    Ok(()) | Err(RecvTimeoutError::Disconnected) => break,
+                }
+            })?;
+
+        Ok(SqliteWriter {
+            max_log_level: self.max_log_level,
+            state,
+            o_timer: Mutex::new(Some((sender, join_handle))),
+        })
+    }
+}
+
+// Quotes the name for use in SQL statements, so that it can contain any character.
+fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+struct Row {
+    timestamp: String,
+    level: &'static str,
+    target: String,
+    module: Option<String>,
+    file: Option<String>,
+    line: Option<u32>,
+    message: String,
+}
+
+struct SqliteState {
+    connection: Connection,
+    insert: String,
+    pending: Vec<Row>,
+    batch_size: usize,
+    batch_interval: Duration,
+    last_commit: Instant,
+}
+impl SqliteState {
+    // Inserts the pending rows in a single transaction.
+    fn commit(&mut self) -> IoResult<()> {
+        self.last_commit = Instant::now();
+        if self.pending.is_empty() {
+            return Ok(());
+        }
+        let result = self.try_commit();
+        // on failure, the batch is dropped, to avoid growing without bounds
+        if result.is_err() {
+            crate::stats::count_dropped_records(self.pending.len());
+        }
+        self.pending.clear();
+        result.map_err(|e| IoError::new(ErrorKind::Other, e))
+    }
+
+    fn try_commit(&mut self) -> Result<(), rusqlite::Error> {
+        let transaction = self.connection.transaction()?;
+        {
+            let mut statement = transaction.prepare_cached(&self.insert)?;
+            for row in &self.pending {
+                statement.execute(params![
+                    row.timestamp,
+                    row.level,
+                    row.target,
+                    row.module,
+                    row.file,
+                    row.line,
+                    row.message
+                ])?;
+            }
+        }
+        transaction.commit()
+    }
+}
+
+fn poisoned() -> IoError {
+    IoError::new(ErrorKind::Other, "SqliteWriter: mutex is poisoned")
+}
+
+#[cfg(test)]
+mod test {
+    use super::quote_identifier;
+
+    #[test]
+    fn test_quote_identifier() {
+        assert_eq!(quote_identifier("log"), "\"log\"");
+        assert_eq!(quote_identifier("my \"log\""), "\"my \"\"log\"\"\"");
+    }
+}
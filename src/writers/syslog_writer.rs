@@ -1,4 +1,6 @@
 use crate::deferred_now::DeferredNow;
+use crate::primary_writer::buffer_with;
+use crate::timestamp::Layout;
 use crate::writers::log_writer::LogWriter;
 use std::cell::RefCell;
 use std::ffi::OsString;
@@ -175,21 +177,36 @@ impl LogWriter for SyslogWriter {
         let mut syslog = mr_syslog.borrow_mut();
 
         let severity = (self.determine_severity)(record.level());
-        write!(
-            syslog,
-            "{}",
-            format!(
-                "<{}>1 {} {:?} {} {} {} - {}\n",
+        let write_to_syslog = |buffer: &mut Vec<u8>| -> IoResult<()> {
+            writeln!(
+                buffer,
+                "<{}>1 {} {:?} {} {} {} - {}",
                 self.facility as u8 | severity as u8,
-                now.now()
-                    .to_rfc3339_opts(chrono::SecondsFormat::Micros, false),
+                crate::timestamp::format(now.timestamp(), Layout::Rfc3339),
                 self.hostname,
                 self.process,
                 self.pid,
                 self.message_id,
                 &record.args()
-            )
-        )
+            )?;
+            syslog.write_all(buffer)
+        };
+
+        // the message is rendered into a reused thread-local buffer
+        // and then handed over to the syslog with a single write call
+        let mut result = Ok(());
+        buffer_with(|tl_buf| match tl_buf.try_borrow_mut() {
+            Ok(mut buffer) => {
+                result = write_to_syslog(&mut buffer);
+                buffer.clear();
+            }
+            Err(_e) => {
+                // We arrive here in the rare cases of recursive logging
+                // (e.g. log calls in Debug or Display implementations)
+                result = write_to_syslog(&mut Vec::with_capacity(200));
+            }
+        });
+        result
     }
 
     fn flush(&self) -> IoResult<()> {
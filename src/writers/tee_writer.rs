@@ -0,0 +1,109 @@
+use crate::deferred_now::DeferredNow;
+use crate::writers::log_writer::LogWriter;
+use crate::FormatFunction;
+use std::io::{Error as IoError, ErrorKind, Result as IoResult};
+
+/// A `LogWriter` that fans out each record to several inner writers.
+///
+/// This allows composing multi-destination setups from existing writers,
+/// e.g. a [`FileLogWriter`](crate::writers::FileLogWriter) and a network writer,
+/// and using them as a single [`LogTarget::Writer`](crate::LogTarget::Writer)
+/// or as a single named writer with [`Logger::add_writer`](crate::Logger::add_writer).
+///
+/// Each record is given to every inner writer whose `max_log_level` accepts it,
+/// even if a previous writer failed; the errors of all failing writers are then
+/// aggregated into a single error.
+///
+/// ## Example
+///
+/// ```rust
+/// use flexi_logger::writers::{FileLogWriter, TeeWriter};
+///
+/// let file_writer = |discriminant| {
+///     FileLogWriter::builder()
+///         .directory("log_files")
+///         .discriminant(discriminant)
+///         .try_build()
+///         .unwrap()
+/// };
+/// let tee_writer = TeeWriter::new(vec![
+///     Box::new(file_writer("a")),
+///     Box::new(file_writer("b")),
+/// ]);
+/// ```
+pub struct TeeWriter {
+    writers: Vec<Box<dyn LogWriter>>,
+}
+impl TeeWriter {
+    /// Creates a `TeeWriter` that fans out to the given writers.
+    #[must_use]
+    pub fn new(writers: Vec<Box<dyn LogWriter>>) -> Self {
+        Self { writers }
+    }
+}
+
+impl LogWriter for TeeWriter {
+    fn write(&self, now: &mut DeferredNow, record: &log::Record) -> IoResult<()> {
+        aggregate(
+            self.writers
+                .iter()
+                .filter(|writer| record.level() <= writer.max_log_level())
+                .map(|writer| writer.write(now, record)),
+        )
+    }
+
+    fn flush(&self) -> IoResult<()> {
+        aggregate(self.writers.iter().map(|writer| writer.flush()))
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.writers
+            .iter()
+            .map(|writer| writer.max_log_level())
+            .max()
+            .unwrap_or(log::LevelFilter::Off)
+    }
+
+    fn format(&mut self, format: FormatFunction) {
+        for writer in &mut self.writers {
+            writer.format(format);
+        }
+    }
+
+    fn health_check(&self) -> IoResult<()> {
+        aggregate(self.writers.iter().map(|writer| writer.health_check()))
+    }
+
+    fn shutdown(&self) {
+        for writer in &self.writers {
+            writer.shutdown();
+        }
+    }
+
+    fn validate_logs(&self, expected: &[(&'static str, &'static str, &'static str)]) {
+        for writer in &self.writers {
+            writer.validate_logs(expected);
+        }
+    }
+}
+
+// Drains all results and merges the errors, if any, into one.
+fn aggregate<I: Iterator<Item = IoResult<()>>>(results: I) -> IoResult<()> {
+    let errors: Vec<IoError> = results.filter_map(Result::err).collect();
+    match errors.len() {
+        0 => Ok(()),
+        1 => Err(errors.into_iter().next().unwrap(/*ok*/)),
+        n => Err(IoError::new(
+            ErrorKind::Other,
+            format!(
+                "{} writers failed: {}",
+                n,
+                errors
+                    .iter()
+                    .map(ToString::to_string)
+                    .collect::<Vec<_>>()
+                    .join("; ")
+            ),
+        )),
+    }
+}
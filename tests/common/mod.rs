@@ -0,0 +1,69 @@
+// Fixtures that are shared by several tests; not every test uses all of them.
+#![allow(dead_code)]
+
+use flexi_logger::writers::LogWriter;
+use flexi_logger::{default_format, DeferredNow};
+use log::{LevelFilter, Record};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+// A writer that collects the messages of the records,
+// or, with `formatted()`, the lines in the default format.
+pub struct CollectingWriter {
+    data: Arc<Mutex<Vec<String>>>,
+    max_log_level: LevelFilter,
+    formatted: bool,
+    o_shut_down: Option<Arc<AtomicBool>>,
+}
+impl CollectingWriter {
+    pub fn new(data: &Arc<Mutex<Vec<String>>>, max_log_level: LevelFilter) -> Self {
+        Self {
+            data: Arc::clone(data),
+            max_log_level,
+            formatted: false,
+            o_shut_down: None,
+        }
+    }
+
+    pub fn formatted(mut self) -> Self {
+        self.formatted = true;
+        self
+    }
+
+    // Lets the writer set the flag when it is shut down.
+    pub fn signal_shutdown(mut self, shut_down: &Arc<AtomicBool>) -> Self {
+        self.o_shut_down = Some(Arc::clone(shut_down));
+        self
+    }
+}
+impl LogWriter for CollectingWriter {
+    fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        let line = if self.formatted {
+            let mut line = Vec::new();
+            default_format(&mut line, now, record)?;
+            String::from_utf8_lossy(&line).to_string()
+        } else {
+            record.args().to_string()
+        };
+        self.data.lock().unwrap().push(line);
+        Ok(())
+    }
+    fn flush(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+    fn max_log_level(&self) -> LevelFilter {
+        self.max_log_level
+    }
+    fn shutdown(&self) {
+        if let Some(ref shut_down) = self.o_shut_down {
+            shut_down.store(true, Ordering::SeqCst);
+        }
+    }
+}
+
+pub fn collecting_writer(
+    data: &Arc<Mutex<Vec<String>>>,
+    max_log_level: LevelFilter,
+) -> Box<CollectingWriter> {
+    Box::new(CollectingWriter::new(data, max_log_level))
+}
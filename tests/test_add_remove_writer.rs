@@ -0,0 +1,41 @@
+mod common;
+
+use common::{collecting_writer, CollectingWriter};
+use flexi_logger::{LogTarget, Logger};
+use log::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+// Writers can be added and removed while the logger is running.
+#[test]
+fn test_add_remove_writer() {
+    let main = Arc::new(Mutex::new(Vec::new()));
+    let panel = Arc::new(Mutex::new(Vec::new()));
+    let shut_down = Arc::new(AtomicBool::new(false));
+    let handle = Logger::with_str("info")
+        .log_target(LogTarget::Writer(collecting_writer(
+            &main,
+            LevelFilter::Debug,
+        )))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!(target: "{Panel}", "before");
+    handle.add_writer(
+        "Panel",
+        Box::new(CollectingWriter::new(&panel, LevelFilter::Debug).signal_shutdown(&shut_down)),
+    );
+    assert_eq!(handle.effective_max_level(), LevelFilter::Debug);
+    debug!(target: "{Panel}", "during");
+    info!("main");
+
+    assert!(handle.remove_writer("Panel"));
+    assert!(shut_down.load(Ordering::SeqCst));
+    assert!(!handle.remove_writer("Panel"));
+    assert_eq!(handle.effective_max_level(), LevelFilter::Info);
+    info!(target: "{Panel}", "after");
+    handle.shutdown();
+
+    assert_eq!(*main.lock().unwrap(), vec!["main"]);
+    assert_eq!(*panel.lock().unwrap(), vec!["during"]);
+}
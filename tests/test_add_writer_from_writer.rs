@@ -0,0 +1,51 @@
+mod common;
+
+use common::collecting_writer;
+use flexi_logger::writers::LogWriter;
+use flexi_logger::{DeferredNow, LogTarget, Logger, LoggerHandle};
+use log::*;
+use std::sync::{Arc, Mutex};
+
+// A writer that registers another writer when it gets its first record,
+// like a plugin that sets itself up lazily.
+struct RegisteringWriter {
+    o_handle: Mutex<Option<LoggerHandle>>,
+    data: Arc<Mutex<Vec<String>>>,
+}
+impl LogWriter for RegisteringWriter {
+    fn write(&self, _now: &mut DeferredNow, _record: &Record) -> std::io::Result<()> {
+        if let Some(handle) = self.o_handle.lock().unwrap().take() {
+            handle.add_writer("Late", collecting_writer(&self.data, LevelFilter::Info));
+        }
+        Ok(())
+    }
+    fn flush(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+    fn max_log_level(&self) -> LevelFilter {
+        LevelFilter::Info
+    }
+}
+
+// Writers can change the writers of the logger while they are writing.
+#[test]
+fn test_add_writer_from_writer() {
+    let late = Arc::new(Mutex::new(Vec::new()));
+    let handle = Logger::with_str("info")
+        .log_target(LogTarget::DevNull)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+    handle.add_writer(
+        "Setup",
+        Box::new(RegisteringWriter {
+            o_handle: Mutex::new(Some(handle.clone())),
+            data: Arc::clone(&late),
+        }),
+    );
+
+    info!(target: "{Setup}", "set up");
+    info!(target: "{Late}", "late");
+    handle.shutdown();
+
+    assert_eq!(*late.lock().unwrap(), vec!["late"]);
+}
@@ -0,0 +1,36 @@
+use flexi_logger::{default_format, Logger};
+use log::*;
+
+// The level and the module path are padded, so that the messages line up.
+#[test]
+fn test_align_columns() {
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory("log_files/align_columns")
+        .format(default_format)
+        .align_columns(5, 20)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    // building another logger does not change the settings of the running one
+    let _other = Logger::with_str("info")
+        .align_columns(1, 1)
+        .build()
+        .unwrap();
+
+    info!("This is an info message");
+    error!("This is an error message");
+
+    handle.validate_logs(&[
+        (
+            "INFO  [",
+            "[test_align_columns]   This",
+            "This is an info message",
+        ),
+        (
+            "ERROR [",
+            "[test_align_columns]   This",
+            "This is an error message",
+        ),
+    ]);
+}
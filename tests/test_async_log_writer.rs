@@ -0,0 +1,142 @@
+use flexi_logger::writers::{AsyncLogWriter, AsyncWriteFuture, AsyncWriterAdapter};
+use flexi_logger::{LogTarget, Logger};
+use log::*;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+// Simulates an async sink: each write is pending once before it completes.
+struct AsyncSink {
+    data: Arc<Mutex<Vec<u8>>>,
+}
+impl AsyncLogWriter for AsyncSink {
+    fn write_async<'a>(&'a mut self, buffer: &'a [u8]) -> AsyncWriteFuture<'a> {
+        Box::pin(YieldOnce {
+            yielded: false,
+            data: Arc::clone(&self.data),
+            buffer,
+        })
+    }
+
+    fn flush_async(&mut self) -> AsyncWriteFuture<'_> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+struct YieldOnce<'a> {
+    yielded: bool,
+    data: Arc<Mutex<Vec<u8>>>,
+    buffer: &'a [u8],
+}
+impl<'a> Future for YieldOnce<'a> {
+    type Output = std::io::Result<()>;
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        if self.yielded {
+            self.data.lock().unwrap().extend_from_slice(self.buffer);
+            Poll::Ready(Ok(()))
+        } else {
+            self.yielded = true;
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+}
+
+#[test]
+fn test_async_log_writer() {
+    let data = Arc::new(Mutex::new(Vec::new()));
+    let writer = AsyncWriterAdapter::builder(Box::new(AsyncSink {
+        data: Arc::clone(&data),
+    }))
+    .max_log_level(LevelFilter::Info)
+    .try_build()
+    .unwrap();
+
+    let handle = Logger::with_str("info")
+        .log_target(LogTarget::DevNull)
+        .add_writer("Async", Box::new(writer))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!(target: "{Async}", "This is an error message");
+    warn!(target: "{Async}", "This is a warning");
+    info!(target: "{Async}", "This is an info message");
+    debug!(target: "{Async}", "This is a debug message - you must not see it!");
+    info!("This is a normal info message - you must not see it!");
+
+    handle.flush();
+    assert_eq!(
+        String::from_utf8(data.lock().unwrap().clone()).unwrap(),
+        "ERROR [test_async_log_writer] This is an error message\n\
+         WARN [test_async_log_writer] This is a warning\n\
+         INFO [test_async_log_writer] This is an info message\n"
+    );
+
+    info!(target: "{Async}", "This is written on shutdown");
+    handle.shutdown();
+    assert!(String::from_utf8(data.lock().unwrap().clone())
+        .unwrap()
+        .ends_with("This is written on shutdown\n"));
+}
+
+// Blocks each write until the gate is opened.
+struct GatedSink {
+    gate: Arc<Mutex<()>>,
+    data: Arc<Mutex<Vec<u8>>>,
+}
+impl AsyncLogWriter for GatedSink {
+    fn write_async<'a>(&'a mut self, buffer: &'a [u8]) -> AsyncWriteFuture<'a> {
+        let _open = self.gate.lock().unwrap();
+        self.data.lock().unwrap().extend_from_slice(buffer);
+        Box::pin(async { Ok(()) })
+    }
+
+    fn flush_async(&mut self) -> AsyncWriteFuture<'_> {
+        Box::pin(async { Ok(()) })
+    }
+}
+
+// When the channel is full, the log lines are dropped instead of blocking the caller.
+#[test]
+fn test_async_log_writer_drops_on_overflow() {
+    use flexi_logger::writers::LogWriter;
+    use flexi_logger::DeferredNow;
+
+    let gate = Arc::new(Mutex::new(()));
+    let data = Arc::new(Mutex::new(Vec::new()));
+    let writer = AsyncWriterAdapter::builder(Box::new(GatedSink {
+        gate: Arc::clone(&gate),
+        data: Arc::clone(&data),
+    }))
+    .channel_capacity(1)
+    .try_build()
+    .unwrap();
+
+    let closed_gate = gate.lock().unwrap();
+    for i in 0..10 {
+        writer
+            .write(
+                &mut DeferredNow::new(),
+                &Record::builder()
+                    .args(format_args!("line {}", i))
+                    .level(Level::Info)
+                    .build(),
+            )
+            .unwrap();
+    }
+    drop(closed_gate);
+    writer.flush().unwrap();
+
+    // one line in the sink, and one in the channel
+    let no_of_lines = String::from_utf8(data.lock().unwrap().clone())
+        .unwrap()
+        .lines()
+        .count();
+    assert!(
+        (1..=2).contains(&no_of_lines),
+        "{} lines were written",
+        no_of_lines
+    );
+    writer.shutdown();
+}
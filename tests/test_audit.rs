@@ -0,0 +1,57 @@
+use chrono::Local;
+use flexi_logger::writers::{FileLogWriter, LogWriter};
+use flexi_logger::{Cleanup, Criterion, FlexiLoggerError, Naming};
+use std::io::Write;
+
+// An audit log survives restarts and rejects settings that would lose records.
+#[test]
+fn test_audit() {
+    let directory = format!(
+        "./log_files/audit/{}",
+        Local::now().format("%Y-%m-%d_%H-%M-%S")
+    );
+    let audit_writer = || {
+        FileLogWriter::builder()
+            .directory(directory.clone())
+            .discriminant("audit")
+            .audit()
+    };
+
+    for run in 0..2 {
+        let file_log_writer = audit_writer().try_build().unwrap();
+        writeln!(&file_log_writer, "record of run {}", run).unwrap();
+        file_log_writer.shutdown();
+    }
+
+    let path = audit_writer().try_build().unwrap().current_path();
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "record of run 0\nrecord of run 1\n"
+    );
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    assert!(audit_writer()
+        .rotate(Criterion::Size(1000), Naming::Numbers, Cleanup::Never)
+        .try_build()
+        .is_ok());
+    match audit_writer()
+        .rotate(
+            Criterion::Size(1000),
+            Naming::Numbers,
+            Cleanup::KeepLogFiles(3),
+        )
+        .try_build()
+    {
+        Err(FlexiLoggerError::RotationConfigConflict(_)) => {}
+        _ => panic!("an audit log must not be cleaned up"),
+    }
+    match audit_writer().o_append(false).try_build() {
+        Err(FlexiLoggerError::FileConfigConflict(_)) => {}
+        _ => panic!("an audit log must not be truncated"),
+    }
+}
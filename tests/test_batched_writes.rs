@@ -0,0 +1,46 @@
+use flexi_logger::Logger;
+use log::*;
+use std::time::{Duration, Instant};
+
+const NO_OF_LINES: usize = 100;
+
+// With buffering and a background flusher, the log lines are written in batches:
+// the file never contains only a part of the lines that fit into the buffer.
+#[test]
+fn test_batched_writes() {
+    let _ = std::fs::remove_dir_all("log_files/batched_writes");
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory("log_files/batched_writes")
+        .basename("batched")
+        .suppress_timestamp()
+        .buffer_and_flush_with(64 * 1024, Duration::from_millis(300))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    for i in 0..NO_OF_LINES {
+        info!("This is line {}", i);
+    }
+
+    let filename = "log_files/batched_writes/batched.log";
+    let start = Instant::now();
+    loop {
+        let no_of_lines = std::fs::read_to_string(filename)
+            .map(|content| content.lines().count())
+            .unwrap_or(0);
+        assert!(
+            no_of_lines == 0 || no_of_lines == NO_OF_LINES,
+            "found a partially written batch with {} lines",
+            no_of_lines
+        );
+        if no_of_lines == NO_OF_LINES {
+            break;
+        }
+        assert!(
+            start.elapsed() < Duration::from_secs(3),
+            "the batch was not written by the flusher"
+        );
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    handle.shutdown();
+}
@@ -0,0 +1,46 @@
+use flexi_logger::{Cleanup, Criterion, FlexiLoggerError, LogTarget, Logger, Naming};
+
+// Contradicting settings are detected when the logger is built.
+#[test]
+fn test_builder_validation() {
+    match Logger::with_str("info").append().build() {
+        Err(FlexiLoggerError::FileConfigConflict(_)) => {}
+        _ => panic!("append() without log file was not detected"),
+    }
+
+    match Logger::with_str("info")
+        .log_to_file()
+        .directory("log_files/builder_validation")
+        .rotate(
+            Criterion::Size(1000),
+            Naming::Numbers,
+            Cleanup::KeepLogFiles(5),
+        )
+        .o_timestamp(true)
+        .build()
+    {
+        Err(FlexiLoggerError::RotationConfigConflict(_)) => {}
+        _ => panic!("cleanup with timestamp in file name was not detected"),
+    }
+
+    match Logger::with_str("info")
+        .log_target(LogTarget::FilePerThread)
+        .directory("log_files/builder_validation")
+        .sharded(4)
+        .build()
+    {
+        Err(FlexiLoggerError::FileConfigConflict(_)) => {}
+        _ => panic!("shards with a file per thread were not detected"),
+    }
+
+    let file = std::path::PathBuf::from("log_files/builder_validation/no_directory");
+    std::fs::write(&file, "").unwrap();
+    match Logger::with_str("info")
+        .log_to_file()
+        .directory(file)
+        .build()
+    {
+        Err(FlexiLoggerError::OutputBadDirectory) => {}
+        _ => panic!("file as log directory was not detected"),
+    }
+}
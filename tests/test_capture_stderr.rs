@@ -0,0 +1,41 @@
+#[cfg(unix)]
+mod test {
+    use flexi_logger::Logger;
+    use log::*;
+    use std::io::Write;
+    use std::process::Command;
+
+    // Output to the own stderr, also from child processes, ends up in the log file.
+    #[test]
+    fn test_capture_stderr() {
+        let handle = Logger::with_str("info")
+            .log_to_file()
+            .directory("log_files/capture_stderr")
+            .capture_stderr()
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        info!("This is a record");
+        std::io::stderr().write_all(b"written to stderr\n").unwrap();
+        Command::new("sh")
+            .args(&["-c", "echo written by a child >&2"])
+            .status()
+            .unwrap();
+        handle.shutdown();
+
+        let log_file = std::fs::read_dir("log_files/capture_stderr")
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .max_by_key(|path| std::fs::metadata(path).unwrap().modified().unwrap())
+            .unwrap();
+        let content = std::fs::read_to_string(log_file).unwrap();
+        assert_eq!(
+            content.lines().collect::<Vec<&str>>(),
+            vec![
+                "INFO [test_capture_stderr::test] This is a record",
+                "written to stderr",
+                "written by a child"
+            ]
+        );
+    }
+}
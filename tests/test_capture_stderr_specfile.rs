@@ -0,0 +1,51 @@
+#[cfg(all(unix, feature = "specfile_without_notification"))]
+mod test {
+    use flexi_logger::{ErrorChannel, Logger};
+    use log::*;
+
+    // A specfile cannot switch on duplication to the captured stderr,
+    // which would write each duplicated line to the log file a second time.
+    #[test]
+    fn test_capture_stderr_specfile() {
+        let directory = "log_files/capture_stderr_specfile";
+        let error_file = std::path::PathBuf::from(directory).join("errors.txt");
+        std::fs::create_dir_all(directory).unwrap();
+        std::fs::remove_file(&error_file).ok();
+        let specfile = "test_spec/test_capture_stderr_specfile.toml";
+        std::fs::create_dir_all("test_spec").unwrap();
+        std::fs::write(
+            specfile,
+            b"
+            duplicate_to_stderr = 'info'
+            global_level = 'info'
+            ",
+        )
+        .unwrap();
+
+        let handle = Logger::with_str("info")
+            .log_to_file()
+            .directory(directory)
+            .capture_stderr()
+            .error_channel(ErrorChannel::File(error_file.clone()))
+            .start_with_specfile(specfile)
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        info!("This is a record");
+        handle.shutdown();
+
+        let log_file = std::fs::read_dir(directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().map_or(false, |ext| ext == "log"))
+            .max_by_key(|path| std::fs::metadata(path).unwrap().modified().unwrap())
+            .unwrap();
+        let content = std::fs::read_to_string(log_file).unwrap();
+        assert_eq!(
+            content.lines().collect::<Vec<&str>>(),
+            vec!["INFO [test_capture_stderr_specfile::test] This is a record"]
+        );
+
+        let errors = std::fs::read_to_string(&error_file).unwrap();
+        assert!(errors.contains("duplicate_to_stderr = Info"), "{}", errors);
+    }
+}
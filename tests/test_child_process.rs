@@ -0,0 +1,50 @@
+#[cfg(unix)]
+#[test]
+fn test_child_process() {
+    use flexi_logger::{ChildProcessCapture, Logger};
+    use log::Level;
+    use std::process::Command;
+
+    // The lines of the child's stdout and stderr become records with the configured settings.
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory("log_files/child_process")
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    let exit_status = ChildProcessCapture::new("child")
+        .stderr_level(Level::Error)
+        .prefix("> ")
+        .spawn(Command::new("sh").args(&["-c", "echo first; echo oops >&2; printf second"]))
+        .unwrap()
+        .wait()
+        .unwrap();
+    assert!(exit_status.success());
+
+    // lines below the max level are not forwarded
+    ChildProcessCapture::new("child")
+        .stdout_level(Level::Debug)
+        .spawn(Command::new("sh").args(&["-c", "echo hidden"]))
+        .unwrap()
+        .wait()
+        .unwrap();
+    handle.shutdown();
+
+    let log_file = std::fs::read_dir("log_files/child_process")
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .max_by_key(|path| std::fs::metadata(path).unwrap().modified().unwrap())
+        .unwrap();
+    let content = std::fs::read_to_string(log_file).unwrap();
+    let mut lines: Vec<&str> = content.lines().collect();
+    // stdout and stderr are read by different threads
+    lines.sort_unstable();
+    assert_eq!(
+        lines,
+        vec![
+            "ERROR [child] > oops",
+            "INFO [child] > first",
+            "INFO [child] > second"
+        ]
+    );
+}
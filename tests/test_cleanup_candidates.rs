@@ -0,0 +1,62 @@
+use chrono::Local;
+use flexi_logger::writers::{CleanupCandidate, FileLogWriter, LogWriter};
+use flexi_logger::{Cleanup, Criterion, Naming};
+use std::path::Path;
+
+// The cleanup candidates are listed without touching the files.
+#[test]
+fn test_cleanup_candidates() {
+    let directory = format!(
+        "./log_files/cleanup_candidates/{}",
+        Local::now().format("%Y-%m-%d_%H-%M-%S")
+    );
+    let builder = |cleanup: Cleanup| {
+        FileLogWriter::builder()
+            .directory(directory.clone())
+            .rotate(Criterion::Size(5), Naming::Numbers, cleanup)
+            .cleanup_in_background_thread(false)
+            .try_build()
+            .unwrap()
+    };
+
+    let file_log_writer = builder(Cleanup::Never);
+    for i in 0..4 {
+        file_log_writer
+            .write_raw(format!("record {}\n", i).as_bytes())
+            .unwrap();
+    }
+    assert!(file_log_writer
+        .list_cleanup_candidates()
+        .unwrap()
+        .is_empty());
+    file_log_writer.shutdown();
+
+    let file_log_writer = builder(Cleanup::KeepLogFiles(1));
+    let file_names: Vec<String> = file_log_writer
+        .list_cleanup_candidates()
+        .unwrap()
+        .into_iter()
+        .map(|candidate| match candidate {
+            CleanupCandidate::Remove { file } => file_name(&file),
+            candidate => panic!("unexpected candidate {:?}", candidate),
+        })
+        .collect();
+    let current = file_name(&file_log_writer.current_path());
+    assert_eq!(
+        file_names,
+        vec![
+            current.replace(".log", ".1.log"),
+            current.replace(".log", ".0.log"),
+        ]
+    );
+    // nothing was removed
+    for idx in &[".0.log", ".1.log", ".2.log"] {
+        assert!(Path::new(&directory)
+            .join(current.replace(".log", idx))
+            .exists());
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name().unwrap().to_string_lossy().to_string()
+}
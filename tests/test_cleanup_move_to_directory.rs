@@ -0,0 +1,73 @@
+use chrono::Local;
+use flexi_logger::writers::{FileLogWriter, LogWriter};
+use flexi_logger::{Cleanup, Criterion, Naming};
+use std::path::Path;
+
+// Outdated log files are moved into the archive directory instead of being deleted,
+// without overwriting the files that are already there.
+#[test]
+fn test_cleanup_move_to_directory() {
+    let directory = format!(
+        "./log_files/cleanup_move_to_directory/{}",
+        Local::now().format("%Y-%m-%d_%H-%M-%S")
+    );
+    let archive = format!("{}/archive", directory);
+
+    // the log directory itself is rejected, also if it is spelled differently
+    std::fs::create_dir_all(&directory).unwrap();
+    assert!(FileLogWriter::builder()
+        .directory(directory.clone())
+        .rotate(
+            Criterion::Size(5),
+            Naming::Numbers,
+            Cleanup::MoveToDirectory(
+                Box::leak(std::path::PathBuf::from(format!("{}/./", directory)).into_boxed_path()),
+                1,
+            ),
+        )
+        .try_build()
+        .is_err());
+
+    let file_log_writer = FileLogWriter::builder()
+        .directory(directory.clone())
+        .rotate(
+            Criterion::Size(5),
+            Naming::Numbers,
+            Cleanup::MoveToDirectory(
+                Box::leak(std::path::PathBuf::from(&archive).into_boxed_path()),
+                1,
+            ),
+        )
+        .cleanup_in_background_thread(false)
+        .try_build()
+        .unwrap();
+    let current_name = file_log_writer
+        .current_path()
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .to_string();
+    let name = |infix: &str| current_name.replace(".log", &format!("{}.log", infix));
+    std::fs::create_dir_all(&archive).unwrap();
+    std::fs::write(Path::new(&archive).join(name(".0")), "earlier run\n").unwrap();
+
+    for i in 0..4 {
+        file_log_writer
+            .write_raw(format!("record {}\n", i).as_bytes())
+            .unwrap();
+    }
+    file_log_writer.shutdown();
+
+    assert_eq!(
+        std::fs::read_to_string(Path::new(&archive).join(name(".0"))).unwrap(),
+        "earlier run\n"
+    );
+    for (infix, moved_infix, record) in &[(".0", "-1.0", 0), (".1", ".1", 1)] {
+        assert!(!Path::new(&directory).join(name(infix)).exists());
+        assert_eq!(
+            std::fs::read_to_string(Path::new(&archive).join(name(moved_infix))).unwrap(),
+            format!("record {}\n", record)
+        );
+    }
+    assert!(Path::new(&directory).join(name(".2")).exists());
+}
@@ -0,0 +1,73 @@
+use chrono::Local;
+use flexi_logger::writers::{FileLogWriter, LogWriter};
+use flexi_logger::{Cleanup, Criterion, Naming};
+use std::path::Path;
+
+// The cleanup limits apply to the rotated files of all discriminants together,
+// already when the writer is built, and they do not apply to the files of other programs.
+#[test]
+fn test_cleanup_other_discriminants() {
+    let directory = format!(
+        "./log_files/cleanup_other_discriminants/{}",
+        Local::now().format("%Y-%m-%d_%H-%M-%S")
+    );
+    let build = |discriminant: &str, cleanup: Cleanup| {
+        FileLogWriter::builder()
+            .directory(directory.clone())
+            .basename("app")
+            .discriminant(discriminant)
+            .rotate(Criterion::Size(5), Naming::Numbers, cleanup)
+            .cleanup_in_background_thread(false)
+            .cleanup_other_discriminants(true)
+            .try_build()
+            .unwrap()
+    };
+    let write_records = |discriminant: &str, cleanup: Cleanup| {
+        let file_log_writer = build(discriminant, cleanup);
+        for i in 0..4 {
+            file_log_writer
+                .write_raw(format!("record {}\n", i).as_bytes())
+                .unwrap();
+            // gives the rotated files distinct modification times
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+        file_log_writer.shutdown();
+    };
+    let rotated_files = || -> Vec<String> {
+        std::fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .filter(|name| name.starts_with("app_"))
+            .filter(|name| !name.ends_with("_previous.log") && !name.ends_with("_current.log"))
+            .collect()
+    };
+
+    // the files of other programs
+    std::fs::create_dir_all(&directory).unwrap();
+    let other_files = ["application.1.log", "app2_previous.1.log"];
+    for name in &other_files {
+        std::fs::write(Path::new(&directory).join(name), "other program\n").unwrap();
+    }
+
+    // a previous run, with another discriminant
+    write_records("previous", Cleanup::Never);
+    assert_eq!(rotated_files().len(), 3);
+
+    write_records("current", Cleanup::KeepLogFiles(4));
+    let files = rotated_files();
+    assert_eq!(files.len(), 4);
+    assert_eq!(
+        files
+            .iter()
+            .filter(|name| name.contains("_current."))
+            .count(),
+        3
+    );
+
+    let file_log_writer = build("next", Cleanup::KeepLogFiles(1));
+    assert_eq!(rotated_files().len(), 1);
+    file_log_writer.shutdown();
+    for name in &other_files {
+        assert!(Path::new(&directory).join(name).exists());
+    }
+}
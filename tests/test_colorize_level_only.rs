@@ -0,0 +1,35 @@
+#[cfg(feature = "colors")]
+#[test]
+fn test_colorize_level_only() {
+    use flexi_logger::{colored_default_format, DeferredNow, Logger};
+
+    let (_logger, _handle) = Logger::with_str("info")
+        .colorize_level_only(true)
+        .build()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    let mut buf = Vec::<u8>::new();
+    colored_default_format(
+        &mut buf,
+        &mut DeferredNow::new(),
+        &log::Record::builder()
+            .args(format_args!("This is an error message"))
+            .level(log::Level::Error)
+            .module_path(Some("test_colorize_level_only"))
+            .build(),
+    )
+    .unwrap();
+    let line = String::from_utf8(buf).unwrap();
+
+    // the level token is colored, the message is not
+    assert!(
+        line.starts_with('\u{1b}'),
+        "level is not colored: {:?}",
+        line
+    );
+    assert!(
+        line.ends_with("[test_colorize_level_only] This is an error message"),
+        "message is colored: {:?}",
+        line
+    );
+}
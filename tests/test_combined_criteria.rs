@@ -0,0 +1,70 @@
+use chrono::Local;
+use flexi_logger::writers::{FileLogWriter, LogWriter};
+use flexi_logger::{Cleanup, Criterion, Naming, NumberOrder};
+use std::time::Duration;
+
+// Combined criteria rotate when any (or all) of their criteria is fulfilled,
+// and a minimum interval suppresses rotations that would come too early.
+#[test]
+fn test_combined_criteria() {
+    let directory = format!(
+        "./log_files/combined_criteria/{}",
+        Local::now().format("%Y-%m-%d_%H-%M-%S")
+    );
+    let write_records = |discriminant: &str, criterion: Criterion| -> String {
+        let file_log_writer = FileLogWriter::builder()
+            .directory(directory.clone())
+            .discriminant(discriminant)
+            .rotate(
+                criterion,
+                Naming::NumbersWith {
+                    width: 1,
+                    start: 0,
+                    order: NumberOrder::Ascending,
+                },
+                Cleanup::Never,
+            )
+            .try_build()
+            .unwrap();
+        for i in 0..3 {
+            file_log_writer
+                .write_raw(format!("record {}\n", i).as_bytes())
+                .unwrap();
+        }
+        file_log_writer.shutdown();
+        file_log_writer.current_path().to_string_lossy().to_string()
+    };
+    let read = |current_path: &str, infix: &str| {
+        std::fs::read_to_string(format!(
+            "{}{}.log",
+            current_path.trim_end_matches(".log"),
+            infix
+        ))
+        .unwrap()
+    };
+
+    let current_path = write_records(
+        "any",
+        Criterion::Any(&[Criterion::Size(1000), Criterion::Lines(2)]),
+    );
+    assert_eq!(read(&current_path, ".0"), "record 0\nrecord 1\n");
+    assert_eq!(read(&current_path, ""), "record 2\n");
+
+    let current_path = write_records(
+        "all",
+        Criterion::All(&[Criterion::Size(1000), Criterion::Lines(2)]),
+    );
+    assert_eq!(read(&current_path, ""), "record 0\nrecord 1\nrecord 2\n");
+
+    let current_path = write_records(
+        "min_interval",
+        Criterion::MinInterval(Duration::from_secs(3600), &Criterion::Lines(1)),
+    );
+    assert_eq!(read(&current_path, ""), "record 0\nrecord 1\nrecord 2\n");
+
+    assert!(FileLogWriter::builder()
+        .directory(directory)
+        .rotate(Criterion::All(&[]), Naming::Numbers, Cleanup::Never)
+        .try_build()
+        .is_err());
+}
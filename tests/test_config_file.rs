@@ -0,0 +1,54 @@
+#[cfg(feature = "config_file")]
+mod a {
+    use flexi_logger::Logger;
+    use log::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_config_file() {
+        let config_file = "test_spec/test_config_file.yaml";
+        std::fs::create_dir_all("test_spec").unwrap();
+        std::fs::File::create(config_file)
+            .unwrap()
+            .write_all(
+                b"
+spec: info, test_config_file::a::mymod=debug
+target: file
+format: detailed
+file:
+  directory: log_files/config_file
+  rotate:
+    criterion:
+      size: 100000
+    naming: numbers
+    cleanup:
+      keep_log_files: 2
+",
+            )
+            .unwrap();
+
+        let handle = Logger::with_config_file(config_file)
+            .unwrap_or_else(|e| panic!("Reading the config file failed with {}", e))
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        error!("This is an error message");
+        info!("This is an info message");
+        debug!("This is a debug message - you must not see it!");
+        mymod::test_traces();
+
+        handle.validate_logs(&[
+            ("ERROR", "test_config_file.rs", "error"),
+            ("INFO", "test_config_file.rs", "info"),
+            ("DEBUG", "test_config_file::a::mymod", "debug"),
+        ]);
+    }
+
+    mod mymod {
+        use log::*;
+        pub fn test_traces() {
+            debug!("This is a debug message");
+            trace!("This is a trace message - you must not see it!");
+        }
+    }
+}
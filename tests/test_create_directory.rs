@@ -0,0 +1,38 @@
+use flexi_logger::{FlexiLoggerError, Logger};
+
+#[test]
+fn test_create_directory() {
+    let directory = "log_files/create_directory/deeply/nested";
+    let _ = std::fs::remove_dir_all("log_files/create_directory");
+
+    // without creating the folder, the initialization fails
+    match Logger::with_str("info")
+        .log_to_file()
+        .directory(directory)
+        .create_directory(false)
+        .build()
+    {
+        Err(FlexiLoggerError::OutputMissingDirectory(path)) => {
+            assert_eq!(path, std::path::Path::new(directory))
+        }
+        _ => panic!("missing directory was not detected"),
+    }
+    assert!(!std::path::Path::new(directory).exists());
+
+    // by default, all missing parent folders are created
+    let logger = Logger::with_str("info").log_to_file().directory(directory);
+    #[cfg(unix)]
+    let logger = logger.directory_mode(0o750);
+    let _ = logger
+        .build()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+    assert!(std::path::Path::new(directory).is_dir());
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = std::fs::metadata(directory).unwrap().permissions().mode();
+        // the umask can only remove permissions
+        assert_eq!(mode & 0o027, 0);
+    }
+}
@@ -0,0 +1,36 @@
+#[cfg(target_os = "linux")]
+mod test {
+    use flexi_logger::{ErrorChannel, Logger};
+    use log::*;
+
+    // Writing to /dev/full fails with ENOSPC, like writing to a full disk.
+    #[test]
+    fn test_disk_full() {
+        let directory = std::path::PathBuf::from("log_files/disk_full");
+        std::fs::create_dir_all(&directory).unwrap();
+        let log_file = directory.join("disk_full.log");
+        let error_file = directory.join("errors.log");
+        std::fs::remove_file(&log_file).ok();
+        std::fs::remove_file(&error_file).ok();
+        std::os::unix::fs::symlink("/dev/full", &log_file).unwrap();
+
+        let handle = Logger::with_str("info")
+            .log_to_file()
+            .directory(directory)
+            .basename("disk_full")
+            .suppress_timestamp()
+            .error_channel(ErrorChannel::File(error_file.clone()))
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        for i in 0..3 {
+            info!("This is line {}", i);
+        }
+        handle.flush();
+
+        assert_eq!(handle.stats().records_dropped(), 3);
+        let errors = std::fs::read_to_string(&error_file).unwrap();
+        assert_eq!(errors.lines().count(), 1, "{}", errors);
+        assert!(errors.contains("the disk is full"), "{}", errors);
+    }
+}
@@ -0,0 +1,52 @@
+use flexi_logger::{
+    detailed_format, Cleanup, Criterion, Duplicate, EffectiveConfig, EffectiveFileConfig, Logger,
+    Naming,
+};
+
+// The effective configuration is described without creating the log directory.
+#[test]
+fn test_effective_config() {
+    let directory = "log_files/effective_config/never_created";
+    let logger = Logger::with_str("info, mod1=debug")
+        .log_to_file()
+        .directory(directory)
+        .basename("effective")
+        .rotate(
+            Criterion::Size(1000),
+            Naming::Numbers,
+            Cleanup::KeepLogFiles(3),
+        )
+        .format_for_files(detailed_format)
+        .duplicate_to_stderr(Duplicate::Warn);
+
+    let config = logger.build_config().unwrap();
+    let path = |file_name: &str| {
+        std::path::Path::new(directory)
+            .join(file_name)
+            .display()
+            .to_string()
+    };
+    assert_eq!(
+        config.file,
+        Some(EffectiveFileConfig {
+            path: path("effective.log"),
+            rotation: Some("Size(1000), Numbers, KeepLogFiles(3)".to_string()),
+            rotated_path: Some(path("effective.<number>.log")),
+            append: false,
+            symlink: None,
+        })
+    );
+    assert_eq!(config.spec, "info, mod1=debug");
+    assert_eq!(config.target, "file");
+    // format functions that are given directly are not identified
+    assert_eq!(config.format_for_file, "custom");
+    assert_eq!(config.format_for_writer, "default");
+    assert_eq!(config.duplicate_to_stderr, "Warn");
+    assert!(config.writers.is_empty());
+    assert!(config.to_string().contains("log target:          file"));
+    assert!(!std::path::Path::new(directory).exists());
+
+    let config: EffectiveConfig = Logger::with_str("warn").build_config().unwrap();
+    assert_eq!(config.target, "stderr");
+    assert_eq!(config.file, None);
+}
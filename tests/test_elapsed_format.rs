@@ -0,0 +1,48 @@
+use flexi_logger::{elapsed_format, Logger};
+use log::*;
+use std::time::Duration;
+
+#[test]
+fn test_elapsed_format() {
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory("log_files/elapsed_format")
+        .format(elapsed_format)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("first");
+    std::thread::sleep(Duration::from_millis(100));
+    info!("second");
+    handle.flush();
+
+    let path = glob::glob("log_files/elapsed_format/test_elapsed_format*.log")
+        .unwrap()
+        .filter_map(Result::ok)
+        .max()
+        .unwrap();
+    let content = std::fs::read_to_string(path).unwrap();
+    let times: Vec<(f64, f64)> = content.lines().map(relative_times).collect();
+    assert_eq!(times.len(), 2);
+    let (elapsed_1, _) = times[0];
+    let (elapsed_2, delta_2) = times[1];
+    assert!(delta_2 >= 0.1, "delta is too small: {}", content);
+    assert!(
+        elapsed_2 >= elapsed_1 + 0.1,
+        "elapsed is too small: {}",
+        content
+    );
+    assert!(content.contains("INFO [test_elapsed_format] second"));
+}
+
+// Parses lines like `[3.016481s +0.000112s] INFO [...] ...`.
+fn relative_times(line: &str) -> (f64, f64) {
+    let end = line.find(']').unwrap();
+    let mut parts = line[1..end].split(' ');
+    let mut next = |prefix: &str| -> f64 {
+        let part = parts.next().unwrap();
+        assert!(part.starts_with(prefix) && part.ends_with('s'), "{}", line);
+        part[prefix.len()..part.len() - 1].parse().unwrap()
+    };
+    (next(""), next("+"))
+}
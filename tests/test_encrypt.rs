@@ -0,0 +1,61 @@
+#[cfg(feature = "encrypt")]
+mod test {
+    use chrono::Local;
+    use flexi_logger::writers::Encryption;
+    use flexi_logger::{Cleanup, Criterion, Logger, Naming};
+    use glob::glob;
+    use log::*;
+
+    const PUBLIC_KEY: &str = "age1ql3z7hjy54pw3hyww5ayyfg7zqgvc7w3j2elw8zmrj2kg5sfn9aqmcac8p";
+
+    #[test]
+    fn test_encrypt() {
+        assert!(Encryption::with_public_key("age1nonsense").is_err());
+
+        let directory = define_directory();
+        let handle = Logger::with_str("info")
+            .log_to_file()
+            .directory(directory.clone())
+            .rotate(
+                Criterion::Size(10),
+                Naming::Numbers,
+                Cleanup::KeepLogFiles(3),
+            )
+            .cleanup_in_background_thread(false)
+            .encrypt_rotated_files(Encryption::with_public_key(PUBLIC_KEY).unwrap())
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        for i in 0..6 {
+            info!("This is secret line {}", i);
+        }
+        handle.shutdown();
+
+        let files = |pattern: &str| -> Vec<std::path::PathBuf> {
+            glob(&format!("{}/{}", directory, pattern))
+                .unwrap()
+                .filter_map(Result::ok)
+                .collect()
+        };
+        assert_eq!(files("*.[0-9]*.log").len(), 0, "unencrypted rotated files");
+        let encrypted_files = files("*.[0-9]*.log.age");
+        assert_eq!(encrypted_files.len(), 3, "wrong number of encrypted files");
+        for file in encrypted_files {
+            let content = std::fs::read(&file).unwrap();
+            assert!(
+                !String::from_utf8_lossy(&content).contains("secret line"),
+                "file {:?} contains cleartext",
+                file
+            );
+        }
+        // only the current file is left unencrypted
+        assert_eq!(files("*.log").len(), 1);
+    }
+
+    fn define_directory() -> String {
+        format!(
+            "./log_files/encrypt/{}",
+            Local::now().format("%Y-%m-%d_%H-%M-%S")
+        )
+    }
+}
@@ -0,0 +1,62 @@
+use chrono::Local;
+use flexi_logger::{log_error_chain, ErrorChain, Logger};
+use std::fmt;
+
+#[derive(Debug)]
+struct Outer(std::io::Error);
+impl fmt::Display for Outer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "loading the configuration failed")
+    }
+}
+impl std::error::Error for Outer {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.0)
+    }
+}
+
+// Error chains and panics are written with one line per cause.
+#[test]
+fn test_error_chain() {
+    let directory = format!(
+        "./log_files/error_chain/{}",
+        Local::now().format("%Y-%m-%d_%H-%M-%S")
+    );
+    let error = Outer(std::io::Error::new(
+        std::io::ErrorKind::NotFound,
+        "app.toml is missing",
+    ));
+    assert_eq!(
+        ErrorChain::new(&error).to_string(),
+        "loading the configuration failed\n  caused by: app.toml is missing"
+    );
+
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory(&directory)
+        .suppress_timestamp()
+        .log_panics()
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    log_error_chain!(&error);
+    assert!(std::thread::Builder::new()
+        .name("doomed".to_string())
+        .spawn(|| panic!("no way"))
+        .unwrap()
+        .join()
+        .is_err());
+    handle.shutdown();
+
+    let path = std::fs::read_dir(&directory)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    let content = std::fs::read_to_string(path).unwrap();
+    assert!(
+        content.contains("loading the configuration failed\n  caused by: app.toml is missing\n")
+    );
+    assert!(content.contains("thread 'doomed' panicked: no way\n  at: "));
+}
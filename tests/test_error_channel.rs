@@ -0,0 +1,29 @@
+use flexi_logger::{ErrorChannel, Logger};
+use log::*;
+
+// Problems of the logger itself go to the configured error channel.
+#[test]
+fn test_error_channel() {
+    let error_file = std::path::PathBuf::from("log_files/error_channel/errors.log");
+    std::fs::create_dir_all(error_file.parent().unwrap()).unwrap();
+    std::fs::remove_file(&error_file).ok();
+
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory("log_files/error_channel")
+        .error_channel(ErrorChannel::File(error_file.clone()))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("This is fine");
+    info!(target: "{missing}", "This addresses an unknown writer");
+    handle.flush();
+
+    let errors = std::fs::read_to_string(&error_file).unwrap();
+    assert_eq!(errors.lines().count(), 1, "{}", errors);
+    assert!(
+        errors.contains("[flexi_logger] log call addresses unknown writer \"missing\""),
+        "{}",
+        errors
+    );
+}
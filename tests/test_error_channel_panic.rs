@@ -0,0 +1,40 @@
+#[cfg(target_os = "linux")]
+mod test {
+    use flexi_logger::{ErrorChannel, Logger};
+    use log::*;
+
+    // With ErrorChannel::Panic, the panic is raised after the file log writer has released
+    // its lock, so that the logger keeps working.
+    #[test]
+    fn test_error_channel_panic() {
+        let directory = std::path::PathBuf::from("log_files/error_channel_panic");
+        std::fs::create_dir_all(&directory).unwrap();
+        let log_file = directory.join("disk_full.log");
+        std::fs::remove_file(&log_file).ok();
+        // writing to /dev/full fails with ENOSPC, like writing to a full disk
+        std::os::unix::fs::symlink("/dev/full", &log_file).unwrap();
+
+        let handle = Logger::with_str("info")
+            .log_to_file()
+            .directory(directory)
+            .basename("disk_full")
+            .suppress_timestamp()
+            .error_channel(ErrorChannel::Panic)
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        let panic = std::panic::catch_unwind(|| info!("This is line 0")).unwrap_err();
+        let message = panic
+            .downcast_ref::<String>()
+            .map_or("", String::as_str)
+            .to_string();
+        assert!(message.contains("the disk is full"), "{}", message);
+
+        // the lock of the writer is not poisoned, the records are dropped as before
+        for i in 1..3 {
+            info!("This is line {}", i);
+        }
+        handle.flush();
+        assert_eq!(handle.stats().records_dropped(), 3);
+    }
+}
@@ -0,0 +1,65 @@
+use chrono::Local;
+use flexi_logger::writers::{CloseReason, FileFooter};
+use flexi_logger::{Cleanup, Criterion, Logger, Naming};
+use glob::glob;
+use log::*;
+use std::ops::Add;
+
+// Rotated files end with a rotation footer, the last file ends with a shutdown footer.
+#[test]
+fn test_file_footer() {
+    let directory = define_directory();
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory(directory.clone())
+        .rotate(Criterion::Size(200), Naming::Numbers, Cleanup::Never)
+        .file_footer(FileFooter::from_fn(|w, reason| {
+            write!(w, "===== closed due to {} =====", reason)
+        }))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    for i in 0..20 {
+        info!("This is line {}", i);
+    }
+    handle.shutdown();
+    // a second shutdown must not add another footer
+    handle.shutdown();
+
+    let pattern = String::from(&directory).add("/*");
+    let mut no_of_log_lines = 0;
+    let mut no_of_rotated_files = 0;
+    for globresult in glob(&pattern).unwrap() {
+        let path = globresult.unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = content.lines().collect();
+        let expected_reason = if !path.file_stem().unwrap().to_string_lossy().contains('.') {
+            CloseReason::Shutdown
+        } else {
+            no_of_rotated_files += 1;
+            CloseReason::Rotation
+        };
+        assert_eq!(
+            lines.last().unwrap(),
+            &format!("===== closed due to {} =====", expected_reason),
+            "file {:?} does not end with the expected footer",
+            path
+        );
+        assert_eq!(
+            lines.iter().filter(|l| l.starts_with("=====")).count(),
+            1,
+            "file {:?} has more than one footer",
+            path
+        );
+        no_of_log_lines += lines.len() - 1;
+    }
+    assert!(no_of_rotated_files > 0, "no rotation happened");
+    assert_eq!(no_of_log_lines, 20);
+}
+
+fn define_directory() -> String {
+    format!(
+        "./log_files/file_footer/{}",
+        Local::now().format("%Y-%m-%d_%H-%M-%S")
+    )
+}
@@ -0,0 +1,58 @@
+use chrono::Local;
+use flexi_logger::writers::FileHeader;
+use flexi_logger::{Cleanup, Criterion, Logger, Naming};
+use glob::glob;
+use log::*;
+use std::ops::Add;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+// Every file, the initial one and the rotated ones, starts with the header.
+#[test]
+fn test_file_header() {
+    let directory = define_directory();
+    let headers_written = Arc::new(AtomicUsize::new(0));
+    let headers_written_c = Arc::clone(&headers_written);
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory(directory.clone())
+        .rotate(Criterion::Size(200), Naming::Numbers, Cleanup::Never)
+        .file_header(FileHeader::from_fn(move |w| {
+            let no = headers_written_c.fetch_add(1, Ordering::SeqCst);
+            write!(w, "===== test_file_header, file {} =====", no)
+        }))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    for i in 0..20 {
+        info!("This is line {}", i);
+    }
+    handle.shutdown();
+
+    let pattern = String::from(&directory).add("/*");
+    let mut no_of_log_files = 0;
+    let mut no_of_log_lines = 0;
+    for globresult in glob(&pattern).unwrap() {
+        let path = globresult.unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        let mut lines = content.lines();
+        let header = lines.next().unwrap();
+        assert!(
+            header.starts_with("===== test_file_header, file "),
+            "file {:?} does not start with the header",
+            path
+        );
+        no_of_log_files += 1;
+        no_of_log_lines += lines.count();
+    }
+    assert!(no_of_log_files > 1, "no rotation happened");
+    assert_eq!(no_of_log_files, headers_written.load(Ordering::SeqCst));
+    assert_eq!(no_of_log_lines, 20);
+}
+
+fn define_directory() -> String {
+    format!(
+        "./log_files/file_header/{}",
+        Local::now().format("%Y-%m-%d_%H-%M-%S")
+    )
+}
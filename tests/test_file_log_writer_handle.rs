@@ -0,0 +1,71 @@
+use chrono::Local;
+use flexi_logger::writers::FileLogWriter;
+use flexi_logger::{Cleanup, Criterion, FlexiLoggerError, Naming};
+use std::io::Write;
+
+fn directory(name: &str) -> String {
+    format!(
+        "./log_files/file_log_writer_handle/{}/{}",
+        name,
+        Local::now().format("%Y-%m-%d_%H-%M-%S")
+    )
+}
+
+fn read(path: &std::path::Path) -> String {
+    std::fs::read_to_string(path).unwrap()
+}
+
+// The handle rotates the file on request, also from another thread.
+#[test]
+fn test_rotate_now() {
+    let directory = directory("rotate_now");
+    let file_log_writer = FileLogWriter::builder()
+        .directory(directory.clone())
+        .rotate(Criterion::Size(1_000_000), Naming::Numbers, Cleanup::Never)
+        .try_build()
+        .unwrap();
+    let current_path = file_log_writer.current_path();
+
+    writeln!(&file_log_writer, "first line").unwrap();
+    let handle = file_log_writer.handle();
+    std::thread::spawn(move || handle.rotate_now().unwrap())
+        .join()
+        .unwrap();
+    writeln!(&file_log_writer, "second line").unwrap();
+    flexi_logger::writers::LogWriter::shutdown(&file_log_writer);
+
+    let rotated = glob::glob(&format!("{}/*.0.log", directory))
+        .unwrap()
+        .filter_map(Result::ok)
+        .next()
+        .unwrap();
+    assert_eq!(read(&rotated), "first line\n");
+    assert_eq!(read(&current_path), "second line\n");
+}
+
+// After an external rename, reopen() continues in a new file with the original name.
+#[test]
+fn test_reopen() {
+    let directory = directory("reopen");
+    let file_log_writer = FileLogWriter::builder()
+        .directory(directory.clone())
+        .try_build()
+        .unwrap();
+    writeln!(&file_log_writer, "before logrotate").unwrap();
+    flexi_logger::writers::LogWriter::flush(&file_log_writer).unwrap();
+
+    let path = file_log_writer.current_path();
+    let moved = path.with_extension("log.1");
+    std::fs::rename(&path, &moved).unwrap();
+    file_log_writer.reopen().unwrap();
+    writeln!(&file_log_writer, "after logrotate").unwrap();
+    flexi_logger::writers::LogWriter::shutdown(&file_log_writer);
+
+    assert_eq!(read(&moved), "before logrotate\n");
+    assert_eq!(read(&path), "after logrotate\n");
+
+    match file_log_writer.rotate_now() {
+        Err(FlexiLoggerError::RotationConfigConflict(_)) => {}
+        _ => panic!("rotate_now() must fail without rotation"),
+    }
+}
@@ -0,0 +1,93 @@
+use flexi_logger::{LogTarget, Logger};
+use log::*;
+
+const NO_OF_THREADS: usize = 3;
+const DIRECTORY: &str = "log_files/file_per_thread";
+
+#[test]
+fn test_file_per_thread() {
+    let _ = std::fs::remove_dir_all(DIRECTORY);
+    let handle = Logger::with_str("info")
+        .log_target(LogTarget::FilePerThread)
+        .directory(DIRECTORY)
+        .basename("per_thread")
+        .suppress_timestamp()
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    let workers: Vec<_> = (0..NO_OF_THREADS)
+        .map(|idx| {
+            std::thread::Builder::new()
+                .name(format!("worker-{}", idx))
+                .spawn(move || {
+                    for line in 0..10 {
+                        info!("worker {} writes line {}", idx, line);
+                    }
+                })
+                .unwrap()
+        })
+        .collect();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    // an unnamed thread
+    std::thread::spawn(|| info!("unnamed thread writes a line"))
+        .join()
+        .unwrap();
+    handle.shutdown();
+
+    for idx in 0..NO_OF_THREADS {
+        let content =
+            std::fs::read_to_string(format!("{}/per_thread_worker-{}.log", DIRECTORY, idx))
+                .unwrap();
+        assert_eq!(content.lines().count(), 10);
+        assert!(content
+            .lines()
+            .all(|line| line.contains(&format!("worker {} writes", idx))));
+    }
+    let unnamed = std::fs::read_dir(DIRECTORY)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .find(|name| name.starts_with("per_thread_thread-"))
+        .expect("no file for the unnamed thread");
+    assert!(
+        std::fs::read_to_string(format!("{}/{}", DIRECTORY, unnamed))
+            .unwrap()
+            .contains("unnamed thread")
+    );
+}
+
+// The file of a thread that has ended is closed with the next flush,
+// and not only on shutdown.
+#[test]
+fn test_files_of_ended_threads_are_closed() {
+    use flexi_logger::writers::{FileLogWriter, LogWriter};
+    use std::io::Write;
+
+    let directory = "log_files/file_per_thread_ended";
+    let _ = std::fs::remove_dir_all(directory);
+    let file_log_writer = std::sync::Arc::new(
+        FileLogWriter::builder()
+            .directory(directory)
+            .basename("ended")
+            .suppress_timestamp()
+            .per_thread(true)
+            .file_footer("===== closed =====")
+            .try_build()
+            .unwrap(),
+    );
+    let flw = std::sync::Arc::clone(&file_log_writer);
+    std::thread::Builder::new()
+        .name("short-lived".to_string())
+        .spawn(move || writeln!(&*flw, "a line").unwrap())
+        .unwrap()
+        .join()
+        .unwrap();
+
+    file_log_writer.flush().unwrap();
+    assert_eq!(
+        std::fs::read_to_string(format!("{}/ended_short-lived.log", directory)).unwrap(),
+        "a line\n===== closed =====\n"
+    );
+    file_log_writer.shutdown();
+}
@@ -0,0 +1,40 @@
+use flexi_logger::writers::{FileLogWriter, LogWriter};
+use flexi_logger::DeferredNow;
+
+#[test]
+fn test_filename_placeholders() {
+    let flw = FileLogWriter::builder()
+        .directory("log_files/filename_placeholders")
+        .basename("{exe}-app")
+        .discriminant("{hostname}_{pid}")
+        .suppress_timestamp()
+        .try_build()
+        .unwrap();
+    flw.write(
+        &mut DeferredNow::new(),
+        &log::Record::builder()
+            .args(format_args!("This is an info message"))
+            .level(log::Level::Info)
+            .build(),
+    )
+    .unwrap();
+
+    let filename = flw
+        .current_filename()
+        .file_name()
+        .unwrap()
+        .to_string_lossy()
+        .to_string();
+    assert!(
+        filename.starts_with("test_filename_placeholders"),
+        "{}",
+        filename
+    );
+    assert!(filename.contains("-app_"), "{}", filename);
+    assert!(
+        filename.ends_with(&format!("_{}.log", std::process::id())),
+        "{}",
+        filename
+    );
+    assert!(!filename.contains('{'), "{}", filename);
+}
@@ -0,0 +1,55 @@
+mod common;
+
+use common::collecting_writer;
+use flexi_logger::writers::{FilteredWriter, TeeWriter};
+use flexi_logger::{LogTarget, Logger};
+use log::*;
+use std::sync::{Arc, Mutex};
+
+mod db {
+    pub fn query() {
+        log::debug!("db: query");
+        log::info!("db: query with password");
+    }
+}
+
+#[test]
+fn test_filtered_writer() {
+    let all = Arc::new(Mutex::new(Vec::new()));
+    let errors = Arc::new(Mutex::new(Vec::new()));
+    let db = Arc::new(Mutex::new(Vec::new()));
+    let handle = Logger::with_str("debug")
+        .log_target(LogTarget::Writer(Box::new(TeeWriter::new(vec![
+            collecting_writer(&all, LevelFilter::Trace),
+            Box::new(
+                FilteredWriter::new(collecting_writer(&errors, LevelFilter::Trace))
+                    .level_threshold(LevelFilter::Error),
+            ),
+            Box::new(
+                FilteredWriter::new(collecting_writer(&db, LevelFilter::Trace))
+                    .module_prefixes(vec!["test_filtered_writer::db".to_string()])
+                    .filter(Box::new(|record| {
+                        !record.args().to_string().contains("password")
+                    })),
+            ),
+        ]))))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!("main: error");
+    info!("main: info");
+    db::query();
+    handle.shutdown();
+
+    assert_eq!(
+        *all.lock().unwrap(),
+        vec![
+            "main: error",
+            "main: info",
+            "db: query",
+            "db: query with password"
+        ]
+    );
+    assert_eq!(*errors.lock().unwrap(), vec!["main: error"]);
+    assert_eq!(*db.lock().unwrap(), vec!["db: query"]);
+}
@@ -0,0 +1,66 @@
+#[cfg(feature = "fluent_writer")]
+mod test {
+    use flexi_logger::writers::FluentWriter;
+    use flexi_logger::{LogTarget, Logger};
+    use log::*;
+    use std::io::Read;
+    use std::net::TcpListener;
+    use std::time::Duration;
+
+    // The records are buffered while the receiver is not reachable,
+    // and are sent when it becomes reachable.
+    #[test]
+    fn test_fluent_writer() {
+        // find a free port, and keep it unused for now
+        let address = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            listener.local_addr().unwrap()
+        };
+
+        let fluent_writer = FluentWriter::builder(address.to_string())
+            .tag("test.fluent")
+            .reconnect_interval(Duration::from_millis(0))
+            .try_build()
+            .unwrap();
+        let handle = Logger::with_str("info")
+            .log_target(LogTarget::DevNull)
+            .add_writer("Fluent", Box::new(fluent_writer))
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        error!(target: "{Fluent}", "first record, while the receiver is down");
+
+        let listener = TcpListener::bind(address).unwrap();
+        warn!(target: "{Fluent}", "second record, after the receiver is up");
+        handle.shutdown();
+
+        let (mut stream, _) = listener.accept().unwrap();
+        stream
+            .set_read_timeout(Some(Duration::from_secs(5)))
+            .unwrap();
+        let mut received = Vec::new();
+        let mut buffer = [0_u8; 1024];
+        while !contains(&received, b"after the receiver is up") {
+            let n = stream.read(&mut buffer).unwrap();
+            assert!(n > 0, "connection closed prematurely");
+            received.extend_from_slice(&buffer[..n]);
+        }
+
+        // messages in Forward mode: [tag, [entry, ...]]
+        assert_eq!(&received[..2], &[0x92, 0xab]);
+        assert_eq!(&received[2..13], b"test.fluent");
+        assert!(received[13] == 0x91 || received[13] == 0x92);
+        assert!(contains(
+            &received,
+            b"first record, while the receiver is down"
+        ));
+        assert!(contains(&received, b"ERROR"));
+        assert!(contains(&received, b"WARN"));
+    }
+
+    fn contains(haystack: &[u8], needle: &[u8]) -> bool {
+        haystack
+            .windows(needle.len())
+            .any(|window| window == needle)
+    }
+}
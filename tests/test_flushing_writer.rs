@@ -0,0 +1,67 @@
+use chrono::Local;
+use flexi_logger::writers::{FileLogWriter, FlushingWriter, LogWriter};
+use flexi_logger::{DeferredNow, FlushPolicy};
+use std::time::Duration;
+
+fn buffered_writer(directory: &str, policy: FlushPolicy) -> (FlushingWriter, std::path::PathBuf) {
+    let file_log_writer = FileLogWriter::builder()
+        .directory(directory)
+        .discriminant(format!("{:?}", policy).replace(|c: char| !c.is_alphanumeric(), "_"))
+        .use_buffering(true)
+        .try_build()
+        .unwrap();
+    let path = file_log_writer.current_path();
+    (FlushingWriter::new(Box::new(file_log_writer), policy), path)
+}
+
+fn write_line(writer: &FlushingWriter) {
+    writer
+        .write(
+            &mut DeferredNow::new(),
+            &log::Record::builder()
+                .args(format_args!("a line"))
+                .level(log::Level::Info)
+                .build(),
+        )
+        .unwrap();
+}
+
+fn lines(path: &std::path::Path) -> usize {
+    std::fs::read_to_string(path)
+        .unwrap_or_default()
+        .matches("a line")
+        .count()
+}
+
+// Each policy decides when the buffered records reach the file.
+#[test]
+fn test_flush_policies() {
+    let directory = format!(
+        "./log_files/flushing_writer/{}",
+        Local::now().format("%Y-%m-%d_%H-%M-%S")
+    );
+
+    let (writer, path) = buffered_writer(&directory, FlushPolicy::EveryRecord);
+    write_line(&writer);
+    assert_eq!(lines(&path), 1);
+
+    let (writer, path) = buffered_writer(&directory, FlushPolicy::EveryNRecords(3));
+    write_line(&writer);
+    write_line(&writer);
+    assert_eq!(lines(&path), 0);
+    write_line(&writer);
+    assert_eq!(lines(&path), 3);
+
+    let (writer, path) =
+        buffered_writer(&directory, FlushPolicy::Interval(Duration::from_millis(50)));
+    write_line(&writer);
+    std::thread::sleep(Duration::from_millis(500));
+    assert_eq!(lines(&path), 1);
+
+    let (writer, path) = buffered_writer(&directory, FlushPolicy::OnShutdown);
+    write_line(&writer);
+    writer.flush().unwrap();
+    assert_eq!(lines(&path), 0);
+    writer.shutdown();
+    assert_eq!(lines(&path), 1);
+}
@@ -0,0 +1,42 @@
+use flexi_logger::{DeferredNow, Logger, Record};
+use log::*;
+use std::io::{Error, ErrorKind};
+
+fn picky_format(
+    w: &mut dyn std::io::Write,
+    _now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    let message = record.args().to_string();
+    if message.contains("fail") {
+        write!(w, "half of a line")?;
+        return Err(Error::new(ErrorKind::Other, "cannot format this"));
+    }
+    if message.contains("panic") {
+        panic!("cannot format this either");
+    }
+    write!(w, "PICKY {}", message)
+}
+
+// Records whose format function fails or panics are written in the fallback format.
+#[test]
+fn test_format_fallback() {
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory("log_files/format_fallback")
+        .format(picky_format)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("This is fine");
+    warn!("This will fail");
+    error!("This will panic");
+    info!("This is fine again");
+
+    handle.validate_logs(&[
+        ("PICKY", "", "This is fine"),
+        ("WARN", "[test_format_fallback]", "This will fail"),
+        ("ERROR", "[test_format_fallback]", "This will panic"),
+        ("PICKY", "", "This is fine again"),
+    ]);
+}
@@ -0,0 +1,32 @@
+use flexi_logger::{detailed_format, Logger};
+use log::*;
+
+// The toggles add and remove fields of the provided format functions.
+#[test]
+fn test_format_toggles() {
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory("log_files/format_toggles")
+        .format(detailed_format)
+        .show_module(false)
+        .show_thread(true)
+        .show_target(true)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("This is an info message");
+    warn!(target: "audit", "This is a warning");
+
+    handle.validate_logs(&[
+        (
+            "T[\"test_format_toggles\"] INFO [",
+            "test_format_toggles.rs:",
+            "] <test_format_toggles> This is an info message",
+        ),
+        (
+            "WARN [",
+            "test_format_toggles.rs:",
+            "] <audit> This is a warning",
+        ),
+    ]);
+}
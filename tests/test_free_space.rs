@@ -0,0 +1,46 @@
+#[cfg(feature = "free_space")]
+mod test {
+    use chrono::Local;
+    use flexi_logger::{Cleanup, Criterion, ErrorChannel, Logger, Naming};
+    use log::*;
+
+    // With an unreachable threshold, all rotated log files are removed by the emergency cleanup.
+    #[test]
+    fn test_free_space() {
+        let directory = format!(
+            "log_files/free_space/{}",
+            Local::now().format("%Y-%m-%d_%H-%M-%S")
+        );
+        std::fs::create_dir_all(&directory).unwrap();
+        let error_file = std::path::PathBuf::from(format!("{}/errors.txt", directory));
+        let handle = Logger::with_str("info")
+            .log_to_file()
+            .directory(directory.clone())
+            .rotate(Criterion::Size(100), Naming::Numbers, Cleanup::Never)
+            .min_free_space(u64::MAX)
+            .error_channel(ErrorChannel::File(error_file.clone()))
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        for i in 0..10 {
+            info!("This is line {}", i);
+        }
+        let rotated_files = || {
+            glob::glob(&format!("{}/*.[0-9]*.log", directory))
+                .unwrap()
+                .filter_map(Result::ok)
+                .count()
+        };
+        assert!(rotated_files() > 0);
+
+        // the free space is checked at most once per second
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+        info!("This line triggers the emergency cleanup");
+        handle.flush();
+
+        assert_eq!(rotated_files(), 0);
+        let errors = std::fs::read_to_string(&error_file).unwrap();
+        assert_eq!(errors.lines().count(), 1, "{}", errors);
+        assert!(errors.contains("removed the"), "{}", errors);
+    }
+}
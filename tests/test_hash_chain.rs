@@ -0,0 +1,84 @@
+#[cfg(feature = "integrity")]
+mod test {
+    use chrono::Local;
+    use flexi_logger::writers::{verify_hash_chain, FileFooter};
+    use flexi_logger::{Cleanup, Criterion, FlexiLoggerError, Logger, Naming};
+    use glob::glob;
+    use log::*;
+    use std::path::PathBuf;
+
+    const KEY: &[u8] = b"secret key of the auditor";
+
+    #[test]
+    fn test_hash_chain() {
+        let directory = define_directory();
+        let handle = Logger::with_str("info")
+            .log_to_file()
+            .directory(directory.clone())
+            .rotate(Criterion::Size(100), Naming::Numbers, Cleanup::Never)
+            .file_header("===== header =====")
+            .file_footer(FileFooter::from("===== footer ====="))
+            .use_hash_chain(KEY)
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        for i in 0..10 {
+            info!("This is line {}", i);
+        }
+        handle.shutdown();
+
+        // the numbered files in their order, then the current file
+        let number = |path: &PathBuf| -> Option<u32> {
+            PathBuf::from(path.file_stem()?)
+                .extension()?
+                .to_str()?
+                .parse()
+                .ok()
+        };
+        let (mut files, current): (Vec<PathBuf>, Vec<PathBuf>) =
+            glob(&format!("{}/*.log", directory))
+                .unwrap()
+                .filter_map(Result::ok)
+                .partition(|path| number(path).is_some());
+        files.sort_by_key(|path| number(path));
+        files.extend(current);
+        assert!(files.len() > 2, "no rotation happened");
+        verify_hash_chain(&files, KEY).unwrap();
+
+        // a different key does not verify the chain
+        assert_broken(verify_hash_chain(&files, b"wrong key"));
+
+        // a missing file breaks the chain
+        let mut incomplete_files = files.clone();
+        incomplete_files.remove(1);
+        assert_broken(verify_hash_chain(&incomplete_files, KEY));
+
+        // a modified line breaks the chain
+        let content = std::fs::read_to_string(&files[1]).unwrap();
+        std::fs::write(&files[1], content.replacen("line", "lime", 1)).unwrap();
+        assert_broken(verify_hash_chain(&files, KEY));
+
+        // a truncated file breaks the chain
+        let truncated: String = content
+            .lines()
+            .take(2)
+            .map(|l| format!("{}\n", l))
+            .collect();
+        std::fs::write(&files[1], truncated).unwrap();
+        assert_broken(verify_hash_chain(&files, KEY));
+    }
+
+    fn assert_broken(result: Result<(), FlexiLoggerError>) {
+        match result {
+            Err(FlexiLoggerError::HashChain(_)) => {}
+            other => panic!("broken chain was not detected: {:?}", other),
+        }
+    }
+
+    fn define_directory() -> String {
+        format!(
+            "./log_files/hash_chain/{}",
+            Local::now().format("%Y-%m-%d_%H-%M-%S")
+        )
+    }
+}
@@ -0,0 +1,68 @@
+use flexi_logger::writers::LogWriter;
+use flexi_logger::{DeferredNow, FlexiLoggerError, Logger};
+use log::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+struct NetWriter {
+    reachable: Arc<AtomicBool>,
+}
+impl LogWriter for NetWriter {
+    fn write(&self, _now: &mut DeferredNow, _record: &Record) -> std::io::Result<()> {
+        Ok(())
+    }
+    fn flush(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+    fn max_log_level(&self) -> LevelFilter {
+        LevelFilter::Info
+    }
+    fn health_check(&self) -> std::io::Result<()> {
+        if self.reachable.load(Ordering::SeqCst) {
+            Ok(())
+        } else {
+            Err(std::io::Error::new(
+                std::io::ErrorKind::ConnectionRefused,
+                "host unreachable",
+            ))
+        }
+    }
+}
+
+// A writer that fails its health check lets the start fail.
+#[test]
+fn test_health_check() {
+    let reachable = Arc::new(AtomicBool::new(false));
+    let net_writer = || {
+        Box::new(NetWriter {
+            reachable: Arc::clone(&reachable),
+        })
+    };
+
+    match Logger::with_str("info")
+        .add_writer("Net", net_writer())
+        .build()
+    {
+        Err(FlexiLoggerError::WriterHealthCheck(name, e)) => {
+            assert_eq!(name, "Net");
+            assert_eq!(e.kind(), std::io::ErrorKind::ConnectionRefused);
+        }
+        _ => panic!("failing health check was not detected"),
+    }
+
+    reachable.store(true, Ordering::SeqCst);
+    let (_log, handle) = Logger::with_str("info")
+        .log_to_file()
+        .directory("log_files/health_check")
+        .add_writer("Net", net_writer())
+        .build()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+    handle.health_check().unwrap();
+
+    reachable.store(false, Ordering::SeqCst);
+    assert!(matches!(
+        handle.health_check(),
+        Err(FlexiLoggerError::WriterHealthCheck(_, _))
+    ));
+    handle.shutdown();
+}
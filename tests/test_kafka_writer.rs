@@ -0,0 +1,36 @@
+#[cfg(feature = "kafka_writer")]
+mod test {
+    use flexi_logger::writers::{KafkaPartitioning, KafkaWriter};
+    use flexi_logger::{json_format, LogTarget, Logger};
+    use log::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    // Needs a Kafka broker at localhost:9092 that accepts the topic "flexi_logger_test".
+    #[test]
+    #[ignore]
+    fn test_kafka_writer() {
+        let failures = Arc::new(AtomicUsize::new(0));
+        let failures_in_callback = Arc::clone(&failures);
+        let kafka_writer =
+            KafkaWriter::builder(vec!["localhost:9092".to_string()], "flexi_logger_test")
+                .format(json_format)
+                .partitioning(KafkaPartitioning::ByLevel)
+                .on_delivery_failure(Box::new(move |_record, _e| {
+                    failures_in_callback.fetch_add(1, Ordering::SeqCst);
+                }))
+                .try_build()
+                .unwrap_or_else(|e| panic!("KafkaWriter could not be created: {}", e));
+        let handle = Logger::with_str("info")
+            .log_target(LogTarget::DevNull)
+            .add_writer("Kafka", Box::new(kafka_writer))
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        for i in 0..100 {
+            error!(target: "{Kafka}", "This is record {} for Kafka", i);
+        }
+        handle.shutdown();
+        assert_eq!(failures.load(Ordering::SeqCst), 0);
+    }
+}
@@ -0,0 +1,40 @@
+use chrono::Local;
+use flexi_logger::{Cleanup, Criterion, Logger, Naming};
+use log::*;
+
+// latest.log shows the current file, latest_rotated.log the most recently rotated file.
+#[test]
+fn test_latest_links() {
+    let directory = format!(
+        "./log_files/latest_links/{}",
+        Local::now().format("%Y-%m-%d_%H-%M-%S")
+    );
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory(directory.clone())
+        .basename("latest_links")
+        .rotate(Criterion::Size(100), Naming::Numbers, Cleanup::Never)
+        .create_latest_links()
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    for i in 0..5 {
+        info!("This is line {}", i);
+    }
+    handle.flush();
+
+    let read = |file: &str| std::fs::read_to_string(format!("{}/{}", directory, file)).unwrap();
+    assert_eq!(read("latest.log"), read("latest_links.log"));
+    assert!(read("latest.log").contains("This is line 4"));
+
+    let last_rotated = glob::glob(&format!("{}/latest_links.[0-9]*.log", directory))
+        .unwrap()
+        .filter_map(Result::ok)
+        .max()
+        .unwrap();
+    assert_eq!(
+        read("latest_rotated.log"),
+        std::fs::read_to_string(last_rotated).unwrap()
+    );
+    assert!(read("latest_rotated.log").contains("This is line 2"));
+}
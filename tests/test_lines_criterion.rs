@@ -0,0 +1,49 @@
+use chrono::Local;
+use flexi_logger::writers::{FileLogWriter, LogWriter};
+use flexi_logger::{Cleanup, Criterion, Naming, NumberOrder};
+use std::io::Write;
+
+// Each file gets the specified number of lines, also if the records arrive
+// in a batch from a staging buffer.
+#[test]
+fn test_lines_criterion() {
+    let directory = format!(
+        "./log_files/lines_criterion/{}",
+        Local::now().format("%Y-%m-%d_%H-%M-%S")
+    );
+    let file_log_writer = FileLogWriter::builder()
+        .directory(directory)
+        .rotate(
+            Criterion::Lines(2),
+            Naming::NumbersWith {
+                width: 1,
+                start: 0,
+                order: NumberOrder::Ascending,
+            },
+            Cleanup::Never,
+        )
+        .sharded(1)
+        .try_build()
+        .unwrap();
+    for i in 0..5 {
+        (&file_log_writer)
+            .write_all(format!("record {}\n", i).as_bytes())
+            .unwrap();
+    }
+    // writes the staging buffer with all five records at once
+    LogWriter::flush(&file_log_writer).unwrap();
+    file_log_writer.shutdown();
+
+    let current_path = file_log_writer.current_path().to_string_lossy().to_string();
+    let read = |infix: &str| {
+        std::fs::read_to_string(format!(
+            "{}{}.log",
+            current_path.trim_end_matches(".log"),
+            infix
+        ))
+        .unwrap()
+    };
+    assert_eq!(read(".0"), "record 0\nrecord 1\n");
+    assert_eq!(read(".1"), "record 2\nrecord 3\n");
+    assert_eq!(read(""), "record 4\n");
+}
@@ -0,0 +1,45 @@
+use chrono::Local;
+use flexi_logger::writers::{FileLogWriter, LogWriter};
+use flexi_logger::{Cleanup, Criterion, Naming};
+use std::path::PathBuf;
+
+// The log is written to exactly the given file, and the rotated files are named after it.
+#[test]
+fn test_log_file_path() {
+    let file_path = PathBuf::from(format!(
+        "./log_files/log_file_path/{}/mandated.txt",
+        Local::now().format("%Y-%m-%d_%H-%M-%S")
+    ));
+    let file_log_writer = FileLogWriter::builder()
+        .log_file_path(file_path.clone())
+        .discriminant("ignored")
+        .suffix("ignored")
+        .rotate(Criterion::Size(5), Naming::Logrotate, Cleanup::Never)
+        .try_build()
+        .unwrap();
+    for i in 0..2 {
+        file_log_writer
+            .write_raw(format!("record {}\n", i).as_bytes())
+            .unwrap();
+    }
+    file_log_writer.shutdown();
+
+    assert_eq!(file_log_writer.current_path(), file_path);
+    assert_eq!(std::fs::read_to_string(&file_path).unwrap(), "record 1\n");
+    assert_eq!(
+        std::fs::read_to_string(file_path.with_extension("txt.1")).unwrap(),
+        "record 0\n"
+    );
+
+    // with numbers, the rotated file keeps the extension
+    let file_log_writer = FileLogWriter::builder()
+        .log_file_path(file_path.clone())
+        .rotate(Criterion::Size(5), Naming::Numbers, Cleanup::Never)
+        .try_build()
+        .unwrap();
+    file_log_writer.shutdown();
+    assert_eq!(
+        std::fs::read_to_string(file_path.with_extension("0.txt")).unwrap(),
+        "record 1\n"
+    );
+}
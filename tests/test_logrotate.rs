@@ -0,0 +1,43 @@
+use chrono::Local;
+use flexi_logger::writers::{FileLogWriter, LogWriter};
+use flexi_logger::{Cleanup, Criterion, Naming};
+
+// The log file keeps its name, the rotated files are shifted like logrotate does it.
+#[test]
+fn test_logrotate() {
+    let directory = format!(
+        "./log_files/logrotate/{}",
+        Local::now().format("%Y-%m-%d_%H-%M-%S")
+    );
+    let file_log_writer = FileLogWriter::builder()
+        .directory(directory)
+        .basename("app")
+        .rotate(
+            Criterion::Size(5),
+            Naming::Logrotate,
+            Cleanup::KeepLogFiles(2),
+        )
+        .cleanup_in_background_thread(false)
+        .try_build()
+        .unwrap();
+    for i in 0..4 {
+        file_log_writer
+            .write_raw(format!("record {}\n", i).as_bytes())
+            .unwrap();
+    }
+    file_log_writer.shutdown();
+
+    let current_path = file_log_writer.current_path();
+    assert!(current_path.ends_with("app.log"));
+    let read = |suffix: &str| {
+        let mut path = current_path.clone().into_os_string();
+        path.push(suffix);
+        std::fs::read_to_string(path).unwrap()
+    };
+    assert_eq!(read(""), "record 3\n");
+    assert_eq!(read(".1"), "record 2\n");
+    assert_eq!(read(".2"), "record 1\n");
+    let mut path = current_path.into_os_string();
+    path.push(".3");
+    assert!(!std::path::Path::new(&path).exists());
+}
@@ -0,0 +1,41 @@
+mod common;
+
+use common::collecting_writer;
+use flexi_logger::{LogTarget, Logger};
+use log::*;
+use std::sync::{Arc, Mutex};
+
+// A writer that takes everything does not force the global max level to trace
+// if its level is lowered.
+#[test]
+fn test_max_level_for_writer() {
+    let main = Arc::new(Mutex::new(Vec::new()));
+    let audits = Arc::new(Mutex::new(Vec::new()));
+    let mut handle = Logger::with_str("info")
+        .log_target(LogTarget::Writer(collecting_writer(
+            &main,
+            LevelFilter::Trace,
+        )))
+        .add_writer("Audit", collecting_writer(&audits, LevelFilter::Trace))
+        .max_level_for_writer("Audit", LevelFilter::Info)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    assert_eq!(handle.effective_max_level(), LevelFilter::Info);
+    trace!(target: "{Audit}", "dropped trace");
+    info!(target: "{Audit}", "audit");
+
+    // the max level is recomputed with every spec change
+    handle.parse_new_spec("debug");
+    assert_eq!(handle.effective_max_level(), LevelFilter::Debug);
+    debug!("debug");
+    debug!(target: "{Audit}", "dropped debug");
+
+    handle.set_max_level_for_writer("Audit", None);
+    assert_eq!(handle.effective_max_level(), LevelFilter::Trace);
+    trace!(target: "{Audit}", "audit trace");
+    handle.shutdown();
+
+    assert_eq!(*main.lock().unwrap(), vec!["debug"]);
+    assert_eq!(*audits.lock().unwrap(), vec!["audit", "audit trace"]);
+}
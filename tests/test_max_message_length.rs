@@ -0,0 +1,53 @@
+use flexi_logger::{detailed_format, Logger};
+use log::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+// counts how often a message is formatted
+static NO_OF_FORMATTINGS: AtomicUsize = AtomicUsize::new(0);
+struct CountedMessage;
+impl std::fmt::Display for CountedMessage {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        NO_OF_FORMATTINGS.fetch_add(1, Ordering::SeqCst);
+        write!(f, "counted message")
+    }
+}
+
+#[test]
+fn test_max_message_length() {
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory("log_files/max_message_length")
+        .format(detailed_format)
+        .max_message_length(20)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("Short message");
+    warn!("This message is longer: {}", "x".repeat(1000));
+    error!("This message is twenty!");
+
+    // a record that is not written is not formatted, also if it bypasses the log macros
+    log::logger().log(
+        &Record::builder()
+            .args(format_args!("{}", CountedMessage))
+            .level(Level::Debug)
+            .target("test_max_message_length")
+            .module_path(Some("test_max_message_length"))
+            .build(),
+    );
+    assert_eq!(NO_OF_FORMATTINGS.load(Ordering::SeqCst), 0);
+
+    handle.validate_logs(&[
+        ("INFO", "test_max_message_length", "Short message"),
+        (
+            "WARN",
+            "test_max_message_length",
+            "This message is long...[1004 bytes omitted]",
+        ),
+        (
+            "ERROR",
+            "test_max_message_length",
+            "This message is twen...[3 bytes omitted]",
+        ),
+    ]);
+}
@@ -0,0 +1,63 @@
+#[cfg(feature = "metrics")]
+mod test {
+    use flexi_logger::{Level, Logger};
+    use log::*;
+    use metrics::{GaugeValue, Key, Recorder, Unit};
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+
+    // Collects the values of the counters by name and level label.
+    #[derive(Clone, Default)]
+    struct CountingRecorder(Arc<Mutex<HashMap<(String, String), u64>>>);
+    impl Recorder for CountingRecorder {
+        fn register_counter(&self, _: &Key, _: Option<Unit>, _: Option<&'static str>) {}
+        fn register_gauge(&self, _: &Key, _: Option<Unit>, _: Option<&'static str>) {}
+        fn register_histogram(&self, _: &Key, _: Option<Unit>, _: Option<&'static str>) {}
+        fn increment_counter(&self, key: &Key, value: u64) {
+            let level = key
+                .labels()
+                .find(|label| label.key() == "level")
+                .map(|label| label.value().to_string())
+                .unwrap_or_default();
+            *self
+                .0
+                .lock()
+                .unwrap()
+                .entry((key.name().to_string(), level))
+                .or_insert(0) += value;
+        }
+        fn update_gauge(&self, _: &Key, _: GaugeValue) {}
+        fn record_histogram(&self, _: &Key, _: f64) {}
+    }
+
+    #[test]
+    fn test_metrics() {
+        let recorder = CountingRecorder::default();
+        metrics::set_boxed_recorder(Box::new(recorder.clone())).unwrap();
+
+        let handle = Logger::with_str("info")
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        error!("This is an error");
+        error!("This is another error");
+        warn!("This is a warning");
+        info!("This is an info");
+        debug!("This is filtered out");
+
+        let count = |level: &str| {
+            recorder
+                .0
+                .lock()
+                .unwrap()
+                .get(&("log_messages_total".to_string(), level.to_string()))
+                .copied()
+                .unwrap_or(0)
+        };
+        assert_eq!(count("error"), 2);
+        assert_eq!(count("warn"), 1);
+        assert_eq!(count("info"), 1);
+        assert_eq!(count("debug"), 0);
+        assert_eq!(handle.stats().records(Level::Error), 2);
+    }
+}
@@ -0,0 +1,43 @@
+use flexi_logger::{detailed_format, Logger, RecordDraft};
+use log::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+static PROCESSED: AtomicUsize = AtomicUsize::new(0);
+
+// The middlewares are applied in order and can drop, modify, and reroute records;
+// they only see the records that are written.
+#[test]
+fn test_middleware() {
+    let handle = Logger::with_str("info, test_middleware::quiet = error")
+        .log_to_file()
+        .directory("log_files/middleware")
+        .format(detailed_format)
+        .add_middleware(|_record: &mut RecordDraft| {
+            PROCESSED.fetch_add(1, Ordering::SeqCst);
+            true
+        })
+        .add_middleware(|record: &mut RecordDraft| !record.message.starts_with("heartbeat"))
+        .add_middleware(|record: &mut RecordDraft| {
+            record.message = record.message.replace("secret", "******");
+            true
+        })
+        .add_middleware(|record: &mut RecordDraft| {
+            if record.target == "audit" {
+                record.level = Level::Warn;
+            }
+            true
+        })
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("heartbeat");
+    info!("The password is secret");
+    info!(target: "audit", "This is an audit message");
+    info!(target: "test_middleware::quiet", "This is filtered by the log specification");
+    assert_eq!(PROCESSED.load(Ordering::SeqCst), 3);
+
+    handle.validate_logs(&[
+        ("INFO", "test_middleware", "The password is ******"),
+        ("WARN", "test_middleware", "This is an audit message"),
+    ]);
+}
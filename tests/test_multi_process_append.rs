@@ -0,0 +1,63 @@
+use flexi_logger::Logger;
+use log::*;
+use std::io::BufRead;
+use std::process::Command;
+
+const NO_OF_PROCESSES: usize = 4;
+const NO_OF_LINES: usize = 2_000;
+const CHILD_ENV: &str = "FLEXI_LOGGER_TEST_APPEND_CHILD";
+const DIRECTORY: &str = "log_files/multi_process_append";
+
+// Starts several child processes (instances of this test) that append to the same file,
+// and verifies that no lines are interleaved.
+#[test]
+fn test_multi_process_append() {
+    if let Ok(id) = std::env::var(CHILD_ENV) {
+        run_child(&id);
+        return;
+    }
+
+    let _ = std::fs::remove_dir_all(DIRECTORY);
+    let children: Vec<_> = (0..NO_OF_PROCESSES)
+        .map(|id| {
+            Command::new(std::env::current_exe().unwrap())
+                .args(&["--exact", "test_multi_process_append", "--nocapture"])
+                .env(CHILD_ENV, id.to_string())
+                .spawn()
+                .unwrap()
+        })
+        .collect();
+    for mut child in children {
+        assert!(child.wait().unwrap().success());
+    }
+
+    let f = std::fs::File::open(format!("{}/shared.log", DIRECTORY)).unwrap();
+    let mut count = 0;
+    for line in std::io::BufReader::new(f).lines() {
+        let line = line.unwrap();
+        assert!(
+            line.starts_with("INFO [test_multi_process_append] child ")
+                && line.ends_with(&"x".repeat(200)),
+            "interleaved line: {}",
+            line
+        );
+        count += 1;
+    }
+    assert_eq!(count, NO_OF_PROCESSES * NO_OF_LINES);
+}
+
+fn run_child(id: &str) {
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory(DIRECTORY)
+        .basename("shared")
+        .suppress_timestamp()
+        .append()
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+    let padding = "x".repeat(200);
+    for i in 0..NO_OF_LINES {
+        info!("child {} line {} {}", id, i, padding);
+    }
+    handle.shutdown();
+}
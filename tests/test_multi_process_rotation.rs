@@ -0,0 +1,72 @@
+use flexi_logger::{Cleanup, Criterion, Logger, Naming};
+use log::*;
+use std::io::BufRead;
+use std::process::Command;
+
+const NO_OF_PROCESSES: usize = 4;
+const NO_OF_LINES: usize = 2_000;
+const CHILD_ENV: &str = "FLEXI_LOGGER_TEST_ROTATION_CHILD";
+const DIRECTORY: &str = "log_files/multi_process_rotation";
+
+// Starts several child processes (instances of this test) that write to and rotate
+// the same files, using the rotation lock, and verifies that no line is lost.
+#[test]
+fn test_multi_process_rotation() {
+    if let Ok(id) = std::env::var(CHILD_ENV) {
+        run_child(&id);
+        return;
+    }
+
+    let _ = std::fs::remove_dir_all(DIRECTORY);
+    let children: Vec<_> = (0..NO_OF_PROCESSES)
+        .map(|id| {
+            Command::new(std::env::current_exe().unwrap())
+                .args(&["--exact", "test_multi_process_rotation", "--nocapture"])
+                .env(CHILD_ENV, id.to_string())
+                .spawn()
+                .unwrap()
+        })
+        .collect();
+    for mut child in children {
+        assert!(child.wait().unwrap().success());
+    }
+
+    let mut no_of_files = 0;
+    let mut count = 0;
+    for entry in std::fs::read_dir(DIRECTORY).unwrap() {
+        let path = entry.unwrap().path();
+        if path.extension().unwrap() == "lock" {
+            // the lock file stays in place
+            continue;
+        }
+        no_of_files += 1;
+        let f = std::fs::File::open(path).unwrap();
+        for line in std::io::BufReader::new(f).lines() {
+            let line = line.unwrap();
+            assert!(
+                line.starts_with("INFO [test_multi_process_rotation] child "),
+                "interleaved line: {}",
+                line
+            );
+            count += 1;
+        }
+    }
+    assert!(no_of_files > 1, "no rotation happened");
+    assert_eq!(count, NO_OF_PROCESSES * NO_OF_LINES);
+}
+
+fn run_child(id: &str) {
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory(DIRECTORY)
+        .basename("shared")
+        .append()
+        .rotate(Criterion::Size(20_000), Naming::Numbers, Cleanup::Never)
+        .use_rotation_lock(true)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+    for i in 0..NO_OF_LINES {
+        info!("child {} line {}", id, i);
+    }
+    handle.shutdown();
+}
@@ -0,0 +1,52 @@
+mod common;
+
+use common::collecting_writer;
+use flexi_logger::{LogTarget, Logger};
+use log::*;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_multi_target() {
+    let main = Arc::new(Mutex::new(Vec::new()));
+    let alerts = Arc::new(Mutex::new(Vec::new()));
+    let security = Arc::new(Mutex::new(Vec::new()));
+    let handle = Logger::with_str("info")
+        .log_target(LogTarget::Writer(collecting_writer(
+            &main,
+            LevelFilter::Trace,
+        )))
+        .add_writer("Alert", collecting_writer(&alerts, LevelFilter::Warn))
+        .add_writer("Security", collecting_writer(&security, LevelFilter::Trace))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    warn!(target: "{Alert,Security}", "both");
+    warn!(target: "{ Alert , Security ,}", "both, with whitespace");
+    warn!(target: "{Alert,Unknown}", "alert and unknown");
+    warn!(target: "{Alert,Unknown}", "alert and unknown, again");
+    trace!(target: "{Security,_Default}", "security and default, but trace");
+    info!(target: "{Alert", "no writer list, but a normal target");
+    handle.shutdown();
+
+    assert_eq!(
+        *alerts.lock().unwrap(),
+        vec![
+            "both",
+            "both, with whitespace",
+            "alert and unknown",
+            "alert and unknown, again"
+        ]
+    );
+    assert_eq!(
+        *security.lock().unwrap(),
+        vec![
+            "both",
+            "both, with whitespace",
+            "security and default, but trace"
+        ]
+    );
+    assert_eq!(
+        *main.lock().unwrap(),
+        vec!["no writer list, but a normal target"]
+    );
+}
@@ -107,10 +107,14 @@ mod d {
         now: &mut DeferredNow,
         record: &Record,
     ) -> std::io::Result<()> {
+        #[cfg(feature = "chrono")]
+        let timestamp = now.now().format("%Y-%m-%d %H:%M:%S%.6f %:z");
+        #[cfg(not(feature = "chrono"))]
+        let timestamp = now.now_time();
         write!(
             w,
             "XXXXX [{}] T[{:?}] {} [{}:{}] {}",
-            now.now().format("%Y-%m-%d %H:%M:%S%.6f %:z"),
+            timestamp,
             thread::current().name().unwrap_or("<unnamed>"),
             record.level(),
             record.file().unwrap_or("<unnamed>"),
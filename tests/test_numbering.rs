@@ -0,0 +1,62 @@
+use chrono::Local;
+use flexi_logger::writers::{FileLogWriter, LogWriter};
+use flexi_logger::{Cleanup, Criterion, Naming, NumberOrder};
+
+// The rotated files are numbered with the configured width, start index, and order.
+#[test]
+fn test_numbering() {
+    let directory = format!(
+        "./log_files/numbering/{}",
+        Local::now().format("%Y-%m-%d_%H-%M-%S")
+    );
+
+    for (discriminant, order, expected) in &[
+        (
+            "ascending",
+            NumberOrder::Ascending,
+            [("001", 0), ("002", 1), ("003", 2)],
+        ),
+        (
+            "shifting",
+            NumberOrder::Shifting,
+            [("001", 2), ("002", 1), ("003", 0)],
+        ),
+    ] {
+        let file_log_writer = FileLogWriter::builder()
+            .directory(directory.clone())
+            .discriminant(*discriminant)
+            .rotate(
+                Criterion::Size(5),
+                Naming::NumbersWith {
+                    width: 3,
+                    start: 1,
+                    order: *order,
+                },
+                Cleanup::Never,
+            )
+            .try_build()
+            .unwrap();
+        for i in 0..4 {
+            // one call per record, the file may be rotated between two calls
+            file_log_writer
+                .write_raw(format!("record {}\n", i).as_bytes())
+                .unwrap();
+        }
+        file_log_writer.shutdown();
+
+        let basename = file_log_writer
+            .current_path()
+            .to_string_lossy()
+            .replace(".log", "");
+        for (infix, record) in expected {
+            assert_eq!(
+                std::fs::read_to_string(format!("{}.{}.log", basename, infix)).unwrap(),
+                format!("record {}\n", record)
+            );
+        }
+        assert_eq!(
+            std::fs::read_to_string(file_log_writer.current_path()).unwrap(),
+            "record 3\n"
+        );
+    }
+}
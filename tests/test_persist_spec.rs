@@ -0,0 +1,35 @@
+#[cfg(feature = "specfile_without_notification")]
+mod a {
+    use flexi_logger::{FlexiLoggerError, LogSpecification, Logger};
+
+    // The current spec is written into the specfile, and its writer settings are kept.
+    #[test]
+    fn test_persist_spec() {
+        std::fs::create_dir_all("test_spec").unwrap();
+        let specfile = "test_spec/test_persist_spec.json";
+        std::fs::write(
+            specfile,
+            r#"{"format": "detailed", "global_level": "info"}"#,
+        )
+        .unwrap();
+
+        let (_log, mut handle) = Logger::with_str("info")
+            .build_with_specfile(specfile)
+            .unwrap_or_else(|e| panic!("Logger initialization failed because: {}", e));
+        handle.parse_and_push_temp_spec("warn, mod1::mod2=trace");
+        handle.persist_spec_to_specfile().unwrap();
+
+        let content = std::fs::read_to_string(specfile).unwrap();
+        assert!(content.contains("\"format\": \"detailed\""), "{}", content);
+        assert_eq!(
+            LogSpecification::from_json(&content).unwrap(),
+            LogSpecification::parse("warn, mod1::mod2=trace").unwrap()
+        );
+
+        let (_log, handle) = Logger::with_str("info").build().unwrap();
+        match handle.persist_spec_to_specfile() {
+            Err(FlexiLoggerError::NoSpecfile) => {}
+            _ => panic!("missing specfile was not detected"),
+        }
+    }
+}
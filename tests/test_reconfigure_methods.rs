@@ -36,6 +36,7 @@ fn test_parse_new_spec(logger: &mut ReconfigurationHandle) {
     trace!("1-trace message");
 
     logger.parse_new_spec("info");
+    assert_eq!(logger.current_spec().to_string(), "info");
 }
 
 #[allow(clippy::cognitive_complexity)]
@@ -54,6 +55,8 @@ fn test_push_new_spec(logger: &mut ReconfigurationHandle) {
     trace!("2-trace message - you must not see it!");
 
     logger.parse_and_push_temp_spec("trace");
+    assert_eq!(logger.current_spec().to_string(), "trace");
+    assert_eq!(logger.temp_spec_depth(), 2);
     error!("2-error message");
     warn!("2-warning");
     info!("2-info message");
@@ -61,6 +64,8 @@ fn test_push_new_spec(logger: &mut ReconfigurationHandle) {
     trace!("2-trace message");
 
     logger.pop_temp_spec(); // we should be back on error
+    assert_eq!(logger.current_spec().to_string(), "error");
+    assert_eq!(logger.temp_spec_depth(), 1);
     error!("2-error message");
     warn!("2-warning - you must not see it!");
     info!("2-info message - you must not see it!");
@@ -68,6 +73,8 @@ fn test_push_new_spec(logger: &mut ReconfigurationHandle) {
     trace!("2-trace message - you must not see it!");
 
     logger.pop_temp_spec(); // we should be back on info
+    assert_eq!(logger.current_spec().to_string(), "info");
+    assert_eq!(logger.temp_spec_depth(), 0);
 
     error!("2-error message");
     warn!("2-warning");
@@ -0,0 +1,55 @@
+use chrono::Local;
+use flexi_logger::{detailed_format, Logger};
+use log::*;
+
+// After replace(), the records go to the new target, with the new spec and format.
+#[test]
+fn test_replace_logger() {
+    let directory = format!(
+        "./log_files/replace_logger/{}",
+        Local::now().format("%Y-%m-%d_%H-%M-%S")
+    );
+    let dir_a = format!("{}/a", directory);
+    let dir_b = format!("{}/b", directory);
+
+    let mut handle = Logger::with_str("info")
+        .log_to_file()
+        .directory(dir_a.clone())
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("This goes to a");
+    debug!("This is not logged");
+
+    handle
+        .replace(
+            Logger::with_str("debug")
+                .log_to_file()
+                .directory(dir_b.clone())
+                .format(detailed_format),
+        )
+        .unwrap_or_else(|e| panic!("Replacing the logger failed with {}", e));
+
+    info!("This goes to b");
+    debug!("This is also logged to b");
+    handle.shutdown();
+
+    let read = |dir: &str| {
+        let path = glob::glob(&format!("{}/*.log", dir))
+            .unwrap()
+            .filter_map(Result::ok)
+            .next()
+            .unwrap();
+        std::fs::read_to_string(path).unwrap()
+    };
+    let content_a = read(&dir_a);
+    let content_b = read(&dir_b);
+    assert!(content_a.contains("This goes to a"));
+    assert!(!content_a.contains("to b"));
+    assert!(!content_a.contains("not logged"));
+    assert!(!content_b.contains("This goes to a"));
+    assert!(content_b.contains("This goes to b"));
+    assert!(content_b.contains("This is also logged to b"));
+    // detailed_format shows the source location
+    assert!(content_b.contains("test_replace_logger.rs:"));
+}
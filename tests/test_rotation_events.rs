@@ -0,0 +1,98 @@
+use chrono::Local;
+use flexi_logger::writers::RotationEvent;
+use flexi_logger::{Cleanup, Criterion, Logger, Naming};
+use log::*;
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    Rotated(PathBuf, PathBuf),
+    Removed(PathBuf),
+}
+
+// The callback sees every rotation, and every file that is removed by the cleanup.
+#[test]
+fn test_rotation_events() {
+    let directory = define_directory();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let events_c = Arc::clone(&events);
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory(directory.clone())
+        .rotate(
+            Criterion::Size(10),
+            Naming::Numbers,
+            Cleanup::KeepLogFiles(2),
+        )
+        .cleanup_in_background_thread(false)
+        .on_rotation(move |event| {
+            let event = match event {
+                RotationEvent::Rotated {
+                    rotated_file,
+                    current_file,
+                } => Event::Rotated(rotated_file.to_path_buf(), current_file.to_path_buf()),
+                RotationEvent::Removed { file } => Event::Removed(file.to_path_buf()),
+                RotationEvent::Compressed { .. }
+                | RotationEvent::Encrypted { .. }
+                | RotationEvent::Moved { .. } => {
+                    panic!("unexpected event {:?}", event)
+                }
+            };
+            events_c.lock().unwrap().push(event);
+        })
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    for i in 0..10 {
+        info!(
+            "This is line {} which is long enough to cause a rotation",
+            i
+        );
+    }
+    handle.shutdown();
+
+    let events = events.lock().unwrap();
+    let rotated: Vec<&PathBuf> = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Rotated(rotated_file, current_file) => {
+                assert_eq!(current_file.extension().unwrap(), "log");
+                assert!(!current_file
+                    .file_stem()
+                    .unwrap()
+                    .to_string_lossy()
+                    .contains('.'));
+                assert!(current_file.exists());
+                Some(rotated_file)
+            }
+            Event::Removed(_) => None,
+        })
+        .collect();
+    let removed: Vec<&PathBuf> = events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Removed(file) => Some(file),
+            Event::Rotated(..) => None,
+        })
+        .collect();
+
+    assert_eq!(rotated.len(), 9, "wrong number of rotations");
+    assert_eq!(removed.len(), 7, "wrong number of removed files");
+    for file in &removed {
+        assert!(
+            rotated.contains(file),
+            "unknown file {:?} was removed",
+            file
+        );
+        assert!(!file.exists(), "removed file {:?} still exists", file);
+    }
+    assert!(rotated[7].exists() && rotated[8].exists());
+}
+
+fn define_directory() -> String {
+    format!(
+        "./log_files/rotation_events/{}",
+        Local::now().format("%Y-%m-%d_%H-%M-%S")
+    )
+}
@@ -0,0 +1,26 @@
+use flexi_logger::{LogTarget, Logger};
+
+#[test]
+fn test_scoped_spec() {
+    let mut handle = Logger::with_str("info")
+        .log_target(LogTarget::DevNull)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    {
+        let _guard = handle.scoped_spec("trace");
+        assert!(log::log_enabled!(log::Level::Trace));
+    }
+    assert_eq!(handle.current_spec().to_string(), "info");
+    assert_eq!(handle.temp_spec_depth(), 0);
+    assert!(!log::log_enabled!(log::Level::Debug));
+
+    // forgotten pops within the scope are reverted, too
+    {
+        let mut guard = handle.scoped_spec("debug");
+        guard.parse_and_push_temp_spec("trace");
+        assert_eq!(guard.current_spec().to_string(), "trace");
+    }
+    assert_eq!(handle.current_spec().to_string(), "info");
+    assert_eq!(handle.temp_spec_depth(), 0);
+}
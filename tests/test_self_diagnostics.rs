@@ -0,0 +1,39 @@
+mod common;
+
+use common::collecting_writer;
+use flexi_logger::{LogTarget, Logger};
+use log::*;
+use std::sync::{Arc, Mutex};
+
+// The lifecycle events of the logger are written to the selected writer.
+#[test]
+fn test_self_diagnostics() {
+    let main = Arc::new(Mutex::new(Vec::new()));
+    let diagnostics = Arc::new(Mutex::new(Vec::new()));
+    let mut handle = Logger::with_str("info")
+        .log_target(LogTarget::Writer(collecting_writer(
+            &main,
+            LevelFilter::Info,
+        )))
+        .add_writer(
+            "diagnostics",
+            collecting_writer(&diagnostics, LevelFilter::Info),
+        )
+        .self_diagnostics(Level::Info, "{diagnostics}")
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("main");
+    handle.parse_new_spec("warn");
+    handle.shutdown();
+
+    assert_eq!(*main.lock().unwrap(), vec!["main"]);
+    assert_eq!(
+        *diagnostics.lock().unwrap(),
+        vec![
+            "event=started",
+            "event=spec_changed spec=\"warn\"",
+            "event=shutdown"
+        ]
+    );
+}
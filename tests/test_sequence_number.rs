@@ -0,0 +1,70 @@
+mod common;
+
+use chrono::Local;
+use common::collecting_writer;
+use flexi_logger::{json_format, opt_format, Logger};
+use log::*;
+use std::sync::{Arc, Mutex};
+
+// The log file and its structured sibling show the same, gapless sequence numbers,
+// also if records are written only to an additional writer.
+#[test]
+fn test_sequence_number() {
+    let directory = format!(
+        "./log_files/sequence_number/{}",
+        Local::now().format("%Y-%m-%d_%H-%M-%S")
+    );
+    let audit_data = Arc::new(Mutex::new(Vec::new()));
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory(directory.clone())
+        .format(opt_format)
+        .structured_file(json_format)
+        .show_sequence_number(true)
+        .add_writer("Audit", collecting_writer(&audit_data, LevelFilter::Info))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    for i in 0..5 {
+        info!("This is line {}", i);
+        debug!("This is not written");
+        info!(target: "{Audit}", "This is only written to the additional writer");
+    }
+    handle.shutdown();
+
+    let read_lines = |pattern: &str| -> Vec<String> {
+        let path = glob::glob(&format!("{}/{}", directory, pattern))
+            .unwrap()
+            .filter_map(Result::ok)
+            .next()
+            .unwrap();
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(ToString::to_string)
+            .collect()
+    };
+    let log_lines = read_lines("*.log");
+    let structured_lines = read_lines("*.jsonl");
+    assert_eq!(log_lines.len(), 5);
+    assert_eq!(structured_lines.len(), 5);
+
+    let sequence_number = |line: &str, prefix: &str, end: char| -> u64 {
+        assert!(line.starts_with(prefix), "{}", line);
+        let rest = &line[prefix.len()..];
+        rest[..rest.find(end).unwrap()].parse().unwrap()
+    };
+    let first = sequence_number(&log_lines[0], "#", ' ');
+    assert_eq!(first, 1);
+    for (i, (log_line, structured_line)) in
+        log_lines.iter().zip(structured_lines.iter()).enumerate()
+    {
+        assert_eq!(sequence_number(log_line, "#", ' '), first + i as u64);
+        assert_eq!(
+            sequence_number(structured_line, "{\"seq\":", ','),
+            first + i as u64
+        );
+        assert!(structured_line.contains(&format!("This is line {}", i)));
+    }
+    assert_eq!(audit_data.lock().unwrap().len(), 5);
+}
@@ -0,0 +1,50 @@
+use flexi_logger::Logger;
+use log::*;
+use std::collections::HashMap;
+
+const NO_OF_THREADS: usize = 8;
+const NO_OF_LINES: usize = 5_000;
+const DIRECTORY: &str = "log_files/sharded";
+
+// Many threads log concurrently through sharded staging buffers;
+// verifies that no line is lost and that the lines of each thread keep their order.
+#[test]
+fn test_sharded() {
+    let _ = std::fs::remove_dir_all(DIRECTORY);
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory(DIRECTORY)
+        .basename("sharded")
+        .suppress_timestamp()
+        .buffer_and_flush()
+        .sharded(4)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    let threads: Vec<_> = (0..NO_OF_THREADS)
+        .map(|thread| {
+            std::thread::spawn(move || {
+                for line in 0..NO_OF_LINES {
+                    info!("thread {} line {}", thread, line);
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    handle.shutdown();
+
+    let content = std::fs::read_to_string(format!("{}/sharded.log", DIRECTORY)).unwrap();
+    let mut next_lines = HashMap::new();
+    for line in content.lines() {
+        let words: Vec<&str> = line.split(' ').collect();
+        let thread: usize = words[3].parse().unwrap();
+        let line: usize = words[5].parse().unwrap();
+        let next_line = next_lines.entry(thread).or_insert(0);
+        assert_eq!(line, *next_line, "thread {} lost its order", thread);
+        *next_line += 1;
+    }
+    assert_eq!(next_lines.len(), NO_OF_THREADS);
+    assert!(next_lines.values().all(|count| *count == NO_OF_LINES));
+}
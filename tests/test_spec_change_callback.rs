@@ -0,0 +1,44 @@
+use flexi_logger::{LogTarget, Logger};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_spec_change_callback() {
+    let mut handle = Logger::with_str("info")
+        .log_target(LogTarget::DevNull)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    let seen_specs = Arc::new(Mutex::new(Vec::<String>::new()));
+    let seen_specs_c = Arc::clone(&seen_specs);
+    {
+        let seen_specs = Arc::clone(&seen_specs);
+        handle.on_spec_change(move |spec| seen_specs.lock().unwrap().push(spec.to_string()));
+    }
+
+    handle.parse_new_spec("warn");
+    handle.parse_and_push_temp_spec("trace");
+    handle.pop_temp_spec();
+    // nothing to pop, so nothing changes
+    handle.pop_temp_spec();
+
+    assert_eq!(*seen_specs.lock().unwrap(), vec!["warn", "trace", "warn"]);
+
+    // a callback can register further callbacks
+    let handle_c = handle.clone();
+    let registered = AtomicBool::new(false);
+    handle.on_spec_change(move |_| {
+        if !registered.swap(true, Ordering::SeqCst) {
+            let seen_specs = Arc::clone(&seen_specs);
+            handle_c.on_spec_change(move |spec| {
+                seen_specs.lock().unwrap().push(format!("late {}", spec))
+            });
+        }
+    });
+    handle.parse_new_spec("info");
+    handle.parse_new_spec("debug");
+    assert_eq!(
+        *seen_specs_c.lock().unwrap(),
+        vec!["warn", "trace", "warn", "info", "debug", "late debug"]
+    );
+}
@@ -0,0 +1,99 @@
+#[cfg(feature = "specfile_without_notification")]
+mod a {
+    use flexi_logger::{default_format, FlexiLoggerError, Logger};
+    use log::*;
+    use std::io::{BufRead, Write};
+    use std::ops::Add;
+
+    // The specfile exists already and switches the format of the log file.
+    #[test]
+    fn test_specfile_settings() {
+        let specfile = "test_spec/test_specfile_settings.toml";
+        write_specfile(
+            specfile,
+            b"
+            format = 'detailed'
+            duplicate_to_stderr = 'error'
+            global_level = 'info'
+            ",
+        );
+
+        Logger::with_str("trace")
+            .format(default_format)
+            .log_to_file()
+            .suppress_timestamp()
+            .start_with_specfile(specfile)
+            .unwrap_or_else(|e| panic!("Logger initialization failed because: {}", e));
+
+        error!("This is an error-0");
+        info!("This is an info-0");
+        debug!("This is a debug-0");
+
+        let logfile = std::path::Path::new(&std::env::args().nth(0).unwrap())
+            .file_stem()
+            .unwrap()
+            .to_string_lossy()
+            .to_string()
+            .add(".log");
+
+        // detailed_format contains the source file, default_format does not
+        validate_logs(
+            &logfile,
+            &[
+                ("ERROR", "test_specfile_settings.rs", "error-0"),
+                ("INFO", "test_specfile_settings.rs", "info-0"),
+            ],
+        );
+    }
+
+    #[test]
+    fn test_specfile_invalid_settings() {
+        let specfile = "test_spec/test_specfile_invalid_format.toml";
+        write_specfile(specfile, b"format = 'fancy'\n");
+        match Logger::with_str("info").build_with_specfile(specfile) {
+            Err(FlexiLoggerError::SpecfileValue(_)) => {}
+            _ => panic!("unknown format was not detected"),
+        }
+
+        let specfile = "test_spec/test_specfile_invalid_duplicate.json";
+        write_specfile(specfile, b"{\"duplicate_to_stdout\": \"loud\"}");
+        match Logger::with_str("info").build_with_specfile(specfile) {
+            Err(FlexiLoggerError::SpecfileValue(_)) => {}
+            _ => panic!("invalid duplication level was not detected"),
+        }
+    }
+
+    fn write_specfile(specfile: &str, content: &[u8]) {
+        std::fs::create_dir_all("test_spec").unwrap();
+        let mut file = std::fs::OpenOptions::new()
+            .create(true)
+            .truncate(true)
+            .write(true)
+            .open(specfile)
+            .unwrap();
+        file.write_all(content).unwrap();
+    }
+
+    fn validate_logs(logfile: &str, expected: &[(&'static str, &'static str, &'static str)]) {
+        println!("validating log file = {}", logfile);
+
+        let f = std::fs::File::open(logfile).unwrap();
+        let mut reader = std::io::BufReader::new(f);
+
+        let mut buf = String::new();
+        for tuple in expected {
+            buf.clear();
+            reader.read_line(&mut buf).unwrap();
+            assert!(buf.contains(&tuple.0), "Did not find tuple.0 = {}", tuple.0);
+            assert!(buf.contains(&tuple.1), "Did not find tuple.1 = {}", tuple.1);
+            assert!(buf.contains(&tuple.2), "Did not find tuple.2 = {}", tuple.2);
+        }
+        buf.clear();
+        reader.read_line(&mut buf).unwrap();
+        assert!(
+            buf.is_empty(),
+            "Found more log lines than expected: {} ",
+            buf
+        );
+    }
+}
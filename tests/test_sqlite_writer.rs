@@ -0,0 +1,46 @@
+#[cfg(feature = "sqlite_writer")]
+mod test {
+    use flexi_logger::writers::SqliteWriter;
+    use flexi_logger::{LogTarget, Logger};
+    use log::*;
+
+    #[test]
+    fn test_sqlite_writer() {
+        std::fs::create_dir_all("log_files").unwrap();
+        let path = format!(
+            "log_files/test_sqlite_writer_{}.db",
+            chrono::Local::now().format("%Y-%m-%d_%H-%M-%S")
+        );
+        let sqlite_writer = SqliteWriter::builder(&path)
+            .batch_size(7)
+            .try_build()
+            .unwrap_or_else(|e| panic!("SqliteWriter could not be created: {}", e));
+        let handle = Logger::with_str("info")
+            .log_target(LogTarget::Writer(Box::new(sqlite_writer)))
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+        for i in 0..20 {
+            info!("This is record {}", i);
+        }
+        warn!("This is a warning");
+        debug!("This is a debug message - you must not see it!");
+        handle.shutdown();
+
+        let connection = rusqlite::Connection::open(&path).unwrap();
+        let count: i64 = connection
+            .query_row("SELECT COUNT(*) FROM log", rusqlite::NO_PARAMS, |row| {
+                row.get(0)
+            })
+            .unwrap();
+        assert_eq!(count, 21);
+        let message: String = connection
+            .query_row(
+                "SELECT message FROM log WHERE level = 'WARN'",
+                rusqlite::NO_PARAMS,
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(message, "This is a warning");
+    }
+}
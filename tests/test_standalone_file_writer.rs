@@ -0,0 +1,73 @@
+use chrono::Local;
+use flexi_logger::writers::{FileLogWriter, LogWriter};
+use flexi_logger::{detailed_format, Cleanup, Criterion, DeferredNow, Naming};
+use std::io::Write;
+use std::sync::Arc;
+
+// A FileLogWriter can be used from several threads, without a global logger,
+// with preformatted lines and with log records.
+#[test]
+fn test_standalone_file_writer() {
+    let directory = format!(
+        "./log_files/standalone_file_writer/{}",
+        Local::now().format("%Y-%m-%d_%H-%M-%S")
+    );
+    let file_log_writer = Arc::new(
+        FileLogWriter::builder()
+            .directory(directory.clone())
+            .format(detailed_format)
+            .rotate(Criterion::Size(500), Naming::Numbers, Cleanup::Never)
+            .try_build()
+            .unwrap(),
+    );
+
+    let threads: Vec<_> = (0..4)
+        .map(|t| {
+            let file_log_writer = Arc::clone(&file_log_writer);
+            std::thread::spawn(move || {
+                let mut raw_writer = file_log_writer.raw_writer();
+                for i in 0..10 {
+                    // the line is handed over in pieces
+                    write!(raw_writer, "preformatted line {}", i).unwrap();
+                    writeln!(raw_writer, " of thread {}", t).unwrap();
+                }
+            })
+        })
+        .collect();
+    for thread in threads {
+        thread.join().unwrap();
+    }
+    file_log_writer
+        .write(
+            &mut DeferredNow::new(),
+            &log::Record::builder()
+                .args(format_args!("a formatted record"))
+                .level(log::Level::Info)
+                .target("standalone")
+                .file(Some("standalone.rs"))
+                .line(Some(42))
+                .build(),
+        )
+        .unwrap();
+    file_log_writer.shutdown();
+
+    assert!(log::max_level() == log::LevelFilter::Off);
+    let mut files: Vec<_> = glob::glob(&format!("{}/*.log", directory))
+        .unwrap()
+        .filter_map(Result::ok)
+        .collect();
+    assert!(files.len() > 1, "the log files were not rotated");
+    files.sort();
+    let content: String = files
+        .iter()
+        .map(|path| std::fs::read_to_string(path).unwrap())
+        .collect();
+    assert_eq!(content.matches("preformatted line").count(), 40);
+    for line in content
+        .lines()
+        .filter(|line| line.starts_with("preformatted"))
+    {
+        assert!(line.contains(" of thread "), "split line: {}", line);
+    }
+    assert!(content.contains("standalone.rs:42: a formatted record"));
+}
@@ -0,0 +1,39 @@
+use chrono::Local;
+use flexi_logger::{Cleanup, Criterion, Level, Logger, Naming};
+use log::*;
+
+#[test]
+fn test_stats() {
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory(define_directory())
+        .rotate(Criterion::Size(100), Naming::Numbers, Cleanup::Never)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    for i in 0..10 {
+        info!("This is info line {}", i);
+    }
+    warn!("This is a warning");
+    error!("This is an error");
+    debug!("This is filtered out");
+    handle.flush();
+
+    let stats = handle.stats();
+    assert_eq!(stats.records(Level::Info), 10);
+    assert_eq!(stats.records(Level::Warn), 1);
+    assert_eq!(stats.records(Level::Error), 1);
+    assert_eq!(stats.records(Level::Debug), 0);
+    assert_eq!(stats.total_records(), 12);
+    assert!(stats.bytes_written() > 12 * 30, "{:?}", stats);
+    assert!(stats.rotations() > 1, "{:?}", stats);
+    assert_eq!(stats.records_dropped(), 0);
+    assert_eq!(stats.write_errors(), 0);
+}
+
+fn define_directory() -> String {
+    format!(
+        "./log_files/stats/{}",
+        Local::now().format("%Y-%m-%d_%H-%M-%S")
+    )
+}
@@ -0,0 +1,33 @@
+use flexi_logger::writers::{FileLogWriter, LogWriter, StdErrWriter, StdOutWriter, TeeWriter};
+use flexi_logger::{default_format, detailed_format, LogTarget, Logger, WriteMode};
+use log::*;
+
+// The std writers can be used as additional writers, also within a TeeWriter.
+#[test]
+fn test_std_writers() {
+    let stderr_writer =
+        StdErrWriter::new(detailed_format, &WriteMode::Direct).level_threshold(LevelFilter::Warn);
+    assert_eq!(stderr_writer.max_log_level(), LevelFilter::Warn);
+
+    let tee_writer = TeeWriter::new(vec![
+        Box::new(StdOutWriter::new(default_format, &WriteMode::Buffer(1024))),
+        Box::new(
+            FileLogWriter::builder()
+                .directory("log_files/std_writers")
+                .try_build()
+                .unwrap(),
+        ),
+    ]);
+
+    let handle = Logger::with_str("info")
+        .log_target(LogTarget::StdOut)
+        .add_writer("Console", Box::new(stderr_writer))
+        .add_writer("Tee", Box::new(tee_writer))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!(target: "{Console}", "This goes to stderr");
+    warn!(target: "{Tee}", "This goes to stdout and into a file");
+    info!("This is a normal info message");
+    handle.flush();
+}
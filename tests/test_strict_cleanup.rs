@@ -0,0 +1,52 @@
+use chrono::Local;
+use flexi_logger::writers::{FileLogWriter, LogWriter};
+use flexi_logger::{Cleanup, Criterion, Naming};
+use std::path::Path;
+
+// The cleanup leaves files alone whose names only resemble those of rotated files.
+#[test]
+fn test_strict_cleanup() {
+    let directory = format!(
+        "./log_files/strict_cleanup/{}",
+        Local::now().format("%Y-%m-%d_%H-%M-%S")
+    );
+    for (discriminant, strict_cleanup) in &[("lenient", false), ("strict", true)] {
+        let file_log_writer = FileLogWriter::builder()
+            .directory(directory.clone())
+            .discriminant(*discriminant)
+            .rotate(
+                Criterion::Size(5),
+                Naming::Numbers,
+                Cleanup::KeepLogFiles(1),
+            )
+            .cleanup_in_background_thread(false)
+            .strict_cleanup(*strict_cleanup)
+            .try_build()
+            .unwrap();
+        let current_path = file_log_writer.current_path().to_string_lossy().to_string();
+        let path = |infix: &str| format!("{}{}", current_path.trim_end_matches(".log"), infix);
+
+        // files of someone else
+        std::fs::create_dir_all(&directory).unwrap();
+        let mut foreign_files = vec![path(".9.txt.gz")];
+        if *strict_cleanup {
+            foreign_files.push(path(".1-backup.log"));
+        }
+        for foreign_file in &foreign_files {
+            std::fs::write(foreign_file, "foreign").unwrap();
+        }
+
+        for i in 0..3 {
+            file_log_writer
+                .write_raw(format!("record {}\n", i).as_bytes())
+                .unwrap();
+        }
+        file_log_writer.shutdown();
+
+        assert!(!Path::new(&path(".0.log")).exists());
+        assert!(Path::new(&path(".1.log")).exists());
+        for foreign_file in &foreign_files {
+            assert_eq!(std::fs::read_to_string(foreign_file).unwrap(), "foreign");
+        }
+    }
+}
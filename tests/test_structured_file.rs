@@ -0,0 +1,122 @@
+use chrono::Local;
+use flexi_logger::writers::FileLogWriter;
+use flexi_logger::{json_format, Cleanup, Criterion, FlexiLoggerError, Logger, Naming};
+use glob::glob;
+use log::*;
+use std::path::PathBuf;
+
+// Each log file has a structured sibling with the same records, also after rotation and cleanup.
+#[test]
+fn test_structured_file() {
+    let directory = define_directory();
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory(directory.clone())
+        .rotate(
+            Criterion::Size(100),
+            Naming::Numbers,
+            Cleanup::KeepLogFiles(2),
+        )
+        .cleanup_in_background_thread(false)
+        .structured_file(json_format)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    for i in 0..10 {
+        info!("This is line {}", i);
+    }
+    handle.shutdown();
+
+    let files = |pattern: &str| -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = glob(&format!("{}/{}", directory, pattern))
+            .unwrap()
+            .filter_map(Result::ok)
+            .collect();
+        files.sort();
+        files
+    };
+    let log_files = files("*.log");
+    let structured_files = files("*.jsonl");
+    assert_eq!(log_files.len(), 3, "wrong number of log files");
+    assert_eq!(
+        structured_files.len(),
+        3,
+        "wrong number of structured files"
+    );
+
+    for (log_file, structured_file) in log_files.iter().zip(structured_files.iter()) {
+        assert_eq!(log_file.with_extension("jsonl"), *structured_file);
+        let log_lines: Vec<String> = std::fs::read_to_string(log_file)
+            .unwrap()
+            .lines()
+            .map(ToString::to_string)
+            .collect();
+        let structured_lines: Vec<String> = std::fs::read_to_string(structured_file)
+            .unwrap()
+            .lines()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(log_lines.len(), structured_lines.len());
+        for (log_line, structured_line) in log_lines.iter().zip(structured_lines.iter()) {
+            assert!(structured_line.starts_with('{'), "{}", structured_line);
+            let text = &log_line[log_line.find("This is line").unwrap()..];
+            assert!(structured_line.contains(text), "{}", structured_line);
+        }
+    }
+}
+
+// The structured file is compressed together with its log file.
+#[cfg(feature = "compress")]
+#[test]
+fn test_structured_file_compressed() {
+    let directory = define_directory() + "_compressed";
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory(directory.clone())
+        .rotate(
+            Criterion::Size(100),
+            Naming::Numbers,
+            Cleanup::KeepLogAndCompressedFiles(1, 10),
+        )
+        .cleanup_in_background_thread(false)
+        .structured_file(json_format)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    for i in 0..10 {
+        info!("This is line {}", i);
+    }
+    handle.shutdown();
+
+    let no_of_files = |pattern: &str| {
+        glob(&format!("{}/{}", directory, pattern))
+            .unwrap()
+            .filter_map(Result::ok)
+            .count()
+    };
+    let no_of_compressed_files = no_of_files("*.log.gz");
+    assert!(no_of_compressed_files > 0, "no file was compressed");
+    assert_eq!(no_of_files("*.jsonl.gz"), no_of_compressed_files);
+    assert_eq!(no_of_files("*.jsonl"), no_of_files("*.log"));
+}
+
+// A structured file needs a different suffix than the log file.
+#[test]
+fn test_structured_file_suffix_conflict() {
+    match FileLogWriter::builder()
+        .directory(define_directory())
+        .suffix("jsonl")
+        .structured_file(json_format)
+        .try_build()
+    {
+        Err(FlexiLoggerError::FileConfigConflict(_)) => {}
+        other => panic!("conflict was not detected: {:?}", other.map(|_| ())),
+    }
+}
+
+fn define_directory() -> String {
+    format!(
+        "./log_files/structured_file/{}",
+        Local::now().format("%Y-%m-%d_%H-%M-%S")
+    )
+}
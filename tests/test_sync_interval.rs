@@ -0,0 +1,32 @@
+use chrono::Local;
+use flexi_logger::{Logger, SyncPolicy};
+use log::*;
+
+// With SyncPolicy::Interval, the flusher thread writes and syncs the buffered records
+// also if no further records follow.
+#[test]
+fn test_sync_interval() {
+    let directory = format!(
+        "./log_files/sync_interval/{}",
+        Local::now().format("%Y-%m-%d_%H-%M-%S")
+    );
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory(directory.clone())
+        .use_buffering(true)
+        .sync_policy(SyncPolicy::Interval(std::time::Duration::from_millis(50)))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("This line is synced by the flusher thread");
+    std::thread::sleep(std::time::Duration::from_millis(500));
+
+    let path = glob::glob(&format!("{}/*.log", directory))
+        .unwrap()
+        .filter_map(Result::ok)
+        .next()
+        .unwrap();
+    let content = std::fs::read_to_string(path).unwrap();
+    assert!(content.contains("This line is synced by the flusher thread"));
+    handle.shutdown();
+}
@@ -0,0 +1,30 @@
+use chrono::Local;
+use flexi_logger::{Logger, SyncPolicy};
+use log::*;
+
+// With a sync policy, the records reach the file although buffering is used.
+#[test]
+fn test_sync_policy() {
+    let directory = format!(
+        "./log_files/sync_policy/{}",
+        Local::now().format("%Y-%m-%d_%H-%M-%S")
+    );
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory(directory.clone())
+        .use_buffering(true)
+        .sync_policy(SyncPolicy::EveryRecord)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("This line is synced immediately");
+
+    let path = glob::glob(&format!("{}/*.log", directory))
+        .unwrap()
+        .filter_map(Result::ok)
+        .next()
+        .unwrap();
+    let content = std::fs::read_to_string(path).unwrap();
+    assert!(content.contains("This line is synced immediately"));
+    handle.shutdown();
+}
@@ -0,0 +1,76 @@
+mod common;
+
+use common::{collecting_writer, CollectingWriter};
+use flexi_logger::writers::{LogWriter, TeeWriter};
+use flexi_logger::{DeferredNow, LogTarget, Logger};
+use log::*;
+use std::sync::{Arc, Mutex};
+
+struct FailingWriter(&'static str);
+impl LogWriter for FailingWriter {
+    fn write(&self, _now: &mut DeferredNow, _record: &Record) -> std::io::Result<()> {
+        Err(std::io::Error::new(std::io::ErrorKind::Other, self.0))
+    }
+    fn flush(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+    fn max_log_level(&self) -> LevelFilter {
+        LevelFilter::Trace
+    }
+}
+
+#[test]
+fn test_tee_writer() {
+    let all = Arc::new(Mutex::new(Vec::new()));
+    let warnings = Arc::new(Mutex::new(Vec::new()));
+    let tee_writer = TeeWriter::new(vec![
+        Box::new(CollectingWriter::new(&all, LevelFilter::Trace).formatted()),
+        Box::new(CollectingWriter::new(&warnings, LevelFilter::Warn).formatted()),
+    ]);
+    assert_eq!(tee_writer.max_log_level(), LevelFilter::Trace);
+
+    let handle = Logger::with_str("info")
+        .log_target(LogTarget::Writer(Box::new(tee_writer)))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    error!("This is an error message");
+    info!("This is an info message");
+    debug!("This is a debug message - you must not see it!");
+    handle.shutdown();
+
+    assert_eq!(
+        *all.lock().unwrap(),
+        vec![
+            "ERROR [test_tee_writer] This is an error message",
+            "INFO [test_tee_writer] This is an info message"
+        ]
+    );
+    assert_eq!(
+        *warnings.lock().unwrap(),
+        vec!["ERROR [test_tee_writer] This is an error message"]
+    );
+
+    // all writers are served, and the errors are aggregated
+    let collected = Arc::new(Mutex::new(Vec::new()));
+    let tee_writer = TeeWriter::new(vec![
+        Box::new(FailingWriter("first failure")),
+        collecting_writer(&collected, LevelFilter::Trace),
+        Box::new(FailingWriter("second failure")),
+    ]);
+    let error = tee_writer
+        .write(
+            &mut DeferredNow::new(),
+            &Record::builder()
+                .args(format_args!("written despite failures"))
+                .level(Level::Info)
+                .module_path(Some("test_tee_writer"))
+                .build(),
+        )
+        .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "2 writers failed: first failure; second failure"
+    );
+    assert_eq!(collected.lock().unwrap().len(), 1);
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use common::collecting_writer;
+use flexi_logger::{LogSpecification, LogTarget, Logger};
+use log::*;
+use std::sync::{Arc, Mutex};
+
+// A thread-specific spec enables traces only for the thread that activated it.
+#[test]
+fn test_thread_spec() {
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    let handle = Logger::with_str("info")
+        .log_target(LogTarget::Writer(collecting_writer(
+            &lines,
+            LevelFilter::Trace,
+        )))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    {
+        let _guard = handle.thread_spec(LogSpecification::parse("trace").unwrap());
+        trace!("trace in the traced thread");
+        std::thread::spawn(|| {
+            trace!("trace in another thread - you must not see it!");
+            info!("info in another thread");
+        })
+        .join()
+        .unwrap();
+        {
+            let _inner_guard = handle.thread_spec(LogSpecification::parse("error").unwrap());
+            info!("info in the nested scope - you must not see it!");
+        }
+        debug!("debug after the nested scope");
+    }
+    trace!("trace after the scope - you must not see it!");
+    info!("info after the scope");
+    assert_eq!(log::max_level(), LevelFilter::Info);
+
+    assert_eq!(
+        *lines.lock().unwrap(),
+        vec![
+            "trace in the traced thread",
+            "info in another thread",
+            "debug after the nested scope",
+            "info after the scope"
+        ]
+    );
+}
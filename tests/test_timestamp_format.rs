@@ -0,0 +1,32 @@
+use flexi_logger::{detailed_format, Logger, TimestampFormat};
+use log::*;
+
+// The provided format functions write their timestamps in the chosen format.
+#[test]
+fn test_timestamp_format() {
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory("log_files/timestamp_format")
+        .format(detailed_format)
+        .timestamp_format(TimestampFormat::Rfc3339Millis)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("This is an info message");
+    handle.validate_logs(&[("INFO", "test_timestamp_format", "This is an info message")]);
+
+    handle.shutdown();
+    let log_file = std::fs::read_dir("log_files/timestamp_format")
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .max_by_key(|path| std::fs::metadata(path).unwrap().modified().unwrap())
+        .unwrap();
+    let line = std::fs::read_to_string(log_file).unwrap();
+    // e.g. `[2016-01-13T15:25:01.640+01:00] INFO ...`
+    let timestamp = &line[1..line.find(']').unwrap()];
+    assert_eq!(timestamp.len(), 29, "unexpected timestamp {}", timestamp);
+    assert_eq!(&timestamp[10..11], "T");
+    assert_eq!(&timestamp[19..20], ".");
+    assert!(timestamp[20..23].chars().all(|c| c.is_ascii_digit()));
+    assert!(&timestamp[23..24] == "+" || &timestamp[23..24] == "-");
+}
@@ -0,0 +1,36 @@
+use chrono::Local;
+use flexi_logger::{opt_format, FlexiLoggerError, Logger};
+use log::*;
+
+// With an assumed UTC offset, the timestamps show this offset, regardless of the local timezone.
+#[test]
+fn test_utc_offset() {
+    match Logger::with_str("info").assume_utc_offset(86_400).build() {
+        Err(FlexiLoggerError::UtcOffset(86_400)) => {}
+        Err(e) => panic!("unexpected error {}", e),
+        Ok(_) => panic!("an offset of a day must not be accepted"),
+    }
+
+    let directory = format!(
+        "./log_files/utc_offset/{}",
+        Local::now().format("%Y-%m-%d_%H-%M-%S")
+    );
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory(directory.clone())
+        .format(opt_format)
+        .assume_utc_offset(-(9 * 3600 + 30 * 60))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("This line has a timestamp in a timezone west of UTC");
+    handle.shutdown();
+
+    let path = glob::glob(&format!("{}/*.log", directory))
+        .unwrap()
+        .filter_map(Result::ok)
+        .next()
+        .unwrap();
+    let content = std::fs::read_to_string(path).unwrap();
+    assert!(content.contains(" -09:30]"), "{}", content);
+}
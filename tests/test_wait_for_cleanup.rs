@@ -0,0 +1,50 @@
+use chrono::Local;
+use flexi_logger::writers::{FileLogWriter, LogWriter};
+use flexi_logger::{Cleanup, Criterion, Naming};
+use std::time::Duration;
+
+// After wait_for_cleanup, the cleanup thread has removed the files
+// of all previous rotations.
+#[test]
+fn test_wait_for_cleanup() {
+    let directory = format!(
+        "./log_files/wait_for_cleanup/{}",
+        Local::now().format("%Y-%m-%d_%H-%M-%S")
+    );
+    let file_log_writer = FileLogWriter::builder()
+        .directory(directory.clone())
+        .rotate(
+            Criterion::Size(5),
+            Naming::Numbers,
+            Cleanup::KeepLogFiles(1),
+        )
+        .cleanup_in_background_thread(true)
+        .cleanup_timeout(Duration::from_secs(10))
+        .try_build()
+        .unwrap();
+    for i in 0..4 {
+        file_log_writer
+            .write_raw(format!("record {}\n", i).as_bytes())
+            .unwrap();
+    }
+    assert!(file_log_writer.wait_for_cleanup(Duration::from_secs(10)));
+
+    let current_name = file_log_writer
+        .current_path()
+        .file_name()
+        .unwrap()
+        .to_owned();
+    let rotated_files: Vec<std::path::PathBuf> = std::fs::read_dir(&directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.file_name().unwrap() != current_name)
+        .collect();
+    assert_eq!(rotated_files.len(), 1);
+    assert_eq!(
+        std::fs::read_to_string(&rotated_files[0]).unwrap(),
+        "record 2\n"
+    );
+
+    file_log_writer.shutdown();
+    assert!(file_log_writer.wait_for_cleanup(Duration::from_secs(0)));
+}
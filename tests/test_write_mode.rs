@@ -0,0 +1,71 @@
+use flexi_logger::{Duplicate, LogTarget, Logger, WriteMode};
+use log::*;
+use std::process::{Command, Output};
+
+const NO_OF_LINES: usize = 1_000;
+const CHILD_ENV: &str = "FLEXI_LOGGER_TEST_WRITE_MODE_CHILD";
+
+// Runs instances of this test as child processes that log to stderr and to stdout
+// with different write modes, and verifies that all lines arrive in the right order.
+#[test]
+fn test_write_mode() {
+    if let Ok(variant) = std::env::var(CHILD_ENV) {
+        run_child(&variant);
+        return;
+    }
+
+    for variant in &["direct", "buffer", "buffer_and_flush"] {
+        let output = run_as_child(variant);
+        assert!(output.status.success());
+        verify(&output.stderr, "ERROR", variant);
+        verify(&output.stdout, "ERROR", variant);
+    }
+}
+
+fn run_as_child(variant: &str) -> Output {
+    Command::new(std::env::current_exe().unwrap())
+        .args(&["--exact", "test_write_mode", "--nocapture", "--quiet"])
+        .env(CHILD_ENV, variant)
+        .output()
+        .unwrap()
+}
+
+fn run_child(variant: &str) {
+    let write_mode = match variant {
+        "direct" => WriteMode::Direct,
+        "buffer" => WriteMode::Buffer(4 * 1024),
+        "buffer_and_flush" => {
+            WriteMode::BufferAndFlush(4 * 1024, std::time::Duration::from_millis(10))
+        }
+        _ => unreachable!(),
+    };
+    let handle = Logger::with_str("info")
+        .log_target(LogTarget::DevNull)
+        .duplicate_to_stderr(Duplicate::Error)
+        .duplicate_to_stdout(Duplicate::Error)
+        .write_mode_for_stderr(write_mode)
+        .write_mode_for_stdout(write_mode)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+    for i in 0..NO_OF_LINES {
+        error!("{} {}", variant, i);
+    }
+    handle.shutdown();
+}
+
+fn verify(output: &[u8], level: &str, variant: &str) {
+    let lines: Vec<String> = String::from_utf8_lossy(output)
+        .lines()
+        .filter(|line| line.starts_with(level))
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(lines.len(), NO_OF_LINES, "variant {}", variant);
+    for (i, line) in lines.iter().enumerate() {
+        assert_eq!(
+            *line,
+            format!("{} [test_write_mode] {} {}", level, variant, i),
+            "variant {}",
+            variant
+        );
+    }
+}
@@ -0,0 +1,38 @@
+use flexi_logger::{FlexiLoggerError, Logger};
+use log::*;
+
+// Preformatted output ends up in the log file unchanged, between the formatted records;
+// a missing line break is added.
+#[test]
+fn test_write_raw() {
+    let handle = Logger::with_str("info")
+        .log_to_file()
+        .directory("log_files/write_raw")
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("before");
+    handle.write_raw(b"child | preformatted line\n").unwrap();
+    handle.write_raw(b"child | without line break").unwrap();
+    info!("after");
+    handle.shutdown();
+
+    let log_file = std::fs::read_dir("log_files/write_raw")
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .max_by_key(|path| std::fs::metadata(path).unwrap().modified().unwrap())
+        .unwrap();
+    let content = std::fs::read_to_string(log_file).unwrap();
+    let lines: Vec<&str> = content.lines().collect();
+    assert_eq!(lines.len(), 4, "{}", content);
+    assert!(lines[0].ends_with("before"));
+    assert_eq!(lines[1], "child | preformatted line");
+    assert_eq!(lines[2], "child | without line break");
+    assert!(lines[3].ends_with("after"));
+
+    let (_logger, handle) = Logger::with_str("info").build().unwrap();
+    assert!(matches!(
+        handle.write_raw(b"nowhere\n"),
+        Err(FlexiLoggerError::NoFileLog)
+    ));
+}
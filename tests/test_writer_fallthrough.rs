@@ -0,0 +1,42 @@
+mod common;
+
+use common::collecting_writer;
+use flexi_logger::{LogTarget, Logger};
+use log::*;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_writer_fallthrough() {
+    let main = Arc::new(Mutex::new(Vec::new()));
+    let alerts = Arc::new(Mutex::new(Vec::new()));
+    let audits = Arc::new(Mutex::new(Vec::new()));
+    let handle = Logger::with_str("info")
+        .log_target(LogTarget::Writer(collecting_writer(
+            &main,
+            LevelFilter::Trace,
+        )))
+        .add_writer_with_fallthrough("Alert", collecting_writer(&alerts, LevelFilter::Trace))
+        .add_writer("Audit", collecting_writer(&audits, LevelFilter::Trace))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("normal");
+    error!(target: "{Alert}", "alert");
+    info!(target: "{Audit}", "audit");
+    info!(target: "{Audit,_Default}", "audit and default");
+    debug!(target: "{Alert}", "debug alert, not in the main log due to the log spec");
+    handle.shutdown();
+
+    assert_eq!(
+        *main.lock().unwrap(),
+        vec!["normal", "alert", "audit and default"]
+    );
+    assert_eq!(
+        *alerts.lock().unwrap(),
+        vec![
+            "alert",
+            "debug alert, not in the main log due to the log spec"
+        ]
+    );
+    assert_eq!(*audits.lock().unwrap(), vec!["audit", "audit and default"]);
+}
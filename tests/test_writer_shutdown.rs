@@ -0,0 +1,58 @@
+use flexi_logger::writers::LogWriter;
+use flexi_logger::{DeferredNow, LogTarget, Logger};
+use log::*;
+use std::sync::{Arc, Mutex};
+
+// Records the calls it gets, like a writer with a connection would.
+struct ConnectionWriter {
+    calls: Arc<Mutex<Vec<&'static str>>>,
+}
+impl LogWriter for ConnectionWriter {
+    fn write(&self, _now: &mut DeferredNow, _record: &Record) -> std::io::Result<()> {
+        self.calls.lock().unwrap().push("write");
+        Ok(())
+    }
+    fn flush(&self) -> std::io::Result<()> {
+        self.calls.lock().unwrap().push("flush");
+        Ok(())
+    }
+    fn max_log_level(&self) -> LevelFilter {
+        LevelFilter::Info
+    }
+    fn shutdown(&self) {
+        self.calls.lock().unwrap().push("shutdown");
+    }
+}
+
+// All writers are flushed and then shut down exactly once.
+#[test]
+fn test_writer_shutdown() {
+    let primary_calls = Arc::new(Mutex::new(Vec::new()));
+    let other_calls = Arc::new(Mutex::new(Vec::new()));
+    let handle = Logger::with_str("info")
+        .log_target(LogTarget::Writer(Box::new(ConnectionWriter {
+            calls: Arc::clone(&primary_calls),
+        })))
+        .add_writer(
+            "Db",
+            Box::new(ConnectionWriter {
+                calls: Arc::clone(&other_calls),
+            }),
+        )
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {}", e));
+
+    info!("primary");
+    info!(target: "{Db}", "db");
+    handle.shutdown();
+    handle.clone().shutdown();
+
+    assert_eq!(
+        *primary_calls.lock().unwrap(),
+        vec!["write", "flush", "shutdown"]
+    );
+    assert_eq!(
+        *other_calls.lock().unwrap(),
+        vec!["write", "flush", "shutdown"]
+    );
+}